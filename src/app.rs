@@ -0,0 +1,1415 @@
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoopProxy;
+use winit::window::Window;
+
+use crate::config::{Backdrop, Config, WindowConstraints};
+use crate::frame_scheduler::FrameScheduler;
+use crate::gpu_report::GpuReport;
+use crate::Event;
+
+/// The tabs of the settings window.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SettingsTab {
+    General,
+    Style,
+    Accessibility,
+    Shortcuts,
+}
+
+/// The UI of the example: the demo application that ships with egui plus the tool windows of
+/// this example, reachable over the menu bar.
+pub struct App {
+    config: Config,
+    /// Created after the first frame, so that building the demos doesn't delay it.
+    demo_windows: Option<egui_demo_lib::DemoWindows>,
+    settings_open: bool,
+    settings_tab: SettingsTab,
+    style_editor: crate::style_editor::StyleEditor,
+    display_mode: crate::display_mode::DisplayMode,
+    display_mode_open: bool,
+    presentation: crate::presentation::Presentation,
+    presentation_open: bool,
+    gpu_report: GpuReport,
+    gpu_report_open: bool,
+    startup_report: crate::startup_report::StartupReport,
+    startup_report_open: bool,
+    /// Whether the warning about the software renderer is shown.
+    software_renderer_banner: bool,
+    crash_dialog: crate::crash_report::CrashDialog,
+    screenshot: crate::screenshot::RegionScreenshot,
+    interaction_recorder: crate::interaction_recorder::InteractionRecorder,
+    color_picker: crate::color_picker::ColorPicker,
+    color_picker_open: bool,
+    emoji_demo: crate::emoji::EmojiDemo,
+    emoji_demo_open: bool,
+    system_monitor: crate::system_monitor::SystemMonitor,
+    system_monitor_open: bool,
+    file_browser: crate::file_browser::FileBrowser,
+    file_browser_open: bool,
+    image_viewer: crate::image_viewer::ImageViewer,
+    image_viewer_open: bool,
+    tile_editor: crate::tile_editor::TileEditor,
+    tile_editor_open: bool,
+    ecs_scene: crate::ecs_scene::EcsScene,
+    ecs_scene_open: bool,
+    assets: crate::assets::Assets,
+    assets_open: bool,
+    csv_viewer: crate::csv_viewer::CsvViewer,
+    csv_viewer_open: bool,
+    json_inspector: crate::json_inspector::JsonInspector,
+    json_inspector_open: bool,
+    log_tail: crate::log_tail::LogTail,
+    log_tail_open: bool,
+    hex_viewer: crate::hex_viewer::HexViewer,
+    hex_viewer_open: bool,
+    #[cfg(feature = "physics")]
+    physics_sandbox: crate::physics_sandbox::PhysicsSandbox,
+    #[cfg(feature = "physics")]
+    physics_sandbox_open: bool,
+    #[cfg(feature = "sqlite")]
+    sqlite_browser: crate::sqlite_browser::SqliteBrowser,
+    #[cfg(feature = "sqlite")]
+    sqlite_browser_open: bool,
+    #[cfg(feature = "mqtt")]
+    mqtt_dashboard: crate::mqtt_dashboard::MqttDashboard,
+    #[cfg(feature = "mqtt")]
+    mqtt_dashboard_open: bool,
+    #[cfg(feature = "terminal")]
+    terminal: crate::terminal::Terminal,
+    #[cfg(feature = "terminal")]
+    terminal_open: bool,
+    stress_test: crate::stress_test::StressTest,
+    stress_test_open: bool,
+    large_text: crate::large_text::LargeText,
+    large_text_open: bool,
+    latency_tester: crate::latency_tester::LatencyTester,
+    latency_tester_open: bool,
+    debug_tools: crate::debug_tools::DebugTools,
+    workspaces_menu: crate::workspaces::WorkspacesMenu,
+    onboarding: crate::onboarding::Onboarding,
+    shortcut_editor: crate::shortcuts::ShortcutEditor,
+    profiles_editor: crate::profiles::ProfilesEditor,
+    on_screen_keyboard: crate::on_screen_keyboard::OnScreenKeyboard,
+    /// Restored at the start of the next frame, so that the memory of egui isn't replaced while
+    /// a frame uses it.
+    restored_workspace: Option<Box<crate::workspaces::Workspace>>,
+    /// The scale the font atlas was pre-warmed for, egui recreates it for a new one.
+    prewarmed_for: Option<f32>,
+    /// How many glyphs the last pre-warm rasterized, or why it failed.
+    prewarm_result: Option<Result<usize, String>>,
+    /// Whether `--kiosk` is on, and the workspace of its first frame that `reset_ui` goes back to.
+    kiosk: bool,
+    kiosk_start: Option<crate::workspaces::Workspace>,
+    /// Whether the HUD layer is drawn below the UI.
+    hud_enabled: bool,
+    /// The theme of `--theme`, which replaces the one of the stored style.
+    theme: Option<crate::options::Theme>,
+    #[cfg(feature = "audio")]
+    audio_visualizer: Option<crate::audio::AudioVisualizer>,
+    #[cfg(feature = "audio")]
+    audio_visualizer_open: bool,
+    /// Reopens the recent files.
+    event_loop_proxy: EventLoopProxy<Event>,
+    /// Registered while the setting is on.
+    global_hotkey: Option<crate::global_hotkey::GlobalHotkey>,
+    /// Why the hotkey couldn't be registered, until the setting changes.
+    global_hotkey_error: Option<String>,
+    /// The memory of the user textures, for the settings.
+    texture_stats: crate::user_textures::ResidencyStats,
+    #[cfg(feature = "midi")]
+    midi: crate::midi::Midi,
+    #[cfg(feature = "tts")]
+    speech: crate::speech::Speech,
+}
+
+impl App {
+    pub fn new(
+        mut config: Config,
+        event_loop_proxy: EventLoopProxy<Event>,
+        gpu_report: GpuReport,
+    ) -> Self {
+        let mut latency_tester =
+            crate::latency_tester::LatencyTester::new(gpu_report.present_modes().to_vec());
+        // The present mode isn't saved on its own, the other settings of the profile are.
+        if let Some(profile) = config.profiles.active() {
+            latency_tester.set_present_mode(profile.present_mode);
+        }
+        let mut onboarding = crate::onboarding::Onboarding::default();
+        if !config.onboarding_done {
+            config.onboarding_done = true;
+            onboarding.start();
+        }
+        Self {
+            config,
+            demo_windows: None,
+            settings_open: false,
+            settings_tab: SettingsTab::General,
+            style_editor: Default::default(),
+            display_mode: Default::default(),
+            display_mode_open: false,
+            presentation: Default::default(),
+            presentation_open: false,
+            system_monitor: crate::system_monitor::SystemMonitor::new(gpu_report.info.name.clone()),
+            software_renderer_banner: gpu_report.info.device_type == wgpu::DeviceType::Cpu,
+            crash_dialog: Default::default(),
+            gpu_report,
+            gpu_report_open: false,
+            startup_report: Default::default(),
+            startup_report_open: false,
+            screenshot: Default::default(),
+            interaction_recorder: Default::default(),
+            color_picker: Default::default(),
+            color_picker_open: false,
+            emoji_demo: Default::default(),
+            emoji_demo_open: false,
+            system_monitor_open: false,
+            file_browser: Default::default(),
+            file_browser_open: false,
+            image_viewer: Default::default(),
+            image_viewer_open: false,
+            tile_editor: Default::default(),
+            tile_editor_open: false,
+            ecs_scene: Default::default(),
+            ecs_scene_open: false,
+            assets: Default::default(),
+            assets_open: false,
+            csv_viewer: Default::default(),
+            csv_viewer_open: false,
+            json_inspector: Default::default(),
+            json_inspector_open: false,
+            log_tail: Default::default(),
+            log_tail_open: false,
+            hex_viewer: Default::default(),
+            hex_viewer_open: false,
+            #[cfg(feature = "physics")]
+            physics_sandbox: Default::default(),
+            #[cfg(feature = "physics")]
+            physics_sandbox_open: false,
+            #[cfg(feature = "sqlite")]
+            sqlite_browser: Default::default(),
+            #[cfg(feature = "sqlite")]
+            sqlite_browser_open: false,
+            #[cfg(feature = "mqtt")]
+            mqtt_dashboard: Default::default(),
+            #[cfg(feature = "mqtt")]
+            mqtt_dashboard_open: false,
+            #[cfg(feature = "terminal")]
+            terminal: Default::default(),
+            #[cfg(feature = "terminal")]
+            terminal_open: false,
+            stress_test: Default::default(),
+            stress_test_open: false,
+            large_text: Default::default(),
+            large_text_open: false,
+            latency_tester,
+            latency_tester_open: false,
+            debug_tools: Default::default(),
+            workspaces_menu: Default::default(),
+            onboarding,
+            shortcut_editor: Default::default(),
+            profiles_editor: Default::default(),
+            on_screen_keyboard: Default::default(),
+            restored_workspace: None,
+            prewarmed_for: None,
+            prewarm_result: None,
+            kiosk: false,
+            kiosk_start: None,
+            hud_enabled: false,
+            theme: None,
+            #[cfg(feature = "audio")]
+            audio_visualizer: None,
+            #[cfg(feature = "audio")]
+            audio_visualizer_open: false,
+            #[cfg(feature = "midi")]
+            midi: crate::midi::Midi::new(event_loop_proxy.clone()),
+            event_loop_proxy,
+            global_hotkey: None,
+            global_hotkey_error: None,
+            texture_stats: Default::default(),
+            #[cfg(feature = "tts")]
+            speech: Default::default(),
+        }
+    }
+
+    /// Draws the UI in `theme`, whatever the stored style says.
+    pub fn override_theme(&mut self, theme: Option<crate::options::Theme>) {
+        self.theme = theme;
+    }
+
+    /// Makes the UI remember its first frame, for `reset_ui`.
+    pub fn set_kiosk(&mut self, kiosk: bool) {
+        self.kiosk = kiosk;
+    }
+
+    /// Goes back to the windows, files and memory of the first frame of the kiosk.
+    pub fn reset_ui(&mut self) {
+        self.restored_workspace = self.kiosk_start.clone().map(Box::new);
+    }
+
+    /// Switches to the profile `name`: its renderer settings apply from the next frame on, and its
+    /// theme replaces the colors of the style unless `--theme` does.
+    pub fn switch_profile(&mut self, name: &str) -> Result<(), String> {
+        let profile = self
+            .config
+            .profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("There is no profile named {:?}", name))?;
+        self.config.settings.frame_rate_limit = profile.frame_rate_limit;
+        self.config.settings.msaa_samples =
+            crate::config::supported_msaa_samples(profile.msaa_samples);
+        self.latency_tester.set_present_mode(profile.present_mode);
+        self.config.profiles.active = Some(profile.name);
+        Ok(())
+    }
+
+    /// The theme that replaces the colors of the style, of `--theme` or of the profile.
+    fn theme(&self) -> Option<crate::options::Theme> {
+        self.theme.or_else(|| {
+            self.config
+                .profiles
+                .active()
+                .and_then(|profile| profile.theme)
+        })
+    }
+
+    /// The persistent state of the app.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The color the frame is cleared with, as set in the settings window.
+    pub fn clear_color(&self) -> wgpu::Color {
+        let [r, g, b] = self.config.settings.clear_color;
+        wgpu::Color {
+            r: r as f64,
+            g: g as f64,
+            b: b as f64,
+            a: 1.0,
+        }
+    }
+
+    /// The region of the current frame the screenshot tool wants to capture.
+    pub fn take_screenshot_request(&mut self) -> Option<crate::screenshot::Request> {
+        self.screenshot.take_request()
+    }
+
+    /// Hands the pixels read back for a screenshot request to the screenshot tool.
+    pub fn finish_screenshot(
+        &mut self,
+        ctx: &egui::Context,
+        request: crate::screenshot::Request,
+        pixels: Vec<u8>,
+    ) {
+        self.screenshot.finish(ctx, request, pixels);
+    }
+
+    /// The region of the current frame the interaction recorder captures, a frame of
+    /// `size` physical pixels.
+    pub fn take_recording_region(&mut self, size: [u32; 2]) -> Option<crate::readback::Region> {
+        self.interaction_recorder.take_capture_region(size)
+    }
+
+    /// Hands the pixels read back for `take_recording_region` to the interaction recorder.
+    pub fn add_recorded_frame(&mut self, pixels: Vec<u8>, size: [u32; 2]) {
+        self.interaction_recorder.add_frame(pixels, size);
+    }
+
+    /// Whether the current frame should be exported as SVG.
+    pub fn take_svg_request(&mut self) -> bool {
+        self.screenshot.take_svg_request()
+    }
+
+    /// Shows where the SVG export ended up.
+    pub fn finish_svg(&mut self, ctx: &egui::Context, result: Result<std::path::PathBuf, String>) {
+        self.screenshot.finish_svg(ctx, result);
+    }
+
+    /// The pixel of the current frame the eyedropper of the color picker wants to read.
+    pub fn color_sample_region(&self) -> Option<crate::readback::Region> {
+        self.color_picker.sample_region()
+    }
+
+    /// The color vision deficiency the frame is filtered to look like, if any.
+    pub fn color_blindness(&self) -> Option<crate::color_blindness::Simulation> {
+        self.debug_tools.color_blindness()
+    }
+
+    /// Whether the damage of each frame is shown, see `DamageTracker`.
+    pub fn show_damage(&self) -> bool {
+        self.debug_tools.show_damage()
+    }
+
+    /// Hands the part of the window that changed in the last frame to the damage view.
+    pub fn set_damage(&mut self, damage: egui::Rect) {
+        self.debug_tools.set_damage(damage);
+    }
+
+    /// Hands the pixel read back for `color_sample_region` to the color picker.
+    pub fn set_color_sample(&mut self, pixel: &[u8]) {
+        self.color_picker.set_sample(pixel);
+    }
+
+    /// Handles a MIDI message that arrived over the event loop.
+    #[cfg(feature = "midi")]
+    pub fn handle_midi(&mut self, control_change: crate::midi::ControlChange) {
+        self.midi
+            .handle(control_change, &mut self.config.midi_bindings);
+    }
+
+    /// Loads the images, fonts and shaders in `dir`, and again whenever they change.
+    pub fn watch_assets(&mut self, ctx: &egui::Context, dir: &std::path::Path) {
+        self.assets.watch(ctx, dir);
+    }
+
+    /// Shows the wgpu trace that is recorded into `dir` in the Debug menu. The trace ends after
+    /// `frames`, or once the user asks for it.
+    pub fn start_trace(&mut self, dir: std::path::PathBuf, frames: Option<u32>) {
+        self.debug_tools.start_trace(dir, frames);
+    }
+
+    /// Whether the frames of the wgpu trace are recorded and the example should exit.
+    pub fn trace_finished(&self) -> bool {
+        self.debug_tools.trace_finished()
+    }
+
+    /// Hands over the startup times that were measured before the app existed.
+    pub fn set_startup_report(&mut self, startup_report: crate::startup_report::StartupReport) {
+        self.startup_report = startup_report;
+    }
+
+    /// Reports that a frame was presented, for the startup report.
+    pub fn first_frame_presented(&mut self) {
+        self.startup_report.first_frame_presented();
+    }
+
+    /// Whether the HUD layer is drawn and gets input.
+    pub fn hud_enabled(&self) -> bool {
+        self.hud_enabled
+    }
+
+    /// Whether the runner coalesces the pointer motion and scrolling between frames.
+    pub fn batch_input(&self) -> bool {
+        self.debug_tools.batch_input()
+    }
+
+    /// How many window events winit sent for the last frame, and how many were forwarded.
+    pub fn set_input_counts(&mut self, received: u32, forwarded: u32) {
+        self.debug_tools.set_input_counts(received, forwarded);
+    }
+
+    /// Adds a winit event that is forwarded to the platform integration to the event log.
+    pub fn log_event(&mut self, event: &winit::event::Event<Event>) {
+        self.debug_tools.log_event(event);
+    }
+
+    /// Applies the size limits of the settings to `window`. Called once the window exists.
+    pub fn init_window(&self, window: &Window) {
+        apply_window_constraints(window, &self.config.settings.window);
+        #[cfg(target_os = "windows")]
+        crate::title_bar::apply(window, &self.config.settings.title_bar);
+        #[cfg(target_os = "macos")]
+        crate::macos_title_bar::apply(window, self.config.settings.title_bar.transparent);
+    }
+
+    /// Requests the corrected size if the user resized `window` against the aspect-ratio lock
+    /// or a window manager that ignores the size hints.
+    pub fn window_resized(&self, window: &Window, size: PhysicalSize<u32>) {
+        if window.fullscreen().is_none() {
+            request_constrained_size(window, &self.config.settings.window, size);
+        }
+    }
+
+    /// The controls of the presentation window, which the runner opens and renders.
+    pub fn presentation_mut(&mut self) -> &mut crate::presentation::Presentation {
+        &mut self.presentation
+    }
+
+    /// Draws the current slide into the context of the presentation window.
+    pub fn slide_ui(&mut self, ctx: &egui::Context) {
+        self.presentation.slide_ui(ctx, &self.config.slides);
+    }
+
+    /// Notifies the display mode tool that `window` gained or lost focus.
+    pub fn focus_changed(&mut self, window: &Window, focused: bool) {
+        self.display_mode.focus_changed(window, focused);
+    }
+
+    /// Whether the frame statistics should be exported after this frame.
+    pub fn take_frame_stats_export(&mut self) -> bool {
+        self.debug_tools.take_frame_stats_export()
+    }
+
+    /// Shows in the debug panel where the frame stats were exported to, or why they weren't.
+    pub fn set_frame_stats_exported(&mut self, result: Result<std::path::PathBuf, String>) {
+        self.debug_tools.set_frame_stats_exported(result);
+    }
+
+    /// The tessellation options of the debug panel, for the main UI and the HUD.
+    pub fn tessellation_options(&self) -> egui::epaint::TessellationOptions {
+        self.debug_tools.tessellation_options()
+    }
+
+    /// Whether the runner encodes the layers on threads of their own.
+    pub fn multithreaded_encoding(&self) -> bool {
+        self.stress_test.multithreaded_encoding()
+    }
+
+    /// The present mode the latency tester wants the surface to use.
+    pub fn present_mode(&self) -> wgpu::PresentMode {
+        self.latency_tester.present_mode()
+    }
+
+    /// Starts a measurement of the latency tester, called as soon as winit delivers a press.
+    pub fn pointer_pressed(&mut self) {
+        self.latency_tester.pointer_pressed();
+    }
+
+    /// The frame was submitted to `queue`, with the flash of the latency tester if it was due.
+    pub fn frame_submitted(&mut self, queue: &wgpu::Queue) {
+        self.latency_tester.submitted(queue);
+    }
+
+    /// The frame was presented.
+    pub fn frame_presented(&mut self) {
+        self.latency_tester.presented();
+    }
+
+    /// The residency of the user textures, shown in the GPU memory section of the settings.
+    pub fn set_texture_stats(&mut self, stats: crate::user_textures::ResidencyStats) {
+        self.texture_stats = stats;
+    }
+
+    /// Adds the input of the debug tools to the next frame. Called before it starts.
+    pub fn prepare_frame(&mut self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
+        self.debug_tools.next_frame(ctx, raw_input);
+        self.on_screen_keyboard.next_frame(raw_input);
+    }
+
+    /// Lets the debug tools look at the frame that was just laid out.
+    pub fn observe_frame(&mut self, ctx: &egui::Context, output: &egui::FullOutput) {
+        self.debug_tools.observe(ctx, output);
+        if self.large_text_open {
+            self.large_text.observe(ctx, output);
+        }
+    }
+
+    /// Hands the statistics of the recent frames to the debug tools and the stress test.
+    pub fn set_recent_frames(&mut self, frames: &[crate::frame_stats::FrameRecord]) {
+        self.debug_tools.set_recent_frames(frames);
+        if self.stress_test_open {
+            self.stress_test.set_recent_frames(frames);
+        }
+    }
+
+    /// Tells that the previous session crashed and wrote `report`.
+    pub fn show_crash_dialog(&mut self, report: std::path::PathBuf) {
+        self.crash_dialog = crate::crash_report::CrashDialog::new(report);
+    }
+
+    /// Remembers a file that was dropped onto the window in the File menu.
+    pub fn add_recent_file(&mut self, path: &std::path::Path) {
+        self.config.add_recent_file(path);
+    }
+
+    /// Opens the tool windows that show `files`, picked by their extension. The tools then load
+    /// the files like dropped ones.
+    pub fn open_tools_for(&mut self, files: &[std::path::PathBuf]) {
+        for file in files {
+            self.config.add_recent_file(file);
+            let extension = file
+                .extension()
+                .map(|extension| extension.to_string_lossy().to_lowercase());
+            match extension.as_deref() {
+                Some("csv" | "tsv") => self.csv_viewer_open = true,
+                Some("json") => self.json_inspector_open = true,
+                Some("log" | "txt") => self.log_tail_open = true,
+                Some(extension) if crate::image_viewer::EXTENSIONS.contains(&extension) => {
+                    self.image_viewer_open = true
+                }
+                #[cfg(feature = "sqlite")]
+                Some("db" | "sqlite" | "sqlite3") => self.sqlite_browser_open = true,
+                _ => self.hex_viewer_open = true,
+            }
+        }
+    }
+
+    /// Reacts to the output of the last frame, like the accessibility events.
+    pub fn handle_platform_output(
+        &mut self,
+        #[cfg_attr(not(feature = "tts"), allow(unused_variables))] output: &egui::PlatformOutput,
+    ) {
+        #[cfg(feature = "tts")]
+        self.speech
+            .handle_output(output, &self.config.settings.speech);
+    }
+
+    /// Draws the whole UI for one frame.
+    pub fn ui(
+        &mut self,
+        ctx: &egui::Context,
+        window: &Window,
+        scheduler: &FrameScheduler,
+        textures: &mut crate::user_textures::UserTextures,
+    ) {
+        if let Some(workspace) = self.restored_workspace.take() {
+            self.restore_workspace(ctx, *workspace);
+        }
+        if self.kiosk && self.kiosk_start.is_none() {
+            self.kiosk_start = Some(self.save_workspace(ctx));
+        }
+        self.onboarding.begin_frame();
+        for action in self.shortcut_editor.pressed(ctx, &self.config.shortcuts) {
+            self.run_action(action);
+        }
+        let mut style = crate::style_editor::effective_style(
+            &self.config.style,
+            self.theme(),
+            &self.config.settings.accessibility,
+        );
+        self.debug_tools.mark_focus(&mut style);
+        if *ctx.style() != style {
+            ctx.set_style(style);
+        }
+        if self.config.settings.font_prewarm.enabled
+            && self.prewarmed_for != Some(ctx.pixels_per_point())
+        {
+            self.prewarm_fonts(ctx);
+        }
+        self.assets.update(ctx, textures);
+        // Makes labels focusable, so that they can be read out too.
+        ctx.options().screen_reader = self.config.settings.speech.enabled;
+        self.update_global_hotkey();
+
+        // With a transparent title bar on macOS, the menu bar takes its place next to the window
+        // buttons, and the empty part of it moves the window.
+        #[cfg(target_os = "macos")]
+        let title_bar = self
+            .config
+            .settings
+            .title_bar
+            .transparent
+            .then(|| crate::macos_title_bar::insets(window));
+        #[cfg(not(target_os = "macos"))]
+        let title_bar: Option<egui::style::Margin> = None;
+        let mut menu_bar = egui::TopBottomPanel::top("app_menu_bar");
+        if let Some(insets) = title_bar {
+            menu_bar = menu_bar.min_height(insets.top);
+        }
+        menu_bar.show(ctx, |ui| {
+            if let Some(insets) = title_bar {
+                // Centered in the title bar, the panel has margins of 2 points.
+                ui.add_space(((insets.top - ui.spacing().interact_size.y) / 2.0 - 2.0).max(0.0));
+            }
+            egui::menu::bar(ui, |ui| {
+                if let Some(insets) = title_bar {
+                    ui.add_space(insets.left);
+                }
+                let file = ui.menu_button("File", |ui| self.file_menu(ui));
+                self.onboarding.track("file_menu", file.response.rect);
+                let tools = ui.menu_button("Tools", |ui| self.tools_menu(ui));
+                self.onboarding.track("tools_menu", tools.response.rect);
+                let workspaces = ui.menu_button("Workspaces", |ui| self.workspaces_menu.ui(ui));
+                self.onboarding
+                    .track("workspaces_menu", workspaces.response.rect);
+                match workspaces.inner {
+                    Some(Some(crate::workspaces::Action::Save(name))) => {
+                        let result = crate::workspaces::save(&name, &self.save_workspace(ui.ctx()));
+                        self.workspaces_menu.saved(&name, result);
+                    }
+                    Some(Some(crate::workspaces::Action::Restore(workspace))) => {
+                        self.restored_workspace = Some(workspace);
+                    }
+                    Some(None) => {}
+                    None => self.workspaces_menu.closed(),
+                }
+                let debug = ui.menu_button("Debug", |ui| self.debug_tools.menu(ui));
+                self.onboarding.track("debug_menu", debug.response.rect);
+                let help = ui.menu_button("Help", |ui| {
+                    if ui.button("🎓 Show the tour").clicked() {
+                        self.onboarding.start();
+                        ui.close_menu();
+                    }
+                });
+                self.onboarding.track("help_menu", help.response.rect);
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    self.debug_tools.status_ui(ui);
+                    self.interaction_recorder.status_ui(ui);
+                    if title_bar.is_some() {
+                        let (_, response) =
+                            ui.allocate_exact_size(ui.available_size(), egui::Sense::drag());
+                        if response.drag_started() {
+                            if let Err(err) = window.drag_window() {
+                                eprintln!("Can't move the window: {}", err);
+                            }
+                        }
+                    }
+                });
+            });
+        });
+        if self.software_renderer_banner {
+            self.software_renderer_banner_ui(ctx);
+        }
+        if let Some(report) = self.crash_dialog.show(ctx) {
+            self.event_loop_proxy
+                .send_event(Event::Activate(vec![report]))
+                .ok();
+        }
+        // Before the demos, so that their panel ends above the terminal.
+        #[cfg(feature = "terminal")]
+        self.terminal.show(ctx, &mut self.terminal_open);
+
+        match &mut self.demo_windows {
+            Some(demo_windows) => demo_windows.ui(ctx),
+            None if self.startup_report.first_frame().is_some() => {
+                let start = std::time::Instant::now();
+                self.demo_windows = Some(Default::default());
+                self.startup_report
+                    .record("Demo windows (after the first frame)", start);
+                ctx.request_repaint();
+            }
+            // Draws the first frame without the demos, then builds them for the next one.
+            None => ctx.request_repaint(),
+        }
+
+        let mut settings_open = self.settings_open;
+        egui::Window::new("⚙ Settings")
+            .open(&mut settings_open)
+            .show(ctx, |ui| self.settings_ui(ui, window, scheduler));
+        self.settings_open = settings_open;
+
+        self.display_mode
+            .show(ctx, &mut self.display_mode_open, window);
+        self.presentation.show(
+            ctx,
+            &mut self.presentation_open,
+            window,
+            &mut self.config.slides,
+        );
+        self.system_monitor.show(ctx, &mut self.system_monitor_open);
+        self.gpu_report.show(ctx, &mut self.gpu_report_open);
+        self.startup_report.show(ctx, &mut self.startup_report_open);
+        self.color_picker
+            .show(ctx, &mut self.color_picker_open, &mut self.config.palette);
+        self.emoji_demo.show(ctx, &mut self.emoji_demo_open);
+        if let Some(file) = self
+            .file_browser
+            .show(ctx, &mut self.file_browser_open, textures)
+        {
+            // Opened like a recent file.
+            self.event_loop_proxy
+                .send_event(Event::Activate(vec![file]))
+                .ok();
+        }
+        self.image_viewer
+            .show(ctx, &mut self.image_viewer_open, textures);
+        self.tile_editor
+            .show(ctx, &mut self.tile_editor_open, textures);
+        self.ecs_scene
+            .show(ctx, &mut self.ecs_scene_open, textures, &self.assets);
+        self.assets.show(ctx, &mut self.assets_open);
+        self.csv_viewer.show(ctx, &mut self.csv_viewer_open);
+        self.json_inspector.show(ctx, &mut self.json_inspector_open);
+        self.log_tail.show(ctx, &mut self.log_tail_open);
+        self.hex_viewer.show(ctx, &mut self.hex_viewer_open);
+        #[cfg(feature = "physics")]
+        self.physics_sandbox
+            .show(ctx, &mut self.physics_sandbox_open, textures);
+        #[cfg(feature = "sqlite")]
+        self.sqlite_browser.show(ctx, &mut self.sqlite_browser_open);
+        #[cfg(feature = "mqtt")]
+        self.mqtt_dashboard.show(ctx, &mut self.mqtt_dashboard_open);
+        self.stress_test.show(ctx, &mut self.stress_test_open);
+        self.large_text.show(ctx, &mut self.large_text_open);
+        self.latency_tester.show(ctx, &mut self.latency_tester_open);
+        self.screenshot.show(ctx);
+        if let Some(message) = self.interaction_recorder.update(ctx) {
+            self.screenshot.set_status(ctx, message);
+        }
+        let clear_color = self.clear_color();
+        self.debug_tools.show(ctx, textures, clear_color);
+
+        #[cfg(feature = "audio")]
+        if self.audio_visualizer_open {
+            // Created on first use, so that the audio host isn't touched unless it's needed.
+            self.audio_visualizer
+                .get_or_insert_with(Default::default)
+                .show(ctx, &mut self.audio_visualizer_open);
+        }
+        self.on_screen_keyboard
+            .show(ctx, self.config.settings.on_screen_keyboard);
+        self.onboarding.show(ctx);
+    }
+
+    fn software_renderer_banner_ui(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("software_renderer_banner").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!(
+                        "⚠ Running on software renderer {} — expect low performance",
+                        self.gpu_report.info.name
+                    ),
+                );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("✖").on_hover_text("Dismiss").clicked() {
+                        self.software_renderer_banner = false;
+                    }
+                });
+            });
+        });
+    }
+
+    fn file_menu(&mut self, ui: &mut egui::Ui) {
+        ui.add_enabled_ui(!self.config.recent_files.is_empty(), |ui| {
+            ui.menu_button("🕘 Open recent", |ui| {
+                for path in &self.config.recent_files {
+                    let name = path.file_name().map_or_else(
+                        || path.display().to_string(),
+                        |name| name.to_string_lossy().into_owned(),
+                    );
+                    let response = ui
+                        .add_enabled(path.exists(), egui::Button::new(name))
+                        .on_hover_text(path.display().to_string())
+                        .on_disabled_hover_text(format!("{} is gone", path.display()));
+                    if response.clicked() {
+                        // Opened like the files of another instance, which also puts it first.
+                        self.event_loop_proxy
+                            .send_event(Event::Activate(vec![path.clone()]))
+                            .ok();
+                        ui.close_menu();
+                    }
+                }
+                ui.separator();
+                if ui.button("Clear recent files").clicked() {
+                    self.config.recent_files.clear();
+                    ui.close_menu();
+                }
+            });
+        });
+    }
+
+    /// Does what the keyboard shortcut of `action` stands for.
+    fn run_action(&mut self, action: crate::shortcuts::Action) {
+        use crate::shortcuts::Action;
+
+        match action {
+            Action::Settings => self.settings_open = !self.settings_open,
+            Action::SystemMonitor => self.system_monitor_open = !self.system_monitor_open,
+            Action::GpuReport => self.gpu_report_open = !self.gpu_report_open,
+            Action::ColorPicker => self.color_picker_open = !self.color_picker_open,
+            Action::StressTest => self.stress_test_open = !self.stress_test_open,
+            Action::Hud => self.hud_enabled = !self.hud_enabled,
+            Action::ScreenshotToFile => self.screenshot.start(crate::screenshot::Target::File),
+            Action::ScreenshotToClipboard => {
+                self.screenshot.start(crate::screenshot::Target::Clipboard)
+            }
+            Action::ExportSvg => self.screenshot.start_svg_export(),
+            Action::Tour => self.onboarding.start(),
+            Action::NextSlide => self.presentation.step(1, &self.config.slides),
+            Action::PreviousSlide => self.presentation.step(-1, &self.config.slides),
+        }
+    }
+
+    /// The open flags of the tool windows, by the names the workspaces know them by.
+    fn windows(&mut self) -> Vec<(&'static str, &mut bool)> {
+        vec![
+            ("settings", &mut self.settings_open),
+            ("display_mode", &mut self.display_mode_open),
+            ("presentation", &mut self.presentation_open),
+            ("system_monitor", &mut self.system_monitor_open),
+            ("gpu_report", &mut self.gpu_report_open),
+            ("startup_report", &mut self.startup_report_open),
+            ("color_picker", &mut self.color_picker_open),
+            ("emoji_demo", &mut self.emoji_demo_open),
+            ("file_browser", &mut self.file_browser_open),
+            ("image_viewer", &mut self.image_viewer_open),
+            ("tile_editor", &mut self.tile_editor_open),
+            ("ecs_scene", &mut self.ecs_scene_open),
+            ("assets", &mut self.assets_open),
+            ("csv_viewer", &mut self.csv_viewer_open),
+            ("json_inspector", &mut self.json_inspector_open),
+            ("log_tail", &mut self.log_tail_open),
+            ("hex_viewer", &mut self.hex_viewer_open),
+            ("hud", &mut self.hud_enabled),
+            ("stress_test", &mut self.stress_test_open),
+            ("large_text", &mut self.large_text_open),
+            ("latency_tester", &mut self.latency_tester_open),
+            #[cfg(feature = "audio")]
+            ("audio_visualizer", &mut self.audio_visualizer_open),
+            #[cfg(feature = "physics")]
+            ("physics_sandbox", &mut self.physics_sandbox_open),
+            #[cfg(feature = "sqlite")]
+            ("sqlite_browser", &mut self.sqlite_browser_open),
+            #[cfg(feature = "mqtt")]
+            ("mqtt_dashboard", &mut self.mqtt_dashboard_open),
+            #[cfg(feature = "terminal")]
+            ("terminal", &mut self.terminal_open),
+        ]
+    }
+
+    fn save_workspace(&mut self, ctx: &egui::Context) -> crate::workspaces::Workspace {
+        use crate::workspaces::SaveState;
+
+        let windows = self
+            .windows()
+            .into_iter()
+            .map(|(name, open)| (name.to_owned(), *open))
+            .collect();
+        crate::workspaces::Workspace {
+            windows,
+            memory: ctx.memory().clone(),
+            csv_viewer: self.csv_viewer.save_state(),
+            json_inspector: self.json_inspector.save_state(),
+            log_tail: self.log_tail.save_state(),
+            image_viewer: self.image_viewer.save_state(),
+            hex_viewer: self.hex_viewer.save_state(),
+            ecs_scene: self.ecs_scene.save_state(),
+            theme: self.config.save_state(),
+        }
+    }
+
+    /// Windows the workspace doesn't know keep their state, the files open in their tools once
+    /// those are shown.
+    fn restore_workspace(&mut self, ctx: &egui::Context, workspace: crate::workspaces::Workspace) {
+        use crate::workspaces::SaveState;
+
+        for (name, open) in self.windows() {
+            if let Some(saved) = workspace.windows.get(name) {
+                *open = *saved;
+            }
+        }
+        // The options are settings of this example, not of the workspace.
+        let options = ctx.options().clone();
+        *ctx.memory() = workspace.memory;
+        *ctx.options() = options;
+        self.csv_viewer.restore_state(workspace.csv_viewer);
+        self.json_inspector.restore_state(workspace.json_inspector);
+        self.log_tail.restore_state(workspace.log_tail);
+        self.image_viewer.restore_state(workspace.image_viewer);
+        self.hex_viewer.restore_state(workspace.hex_viewer);
+        self.ecs_scene.restore_state(workspace.ecs_scene);
+        self.config.restore_state(workspace.theme);
+    }
+
+    fn tools_menu(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.settings_open, "⚙ Settings");
+        ui.checkbox(&mut self.display_mode_open, "🖵 Display mode");
+        ui.checkbox(&mut self.presentation_open, "📽 Presentation");
+        ui.checkbox(&mut self.system_monitor_open, "📈 System monitor");
+        ui.checkbox(&mut self.gpu_report_open, "🖥 GPU report");
+        ui.checkbox(&mut self.startup_report_open, "⏱ Startup report");
+        ui.checkbox(&mut self.color_picker_open, "🎨 Color picker");
+        ui.checkbox(&mut self.emoji_demo_open, "😀 Emoji");
+        ui.checkbox(&mut self.file_browser_open, "📁 File browser");
+        ui.checkbox(&mut self.image_viewer_open, "🖼 Image viewer");
+        ui.checkbox(&mut self.tile_editor_open, "🗺 Tile editor");
+        ui.checkbox(&mut self.ecs_scene_open, "🧊 ECS scene");
+        ui.checkbox(&mut self.assets_open, "📦 Assets");
+        ui.checkbox(&mut self.csv_viewer_open, "📄 CSV viewer");
+        ui.checkbox(&mut self.json_inspector_open, "🌲 JSON inspector");
+        ui.checkbox(&mut self.log_tail_open, "📜 Log tail");
+        ui.checkbox(&mut self.hex_viewer_open, "🔢 Hex viewer");
+        ui.checkbox(&mut self.hud_enabled, "🎯 HUD layer");
+        ui.checkbox(&mut self.stress_test_open, "🔥 Stress test");
+        ui.checkbox(&mut self.large_text_open, "📚 Large text");
+        ui.checkbox(&mut self.latency_tester_open, "⏲ Latency tester");
+        let mut switched = None;
+        ui.menu_button("🔋 Profile", |ui| {
+            switched = crate::profiles::ProfilesEditor::menu(ui, &self.config.profiles);
+        });
+        if let Some(name) = switched {
+            self.switch_profile(&name)
+                .expect("The menu lists the existing profiles");
+        }
+        ui.separator();
+        if ui.button("📷 Screenshot region to file").clicked() {
+            self.screenshot.start(crate::screenshot::Target::File);
+            ui.close_menu();
+        }
+        if ui.button("📋 Screenshot region to clipboard").clicked() {
+            self.screenshot.start(crate::screenshot::Target::Clipboard);
+            ui.close_menu();
+        }
+        if ui.button("🖋 Export frame as SVG").clicked() {
+            self.screenshot.start_svg_export();
+            ui.close_menu();
+        }
+        let recording = self.interaction_recorder.is_recording();
+        let label = match recording {
+            true => "⏹ Stop recording",
+            false => "⏺ Record interaction to GIF",
+        };
+        if ui.button(label).clicked() {
+            match recording {
+                true => self.interaction_recorder.stop(),
+                false => self.interaction_recorder.start(ui.ctx()),
+            }
+            ui.close_menu();
+        }
+        #[cfg(feature = "pdf")]
+        ui.menu_button("🖨 Export to PDF", |ui| self.pdf_menu(ui));
+        #[cfg(not(feature = "pdf"))]
+        ui.add_enabled(false, egui::Label::new("🖨 Export to PDF"))
+            .on_disabled_hover_text("Build with `--features pdf` to enable");
+        ui.separator();
+        #[cfg(feature = "audio")]
+        ui.checkbox(&mut self.audio_visualizer_open, "🎤 Audio visualizer");
+        #[cfg(not(feature = "audio"))]
+        ui.add_enabled(false, egui::Label::new("🎤 Audio visualizer"))
+            .on_disabled_hover_text("Build with `--features audio` to enable");
+        #[cfg(feature = "physics")]
+        ui.checkbox(&mut self.physics_sandbox_open, "🎱 Physics sandbox");
+        #[cfg(not(feature = "physics"))]
+        ui.add_enabled(false, egui::Label::new("🎱 Physics sandbox"))
+            .on_disabled_hover_text("Build with `--features physics` to enable");
+        #[cfg(feature = "sqlite")]
+        ui.checkbox(&mut self.sqlite_browser_open, "🗄 SQLite browser");
+        #[cfg(not(feature = "sqlite"))]
+        ui.add_enabled(false, egui::Label::new("🗄 SQLite browser"))
+            .on_disabled_hover_text("Build with `--features sqlite` to enable");
+        #[cfg(feature = "mqtt")]
+        ui.checkbox(&mut self.mqtt_dashboard_open, "📡 MQTT dashboard");
+        #[cfg(not(feature = "mqtt"))]
+        ui.add_enabled(false, egui::Label::new("📡 MQTT dashboard"))
+            .on_disabled_hover_text("Build with `--features mqtt` to enable");
+        #[cfg(feature = "terminal")]
+        ui.checkbox(&mut self.terminal_open, "💻 Terminal");
+        #[cfg(not(feature = "terminal"))]
+        ui.add_enabled(false, egui::Label::new("💻 Terminal"))
+            .on_disabled_hover_text("Build with `--features terminal` to enable");
+    }
+
+    /// Exports one of the panels to PDF. The export takes a moment, the menu closes once it's
+    /// done.
+    #[cfg(feature = "pdf")]
+    fn pdf_menu(&mut self, ui: &mut egui::Ui) {
+        use crate::pdf_export::export;
+
+        let result = if ui.button("📈 System monitor").clicked() {
+            let system_monitor = &mut self.system_monitor;
+            Some(export("System monitor", "system_monitor", |ui| {
+                system_monitor.ui(ui)
+            }))
+        } else if ui.button("🖥 GPU report").clicked() {
+            Some(export("GPU report", "gpu_report", |ui| {
+                self.gpu_report.ui(ui)
+            }))
+        } else if ui.button("⏱ Startup report").clicked() {
+            Some(export("Startup report", "startup_report", |ui| {
+                self.startup_report.ui(ui)
+            }))
+        } else if ui.button("😀 Emoji").clicked() {
+            let mut emoji_demo = self.emoji_demo.for_print();
+            Some(export("Emoji", "emoji", |ui| emoji_demo.ui(ui)))
+        } else {
+            None
+        };
+        if let Some(result) = result {
+            let message = match result {
+                Ok(path) => format!("Saved PDF to {}", path.display()),
+                Err(err) => format!("PDF export failed: {}", err),
+            };
+            self.screenshot.set_status(ui.ctx(), message);
+            ui.close_menu();
+        }
+    }
+
+    fn settings_ui(&mut self, ui: &mut egui::Ui, window: &Window, scheduler: &FrameScheduler) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.settings_tab, SettingsTab::General, "General");
+            ui.selectable_value(&mut self.settings_tab, SettingsTab::Style, "Style");
+            ui.selectable_value(
+                &mut self.settings_tab,
+                SettingsTab::Accessibility,
+                "Accessibility",
+            );
+            ui.selectable_value(&mut self.settings_tab, SettingsTab::Shortcuts, "Shortcuts");
+        });
+        ui.separator();
+        match self.settings_tab {
+            SettingsTab::General => self.general_settings_ui(ui, window, scheduler),
+            SettingsTab::Style => {
+                if self.theme.is_some() {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        "⚠ --theme replaces the colors of this style while it runs",
+                    );
+                } else if self.theme().is_some() {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        "⚠ The theme of the profile replaces the colors of this style",
+                    );
+                }
+                self.style_editor.ui(ui, &mut self.config.style)
+            }
+            SettingsTab::Accessibility => self.accessibility_settings_ui(ui),
+            SettingsTab::Shortcuts => self.shortcut_editor.ui(ui, &mut self.config.shortcuts),
+        }
+    }
+
+    fn accessibility_settings_ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Display");
+        let accessibility = &mut self.config.settings.accessibility;
+        ui.checkbox(&mut accessibility.high_contrast, "High contrast")
+            .on_hover_text("Solid colors and thicker outlines, on top of the style");
+        ui.checkbox(&mut accessibility.reduced_motion, "Reduced motion")
+            .on_hover_text("Disables animations");
+        ui.horizontal(|ui| {
+            let mut custom = accessibility.focus_outline.is_some();
+            ui.checkbox(&mut custom, "Focus outline")
+                .on_hover_text("Outlines the widget with the keyboard focus like this");
+            match custom {
+                true => {
+                    let outline = accessibility.focus_outline.get_or_insert(egui::Stroke::new(
+                        2.0,
+                        egui::Color32::from_rgb(255, 160, 0),
+                    ));
+                    egui::widgets::stroke_ui(ui, outline, "");
+                }
+                false => accessibility.focus_outline = None,
+            }
+        });
+
+        ui.separator();
+        ui.heading("Text-to-speech");
+        #[cfg(feature = "tts")]
+        self.speech.ui(ui, &mut self.config.settings.speech);
+        #[cfg(not(feature = "tts"))]
+        ui.weak("Build with `--features tts` to speak the focused widget.");
+    }
+
+    fn general_settings_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        window: &Window,
+        scheduler: &FrameScheduler,
+    ) {
+        ui.heading("Profiles");
+        let present_mode = self.latency_tester.present_mode();
+        let switched = self.profiles_editor.ui(
+            ui,
+            &mut self.config.profiles,
+            &self.config.settings,
+            present_mode,
+        );
+        if let Some(name) = switched {
+            self.switch_profile(&name)
+                .expect("The editor lists the existing profiles");
+        }
+
+        ui.separator();
+        ui.heading("Background");
+        for (index, channel) in ["Red", "Green", "Blue"].into_iter().enumerate() {
+            let value = &mut self.config.settings.clear_color[index];
+            #[cfg_attr(not(feature = "midi"), allow(unused_variables))]
+            let response = ui.add(egui::Slider::new(&mut *value, 0.0..=1.0).text(channel));
+
+            // Every slider can be bound to a MIDI controller over its context menu.
+            #[cfg(feature = "midi")]
+            self.midi.bind(
+                response,
+                &format!("settings.clear_color.{}", channel.to_lowercase()),
+                value,
+                0.0..=1.0,
+                &mut self.config.midi_bindings,
+            );
+        }
+
+        ui.separator();
+        ui.heading("Window");
+        let constraints = &mut self.config.settings.window;
+        let before = *constraints;
+        size_constraint_ui(ui, "Minimum size", &mut constraints.min_size, [640, 360]);
+        size_constraint_ui(ui, "Maximum size", &mut constraints.max_size, [3840, 2160]);
+        ui.horizontal(|ui| {
+            let mut locked = constraints.aspect_ratio.is_some();
+            ui.checkbox(&mut locked, "Lock aspect ratio");
+            if !locked {
+                constraints.aspect_ratio = None;
+            } else {
+                let size = window.inner_size();
+                let [width, height] = constraints
+                    .aspect_ratio
+                    .get_or_insert([size.width, size.height]);
+                ui.add(egui::DragValue::new(width).clamp_range(1..=u32::MAX));
+                ui.label(":");
+                ui.add(egui::DragValue::new(height).clamp_range(1..=u32::MAX));
+                if ui.button("16:9").clicked() {
+                    constraints.aspect_ratio = Some([16, 9]);
+                }
+                if ui.button("4:3").clicked() {
+                    constraints.aspect_ratio = Some([4, 3]);
+                }
+            }
+        });
+        if *constraints != before {
+            apply_window_constraints(window, constraints);
+        }
+        self.title_bar_ui(ui, window);
+        let settings = &mut self.config.settings;
+        ui.checkbox(&mut settings.live_resize, "Draw while resizing")
+            .on_hover_text("Draws right in the resize event, for the live resize on Windows");
+        ui.checkbox(&mut settings.relayout_on_resize, "Lay out twice on resize")
+            .on_hover_text("Keeps the panels from lagging a frame behind a new window size");
+        ui.checkbox(
+            &mut self.config.settings.global_hotkey,
+            "Ctrl+Shift+Space shows and hides the window",
+        )
+        .on_hover_text("From anywhere, also while another application is in front");
+        if let Some(err) = &self.global_hotkey_error {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+        ui.horizontal(|ui| {
+            use crate::on_screen_keyboard::Mode;
+
+            ui.label("On-screen keyboard:");
+            let mode = &mut self.config.settings.on_screen_keyboard;
+            ui.selectable_value(mode, Mode::Auto, "Auto")
+                .on_hover_text("Once the screen is touched, until a hardware keyboard is used");
+            ui.selectable_value(mode, Mode::Always, "Always");
+            ui.selectable_value(mode, Mode::Never, "Never");
+        });
+        ui.horizontal(|ui| {
+            let after = &mut self.config.settings.attract_after;
+            let mut enabled = after.is_some();
+            ui.checkbox(&mut enabled, "Attract screen after")
+                .on_hover_text("An animation replaces the UI until the next input");
+            if !enabled {
+                *after = None;
+            } else {
+                let after = after.get_or_insert(300);
+                ui.add(
+                    egui::DragValue::new(after)
+                        .clamp_range(5..=86400)
+                        .suffix(" s"),
+                );
+            }
+        });
+
+        ui.separator();
+        ui.heading("Frame rate");
+        ui.horizontal(|ui| {
+            let limit = &mut self.config.settings.frame_rate_limit;
+            let mut limited = limit.is_some();
+            ui.checkbox(&mut limited, "Limit frame rate");
+            if !limited {
+                *limit = None;
+            } else {
+                let limit = limit.get_or_insert(30);
+                ui.add(
+                    egui::DragValue::new(limit)
+                        .clamp_range(1..=1000)
+                        .suffix(" fps"),
+                );
+            }
+        });
+        ui.horizontal(|ui| {
+            let limit = &mut self.config.settings.max_frames_in_flight;
+            let mut limited = limit.is_some();
+            ui.checkbox(&mut limited, "Limit frames in flight")
+                .on_hover_text("Fewer frames in flight lower the latency, more the throughput.");
+            if !limited {
+                *limit = None;
+            } else {
+                let limit = limit.get_or_insert(1);
+                ui.add(egui::DragValue::new(limit).clamp_range(1..=3));
+            }
+        });
+        scheduler.ui(ui);
+
+        ui.separator();
+        ui.heading("Anti-aliasing");
+        let mut msaa = self.config.settings.msaa_samples > 1;
+        if ui
+            .checkbox(&mut msaa, "4× MSAA in the 3D scene")
+            .on_hover_text("egui smooths the edges of its own shapes without it")
+            .changed()
+        {
+            self.config.settings.msaa_samples = if msaa { 4 } else { 1 };
+        }
+
+        ui.separator();
+        ui.heading("GPU memory");
+        ui.horizontal(|ui| {
+            ui.label("Texture budget:");
+            ui.add(
+                egui::DragValue::new(&mut self.config.settings.texture_budget_mib)
+                    .clamp_range(64..=65536)
+                    .speed(16.0)
+                    .suffix(" MiB"),
+            )
+            .on_hover_text("The least recently displayed image tiles are evicted above it");
+        });
+        let stats = self.texture_stats;
+        let mib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+        ui.label(format!(
+            "{} user textures take {:.1} MiB, {:.1} MiB of it can be evicted. {} evicted so far.",
+            stats.textures,
+            mib(stats.bytes),
+            mib(stats.evictable_bytes),
+            stats.evicted
+        ));
+
+        ui.separator();
+        ui.heading("Font atlas");
+        let prewarm = &mut self.config.settings.font_prewarm;
+        ui.checkbox(&mut prewarm.enabled, "Pre-warm at the start")
+            .on_hover_text("Rasterizes the glyphs in every text style, instead of on first use");
+        ui.horizontal(|ui| {
+            ui.label("Code points:");
+            ui.add_enabled(
+                prewarm.enabled,
+                egui::TextEdit::singleline(&mut prewarm.ranges).hint_text("20-7E, A0-FF"),
+            );
+            if ui
+                .add_enabled(prewarm.enabled, egui::Button::new("Pre-warm now"))
+                .clicked()
+            {
+                self.prewarmed_for = None;
+            }
+        });
+        match &self.prewarm_result {
+            Some(Ok(glyphs)) => {
+                ui.weak(format!("Pre-warmed {} glyphs", glyphs));
+            }
+            Some(Err(err)) => {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            None => {}
+        }
+
+        #[cfg(feature = "midi")]
+        {
+            ui.separator();
+            ui.heading("MIDI");
+            self.midi.ui(ui, &mut self.config.midi_bindings);
+        }
+    }
+
+    /// Rasterizes the glyph ranges of the settings into the font atlas.
+    fn prewarm_fonts(&mut self, ctx: &egui::Context) {
+        self.prewarmed_for = Some(ctx.pixels_per_point());
+        let result = crate::font_atlas::parse_ranges(&self.config.settings.font_prewarm.ranges)
+            .and_then(|ranges| crate::font_atlas::prewarm(ctx, &ranges));
+        if let Err(err) = &result {
+            eprintln!("Can't pre-warm the font atlas: {}", err);
+        }
+        self.prewarm_result = Some(result);
+    }
+
+    /// Registers or drops the global hotkey when the setting changed. A failed registration is
+    /// retried once the setting is turned off and on again.
+    fn update_global_hotkey(&mut self) {
+        let enabled = self.config.settings.global_hotkey;
+        if !enabled {
+            self.global_hotkey = None;
+            self.global_hotkey_error = None;
+        } else if self.global_hotkey.is_none() && self.global_hotkey_error.is_none() {
+            match crate::global_hotkey::GlobalHotkey::register(self.event_loop_proxy.clone()) {
+                Ok(hotkey) => self.global_hotkey = Some(hotkey),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    self.global_hotkey_error = Some(err);
+                }
+            }
+        }
+    }
+
+    /// The title bar settings, which only Windows and macOS apply.
+    #[cfg_attr(
+        not(any(target_os = "windows", target_os = "macos")),
+        allow(unused_variables)
+    )]
+    fn title_bar_ui(&mut self, ui: &mut egui::Ui, window: &Window) {
+        let title_bar = &mut self.config.settings.title_bar;
+        let before = *title_bar;
+        ui.add_enabled_ui(
+            cfg!(any(target_os = "windows", target_os = "macos")),
+            |ui| {
+                ui.horizontal(|ui| {
+                    if cfg!(target_os = "macos") {
+                        ui.checkbox(&mut title_bar.transparent, "Menu bar in the title bar");
+                        return;
+                    }
+                    ui.checkbox(&mut title_bar.dark, "Dark title bar");
+                    ui.label("Backdrop:");
+                    for backdrop in [Backdrop::None, Backdrop::Mica, Backdrop::Acrylic] {
+                        ui.selectable_value(
+                            &mut title_bar.backdrop,
+                            backdrop,
+                            format!("{:?}", backdrop),
+                        );
+                    }
+                })
+                .response
+                .on_hover_text(if cfg!(target_os = "macos") {
+                    "Makes the title bar transparent and moves the menu bar up next to the window \
+                 buttons"
+                } else {
+                    "Mica and Acrylic need Windows 11"
+                })
+                .on_disabled_hover_text("Only on Windows and macOS");
+            },
+        );
+        #[cfg(target_os = "windows")]
+        if *title_bar != before {
+            crate::title_bar::apply(window, title_bar);
+        }
+        #[cfg(target_os = "macos")]
+        if title_bar.transparent != before.transparent {
+            crate::macos_title_bar::apply(window, title_bar.transparent);
+        }
+    }
+}
+
+/// A checkbox that enables a size constraint, followed by the editable width and height.
+fn size_constraint_ui(
+    ui: &mut egui::Ui,
+    label: &str,
+    constraint: &mut Option<[u32; 2]>,
+    default: [u32; 2],
+) {
+    ui.horizontal(|ui| {
+        let mut enabled = constraint.is_some();
+        ui.checkbox(&mut enabled, label);
+        if !enabled {
+            *constraint = None;
+        } else {
+            let [width, height] = constraint.get_or_insert(default);
+            ui.add(
+                egui::DragValue::new(width)
+                    .clamp_range(1..=u32::MAX)
+                    .suffix(" px"),
+            );
+            ui.label("×");
+            ui.add(
+                egui::DragValue::new(height)
+                    .clamp_range(1..=u32::MAX)
+                    .suffix(" px"),
+            );
+        }
+    });
+}
+
+/// Hands the size limits to the window manager and corrects the current size.
+fn apply_window_constraints(window: &Window, constraints: &WindowConstraints) {
+    window.set_min_inner_size(constraints.min_size.map(|[w, h]| PhysicalSize::new(w, h)));
+    window.set_max_inner_size(constraints.max_size.map(|[w, h]| PhysicalSize::new(w, h)));
+    if window.fullscreen().is_none() {
+        request_constrained_size(window, constraints, window.inner_size());
+    }
+}
+
+fn request_constrained_size(
+    window: &Window,
+    constraints: &WindowConstraints,
+    size: PhysicalSize<u32>,
+) {
+    let [width, height] = constraints.constrain([size.width, size.height]);
+    // Rounding of the aspect ratio can be off by a pixel. Requesting such a size again would
+    // only start a resize loop.
+    if width.abs_diff(size.width) > 1 || height.abs_diff(size.height) > 1 {
+        window.set_inner_size(PhysicalSize::new(width, height));
+    }
+}
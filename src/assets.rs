@@ -0,0 +1,418 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use notify::Watcher;
+
+use crate::user_textures::{Sampling, UserTexture, UserTextures};
+
+/// How long a file has to stay unchanged before it's loaded, editors write in several steps.
+const SETTLE_TIME: Duration = Duration::from_millis(100);
+/// How many lines the reload log keeps, the oldest are dropped first.
+const MAX_LOG: usize = 100;
+/// The height of the thumbnails in the "Assets" window.
+const THUMBNAIL_HEIGHT: f32 = 32.0;
+
+/// The kinds of files the registry loads, by their extension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Image,
+    Font,
+    Shader,
+}
+
+impl Kind {
+    pub fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" | "jpg" | "jpeg" => Some(Self::Image),
+            "ttf" | "otf" | "ttc" => Some(Self::Font),
+            "wgsl" => Some(Self::Shader),
+            _ => None,
+        }
+    }
+}
+
+/// What a file was loaded into.
+enum Handle {
+    Texture {
+        texture: UserTexture,
+        size: [usize; 2],
+    },
+    /// A font family named like the path of the file.
+    Font(Vec<u8>),
+    /// Compiled once to check it. The renderers that use it build their pipelines from the
+    /// source.
+    Shader(String),
+}
+
+struct Entry {
+    kind: Kind,
+    handle: Result<Handle, String>,
+    /// Changes with every load, for the users of an asset to notice that it was reloaded.
+    generation: u64,
+}
+
+/// A line of the reload log.
+struct LogLine {
+    time: Instant,
+    text: String,
+    failed: bool,
+}
+
+type Changes = mpsc::Receiver<notify::Result<notify::Event>>;
+
+/// The central registry of the files in the assets directory. Maps their paths to the user
+/// textures, font families and shaders they were loaded into, and loads them again when they
+/// change on disk.
+#[derive(Default)]
+pub struct Assets {
+    /// `None` until `watch` found the directory.
+    dir: Option<PathBuf>,
+    watcher: Option<(notify::RecommendedWatcher, Changes)>,
+    /// The files that changed and when they did last, until they settled.
+    pending: HashMap<PathBuf, Instant>,
+    /// By the path relative to the directory.
+    entries: BTreeMap<PathBuf, Entry>,
+    generation: u64,
+    /// The fonts of the example without the ones of the assets.
+    base_fonts: Option<egui::FontDefinitions>,
+    log: VecDeque<LogLine>,
+    error: Option<String>,
+}
+
+impl Assets {
+    /// Loads the files in `dir` and its subdirectories in the next frame, and again whenever
+    /// they change. A missing directory just means there are no assets.
+    pub fn watch(&mut self, ctx: &egui::Context, dir: &Path) {
+        if !dir.is_dir() {
+            self.error = Some(format!("There is no directory {}", dir.display()));
+            return;
+        }
+        // Events name the files by the absolute path.
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_owned());
+        match start_watcher(ctx, &dir) {
+            Ok(watcher) => self.watcher = Some(watcher),
+            // The files are still loaded once.
+            Err(err) => {
+                eprintln!("{}", err);
+                self.error = Some(err);
+            }
+        }
+        let mut files = Vec::new();
+        list_files(&dir, &mut files);
+        let settled = Instant::now() - SETTLE_TIME;
+        self.pending
+            .extend(files.into_iter().map(|path| (path, settled)));
+        self.dir = Some(dir);
+    }
+
+    /// Loads the files that changed and settled since the last frame. Images go into
+    /// `textures`, fonts into the fonts of `ctx`.
+    pub fn update(&mut self, ctx: &egui::Context, textures: &mut UserTextures) {
+        let dir = match &self.dir {
+            Some(dir) => dir.clone(),
+            None => return,
+        };
+        if let Some((_, changes)) = &self.watcher {
+            for event in changes.try_iter().flatten() {
+                if matches!(event.kind, notify::EventKind::Access(_)) {
+                    continue;
+                }
+                let files = event
+                    .paths
+                    .into_iter()
+                    .filter(|path| Kind::of(path).is_some());
+                self.pending
+                    .extend(files.map(|path| (path, Instant::now())));
+            }
+        }
+        let now = Instant::now();
+        let (settled, waiting): (Vec<_>, Vec<_>) = self
+            .pending
+            .iter()
+            .map(|(path, changed)| (path.clone(), *changed + SETTLE_TIME))
+            .partition(|(_, settles)| *settles <= now);
+        if let Some(settles) = waiting.iter().map(|(_, settles)| *settles).min() {
+            ctx.request_repaint_after(settles - now);
+        }
+        let mut fonts_changed = false;
+        for (path, _) in settled {
+            self.pending.remove(&path);
+            let kind = match Kind::of(&path) {
+                Some(kind) => kind,
+                None => continue,
+            };
+            let name = path.strip_prefix(&dir).unwrap_or(&path).to_owned();
+            fonts_changed |= kind == Kind::Font;
+            let old = self.entries.remove(&name);
+            let reloaded = old.is_some();
+            if let Some(Entry {
+                handle: Ok(Handle::Texture { texture, .. }),
+                ..
+            }) = old
+            {
+                textures.free([texture]);
+            }
+            if !path.is_file() {
+                if reloaded {
+                    self.log(format!("Removed {}", name.display()), false);
+                }
+                continue;
+            }
+            let handle = load(&path, kind, textures);
+            match &handle {
+                Ok(_) if reloaded => self.log(format!("Reloaded {}", name.display()), false),
+                Ok(_) => self.log(format!("Loaded {}", name.display()), false),
+                Err(err) => self.log(format!("Can't load {}: {}", name.display(), err), true),
+            }
+            self.generation += 1;
+            let entry = Entry {
+                kind,
+                handle,
+                generation: self.generation,
+            };
+            self.entries.insert(name, entry);
+        }
+        if fonts_changed {
+            self.set_fonts(ctx);
+        }
+    }
+
+    /// The source of the shader at `path`, relative to the assets directory, and its
+    /// generation. `None` when there is no such shader or it doesn't compile.
+    pub fn shader(&self, path: &str) -> Option<(u64, &str)> {
+        match self.entries.get(Path::new(path))? {
+            Entry {
+                handle: Ok(Handle::Shader(source)),
+                generation,
+                ..
+            } => Some((*generation, source)),
+            _ => None,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        egui::Window::new("📦 Assets")
+            .open(open)
+            .default_size([420.0, 360.0])
+            .show(ctx, |ui| self.ui(ui));
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        match (&self.dir, &self.error) {
+            (_, Some(err)) => {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            (Some(dir), None) => {
+                ui.label(format!("Watching {}", dir.display()));
+            }
+            (None, None) => {
+                ui.weak("Not watching a directory");
+            }
+        }
+        ui.separator();
+        egui::TopBottomPanel::bottom("assets_log")
+            .resizable(true)
+            .default_height(120.0)
+            .show_inside(ui, |ui| self.log_ui(ui));
+        if self.entries.is_empty() {
+            ui.weak("No images, fonts or shaders");
+            return;
+        }
+        let families = ui.fonts().families();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("assets_entries")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (name, entry) in &self.entries {
+                        ui.monospace(name.display().to_string());
+                        ui.label(format!("{:?}", entry.kind));
+                        match &entry.handle {
+                            Ok(Handle::Texture { texture, size }) => {
+                                let [width, height] = size.map(|side| side as f32);
+                                let scale = THUMBNAIL_HEIGHT / height.max(1.0);
+                                ui.image(texture.id, [width * scale, THUMBNAIL_HEIGHT])
+                                    .on_hover_text(format!("{} × {}", size[0], size[1]));
+                            }
+                            Ok(Handle::Font(data)) => {
+                                let family = font_family(name);
+                                // The family only exists from the frame after it was added.
+                                if families.contains(&family) {
+                                    ui.label(
+                                        egui::RichText::new("The quick brown fox")
+                                            .font(egui::FontId::new(16.0, family)),
+                                    )
+                                    .on_hover_text(
+                                        crate::system_monitor::format_bytes(data.len() as u64),
+                                    );
+                                } else {
+                                    ui.spinner();
+                                }
+                            }
+                            Ok(Handle::Shader(source)) => {
+                                ui.label(format!("{} lines", source.lines().count()));
+                            }
+                            Err(err) => {
+                                ui.colored_label(ui.visuals().error_fg_color, err);
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
+    fn log_ui(&self, ui: &mut egui::Ui) {
+        egui::ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                // The newest first.
+                for line in self.log.iter().rev() {
+                    let age = line.time.elapsed().as_secs();
+                    let text = format!("{:>4} s ago  {}", age, line.text);
+                    match line.failed {
+                        true => ui.colored_label(ui.visuals().error_fg_color, text),
+                        false => ui.monospace(text),
+                    };
+                }
+            });
+    }
+
+    /// Logs `text` to the console and the "Assets" window.
+    fn log(&mut self, text: String, failed: bool) {
+        match failed {
+            true => eprintln!("{}", text),
+            false => println!("{}", text),
+        }
+        if self.log.len() == MAX_LOG {
+            self.log.pop_front();
+        }
+        self.log.push_back(LogLine {
+            time: Instant::now(),
+            text,
+            failed,
+        });
+    }
+
+    /// Replaces the fonts of `ctx` with the ones of the example plus a family for each font
+    /// asset, which falls back to the proportional fonts.
+    fn set_fonts(&mut self, ctx: &egui::Context) {
+        let mut fonts = self
+            .base_fonts
+            .get_or_insert_with(|| ctx.fonts().lock().fonts.definitions().clone())
+            .clone();
+        let fallbacks = fonts
+            .families
+            .get(&egui::FontFamily::Proportional)
+            .cloned()
+            .unwrap_or_default();
+        for (name, entry) in &self.entries {
+            if let Ok(Handle::Font(data)) = &entry.handle {
+                let key = format!("asset:{}", name.display());
+                fonts
+                    .font_data
+                    .insert(key.clone(), egui::FontData::from_owned(data.clone()));
+                let family = std::iter::once(key).chain(fallbacks.iter().cloned());
+                fonts.families.insert(font_family(name), family.collect());
+            }
+        }
+        ctx.set_fonts(fonts);
+    }
+}
+
+/// The font family of the font asset at `path`.
+fn font_family(path: &Path) -> egui::FontFamily {
+    egui::FontFamily::Name(path.display().to_string().into())
+}
+
+/// Reads the font at `path`. egui panics on data that doesn't parse, so at least the signature
+/// has to fit.
+pub fn read_font(path: &Path) -> Result<Vec<u8>, String> {
+    let data = std::fs::read(path).map_err(|err| err.to_string())?;
+    match data.get(..4) {
+        Some([0, 1, 0, 0] | b"true" | b"OTTO" | b"ttcf") => Ok(data),
+        _ => Err("not a TrueType or OpenType font".to_owned()),
+    }
+}
+
+fn load(path: &Path, kind: Kind, textures: &mut UserTextures) -> Result<Handle, String> {
+    match kind {
+        Kind::Image => {
+            let image = image::open(path).map_err(|err| err.to_string())?.to_rgba8();
+            let size = [image.width() as usize, image.height() as usize];
+            let image = egui::ColorImage::from_rgba_unmultiplied(size, &image);
+            let label = path.display().to_string();
+            let texture =
+                crate::user_textures::upload(textures.device, textures.queue, &label, &image);
+            let bytes = (size[0] * size[1] * 4) as u64;
+            let texture = textures.register(&texture, bytes, Sampling::default());
+            Ok(Handle::Texture { texture, size })
+        }
+        Kind::Font => read_font(path).map(Handle::Font),
+        Kind::Shader => {
+            let source = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+            let device = textures.device;
+            // Keeps a shader that doesn't compile from ending up in the error handler of wgpu.
+            device.push_error_scope(wgpu::ErrorFilter::Validation);
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(&path.display().to_string()),
+                source: wgpu::ShaderSource::Wgsl(source.as_str().into()),
+            });
+            match pollster::block_on(device.pop_error_scope()) {
+                Some(err) => Err(err.to_string()),
+                None => Ok(Handle::Shader(source)),
+            }
+        }
+    }
+}
+
+/// Watches `dir` and its subdirectories. The changes wake the event loop.
+fn start_watcher(
+    ctx: &egui::Context,
+    dir: &Path,
+) -> Result<(notify::RecommendedWatcher, Changes), String> {
+    let (sender, changes) = mpsc::channel();
+    let ctx = ctx.clone();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = sender.send(event);
+        ctx.request_repaint();
+    })
+    .map_err(|err| err.to_string())?;
+    watcher
+        .watch(dir, notify::RecursiveMode::Recursive)
+        .map_err(|err| format!("Can't watch {}: {}", dir.display(), err))?;
+    Ok((watcher, changes))
+}
+
+/// Collects the files in `dir` and its subdirectories that are assets.
+fn list_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => return eprintln!("Can't list {}: {}", dir.display(), err),
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            list_files(&path, files);
+        } else if Kind::of(&path).is_some() {
+            files.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds_follow_the_extension() {
+        assert_eq!(Kind::of(Path::new("ui/logo.PNG")), Some(Kind::Image));
+        assert_eq!(Kind::of(Path::new("fonts/Inter.otf")), Some(Kind::Font));
+        assert_eq!(Kind::of(Path::new("scene.wgsl")), Some(Kind::Shader));
+        assert_eq!(Kind::of(Path::new("notes.txt")), None);
+        assert_eq!(Kind::of(Path::new("Makefile")), None);
+    }
+}
@@ -0,0 +1,44 @@
+// The animated background of the attract screen, a plasma of moving sine waves.
+
+struct Uniforms {
+    resolution: vec2<f32>,
+    time: f32,
+    // 1.0 if the colors have to be sRGB-encoded, because the surface doesn't encode on its own.
+    encoded: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+// A triangle that covers the whole target.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+fn to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, color <= vec3<f32>(0.0031308));
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    // Scaled by the height, so that the waves keep their shape in wide windows.
+    let p = (position.xy - 0.5 * uniforms.resolution) / uniforms.resolution.y * 6.0;
+    let t = uniforms.time * 0.5;
+    var v = sin(p.x + t);
+    v = v + sin(0.5 * (p.y + t));
+    v = v + sin(0.5 * (p.x + p.y + t));
+    let c = p + 0.5 * vec2<f32>(sin(t / 3.0), cos(t / 2.0)) * 6.0;
+    v = v + sin(sqrt(dot(c, c) + 1.0) + t);
+    let phase = v * 3.14159;
+    var color = 0.5 + 0.5 * vec3<f32>(sin(phase), sin(phase + 2.094), sin(phase + 4.189));
+    // Dark enough for a screen that runs all day.
+    color = color * color * 0.6;
+    if (uniforms.encoded > 0.5) {
+        color = to_srgb(color);
+    }
+    return vec4<f32>(color, 1.0);
+}
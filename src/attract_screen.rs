@@ -0,0 +1,97 @@
+use crate::render_graph::Pass;
+
+/// The animated screen that replaces the UI once the user was idle for the time of the settings,
+/// like a screensaver. It's drawn by a shader of its own, the UI isn't run meanwhile.
+pub struct AttractScreen {
+    pipeline: wgpu::RenderPipeline,
+    uniforms: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    /// Whether the surface stores sRGB without encoding it on its own.
+    encoded: bool,
+}
+
+impl AttractScreen {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("attract.wgsl"));
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("attract_pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(format.into())],
+            }),
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+            multiview: None,
+        });
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("attract_uniforms"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("attract_bind_group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniforms.as_entire_binding(),
+            }],
+        });
+        Self {
+            pipeline,
+            uniforms,
+            bind_group,
+            encoded: !format.describe().srgb,
+        }
+    }
+
+    /// The pass that draws the screen at `time` seconds into `target`, of `size` physical pixels.
+    pub fn pass<'a>(
+        &'a self,
+        queue: &wgpu::Queue,
+        size: [u32; 2],
+        time: f64,
+        target: &'static str,
+    ) -> Pass<'a> {
+        // Wrapped, so that the single precision of the shader doesn't lose the animation.
+        let time = (time % 3600.0) as f32;
+        let uniforms = [
+            size[0] as f32,
+            size[1] as f32,
+            time,
+            if self.encoded { 1.0 } else { 0.0 },
+        ];
+        let bytes: Vec<u8> = uniforms
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        queue.write_buffer(&self.uniforms, 0, &bytes);
+
+        Pass::new("attract", move |encoder, resources| {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("attract"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: resources.view(target),
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        })
+        .writes(target)
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+
+/// Number of samples used for one FFT. Gives 1024 frequency bins.
+const FFT_SIZE: usize = 2048;
+/// How many new samples have to arrive before the next FFT is calculated.
+const HOP_SIZE: usize = FFT_SIZE / 4;
+/// Number of spectra kept for the spectrogram (columns of the spectrogram texture).
+const HISTORY_LENGTH: usize = 256;
+/// Number of rows of the spectrogram texture. The spectrum bins are averaged down to this.
+const SPECTROGRAM_ROWS: usize = 256;
+/// Lower bound of the displayed magnitude range.
+const MIN_DB: f32 = -100.0;
+
+/// The analyzed data shared between the FFT worker thread and the UI.
+#[derive(Default)]
+struct Spectrum {
+    sample_rate: u32,
+    /// Magnitudes of the latest FFT in dBFS.
+    magnitudes: Vec<f32>,
+    /// Downsampled magnitudes of the last `HISTORY_LENGTH` FFTs, oldest first.
+    history: VecDeque<Vec<f32>>,
+    /// Set by the worker whenever `history` changed, so that the UI only re-uploads the
+    /// spectrogram texture when needed.
+    dirty: bool,
+}
+
+/// A running capture: the cpal stream and the thread that analyzes its samples.
+struct Capture {
+    // Dropping the stream stops the capture and closes the channel to the worker,
+    // which then exits on its own.
+    _stream: cpal::Stream,
+    device_name: String,
+}
+
+/// Captures microphone input and shows a real-time spectrum and spectrogram.
+///
+/// The audio callback only forwards the samples to a worker thread, which calculates the FFT and
+/// asks egui for a repaint. This way neither the audio thread nor the UI thread is blocked by the
+/// analysis.
+pub struct AudioVisualizer {
+    host: cpal::Host,
+    device_names: Vec<String>,
+    selected_device: usize,
+    /// The gain as `f32` bits, so that it can be changed while the worker is running.
+    gain: Arc<AtomicU32>,
+    spectrum: Arc<Mutex<Spectrum>>,
+    capture: Option<Capture>,
+    spectrogram: Option<egui::TextureHandle>,
+    error: Option<String>,
+}
+
+impl Default for AudioVisualizer {
+    fn default() -> Self {
+        let mut visualizer = Self {
+            host: cpal::default_host(),
+            device_names: Vec::new(),
+            selected_device: 0,
+            gain: Arc::new(AtomicU32::new(1.0f32.to_bits())),
+            spectrum: Default::default(),
+            capture: None,
+            spectrogram: None,
+            error: None,
+        };
+        visualizer.refresh_devices();
+        visualizer
+    }
+}
+
+impl AudioVisualizer {
+    fn refresh_devices(&mut self) {
+        let devices = match self.host.input_devices() {
+            Ok(devices) => devices,
+            Err(err) => {
+                self.error = Some(format!("Can't enumerate input devices: {}", err));
+                return;
+            }
+        };
+        self.device_names = devices.filter_map(|device| device.name().ok()).collect();
+
+        // Preselect the default device of the host.
+        let default_name = self
+            .host
+            .default_input_device()
+            .and_then(|device| device.name().ok());
+        self.selected_device = default_name
+            .and_then(|name| self.device_names.iter().position(|n| *n == name))
+            .unwrap_or(0);
+    }
+
+    fn start(&mut self, ctx: &egui::Context) {
+        self.stop();
+
+        let name = match self.device_names.get(self.selected_device) {
+            Some(name) => name.clone(),
+            None => {
+                self.error = Some("No input device available".to_owned());
+                return;
+            }
+        };
+
+        match self.start_capture(&name, ctx.clone()) {
+            Ok(capture) => {
+                self.capture = Some(capture);
+                self.error = None;
+            }
+            Err(err) => self.error = Some(err),
+        }
+    }
+
+    fn start_capture(&self, name: &str, ctx: egui::Context) -> Result<Capture, String> {
+        let device = self
+            .host
+            .input_devices()
+            .map_err(|err| err.to_string())?
+            .find(|device| device.name().map(|n| n == name).unwrap_or(false))
+            .ok_or_else(|| format!("Input device {:?} disappeared", name))?;
+
+        let supported_config = device
+            .default_input_config()
+            .map_err(|err| err.to_string())?;
+        let config = supported_config.config();
+
+        let (sender, receiver) = mpsc::channel();
+        let stream = match supported_config.sample_format() {
+            cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config, sender),
+            cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config, sender),
+            cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config, sender),
+        }
+        .map_err(|err| err.to_string())?;
+        stream.play().map_err(|err| err.to_string())?;
+
+        {
+            let mut spectrum = self.spectrum.lock().unwrap();
+            *spectrum = Spectrum {
+                sample_rate: config.sample_rate.0,
+                ..Default::default()
+            };
+        }
+
+        let spectrum = self.spectrum.clone();
+        let gain = self.gain.clone();
+        thread::Builder::new()
+            .name("audio-fft".to_owned())
+            .spawn(move || analyze(receiver, spectrum, gain, ctx))
+            .map_err(|err| err.to_string())?;
+
+        Ok(Capture {
+            _stream: stream,
+            device_name: name.to_owned(),
+        })
+    }
+
+    fn stop(&mut self) {
+        self.capture = None;
+    }
+
+    /// Shows the visualizer window.
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        egui::Window::new("🎤 Audio visualizer")
+            .open(open)
+            .default_width(520.0)
+            .show(ctx, |ui| self.ui(ui));
+
+        // Don't keep the microphone open while nobody is looking.
+        if !*open {
+            self.stop();
+        }
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let selected_text = self
+                .device_names
+                .get(self.selected_device)
+                .map(String::as_str)
+                .unwrap_or("<none>");
+            egui::ComboBox::from_label("Device")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for (index, name) in self.device_names.iter().enumerate() {
+                        ui.selectable_value(&mut self.selected_device, index, name);
+                    }
+                });
+            if ui.button("🔄").on_hover_text("Refresh devices").clicked() {
+                self.refresh_devices();
+            }
+        });
+
+        let mut gain = f32::from_bits(self.gain.load(Ordering::Relaxed));
+        if ui
+            .add(
+                egui::Slider::new(&mut gain, 0.1..=100.0)
+                    .logarithmic(true)
+                    .text("Gain"),
+            )
+            .changed()
+        {
+            self.gain.store(gain.to_bits(), Ordering::Relaxed);
+        }
+
+        let mut toggle_capture = false;
+        ui.horizontal(|ui| {
+            if let Some(capture) = &self.capture {
+                ui.label(format!("Capturing from {}", capture.device_name));
+                toggle_capture = ui.button("⏹ Stop").clicked();
+            } else {
+                toggle_capture = ui.button("▶ Start").clicked();
+            }
+        });
+        if toggle_capture {
+            if self.capture.is_some() {
+                self.stop();
+            } else {
+                self.start(&ui.ctx().clone());
+            }
+        }
+
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
+        ui.separator();
+
+        let (sample_rate, line, history) = {
+            let mut spectrum = self.spectrum.lock().unwrap();
+            let bin_width = spectrum.sample_rate as f64 / FFT_SIZE as f64;
+            let line: egui::plot::PlotPoints = spectrum
+                .magnitudes
+                .iter()
+                .enumerate()
+                .map(|(bin, db)| [bin as f64 * bin_width, *db as f64])
+                .collect();
+            let history = if spectrum.dirty {
+                spectrum.dirty = false;
+                Some(spectrogram_image(&spectrum.history))
+            } else {
+                None
+            };
+            (spectrum.sample_rate, line, history)
+        };
+
+        ui.label("Spectrum");
+        egui::plot::Plot::new("audio_spectrum")
+            .height(180.0)
+            .include_y(MIN_DB)
+            .include_y(0.0)
+            .include_x(0.0)
+            .include_x(sample_rate as f64 / 2.0)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .show(ui, |plot_ui| plot_ui.line(egui::plot::Line::new(line)));
+
+        ui.label("Spectrogram");
+        if let Some(image) = history {
+            match &mut self.spectrogram {
+                Some(texture) => texture.set(image, egui::TextureFilter::Linear),
+                None => {
+                    self.spectrogram = Some(ui.ctx().load_texture(
+                        "audio_spectrogram",
+                        image,
+                        egui::TextureFilter::Linear,
+                    ))
+                }
+            }
+        }
+        if let Some(texture) = &self.spectrogram {
+            let width = ui.available_width();
+            ui.image(texture, [width, 180.0]);
+        }
+    }
+}
+
+/// Builds an input stream that down-mixes all channels to mono and sends them to the worker.
+fn build_stream<T: cpal::Sample>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    sender: mpsc::Sender<Vec<f32>>,
+) -> Result<cpal::Stream, cpal::BuildStreamError> {
+    let channels = config.channels as usize;
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            let mono = data
+                .chunks(channels)
+                .map(|frame| frame.iter().map(|s| s.to_f32()).sum::<f32>() / channels as f32)
+                .collect();
+            // The worker is gone when the capture is being shut down.
+            sender.send(mono).ok();
+        },
+        |err| eprintln!("Audio input stream error: {}", err),
+    )
+}
+
+/// The worker thread: Collects the samples, calculates the spectrum and requests a repaint.
+fn analyze(
+    receiver: mpsc::Receiver<Vec<f32>>,
+    spectrum: Arc<Mutex<Spectrum>>,
+    gain: Arc<AtomicU32>,
+    ctx: egui::Context,
+) {
+    let fft = FftPlanner::new().plan_fft_forward(FFT_SIZE);
+    let window: Vec<f32> = (0..FFT_SIZE)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / (FFT_SIZE - 1) as f32).cos())
+        .collect();
+
+    let mut samples: VecDeque<f32> = VecDeque::with_capacity(FFT_SIZE * 2);
+    let mut buffer = vec![Complex::new(0.0, 0.0); FFT_SIZE];
+    let mut new_samples = 0;
+
+    // Ends as soon as the stream is dropped.
+    while let Ok(chunk) = receiver.recv() {
+        new_samples += chunk.len();
+        samples.extend(chunk);
+        while samples.len() > FFT_SIZE {
+            samples.pop_front();
+        }
+        if samples.len() < FFT_SIZE || new_samples < HOP_SIZE {
+            continue;
+        }
+        new_samples = 0;
+
+        let gain = f32::from_bits(gain.load(Ordering::Relaxed));
+        for ((value, sample), weight) in buffer.iter_mut().zip(samples.iter()).zip(&window) {
+            *value = Complex::new(sample * gain * weight, 0.0);
+        }
+        fft.process(&mut buffer);
+
+        // Only the first half of the bins is meaningful for a real signal.
+        let normalization = 2.0 / FFT_SIZE as f32;
+        let magnitudes: Vec<f32> = buffer[..FFT_SIZE / 2]
+            .iter()
+            .map(|c| (20.0 * (c.norm() * normalization).max(1e-10).log10()).max(MIN_DB))
+            .collect();
+
+        let bins_per_row = magnitudes.len() / SPECTROGRAM_ROWS;
+        let column = magnitudes
+            .chunks(bins_per_row)
+            .map(|bins| bins.iter().sum::<f32>() / bins.len() as f32)
+            .collect();
+
+        {
+            let mut spectrum = spectrum.lock().unwrap();
+            spectrum.magnitudes = magnitudes;
+            if spectrum.history.len() == HISTORY_LENGTH {
+                spectrum.history.pop_front();
+            }
+            spectrum.history.push_back(column);
+            spectrum.dirty = true;
+        }
+
+        // Wakes up the event loop through the repaint callback.
+        ctx.request_repaint();
+    }
+}
+
+/// Renders the spectrum history into an image: time runs left to right, low frequencies are at
+/// the bottom.
+fn spectrogram_image(history: &VecDeque<Vec<f32>>) -> egui::ColorImage {
+    let mut image = egui::ColorImage::new([HISTORY_LENGTH, SPECTROGRAM_ROWS], egui::Color32::BLACK);
+    let offset = HISTORY_LENGTH - history.len();
+    for (x, column) in history.iter().enumerate() {
+        for (row, db) in column.iter().enumerate() {
+            let intensity = 1.0 - db / MIN_DB;
+            let y = SPECTROGRAM_ROWS - 1 - row;
+            image.pixels[y * HISTORY_LENGTH + x + offset] = heat_color(intensity);
+        }
+    }
+    image
+}
+
+/// Maps an intensity in `0.0..=1.0` onto a black → blue → yellow → white color ramp.
+fn heat_color(intensity: f32) -> egui::Color32 {
+    let t = intensity.clamp(0.0, 1.0);
+    let r = (t * 3.0 - 1.0).clamp(0.0, 1.0);
+    let g = (t * 3.0 - 1.5).clamp(0.0, 1.0);
+    let b = if t < 0.5 {
+        t * 2.0
+    } else {
+        2.0 - t * 2.0 + (t * 3.0 - 2.0).max(0.0)
+    };
+    egui::Color32::from_rgb(
+        (r * 255.0) as u8,
+        (g * 255.0) as u8,
+        (b.clamp(0.0, 1.0) * 255.0) as u8,
+    )
+}
@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Where the time of the frames comes from. egui animates with it and the HUD shows it, so a
+/// clock that doesn't follow the wall clock makes every frame reproducible.
+pub trait Clock {
+    /// Moves on to the next frame and returns its time, in seconds since the start.
+    fn tick(&mut self) -> f64;
+
+    /// The time of the current frame, without moving on, for what's drawn along with it.
+    fn now(&self) -> f64;
+
+    /// The time of day of the current frame, in seconds since midnight.
+    fn seconds_since_midnight(&self) -> f64;
+}
+
+/// The wall clock.
+pub struct RealClock {
+    start: Instant,
+}
+
+impl Default for RealClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for RealClock {
+    fn tick(&mut self) -> f64 {
+        self.start.elapsed().as_secs_f64()
+    }
+
+    fn now(&self) -> f64 {
+        self.start.elapsed().as_secs_f64()
+    }
+
+    fn seconds_since_midnight(&self) -> f64 {
+        crate::seconds_since_midnight()
+    }
+}
+
+/// Moves on by the same step every frame, however long the frames take. The day starts at
+/// midnight.
+pub struct FixedStepClock {
+    step: f64,
+    frames: u64,
+}
+
+impl FixedStepClock {
+    pub fn new(step: f64) -> Self {
+        Self { step, frames: 0 }
+    }
+
+    fn time(&self) -> f64 {
+        self.frames.saturating_sub(1) as f64 * self.step
+    }
+}
+
+impl Clock for FixedStepClock {
+    fn tick(&mut self) -> f64 {
+        self.frames += 1;
+        self.time()
+    }
+
+    fn now(&self) -> f64 {
+        self.time()
+    }
+
+    fn seconds_since_midnight(&self) -> f64 {
+        self.time()
+    }
+}
+
+/// The frame times of a recording, see `Replay::clock`. Stays at the last one once the recording
+/// is over. The day starts at midnight, the recordings don't keep the time of day.
+pub struct ReplayClock {
+    times: VecDeque<f64>,
+    time: f64,
+}
+
+impl ReplayClock {
+    pub fn new(times: VecDeque<f64>) -> Self {
+        Self { times, time: 0.0 }
+    }
+}
+
+impl Clock for ReplayClock {
+    fn tick(&mut self) -> f64 {
+        if let Some(time) = self.times.pop_front() {
+            self.time = time;
+        }
+        self.time
+    }
+
+    fn now(&self) -> f64 {
+        self.time
+    }
+
+    fn seconds_since_midnight(&self) -> f64 {
+        self.time
+    }
+}
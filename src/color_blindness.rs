@@ -0,0 +1,156 @@
+use crate::render_graph::{AttachmentDesc, Pass};
+
+/// The color vision deficiencies the filter simulates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Simulation {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+impl Simulation {
+    pub const ALL: [Simulation; 3] = [
+        Simulation::Protanopia,
+        Simulation::Deuteranopia,
+        Simulation::Tritanopia,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Simulation::Protanopia => "Protanopia (no red cones)",
+            Simulation::Deuteranopia => "Deuteranopia (no green cones)",
+            Simulation::Tritanopia => "Tritanopia (no blue cones)",
+        }
+    }
+
+    /// The matrix for linear RGB at full severity, from Machado, Oliveira and Fernandes, "A
+    /// Physiologically-based Model for Simulation of Color Vision Deficiency" (2009).
+    fn matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            Simulation::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Simulation::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Simulation::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+}
+
+/// The attachment the UI is composited into before the filter writes it to the surface.
+pub const COMPOSITED: &str = "composited";
+
+/// A post-process pass that draws the composited frame through a color-blindness simulation.
+pub struct ColorBlindnessFilter {
+    pipeline: wgpu::RenderPipeline,
+    uniforms: wgpu::Buffer,
+    /// Whether the surface stores sRGB without encoding it on its own.
+    encoded: bool,
+}
+
+impl ColorBlindnessFilter {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("color_blindness.wgsl"));
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("color_blindness_pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(format.into())],
+            }),
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+            multiview: None,
+        });
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("color_blindness_uniforms"),
+            size: 64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            pipeline,
+            uniforms,
+            encoded: !format.describe().srgb,
+        }
+    }
+
+    /// The attachment to composite into, in the format of the surface.
+    pub fn attachment(format: wgpu::TextureFormat) -> AttachmentDesc {
+        AttachmentDesc {
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        }
+    }
+
+    /// The pass that reads [`COMPOSITED`] and writes `target` through `simulation`.
+    pub fn pass<'a>(
+        &'a self,
+        device: &'a wgpu::Device,
+        queue: &wgpu::Queue,
+        simulation: Simulation,
+        target: &'static str,
+    ) -> Pass<'a> {
+        let mut uniforms: Vec<f32> = simulation
+            .matrix()
+            .iter()
+            .flat_map(|row| [row[0], row[1], row[2], 0.0])
+            .collect();
+        uniforms.extend([if self.encoded { 1.0 } else { 0.0 }, 0.0, 0.0, 0.0]);
+        let bytes: Vec<u8> = uniforms
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        queue.write_buffer(&self.uniforms, 0, &bytes);
+
+        Pass::new("color_blindness", move |encoder, resources| {
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("color_blindness_bind_group"),
+                layout: &self.pipeline.get_bind_group_layout(0),
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: self.uniforms.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(resources.view(COMPOSITED)),
+                    },
+                ],
+            });
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("color_blindness"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: resources.view(target),
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        })
+        .reads(COMPOSITED)
+        .writes(target)
+    }
+}
@@ -0,0 +1,50 @@
+// Simulates a color vision deficiency on the composited frame, in linear RGB.
+
+struct Simulation {
+    // The rows of the matrix, padded to vec4.
+    rows: array<vec4<f32>, 3>,
+    // 1.0 if the frame holds sRGB-encoded values, because the surface doesn't encode on its own.
+    encoded: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> simulation: Simulation;
+@group(0) @binding(1)
+var source: texture_2d<f32>;
+
+// A triangle that covers the whole target.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+fn to_linear(color: vec3<f32>) -> vec3<f32> {
+    let low = color / 12.92;
+    let high = pow((color + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, color <= vec3<f32>(0.04045));
+}
+
+fn to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, color <= vec3<f32>(0.0031308));
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let texel = textureLoad(source, vec2<i32>(position.xy), 0);
+    var color = texel.rgb;
+    if (simulation.encoded > 0.5) {
+        color = to_linear(color);
+    }
+    color = clamp(vec3<f32>(
+        dot(simulation.rows[0].xyz, color),
+        dot(simulation.rows[1].xyz, color),
+        dot(simulation.rows[2].xyz, color)
+    ), vec3<f32>(0.0), vec3<f32>(1.0));
+    if (simulation.encoded > 0.5) {
+        color = to_srgb(color);
+    }
+    return vec4<f32>(color, texel.a);
+}
@@ -0,0 +1,199 @@
+use crate::readback::Region;
+
+/// A color picker with an eyedropper that reads the pixel under the cursor back from the
+/// rendered frame, and a palette of picked colors.
+///
+/// The sampled value is exactly what ended up in the frame, which makes this useful to verify
+/// the color-space handling of the render pipeline.
+#[derive(Default)]
+pub struct ColorPicker {
+    picking: bool,
+    /// The pixel to read back from the current frame.
+    sample_position: Option<[u32; 2]>,
+    /// The color under the cursor, read back from the previous frame.
+    hovered: Option<egui::Color32>,
+    selected: Option<egui::Color32>,
+}
+
+impl ColorPicker {
+    /// The 1×1 region that should be read back from the current frame while picking.
+    pub fn sample_region(&self) -> Option<Region> {
+        self.sample_position.map(|origin| Region {
+            origin,
+            size: [1, 1],
+        })
+    }
+
+    /// Receives the RGBA pixel read back for `sample_region`.
+    pub fn set_sample(&mut self, pixel: &[u8]) {
+        if let [r, g, b, ..] = *pixel {
+            self.hovered = Some(egui::Color32::from_rgb(r, g, b));
+        }
+    }
+
+    /// Shows the picker window and, while picking, the eyedropper.
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool, palette: &mut Vec<[u8; 3]>) {
+        egui::Window::new("🎨 Color picker")
+            .open(open)
+            .default_width(260.0)
+            .show(ctx, |ui| self.ui(ui, palette));
+
+        self.sample_position = None;
+        if self.picking && *open {
+            self.eyedropper(ctx, palette);
+        } else {
+            self.picking = false;
+        }
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, palette: &mut Vec<[u8; 3]>) {
+        if ui
+            .button("💉 Pick from screen")
+            .on_hover_text("Click anywhere to pick the color of that pixel. Esc cancels.")
+            .clicked()
+        {
+            self.picking = true;
+            self.hovered = None;
+        }
+
+        if let Some(color) = self.selected {
+            ui.separator();
+            ui.horizontal(|ui| {
+                let (rect, _) =
+                    ui.allocate_exact_size(egui::vec2(48.0, 48.0), egui::Sense::hover());
+                ui.painter().rect_filled(rect, 4.0, color);
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.monospace(hex(color));
+                        if ui.small_button("📋").on_hover_text("Copy as hex").clicked() {
+                            ui.output().copied_text = hex(color);
+                        }
+                    });
+                    ui.monospace(format!(
+                        "sRGB   {:3} {:3} {:3}",
+                        color.r(),
+                        color.g(),
+                        color.b()
+                    ));
+                    let srgb = [color.r(), color.g(), color.b()].map(|c| c as f32 / 255.0);
+                    ui.monospace(format!(
+                        "sRGB   {:.3} {:.3} {:.3}",
+                        srgb[0], srgb[1], srgb[2]
+                    ));
+                    let linear = egui::Rgba::from(color);
+                    ui.monospace(format!(
+                        "linear {:.3} {:.3} {:.3}",
+                        linear.r(),
+                        linear.g(),
+                        linear.b()
+                    ));
+                });
+            });
+            if ui.button("➕ Add to palette").clicked() {
+                palette.push([color.r(), color.g(), color.b()]);
+            }
+        }
+
+        ui.separator();
+        ui.label("Palette");
+        if palette.is_empty() {
+            ui.weak("Picked colors show up here.");
+        }
+        let mut remove = None;
+        ui.horizontal_wrapped(|ui| {
+            for (index, [r, g, b]) in palette.iter().copied().enumerate() {
+                let color = egui::Color32::from_rgb(r, g, b);
+                let (rect, response) =
+                    ui.allocate_exact_size(egui::vec2(24.0, 24.0), egui::Sense::click());
+                ui.painter().rect_filled(rect, 2.0, color);
+                if response.hovered() {
+                    ui.painter()
+                        .rect_stroke(rect, 2.0, ui.visuals().widgets.hovered.fg_stroke);
+                }
+                if response.clicked() {
+                    self.selected = Some(color);
+                }
+                response.on_hover_text(hex(color)).context_menu(|ui| {
+                    if ui.button("📋 Copy as hex").clicked() {
+                        ui.output().copied_text = hex(color);
+                        ui.close_menu();
+                    }
+                    if ui.button("🗑 Remove").clicked() {
+                        remove = Some(index);
+                        ui.close_menu();
+                    }
+                });
+            }
+        });
+        if let Some(index) = remove {
+            palette.remove(index);
+        }
+        if !palette.is_empty() && ui.button("📋 Copy palette as hex").clicked() {
+            let colors: Vec<String> = palette
+                .iter()
+                .map(|&[r, g, b]| hex(egui::Color32::from_rgb(r, g, b)))
+                .collect();
+            ui.output().copied_text = colors.join("\n");
+        }
+    }
+
+    fn eyedropper(&mut self, ctx: &egui::Context, palette: &mut Vec<[u8; 3]>) {
+        if ctx.input().key_pressed(egui::Key::Escape) {
+            self.picking = false;
+            return;
+        }
+
+        let screen = ctx.input().screen_rect();
+        let pixels_per_point = ctx.pixels_per_point();
+        ctx.output().cursor_icon = egui::CursorIcon::Crosshair;
+
+        // An invisible layer above everything, so that the click doesn't reach the widgets below.
+        let response = egui::Area::new("eyedropper_overlay")
+            .order(egui::Order::Foreground)
+            .fixed_pos(screen.min)
+            .show(ctx, |ui| ui.allocate_rect(screen, egui::Sense::click()))
+            .inner;
+
+        let pointer = match response.hover_pos() {
+            Some(pointer) => pointer,
+            None => return,
+        };
+        self.sample_position = Some([
+            (pointer.x * pixels_per_point) as u32,
+            (pointer.y * pixels_per_point) as u32,
+        ]);
+        // The loupe needs to follow the cursor, even if the pointer doesn't move.
+        ctx.request_repaint();
+
+        if let Some(color) = self.hovered {
+            // Offset from the cursor, so that it doesn't cover the sampled pixel.
+            egui::Area::new("eyedropper_loupe")
+                .order(egui::Order::Tooltip)
+                .fixed_pos(pointer + egui::vec2(16.0, 16.0))
+                .interactable(false)
+                .show(ctx, |ui| {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            let (rect, _) = ui
+                                .allocate_exact_size(egui::vec2(20.0, 20.0), egui::Sense::hover());
+                            ui.painter().rect_filled(rect, 2.0, color);
+                            ui.monospace(hex(color));
+                        });
+                    });
+                });
+
+            if response.clicked() {
+                self.selected = Some(color);
+                palette.push([color.r(), color.g(), color.b()]);
+                self.picking = false;
+            }
+        }
+        if response.secondary_clicked() {
+            self.picking = false;
+        }
+    }
+}
+
+fn hex(color: egui::Color32) -> String {
+    format!("#{:02X}{:02X}{:02X}", color.r(), color.g(), color.b())
+}
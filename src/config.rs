@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::{fs, io};
+
+use serde::{Deserialize, Serialize};
+
+/// How many files the File menu remembers.
+const MAX_RECENT_FILES: usize = 10;
+
+/// Everything the example persists between runs. Stored as JSON in the config directory of the OS.
+///
+/// Missing fields fall back to their defaults, so older config files keep working.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub settings: Settings,
+    /// Maps the key of a slider to the MIDI controller that drives it.
+    pub midi_bindings: BTreeMap<String, MidiBinding>,
+    /// The colors collected with the color picker, as sRGB.
+    pub palette: Vec<[u8; 3]>,
+    /// The style edited in the settings. `None` keeps egui's default style.
+    pub style: Option<egui::Style>,
+    /// The files opened last, the newest first.
+    pub recent_files: Vec<PathBuf>,
+    /// The keyboard shortcuts the user changed.
+    pub shortcuts: crate::shortcuts::Bindings,
+    /// The named bundles of renderer settings and theme.
+    pub profiles: crate::profiles::Profiles,
+    /// The slides of the presentation. `None` shows the built-in ones.
+    pub slides: Option<String>,
+    /// Whether the tour of the UI was shown, which happens on the first launch.
+    pub onboarding_done: bool,
+}
+
+/// The settings that can be changed in the settings window.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Linear RGB color the frame is cleared with before the UI is drawn.
+    pub clear_color: [f32; 3],
+    pub window: WindowConstraints,
+    pub title_bar: TitleBar,
+    /// Ctrl+Shift+Space shows and hides the window from anywhere.
+    pub global_hotkey: bool,
+    /// Draws a frame right in the resize event, so that the content follows the border during
+    /// the live resize on Windows.
+    pub live_resize: bool,
+    /// Lays the UI out twice when the size changed, so that panels don't lag behind by a frame.
+    pub relayout_on_resize: bool,
+    pub speech: SpeechSettings,
+    pub accessibility: Accessibility,
+    /// The most frames drawn per second. `None` is only limited by the vsync of the surface.
+    pub frame_rate_limit: Option<u32>,
+    /// The most frames the GPU works on at the same time. `None` leaves it to the swapchain.
+    pub max_frames_in_flight: Option<u32>,
+    pub on_screen_keyboard: crate::on_screen_keyboard::Mode,
+    /// The seconds without input before the attract screen replaces the UI. `None` never
+    /// shows it.
+    pub attract_after: Option<u32>,
+    pub font_prewarm: FontPrewarm,
+    /// The samples per pixel of the 3D scene, 1 or 4. egui anti-aliases its shapes itself.
+    pub msaa_samples: u32,
+    /// The GPU memory the user textures may take, in MiB, before the least recently displayed
+    /// ones are evicted.
+    pub texture_budget_mib: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            clear_color: [0.0, 0.0, 0.0],
+            window: WindowConstraints::default(),
+            title_bar: TitleBar::default(),
+            global_hotkey: false,
+            live_resize: true,
+            relayout_on_resize: false,
+            speech: SpeechSettings::default(),
+            accessibility: Accessibility::default(),
+            frame_rate_limit: None,
+            max_frames_in_flight: None,
+            on_screen_keyboard: Default::default(),
+            attract_after: None,
+            font_prewarm: FontPrewarm::default(),
+            msaa_samples: 1,
+            texture_budget_mib: 1024,
+        }
+    }
+}
+
+/// Visual accessibility modes, applied on top of the style.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Accessibility {
+    /// Solid colors and thicker strokes.
+    pub high_contrast: bool,
+    /// Disables the animations of egui.
+    pub reduced_motion: bool,
+    /// The outline of the focused widget, instead of the one of the style.
+    pub focus_outline: Option<egui::Stroke>,
+}
+
+/// Text-to-speech feedback for the focused widget.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeechSettings {
+    pub enabled: bool,
+    /// Relative to the normal rate of the speech engine.
+    pub rate: f32,
+    /// The id of the voice. `None` uses the default voice of the system.
+    pub voice: Option<String>,
+}
+
+impl Default for SpeechSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rate: 1.0,
+            voice: None,
+        }
+    }
+}
+
+/// The glyphs rasterized into the font atlas at the start, see `font_atlas::prewarm`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FontPrewarm {
+    pub enabled: bool,
+    /// Code point ranges in hexadecimal, like `20-7E, A0-FF`.
+    pub ranges: String,
+}
+
+impl Default for FontPrewarm {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            // Basic Latin, Latin-1 and Latin Extended-A.
+            ranges: "20-7E, A0-17F".to_owned(),
+        }
+    }
+}
+
+/// Limits for the inner size of the window, in physical pixels.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowConstraints {
+    pub min_size: Option<[u32; 2]>,
+    pub max_size: Option<[u32; 2]>,
+    /// Width and height of the locked aspect ratio, e.g. `[16, 9]`.
+    pub aspect_ratio: Option<[u32; 2]>,
+}
+
+impl WindowConstraints {
+    /// The size closest to `size` that satisfies the constraints. The aspect ratio is corrected
+    /// over the height, since most window managers resize the width more freely.
+    pub fn constrain(&self, size: [u32; 2]) -> [u32; 2] {
+        let [mut width, mut height] = size;
+        if let Some([min_width, min_height]) = self.min_size {
+            width = width.max(min_width);
+            height = height.max(min_height);
+        }
+        if let Some([max_width, max_height]) = self.max_size {
+            width = width.min(max_width);
+            height = height.min(max_height);
+        }
+        if let Some([aspect_width, aspect_height]) = self.aspect_ratio {
+            if aspect_width > 0 && aspect_height > 0 {
+                height = (width as f64 * aspect_height as f64 / aspect_width as f64).round() as u32;
+            }
+        }
+        [width, height.max(1)]
+    }
+}
+
+/// How Windows and macOS draw the title bar.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TitleBar {
+    /// The dark title bar of Windows 10 and 11, to match the dark egui theme.
+    pub dark: bool,
+    pub backdrop: Backdrop,
+    /// A transparent title bar on macOS, with the menu bar of the example next to the window
+    /// buttons.
+    pub transparent: bool,
+}
+
+impl Default for TitleBar {
+    fn default() -> Self {
+        Self {
+            dark: true,
+            backdrop: Backdrop::None,
+            transparent: false,
+        }
+    }
+}
+
+/// The material Windows 11 draws behind the title bar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Backdrop {
+    None,
+    /// Tinted with the desktop wallpaper, for long-lived windows.
+    Mica,
+    /// Blurs what lies behind the window, for transient ones.
+    Acrylic,
+}
+
+/// `samples` if the 3D scene can draw with it, otherwise 4 for more and 1 for fewer, as wgpu
+/// only guarantees those counts on every GPU.
+pub fn supported_msaa_samples(samples: u32) -> u32 {
+    let supported = if samples >= 4 { 4 } else { 1 };
+    if samples != supported {
+        eprintln!(
+            "{} MSAA samples aren't supported, using {}",
+            samples, supported
+        );
+    }
+    supported
+}
+
+/// A MIDI control change (CC) controller, identified by its channel and controller number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MidiBinding {
+    pub channel: u8,
+    pub controller: u8,
+}
+
+impl Config {
+    /// Loads the config. Falls back to the defaults if there is none yet or it can't be read.
+    pub fn load() -> Self {
+        let path = match crate::paths::config_file() {
+            Some(path) => path,
+            None => return Self::default(),
+        };
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(err) => {
+                eprintln!("Can't read config {}: {}", path.display(), err);
+                return Self::default();
+            }
+        };
+        let mut config: Self = serde_json::from_slice(&data).unwrap_or_else(|err| {
+            eprintln!("Ignoring invalid config {}: {}", path.display(), err);
+            Self::default()
+        });
+        config.settings.msaa_samples = supported_msaa_samples(config.settings.msaa_samples);
+        for profile in &mut config.profiles.list {
+            profile.msaa_samples = supported_msaa_samples(profile.msaa_samples);
+        }
+        config
+    }
+
+    /// Writes the config to disk.
+    pub fn save(&self) -> io::Result<()> {
+        let path = crate::paths::config_file().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "no config directory available")
+        })?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_vec_pretty(self)?;
+        fs::write(path, data)
+    }
+
+    /// Moves `path` to the front of the recent files, and forgets the oldest ones.
+    pub fn add_recent_file(&mut self, path: &std::path::Path) {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+        self.recent_files.retain(|recent| *recent != path);
+        self.recent_files.insert(0, path);
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+}
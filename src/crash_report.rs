@@ -0,0 +1,217 @@
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// How many log records end up in the report.
+const LOG_LINES: usize = 200;
+
+/// The recent log records of wgpu and the other dependencies.
+static LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+/// The GPU report, once the device is created.
+static GPU_INFO: Mutex<Option<String>> = Mutex::new(None);
+
+/// Keeps the recent log records for the crash report. Doesn't print them, the example never did.
+struct RecentLog;
+
+impl log::Log for RecentLog {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Info
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {} {}: {}",
+            chrono::Local::now().format("%H:%M:%S%.3f"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        let mut log = LOG.lock().unwrap_or_else(|err| err.into_inner());
+        if log.len() == LOG_LINES {
+            log.pop_front();
+        }
+        log.push_back(line);
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the panic hook that writes the crash report, before it prints the panic as usual.
+pub fn install() {
+    if log::set_logger(&RecentLog).is_ok() {
+        log::set_max_level(log::LevelFilter::Info);
+    }
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        match write_report(info) {
+            Ok(path) => eprintln!("Wrote a crash report to {}", path.display()),
+            Err(err) => eprintln!("Can't write the crash report: {}", err),
+        }
+        default_hook(info);
+    }));
+}
+
+/// Remembers the GPU the example runs on, for the crash report.
+pub fn set_gpu_info(markdown: String) {
+    *GPU_INFO.lock().unwrap_or_else(|err| err.into_inner()) = Some(markdown);
+}
+
+fn write_report(info: &std::panic::PanicHookInfo) -> std::io::Result<PathBuf> {
+    let thread = std::thread::current();
+    let message = match (
+        info.payload().downcast_ref::<&str>(),
+        info.payload().downcast_ref::<String>(),
+    ) {
+        (Some(message), _) => message.to_string(),
+        (_, Some(message)) => message.clone(),
+        _ => "Box<dyn Any>".to_owned(),
+    };
+    write_pending("Crash report", |report| {
+        writeln!(
+            report,
+            "Thread '{}' panicked at {}:",
+            thread.name().unwrap_or("<unnamed>"),
+            info.location()
+                .map_or_else(|| "an unknown location".to_owned(), ToString::to_string)
+        )?;
+        writeln!(report, "{}", message)?;
+        writeln!(report)?;
+        writeln!(report, "## Backtrace")?;
+        writeln!(report)?;
+        writeln!(report, "{}", std::backtrace::Backtrace::force_capture())
+    })
+}
+
+/// Writes a report for a GPU that stopped finishing its work, which the next session offers like
+/// the one of a crash.
+pub fn write_hang_report(details: &str) -> std::io::Result<PathBuf> {
+    write_pending("GPU hang report", |report| {
+        writeln!(report, "{}\n", details)
+    })
+}
+
+/// Writes the report the next session picks up: `body`, the GPU and the recent log.
+fn write_pending(
+    title: &str,
+    body: impl FnOnce(&mut String) -> std::fmt::Result,
+) -> std::io::Result<PathBuf> {
+    let path = crate::paths::pending_crash_report().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no config directory available",
+        )
+    })?;
+
+    let mut report = String::new();
+    // Writing into a `String` can't fail.
+    let _ = (|| -> std::fmt::Result {
+        writeln!(report, "# {}", title)?;
+        writeln!(report)?;
+        writeln!(
+            report,
+            "{} {} on {} {}, {}",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+        )?;
+        writeln!(report)?;
+        body(&mut report)?;
+        // The locks are only tried, the panic might have happened while they were held.
+        match GPU_INFO.try_lock().ok().and_then(|info| info.clone()) {
+            Some(gpu_info) => writeln!(report, "{}", gpu_info)?,
+            None => writeln!(report, "No GPU report, the device wasn't created yet.\n")?,
+        }
+        writeln!(report, "## Log")?;
+        writeln!(report)?;
+        if let Ok(log) = LOG.try_lock() {
+            for line in log.iter() {
+                writeln!(report, "{}", line)?;
+            }
+        }
+        Ok(())
+    })();
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
+
+/// Takes the report of a crash in the previous session. It's moved next to the config, with the
+/// time of the crash in its name, so that it's only shown once but stays around.
+pub fn take_previous() -> Option<PathBuf> {
+    let pending = crate::paths::pending_crash_report()?;
+    let crashed_at = std::fs::metadata(&pending).and_then(|metadata| metadata.modified());
+    let crashed_at: chrono::DateTime<chrono::Local> = crashed_at.ok()?.into();
+    let path = pending.with_file_name(format!("crash-{}.txt", crashed_at.format("%Y%m%d-%H%M%S")));
+    match std::fs::rename(&pending, &path) {
+        Ok(()) => Some(path),
+        Err(err) => {
+            eprintln!("Can't move the crash report {}: {}", pending.display(), err);
+            None
+        }
+    }
+}
+
+/// Tells that the previous session crashed, and offers to open the report.
+#[derive(Default)]
+pub struct CrashDialog {
+    report: Option<PathBuf>,
+}
+
+impl CrashDialog {
+    pub fn new(report: PathBuf) -> Self {
+        Self {
+            report: Some(report),
+        }
+    }
+
+    /// Shows the dialog until it's closed. Returns the report if it should be opened.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<PathBuf> {
+        let path = self.report.as_ref()?;
+        let mut open = true;
+        let mut close = false;
+        let mut open_report = None;
+        egui::Window::new("💥 The previous session crashed")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(
+                    "The example crashed or the GPU hung the last time it ran. The crash report \
+                     holds the panic and a backtrace or the hang, the GPU and the recent log.",
+                );
+                ui.horizontal(|ui| {
+                    ui.label("Report:");
+                    ui.monospace(path.display().to_string());
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("📜 Open report").clicked() {
+                        open_report = Some(path.clone());
+                        close = true;
+                    }
+                    if ui.button("📋 Copy report").clicked() {
+                        match std::fs::read_to_string(path) {
+                            Ok(report) => ui.output().copied_text = report,
+                            Err(err) => eprintln!("Can't read {}: {}", path.display(), err),
+                        }
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        if !open || close {
+            self.report = None;
+        }
+        open_report
+    }
+}
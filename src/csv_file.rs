@@ -0,0 +1,301 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// How many distinct values of a column are counted before giving up.
+const MAX_DISTINCT: usize = 10_000;
+/// How many rows are processed between two checks for cancellation.
+const CANCEL_CHECK_ROWS: usize = 65_536;
+
+/// A memory mapped CSV file, of which only the row boundaries are known. Rows are parsed when
+/// they are needed, so that opening a large file doesn't parse all of it.
+pub struct CsvFile {
+    data: Data,
+    /// The field separator, detected from the header.
+    pub delimiter: u8,
+    pub header: Vec<String>,
+    /// The byte offsets of the data rows, followed by the end of the last one.
+    starts: Vec<u64>,
+}
+
+enum Data {
+    Mapped(memmap2::Mmap),
+    /// Empty files can't be mapped.
+    Empty,
+}
+
+impl std::ops::Deref for Data {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Data::Mapped(map) => map,
+            Data::Empty => &[],
+        }
+    }
+}
+
+/// The statistics of a column over all rows.
+pub struct ColumnStats {
+    pub empty: usize,
+    /// The values that parse as numbers, with their range and mean.
+    pub numbers: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// The distinct values, up to [`MAX_DISTINCT`].
+    pub distinct: usize,
+    pub longest: usize,
+}
+
+impl ColumnStats {
+    /// Whether counting the distinct values stopped at the limit.
+    pub fn distinct_capped(&self) -> bool {
+        self.distinct >= MAX_DISTINCT
+    }
+}
+
+impl CsvFile {
+    /// Maps the file at `path` and finds its rows. `progress` counts the bytes scanned so far.
+    pub fn open(path: &Path, progress: &AtomicU64) -> Result<Self, String> {
+        let file = std::fs::File::open(path).map_err(|err| err.to_string())?;
+        let len = file.metadata().map_err(|err| err.to_string())?.len();
+        let data = match len {
+            0 => Data::Empty,
+            // The file could change while it's mapped, which at worst garbles the shown rows.
+            _ => Data::Mapped(unsafe { memmap2::Mmap::map(&file) }.map_err(|err| err.to_string())?),
+        };
+
+        let mut starts = row_starts(&data, progress);
+        if starts.len() < 2 {
+            return Err("The file has no header".to_owned());
+        }
+        let header_row = &data[starts[0] as usize..starts[1] as usize];
+        let is_tsv = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("tsv"));
+        let delimiter = match is_tsv {
+            true => b'\t',
+            false => detect_delimiter(header_row),
+        };
+        let header = parse_record(header_row, delimiter);
+        starts.remove(0);
+        Ok(Self {
+            data,
+            delimiter,
+            header,
+            starts,
+        })
+    }
+
+    /// The number of data rows, without the header.
+    pub fn rows(&self) -> usize {
+        self.starts.len() - 1
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.data.len()
+    }
+
+    /// The fields of data row `row`.
+    pub fn record(&self, row: usize) -> Vec<String> {
+        parse_record(self.raw(row), self.delimiter)
+    }
+
+    fn raw(&self, row: usize) -> &[u8] {
+        &self.data[self.starts[row] as usize..self.starts[row + 1] as usize]
+    }
+
+    /// The data rows that contain `filter`, ignoring case, sorted by `sort` with its column and
+    /// whether it's descending. Columns of numbers sort numerically. `None` if `cancel` was set.
+    pub fn view(
+        &self,
+        filter: &str,
+        sort: Option<(usize, bool)>,
+        cancel: &AtomicBool,
+    ) -> Option<Vec<u32>> {
+        let filter = filter.to_lowercase();
+        let mut rows = Vec::new();
+        for row in 0..self.rows() {
+            if row % CANCEL_CHECK_ROWS == 0 && cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            if filter.is_empty()
+                || String::from_utf8_lossy(self.raw(row))
+                    .to_lowercase()
+                    .contains(&filter)
+            {
+                rows.push(row as u32);
+            }
+        }
+        let (column, descending) = match sort {
+            Some(sort) => sort,
+            None => return Some(rows),
+        };
+
+        let mut keys = Vec::with_capacity(rows.len());
+        for (index, row) in rows.iter().enumerate() {
+            if index % CANCEL_CHECK_ROWS == 0 && cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            let mut record = self.record(*row as usize);
+            keys.push(match column < record.len() {
+                true => record.swap_remove(column),
+                false => String::new(),
+            });
+        }
+        let order = crate::data_table::sort_order(&keys, descending);
+        Some(order.into_iter().map(|index| rows[index]).collect())
+    }
+
+    /// The statistics of `column`. `None` if `cancel` was set.
+    pub fn column_stats(&self, column: usize, cancel: &AtomicBool) -> Option<ColumnStats> {
+        let mut stats = ColumnStats {
+            empty: 0,
+            numbers: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            mean: 0.0,
+            distinct: 0,
+            longest: 0,
+        };
+        let mut sum = 0.0;
+        let mut distinct = HashSet::new();
+        for row in 0..self.rows() {
+            if row % CANCEL_CHECK_ROWS == 0 && cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            let mut record = self.record(row);
+            let value = match column < record.len() {
+                true => record.swap_remove(column),
+                false => String::new(),
+            };
+            if value.trim().is_empty() {
+                stats.empty += 1;
+                continue;
+            }
+            stats.longest = stats.longest.max(value.chars().count());
+            if let Ok(number) = value.trim().parse::<f64>() {
+                stats.numbers += 1;
+                stats.min = stats.min.min(number);
+                stats.max = stats.max.max(number);
+                sum += number;
+            }
+            if distinct.len() < MAX_DISTINCT {
+                distinct.insert(value);
+            }
+        }
+        stats.mean = sum / stats.numbers as f64;
+        stats.distinct = distinct.len();
+        Some(stats)
+    }
+}
+
+/// The byte offsets at which rows start, followed by the end of the data. Line breaks in
+/// quoted fields don't end a row.
+fn row_starts(data: &[u8], progress: &AtomicU64) -> Vec<u64> {
+    let mut starts = vec![0];
+    let mut quoted = false;
+    for (chunk_index, chunk) in data.chunks(1 << 20).enumerate() {
+        let offset = chunk_index << 20;
+        for (index, byte) in chunk.iter().enumerate() {
+            match byte {
+                // A doubled quote in a quoted field toggles twice, which keeps it quoted.
+                b'"' => quoted = !quoted,
+                b'\n' if !quoted => starts.push((offset + index + 1) as u64),
+                _ => {}
+            }
+        }
+        progress.store((offset + chunk.len()) as u64, Ordering::Relaxed);
+    }
+    if starts.last() != Some(&(data.len() as u64)) {
+        starts.push(data.len() as u64);
+    }
+    starts
+}
+
+/// The most common of the usual separators in `header`.
+fn detect_delimiter(header: &[u8]) -> u8 {
+    [b',', b';', b'\t', b'|']
+        .into_iter()
+        .max_by_key(|delimiter| header.iter().filter(|byte| *byte == delimiter).count())
+        .expect("There are delimiters")
+}
+
+/// Splits a row into its fields, with the quotes of quoted fields removed.
+fn parse_record(row: &[u8], delimiter: u8) -> Vec<String> {
+    let row = row.strip_suffix(b"\n").unwrap_or(row);
+    let row = row.strip_suffix(b"\r").unwrap_or(row);
+    let mut fields = Vec::new();
+    let mut field = Vec::new();
+    let mut quoted = false;
+    let mut bytes = row.iter().peekable();
+    while let Some(&byte) = bytes.next() {
+        match byte {
+            b'"' if quoted && bytes.peek() == Some(&&b'"') => {
+                field.push(b'"');
+                bytes.next();
+            }
+            b'"' => quoted = !quoted,
+            _ if byte == delimiter && !quoted => {
+                fields.push(String::from_utf8_lossy(&field).into_owned());
+                field.clear();
+            }
+            _ => field.push(byte),
+        }
+    }
+    fields.push(String::from_utf8_lossy(&field).into_owned());
+    fields
+}
+
+/// Appends a row of `fields` to `out`, quoting the fields that need it.
+pub fn write_record(out: &mut String, fields: &[String], delimiter: char) {
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            out.push(delimiter);
+        }
+        if field.contains([delimiter, '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quoted_fields() {
+        assert_eq!(
+            parse_record(b"a,\"b,c\",\"say \"\"hi\"\"\",\r\n", b','),
+            ["a", "b,c", "say \"hi\"", ""]
+        );
+    }
+
+    #[test]
+    fn quoted_line_breaks_stay_in_the_row() {
+        let data = b"name,note\nx,\"two\nlines\"\ny,last";
+        let starts = row_starts(data, &AtomicU64::new(0));
+        assert_eq!(starts, [0, 10, 24, data.len() as u64]);
+        assert_eq!(parse_record(&data[10..24], b','), ["x", "two\nlines"]);
+    }
+
+    #[test]
+    fn written_records_parse_back() {
+        let fields = ["plain", "a,b", "say \"hi\"", "two\nlines", ""].map(String::from);
+        let mut out = String::new();
+        write_record(&mut out, &fields, ',');
+        assert_eq!(parse_record(out.as_bytes(), b','), fields);
+    }
+
+    #[test]
+    fn detects_the_delimiter() {
+        assert_eq!(detect_delimiter(b"a;b;c,d"), b';');
+        assert_eq!(detect_delimiter(b"a\tb\tc"), b'\t');
+    }
+}
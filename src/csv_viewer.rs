@@ -0,0 +1,330 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+
+use crate::csv_file::{ColumnStats, CsvFile};
+use crate::data_table::{self, column_name, DataTable};
+use crate::workspaces::SaveState;
+
+/// The rows the default column widths are measured from.
+const MEASURED_ROWS: usize = 100;
+
+/// A table of a CSV file of any size. The file is memory mapped and only its row boundaries are
+/// indexed up front; the table only parses the rows that are visible. Sorting, filtering and the
+/// column statistics run on threads, so the UI never waits for them.
+///
+/// Files are opened by entering their path or by dropping them onto the window.
+#[derive(Default)]
+pub struct CsvViewer {
+    path: String,
+    loading: Option<Loading>,
+    file: Option<Arc<CsvFile>>,
+    error: Option<String>,
+    /// The widths of the columns, in points.
+    widths: Vec<f32>,
+    filter: String,
+    /// The sorted column, and whether it's sorted descending.
+    sort: Option<(usize, bool)>,
+    /// The data rows that are shown, in order. `None` shows all of them as they are in the file.
+    view: Option<Vec<u32>>,
+    view_job: Option<Job<Vec<u32>>>,
+    stats_open: bool,
+    stats_column: usize,
+    stats: Option<ColumnStats>,
+    stats_job: Option<Job<ColumnStats>>,
+    /// The file of a restored workspace, opened once the window is.
+    reopen: Option<PathBuf>,
+}
+
+struct Loading {
+    receiver: mpsc::Receiver<Result<CsvFile, String>>,
+    /// The bytes indexed so far, of `total`.
+    progress: Arc<AtomicU64>,
+    total: u64,
+}
+
+/// Work on a thread, which is cancelled when the job is dropped.
+struct Job<T> {
+    receiver: mpsc::Receiver<T>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl<T: Send + 'static> Job<T> {
+    fn spawn(
+        ctx: &egui::Context,
+        work: impl FnOnce(&AtomicBool) -> Option<T> + Send + 'static,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let thread_cancel = cancel.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            if let Some(result) = work(&thread_cancel) {
+                let _ = sender.send(result);
+                ctx.request_repaint();
+            }
+        });
+        Self { receiver, cancel }
+    }
+
+    fn poll(&self) -> Option<T> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl<T> Drop for Job<T> {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl CsvViewer {
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if *open {
+            let dropped = self.reopen.take().or_else(|| {
+                ctx.input().raw.dropped_files.iter().find_map(|file| {
+                    file.path.clone().filter(|path| {
+                        path.extension().is_some_and(|extension| {
+                            extension.eq_ignore_ascii_case("csv")
+                                || extension.eq_ignore_ascii_case("tsv")
+                        })
+                    })
+                })
+            });
+            if let Some(path) = dropped {
+                self.path = path.display().to_string();
+                self.load(ctx, path);
+            }
+        }
+        self.poll_jobs(ctx);
+
+        egui::Window::new("📄 CSV viewer")
+            .open(open)
+            .default_size([720.0, 480.0])
+            .show(ctx, |ui| self.ui(ui));
+    }
+
+    fn load(&mut self, ctx: &egui::Context, path: PathBuf) {
+        let (sender, receiver) = mpsc::channel();
+        let progress = Arc::new(AtomicU64::new(0));
+        let total = std::fs::metadata(&path).map_or(0, |metadata| metadata.len());
+        let thread_progress = progress.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let result = CsvFile::open(&path, &thread_progress)
+                .map_err(|err| format!("Can't open {}: {}", path.display(), err));
+            // The viewer might have started to load another file meanwhile.
+            let _ = sender.send(result);
+            ctx.request_repaint();
+        });
+        self.loading = Some(Loading {
+            receiver,
+            progress,
+            total,
+        });
+    }
+
+    fn poll_jobs(&mut self, ctx: &egui::Context) {
+        if let Some(loading) = &self.loading {
+            match loading.receiver.try_recv() {
+                Ok(Ok(file)) => {
+                    self.loading = None;
+                    self.error = None;
+                    self.widths = column_widths(ctx, &file);
+                    self.file = Some(Arc::new(file));
+                    self.sort = None;
+                    self.filter.clear();
+                    self.view = None;
+                    self.view_job = None;
+                    self.stats_column = 0;
+                    self.update_stats(ctx);
+                }
+                Ok(Err(err)) => {
+                    self.loading = None;
+                    self.error = Some(err);
+                }
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.loading = None;
+                    self.error = Some("The CSV indexer crashed".to_owned());
+                }
+                // Shows the progress.
+                Err(mpsc::TryRecvError::Empty) => ctx.request_repaint(),
+            }
+        }
+        if let Some(view) = self.view_job.as_ref().and_then(Job::poll) {
+            self.view = Some(view);
+            self.view_job = None;
+        }
+        if let Some(stats) = self.stats_job.as_ref().and_then(Job::poll) {
+            self.stats = Some(stats);
+            self.stats_job = None;
+        }
+    }
+
+    /// Filters and sorts the rows on a thread, replacing the job that might still be running.
+    fn update_view(&mut self, ctx: &egui::Context) {
+        let file = match &self.file {
+            Some(file) => file.clone(),
+            None => return,
+        };
+        if self.filter.is_empty() && self.sort.is_none() {
+            self.view = None;
+            self.view_job = None;
+            return;
+        }
+        let (filter, sort) = (self.filter.clone(), self.sort);
+        self.view_job = Some(Job::spawn(ctx, move |cancel| {
+            file.view(&filter, sort, cancel)
+        }));
+    }
+
+    fn update_stats(&mut self, ctx: &egui::Context) {
+        let file = match &self.file {
+            Some(file) => file.clone(),
+            None => return,
+        };
+        let column = self.stats_column;
+        self.stats = None;
+        self.stats_job = Some(Job::spawn(ctx, move |cancel| {
+            file.column_stats(column, cancel)
+        }));
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let response = ui
+                .add(egui::TextEdit::singleline(&mut self.path).hint_text("Path, or drop a file"));
+            let entered = response.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+            if ui.button("Open").clicked() || entered {
+                self.load(ui.ctx(), PathBuf::from(&self.path));
+            }
+            ui.separator();
+            ui.toggle_value(&mut self.stats_open, "📊 Statistics");
+        });
+        if let Some(loading) = &self.loading {
+            let progress = loading.progress.load(Ordering::Relaxed);
+            ui.add(
+                egui::ProgressBar::new(progress as f32 / loading.total.max(1) as f32)
+                    .text("Indexing rows…"),
+            );
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        let file = match &self.file {
+            Some(file) => file.clone(),
+            None => return,
+        };
+
+        ui.horizontal(|ui| {
+            let response =
+                ui.add(egui::TextEdit::singleline(&mut self.filter).hint_text("Filter the rows"));
+            if response.changed() {
+                self.update_view(ui.ctx());
+            }
+            let shown = self.view.as_ref().map_or(file.rows(), Vec::len);
+            ui.label(format!(
+                "{} of {} rows, {:.1} MiB",
+                shown,
+                file.rows(),
+                file.bytes() as f64 / (1024.0 * 1024.0)
+            ));
+            if self.view_job.is_some() {
+                ui.spinner();
+            }
+        });
+
+        if self.stats_open {
+            egui::SidePanel::right("csv_stats_panel").show_inside(ui, |ui| {
+                self.stats_ui(ui, &file);
+            });
+        }
+        self.table_ui(ui, &file);
+    }
+
+    fn stats_ui(&mut self, ui: &mut egui::Ui, file: &CsvFile) {
+        let previous = self.stats_column;
+        egui::ComboBox::from_id_source("csv_stats_column")
+            .selected_text(column_name(&file.header, self.stats_column))
+            .show_ui(ui, |ui| {
+                for column in 0..file.header.len() {
+                    ui.selectable_value(
+                        &mut self.stats_column,
+                        column,
+                        column_name(&file.header, column),
+                    );
+                }
+            });
+        if self.stats_column != previous {
+            self.update_stats(ui.ctx());
+        }
+        let stats = match &self.stats {
+            Some(stats) => stats,
+            None => {
+                ui.spinner();
+                return;
+            }
+        };
+        egui::Grid::new("csv_stats").num_columns(2).show(ui, |ui| {
+            ui.label("Empty");
+            ui.label(stats.empty.to_string());
+            ui.end_row();
+            ui.label("Distinct");
+            ui.label(match stats.distinct_capped() {
+                true => format!("{}+", stats.distinct),
+                false => stats.distinct.to_string(),
+            });
+            ui.end_row();
+            ui.label("Longest");
+            ui.label(format!("{} characters", stats.longest));
+            ui.end_row();
+            ui.label("Numbers");
+            ui.label(stats.numbers.to_string());
+            ui.end_row();
+            if stats.numbers > 0 {
+                for (label, value) in [("Min", stats.min), ("Max", stats.max), ("Mean", stats.mean)]
+                {
+                    ui.label(label);
+                    ui.label(format!("{}", value));
+                    ui.end_row();
+                }
+            }
+        });
+    }
+
+    fn table_ui(&mut self, ui: &mut egui::Ui, file: &CsvFile) {
+        let rows = self.view.as_ref().map_or(file.rows(), Vec::len);
+        let clicked = DataTable::new(&file.header, &self.widths, rows)
+            .sort(self.sort)
+            .show(ui, |index| {
+                let row = self
+                    .view
+                    .as_ref()
+                    .map_or(index, |view| view[index] as usize);
+                (row + 1, file.record(row))
+            });
+        if let Some(column) = clicked {
+            self.sort = data_table::next_sort(self.sort, column);
+            self.update_view(ui.ctx());
+        }
+    }
+}
+
+/// Fits the columns to their names and the first rows.
+fn column_widths(ctx: &egui::Context, file: &CsvFile) -> Vec<f32> {
+    let rows = (0..file.rows().min(MEASURED_ROWS)).map(|row| file.record(row));
+    data_table::column_widths(ctx, &file.header, rows)
+}
+
+impl SaveState for CsvViewer {
+    /// The open file.
+    type State = Option<PathBuf>;
+
+    fn save_state(&self) -> Option<PathBuf> {
+        (self.file.is_some() || self.loading.is_some()).then(|| PathBuf::from(&self.path))
+    }
+
+    fn restore_state(&mut self, file: Option<PathBuf>) {
+        self.reopen = file;
+    }
+}
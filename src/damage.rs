@@ -0,0 +1,91 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+use egui::epaint::Primitive;
+
+/// Finds the part of the window that changed since the last frame, by comparing the paint jobs
+/// of both frames.
+///
+/// wgpu 0.13 can only present whole surfaces, so the damage isn't used for presenting yet. The
+/// Debug menu shows it instead, which tells how much a partial present would save.
+#[derive(Default)]
+pub struct DamageTracker {
+    /// The hashes and bounds of the primitives of the last frame.
+    last_frame: Vec<(u64, egui::Rect)>,
+}
+
+impl DamageTracker {
+    /// Compares the paint jobs of all layers with the last frame, in points. Primitives using a
+    /// texture in `changed_textures` are damaged even if they didn't change themselves.
+    pub fn update(
+        &mut self,
+        layers: &[&[egui::ClippedPrimitive]],
+        changed_textures: &HashSet<egui::TextureId>,
+    ) -> egui::Rect {
+        let mut damage = egui::Rect::NOTHING;
+        let mut frame = Vec::new();
+        for (index, job) in layers.iter().flat_map(|jobs| jobs.iter()).enumerate() {
+            let bounds = bounds(job);
+            // What a callback paints is unknown.
+            let always_damaged = match &job.primitive {
+                Primitive::Mesh(mesh) => changed_textures.contains(&mesh.texture_id),
+                Primitive::Callback(_) => true,
+            };
+            if always_damaged {
+                damage = damage.union(bounds);
+            }
+            frame.push((hash(index, job), bounds));
+        }
+
+        // Whatever appeared is drawn anew, and whatever disappeared needs to be painted over.
+        let hashes = |frame: &[(u64, egui::Rect)]| -> HashSet<u64> {
+            frame.iter().map(|(hash, _)| *hash).collect()
+        };
+        let (last_hashes, hashes) = (hashes(&self.last_frame), hashes(&frame));
+        let appeared = frame.iter().filter(|(hash, _)| !last_hashes.contains(hash));
+        let disappeared = self
+            .last_frame
+            .iter()
+            .filter(|(hash, _)| !hashes.contains(hash));
+        for (_, bounds) in appeared.chain(disappeared) {
+            damage = damage.union(*bounds);
+        }
+        self.last_frame = frame;
+        damage
+    }
+}
+
+/// Hashes what `job` paints. `index` keeps the draw order, since a primitive that moves below
+/// another one changes the picture as well.
+fn hash(index: usize, job: &egui::ClippedPrimitive) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    index.hash(&mut hasher);
+    [
+        job.clip_rect.min.x,
+        job.clip_rect.min.y,
+        job.clip_rect.max.x,
+        job.clip_rect.max.y,
+    ]
+    .map(f32::to_bits)
+    .hash(&mut hasher);
+    if let Primitive::Mesh(mesh) = &job.primitive {
+        mesh.texture_id.hash(&mut hasher);
+        mesh.indices.hash(&mut hasher);
+        for vertex in &mesh.vertices {
+            [vertex.pos.x, vertex.pos.y, vertex.uv.x, vertex.uv.y]
+                .map(f32::to_bits)
+                .hash(&mut hasher);
+            vertex.color.hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+/// The part of the screen `job` can paint to.
+fn bounds(job: &egui::ClippedPrimitive) -> egui::Rect {
+    match &job.primitive {
+        Primitive::Mesh(mesh) => mesh.calc_bounds().intersect(job.clip_rect),
+        Primitive::Callback(callback) => callback.rect.intersect(job.clip_rect),
+    }
+}
@@ -0,0 +1,175 @@
+/// The width of the row number column, in points.
+const ROW_NUMBER_WIDTH: f32 = 64.0;
+
+/// A table of any number of rows, of which only the visible ones are requested. The columns
+/// have fixed widths, see [`column_widths`], and their headers sort the table when clicked.
+pub struct DataTable<'a> {
+    names: &'a [String],
+    widths: &'a [f32],
+    rows: usize,
+    /// The sorted column, and whether it's sorted descending.
+    sort: Option<(usize, bool)>,
+}
+
+impl<'a> DataTable<'a> {
+    pub fn new(names: &'a [String], widths: &'a [f32], rows: usize) -> Self {
+        Self {
+            names,
+            widths,
+            rows,
+            sort: None,
+        }
+    }
+
+    /// Marks the sorted column, with whether it's sorted descending.
+    pub fn sort(mut self, sort: Option<(usize, bool)>) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// `row` returns the number shown for a row of the table and its values. Returns the column
+    /// whose header was clicked.
+    pub fn show(
+        self,
+        ui: &mut egui::Ui,
+        mut row: impl FnMut(usize) -> (usize, Vec<String>),
+    ) -> Option<usize> {
+        let row_height = ui.text_style_height(&egui::TextStyle::Body) + 4.0;
+        let width = ROW_NUMBER_WIDTH + self.widths.iter().sum::<f32>();
+        let mut clicked = None;
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            ui.set_min_width(width);
+            ui.spacing_mut().item_spacing = egui::Vec2::ZERO;
+            ui.horizontal(|ui| {
+                ui.add_space(ROW_NUMBER_WIDTH);
+                for (column, width) in self.widths.iter().enumerate() {
+                    let arrow = match self.sort {
+                        Some((sorted, false)) if sorted == column => " ⬆",
+                        Some((sorted, true)) if sorted == column => " ⬇",
+                        _ => "",
+                    };
+                    let label = format!("{}{}", column_name(self.names, column), arrow);
+                    let button = egui::Button::new(egui::RichText::new(label).strong());
+                    if ui
+                        .add_sized([*width, row_height], button)
+                        .on_hover_text("Sort by this column")
+                        .clicked()
+                    {
+                        clicked = Some(column);
+                    }
+                }
+            });
+            egui::ScrollArea::vertical().show_rows(ui, row_height, self.rows, |ui, range| {
+                let font_id = egui::TextStyle::Body.resolve(ui.style());
+                for index in range {
+                    let (number, values) = row(index);
+                    let (rect, _) =
+                        ui.allocate_exact_size(egui::vec2(width, row_height), egui::Sense::hover());
+                    if index % 2 == 1 {
+                        ui.painter()
+                            .rect_filled(rect, 0.0, ui.visuals().faint_bg_color);
+                    }
+                    let cell = |x: f32, width: f32, text: &str, color: egui::Color32| {
+                        let cell = egui::Rect::from_min_size(
+                            egui::pos2(x, rect.top()),
+                            egui::vec2(width, row_height),
+                        );
+                        ui.painter()
+                            .with_clip_rect(
+                                cell.shrink2(egui::vec2(2.0, 0.0)).intersect(ui.clip_rect()),
+                            )
+                            .text(
+                                cell.left_center() + egui::vec2(4.0, 0.0),
+                                egui::Align2::LEFT_CENTER,
+                                text,
+                                font_id.clone(),
+                                color,
+                            );
+                    };
+                    cell(
+                        rect.left(),
+                        ROW_NUMBER_WIDTH,
+                        &number.to_string(),
+                        ui.visuals().weak_text_color(),
+                    );
+                    let mut x = rect.left() + ROW_NUMBER_WIDTH;
+                    for (value, width) in values.iter().zip(self.widths) {
+                        cell(x, *width, value, ui.visuals().text_color());
+                        x += width;
+                    }
+                }
+            });
+        });
+        clicked
+    }
+}
+
+/// The name of `column`, or its number for columns without a name.
+pub fn column_name(names: &[String], column: usize) -> String {
+    match names.get(column).map(String::as_str) {
+        Some("") | None => format!("Column {}", column + 1),
+        Some(name) => name.to_owned(),
+    }
+}
+
+/// Fits the columns to their names and the given rows, within limits.
+pub fn column_widths(
+    ctx: &egui::Context,
+    names: &[String],
+    rows: impl Iterator<Item = Vec<String>>,
+) -> Vec<f32> {
+    let font_id = egui::TextStyle::Body.resolve(&ctx.style());
+    let char_width = ctx.fonts().glyph_width(&font_id, '0');
+    let mut chars: Vec<usize> = (0..names.len())
+        .map(|column| column_name(names, column).chars().count() + 2)
+        .collect();
+    for row in rows {
+        for (column, value) in row.iter().enumerate() {
+            if let Some(chars) = chars.get_mut(column) {
+                *chars = (*chars).max(value.chars().count());
+            }
+        }
+    }
+    chars
+        .into_iter()
+        .map(|chars| (chars as f32 * char_width + 12.0).clamp(48.0, 320.0))
+        .collect()
+}
+
+/// The sort after the header of `column` was clicked: ascending, then descending, then none.
+pub fn next_sort(sort: Option<(usize, bool)>, column: usize) -> Option<(usize, bool)> {
+    match sort {
+        Some((sorted, false)) if sorted == column => Some((column, true)),
+        Some((sorted, true)) if sorted == column => None,
+        _ => Some((column, false)),
+    }
+}
+
+/// The order of `keys` when sorted. If all keys are numbers, they sort numerically, with the
+/// empty ones last.
+pub fn sort_order(keys: &[String], descending: bool) -> Vec<usize> {
+    let numeric = keys
+        .iter()
+        .all(|key| key.trim().is_empty() || key.trim().parse::<f64>().is_ok());
+    let mut order: Vec<usize> = (0..keys.len()).collect();
+    if numeric {
+        let numbers: Vec<f64> = keys
+            .iter()
+            .map(|key| key.trim().parse().unwrap_or(f64::NAN))
+            .collect();
+        order.sort_by(|a, b| {
+            let (a, b) = (numbers[*a], numbers[*b]);
+            match (a.is_nan(), b.is_nan()) {
+                (false, false) if descending => b.total_cmp(&a),
+                (false, false) => a.total_cmp(&b),
+                (a, b) => a.cmp(&b),
+            }
+        });
+    } else {
+        order.sort_by(|a, b| match descending {
+            true => keys[*b].cmp(&keys[*a]),
+            false => keys[*a].cmp(&keys[*b]),
+        });
+    }
+    order
+}
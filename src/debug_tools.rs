@@ -0,0 +1,388 @@
+use std::collections::VecDeque;
+
+use winit::event::WindowEvent;
+
+/// How many events the event log keeps.
+const MAX_LOGGED_EVENTS: usize = 1000;
+
+/// The Debug menu: egui's own settings, inspection and memory windows, a log of the winit
+/// events that are forwarded to the platform integration, the tessellation options, the slow
+/// frames, the resizes of the font atlas, the damage of each frame, and RenderDoc captures.
+#[derive(Default)]
+pub struct DebugTools {
+    settings_open: bool,
+    inspection_open: bool,
+    memory_open: bool,
+    event_log_open: bool,
+    event_log: EventLog,
+    tessellation_open: bool,
+    tessellation: crate::tessellator::TessellationPanel,
+    hitch_reports_open: bool,
+    hitch_reports: crate::hitch_reports::HitchReports,
+    font_atlas_open: bool,
+    font_atlas: crate::font_atlas::FontAtlasPanel,
+    focus_order_open: bool,
+    focus_debug: crate::focus_debug::FocusDebug,
+    frame_debugger_open: bool,
+    frame_debugger: crate::frame_debugger::FrameDebugger,
+    show_damage: bool,
+    /// Draws the frame as someone with this color vision deficiency sees it.
+    color_blindness: Option<crate::color_blindness::Simulation>,
+    /// The part of the window that changed in the last frame.
+    damage: Option<egui::Rect>,
+    #[cfg(feature = "renderdoc")]
+    frame_capture: crate::frame_capture::FrameCapture,
+    trace: Option<Trace>,
+    /// Whether the frame statistics should be exported after this frame.
+    export_frame_stats: bool,
+    /// Where the frame statistics were exported to, or why that failed.
+    frame_stats_status: Option<String>,
+}
+
+/// A wgpu API trace, which only ends when the device is dropped.
+struct Trace {
+    dir: std::path::PathBuf,
+    /// The frames until the example exits to finish the trace.
+    frames_left: Option<u32>,
+}
+
+impl DebugTools {
+    pub fn menu(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.settings_open, "🔧 egui settings");
+        ui.checkbox(&mut self.inspection_open, "🔍 egui inspection");
+        ui.checkbox(&mut self.memory_open, "📝 egui memory");
+        ui.checkbox(&mut self.event_log_open, "📜 Event log");
+        ui.checkbox(&mut self.tessellation_open, "📐 Tessellation");
+        ui.checkbox(&mut self.hitch_reports_open, "🐌 Hitch reports")
+            .on_hover_text("Lists the frames that took much longer than the ones before them");
+        ui.checkbox(&mut self.font_atlas_open, "🔤 Font atlas")
+            .on_hover_text("Logs the resizes of the font atlas and what uploading it cost");
+        ui.checkbox(&mut self.focus_order_open, "⌨ Focus order")
+            .on_hover_text("Records where Tab moves the keyboard focus");
+        ui.checkbox(&mut self.frame_debugger_open, "🎞 Frame debugger")
+            .on_hover_text("Keeps the last frames, to step through them");
+        ui.checkbox(&mut self.show_damage, "🟥 Show damage")
+            .on_hover_text("Outlines what changed in the last frame");
+        ui.menu_button("👁 Color blindness", |ui| {
+            ui.radio_value(&mut self.color_blindness, None, "Off");
+            for simulation in crate::color_blindness::Simulation::ALL {
+                ui.radio_value(
+                    &mut self.color_blindness,
+                    Some(simulation),
+                    simulation.name(),
+                );
+            }
+        });
+        ui.separator();
+        #[cfg(feature = "renderdoc")]
+        self.frame_capture.menu(ui);
+        #[cfg(not(feature = "renderdoc"))]
+        ui.add_enabled(false, egui::Label::new("📸 Capture frame with RenderDoc"))
+            .on_disabled_hover_text("Build with `--features renderdoc` to enable");
+        self.trace_menu(ui);
+        ui.separator();
+        if ui
+            .button("📊 Export frame statistics")
+            .on_hover_text(
+                "Writes the timings, uploads and draw calls of every frame so far to CSV and JSON",
+            )
+            .clicked()
+        {
+            self.export_frame_stats = true;
+            ui.close_menu();
+        }
+        if let Some(status) = &self.frame_stats_status {
+            ui.weak(status);
+        }
+    }
+
+    /// Whether the frame statistics should be exported, once.
+    pub fn take_frame_stats_export(&mut self) -> bool {
+        std::mem::take(&mut self.export_frame_stats)
+    }
+
+    pub fn set_frame_stats_exported(&mut self, result: Result<std::path::PathBuf, String>) {
+        let status = match result {
+            Ok(path) => format!("Exported to {}", path.display()),
+            Err(err) => format!("Can't export the frame statistics: {}", err),
+        };
+        println!("{}", status);
+        self.frame_stats_status = Some(status);
+    }
+
+    pub fn tessellation_options(&self) -> egui::epaint::TessellationOptions {
+        self.tessellation.options()
+    }
+
+    /// Hands the statistics of the recent frames to the tessellation panel and checks the last
+    /// one for a hitch.
+    pub fn set_recent_frames(&mut self, frames: &[crate::frame_stats::FrameRecord]) {
+        if let Some(last) = frames.last() {
+            self.tessellation.set_stats(last);
+        }
+        self.hitch_reports.check(frames);
+        self.font_atlas.check(frames);
+    }
+
+    fn trace_menu(&mut self, ui: &mut egui::Ui) {
+        let trace = match &mut self.trace {
+            Some(trace) => trace,
+            None => {
+                ui.add_enabled(false, egui::Label::new("⏺ wgpu trace"))
+                    .on_disabled_hover_text(if cfg!(feature = "trace") {
+                        "Start with `--trace <dir>` to record a trace"
+                    } else {
+                        "Build with `--features trace` and start with `--trace <dir>`"
+                    });
+                return;
+            }
+        };
+        ui.label(format!("⏺ Tracing into {}", trace.dir.display()));
+        match trace.frames_left {
+            Some(frames) => {
+                ui.label(format!("Exits after {} more frames", frames));
+            }
+            // A trace can't be started or stopped while the device lives, so recording the
+            // next frames means ending the trace, and with it the example, after them.
+            None => {
+                if ui.button("Record 100 more frames, then exit").clicked() {
+                    trace.frames_left = Some(100);
+                    ui.close_menu();
+                }
+            }
+        }
+    }
+
+    pub fn color_blindness(&self) -> Option<crate::color_blindness::Simulation> {
+        self.color_blindness
+    }
+
+    pub fn show_damage(&self) -> bool {
+        self.show_damage
+    }
+
+    pub fn set_damage(&mut self, damage: egui::Rect) {
+        self.damage = Some(damage);
+    }
+
+    pub fn start_trace(&mut self, dir: std::path::PathBuf, frames: Option<u32>) {
+        self.trace = Some(Trace {
+            dir,
+            frames_left: frames,
+        });
+    }
+
+    pub fn trace_finished(&self) -> bool {
+        self.trace
+            .as_ref()
+            .is_some_and(|trace| trace.frames_left == Some(0))
+    }
+
+    /// Shows the state of the debugging tools in the menu bar.
+    pub fn status_ui(
+        &mut self,
+        #[cfg_attr(not(feature = "renderdoc"), allow(unused_variables))] ui: &mut egui::Ui,
+    ) {
+        #[cfg(feature = "renderdoc")]
+        self.frame_capture.status_ui(ui);
+    }
+
+    /// `clear_color` is the one the frame is rendered with.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        textures: &mut crate::user_textures::UserTextures,
+        clear_color: wgpu::Color,
+    ) {
+        #[cfg(feature = "renderdoc")]
+        self.frame_capture.handle_input(ctx);
+
+        egui::Window::new("🔧 egui settings")
+            .open(&mut self.settings_open)
+            .vscroll(true)
+            .show(ctx, |ui| ctx.settings_ui(ui));
+        egui::Window::new("🔍 egui inspection")
+            .open(&mut self.inspection_open)
+            .vscroll(true)
+            .show(ctx, |ui| ctx.inspection_ui(ui));
+        egui::Window::new("📝 egui memory")
+            .open(&mut self.memory_open)
+            .vscroll(true)
+            .show(ctx, |ui| ctx.memory_ui(ui));
+        egui::Window::new("📜 Event log")
+            .open(&mut self.event_log_open)
+            .default_width(480.0)
+            .show(ctx, |ui| self.event_log.ui(ui));
+        egui::Window::new("📐 Tessellation")
+            .open(&mut self.tessellation_open)
+            .resizable(false)
+            .show(ctx, |ui| self.tessellation.ui(ui));
+        egui::Window::new("🐌 Hitch reports")
+            .open(&mut self.hitch_reports_open)
+            .default_width(420.0)
+            .show(ctx, |ui| self.hitch_reports.ui(ui));
+        egui::Window::new("🔤 Font atlas")
+            .open(&mut self.font_atlas_open)
+            .default_width(420.0)
+            .show(ctx, |ui| self.font_atlas.ui(ui));
+        egui::Window::new("⌨ Focus order")
+            .open(&mut self.focus_order_open)
+            .default_width(320.0)
+            .show(ctx, |ui| self.focus_debug.ui(ui));
+        self.focus_debug.overlay(ctx);
+        egui::Window::new("🎞 Frame debugger")
+            .open(&mut self.frame_debugger_open)
+            .default_width(520.0)
+            .show(ctx, |ui| self.frame_debugger.ui(ui, textures, clear_color));
+        self.event_log.frame += 1;
+        match (self.show_damage, self.damage) {
+            (true, Some(damage)) => damage_ui(ctx, damage),
+            _ => self.damage = None,
+        }
+        if let Some(frames) = self
+            .trace
+            .as_mut()
+            .and_then(|trace| trace.frames_left.as_mut())
+        {
+            *frames = frames.saturating_sub(1);
+            // Keeps drawing until the trace has all its frames.
+            ctx.request_repaint();
+        }
+    }
+
+    pub fn mark_focus(&self, style: &mut egui::Style) {
+        self.focus_debug.mark_focus(style);
+    }
+
+    pub fn next_frame(&mut self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
+        self.focus_debug.next_frame(ctx, raw_input);
+    }
+
+    pub fn observe(&mut self, ctx: &egui::Context, output: &egui::FullOutput) {
+        self.focus_debug.observe(ctx, output);
+        self.frame_debugger
+            .observe(ctx, output, self.frame_debugger_open);
+    }
+
+    pub fn batch_input(&self) -> bool {
+        self.event_log.batch_input
+    }
+
+    pub fn set_input_counts(&mut self, received: u32, forwarded: u32) {
+        self.event_log.counts = (received, forwarded);
+    }
+
+    /// Adds `event` to the event log. Only logs while the event log window is open.
+    pub fn log_event(&mut self, event: &winit::event::Event<crate::Event>) {
+        if let (true, winit::event::Event::WindowEvent { event, .. }) = (self.event_log_open, event)
+        {
+            self.event_log.log(event);
+        }
+    }
+}
+
+/// Outlines `damage`. The outline and its label are drawn into the next frame, so they add to
+/// its damage whenever they change.
+fn damage_ui(ctx: &egui::Context, damage: egui::Rect) {
+    let screen_rect = ctx.input().screen_rect;
+    let painter = ctx.debug_painter();
+    let text = if damage.is_positive() {
+        painter.rect_stroke(damage, 0.0, (2.0, egui::Color32::RED));
+        format!(
+            "Damage: {:.0} × {:.0} points, {:.1} % of the window",
+            damage.width(),
+            damage.height(),
+            100.0 * damage.area() / screen_rect.area()
+        )
+    } else {
+        "No damage".to_owned()
+    };
+    painter.debug_text(
+        screen_rect.right_bottom() - egui::vec2(8.0, 8.0),
+        egui::Align2::RIGHT_BOTTOM,
+        egui::Color32::RED,
+        text,
+    );
+}
+
+struct EventLog {
+    entries: VecDeque<(u64, String)>,
+    /// The frame the logged events are passed to egui in.
+    frame: u64,
+    paused: bool,
+    /// Whether the pointer motion events are logged. They drown out everything else.
+    log_motion: bool,
+    /// Whether the pointer motion and scrolling are coalesced between frames, see `InputBatch`.
+    batch_input: bool,
+    /// The window events of the last frame, as received from winit and as forwarded.
+    counts: (u32, u32),
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            frame: 0,
+            paused: false,
+            log_motion: false,
+            batch_input: true,
+            counts: (0, 0),
+        }
+    }
+}
+
+impl EventLog {
+    fn log(&mut self, event: &WindowEvent) {
+        if self.paused {
+            return;
+        }
+        let motion = matches!(
+            event,
+            WindowEvent::CursorMoved { .. } | WindowEvent::AxisMotion { .. }
+        );
+        if motion && !self.log_motion {
+            return;
+        }
+        if self.entries.len() == MAX_LOGGED_EVENTS {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((self.frame, format!("{:?}", event)));
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.paused, "Pause");
+            ui.checkbox(&mut self.log_motion, "Pointer motion");
+            if ui.button("Clear").clicked() {
+                self.entries.clear();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.batch_input, "Coalesce pointer motion")
+                .on_hover_text(
+                    "Forwards only the last position and the summed scrolling per frame",
+                );
+            let (received, forwarded) = self.counts;
+            ui.weak(format!(
+                "Last frame: {} events from winit, {} forwarded",
+                received, forwarded
+            ));
+        });
+        ui.separator();
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::both()
+            .auto_shrink([false, false])
+            .max_height(400.0)
+            .stick_to_bottom(true)
+            .show_rows(ui, row_height, self.entries.len(), |ui, rows| {
+                for (frame, event) in self.entries.range(rows) {
+                    ui.add(
+                        egui::Label::new(
+                            egui::RichText::new(format!("{:>6} {}", frame, event)).monospace(),
+                        )
+                        .wrap(false),
+                    );
+                }
+            });
+    }
+}
@@ -0,0 +1,232 @@
+use winit::monitor::{MonitorHandle, VideoMode};
+use winit::window::{Fullscreen, Window};
+
+/// Switches the window between windowed, borderless fullscreen and exclusive fullscreen with a
+/// video mode of the monitor.
+///
+/// Exclusive fullscreen is left while the window is in the background, so that Alt+Tab gives the
+/// desktop its own mode back, and entered again once the window regains focus.
+#[derive(Default)]
+pub struct DisplayMode {
+    /// The monitor to list the video modes of. Defaults to the one the window is on.
+    monitor: Option<MonitorHandle>,
+    video_mode: Option<VideoMode>,
+    /// The exclusive mode that was left because the window lost focus.
+    suspended: Option<VideoMode>,
+}
+
+impl DisplayMode {
+    /// Leaves and re-enters exclusive fullscreen when the window loses or regains focus.
+    pub fn focus_changed(&mut self, window: &Window, focused: bool) {
+        if focused {
+            if let Some(video_mode) = self.suspended.take() {
+                window.set_fullscreen(Some(Fullscreen::Exclusive(video_mode)));
+            }
+        } else if let Some(Fullscreen::Exclusive(video_mode)) = window.fullscreen() {
+            window.set_fullscreen(None);
+            self.suspended = Some(video_mode);
+        }
+    }
+
+    /// Shows the display mode window.
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool, window: &Window) {
+        egui::Window::new("🖵 Display mode")
+            .open(open)
+            .resizable(false)
+            .show(ctx, |ui| self.ui(ui, window));
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, window: &Window) {
+        let monitors: Vec<MonitorHandle> = window.available_monitors().collect();
+        if self.monitor.is_none() {
+            self.monitor = window
+                .current_monitor()
+                .or_else(|| monitors.first().cloned());
+        }
+        let monitor = match self.monitor.clone() {
+            Some(monitor) => monitor,
+            None => {
+                ui.label("No monitor found.");
+                return;
+            }
+        };
+
+        let current = match window.fullscreen() {
+            None if self.suspended.is_some() => {
+                "Exclusive fullscreen (in the background)".to_owned()
+            }
+            None => "Windowed".to_owned(),
+            Some(Fullscreen::Borderless(_)) => "Borderless fullscreen".to_owned(),
+            Some(Fullscreen::Exclusive(video_mode)) => {
+                format!("Exclusive fullscreen, {}", video_mode_label(&video_mode))
+            }
+        };
+        ui.label(format!("Current: {}", current));
+        ui.separator();
+
+        egui::Grid::new("display_mode_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Monitor");
+                egui::ComboBox::from_id_source("display_mode_monitor")
+                    .width(260.0)
+                    .selected_text(monitor_label(&monitor))
+                    .show_ui(ui, |ui| {
+                        for candidate in &monitors {
+                            let selected = *candidate == monitor;
+                            if ui
+                                .selectable_label(selected, monitor_label(candidate))
+                                .clicked()
+                                && !selected
+                            {
+                                self.monitor = Some(candidate.clone());
+                                self.video_mode = None;
+                            }
+                        }
+                    });
+                ui.end_row();
+
+                // Largest and fastest modes first.
+                let mut video_modes: Vec<VideoMode> = monitor.video_modes().collect();
+                video_modes.sort_by_key(|mode| {
+                    let size = mode.size();
+                    std::cmp::Reverse((
+                        size.width,
+                        size.height,
+                        mode.refresh_rate_millihertz(),
+                        mode.bit_depth(),
+                    ))
+                });
+                if self.video_mode.is_none() {
+                    self.video_mode = video_modes.first().cloned();
+                }
+
+                ui.label("Video mode");
+                egui::ComboBox::from_id_source("display_mode_video_mode")
+                    .width(260.0)
+                    .selected_text(
+                        self.video_mode
+                            .as_ref()
+                            .map(video_mode_label)
+                            .unwrap_or_else(|| "None available".to_owned()),
+                    )
+                    .show_ui(ui, |ui| {
+                        for video_mode in video_modes {
+                            let label = video_mode_label(&video_mode);
+                            ui.selectable_value(&mut self.video_mode, Some(video_mode), label);
+                        }
+                    });
+                ui.end_row();
+            });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("Windowed").clicked() {
+                self.suspended = None;
+                window.set_fullscreen(None);
+            }
+            if ui.button("Borderless").clicked() {
+                self.suspended = None;
+                window.set_fullscreen(Some(Fullscreen::Borderless(Some(monitor.clone()))));
+            }
+            if ui
+                .add_enabled(self.video_mode.is_some(), egui::Button::new("Exclusive"))
+                .on_hover_text("Switches the monitor to the selected video mode")
+                .clicked()
+            {
+                self.suspended = None;
+                window.set_fullscreen(self.video_mode.clone().map(Fullscreen::Exclusive));
+            }
+        });
+
+        ui.separator();
+        scale_ui(ui, window, &monitor);
+    }
+}
+
+/// The scale factor and the size of the window, to tell blurry scaling apart from blurry
+/// rendering.
+fn scale_ui(ui: &mut egui::Ui, window: &Window, monitor: &MonitorHandle) {
+    let scale_factor = window.scale_factor();
+    let size = window.inner_size();
+    egui::Grid::new("display_mode_scale")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Window system");
+            ui.label(window_system(window));
+            ui.end_row();
+            ui.label("Scale factor");
+            ui.label(format!(
+                "{} (monitor {})",
+                scale_factor,
+                monitor.scale_factor()
+            ));
+            ui.end_row();
+            ui.label("Surface");
+            ui.label(format!(
+                "{} × {} px, {:.1} × {:.1} points",
+                size.width,
+                size.height,
+                size.width as f64 / scale_factor,
+                size.height as f64 / scale_factor
+            ));
+            ui.end_row();
+        });
+    if window_system(window) == "Wayland" {
+        ui.weak(
+            "winit 0.27 only gets integer scales from Wayland compositors. At 125 or 150 % they \
+             scale the buffer down, which blurs the text; --x11 renders at the fractional scale \
+             through XWayland.",
+        );
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn window_system(window: &Window) -> &'static str {
+    use winit::platform::unix::WindowExtUnix;
+    if window.wayland_display().is_some() {
+        "Wayland"
+    } else {
+        "X11"
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+fn window_system(_: &Window) -> &'static str {
+    std::env::consts::OS
+}
+
+pub fn monitor_label(monitor: &MonitorHandle) -> String {
+    let size = monitor.size();
+    format!(
+        "{} ({} × {})",
+        monitor
+            .name()
+            .unwrap_or_else(|| "Unknown monitor".to_owned()),
+        size.width,
+        size.height
+    )
+}
+
+fn video_mode_label(video_mode: &VideoMode) -> String {
+    let size = video_mode.size();
+    format!(
+        "{} × {} @ {:.2} Hz, {} bit",
+        size.width,
+        size.height,
+        video_mode.refresh_rate_millihertz() as f32 / 1000.0,
+        video_mode.bit_depth()
+    )
+}
@@ -0,0 +1,687 @@
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::assets::Assets;
+use crate::gizmo::{Gizmo, Mode, Transform};
+use crate::scene_graph::{self, Edit, HierarchyPanel, History, Name};
+use crate::scene_renderer::{self, Camera, Instance, SceneRenderer, SceneView};
+use crate::user_textures::UserTextures;
+use crate::workspaces::SaveState;
+
+/// How much a point of dragging turns the camera, in radians.
+const ORBIT_PER_POINT: f32 = 0.01;
+/// How much a point of scrolling moves the camera closer or further away.
+const DOLLY_PER_SCROLL_POINT: f32 = 0.002;
+/// The part of the viewport the picture-in-picture covers on each side.
+const PIP_FRACTION: f32 = 0.3;
+/// The space between the picture-in-picture and the corner of the viewport, in points.
+const PIP_MARGIN: f32 = 8.0;
+/// The asset that replaces the shader of the scene.
+const SHADER_ASSET: &str = "scene.wgsl";
+
+#[derive(Clone, Copy)]
+struct Material {
+    /// Linear RGB.
+    color: [f32; 3],
+    shininess: f32,
+}
+
+/// Turns an entity around its Y axis.
+struct Spin {
+    degrees_per_second: f32,
+}
+
+/// The orbit of the camera around the origin, the angles in radians.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Orbit {
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+}
+
+impl Default for Orbit {
+    fn default() -> Self {
+        Self {
+            yaw: 0.6,
+            pitch: 0.5,
+            distance: 9.0,
+        }
+    }
+}
+
+impl Orbit {
+    fn camera(&self) -> Camera {
+        Camera {
+            eye: self.distance
+                * Vec3::new(
+                    self.pitch.cos() * self.yaw.sin(),
+                    self.pitch.sin(),
+                    self.pitch.cos() * self.yaw.cos(),
+                ),
+            target: Vec3::ZERO,
+            fov: 45f32.to_radians(),
+        }
+    }
+
+    /// Turns the orbit by dragging `response`, and moves it closer by scrolling while `dolly`.
+    fn control(&mut self, ui: &egui::Ui, response: &egui::Response, dolly: bool) {
+        let delta = response.drag_delta();
+        self.yaw -= delta.x * ORBIT_PER_POINT;
+        self.pitch = (self.pitch + delta.y * ORBIT_PER_POINT).clamp(-1.5, 1.5);
+        if dolly {
+            let scroll = ui.input().scroll_delta.y;
+            self.distance =
+                (self.distance * (-scroll * DOLLY_PER_SCROLL_POINT).exp()).clamp(2.0, 50.0);
+        }
+    }
+}
+
+/// A camera of the scene, which the viewport or the picture-in-picture look through.
+struct SceneCamera {
+    name: &'static str,
+    orbit: Orbit,
+}
+
+fn cameras() -> Vec<SceneCamera> {
+    vec![
+        SceneCamera {
+            name: "Main",
+            orbit: Orbit::default(),
+        },
+        SceneCamera {
+            name: "Top",
+            orbit: Orbit {
+                yaw: 0.0,
+                pitch: 1.5,
+                distance: 14.0,
+            },
+        },
+        SceneCamera {
+            name: "Side",
+            orbit: Orbit {
+                yaw: std::f32::consts::FRAC_PI_2,
+                pitch: 0.05,
+                distance: 10.0,
+            },
+        },
+    ]
+}
+
+/// A scene whose entities live in a `hecs` world: the inspector edits their components with
+/// egui, a spin system animates them, and a [`SceneRenderer`] draws whatever the world holds
+/// each frame.
+pub struct EcsScene {
+    world: hecs::World,
+    selected: Option<hecs::Entity>,
+    /// How many entities were spawned, for the names of new ones.
+    spawned: u32,
+    /// The first one is the main camera, whose orbit the workspaces keep.
+    cameras: Vec<SceneCamera>,
+    /// The cameras the viewport and the picture-in-picture look through, `None` hides the
+    /// picture-in-picture.
+    viewport_camera: usize,
+    pip_camera: Option<usize>,
+    viewport: SceneView,
+    pip: SceneView,
+    gizmo: Gizmo,
+    history: History,
+    hierarchy: HierarchyPanel,
+    /// The time of the last spin update.
+    last_time: Option<f64>,
+    /// Created when the scene is first shown.
+    renderer: Option<SceneRenderer>,
+    /// The generation of the `scene.wgsl` asset the renderer draws with, `None` for the shader
+    /// of the example.
+    shader_generation: Option<u64>,
+}
+
+impl Default for EcsScene {
+    fn default() -> Self {
+        let mut world = hecs::World::new();
+        world.spawn((
+            Name("Ground".to_owned()),
+            Transform {
+                translation: Vec3::new(0.0, -0.55, 0.0),
+                rotation: Vec3::ZERO,
+                scale: Vec3::new(8.0, 0.1, 8.0),
+            },
+            Material {
+                color: [0.3, 0.32, 0.3],
+                shininess: 4.0,
+            },
+        ));
+        let mut scene = Self {
+            world,
+            selected: None,
+            spawned: 0,
+            cameras: cameras(),
+            viewport_camera: 0,
+            pip_camera: None,
+            viewport: SceneView::default(),
+            pip: SceneView::default(),
+            gizmo: Gizmo::default(),
+            history: History::default(),
+            hierarchy: HierarchyPanel::default(),
+            last_time: None,
+            renderer: None,
+            shader_generation: None,
+        };
+        for _ in 0..3 {
+            scene.spawn_cube();
+        }
+        scene
+    }
+}
+
+impl EcsScene {
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        textures: &mut UserTextures,
+        assets: &Assets,
+    ) {
+        if !*open {
+            self.last_time = None;
+            return;
+        }
+        self.spin(ctx);
+        egui::Window::new("🧊 ECS scene")
+            .open(open)
+            .default_size([720.0, 480.0])
+            .show(ctx, |ui| self.ui(ui, textures, assets));
+    }
+
+    /// The system that turns the entities with a [`Spin`].
+    fn spin(&mut self, ctx: &egui::Context) {
+        let time = ctx.input().time;
+        // No jump after the window was closed or the event loop slept.
+        let dt = self
+            .last_time
+            .map_or(0.0, |last_time| (time - last_time).min(0.1)) as f32;
+        self.last_time = Some(time);
+        let mut spinning = false;
+        for (transform, spin) in self.world.query_mut::<(&mut Transform, &Spin)>() {
+            transform.rotation.y = (transform.rotation.y + spin.degrees_per_second * dt) % 360.0;
+            spinning = true;
+        }
+        if spinning {
+            ctx.request_repaint();
+        }
+    }
+
+    fn spawn_cube(&mut self) {
+        self.spawned += 1;
+        // Spreads new cubes on a circle with hues around the color wheel.
+        let angle = self.spawned as f32 * 2.4;
+        let color = egui::Rgba::from(egui::color::Hsva::new(
+            angle / std::f32::consts::TAU % 1.0,
+            0.7,
+            0.9,
+            1.0,
+        ));
+        let entity = self.world.spawn((
+            Name(format!("Cube {}", self.spawned)),
+            Transform {
+                translation: Vec3::new(angle.cos() * 2.5, 0.0, angle.sin() * 2.5),
+                rotation: Vec3::ZERO,
+                scale: Vec3::ONE,
+            },
+            Material {
+                color: [color.r(), color.g(), color.b()],
+                shininess: 32.0,
+            },
+        ));
+        if self.spawned % 2 == 1 {
+            self.world
+                .insert_one(
+                    entity,
+                    Spin {
+                        degrees_per_second: 45.0,
+                    },
+                )
+                .expect("The entity was just spawned");
+        }
+        self.selected = Some(entity);
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, textures: &mut UserTextures, assets: &Assets) {
+        egui::SidePanel::left("ecs_entities")
+            .resizable(false)
+            .show_inside(ui, |ui| self.entities_ui(ui));
+        egui::SidePanel::right("ecs_inspector")
+            .resizable(false)
+            .min_width(200.0)
+            .show_inside(ui, |ui| self.inspector_ui(ui));
+        egui::TopBottomPanel::top("ecs_toolbar").show_inside(ui, |ui| self.toolbar_ui(ui));
+        egui::TopBottomPanel::bottom("ecs_parameters").show_inside(ui, |ui| {
+            self.cameras_ui(ui);
+            if let Some(renderer) = &mut self.renderer {
+                renderer.parameters_ui(ui);
+                ui.collapsing("🎨 Material", |ui| renderer.material_ui(ui));
+            }
+        });
+        self.viewport_ui(ui, textures, assets);
+    }
+
+    /// The gizmo mode, its snapping and the undo history.
+    fn toolbar_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let modes = [
+                (Mode::Translate, "✥ Move", "W"),
+                (Mode::Rotate, "⟳ Rotate", "E"),
+                (Mode::Scale, "⬌ Scale", "R"),
+            ];
+            for (mode, label, key) in modes {
+                ui.selectable_value(&mut self.gizmo.mode, mode, label)
+                    .on_hover_text(key);
+            }
+            ui.separator();
+            let snapping = &mut self.gizmo.snapping;
+            ui.checkbox(&mut snapping.enabled, "Snap");
+            ui.add_enabled_ui(snapping.enabled, |ui| {
+                let step = match self.gizmo.mode {
+                    Mode::Translate => egui::DragValue::new(&mut snapping.translation)
+                        .speed(0.05)
+                        .clamp_range(0.01..=10.0),
+                    Mode::Rotate => egui::DragValue::new(&mut snapping.rotation)
+                        .speed(1.0)
+                        .clamp_range(1.0..=180.0)
+                        .suffix("°"),
+                    Mode::Scale => egui::DragValue::new(&mut snapping.scale)
+                        .speed(0.01)
+                        .clamp_range(0.01..=10.0),
+                };
+                ui.add(step).on_hover_text("The step of the current mode");
+            });
+            ui.separator();
+            if ui
+                .add_enabled(self.history.can_undo(), egui::Button::new("⟲ Undo"))
+                .on_hover_text("Ctrl+Z")
+                .clicked()
+            {
+                self.undo();
+            }
+            if ui
+                .add_enabled(self.history.can_redo(), egui::Button::new("⟳ Redo"))
+                .on_hover_text("Ctrl+Y")
+                .clicked()
+            {
+                self.redo();
+            }
+        });
+    }
+
+    /// The shortcuts of the toolbar, while the pointer is over the viewport.
+    fn shortcuts(&mut self, ui: &egui::Ui) {
+        if ui.ctx().wants_keyboard_input() {
+            return;
+        }
+        let mut undo = false;
+        let mut redo = false;
+        for event in &ui.input().events {
+            if let egui::Event::Key {
+                key,
+                pressed: true,
+                modifiers,
+            } = event
+            {
+                match key {
+                    egui::Key::Z if modifiers.command && modifiers.shift => redo = true,
+                    egui::Key::Z if modifiers.command => undo = true,
+                    egui::Key::Y if modifiers.command => redo = true,
+                    egui::Key::W => self.gizmo.mode = Mode::Translate,
+                    egui::Key::E => self.gizmo.mode = Mode::Rotate,
+                    egui::Key::R => self.gizmo.mode = Mode::Scale,
+                    _ => {}
+                }
+            }
+        }
+        if undo {
+            self.undo();
+        }
+        if redo {
+            self.redo();
+        }
+    }
+
+    /// Undoes the last edit and selects its entity.
+    fn undo(&mut self) {
+        if let Some(entity) = self.history.undo(&mut self.world) {
+            self.selected = Some(entity);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(entity) = self.history.redo(&mut self.world) {
+            self.selected = Some(entity);
+        }
+    }
+
+    /// Which cameras the viewport and the picture-in-picture look through.
+    fn cameras_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let name = |index: usize| self.cameras[index].name;
+            ui.label("Camera:");
+            egui::ComboBox::from_id_source("ecs_viewport_camera")
+                .selected_text(name(self.viewport_camera))
+                .show_ui(ui, |ui| {
+                    for (index, camera) in self.cameras.iter().enumerate() {
+                        ui.selectable_value(&mut self.viewport_camera, index, camera.name);
+                    }
+                });
+            ui.label("Picture-in-picture:");
+            egui::ComboBox::from_id_source("ecs_pip_camera")
+                .selected_text(self.pip_camera.map_or("Off", name))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.pip_camera, None, "Off");
+                    for (index, camera) in self.cameras.iter().enumerate() {
+                        ui.selectable_value(&mut self.pip_camera, Some(index), camera.name);
+                    }
+                });
+            if ui
+                .button("Reset")
+                .on_hover_text("Puts the cameras back where they started")
+                .clicked()
+            {
+                self.cameras = cameras();
+            }
+        });
+    }
+
+    fn entities_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("➕ Spawn").clicked() {
+                self.spawn_cube();
+            }
+            let despawn = ui
+                .add_enabled(self.selected.is_some(), egui::Button::new("🗑 Despawn"))
+                .on_hover_text("Despawns the children too");
+            if let (Some(entity), true) = (self.selected, despawn.clicked()) {
+                for entity in scene_graph::subtree(&self.world, entity) {
+                    // The selection may have been despawned already, which is fine.
+                    let _ = self.world.despawn(entity);
+                }
+                self.selected = None;
+            }
+        });
+        ui.separator();
+        self.hierarchy
+            .ui(ui, &mut self.world, &mut self.selected, &mut self.history);
+    }
+
+    fn inspector_ui(&mut self, ui: &mut egui::Ui) {
+        let entity = match self.selected.filter(|entity| self.world.contains(*entity)) {
+            Some(entity) => entity,
+            None => {
+                ui.weak("Select an entity in the list or the viewport");
+                return;
+            }
+        };
+        let locked = scene_graph::is_locked(&self.world, entity);
+        let mut add_spin = false;
+        let mut remove_spin = false;
+        if let Ok((name, transform, material, spin)) = self.world.query_one_mut::<(
+            &mut Name,
+            &mut Transform,
+            Option<&mut Material>,
+            Option<&mut Spin>,
+        )>(entity)
+        {
+            ui.text_edit_singleline(&mut name.0);
+            ui.label(format!("Entity {}", entity.id()));
+
+            ui.separator();
+            ui.strong("Transform")
+                .on_hover_text("Relative to the parent");
+            ui.add_enabled_ui(!locked, |ui| {
+                egui::Grid::new("ecs_transform").show(ui, |ui| {
+                    vec3_ui(ui, "Translation", &mut transform.translation, 0.05);
+                    vec3_ui(ui, "Rotation", &mut transform.rotation, 1.0);
+                    vec3_ui(ui, "Scale", &mut transform.scale, 0.05);
+                });
+            });
+            transform.scale = transform.scale.max(Vec3::splat(0.01));
+
+            if let Some(material) = material {
+                ui.separator();
+                ui.strong("Material");
+                ui.horizontal(|ui| {
+                    ui.label("Color");
+                    ui.color_edit_button_rgb(&mut material.color);
+                });
+                ui.add(
+                    egui::Slider::new(&mut material.shininess, 1.0..=256.0)
+                        .logarithmic(true)
+                        .text("Shininess"),
+                );
+            }
+
+            ui.separator();
+            match spin {
+                Some(spin) => {
+                    ui.horizontal(|ui| {
+                        ui.strong("Spin");
+                        remove_spin = ui.small_button("Remove").clicked();
+                    });
+                    ui.add(
+                        egui::Slider::new(&mut spin.degrees_per_second, -360.0..=360.0)
+                            .suffix("°/s"),
+                    );
+                }
+                None => add_spin = ui.button("Add spin").clicked(),
+            }
+        }
+        // The world can't change its archetypes while the query borrows it.
+        if add_spin {
+            let _ = self.world.insert_one(
+                entity,
+                Spin {
+                    degrees_per_second: 45.0,
+                },
+            );
+        }
+        if remove_spin {
+            let _ = self.world.remove_one::<Spin>(entity);
+        }
+    }
+
+    fn viewport_ui(&mut self, ui: &mut egui::Ui, textures: &mut UserTextures, assets: &Assets) {
+        let rect = egui::Rect::from_min_size(ui.cursor().min, ui.available_size());
+        // Interacts before the viewport below it, which would take the drags otherwise.
+        let pip = self.pip_camera.map(|camera| {
+            let rect = egui::Rect::from_min_size(
+                rect.max - rect.size() * PIP_FRACTION - egui::Vec2::splat(PIP_MARGIN),
+                rect.size() * PIP_FRACTION,
+            );
+            let response = ui.interact(rect, ui.id().with("pip"), egui::Sense::click_and_drag());
+            (camera, rect, response)
+        });
+        // Scrolling over the picture-in-picture only moves its camera.
+        let pip_hovered = pip.as_ref().is_some_and(|(_, _, pip)| pip.hovered());
+        let gizmo_camera = self.cameras[self.viewport_camera].orbit.camera();
+        // Hidden and locked entities have no gizmo.
+        let selected = self.selected.filter(|entity| {
+            self.world.contains(*entity)
+                && !scene_graph::is_hidden(&self.world, *entity)
+                && !scene_graph::is_locked(&self.world, *entity)
+        });
+        let space = selected.map(|entity| scene_graph::parent_matrix(&self.world, entity));
+        if let (Some(entity), Some(space)) = (selected, space) {
+            let moved = self
+                .world
+                .get::<&mut Transform>(entity)
+                .ok()
+                .and_then(|mut transform| {
+                    let before = self.gizmo.interact(
+                        ui,
+                        rect,
+                        &gizmo_camera,
+                        space,
+                        &mut transform,
+                        !pip_hovered,
+                    )?;
+                    Some(Edit::Transform {
+                        entity,
+                        before,
+                        after: *transform,
+                    })
+                });
+            if let Some(edit) = moved {
+                self.history.push(edit);
+            }
+        }
+        let (rect, response) = ui.allocate_exact_size(rect.size(), egui::Sense::click_and_drag());
+        if response.hovered() {
+            self.shortcuts(ui);
+        }
+        let orbit = &mut self.cameras[self.viewport_camera].orbit;
+        orbit.control(ui, &response, response.hovered() && !pip_hovered);
+        let camera = orbit.camera();
+        if let (true, Some(pointer)) = (response.clicked(), response.interact_pointer_pos()) {
+            self.selected = self.pick(camera, rect, pointer);
+        }
+        let pip = pip.map(|(index, pip_rect, pip_response)| {
+            let orbit = &mut self.cameras[index].orbit;
+            orbit.control(ui, &pip_response, pip_response.hovered());
+            let camera = orbit.camera();
+            if let (true, Some(pointer)) =
+                (pip_response.clicked(), pip_response.interact_pointer_pos())
+            {
+                self.selected = self.pick(camera, pip_rect, pointer);
+            }
+            (camera, pip_rect)
+        });
+
+        let instances: Vec<Instance> = self
+            .world
+            .query::<(hecs::Entity, &Material)>()
+            .iter()
+            .filter(|(entity, _)| !scene_graph::is_hidden(&self.world, *entity))
+            .map(|(entity, material)| Instance {
+                model: scene_graph::world_matrix(&self.world, entity),
+                color: material.color,
+                shininess: material.shininess,
+                highlighted: self.selected == Some(entity),
+            })
+            .collect();
+        let renderer = self
+            .renderer
+            .get_or_insert_with(|| SceneRenderer::new(textures.device, textures.queue));
+        // A shader that doesn't fit keeps the last one, until the asset changes again.
+        let shader = assets.shader(SHADER_ASSET);
+        if shader.map(|(generation, _)| generation) != self.shader_generation {
+            let source = shader.map_or(scene_renderer::SHADER, |(_, source)| source);
+            if let Err(err) = renderer.set_shader(textures.device, source) {
+                eprintln!("Can't draw the scene with {}: {}", SHADER_ASSET, err);
+            }
+            self.shader_generation = shader.map(|(generation, _)| generation);
+        }
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        let physical_size = |rect: egui::Rect| {
+            let size = (rect.size() * pixels_per_point).round();
+            [size.x as u32, size.y as u32]
+        };
+        let clear_color = wgpu::Color {
+            r: 0.05,
+            g: 0.06,
+            b: 0.08,
+            a: 1.0,
+        };
+        let texture = renderer.render(
+            &mut self.viewport,
+            textures,
+            physical_size(rect),
+            camera,
+            clear_color,
+            &instances,
+        );
+        ui.painter().add(egui::Shape::image(
+            texture,
+            rect,
+            egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0)),
+            egui::Color32::WHITE,
+        ));
+        if let (Some(transform), Some(space)) = (
+            selected
+                .and_then(|entity| self.world.get::<&Transform>(entity).ok())
+                .map(|transform| *transform),
+            space,
+        ) {
+            self.gizmo
+                .paint(ui.painter(), rect, &camera, space, &transform);
+        }
+        match pip {
+            Some((camera, pip_rect)) => {
+                let texture = renderer.render(
+                    &mut self.pip,
+                    textures,
+                    physical_size(pip_rect),
+                    camera,
+                    clear_color,
+                    &instances,
+                );
+                egui::Image::new(texture, pip_rect.size()).paint_at(ui, pip_rect);
+                ui.painter().rect_stroke(
+                    pip_rect,
+                    0.0,
+                    ui.visuals().widgets.noninteractive.fg_stroke,
+                );
+            }
+            None => self.pip.free(textures),
+        }
+    }
+
+    /// The nearest entity under `pointer`, by intersecting the ray through it with the boxes.
+    /// Hidden and locked entities can't be picked.
+    fn pick(&self, camera: Camera, rect: egui::Rect, pointer: egui::Pos2) -> Option<hecs::Entity> {
+        let ndc = (pointer - rect.min) / rect.size() * 2.0 - egui::Vec2::splat(1.0);
+        let inverse = camera.view_projection(rect.size()).inverse();
+        let near = inverse.project_point3(Vec3::new(ndc.x, -ndc.y, 0.0));
+        let far = inverse.project_point3(Vec3::new(ndc.x, -ndc.y, 1.0));
+        let mut query = self.world.query::<(hecs::Entity, &Transform)>();
+        query
+            .iter()
+            .filter(|(entity, _)| {
+                !scene_graph::is_hidden(&self.world, *entity)
+                    && !scene_graph::is_locked(&self.world, *entity)
+            })
+            .filter_map(|(entity, _)| {
+                // In the space of the box, where it spans -0.5 to 0.5 on each axis.
+                let to_local = scene_graph::world_matrix(&self.world, entity).inverse();
+                let origin = to_local.transform_point3(near);
+                let direction = to_local.transform_point3(far) - origin;
+                let t0 = (Vec3::splat(-0.5) - origin) / direction;
+                let t1 = (Vec3::splat(0.5) - origin) / direction;
+                let enter = t0.min(t1).max_element();
+                let exit = t0.max(t1).min_element();
+                (enter <= exit && exit >= 0.0).then_some((entity, enter))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity)
+    }
+}
+
+/// A row of the transform grid.
+fn vec3_ui(ui: &mut egui::Ui, label: &str, value: &mut Vec3, speed: f32) {
+    ui.label(label);
+    ui.horizontal(|ui| {
+        ui.add(egui::DragValue::new(&mut value.x).speed(speed));
+        ui.add(egui::DragValue::new(&mut value.y).speed(speed));
+        ui.add(egui::DragValue::new(&mut value.z).speed(speed));
+    });
+    ui.end_row();
+}
+
+impl SaveState for EcsScene {
+    type State = Orbit;
+
+    fn save_state(&self) -> Orbit {
+        self.cameras[0].orbit
+    }
+
+    fn restore_state(&mut self, orbit: Orbit) {
+        self.cameras[0].orbit = orbit;
+    }
+}
@@ -0,0 +1,101 @@
+use std::time::Instant;
+
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::platform::run_return::EventLoopExtRunReturn;
+
+use crate::runner::{ExternalGpu, RenderTarget, Runner};
+use crate::Event;
+
+/// What the host should do after [`Embedded::pump`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControlFlowDecision {
+    /// Pump again right away, e.g. while egui animates.
+    Poll,
+    /// Pump again at this time, or earlier if the host has a reason to.
+    WaitUntil(Instant),
+    /// Nothing to do until there is new input. Pumping regularly anyway is fine.
+    Wait,
+    /// The window was closed, the example is done.
+    Exit,
+}
+
+/// The example for host applications that own the main loop, like a plugin inside a DAW or an
+/// existing engine. Instead of handing the process to `run`, the host calls [`Embedded::pump`]
+/// whenever it has time, which handles the pending window events and draws a frame if one is due.
+///
+/// Not available on Android and iOS, where the operating system owns the main loop.
+pub struct Embedded {
+    event_loop: EventLoop<Event>,
+    runner: Runner,
+    exited: bool,
+}
+
+impl Embedded {
+    /// Creates the window and the GPU objects, like `run`.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self::create(None)
+    }
+
+    /// Creates the window and renders with the GPU objects of the host, like `run_with_device`.
+    pub fn with_device(gpu: ExternalGpu) -> Self {
+        Self::create(Some(gpu))
+    }
+
+    fn create(gpu: Option<ExternalGpu>) -> Self {
+        let options = crate::options::Options::from_args();
+        crate::paths::init(options.config_dir.clone());
+        let event_loop = crate::create_event_loop(&options);
+        let runner = crate::create_runner(&event_loop, options, gpu);
+        Self {
+            event_loop,
+            runner,
+            exited: false,
+        }
+    }
+
+    /// Handles the pending events, draws a frame if one is due, and returns without waiting for
+    /// more events.
+    ///
+    /// On Windows and macOS this doesn't return while the user resizes the window, see the
+    /// caveats of winit's `run_return`.
+    pub fn pump(&mut self) -> ControlFlowDecision {
+        if self.exited {
+            return ControlFlowDecision::Exit;
+        }
+        let runner = &mut self.runner;
+        let exited = &mut self.exited;
+        let mut decision = ControlFlow::Poll;
+        self.event_loop.run_return(|event, target, control_flow| {
+            let cleared = matches!(event, winit::event::Event::RedrawEventsCleared);
+            // Like in the event loop of winit, an exit can't be revoked.
+            if !*exited {
+                runner.handle_event(event, target, &mut decision);
+                *exited = matches!(decision, ControlFlow::ExitWithCode(_));
+            }
+            // Return to the host once all pending events are handled.
+            *control_flow = match cleared || *exited {
+                true => ControlFlow::Exit,
+                false => ControlFlow::Poll,
+            };
+        });
+        match (self.exited, decision) {
+            (true, _) | (_, ControlFlow::ExitWithCode(_)) => ControlFlowDecision::Exit,
+            (false, ControlFlow::Poll) => ControlFlowDecision::Poll,
+            (false, ControlFlow::Wait) => ControlFlowDecision::Wait,
+            (false, ControlFlow::WaitUntil(instant)) => ControlFlowDecision::WaitUntil(instant),
+        }
+    }
+
+    /// Runs one frame of the UI and renders it into a texture of the host, e.g. to show the UI as
+    /// a layer in its renderer. Fails before the first [`Embedded::pump`] created the device, or
+    /// if the format of `target` doesn't match.
+    pub fn render_to(&mut self, target: &RenderTarget) -> Result<(), String> {
+        self.runner.render_to(target)
+    }
+
+    /// The format the UI is rendered with, once the device exists.
+    pub fn format(&self) -> Option<wgpu::TextureFormat> {
+        self.runner.format()
+    }
+}
@@ -0,0 +1,234 @@
+use std::path::Path;
+
+/// Fonts with outline emoji glyphs installed by the operating system. egui's rasterizer only
+/// supports outlines, so the bitmap color fonts like Noto Color Emoji or Apple Color Emoji can't
+/// be used. Segoe UI Emoji also contains the monochrome outlines below its color layers.
+const SYSTEM_EMOJI_FONTS: &[&str] = &[
+    "C:\\Windows\\Fonts\\seguiemj.ttf",
+    "C:\\Windows\\Fonts\\seguisym.ttf",
+    "/System/Library/Fonts/Apple Symbols.ttf",
+    "/usr/share/fonts/truetype/ancient-scripts/Symbola_hint.ttf",
+    "/usr/share/fonts/TTF/Symbola.ttf",
+    "/usr/share/fonts/gdouros-symbola/Symbola.ttf",
+];
+
+const SAMPLES: &[&str] = &[
+    "Hello 👋 world 🌍",
+    "Rust 🦀 + egui ✨ = 🎉",
+    "Skin tones: 👍 👍🏻 👍🏽 👍🏿",
+    "ZWJ sequences: 👩‍💻 👨‍👩‍👧 🏳️‍🌈 ❤️‍🔥",
+    "Flags: 🇩🇪 🇯🇵 🇧🇷 🇺🇦",
+    "Keycaps: 1️⃣ 2️⃣ #️⃣",
+    "Newer emoji: 🫠 🫶 🪿 🫎",
+];
+
+/// egui's default fonts, with the emoji fonts of the system as fallback for the glyphs the
+/// bundled Noto Emoji doesn't cover.
+pub fn font_definitions() -> egui::FontDefinitions {
+    let mut fonts = egui::FontDefinitions::default();
+    for path in SYSTEM_EMOJI_FONTS.iter().map(Path::new) {
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(_) => continue,
+        };
+        let name = path.display().to_string();
+        fonts
+            .font_data
+            .insert(name.clone(), egui::FontData::from_owned(data));
+        for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+            fonts.families.entry(family).or_default().push(name.clone());
+        }
+    }
+    fonts
+}
+
+/// A window that shows emoji mixed with text, rendered with the fonts and, with the
+/// `color-emoji` feature, as color images.
+pub struct EmojiDemo {
+    text: String,
+    #[cfg(feature = "color-emoji")]
+    color: ColorEmoji,
+}
+
+impl Default for EmojiDemo {
+    fn default() -> Self {
+        Self {
+            text: "Type some emoji here 🙂".to_owned(),
+            #[cfg(feature = "color-emoji")]
+            color: Default::default(),
+        }
+    }
+}
+
+impl EmojiDemo {
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        egui::Window::new("😀 Emoji")
+            .open(open)
+            .default_width(420.0)
+            .show(ctx, |ui| self.ui(ui));
+    }
+
+    /// A copy with the same text, for laying out in another context. The color emoji textures
+    /// belong to the context they were loaded in.
+    #[cfg(feature = "pdf")]
+    pub fn for_print(&self) -> Self {
+        Self {
+            text: self.text.clone(),
+            #[cfg(feature = "color-emoji")]
+            color: Default::default(),
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Font glyphs");
+        ui.label("Monochrome, from egui's bundled fonts and the emoji fonts of the system.");
+        for sample in SAMPLES {
+            ui.label(*sample);
+        }
+
+        ui.separator();
+        ui.heading("Color");
+        #[cfg(feature = "color-emoji")]
+        {
+            ui.label("Twemoji images, laid out between the text.");
+            for sample in SAMPLES {
+                self.color.label(ui, sample);
+            }
+        }
+        #[cfg(not(feature = "color-emoji"))]
+        ui.weak("Build with `--features color-emoji` to render emoji as color images.");
+
+        ui.separator();
+        ui.heading("Text edit");
+        ui.add(egui::TextEdit::multiline(&mut self.text).desired_rows(2));
+        #[cfg(feature = "color-emoji")]
+        self.color.label(ui, &self.text);
+    }
+}
+
+/// Renders the emoji in a text as Twemoji images. The images are decoded and uploaded on first
+/// use.
+#[cfg(feature = "color-emoji")]
+#[derive(Default)]
+struct ColorEmoji {
+    textures: std::collections::HashMap<&'static str, Option<egui::TextureHandle>>,
+}
+
+#[cfg(feature = "color-emoji")]
+impl ColorEmoji {
+    fn label(&mut self, ui: &mut egui::Ui, text: &str) {
+        let size = ui.text_style_height(&egui::TextStyle::Body);
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+            for segment in segments(text) {
+                match segment {
+                    Segment::Text(text) => {
+                        ui.label(text);
+                    }
+                    Segment::Emoji(asset) => match self.texture(ui.ctx(), asset) {
+                        Some(texture) => {
+                            let response = ui.image(texture.id(), egui::vec2(size, size));
+                            if let Some(label) = asset.label.filter(|label| !label.is_empty()) {
+                                response.on_hover_text(label);
+                            }
+                        }
+                        None => {
+                            ui.label(asset.emoji);
+                        }
+                    },
+                }
+            }
+        });
+    }
+
+    fn texture(
+        &mut self,
+        ctx: &egui::Context,
+        asset: &'static twemoji_assets::png::PngTwemojiAsset,
+    ) -> Option<&egui::TextureHandle> {
+        self.textures
+            .entry(asset.emoji)
+            .or_insert_with(|| {
+                let image = image::load_from_memory_with_format(asset, image::ImageFormat::Png)
+                    .map_err(|err| eprintln!("Can't decode emoji {}: {}", asset.emoji, err))
+                    .ok()?
+                    .to_rgba8();
+                let size = [image.width() as usize, image.height() as usize];
+                let image = egui::ColorImage::from_rgba_unmultiplied(size, &image);
+                Some(ctx.load_texture(asset.emoji, image, egui::TextureFilter::Linear))
+            })
+            .as_ref()
+    }
+}
+
+#[cfg(feature = "color-emoji")]
+enum Segment<'a> {
+    Text(&'a str),
+    Emoji(&'static twemoji_assets::png::PngTwemojiAsset),
+}
+
+/// The longest emoji sequence that is tried, in chars. Family ZWJ sequences with skin tones are
+/// the longest ones.
+#[cfg(feature = "color-emoji")]
+const MAX_SEQUENCE_CHARS: usize = 10;
+
+/// Splits `text` into runs of text and the emoji between them, preferring the longest matching
+/// sequence, so that ZWJ sequences, flags and skin tones become a single image.
+#[cfg(feature = "color-emoji")]
+fn segments(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut index = 0;
+    while index < text.len() {
+        let ends: Vec<usize> = text[index..]
+            .char_indices()
+            .skip(1)
+            .map(|(offset, _)| index + offset)
+            .chain(std::iter::once(text.len()))
+            .take(MAX_SEQUENCE_CHARS)
+            .collect();
+        // Single ASCII chars like digits are only emoji as part of a keycap sequence.
+        let min_chars = if text.as_bytes()[index].is_ascii() {
+            2
+        } else {
+            1
+        };
+        let found = ends.iter().enumerate().rev().find_map(|(chars, &end)| {
+            if chars + 1 < min_chars {
+                return None;
+            }
+            lookup(&text[index..end]).map(|asset| (asset, end))
+        });
+        match found {
+            Some((asset, mut end)) => {
+                if text_start < index {
+                    segments.push(Segment::Text(&text[text_start..index]));
+                }
+                segments.push(Segment::Emoji(asset));
+                // Single emoji are stored without the variation selector.
+                if text[end..].starts_with('\u{FE0F}') {
+                    end += '\u{FE0F}'.len_utf8();
+                }
+                index = end;
+                text_start = end;
+            }
+            None => index = ends[0],
+        }
+    }
+    if text_start < text.len() {
+        segments.push(Segment::Text(&text[text_start..]));
+    }
+    segments
+}
+
+/// Twemoji stores some sequences, like keycaps, without the variation selectors.
+#[cfg(feature = "color-emoji")]
+fn lookup(sequence: &str) -> Option<&'static twemoji_assets::png::PngTwemojiAsset> {
+    twemoji_assets::png::PngTwemojiAsset::from_emoji(sequence).or_else(|| {
+        if sequence.contains('\u{FE0F}') {
+            twemoji_assets::png::PngTwemojiAsset::from_emoji(&sequence.replace('\u{FE0F}', ""))
+        } else {
+            None
+        }
+    })
+}
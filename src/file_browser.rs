@@ -0,0 +1,238 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::thumbnails::{Thumbnail, Thumbnails};
+use crate::user_textures::UserTextures;
+
+/// The size of a cell of the grid, with the thumbnail and the name below it.
+const CELL: egui::Vec2 = egui::vec2(
+    crate::thumbnails::SIZE as f32 + 16.0,
+    crate::thumbnails::SIZE as f32 + 28.0,
+);
+
+/// The most characters of a name under a thumbnail.
+const NAME_CHARS: usize = 20;
+
+/// A file or directory in the grid.
+struct Entry {
+    path: PathBuf,
+    name: String,
+    is_dir: bool,
+    modified: Option<SystemTime>,
+}
+
+impl Entry {
+    /// Whether the file is an image the `image` crate is built to decode.
+    fn is_image(&self) -> bool {
+        let extension = self
+            .path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        !self.is_dir && matches!(extension.as_deref(), Some("png" | "jpg" | "jpeg"))
+    }
+}
+
+/// Browses the directories in a grid, with the thumbnails of the images. Clicking a file opens it
+/// in the tool that shows it.
+pub struct FileBrowser {
+    dir: PathBuf,
+    /// The directory in the text field, which is only gone to on Enter.
+    path: String,
+    /// Read when the directory is shown the first time.
+    entries: Option<Result<Vec<Entry>, String>>,
+    thumbnails: Thumbnails,
+}
+
+impl Default for FileBrowser {
+    fn default() -> Self {
+        let dir = dirs::picture_dir()
+            .or_else(dirs::home_dir)
+            .unwrap_or_else(|| PathBuf::from("."));
+        Self {
+            path: dir.display().to_string(),
+            dir,
+            entries: None,
+            thumbnails: Default::default(),
+        }
+    }
+}
+
+impl FileBrowser {
+    /// Returns the file that was clicked, to open it.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        textures: &mut UserTextures,
+    ) -> Option<PathBuf> {
+        self.thumbnails.poll(textures);
+        egui::Window::new("📁 File browser")
+            .open(open)
+            .default_size([640.0, 480.0])
+            .show(ctx, |ui| self.ui(ui, textures))
+            .and_then(|response| response.inner)
+            .flatten()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, textures: &mut UserTextures) -> Option<PathBuf> {
+        let mut go_to = None;
+        ui.horizontal(|ui| {
+            let parent = self.dir.parent().map(Path::to_owned);
+            if ui
+                .add_enabled(parent.is_some(), egui::Button::new("⬆"))
+                .on_hover_text("Up")
+                .clicked()
+            {
+                go_to = parent;
+            }
+            if ui.button("⟳").on_hover_text("Read again").clicked() {
+                self.entries = None;
+            }
+            let response =
+                ui.add(egui::TextEdit::singleline(&mut self.path).desired_width(f32::INFINITY));
+            if response.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
+                go_to = Some(PathBuf::from(&self.path));
+            }
+        });
+        ui.separator();
+
+        let dir = &self.dir;
+        let entries = match self.entries.get_or_insert_with(|| read_dir(dir)) {
+            Ok(entries) => entries,
+            Err(err) => {
+                ui.colored_label(ui.visuals().error_fg_color, err.as_str());
+                if let Some(dir) = go_to {
+                    self.go_to(dir, textures);
+                }
+                return None;
+            }
+        };
+        let columns = ((ui.available_width() / CELL.x) as usize).max(1);
+        let rows = entries.len().div_ceil(columns);
+        let mut clicked = None;
+        egui::ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show_rows(ui, CELL.y, rows, |ui, rows| {
+                for row in rows {
+                    let start = row * columns;
+                    let end = (start + columns).min(entries.len());
+                    ui.horizontal(|ui| {
+                        for entry in &entries[start..end] {
+                            if cell(ui, entry, &mut self.thumbnails, textures).clicked() {
+                                clicked = Some(entry);
+                            }
+                        }
+                    });
+                }
+            });
+
+        let mut opened = None;
+        match clicked {
+            Some(entry) if entry.is_dir => go_to = Some(entry.path.clone()),
+            Some(entry) => opened = Some(entry.path.clone()),
+            None => {}
+        }
+        if let Some(dir) = go_to {
+            self.go_to(dir, textures);
+        }
+        opened
+    }
+
+    fn go_to(&mut self, dir: PathBuf, textures: &mut UserTextures) {
+        self.path = dir.display().to_string();
+        self.dir = dir;
+        self.entries = None;
+        self.thumbnails.clear(textures);
+    }
+}
+
+/// The directories first, then the files, each sorted by name. Hidden ones are left out.
+fn read_dir(dir: &Path) -> Result<Vec<Entry>, String> {
+    let read =
+        std::fs::read_dir(dir).map_err(|err| format!("Can't read {}: {}", dir.display(), err))?;
+    let mut entries: Vec<Entry> = read
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') {
+                return None;
+            }
+            // Follows symbolic links, unlike the metadata of the entry.
+            let metadata = std::fs::metadata(entry.path()).ok();
+            Some(Entry {
+                path: entry.path(),
+                name,
+                is_dir: metadata.as_ref().is_some_and(|metadata| metadata.is_dir()),
+                modified: metadata.and_then(|metadata| metadata.modified().ok()),
+            })
+        })
+        .collect();
+    entries.sort_by_cached_key(|entry| (!entry.is_dir, entry.name.to_lowercase()));
+    Ok(entries)
+}
+
+/// Draws `entry` in a cell of the grid: its thumbnail or an icon, and its name.
+fn cell(
+    ui: &mut egui::Ui,
+    entry: &Entry,
+    thumbnails: &mut Thumbnails,
+    textures: &UserTextures,
+) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(CELL, egui::Sense::click());
+    let response = response.on_hover_text(entry.path.display().to_string());
+    if !ui.is_rect_visible(rect) {
+        return response;
+    }
+    let painter = ui.painter_at(rect);
+    if response.hovered() {
+        painter.rect_filled(rect, 4.0, ui.visuals().widgets.hovered.bg_fill);
+    }
+    let side = crate::thumbnails::SIZE as f32;
+    let image_rect = egui::Rect::from_center_size(
+        egui::pos2(rect.center().x, rect.top() + 4.0 + side / 2.0),
+        egui::Vec2::splat(side),
+    );
+    let thumbnail = match (entry.is_image(), entry.modified) {
+        (true, Some(modified)) => thumbnails.get(ui.ctx(), textures, &entry.path, modified),
+        _ => Thumbnail::Failed,
+    };
+    match thumbnail {
+        Thumbnail::Ready(id, size) => {
+            let uv = egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0));
+            let image_rect = egui::Rect::from_center_size(image_rect.center(), size);
+            painter.add(egui::Shape::image(id, image_rect, uv, egui::Color32::WHITE));
+        }
+        Thumbnail::Loading => {
+            ui.put(
+                egui::Rect::from_center_size(image_rect.center(), egui::Vec2::splat(24.0)),
+                egui::Spinner::new(),
+            );
+        }
+        Thumbnail::Failed => {
+            painter.text(
+                image_rect.center(),
+                egui::Align2::CENTER_CENTER,
+                if entry.is_dir { "📁" } else { "📄" },
+                egui::FontId::proportional(48.0),
+                ui.visuals().text_color(),
+            );
+        }
+    }
+    let name = match entry.name.chars().count() > NAME_CHARS {
+        true => entry
+            .name
+            .chars()
+            .take(NAME_CHARS - 1)
+            .chain(['…'])
+            .collect(),
+        false => entry.name.clone(),
+    };
+    painter.text(
+        egui::pos2(rect.center().x, image_rect.bottom() + 4.0),
+        egui::Align2::CENTER_TOP,
+        name,
+        egui::FontId::proportional(12.0),
+        ui.visuals().text_color(),
+    );
+    response
+}
@@ -0,0 +1,266 @@
+use egui::epaint::{ClippedShape, Shape};
+
+/// The outline of the focused widget while the focus order is recorded, to find it among the
+/// shapes of the frame.
+const MARKER: egui::Color32 = egui::Color32::from_rgb(255, 0, 255);
+
+/// Where the recording gives up, for UIs that never lead the focus back to the start.
+const MAX_STEPS: usize = 500;
+
+/// The frames in a row without a focused widget, before the recording gives up.
+const MAX_UNFOCUSED_FRAMES: u32 = 3;
+
+/// A widget in the focus order.
+struct Step {
+    id: egui::Id,
+    /// Where it was while the order was recorded. Unknown for widgets that draw no outline.
+    rect: Option<egui::Rect>,
+    description: String,
+}
+
+/// Records the order in which Tab moves the keyboard focus through the UI, and draws it on top
+/// of the UI, with the rect of each widget and its number.
+///
+/// egui keeps the focus order to itself, so it's recorded by pressing Tab once per frame until
+/// the focus comes back to the first widget, and looking for the widget that got it each time:
+/// egui outlines the focused widget with the active visuals or, for text fields, the selection
+/// stroke, which are set to a marker color meanwhile.
+#[derive(Default)]
+pub struct FocusDebug {
+    steps: Vec<Step>,
+    recording: bool,
+    /// The frames in a row that nothing had the focus, while recording.
+    unfocused_frames: u32,
+    show_overlay: bool,
+    status: Option<String>,
+}
+
+impl FocusDebug {
+    /// Outlines the focused widget with the marker, while recording.
+    pub fn mark_focus(&self, style: &mut egui::Style) {
+        if self.recording {
+            let marker = egui::Stroke::new(2.0, MARKER);
+            style.visuals.widgets.active.bg_stroke = marker;
+            style.visuals.selection.stroke = marker;
+        }
+    }
+
+    /// Presses Tab in the next frame, while recording. Called before the frame starts.
+    pub fn next_frame(&mut self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
+        if !self.recording {
+            return;
+        }
+        // The first Tab goes to the first widget once nothing has the focus.
+        if self.steps.is_empty() {
+            let focused = ctx.memory().focus();
+            if let Some(id) = focused {
+                ctx.memory().surrender_focus(id);
+            }
+        }
+        for pressed in [true, false] {
+            raw_input.events.push(egui::Event::Key {
+                key: egui::Key::Tab,
+                pressed,
+                modifiers: Default::default(),
+            });
+        }
+    }
+
+    /// Takes note of the widget that got the focus in the frame that was just drawn.
+    pub fn observe(&mut self, ctx: &egui::Context, output: &egui::FullOutput) {
+        if !self.recording {
+            return;
+        }
+        let id = match ctx.memory().focus() {
+            Some(id) => id,
+            None => {
+                self.unfocused_frames += 1;
+                if self.unfocused_frames > MAX_UNFOCUSED_FRAMES {
+                    self.finish(format!(
+                        "The focus went nowhere after {} widgets",
+                        self.steps.len()
+                    ));
+                }
+                return;
+            }
+        };
+        self.unfocused_frames = 0;
+        if self.steps.first().is_some_and(|first| first.id == id) {
+            self.finish(format!("{} widgets take the focus", self.steps.len()));
+            return;
+        }
+        if let Some(last) = self.steps.last() {
+            if last.id == id {
+                let message = format!("Tab doesn't leave {}, it locks the focus", last.description);
+                self.finish(message);
+                return;
+            }
+        }
+        let description = output
+            .platform_output
+            .events
+            .iter()
+            .find_map(|event| match event {
+                egui::output::OutputEvent::FocusGained(info) => Some(info.description()),
+                _ => None,
+            })
+            .unwrap_or_else(|| format!("{:?}", id));
+        let mut rect = None;
+        for ClippedShape(_, shape) in &output.shapes {
+            find_marker(shape, &mut rect);
+        }
+        self.steps.push(Step {
+            id,
+            rect,
+            description,
+        });
+        if self.steps.len() == MAX_STEPS {
+            self.finish(format!("Stopped after {} widgets", MAX_STEPS));
+        }
+    }
+
+    fn finish(&mut self, status: String) {
+        self.recording = false;
+        self.status = Some(status);
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!self.recording, egui::Button::new("⏺ Record focus order"))
+                .on_hover_text("Presses Tab until the focus comes back to the first widget")
+                .clicked()
+            {
+                self.steps.clear();
+                self.unfocused_frames = 0;
+                self.status = None;
+                self.recording = true;
+                self.show_overlay = true;
+            }
+            ui.checkbox(&mut self.show_overlay, "Show on top of the UI");
+        });
+        ui.weak("The rects are where the widgets were while recording.");
+        if self.recording {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(format!("Recording, {} widgets so far", self.steps.len()));
+            });
+            ui.ctx().request_repaint();
+        }
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+        let focused = ui.memory().focus();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("focus_order").striped(true).show(ui, |ui| {
+                for (index, step) in self.steps.iter().enumerate() {
+                    ui.label(format!("{}", index + 1));
+                    let text = egui::RichText::new(&step.description);
+                    ui.label(match focused == Some(step.id) {
+                        true => text.strong(),
+                        false => text,
+                    });
+                    if step.rect.is_none() {
+                        ui.weak("no outline");
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    /// Draws the recorded order on top of everything: the rects, their numbers and arrows from
+    /// one to the next. The widget that has the focus now is highlighted.
+    pub fn overlay(&self, ctx: &egui::Context) {
+        if !self.show_overlay {
+            return;
+        }
+        let painter = ctx.debug_painter();
+        let focused = ctx.memory().focus();
+        let color = egui::Color32::from_rgb(0, 160, 255);
+        let mut previous: Option<egui::Pos2> = None;
+        for (index, step) in self.steps.iter().enumerate() {
+            let rect = match step.rect {
+                Some(rect) => rect,
+                None => continue,
+            };
+            let width = if focused == Some(step.id) { 3.0 } else { 1.0 };
+            painter.rect_stroke(rect, 2.0, (width, color));
+            if let Some(from) = previous {
+                painter.arrow(
+                    from,
+                    rect.center() - from,
+                    egui::Stroke::new(1.0, color.linear_multiply(0.5)),
+                );
+            }
+            previous = Some(rect.center());
+            let badge = rect.left_top();
+            painter.circle_filled(badge, 8.0, color);
+            painter.text(
+                badge,
+                egui::Align2::CENTER_CENTER,
+                index + 1,
+                egui::FontId::proportional(10.0),
+                egui::Color32::WHITE,
+            );
+        }
+    }
+}
+
+/// Adds the shapes outlined with the marker in `shape` to `rect`.
+fn find_marker(shape: &Shape, rect: &mut Option<egui::Rect>) {
+    let found = match shape {
+        Shape::Vec(shapes) => {
+            for shape in shapes {
+                find_marker(shape, rect);
+            }
+            return;
+        }
+        Shape::Rect(shape) if shape.stroke.color == MARKER => shape.rect,
+        Shape::Circle(shape) if shape.stroke.color == MARKER => {
+            egui::Rect::from_center_size(shape.center, egui::Vec2::splat(shape.radius * 2.0))
+        }
+        _ => return,
+    };
+    *rect = Some(rect.map_or(found, |rect| rect.union(found)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_the_tab_order() {
+        let ctx = egui::Context::default();
+        let mut focus_debug = FocusDebug {
+            recording: true,
+            ..Default::default()
+        };
+        let mut text = String::new();
+        for _ in 0..10 {
+            let mut raw_input = egui::RawInput::default();
+            focus_debug.next_frame(&ctx, &mut raw_input);
+            let mut style = egui::Style::default();
+            focus_debug.mark_focus(&mut style);
+            ctx.set_style(style);
+            let output = ctx.run(raw_input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let _ = ui.button("First");
+                    ui.text_edit_singleline(&mut text);
+                    let _ = ui.button("Last");
+                });
+            });
+            focus_debug.observe(&ctx, &output);
+        }
+        assert!(!focus_debug.recording);
+        let descriptions: Vec<&str> = focus_debug
+            .steps
+            .iter()
+            .map(|step| step.description.as_str())
+            .collect();
+        assert_eq!(descriptions.len(), 3, "{:?}", descriptions);
+        assert!(descriptions[0].contains("First"));
+        assert!(descriptions[2].contains("Last"));
+        assert!(focus_debug.steps.iter().all(|step| step.rect.is_some()));
+    }
+}
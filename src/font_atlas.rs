@@ -0,0 +1,282 @@
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+use std::time::Instant;
+
+use crate::frame_stats::FrameRecord;
+use crate::system_monitor::format_bytes;
+
+/// How many resizes the panel keeps, the oldest are dropped first.
+const MAX_RESIZES: usize = 100;
+/// The most glyphs a pre-warm lays out per text style, so that a typo doesn't fill the atlas.
+const MAX_PREWARM_GLYPHS: usize = 20_000;
+/// egui recreates the atlas at the start of the next frame once it's fuller than this.
+const RECREATE_FILL: f32 = 0.8;
+
+/// Uploads the textures of `delta` with `upload`, the font texture on its own, so that its cost
+/// lands in the font fields of `record`.
+pub fn upload_textures(
+    delta: &mut egui::TexturesDelta,
+    record: &mut FrameRecord,
+    mut upload: impl FnMut(&egui::TexturesDelta),
+) {
+    let (font, other): (Vec<_>, Vec<_>) = std::mem::take(&mut delta.set)
+        .into_iter()
+        .partition(|(id, _)| *id == egui::TextureId::default());
+    if !font.is_empty() {
+        let font = egui::TexturesDelta {
+            set: font,
+            free: Vec::new(),
+        };
+        let start = Instant::now();
+        upload(&font);
+        record.font_upload_ms += start.elapsed().as_secs_f32() * 1000.0;
+        for (_, image) in &font.set {
+            let [width, height] = image.image.size();
+            // The backend uploads the coverage of the glyphs as RGBA.
+            record.font_upload_bytes += (width * height * 4) as u64;
+            if image.pos.is_none() {
+                record.font_atlas = Some([width, height]);
+            }
+        }
+        delta.set = font.set;
+    }
+    let other = egui::TexturesDelta {
+        set: other,
+        free: Vec::new(),
+    };
+    upload(&other);
+    delta.set.extend(other.set);
+}
+
+/// Parses glyph ranges like `20-7E, A0-FF, 20AC`, in hexadecimal code points.
+pub fn parse_ranges(ranges: &str) -> Result<Vec<RangeInclusive<char>>, String> {
+    let code_point = |text: &str| {
+        let text = text.trim();
+        let text = text
+            .strip_prefix("U+")
+            .or_else(|| text.strip_prefix("u+"))
+            .unwrap_or(text);
+        u32::from_str_radix(text, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| format!("{:?} isn't a code point in hexadecimal", text))
+    };
+    ranges
+        .split(',')
+        .filter(|range| !range.trim().is_empty())
+        .map(|range| {
+            let (start, end) = match range.split_once('-') {
+                Some((start, end)) => (code_point(start)?, code_point(end)?),
+                None => {
+                    let single = code_point(range)?;
+                    (single, single)
+                }
+            };
+            match start <= end {
+                true => Ok(start..=end),
+                false => Err(format!("{:?} ends before it starts", range.trim())),
+            }
+        })
+        .collect()
+}
+
+/// Rasterizes the glyphs of `ranges` in every text style of the style of `ctx` into the atlas,
+/// so that they aren't uploaded in the middle of the session. Returns the number of glyphs.
+pub fn prewarm(ctx: &egui::Context, ranges: &[RangeInclusive<char>]) -> Result<usize, String> {
+    let text: String = ranges
+        .iter()
+        .flat_map(|range| range.clone())
+        .filter(|c| !c.is_control())
+        .collect();
+    let glyphs = text.chars().count();
+    if glyphs > MAX_PREWARM_GLYPHS {
+        return Err(format!(
+            "{} glyphs are more than the {} a pre-warm lays out",
+            glyphs, MAX_PREWARM_GLYPHS
+        ));
+    }
+    let font_ids: Vec<egui::FontId> = ctx.style().text_styles.values().cloned().collect();
+    let fonts = ctx.fonts();
+    for font_id in &font_ids {
+        fonts.layout_no_wrap(text.clone(), font_id.clone(), egui::Color32::WHITE);
+    }
+    // A full atlas is recreated empty in the next frame, without the pre-warmed glyphs.
+    if fonts.font_atlas_fill_ratio() > RECREATE_FILL {
+        return Err(format!(
+            "{} glyphs fill the atlas beyond {:.0} %, so egui drops them again",
+            glyphs,
+            RECREATE_FILL * 100.0
+        ));
+    }
+    Ok(glyphs * font_ids.len())
+}
+
+/// A whole upload of the font texture with a new size.
+struct Resize {
+    frame: u64,
+    time: f64,
+    /// `None` for the first upload.
+    from: Option<[usize; 2]>,
+    to: [usize; 2],
+    bytes: u64,
+    upload_ms: f32,
+}
+
+/// Follows the uploads of the font texture in the frame statistics and keeps the resizes of
+/// the atlas, for the "Font atlas" window.
+#[derive(Default)]
+pub struct FontAtlasPanel {
+    size: Option<[usize; 2]>,
+    resizes: VecDeque<Resize>,
+    uploads: usize,
+    uploaded_bytes: u64,
+    upload_ms: f32,
+    last_checked: Option<u64>,
+}
+
+impl FontAtlasPanel {
+    /// Takes note of the font uploads of the last of the recent `frames`, and logs a resize to
+    /// the console.
+    pub fn check(&mut self, frames: &[FrameRecord]) {
+        let last = match frames.last() {
+            Some(last) if self.last_checked != Some(last.frame) => last,
+            _ => return,
+        };
+        self.last_checked = Some(last.frame);
+        if last.font_upload_bytes == 0 {
+            return;
+        }
+        self.uploads += 1;
+        self.uploaded_bytes += last.font_upload_bytes;
+        self.upload_ms += last.font_upload_ms;
+        let to = match last.font_atlas {
+            Some(to) if Some(to) != self.size => to,
+            _ => return,
+        };
+        let resize = Resize {
+            frame: last.frame,
+            time: last.time,
+            from: self.size.replace(to),
+            to,
+            bytes: last.font_upload_bytes,
+            upload_ms: last.font_upload_ms,
+        };
+        // Creating the atlas at the start isn't worth a line.
+        if resize.from.is_some() {
+            println!("{}", resize.describe());
+        }
+        if self.resizes.len() == MAX_RESIZES {
+            self.resizes.pop_front();
+        }
+        self.resizes.push_back(resize);
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        if let Some([width, height]) = self.size {
+            ui.label(format!(
+                "{} × {}, {:.0} % full",
+                width,
+                height,
+                ui.fonts().font_atlas_fill_ratio() * 100.0
+            ));
+        }
+        ui.label(format!(
+            "{} uploads of {} in {:.2} ms in total",
+            self.uploads,
+            format_bytes(self.uploaded_bytes),
+            self.upload_ms
+        ));
+        ui.separator();
+        if self.resizes.is_empty() {
+            ui.weak("No resizes so far");
+            return;
+        }
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("font_atlas_resizes")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Frame");
+                    ui.strong("Size");
+                    ui.strong("Uploaded");
+                    ui.strong("Time");
+                    ui.end_row();
+                    // The newest first.
+                    for resize in self.resizes.iter().rev() {
+                        ui.label(format!("{} at {:.1} s", resize.frame, resize.time));
+                        ui.monospace(resize.sizes());
+                        ui.monospace(format_bytes(resize.bytes));
+                        ui.monospace(format!("{:.2} ms", resize.upload_ms));
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
+
+impl Resize {
+    fn sizes(&self) -> String {
+        let [width, height] = self.to;
+        match self.from {
+            Some([from_width, from_height]) => {
+                format!("{} × {} → {} × {}", from_width, from_height, width, height)
+            }
+            None => format!("{} × {}", width, height),
+        }
+    }
+
+    fn describe(&self) -> String {
+        let what = match self.from {
+            Some([_, from_height]) if from_height < self.to[1] => "grew",
+            Some(_) => "was recreated",
+            None => "was created",
+        };
+        format!(
+            "The font atlas {} in frame {}: {}, uploaded {} in {:.2} ms",
+            what,
+            self.frame,
+            self.sizes(),
+            format_bytes(self.bytes),
+            self.upload_ms
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_are_parsed() {
+        assert_eq!(
+            parse_ranges("20-7E, U+a0-U+FF,20AC,").unwrap(),
+            [' '..='~', '\u{a0}'..='ÿ', '€'..='€']
+        );
+        assert!(parse_ranges("7E-20").is_err());
+        assert!(parse_ranges("D800").is_err());
+        assert!(parse_ranges("latin").is_err());
+    }
+
+    #[test]
+    fn resizes_are_logged_once() {
+        let mut panel = FontAtlasPanel::default();
+        let record = |frame, font_atlas| FrameRecord {
+            frame,
+            font_upload_bytes: 1024,
+            font_atlas,
+            ..Default::default()
+        };
+        let mut frames = vec![record(0, Some([2048, 64]))];
+        panel.check(&frames);
+        panel.check(&frames);
+        frames.push(record(1, None));
+        panel.check(&frames);
+        frames.push(record(2, Some([2048, 128])));
+        panel.check(&frames);
+        assert_eq!(panel.uploads, 3);
+        assert_eq!(panel.resizes.len(), 2);
+        assert!(panel.resizes[1]
+            .describe()
+            .starts_with("The font atlas grew"));
+    }
+}
@@ -0,0 +1,85 @@
+use renderdoc::{RenderDoc, V141};
+
+/// The key that captures the next frame. RenderDoc's own F12 and Print keys keep working too.
+const CAPTURE_KEY: egui::Key = egui::Key::F9;
+
+/// Triggers RenderDoc captures of exactly one frame from the menu or with F9, instead of racing
+/// RenderDoc's capture key against the frame timing.
+///
+/// Only works if the example was started from RenderDoc or RenderDoc was injected into it. The
+/// RenderDoc library is never loaded by the example itself.
+pub struct FrameCapture {
+    renderdoc: Result<RenderDoc<V141>, String>,
+    /// The number of captures before the pending capture was triggered.
+    pending: Option<u32>,
+    last_capture: Option<std::path::PathBuf>,
+}
+
+impl Default for FrameCapture {
+    fn default() -> Self {
+        Self {
+            renderdoc: RenderDoc::new().map_err(|err| err.to_string()),
+            pending: None,
+            last_capture: None,
+        }
+    }
+}
+
+impl FrameCapture {
+    pub fn menu(&mut self, ui: &mut egui::Ui) {
+        let response = ui.add_enabled(
+            self.renderdoc.is_ok(),
+            egui::Button::new("📸 Capture frame with RenderDoc (F9)"),
+        );
+        if response.clicked() {
+            self.trigger();
+            ui.close_menu();
+        }
+        if let Err(err) = &self.renderdoc {
+            response.on_disabled_hover_text(format!("RenderDoc isn't attached: {}", err));
+        }
+    }
+
+    /// Triggers a capture if the capture key was pressed.
+    pub fn handle_input(&mut self, ctx: &egui::Context) {
+        if ctx.input().key_pressed(CAPTURE_KEY) {
+            self.trigger();
+        }
+    }
+
+    /// A short status for the menu bar.
+    pub fn status_ui(&mut self, ui: &mut egui::Ui) {
+        let renderdoc = match &self.renderdoc {
+            Ok(renderdoc) => renderdoc,
+            Err(_) => {
+                ui.weak("RenderDoc: not attached");
+                return;
+            }
+        };
+        let captures = renderdoc.get_num_captures();
+        if let Some(before) = self.pending {
+            if captures > before {
+                self.pending = None;
+                self.last_capture = renderdoc.get_capture(captures - 1).map(|(path, _)| path);
+            } else {
+                ui.colored_label(ui.visuals().warn_fg_color, "RenderDoc: capturing…");
+                // The capture finishes with the next presented frame.
+                ui.ctx().request_repaint();
+                return;
+            }
+        }
+        let response = ui.label(format!("RenderDoc: {} captures", captures));
+        if let Some(path) = &self.last_capture {
+            response.on_hover_text(format!("Last capture: {}", path.display()));
+        }
+    }
+
+    fn trigger(&mut self) {
+        if let Ok(renderdoc) = &mut self.renderdoc {
+            if self.pending.is_none() {
+                self.pending = Some(renderdoc.get_num_captures());
+                renderdoc.trigger_capture();
+            }
+        }
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::{HashSet, VecDeque};
+
+use egui::epaint::{ClippedShape, Primitive};
+use egui_wgpu_backend::ScreenDescriptor;
+
+use crate::user_textures::{Sampling, UserTexture, UserTextures};
+
+/// How many frames are kept until the user says otherwise.
+const DEFAULT_CAPACITY: usize = 120;
+
+/// What one frame of the main UI put out, and the input it got.
+struct Captured {
+    frame: u64,
+    time: f64,
+    screen_rect: egui::Rect,
+    pixels_per_point: f32,
+    shapes: Vec<ClippedShape>,
+    /// The textures egui uploaded in the frame, with their size and whether all of the texture
+    /// or a patch of it.
+    textures_set: Vec<(egui::TextureId, [usize; 2], bool)>,
+    textures_freed: Vec<egui::TextureId>,
+    events: Vec<egui::Event>,
+    pointer: Option<egui::Pos2>,
+}
+
+/// The texture the selected frame is rendered into, in the format of the surface.
+struct Target {
+    texture: UserTexture,
+    view: wgpu::TextureView,
+    size: [u32; 2],
+}
+
+/// What the rendered frame drew.
+struct Rendered {
+    frame: u64,
+    primitives: usize,
+    triangles: usize,
+    /// Meshes with textures that egui freed since, which are left out.
+    skipped: usize,
+}
+
+/// Keeps the output of the last frames of the main UI, to step through them once frozen. The
+/// selected frame is tessellated again with the current options and rendered into a texture,
+/// with the textures as they are now.
+pub struct FrameDebugger {
+    frames: VecDeque<Captured>,
+    capacity: usize,
+    frames_seen: u64,
+    /// Captures no new frames, so that the selected one stays put.
+    frozen: bool,
+    /// The index of the shown frame in `frames`.
+    selected: usize,
+    /// The textures egui manages that are uploaded and not freed yet.
+    live_textures: HashSet<egui::TextureId>,
+    target: Option<Target>,
+    rendered: Option<Rendered>,
+    /// Shows the frame in its physical pixels instead of fitting it into the window.
+    actual_size: bool,
+}
+
+impl Default for FrameDebugger {
+    fn default() -> Self {
+        Self {
+            frames: VecDeque::new(),
+            capacity: DEFAULT_CAPACITY,
+            frames_seen: 0,
+            frozen: false,
+            selected: 0,
+            live_textures: HashSet::new(),
+            target: None,
+            rendered: None,
+            actual_size: false,
+        }
+    }
+}
+
+impl FrameDebugger {
+    /// Takes note of the textures of the frame, and captures it while `capturing`.
+    pub fn observe(&mut self, ctx: &egui::Context, output: &egui::FullOutput, capturing: bool) {
+        self.frames_seen += 1;
+        let delta = &output.textures_delta;
+        self.live_textures
+            .extend(delta.set.iter().map(|(id, _)| *id));
+        // Freed after the frame is drawn, so the frame itself could still use them.
+        for id in &delta.free {
+            self.live_textures.remove(id);
+        }
+        if !capturing || self.frozen {
+            return;
+        }
+        let input = ctx.input();
+        self.frames.push_back(Captured {
+            frame: self.frames_seen,
+            time: input.time,
+            screen_rect: input.screen_rect(),
+            pixels_per_point: input.pixels_per_point(),
+            shapes: output.shapes.clone(),
+            textures_set: delta
+                .set
+                .iter()
+                .map(|(id, image)| (*id, image.image.size(), image.pos.is_none()))
+                .collect(),
+            textures_freed: delta.free.clone(),
+            events: input.events.clone(),
+            pointer: input.pointer.hover_pos(),
+        });
+        while self.frames.len() > self.capacity {
+            self.frames.pop_front();
+        }
+        self.selected = self.frames.len().saturating_sub(1);
+    }
+
+    /// `clear_color` is the one the frames were rendered with.
+    pub fn ui(&mut self, ui: &mut egui::Ui, textures: &mut UserTextures, clear_color: wgpu::Color) {
+        ui.horizontal(|ui| {
+            let label = if self.frozen {
+                "▶ Resume"
+            } else {
+                "⏸ Freeze"
+            };
+            if ui
+                .button(label)
+                .on_hover_text("Stops capturing, to step through the captured frames")
+                .clicked()
+            {
+                self.frozen = !self.frozen;
+                self.rendered = None;
+            }
+            ui.add_enabled_ui(!self.frozen, |ui| {
+                let mut capacity = self.capacity;
+                ui.add(egui::Slider::new(&mut capacity, 10..=1000).text("frames kept"));
+                if capacity != self.capacity {
+                    self.capacity = capacity;
+                    while self.frames.len() > capacity {
+                        self.frames.pop_front();
+                    }
+                }
+            });
+        });
+        if !self.frozen {
+            ui.label(format!(
+                "Capturing, {} frames kept. Freeze to step through them.",
+                self.frames.len()
+            ));
+            // The texture isn't needed until the next freeze.
+            if let Some(old) = self.target.take() {
+                textures.free([old.texture]);
+            }
+            return;
+        }
+        if self.frames.is_empty() {
+            ui.label("No frames captured.");
+            return;
+        }
+
+        let last = self.frames.len() - 1;
+        ui.horizontal(|ui| {
+            if ui.button("⏮").on_hover_text("Oldest").clicked() {
+                self.selected = 0;
+            }
+            if ui
+                .add_enabled(self.selected > 0, egui::Button::new("⏴"))
+                .clicked()
+            {
+                self.selected -= 1;
+            }
+            if ui
+                .add_enabled(self.selected < last, egui::Button::new("⏵"))
+                .clicked()
+            {
+                self.selected += 1;
+            }
+            if ui.button("⏭").on_hover_text("Newest").clicked() {
+                self.selected = last;
+            }
+            ui.add(egui::Slider::new(&mut self.selected, 0..=last).show_value(false));
+            if ui
+                .button("⟳")
+                .on_hover_text("Tessellates and renders the frame again, e.g. with other options")
+                .clicked()
+            {
+                self.rendered = None;
+            }
+            ui.checkbox(&mut self.actual_size, "1:1");
+        });
+
+        let rendered = self.rendered.as_ref().map(|rendered| rendered.frame);
+        if rendered != Some(self.frames[self.selected].frame) {
+            self.render(ui.ctx(), textures, clear_color);
+        }
+        let frame = &self.frames[self.selected];
+        ui.label(format!(
+            "Frame {} at {:.3} s, {} of {} kept, {} shapes",
+            frame.frame,
+            frame.time,
+            self.selected + 1,
+            self.frames.len(),
+            frame.shapes.len()
+        ));
+        if let Some(rendered) = &self.rendered {
+            ui.label(format!(
+                "Tessellated into {} primitives with {} triangles",
+                rendered.primitives, rendered.triangles
+            ));
+            if rendered.skipped > 0 {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!(
+                        "⚠ Left out {} meshes whose textures were freed since",
+                        rendered.skipped
+                    ),
+                );
+            }
+        }
+        egui::CollapsingHeader::new(format!("Input: {} events", frame.events.len()))
+            .id_source("frame_debugger_input")
+            .show(ui, |ui| {
+                match frame.pointer {
+                    Some(pos) => ui.label(format!("Pointer at {:.1}, {:.1}", pos.x, pos.y)),
+                    None => ui.label("No pointer"),
+                };
+                for event in &frame.events {
+                    ui.monospace(format!("{:?}", event));
+                }
+            });
+        egui::CollapsingHeader::new(format!(
+            "Textures: {} set, {} freed",
+            frame.textures_set.len(),
+            frame.textures_freed.len()
+        ))
+        .id_source("frame_debugger_textures")
+        .show(ui, |ui| {
+            for (id, [width, height], whole) in &frame.textures_set {
+                let kind = if *whole { "whole" } else { "patch" };
+                ui.monospace(format!("{:?}: {} × {}, {}", id, width, height, kind));
+            }
+            for id in &frame.textures_freed {
+                ui.monospace(format!("{:?}: freed", id));
+            }
+        });
+
+        if let Some(target) = &self.target {
+            ui.separator();
+            let size = egui::vec2(target.size[0] as f32, target.size[1] as f32);
+            let size = match self.actual_size {
+                true => size / ui.ctx().pixels_per_point(),
+                false => size * (ui.available_width() / size.x).min(1.0),
+            };
+            egui::ScrollArea::both().show(ui, |ui| {
+                ui.image(target.texture.id, size);
+            });
+        }
+    }
+
+    /// Tessellates the selected frame and renders it into the target.
+    fn render(
+        &mut self,
+        ctx: &egui::Context,
+        textures: &mut UserTextures,
+        clear_color: wgpu::Color,
+    ) {
+        let frame = &self.frames[self.selected];
+        let physical = frame.screen_rect.size() * frame.pixels_per_point;
+        let size = [
+            (physical.x.round() as u32).max(1),
+            (physical.y.round() as u32).max(1),
+        ];
+        if self.target.as_ref().map(|target| target.size) != Some(size) {
+            if let Some(old) = self.target.take() {
+                textures.free([old.texture]);
+            }
+            // A surface without sRGB leaves the colors of the capture a bit too bright, since
+            // the texture is sampled like the gamma-encoded one that it is.
+            let texture = textures.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("frame_debugger_target"),
+                size: wgpu::Extent3d {
+                    width: size[0],
+                    height: size[1],
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: textures.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+            });
+            self.target = Some(Target {
+                view: texture.create_view(&Default::default()),
+                texture: textures.register(
+                    &texture,
+                    u64::from(size[0]) * u64::from(size[1]) * 4,
+                    Sampling::default(),
+                ),
+                size,
+            });
+        }
+        let target = self.target.as_ref().expect("The target exists");
+
+        // The target can't show up in itself, where the frame shows this window.
+        let jobs: Vec<_> = ctx
+            .tessellate(frame.shapes.clone())
+            .into_iter()
+            .filter(|job| match &job.primitive {
+                Primitive::Mesh(mesh) => mesh.texture_id != target.texture.id,
+                Primitive::Callback(_) => true,
+            })
+            .collect();
+        let (jobs, skipped): (Vec<_>, Vec<_>) =
+            jobs.into_iter().partition(|job| match &job.primitive {
+                Primitive::Mesh(mesh) => match mesh.texture_id {
+                    egui::TextureId::Managed(_) => self.live_textures.contains(&mesh.texture_id),
+                    egui::TextureId::User(_) => true,
+                },
+                Primitive::Callback(_) => true,
+            });
+        let triangles = jobs
+            .iter()
+            .map(|job| match &job.primitive {
+                Primitive::Mesh(mesh) => mesh.indices.len() / 3,
+                Primitive::Callback(_) => 0,
+            })
+            .sum();
+        let screen_descriptor = ScreenDescriptor {
+            physical_width: size[0],
+            physical_height: size[1],
+            scale_factor: frame.pixels_per_point,
+        };
+        textures.render_ui(&target.view, &jobs, &screen_descriptor, clear_color);
+        self.rendered = Some(Rendered {
+            frame: frame.frame,
+            primitives: jobs.len(),
+            triangles,
+            skipped: skipped.len(),
+        });
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How many frames the latency statistics are computed over.
+const TRACKED_FRAMES: usize = 120;
+
+/// Limits the frames the GPU works on at the same time, and measures how long the GPU takes
+/// from the submission of a frame to its completion.
+///
+/// wgpu 0.13 has no setting for the frame latency of the swapchain, so the limit is enforced on
+/// the CPU: a frame only starts once enough of the submitted ones are done. Fewer frames in
+/// flight lower the latency between input and display, more keep the GPU busy.
+#[derive(Default)]
+pub struct FrameLatency {
+    in_flight: Arc<AtomicUsize>,
+    latencies: Arc<Mutex<VecDeque<Duration>>>,
+    /// How long the last frame waited for the GPU.
+    last_wait: Duration,
+}
+
+impl FrameLatency {
+    /// Blocks until fewer than `limit` frames are in flight. Without a limit, only the swapchain
+    /// throttles.
+    pub fn wait(&mut self, device: &wgpu::Device, limit: Option<u32>) {
+        // Runs the callbacks of the finished frames.
+        device.poll(wgpu::Maintain::Poll);
+        let start = Instant::now();
+        if let Some(limit) = limit.filter(|limit| *limit > 0) {
+            while self.in_flight.load(Ordering::Acquire) >= limit as usize {
+                device.poll(wgpu::Maintain::Poll);
+                std::thread::sleep(Duration::from_micros(100));
+            }
+        }
+        self.last_wait = start.elapsed();
+    }
+
+    /// How long the last frame waited for the GPU.
+    pub fn last_wait(&self) -> Duration {
+        self.last_wait
+    }
+
+    /// Tracks the frame that was just submitted to `queue`.
+    pub fn submitted(&mut self, queue: &wgpu::Queue) {
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        let in_flight = self.in_flight.clone();
+        let latencies = self.latencies.clone();
+        let submitted = Instant::now();
+        queue.on_submitted_work_done(move || {
+            in_flight.fetch_sub(1, Ordering::AcqRel);
+            let mut latencies = latencies.lock().unwrap();
+            if latencies.len() == TRACKED_FRAMES {
+                latencies.pop_front();
+            }
+            latencies.push_back(submitted.elapsed());
+        });
+    }
+
+    /// Shows the frames in flight and the measured latency.
+    pub fn ui(&self, ui: &mut egui::Ui) {
+        ui.label(format!(
+            "Frames in flight: {}, last frame waited {:.2} ms for the GPU",
+            self.in_flight.load(Ordering::Acquire),
+            self.last_wait.as_secs_f64() * 1000.0
+        ));
+        let latencies = self.latencies.lock().unwrap();
+        if latencies.is_empty() {
+            return;
+        }
+        let mean = latencies.iter().sum::<Duration>() / latencies.len() as u32;
+        let worst = latencies.iter().max().copied().unwrap_or_default();
+        ui.label(format!(
+            "Submit to GPU completion: mean {:.2} ms, worst {:.2} ms",
+            mean.as_secs_f64() * 1000.0,
+            worst.as_secs_f64() * 1000.0
+        ))
+        .on_hover_text(
+            "Measured when wgpu reports the work as done, at the latest one frame later.",
+        );
+    }
+}
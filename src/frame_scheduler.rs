@@ -0,0 +1,144 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use winit::event::StartCause;
+use winit::event_loop::ControlFlow;
+
+use crate::frame_latency::FrameLatency;
+
+/// How many wakeups the accuracy statistics are computed over.
+const TRACKED_WAKEUPS: usize = 120;
+
+/// Decides when the next frame is drawn, instead of drawing as fast as possible.
+///
+/// A frame is due after input, after a repaint request from another thread, and when egui asks
+/// for one with `repaint_after`, which also covers its animations. The frame rate limit delays
+/// due frames. In between, the event loop sleeps with `ControlFlow::WaitUntil` or `Wait`.
+pub struct FrameScheduler {
+    /// Draws every frame, for replays and fuzzing that need a steady stream of frames.
+    continuous: bool,
+    frame_rate_limit: Option<u32>,
+    last_frame: Option<Instant>,
+    /// When the next frame is wanted. `None` waits for the next event.
+    next_frame: Option<Instant>,
+    /// How late the event loop woke up for the last timed wakeups.
+    lateness: VecDeque<Duration>,
+    latency: FrameLatency,
+}
+
+impl FrameScheduler {
+    pub fn new(continuous: bool) -> Self {
+        Self {
+            continuous,
+            frame_rate_limit: None,
+            last_frame: None,
+            next_frame: Some(Instant::now()),
+            lateness: VecDeque::with_capacity(TRACKED_WAKEUPS),
+            latency: Default::default(),
+        }
+    }
+
+    /// The frames in flight on the GPU.
+    pub fn latency_mut(&mut self) -> &mut FrameLatency {
+        &mut self.latency
+    }
+
+    /// The most frames drawn per second. `None` is only limited by the vsync of the surface.
+    pub fn set_frame_rate_limit(&mut self, limit: Option<u32>) {
+        self.frame_rate_limit = limit.filter(|limit| *limit > 0);
+    }
+
+    /// Measures how late the event loop woke up, if it woke up for a timed frame.
+    pub fn new_events(&mut self, cause: StartCause) {
+        if let StartCause::ResumeTimeReached {
+            start,
+            requested_resume,
+        } = cause
+        {
+            if self.lateness.len() == TRACKED_WAKEUPS {
+                self.lateness.pop_front();
+            }
+            self.lateness
+                .push_back(start.saturating_duration_since(requested_resume));
+        }
+    }
+
+    /// Schedules a frame as soon as the frame rate limit allows, e.g. after input.
+    pub fn request_frame(&mut self) {
+        self.schedule(Instant::now());
+    }
+
+    pub fn frame_started(&mut self) {
+        self.last_frame = Some(Instant::now());
+        self.next_frame = None;
+    }
+
+    /// Schedules the next frame with the `repaint_after` of the egui output.
+    pub fn frame_finished(&mut self, repaint_after: Duration) {
+        if let Some(at) = Instant::now().checked_add(repaint_after) {
+            self.schedule(at);
+        }
+    }
+
+    /// Whether the next frame should be drawn now.
+    pub fn is_due(&self) -> bool {
+        self.continuous
+            || self
+                .next_wakeup()
+                .is_some_and(|wakeup| wakeup <= Instant::now())
+    }
+
+    /// How the event loop waits for the next frame.
+    pub fn control_flow(&self) -> ControlFlow {
+        if self.continuous {
+            return ControlFlow::Poll;
+        }
+        match self.next_wakeup() {
+            Some(wakeup) => ControlFlow::WaitUntil(wakeup),
+            None => ControlFlow::Wait,
+        }
+    }
+
+    /// Shows the schedule, the achieved wakeup accuracy and the frame latency.
+    pub fn ui(&self, ui: &mut egui::Ui) {
+        self.latency.ui(ui);
+        let now = Instant::now();
+        ui.label(match (self.continuous, self.next_wakeup()) {
+            (true, _) => "Drawing continuously".to_owned(),
+            (false, Some(wakeup)) if wakeup > now => format!(
+                "Next frame in {:.1} ms",
+                (wakeup - now).as_secs_f64() * 1000.0
+            ),
+            (false, Some(_)) => "Next frame is due".to_owned(),
+            (false, None) => "Waiting for input".to_owned(),
+        });
+        if self.lateness.is_empty() {
+            ui.weak("No timed wakeups yet");
+            return;
+        }
+        let mean = self.lateness.iter().sum::<Duration>() / self.lateness.len() as u32;
+        let worst = self.lateness.iter().max().copied().unwrap_or_default();
+        ui.label(format!(
+            "Wakeup lateness over the last {} timed wakeups: mean {:.2} ms, worst {:.2} ms",
+            self.lateness.len(),
+            mean.as_secs_f64() * 1000.0,
+            worst.as_secs_f64() * 1000.0
+        ));
+    }
+
+    /// When the next frame is drawn, with the frame rate limit applied.
+    fn next_wakeup(&self) -> Option<Instant> {
+        let next_frame = self.next_frame?;
+        let earliest = match (self.last_frame, self.frame_rate_limit) {
+            (Some(last_frame), Some(limit)) => {
+                last_frame + Duration::from_secs_f64(1.0 / limit as f64)
+            }
+            _ => next_frame,
+        };
+        Some(next_frame.max(earliest))
+    }
+
+    fn schedule(&mut self, at: Instant) {
+        self.next_frame = Some(self.next_frame.map_or(at, |next_frame| next_frame.min(at)));
+    }
+}
@@ -0,0 +1,689 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use serde::Serialize;
+
+/// What the runner measured in one frame. The CPU stages are in milliseconds.
+#[derive(Clone, Default, Serialize)]
+pub struct FrameRecord {
+    pub frame: u64,
+    /// Seconds since the start of the session.
+    pub time: f64,
+    /// Waiting for the GPU to finish older frames.
+    pub wait_ms: f32,
+    /// Running the main UI.
+    pub ui_ms: f32,
+    /// Running the HUD and tessellating it.
+    pub hud_ms: f32,
+    /// Waiting for the tessellation of the main UI.
+    pub tessellate_ms: f32,
+    /// Uploading the textures and buffers.
+    pub upload_ms: f32,
+    /// Recording the render passes.
+    pub encode_ms: f32,
+    pub submit_ms: f32,
+    /// From the start of the frame until it was submitted.
+    pub cpu_ms: f32,
+    /// The render passes on the GPU. `None` without timestamp queries, or until they're read.
+    pub gpu_ms: Option<f32>,
+    pub texture_upload_bytes: u64,
+    pub buffer_upload_bytes: u64,
+    pub draw_calls: u32,
+    pub triangles: u32,
+    /// The egui render passes, from bottom to top.
+    pub passes: Vec<PassStats>,
+    /// The part of the texture uploads that went to the font atlas of the main UI.
+    pub font_upload_bytes: u64,
+    pub font_upload_ms: f32,
+    /// The size of the font atlas, in the frames that uploaded it as a whole.
+    pub font_atlas: Option<[usize; 2]>,
+}
+
+/// Gets the milliseconds of a stage from a record.
+pub type Stage = fn(&FrameRecord) -> Option<f32>;
+
+/// The CPU stages of a frame, in the order they run.
+pub const STAGES: &[(&str, Stage)] = &[
+    ("Wait for the GPU", |record| Some(record.wait_ms)),
+    ("Main UI", |record| Some(record.ui_ms)),
+    ("HUD", |record| Some(record.hud_ms)),
+    ("Tessellation", |record| Some(record.tessellate_ms)),
+    ("Upload", |record| Some(record.upload_ms)),
+    ("Encode", |record| Some(record.encode_ms)),
+    ("Submit", |record| Some(record.submit_ms)),
+];
+
+/// The time of the whole frame on the CPU and on the GPU.
+pub const TOTALS: &[(&str, Stage)] = &[
+    ("CPU total", |record| Some(record.cpu_ms)),
+    ("GPU", |record| record.gpu_ms),
+];
+
+/// The names of the egui render passes, the columns of the export.
+pub const PASSES: [&str; 2] = ["hud", "ui"];
+
+/// The commands one egui render pass recorded, counted from its paint jobs the way
+/// `egui_wgpu_backend` records them: a scissor rect for every job whose clip rect isn't empty,
+/// and a texture bind group and a draw call for every mesh of those.
+#[derive(Clone, Default, Serialize)]
+pub struct PassStats {
+    pub name: &'static str,
+    pub draw_calls: u32,
+    pub triangles: u32,
+    pub scissor_rects: u32,
+    /// The scissor rects that differ from the previous one.
+    pub scissor_changes: u32,
+    pub texture_binds: u32,
+    /// The texture binds with another texture than the previous one.
+    pub texture_switches: u32,
+}
+
+impl PassStats {
+    pub fn new(
+        name: &'static str,
+        jobs: &[egui::ClippedPrimitive],
+        screen_descriptor: &egui_wgpu_backend::ScreenDescriptor,
+    ) -> Self {
+        let mut stats = Self {
+            name,
+            ..Default::default()
+        };
+        let size = [
+            screen_descriptor.physical_width,
+            screen_descriptor.physical_height,
+        ];
+        let mut scissor = None;
+        let mut texture = None;
+        for job in jobs {
+            let rect = match scissor_rect(job.clip_rect, screen_descriptor.scale_factor, size) {
+                Some(rect) => rect,
+                None => continue,
+            };
+            stats.scissor_rects += 1;
+            if scissor.replace(rect) != Some(rect) {
+                stats.scissor_changes += 1;
+            }
+            if let egui::epaint::Primitive::Mesh(mesh) = &job.primitive {
+                stats.texture_binds += 1;
+                if texture.replace(mesh.texture_id) != Some(mesh.texture_id) {
+                    stats.texture_switches += 1;
+                }
+                stats.draw_calls += 1;
+                stats.triangles += mesh.indices.len() as u32 / 3;
+            }
+        }
+        stats
+    }
+}
+
+/// The scissor rect the backend sets for `clip_rect`, in physical pixels. `None` where it
+/// skips the job.
+fn scissor_rect(clip_rect: egui::Rect, scale_factor: f32, size: [u32; 2]) -> Option<[u32; 4]> {
+    let [width, height] = size.map(|side| side as f32);
+    let min_x = (clip_rect.min.x * scale_factor).clamp(0.0, width);
+    let min_y = (clip_rect.min.y * scale_factor).clamp(0.0, height);
+    let max_x = (clip_rect.max.x * scale_factor).clamp(min_x, width);
+    let max_y = (clip_rect.max.y * scale_factor).clamp(min_y, height);
+    let [min_x, min_y, max_x, max_y] = [min_x, min_y, max_x, max_y].map(|v| v.round() as u32);
+    let x = min_x.min(size[0]);
+    let y = min_y.min(size[1]);
+    let width = (max_x - min_x).max(1).min(size[0] - x);
+    let height = (max_y - min_y).max(1).min(size[1] - y);
+    (width > 0 && height > 0).then_some([x, y, width, height])
+}
+
+impl FrameRecord {
+    /// Counts the commands of the render pass `name`, and the uploads of its paint jobs and
+    /// texture changes.
+    pub fn add_layer(
+        &mut self,
+        name: &'static str,
+        jobs: &[egui::ClippedPrimitive],
+        textures: &egui::TexturesDelta,
+        screen_descriptor: &egui_wgpu_backend::ScreenDescriptor,
+    ) {
+        let pass = PassStats::new(name, jobs, screen_descriptor);
+        self.draw_calls += pass.draw_calls;
+        self.triangles += pass.triangles;
+        self.passes.push(pass);
+        for job in jobs {
+            if let egui::epaint::Primitive::Mesh(mesh) = &job.primitive {
+                self.buffer_upload_bytes += (mesh.vertices.len()
+                    * std::mem::size_of::<egui::epaint::Vertex>()
+                    + mesh.indices.len() * std::mem::size_of::<u32>())
+                    as u64;
+            }
+        }
+        for (_, delta) in &textures.set {
+            let [width, height] = delta.image.size();
+            self.texture_upload_bytes += (width * height * 4) as u64;
+        }
+    }
+}
+
+/// The columns of the export for each of the [`PASSES`], in the order of [`PassStats`].
+const PASS_COLUMNS: [&str; 6] = [
+    "draw_calls",
+    "triangles",
+    "scissor_rects",
+    "scissor_changes",
+    "texture_binds",
+    "texture_switches",
+];
+
+/// Measures the time between the stages of a frame.
+pub struct Stages {
+    start: Instant,
+    last: Instant,
+}
+
+impl Stages {
+    pub fn start() -> Self {
+        let now = Instant::now();
+        Self {
+            start: now,
+            last: now,
+        }
+    }
+
+    /// The milliseconds since the last stage ended.
+    pub fn lap(&mut self) -> f32 {
+        let now = Instant::now();
+        let lap = now - self.last;
+        self.last = now;
+        lap.as_secs_f32() * 1000.0
+    }
+
+    /// The milliseconds since the start.
+    pub fn total(&self) -> f32 {
+        self.last.duration_since(self.start).as_secs_f32() * 1000.0
+    }
+}
+
+/// Collects a [`FrameRecord`] for every frame of the session, to export them and to print a
+/// summary on exit.
+pub struct FrameStats {
+    records: Vec<FrameRecord>,
+    gpu_timer: Option<GpuTimer>,
+    /// The GPU the frames were measured on.
+    adapter: String,
+}
+
+impl FrameStats {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, adapter: &wgpu::AdapterInfo) -> Self {
+        let gpu_timer = device
+            .features()
+            .contains(wgpu::Features::TIMESTAMP_QUERY)
+            .then(|| GpuTimer::new(device, queue));
+        Self {
+            records: Vec::new(),
+            gpu_timer,
+            adapter: format!("{} ({:?})", adapter.name, adapter.backend),
+        }
+    }
+
+    /// The number of the next frame.
+    pub fn next_frame(&self) -> u64 {
+        self.records.len() as u64
+    }
+
+    /// Writes the timestamp before the render passes of the next frame.
+    pub fn begin_gpu(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.begin(encoder);
+        }
+    }
+
+    /// Writes the timestamp after the render passes, and resolves both into a buffer to read
+    /// them back.
+    pub fn end_gpu(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder) {
+        let frame = self.next_frame();
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.end(device, encoder, frame);
+        }
+    }
+
+    /// Adds the record of a frame that was just submitted.
+    pub fn push(&mut self, record: FrameRecord) {
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.map_last();
+        }
+        self.records.push(record);
+    }
+
+    /// The last `count` records, the oldest first.
+    pub fn recent(&self, count: usize) -> &[FrameRecord] {
+        &self.records[self.records.len().saturating_sub(count)..]
+    }
+
+    /// Fills in the GPU times of the frames the GPU finished since the last call.
+    pub fn collect_gpu_times(&mut self) {
+        if let Some(timer) = &mut self.gpu_timer {
+            for (frame, gpu_ms) in timer.collect() {
+                if let Some(record) = self.records.get_mut(frame as usize) {
+                    record.gpu_ms = Some(gpu_ms);
+                }
+            }
+        }
+    }
+
+    /// Writes all records to a CSV and a JSON file next to each other, and returns the path of
+    /// the CSV file.
+    pub fn export(&self) -> Result<PathBuf, String> {
+        let directory = dirs::document_dir().unwrap_or_else(|| PathBuf::from("."));
+        let name = chrono::Local::now()
+            .format("egui_example_frames_%Y%m%d_%H%M%S")
+            .to_string();
+        let csv_path = directory.join(format!("{}.csv", name));
+        let json_path = directory.join(format!("{}.json", name));
+
+        let mut csv = String::new();
+        let mut columns: Vec<String> = [
+            "frame",
+            "time",
+            "wait_ms",
+            "ui_ms",
+            "hud_ms",
+            "tessellate_ms",
+            "upload_ms",
+            "encode_ms",
+            "submit_ms",
+            "cpu_ms",
+            "gpu_ms",
+            "texture_upload_bytes",
+            "buffer_upload_bytes",
+            "draw_calls",
+            "triangles",
+            "font_upload_bytes",
+            "font_upload_ms",
+            "font_atlas",
+        ]
+        .map(String::from)
+        .to_vec();
+        for pass in PASSES {
+            for stat in PASS_COLUMNS {
+                columns.push(format!("{}_{}", pass, stat));
+            }
+        }
+        crate::csv_file::write_record(&mut csv, &columns, ',');
+        for record in &self.records {
+            let mut fields = vec![
+                record.frame.to_string(),
+                format!("{:.4}", record.time),
+                format!("{:.3}", record.wait_ms),
+                format!("{:.3}", record.ui_ms),
+                format!("{:.3}", record.hud_ms),
+                format!("{:.3}", record.tessellate_ms),
+                format!("{:.3}", record.upload_ms),
+                format!("{:.3}", record.encode_ms),
+                format!("{:.3}", record.submit_ms),
+                format!("{:.3}", record.cpu_ms),
+                record
+                    .gpu_ms
+                    .map_or_else(String::new, |gpu_ms| format!("{:.3}", gpu_ms)),
+                record.texture_upload_bytes.to_string(),
+                record.buffer_upload_bytes.to_string(),
+                record.draw_calls.to_string(),
+                record.triangles.to_string(),
+                record.font_upload_bytes.to_string(),
+                format!("{:.3}", record.font_upload_ms),
+                record
+                    .font_atlas
+                    .map_or_else(String::new, |[width, height]| {
+                        format!("{}x{}", width, height)
+                    }),
+            ];
+            // Empty for a pass that didn't run, like the HUD while it's off.
+            for name in PASSES {
+                match record.passes.iter().find(|pass| pass.name == name) {
+                    Some(pass) => fields.extend(
+                        [
+                            pass.draw_calls,
+                            pass.triangles,
+                            pass.scissor_rects,
+                            pass.scissor_changes,
+                            pass.texture_binds,
+                            pass.texture_switches,
+                        ]
+                        .map(|count| count.to_string()),
+                    ),
+                    None => fields.extend(PASS_COLUMNS.map(|_| String::new())),
+                }
+            }
+            crate::csv_file::write_record(&mut csv, &fields, ',');
+        }
+        std::fs::write(&csv_path, csv)
+            .map_err(|err| format!("Can't write {}: {}", csv_path.display(), err))?;
+
+        let json = serde_json::json!({
+            "adapter": self.adapter,
+            "summary": self.summary(),
+            "frames": self.records,
+        });
+        let json = serde_json::to_vec_pretty(&json).map_err(|err| err.to_string())?;
+        std::fs::write(&json_path, json)
+            .map_err(|err| format!("Can't write {}: {}", json_path.display(), err))?;
+        Ok(csv_path)
+    }
+
+    /// Summarizes the whole session. `None` if no frame was drawn.
+    pub fn summary(&self) -> Option<Summary> {
+        let last = self.records.last()?;
+        let first = &self.records[0];
+        let frames = self.records.len();
+        let duration = last.time - first.time;
+        let stage = |value: fn(&FrameRecord) -> Option<f32>| {
+            Distribution::new(self.records.iter().filter_map(value).collect())
+        };
+        Some(Summary {
+            frames,
+            seconds: duration,
+            frames_per_second: match duration > 0.0 {
+                true => (frames - 1) as f64 / duration,
+                false => 0.0,
+            },
+            cpu_ms: stage(|record| Some(record.cpu_ms)),
+            ui_ms: stage(|record| Some(record.ui_ms)),
+            tessellate_ms: stage(|record| Some(record.tessellate_ms)),
+            upload_ms: stage(|record| Some(record.upload_ms)),
+            gpu_ms: stage(|record| record.gpu_ms),
+            uploaded_bytes: self
+                .records
+                .iter()
+                .map(|record| record.texture_upload_bytes + record.buffer_upload_bytes)
+                .sum(),
+            mean_draw_calls: self
+                .records
+                .iter()
+                .map(|record| record.draw_calls as f64)
+                .sum::<f64>()
+                / frames as f64,
+            mean_triangles: self
+                .records
+                .iter()
+                .map(|record| record.triangles as f64)
+                .sum::<f64>()
+                / frames as f64,
+            passes: PASSES
+                .iter()
+                .map(|name| PassSummary::new(name, &self.records))
+                .collect(),
+        })
+    }
+}
+
+/// The statistics of a whole session.
+#[derive(Serialize)]
+pub struct Summary {
+    pub frames: usize,
+    pub seconds: f64,
+    pub frames_per_second: f64,
+    pub cpu_ms: Option<Distribution>,
+    pub ui_ms: Option<Distribution>,
+    pub tessellate_ms: Option<Distribution>,
+    pub upload_ms: Option<Distribution>,
+    pub gpu_ms: Option<Distribution>,
+    pub uploaded_bytes: u64,
+    pub mean_draw_calls: f64,
+    pub mean_triangles: f64,
+    pub passes: Vec<PassSummary>,
+}
+
+/// The means per frame of the [`PassStats`] of one pass, over the frames it ran in.
+#[derive(Serialize)]
+pub struct PassSummary {
+    pub name: &'static str,
+    pub frames: usize,
+    pub draw_calls: f64,
+    pub triangles: f64,
+    pub scissor_rects: f64,
+    pub scissor_changes: f64,
+    pub texture_binds: f64,
+    pub texture_switches: f64,
+}
+
+impl PassSummary {
+    fn new(name: &'static str, records: &[FrameRecord]) -> Self {
+        let passes: Vec<&PassStats> = records
+            .iter()
+            .filter_map(|record| record.passes.iter().find(|pass| pass.name == name))
+            .collect();
+        let mean = |stat: fn(&PassStats) -> u32| {
+            let sum: f64 = passes.iter().map(|pass| stat(pass) as f64).sum();
+            sum / passes.len().max(1) as f64
+        };
+        Self {
+            name,
+            frames: passes.len(),
+            draw_calls: mean(|pass| pass.draw_calls),
+            triangles: mean(|pass| pass.triangles),
+            scissor_rects: mean(|pass| pass.scissor_rects),
+            scissor_changes: mean(|pass| pass.scissor_changes),
+            texture_binds: mean(|pass| pass.texture_binds),
+            texture_switches: mean(|pass| pass.texture_switches),
+        }
+    }
+}
+
+/// The distribution of one measurement over the frames.
+#[derive(Serialize)]
+pub struct Distribution {
+    pub mean: f32,
+    pub median: f32,
+    pub p95: f32,
+    pub p99: f32,
+    pub max: f32,
+}
+
+impl Distribution {
+    /// `None` if there are no values.
+    fn new(mut values: Vec<f32>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f32::total_cmp);
+        let percentile = |p: f32| values[((values.len() - 1) as f32 * p).round() as usize];
+        Some(Self {
+            mean: values.iter().sum::<f32>() / values.len() as f32,
+            median: percentile(0.5),
+            p95: percentile(0.95),
+            p99: percentile(0.99),
+            max: values[values.len() - 1],
+        })
+    }
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} frames in {:.1} s, {:.1} frames per second",
+            self.frames, self.seconds, self.frames_per_second
+        )?;
+        writeln!(
+            f,
+            "{:<12} {:>8} {:>8} {:>8} {:>8} {:>8}",
+            "ms", "mean", "median", "p95", "p99", "max"
+        )?;
+        let stages = [
+            ("CPU", &self.cpu_ms),
+            ("UI", &self.ui_ms),
+            ("Tessellate", &self.tessellate_ms),
+            ("Upload", &self.upload_ms),
+            ("GPU", &self.gpu_ms),
+        ];
+        for (name, distribution) in stages {
+            if let Some(d) = distribution {
+                writeln!(
+                    f,
+                    "{:<12} {:>8.2} {:>8.2} {:>8.2} {:>8.2} {:>8.2}",
+                    name, d.mean, d.median, d.p95, d.p99, d.max
+                )?;
+            }
+        }
+        writeln!(
+            f,
+            "Uploaded {:.1} MiB, {:.1} draw calls and {:.0} triangles per frame",
+            self.uploaded_bytes as f64 / (1024.0 * 1024.0),
+            self.mean_draw_calls,
+            self.mean_triangles
+        )?;
+        write!(
+            f,
+            "{:<12} {:>8} {:>10} {:>8} {:>8} {:>8} {:>8}",
+            "per frame", "draws", "triangles", "scissor", "changes", "binds", "switches"
+        )?;
+        for pass in self.passes.iter().filter(|pass| pass.frames > 0) {
+            write!(
+                f,
+                "\n{:<12} {:>8.1} {:>10.0} {:>8.1} {:>8.1} {:>8.1} {:>8.1}",
+                pass.name,
+                pass.draw_calls,
+                pass.triangles,
+                pass.scissor_rects,
+                pass.scissor_changes,
+                pass.texture_binds,
+                pass.texture_switches
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Measures how long the GPU takes for the render passes of a frame, with timestamp queries
+/// around them. The timestamps are read back once wgpu reports the frame as done.
+struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    /// Nanoseconds per timestamp tick.
+    period: f32,
+    pending: Vec<PendingTimestamps>,
+    /// Mappable buffers whose timestamps were read.
+    free: Vec<wgpu::Buffer>,
+}
+
+struct PendingTimestamps {
+    frame: u64,
+    buffer: wgpu::Buffer,
+    mapped: Arc<AtomicBool>,
+    mapping: bool,
+}
+
+/// The size of the two timestamps.
+const TIMESTAMPS_SIZE: wgpu::BufferAddress = 2 * std::mem::size_of::<u64>() as u64;
+
+impl GpuTimer {
+    fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        Self {
+            query_set: device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("frame timestamps"),
+                ty: wgpu::QueryType::Timestamp,
+                count: 2,
+            }),
+            period: queue.get_timestamp_period(),
+            pending: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    fn begin(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.write_timestamp(&self.query_set, 0);
+    }
+
+    fn end(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder, frame: u64) {
+        encoder.write_timestamp(&self.query_set, 1);
+        let buffer = self.free.pop().unwrap_or_else(|| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("frame timestamps readback"),
+                size: TIMESTAMPS_SIZE,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            })
+        });
+        encoder.resolve_query_set(&self.query_set, 0..2, &buffer, 0);
+        self.pending.push(PendingTimestamps {
+            frame,
+            buffer,
+            mapped: Default::default(),
+            mapping: false,
+        });
+    }
+
+    /// Maps the buffer of the frame that was just submitted.
+    fn map_last(&mut self) {
+        let pending = match self.pending.last_mut() {
+            Some(pending) if !pending.mapping => pending,
+            _ => return,
+        };
+        let mapped = pending.mapped.clone();
+        pending
+            .buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                mapped.store(result.is_ok(), Ordering::Release);
+            });
+        pending.mapping = true;
+    }
+
+    /// The frames whose timestamps arrived, with their GPU time in milliseconds.
+    fn collect(&mut self) -> Vec<(u64, f32)> {
+        let mut times = Vec::new();
+        let mut index = 0;
+        while index < self.pending.len() {
+            if !self.pending[index].mapped.load(Ordering::Acquire) {
+                index += 1;
+                continue;
+            }
+            let pending = self.pending.swap_remove(index);
+            {
+                let data = pending.buffer.slice(..).get_mapped_range();
+                let start = u64::from_le_bytes(data[0..8].try_into().unwrap());
+                let end = u64::from_le_bytes(data[8..16].try_into().unwrap());
+                let nanoseconds = end.saturating_sub(start) as f32 * self.period;
+                times.push((pending.frame, nanoseconds / 1_000_000.0));
+            }
+            pending.buffer.unmap();
+            self.free.push(pending.buffer);
+        }
+        times
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pass_commands_are_counted() {
+        let job = |clip_rect, texture| egui::ClippedPrimitive {
+            clip_rect,
+            primitive: egui::epaint::Primitive::Mesh(egui::Mesh {
+                indices: vec![0, 1, 2],
+                texture_id: egui::TextureId::Managed(texture),
+                ..Default::default()
+            }),
+        };
+        let screen = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(100.0, 100.0));
+        let outside = egui::Rect::from_min_size(egui::pos2(200.0, 0.0), egui::vec2(10.0, 10.0));
+        let jobs = [
+            job(screen, 0),
+            job(screen, 1),
+            job(outside, 1),
+            job(screen.shrink(10.0), 1),
+            job(screen, 0),
+        ];
+        let screen_descriptor = egui_wgpu_backend::ScreenDescriptor {
+            physical_width: 200,
+            physical_height: 200,
+            scale_factor: 2.0,
+        };
+        let stats = PassStats::new("ui", &jobs, &screen_descriptor);
+        assert_eq!(stats.draw_calls, 4);
+        assert_eq!(stats.triangles, 4);
+        assert_eq!(stats.scissor_rects, 4);
+        assert_eq!(stats.scissor_changes, 3);
+        assert_eq!(stats.texture_binds, 4);
+        assert_eq!(stats.texture_switches, 3);
+    }
+}
@@ -0,0 +1,173 @@
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{
+    DeviceId, ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+    TouchPhase, VirtualKeyCode, WindowEvent,
+};
+use winit::window::WindowId;
+
+use crate::Event;
+
+/// The keys that are pressed at random. Mostly the ones egui reacts to.
+const KEYS: &[VirtualKeyCode] = &[
+    VirtualKeyCode::Tab,
+    VirtualKeyCode::Return,
+    VirtualKeyCode::Escape,
+    VirtualKeyCode::Space,
+    VirtualKeyCode::Back,
+    VirtualKeyCode::Delete,
+    VirtualKeyCode::Left,
+    VirtualKeyCode::Right,
+    VirtualKeyCode::Up,
+    VirtualKeyCode::Down,
+    VirtualKeyCode::Home,
+    VirtualKeyCode::End,
+    VirtualKeyCode::PageUp,
+    VirtualKeyCode::PageDown,
+    VirtualKeyCode::A,
+    VirtualKeyCode::C,
+    VirtualKeyCode::V,
+    VirtualKeyCode::X,
+    VirtualKeyCode::Z,
+];
+
+const BUTTONS: &[MouseButton] = &[MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+/// Monkey testing: injects random but valid pointer, keyboard and scroll events every frame.
+///
+/// Buttons and keys are only released after they were pressed, and the pointer stays inside the
+/// window, just like with real input. The same seed gives the same event sequence.
+pub struct Fuzzer {
+    seed: u64,
+    state: u64,
+    cursor: PhysicalPosition<f64>,
+    buttons_down: Vec<MouseButton>,
+    keys_down: Vec<VirtualKeyCode>,
+}
+
+impl Fuzzer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            state: seed,
+            cursor: PhysicalPosition::new(0.0, 0.0),
+            buttons_down: Vec::new(),
+            keys_down: Vec::new(),
+        }
+    }
+
+    /// The seed to pass to `--fuzz-seed` to reproduce a run.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Hands the random events of the next frame to `f`.
+    #[allow(deprecated)]
+    pub fn next_frame(
+        &mut self,
+        window_id: WindowId,
+        size: PhysicalSize<u32>,
+        mut f: impl FnMut(&winit::event::Event<Event>),
+    ) {
+        // The platform integration doesn't look at the device.
+        let device_id = unsafe { DeviceId::dummy() };
+        let modifiers = ModifiersState::empty();
+        for _ in 0..self.below(4) {
+            let event = match self.below(10) {
+                0..=3 => {
+                    // Mostly small moves, sometimes a jump across the window.
+                    self.cursor = if self.below(5) == 0 {
+                        PhysicalPosition::new(
+                            self.unit() * size.width as f64,
+                            self.unit() * size.height as f64,
+                        )
+                    } else {
+                        PhysicalPosition::new(
+                            (self.cursor.x + (self.unit() - 0.5) * 80.0)
+                                .clamp(0.0, size.width as f64),
+                            (self.cursor.y + (self.unit() - 0.5) * 80.0)
+                                .clamp(0.0, size.height as f64),
+                        )
+                    };
+                    WindowEvent::CursorMoved {
+                        device_id,
+                        position: self.cursor,
+                        modifiers,
+                    }
+                }
+                4 | 5 => {
+                    let button = BUTTONS[self.below(BUTTONS.len() as u64) as usize];
+                    let state = self.toggle(button, |fuzzer| &mut fuzzer.buttons_down);
+                    WindowEvent::MouseInput {
+                        device_id,
+                        state,
+                        button,
+                        modifiers,
+                    }
+                }
+                6 => WindowEvent::MouseWheel {
+                    device_id,
+                    delta: MouseScrollDelta::LineDelta(
+                        (self.unit() as f32 - 0.5) * 4.0,
+                        (self.unit() as f32 - 0.5) * 8.0,
+                    ),
+                    phase: TouchPhase::Moved,
+                    modifiers,
+                },
+                7 | 8 => {
+                    let key = KEYS[self.below(KEYS.len() as u64) as usize];
+                    let state = self.toggle(key, |fuzzer| &mut fuzzer.keys_down);
+                    WindowEvent::KeyboardInput {
+                        device_id,
+                        input: KeyboardInput {
+                            scancode: 0,
+                            state,
+                            virtual_keycode: Some(key),
+                            modifiers,
+                        },
+                        is_synthetic: false,
+                    }
+                }
+                _ => WindowEvent::ReceivedCharacter((b' ' + self.below(95) as u8) as char),
+            };
+            f(&winit::event::Event::WindowEvent { window_id, event });
+        }
+    }
+
+    /// Presses `item` if it's up, and releases it if it's down.
+    fn toggle<T: PartialEq>(
+        &mut self,
+        item: T,
+        down: impl FnOnce(&mut Self) -> &mut Vec<T>,
+    ) -> ElementState {
+        let down = down(self);
+        match down.iter().position(|other| *other == item) {
+            Some(index) => {
+                down.remove(index);
+                ElementState::Released
+            }
+            None => {
+                down.push(item);
+                ElementState::Pressed
+            }
+        }
+    }
+
+    /// A random number in `0..n`.
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    /// A random number in `0.0..1.0`.
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// SplitMix64. Good enough to pick events, and reproducible everywhere.
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
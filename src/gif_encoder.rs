@@ -0,0 +1,117 @@
+use std::io::{self, Write};
+
+/// How many pixels NeuQuant skips while it learns the palette of a frame, higher is faster.
+const SAMPLE_FACTOR: i32 = 10;
+/// The bits of a palette index, for 256 colors.
+const INDEX_BITS: u8 = 8;
+
+/// Writes an animated GIF that loops forever. Every frame gets a palette of its own of 256
+/// colors, which NeuQuant picks, so that UIs with few flat colors come out without dithering.
+pub struct GifEncoder<W: Write> {
+    out: W,
+}
+
+impl<W: Write> GifEncoder<W> {
+    /// Writes the header of an animation of `size` pixels.
+    pub fn new(mut out: W, size: [u16; 2]) -> io::Result<Self> {
+        out.write_all(b"GIF89a")?;
+        out.write_all(&size[0].to_le_bytes())?;
+        out.write_all(&size[1].to_le_bytes())?;
+        // No global palette, background color 0, square pixels.
+        out.write_all(&[0, 0, 0])?;
+        // Loops forever.
+        out.write_all(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+        Ok(Self { out })
+    }
+
+    /// Adds a frame of RGBA `pixels` of `size`, in the top left corner, that's shown for
+    /// `delay` hundredths of a second.
+    pub fn frame(&mut self, pixels: &[u8], size: [u16; 2], delay: u16) -> io::Result<()> {
+        let quantizer = color_quant::NeuQuant::new(SAMPLE_FACTOR, 256, pixels);
+        let mut last = None;
+        let indices: Vec<u8> = pixels
+            .chunks_exact(4)
+            .map(|pixel| match last {
+                // UIs have long runs of the same color, the search is the slow part.
+                Some((last_pixel, index)) if last_pixel == pixel => index,
+                _ => {
+                    let index = quantizer.index_of(pixel) as u8;
+                    last = Some((pixel, index));
+                    index
+                }
+            })
+            .collect();
+        let data = weezl::encode::Encoder::new(weezl::BitOrder::Lsb, INDEX_BITS)
+            .encode(&indices)
+            .map_err(|err| io::Error::other(err.to_string()))?;
+
+        // The graphic control extension with the delay, without transparency.
+        self.out.write_all(&[0x21, 0xF9, 0x04, 0x00])?;
+        self.out.write_all(&delay.to_le_bytes())?;
+        self.out.write_all(&[0x00, 0x00])?;
+        // The image descriptor, with a local palette of 2⁸ colors.
+        self.out.write_all(&[0x2C, 0, 0, 0, 0])?;
+        self.out.write_all(&size[0].to_le_bytes())?;
+        self.out.write_all(&size[1].to_le_bytes())?;
+        self.out.write_all(&[0x80 | (INDEX_BITS - 1)])?;
+        let mut palette = quantizer.color_map_rgb();
+        palette.resize(256 * 3, 0);
+        self.out.write_all(&palette)?;
+        self.out.write_all(&[INDEX_BITS])?;
+        for block in data.chunks(255) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0])
+    }
+
+    /// Ends the animation.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.write_all(&[0x3B])?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_keep_their_colors() {
+        let colors = [[255, 0, 0, 255], [0, 0, 255, 255], [20, 20, 20, 255]];
+        let pixels: Vec<u8> = (0..300).flat_map(|index| colors[index % 3]).collect();
+        let mut encoder = GifEncoder::new(Vec::new(), [20, 15]).unwrap();
+        encoder.frame(&pixels, [20, 15], 7).unwrap();
+        let gif = encoder.finish().unwrap();
+
+        assert_eq!(&gif[..10], b"GIF89a\x14\x00\x0F\x00");
+        assert_eq!(gif.last(), Some(&0x3B));
+        // The header, the loop extension, the delay and the image descriptor come first.
+        let image = 13 + 19;
+        assert_eq!(gif[image + 4..image + 6], [7, 0]);
+        let palette = &gif[image + 18..image + 18 + 768];
+        let mut data = Vec::new();
+        let mut block = image + 18 + 768 + 1;
+        while gif[block] != 0 {
+            let len = gif[block] as usize;
+            data.extend_from_slice(&gif[block + 1..block + 1 + len]);
+            block += 1 + len;
+        }
+        let indices = weezl::decode::Decoder::new(weezl::BitOrder::Lsb, INDEX_BITS)
+            .decode(&data)
+            .unwrap();
+        assert_eq!(indices.len(), 300);
+        for (pixel, index) in pixels.chunks_exact(4).zip(indices) {
+            let color = &palette[index as usize * 3..index as usize * 3 + 3];
+            for (channel, expected) in color.iter().zip(pixel) {
+                assert!(
+                    channel.abs_diff(*expected) <= 8,
+                    "{:?} for {:?}",
+                    color,
+                    pixel
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use glam::{EulerRot, Mat4, Quat, Vec3, Vec4Swizzles};
+
+use crate::scene_renderer::Camera;
+
+/// How close the pointer has to come to a handle to grab it, in points.
+const GRAB_DISTANCE: f32 = 8.0;
+/// How long the handles are, as a part of the distance to the camera.
+const HANDLE_LENGTH: f32 = 0.2;
+/// The segments of a rotation ring.
+const RING_SEGMENTS: usize = 48;
+const AXES: [Vec3; 3] = [Vec3::X, Vec3::Y, Vec3::Z];
+const AXIS_COLORS: [egui::Color32; 3] = [
+    egui::Color32::from_rgb(230, 70, 70),
+    egui::Color32::from_rgb(90, 200, 90),
+    egui::Color32::from_rgb(80, 130, 240),
+];
+
+/// Where an entity is placed.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: Vec3,
+    /// Euler angles in degrees, applied around Y, X and then Z.
+    pub rotation: Vec3,
+    pub scale: Vec3,
+}
+
+impl Transform {
+    /// The transform of `matrix`, which can't have a shear.
+    pub fn from_matrix(matrix: Mat4) -> Self {
+        let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
+        let mut transform = Self {
+            translation,
+            rotation: Vec3::ZERO,
+            scale,
+        };
+        transform.set_quat(rotation);
+        transform
+    }
+
+    fn set_quat(&mut self, rotation: Quat) {
+        let (y, x, z) = rotation.to_euler(EulerRot::YXZ);
+        self.rotation = Vec3::new(x, y, z) * 180.0 / std::f32::consts::PI;
+    }
+
+    fn quat(&self) -> Quat {
+        Quat::from_euler(
+            EulerRot::YXZ,
+            self.rotation.y.to_radians(),
+            self.rotation.x.to_radians(),
+            self.rotation.z.to_radians(),
+        )
+    }
+
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.quat(), self.translation)
+    }
+}
+
+/// What dragging a handle of the gizmo changes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    Translate,
+    Rotate,
+    Scale,
+}
+
+/// The steps the gizmo moves in while snapping is on.
+pub struct Snapping {
+    pub enabled: bool,
+    pub translation: f32,
+    /// In degrees.
+    pub rotation: f32,
+    pub scale: f32,
+}
+
+impl Default for Snapping {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            translation: 0.25,
+            rotation: 15.0,
+            scale: 0.1,
+        }
+    }
+}
+
+/// Handles along the axes of the space a [`Transform`] is in, drawn over a viewport at its
+/// origin, which move, rotate or scale it when they are dragged.
+#[derive(Default)]
+pub struct Gizmo {
+    pub mode: Mode,
+    pub snapping: Snapping,
+    /// The axis of the handle under the pointer.
+    hovered: Option<usize>,
+    drag: Option<Drag>,
+}
+
+struct Drag {
+    axis: usize,
+    start: Transform,
+    start_pointer: egui::Pos2,
+    /// The angle of the pointer around the origin in the last frame, and the sum of its changes,
+    /// in radians.
+    pointer_angle: f32,
+    angle: f32,
+}
+
+/// The handles on screen.
+struct Handles {
+    origin: egui::Pos2,
+    /// The length of the handles in the world.
+    length: f32,
+    /// How long a unit along each axis of the space is in the world.
+    units: [f32; 3],
+    /// The tips of the translation and scale handles.
+    tips: [Option<egui::Pos2>; 3],
+    /// The rotation rings, without the points behind the camera.
+    rings: [Vec<egui::Pos2>; 3],
+    /// Whether each axis points towards the camera.
+    facing: [bool; 3],
+}
+
+impl Gizmo {
+    /// Grabs the handle under the pointer in `rect` before the viewport takes the drag, and
+    /// changes `transform` while it's dragged. Returns the transform before the drag once it
+    /// ended with a change, for the undo history. `space` is where `transform` is in, the world
+    /// or its parent.
+    pub fn interact(
+        &mut self,
+        ui: &egui::Ui,
+        rect: egui::Rect,
+        camera: &Camera,
+        space: Mat4,
+        transform: &mut Transform,
+        enabled: bool,
+    ) -> Option<Transform> {
+        let handles = match Handles::new(rect, camera, space, transform.translation) {
+            Some(handles) => handles,
+            None => {
+                self.hovered = None;
+                self.drag = None;
+                return None;
+            }
+        };
+        let pointer = ui.input().pointer.hover_pos();
+        if self.drag.is_none() {
+            self.hovered = pointer
+                .filter(|_| enabled)
+                .and_then(|pointer| handles.hit(self.mode, pointer));
+        }
+        if self.hovered.is_none() && self.drag.is_none() {
+            return None;
+        }
+        let response = ui.interact(rect, ui.id().with("gizmo"), egui::Sense::click_and_drag());
+        if response.drag_started() {
+            if let (Some(axis), Some(start_pointer)) =
+                (self.hovered, ui.input().pointer.press_origin())
+            {
+                self.drag = Some(Drag {
+                    axis,
+                    start: *transform,
+                    start_pointer,
+                    pointer_angle: (start_pointer - handles.origin).angle(),
+                    angle: 0.0,
+                });
+            }
+        }
+        if response.dragged() {
+            if let (true, Some(pointer)) = (self.drag.is_some(), pointer) {
+                *transform = self.drag_to(&handles, pointer);
+            }
+            return None;
+        }
+        self.drag
+            .take()
+            .map(|drag| drag.start)
+            .filter(|start| start != transform)
+    }
+
+    /// The transform at the start of the drag, changed by moving the pointer to `pointer`.
+    fn drag_to(&mut self, handles: &Handles, pointer: egui::Pos2) -> Transform {
+        let drag = self.drag.as_mut().expect("A handle is dragged");
+        let axis = drag.axis;
+        let snap = |value: f32, step: f32| match self.snapping.enabled && step > 0.0 {
+            true => (value / step).round() * step,
+            false => value,
+        };
+        let mut transform = drag.start;
+        match self.mode {
+            Mode::Translate | Mode::Scale => {
+                let tip = match handles.tips[axis] {
+                    Some(tip) => tip,
+                    None => return transform,
+                };
+                let along = tip - handles.origin;
+                if along.length_sq() < 1.0 {
+                    return transform;
+                }
+                // How far the pointer moved along the handle, in handle lengths.
+                let moved = (pointer - drag.start_pointer).dot(along) / along.length_sq();
+                if self.mode == Mode::Translate {
+                    let offset = snap(
+                        moved * handles.length / handles.units[axis],
+                        self.snapping.translation,
+                    );
+                    transform.translation[axis] += offset;
+                } else {
+                    let scale = snap(drag.start.scale[axis] * (1.0 + moved), self.snapping.scale);
+                    transform.scale[axis] = scale.max(0.01);
+                }
+            }
+            Mode::Rotate => {
+                let pointer_angle = (pointer - handles.origin).angle();
+                let mut change = pointer_angle - drag.pointer_angle;
+                if change > std::f32::consts::PI {
+                    change -= std::f32::consts::TAU;
+                } else if change < -std::f32::consts::PI {
+                    change += std::f32::consts::TAU;
+                }
+                drag.pointer_angle = pointer_angle;
+                drag.angle += change;
+                // Screen angles turn clockwise, as y points down.
+                let angle = match handles.facing[axis] {
+                    true => -drag.angle,
+                    false => drag.angle,
+                };
+                let angle = snap(angle.to_degrees(), self.snapping.rotation).to_radians();
+                transform.set_quat(Quat::from_axis_angle(AXES[axis], angle) * drag.start.quat());
+            }
+        }
+        transform
+    }
+
+    pub fn paint(
+        &self,
+        painter: &egui::Painter,
+        rect: egui::Rect,
+        camera: &Camera,
+        space: Mat4,
+        transform: &Transform,
+    ) {
+        let handles = match Handles::new(rect, camera, space, transform.translation) {
+            Some(handles) => handles,
+            None => return,
+        };
+        let active = self.drag.as_ref().map(|drag| drag.axis).or(self.hovered);
+        for (axis, axis_color) in AXIS_COLORS.into_iter().enumerate() {
+            let highlighted = active == Some(axis);
+            let color = match highlighted {
+                true => egui::Color32::from_rgb(255, 220, 80),
+                false => axis_color,
+            };
+            let stroke = egui::Stroke::new(if highlighted { 3.5 } else { 2.0 }, color);
+            match (self.mode, handles.tips[axis]) {
+                (Mode::Rotate, _) => {
+                    painter.add(egui::Shape::line(handles.rings[axis].clone(), stroke));
+                }
+                (Mode::Translate, Some(tip)) => {
+                    painter.line_segment([handles.origin, tip], stroke);
+                    let direction = (tip - handles.origin).normalized();
+                    let side = direction.rot90() * 5.0;
+                    let base = tip - direction * 10.0;
+                    painter.add(egui::Shape::convex_polygon(
+                        vec![tip, base + side, base - side],
+                        color,
+                        egui::Stroke::none(),
+                    ));
+                }
+                (Mode::Scale, Some(tip)) => {
+                    painter.line_segment([handles.origin, tip], stroke);
+                    painter.rect_filled(
+                        egui::Rect::from_center_size(tip, egui::Vec2::splat(9.0)),
+                        0.0,
+                        color,
+                    );
+                }
+                _ => {}
+            }
+        }
+        painter.circle_filled(handles.origin, 3.0, egui::Color32::WHITE);
+    }
+}
+
+impl Handles {
+    /// `None` while `origin` is behind the camera.
+    fn new(rect: egui::Rect, camera: &Camera, space: Mat4, origin: Vec3) -> Option<Self> {
+        let view_projection = camera.view_projection(rect.size());
+        let project = |point: Vec3| {
+            let clip = view_projection * point.extend(1.0);
+            (clip.w > 0.0).then(|| {
+                let ndc = clip.xy() / clip.w;
+                rect.min + egui::vec2((ndc.x + 1.0) / 2.0, (1.0 - ndc.y) / 2.0) * rect.size()
+            })
+        };
+        let origin = space.transform_point3(origin);
+        let axes = AXES.map(|axis| space.transform_vector3(axis));
+        let units = axes.map(|axis| axis.length().max(f32::EPSILON));
+        let axes = axes.map(|axis| axis.normalize_or_zero());
+        let to_camera = camera.eye - origin;
+        let length = to_camera.length() * HANDLE_LENGTH;
+        let rings = axes.map(|axis| {
+            let u = axis.any_orthonormal_vector();
+            let v = axis.cross(u);
+            (0..=RING_SEGMENTS)
+                .filter_map(|segment| {
+                    let angle = segment as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+                    project(origin + (u * angle.cos() + v * angle.sin()) * length)
+                })
+                .collect()
+        });
+        Some(Self {
+            origin: project(origin)?,
+            length,
+            units,
+            tips: axes.map(|axis| project(origin + axis * length)),
+            rings,
+            facing: axes.map(|axis| axis.dot(to_camera) > 0.0),
+        })
+    }
+
+    /// The axis of the handle of `mode` nearest to `pointer`, if it's close enough.
+    fn hit(&self, mode: Mode, pointer: egui::Pos2) -> Option<usize> {
+        (0..3)
+            .filter_map(|axis| {
+                let distance = match mode {
+                    Mode::Rotate => self.rings[axis]
+                        .windows(2)
+                        .map(|segment| distance_to_segment(pointer, segment[0], segment[1]))
+                        .fold(f32::INFINITY, f32::min),
+                    Mode::Translate | Mode::Scale => {
+                        distance_to_segment(pointer, self.origin, self.tips[axis]?)
+                    }
+                };
+                (distance <= GRAB_DISTANCE).then_some((axis, distance))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(axis, _)| axis)
+    }
+}
+
+fn distance_to_segment(point: egui::Pos2, start: egui::Pos2, end: egui::Pos2) -> f32 {
+    let along = end - start;
+    let t = match along.length_sq() > 0.0 {
+        true => ((point - start).dot(along) / along.length_sq()).clamp(0.0, 1.0),
+        false => 0.0,
+    };
+    point.distance(start + along * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gizmo_at_origin() -> (Gizmo, Handles, Transform) {
+        let camera = Camera {
+            eye: Vec3::new(0.0, 0.0, 10.0),
+            target: Vec3::ZERO,
+            fov: 45f32.to_radians(),
+        };
+        let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(400.0, 400.0));
+        let transform = Transform {
+            translation: Vec3::ZERO,
+            rotation: Vec3::ZERO,
+            scale: Vec3::ONE,
+        };
+        let handles = Handles::new(rect, &camera, Mat4::IDENTITY, Vec3::ZERO).unwrap();
+        (Gizmo::default(), handles, transform)
+    }
+
+    #[test]
+    fn translation_follows_the_handle_and_snaps() {
+        let (mut gizmo, handles, transform) = gizmo_at_origin();
+        let tip = handles.tips[0].unwrap();
+        assert_eq!(handles.hit(Mode::Translate, tip), Some(0));
+        gizmo.drag = Some(Drag {
+            axis: 0,
+            start: transform,
+            start_pointer: tip,
+            pointer_angle: 0.0,
+            angle: 0.0,
+        });
+        // Half a handle to the right, and up, which doesn't count along the X axis.
+        let pointer = tip + (tip - handles.origin) * 0.5 + egui::vec2(0.0, -30.0);
+        let moved = gizmo.drag_to(&handles, pointer);
+        assert!((moved.translation.x - handles.length * 0.5).abs() < 1e-4);
+        assert_eq!(moved.translation.y, 0.0);
+        gizmo.snapping.enabled = true;
+        gizmo.snapping.translation = 0.25;
+        let snapped = gizmo.drag_to(&handles, pointer).translation.x;
+        assert_eq!(snapped, (handles.length * 0.5 / 0.25).round() * 0.25);
+    }
+
+    #[test]
+    fn rotation_snaps_to_the_step() {
+        let (mut gizmo, handles, transform) = gizmo_at_origin();
+        gizmo.mode = Mode::Rotate;
+        gizmo.snapping.enabled = true;
+        let start_pointer = handles.origin + egui::vec2(50.0, 0.0);
+        gizmo.drag = Some(Drag {
+            axis: 2,
+            start: transform,
+            start_pointer,
+            pointer_angle: 0.0,
+            angle: 0.0,
+        });
+        // 40° counter-clockwise on screen, around the Z axis that faces the camera.
+        let angle = -40f32.to_radians();
+        let pointer = handles.origin + egui::vec2(angle.cos(), angle.sin()) * 50.0;
+        let rotated = gizmo.drag_to(&handles, pointer);
+        assert!((rotated.rotation.z - 45.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,191 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use winit::event_loop::EventLoopProxy;
+
+use crate::Event;
+
+/// How often the listener checks whether it should stop.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A system-wide Ctrl+Shift+Space that sends [`Event::ToggleVisibility`], also while the window
+/// is in the background or hidden. Unregistered when dropped.
+///
+/// Uses `XGrabKey` on X11 and `RegisterHotKey` on Windows. Wayland has no global hotkeys, there
+/// it only works for windows on XWayland, see `--x11`.
+pub struct GlobalHotkey {
+    stop: Arc<AtomicBool>,
+}
+
+impl GlobalHotkey {
+    /// Registers the hotkey on a thread of its own. Fails if the window system doesn't support
+    /// it, or another application already took the combination.
+    pub fn register(proxy: EventLoopProxy<Event>) -> Result<Self, String> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let (registered, result) = mpsc::channel();
+        let thread_stop = stop.clone();
+        std::thread::Builder::new()
+            .name("global_hotkey".to_owned())
+            .spawn(move || {
+                listen(&thread_stop, registered, || {
+                    proxy.send_event(Event::ToggleVisibility).ok();
+                })
+            })
+            .map_err(|err| format!("Can't start the hotkey thread: {}", err))?;
+        result
+            .recv()
+            .map_err(|_| "The hotkey thread exited".to_owned())??;
+        Ok(Self { stop })
+    }
+}
+
+impl Drop for GlobalHotkey {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn listen(stop: &AtomicBool, registered: mpsc::Sender<Result<(), String>>, pressed: impl Fn()) {
+    use std::os::raw::c_int;
+    use x11_dl::xlib;
+
+    /// Set by the error handler when another client already grabbed the key.
+    static GRAB_FAILED: AtomicBool = AtomicBool::new(false);
+    unsafe extern "C" fn on_error(_: *mut xlib::Display, error: *mut xlib::XErrorEvent) -> c_int {
+        if (*error).error_code == xlib::BadAccess {
+            GRAB_FAILED.store(true, Ordering::Relaxed);
+        }
+        0
+    }
+
+    let xlib = match xlib::Xlib::open() {
+        Ok(xlib) => xlib,
+        Err(err) => {
+            registered
+                .send(Err(format!("Can't load Xlib: {}", err)))
+                .ok();
+            return;
+        }
+    };
+    unsafe {
+        // A connection of its own, so that waiting for the key doesn't block winit.
+        let display = (xlib.XOpenDisplay)(std::ptr::null());
+        if display.is_null() {
+            registered
+                .send(Err(
+                    "There is no X11 display to grab the hotkey on".to_owned()
+                ))
+                .ok();
+            return;
+        }
+        let root = (xlib.XDefaultRootWindow)(display);
+        let keycode = (xlib.XKeysymToKeycode)(display, x11_dl::keysym::XK_space as _) as c_int;
+        let modifiers = xlib::ControlMask | xlib::ShiftMask;
+        // The grab only matches the exact modifiers, so Caps Lock and Num Lock get their own.
+        let variants = [
+            0,
+            xlib::LockMask,
+            xlib::Mod2Mask,
+            xlib::LockMask | xlib::Mod2Mask,
+        ];
+        // The default handler would exit the process on the error of a taken key.
+        GRAB_FAILED.store(false, Ordering::Relaxed);
+        let previous = (xlib.XSetErrorHandler)(Some(on_error));
+        for variant in variants {
+            (xlib.XGrabKey)(
+                display,
+                keycode,
+                modifiers | variant,
+                root,
+                xlib::False,
+                xlib::GrabModeAsync,
+                xlib::GrabModeAsync,
+            );
+        }
+        (xlib.XSync)(display, xlib::False);
+        (xlib.XSetErrorHandler)(previous);
+        if GRAB_FAILED.load(Ordering::Relaxed) {
+            (xlib.XCloseDisplay)(display);
+            registered
+                .send(Err(
+                    "Ctrl+Shift+Space is already taken by another application".to_owned(),
+                ))
+                .ok();
+            return;
+        }
+        registered.send(Ok(())).ok();
+
+        let mut event: xlib::XEvent = std::mem::zeroed();
+        while !stop.load(Ordering::Relaxed) {
+            if (xlib.XPending)(display) == 0 {
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            (xlib.XNextEvent)(display, &mut event);
+            if event.get_type() == xlib::KeyPress {
+                pressed();
+            }
+        }
+        // Closing the connection releases the grabs.
+        (xlib.XCloseDisplay)(display);
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn listen(stop: &AtomicBool, registered: mpsc::Sender<Result<(), String>>, pressed: impl Fn()) {
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+        RegisterHotKey, UnregisterHotKey, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT, VK_SPACE,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{PeekMessageW, MSG, PM_REMOVE, WM_HOTKEY};
+
+    const ID: i32 = 1;
+    unsafe {
+        // Without a window the messages go to the queue of this thread.
+        let hwnd = std::ptr::null_mut();
+        let modifiers = MOD_CONTROL | MOD_SHIFT | MOD_NOREPEAT;
+        if RegisterHotKey(hwnd, ID, modifiers, VK_SPACE as u32) == 0 {
+            registered
+                .send(Err(format!(
+                    "Can't register Ctrl+Shift+Space: {}",
+                    std::io::Error::last_os_error()
+                )))
+                .ok();
+            return;
+        }
+        registered.send(Ok(())).ok();
+
+        let mut message: MSG = std::mem::zeroed();
+        while !stop.load(Ordering::Relaxed) {
+            if PeekMessageW(&mut message, hwnd, 0, 0, PM_REMOVE) == 0 {
+                std::thread::sleep(POLL_INTERVAL);
+            } else if message.message == WM_HOTKEY {
+                pressed();
+            }
+        }
+        UnregisterHotKey(hwnd, ID);
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "windows"
+)))]
+fn listen(_: &AtomicBool, registered: mpsc::Sender<Result<(), String>>, _: impl Fn()) {
+    registered
+        .send(Err(
+            "Global hotkeys aren't supported on this platform".to_owned()
+        ))
+        .ok();
+}
@@ -0,0 +1,249 @@
+use std::fmt::Write;
+
+/// Everything wgpu tells about the adapter, the device and the surface, collected at startup.
+///
+/// Shown in the "GPU report" window, and can be copied as markdown for bug reports.
+pub struct GpuReport {
+    pub info: wgpu::AdapterInfo,
+    adapter_features: wgpu::Features,
+    device_features: wgpu::Features,
+    adapter_limits: wgpu::Limits,
+    device_limits: wgpu::Limits,
+    downlevel: wgpu::DownlevelCapabilities,
+    surface_formats: Vec<wgpu::TextureFormat>,
+    present_modes: Vec<wgpu::PresentMode>,
+    surface_format: wgpu::TextureFormat,
+}
+
+/// Lists all limits as `(name, adapter limit, device limit)`.
+macro_rules! limits {
+    ($adapter:expr, $device:expr, $($name:ident),* $(,)?) => {
+        vec![$((stringify!($name), $adapter.$name as u64, $device.$name as u64)),*]
+    };
+}
+
+impl GpuReport {
+    /// The present modes the surface supports.
+    pub fn present_modes(&self) -> &[wgpu::PresentMode] {
+        &self.present_modes
+    }
+
+    /// Queries the handles. `surface_format` is the format the surface got configured with.
+    pub fn new(
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        surface: &wgpu::Surface,
+        surface_format: wgpu::TextureFormat,
+    ) -> Self {
+        Self {
+            info: adapter.get_info(),
+            adapter_features: adapter.features(),
+            device_features: device.features(),
+            adapter_limits: adapter.limits(),
+            device_limits: device.limits(),
+            downlevel: adapter.get_downlevel_capabilities(),
+            surface_formats: surface.get_supported_formats(adapter),
+            present_modes: surface.get_supported_modes(adapter),
+            surface_format,
+        }
+    }
+
+    fn limits(&self) -> Vec<(&'static str, u64, u64)> {
+        limits!(
+            self.adapter_limits,
+            self.device_limits,
+            max_texture_dimension_1d,
+            max_texture_dimension_2d,
+            max_texture_dimension_3d,
+            max_texture_array_layers,
+            max_bind_groups,
+            max_dynamic_uniform_buffers_per_pipeline_layout,
+            max_dynamic_storage_buffers_per_pipeline_layout,
+            max_sampled_textures_per_shader_stage,
+            max_samplers_per_shader_stage,
+            max_storage_buffers_per_shader_stage,
+            max_storage_textures_per_shader_stage,
+            max_uniform_buffers_per_shader_stage,
+            max_uniform_buffer_binding_size,
+            max_storage_buffer_binding_size,
+            max_vertex_buffers,
+            max_vertex_attributes,
+            max_vertex_buffer_array_stride,
+            max_push_constant_size,
+            min_uniform_buffer_offset_alignment,
+            min_storage_buffer_offset_alignment,
+            max_inter_stage_shader_components,
+            max_compute_workgroup_storage_size,
+            max_compute_invocations_per_workgroup,
+            max_compute_workgroup_size_x,
+            max_compute_workgroup_size_y,
+            max_compute_workgroup_size_z,
+            max_compute_workgroups_per_dimension,
+            max_buffer_size,
+        )
+    }
+
+    /// Formats the whole report as markdown.
+    pub fn markdown(&self) -> String {
+        let mut md = String::new();
+        // Writing into a `String` can't fail.
+        let _ = self.write_markdown(&mut md);
+        md
+    }
+
+    fn write_markdown(&self, md: &mut String) -> std::fmt::Result {
+        writeln!(md, "## GPU report")?;
+        writeln!(md)?;
+        writeln!(md, "| Adapter | |")?;
+        writeln!(md, "|---|---|")?;
+        writeln!(md, "| Name | {} |", self.info.name)?;
+        writeln!(md, "| Vendor | 0x{:04X} |", self.info.vendor)?;
+        writeln!(md, "| Device | 0x{:04X} |", self.info.device)?;
+        writeln!(md, "| Type | {:?} |", self.info.device_type)?;
+        writeln!(md, "| Backend | {:?} |", self.info.backend)?;
+        writeln!(md, "| Shader model | {:?} |", self.downlevel.shader_model)?;
+        writeln!(md)?;
+
+        writeln!(md, "### Features")?;
+        writeln!(md)?;
+        for feature in flag_names(self.adapter_features) {
+            let enabled = if flag_names(self.device_features).contains(&feature) {
+                " (enabled)"
+            } else {
+                ""
+            };
+            writeln!(md, "- {}{}", feature, enabled)?;
+        }
+        writeln!(md)?;
+
+        writeln!(md, "### Downlevel flags")?;
+        writeln!(md)?;
+        for flag in flag_names(self.downlevel.flags) {
+            writeln!(md, "- {}", flag)?;
+        }
+        writeln!(md)?;
+
+        writeln!(md, "### Limits")?;
+        writeln!(md)?;
+        writeln!(md, "| Limit | Adapter | Device |")?;
+        writeln!(md, "|---|---|---|")?;
+        for (name, adapter, device) in self.limits() {
+            writeln!(md, "| {} | {} | {} |", name, adapter, device)?;
+        }
+        writeln!(md)?;
+
+        writeln!(md, "### Surface")?;
+        writeln!(md)?;
+        writeln!(md, "- Configured format: {:?}", self.surface_format)?;
+        writeln!(md, "- Formats: {:?}", self.surface_formats)?;
+        writeln!(md, "- Present modes: {:?}", self.present_modes)?;
+        Ok(())
+    }
+
+    /// Shows the report window.
+    pub fn show(&self, ctx: &egui::Context, open: &mut bool) {
+        egui::Window::new("🖥 GPU report")
+            .open(open)
+            .default_width(480.0)
+            .vscroll(true)
+            .show(ctx, |ui| self.ui(ui));
+    }
+
+    pub fn ui(&self, ui: &mut egui::Ui) {
+        if ui
+            .button("📋 Copy as markdown")
+            .on_hover_text("Paste this into bug reports")
+            .clicked()
+        {
+            ui.output().copied_text = self.markdown();
+        }
+
+        egui::CollapsingHeader::new("Adapter")
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::Grid::new("gpu_report_adapter")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        let rows = [
+                            ("Name", self.info.name.clone()),
+                            ("Vendor", format!("0x{:04X}", self.info.vendor)),
+                            ("Device", format!("0x{:04X}", self.info.device)),
+                            ("Type", format!("{:?}", self.info.device_type)),
+                            ("Backend", format!("{:?}", self.info.backend)),
+                            ("Shader model", format!("{:?}", self.downlevel.shader_model)),
+                        ];
+                        for (name, value) in rows {
+                            ui.label(name);
+                            ui.label(value);
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        egui::CollapsingHeader::new("Features").show(ui, |ui| {
+            let enabled = flag_names(self.device_features);
+            for feature in flag_names(self.adapter_features) {
+                if enabled.contains(&feature) {
+                    ui.label(egui::RichText::new(format!("✔ {}", feature)).strong());
+                } else {
+                    ui.label(format!("  {}", feature));
+                }
+            }
+            let unsupported = flag_names(wgpu::Features::all() - self.adapter_features);
+            ui.collapsing(format!("Unsupported ({})", unsupported.len()), |ui| {
+                for feature in unsupported {
+                    ui.weak(feature);
+                }
+            });
+        });
+
+        egui::CollapsingHeader::new("Downlevel flags").show(ui, |ui| {
+            let missing = flag_names(wgpu::DownlevelFlags::all() - self.downlevel.flags);
+            if missing.is_empty() {
+                ui.label("Fully WebGPU compliant");
+            }
+            for flag in missing {
+                ui.label(format!("✖ {}", flag));
+            }
+        });
+
+        egui::CollapsingHeader::new("Limits").show(ui, |ui| {
+            egui::Grid::new("gpu_report_limits")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Limit");
+                    ui.strong("Adapter");
+                    ui.strong("Device");
+                    ui.end_row();
+                    for (name, adapter, device) in self.limits() {
+                        ui.label(name);
+                        ui.label(adapter.to_string());
+                        ui.label(device.to_string());
+                        ui.end_row();
+                    }
+                });
+        });
+
+        egui::CollapsingHeader::new("Surface").show(ui, |ui| {
+            ui.label(format!("Configured format: {:?}", self.surface_format));
+            ui.label("Formats:");
+            for format in &self.surface_formats {
+                ui.label(format!("  {:?}", format));
+            }
+            ui.label("Present modes:");
+            for mode in &self.present_modes {
+                ui.label(format!("  {:?}", mode));
+            }
+            ui.weak("Alpha modes are not exposed by wgpu 0.13.");
+        });
+    }
+}
+
+/// The names of the set flags, based on the `Debug` output of bitflags (`A | B | C`).
+fn flag_names(flags: impl std::fmt::Debug) -> Vec<String> {
+    let names = format!("{:?}", flags);
+    if names == "(empty)" {
+        return Vec::new();
+    }
+    names.split(" | ").map(str::to_owned).collect()
+}
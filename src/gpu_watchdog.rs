@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use winit::event_loop::EventLoopProxy;
+
+use crate::Event;
+
+/// How often the watchdog looks at the submissions.
+const INTERVAL: Duration = Duration::from_millis(250);
+
+/// Watches that the GPU finishes the submitted frames. A frame that isn't done after the timeout
+/// means a hung GPU or driver, which would otherwise freeze the example for good: the watchdog
+/// writes a report for the next session, tells the event loop to save the config, shows an error
+/// and exits.
+///
+/// The watchdog thread polls the device itself, so that the completion of the last frame is seen
+/// even while the event loop sleeps.
+pub struct GpuWatchdog {
+    /// When the frames that aren't done yet were submitted, the oldest first.
+    submissions: Arc<Mutex<VecDeque<Instant>>>,
+}
+
+impl GpuWatchdog {
+    /// Starts the watchdog thread. It ends when the watchdog or the device is dropped.
+    pub fn start(
+        device: &Arc<wgpu::Device>,
+        timeout: Duration,
+        proxy: EventLoopProxy<Event>,
+    ) -> Self {
+        let submissions = Arc::new(Mutex::new(VecDeque::new()));
+        let watched = Arc::downgrade(&submissions);
+        let device = Arc::downgrade(device);
+        let spawned = std::thread::Builder::new()
+            .name("gpu watchdog".to_owned())
+            .spawn(move || watch(watched, device, timeout, proxy));
+        if let Err(err) = spawned {
+            eprintln!("Can't start the GPU watchdog: {}", err);
+        }
+        Self { submissions }
+    }
+
+    /// Tracks the frame that was just submitted to `queue`.
+    pub fn submitted(&self, queue: &wgpu::Queue) {
+        self.submissions.lock().unwrap().push_back(Instant::now());
+        let submissions = self.submissions.clone();
+        queue.on_submitted_work_done(move || {
+            submissions.lock().unwrap().pop_front();
+        });
+    }
+}
+
+fn watch(
+    submissions: Weak<Mutex<VecDeque<Instant>>>,
+    device: Weak<wgpu::Device>,
+    timeout: Duration,
+    proxy: EventLoopProxy<Event>,
+) {
+    loop {
+        std::thread::sleep(INTERVAL);
+        let (submissions, device) = match (submissions.upgrade(), device.upgrade()) {
+            (Some(submissions), Some(device)) => (submissions, device),
+            _ => return,
+        };
+        device.poll(wgpu::Maintain::Poll);
+        let (pending, oldest) = {
+            let submissions = submissions.lock().unwrap();
+            (submissions.len(), submissions.front().copied())
+        };
+        let waited = match oldest {
+            Some(submitted) => submitted.elapsed(),
+            None => continue,
+        };
+        if waited < timeout {
+            continue;
+        }
+
+        let details = format!(
+            "The GPU didn't finish a frame for {:.1} s, {} frames are waiting for it.",
+            waited.as_secs_f64(),
+            pending
+        );
+        eprintln!("{}", details);
+        let report = match crate::crash_report::write_hang_report(&details) {
+            Ok(path) => format!("The report is in {}.", path.display()),
+            Err(err) => format!("Can't write the report: {}", err),
+        };
+        eprintln!("{}", report);
+        // The event loop might be stuck on the GPU as well, then the config stays as it was.
+        proxy.send_event(Event::GpuHung).ok();
+        show_error(&format!(
+            "{}\n\nThe example has to close. {}",
+            details, report
+        ));
+        std::process::exit(1);
+    }
+}
+
+/// Shows `message` in a message box of the platform, or only on stderr where there is none.
+fn show_error(message: &str) {
+    const TITLE: &str = "The GPU stopped responding";
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows_sys::Win32::UI::WindowsAndMessaging::{
+            MessageBoxW, MB_ICONERROR, MB_OK, MB_TOPMOST,
+        };
+        let wide = |text: &str| text.encode_utf16().chain([0]).collect::<Vec<u16>>();
+        let (text, caption) = (wide(message), wide(TITLE));
+        unsafe {
+            MessageBoxW(
+                std::ptr::null_mut(),
+                text.as_ptr(),
+                caption.as_ptr(),
+                MB_OK | MB_ICONERROR | MB_TOPMOST,
+            );
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let script = format!(
+            "display alert {:?} message {:?} as critical",
+            TITLE, message
+        );
+        if let Err(err) = std::process::Command::new("osascript")
+            .args(["-e", &script])
+            .status()
+        {
+            eprintln!("Can't show the error: {}", err);
+        }
+    }
+
+    // Whatever the desktop has of zenity and kdialog.
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    {
+        let text = format!("{}\n\n{}", TITLE, message);
+        let shown = std::process::Command::new("zenity")
+            .args(["--error", "--no-wrap", "--no-markup", "--text", &text])
+            .status()
+            .or_else(|_| {
+                std::process::Command::new("kdialog")
+                    .args(["--error", &text])
+                    .status()
+            });
+        if shown.is_err() {
+            eprintln!("Neither zenity nor kdialog is there to show the error");
+        }
+    }
+
+    #[cfg(any(target_os = "android", target_os = "ios"))]
+    let _ = (TITLE, message);
+}
@@ -0,0 +1,178 @@
+use std::num::NonZeroU32;
+
+/// The target of the tonemapping, which egui can show like any other texture.
+const TARGET_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// An OpenEXR or Radiance HDR image in a `Rgba16Float` texture, tonemapped with a shader into
+/// a texture the image viewer shows.
+///
+/// egui_wgpu_backend 0.19 skips paint callbacks, so the tonemapping renders into a texture
+/// whenever the exposure or gamma change, instead of while egui draws.
+pub struct HdrImage {
+    pub exposure: f32,
+    pub gamma: f32,
+    size: wgpu::Extent3d,
+    pipeline: wgpu::RenderPipeline,
+    uniforms: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    target: wgpu::TextureView,
+}
+
+impl HdrImage {
+    /// Loads the image at `path` and tonemaps it. Returns the target texture to show along
+    /// with the image.
+    pub fn load(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: &std::path::Path,
+    ) -> Result<(Self, wgpu::Texture), String> {
+        let image = image::open(path)
+            .map_err(|err| err.to_string())?
+            .into_rgba32f();
+        let max_side = device.limits().max_texture_dimension_2d;
+        if image.width().max(image.height()) > max_side {
+            return Err(format!(
+                "HDR images can't be larger than {} pixels, only 8-bit ones are tiled",
+                max_side
+            ));
+        }
+        let size = wgpu::Extent3d {
+            width: image.width(),
+            height: image.height(),
+            depth_or_array_layers: 1,
+        };
+
+        let source = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("hdr_source"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba16Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        });
+        let pixels: Vec<u8> = image
+            .as_raw()
+            .iter()
+            .flat_map(|value| half::f16::from_f32(*value).to_le_bytes())
+            .collect();
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &source,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(size.width * 8),
+                rows_per_image: None,
+            },
+            size,
+        );
+        let target = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("hdr_tonemapped"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TARGET_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        });
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("tonemap.wgsl"));
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("tonemap_pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(TARGET_FORMAT.into())],
+            }),
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+            multiview: None,
+        });
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("tonemap_uniforms"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("tonemap_bind_group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniforms.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(
+                        &source.create_view(&Default::default()),
+                    ),
+                },
+            ],
+        });
+
+        let hdr_image = Self {
+            exposure: 0.0,
+            gamma: 2.2,
+            size,
+            pipeline,
+            uniforms,
+            bind_group,
+            target: target.create_view(&Default::default()),
+        };
+        hdr_image.tonemap(device, queue);
+        Ok((hdr_image, target))
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        [self.size.width, self.size.height]
+    }
+
+    /// The GPU memory of the source and the tonemapped texture.
+    pub fn bytes(&self) -> usize {
+        (self.size.width * self.size.height) as usize * (8 + 4)
+    }
+
+    /// Renders the image with the current exposure and gamma into the target texture.
+    pub fn tonemap(&self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let uniforms: Vec<u8> = [self.exposure, self.gamma, 0.0, 0.0]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        queue.write_buffer(&self.uniforms, 0, &uniforms);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("tonemap_encoder"),
+        });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("tonemap_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+}
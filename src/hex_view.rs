@@ -0,0 +1,385 @@
+use std::collections::BTreeMap;
+
+/// The bytes shown in a row.
+const BYTES_PER_ROW: usize = 16;
+/// The characters before the hex column: the offset and a gap.
+const HEX_COLUMN: usize = 10;
+/// The characters before the ASCII column.
+const ASCII_COLUMN: usize = HEX_COLUMN + BYTES_PER_ROW * 3 + 2;
+
+/// A hex view of bytes with offset, hex and ASCII columns, which only lays out the visible rows.
+/// Bytes are selected with the mouse or with the arrow keys and Shift, and overwritten by typing
+/// hex digits in the hex column or characters in the ASCII column, Tab switches between them.
+/// Ctrl+Z and Ctrl+Y undo and redo, copying copies the selection as hex.
+///
+/// The view keeps the selection and the edit history, the bytes are passed to [`HexView::show`].
+#[derive(Default)]
+pub struct HexView {
+    /// Where the selection started and where it ends, which is where typing goes.
+    anchor: usize,
+    cursor: usize,
+    /// Whether the ASCII column is edited, rather than the hex one.
+    ascii: bool,
+    /// The high nibble typed into the cursor byte, which is written with the low one.
+    nibble: Option<u8>,
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    /// The original values of the bytes that were changed.
+    original: BTreeMap<usize, u8>,
+    scroll_to_cursor: bool,
+}
+
+#[derive(Clone, Copy)]
+struct Edit {
+    offset: usize,
+    old: u8,
+    new: u8,
+}
+
+impl HexView {
+    /// The selected offsets.
+    pub fn selection(&self) -> std::ops::RangeInclusive<usize> {
+        self.anchor.min(self.cursor)..=self.anchor.max(self.cursor)
+    }
+
+    /// Selects `range` and scrolls to it.
+    pub fn select(&mut self, range: std::ops::Range<usize>) {
+        self.anchor = range.start;
+        self.cursor = range.end.max(range.start + 1) - 1;
+        self.nibble = None;
+        self.scroll_to_cursor = true;
+    }
+
+    /// The changed offsets, in order.
+    pub fn modified(&self) -> impl Iterator<Item = usize> + '_ {
+        self.original.keys().copied()
+    }
+
+    /// Forgets the edit history, for new bytes or after the changes were saved.
+    pub fn reset(&mut self, keep_selection: bool) {
+        if !keep_selection {
+            self.anchor = 0;
+            self.cursor = 0;
+        }
+        self.nibble = None;
+        self.undo.clear();
+        self.redo.clear();
+        self.original.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo(&mut self, data: &mut [u8]) {
+        if let Some(edit) = self.undo.pop() {
+            self.apply(data, edit.offset, edit.old);
+            self.redo.push(edit);
+            self.select(edit.offset..edit.offset + 1);
+        }
+    }
+
+    pub fn redo(&mut self, data: &mut [u8]) {
+        if let Some(edit) = self.redo.pop() {
+            self.apply(data, edit.offset, edit.new);
+            self.undo.push(edit);
+            self.select(edit.offset..edit.offset + 1);
+        }
+    }
+
+    fn write(&mut self, data: &mut [u8], offset: usize, value: u8) {
+        let old = data[offset];
+        if old != value {
+            self.undo.push(Edit {
+                offset,
+                old,
+                new: value,
+            });
+            self.redo.clear();
+            self.apply(data, offset, value);
+        }
+    }
+
+    fn apply(&mut self, data: &mut [u8], offset: usize, value: u8) {
+        let original = *self.original.entry(offset).or_insert(data[offset]);
+        if original == value {
+            self.original.remove(&offset);
+        }
+        data[offset] = value;
+    }
+
+    /// Returns whether the bytes were edited.
+    pub fn show(&mut self, ui: &mut egui::Ui, data: &mut [u8]) -> bool {
+        let id = ui.id().with("hex_view");
+        let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+        let char_width = ui.fonts().glyph_width(&font_id, '0');
+        let row_height = ui.fonts().row_height(&font_id);
+        let rows = data.len().div_ceil(BYTES_PER_ROW);
+        self.cursor = self.cursor.min(data.len().saturating_sub(1));
+        self.anchor = self.anchor.min(data.len().saturating_sub(1));
+
+        let focused = ui.memory().has_focus(id);
+        let mut changed = false;
+        if focused && !data.is_empty() {
+            // Tab switches between the hex and ASCII columns, rather than moving the focus.
+            ui.memory().lock_focus(id, true);
+            changed = self.keyboard(ui, data);
+        }
+
+        let mut scroll_area = egui::ScrollArea::both().auto_shrink([false; 2]);
+        if std::mem::take(&mut self.scroll_to_cursor) {
+            // Keeps the cursor row in view, a few rows from the top.
+            let row = (self.cursor / BYTES_PER_ROW).saturating_sub(4);
+            let spacing = ui.spacing().item_spacing.y;
+            let offset = row as f32 * (row_height + spacing);
+            let visible = ui.available_height();
+            let current = ui
+                .data()
+                .get_temp::<f32>(id.with("scroll"))
+                .unwrap_or_default();
+            if offset < current || offset > current + visible - 6.0 * row_height {
+                scroll_area = scroll_area.vertical_scroll_offset(offset);
+            }
+        }
+        let output = scroll_area.show_rows(ui, row_height, rows, |ui, range| {
+            let width = (ASCII_COLUMN + BYTES_PER_ROW) as f32 * char_width;
+            let mut origin = None;
+            for row in range {
+                let (rect, response) = ui.allocate_exact_size(
+                    egui::vec2(width, row_height),
+                    egui::Sense::click_and_drag(),
+                );
+                let top = *origin.get_or_insert(
+                    rect.top() - row as f32 * (row_height + ui.spacing().item_spacing.y),
+                );
+                let byte_at = |pos: egui::Pos2| -> Option<(usize, bool)> {
+                    let row = ((pos.y - top) / (row_height + ui.spacing().item_spacing.y)).floor();
+                    let column = ((pos.x - rect.left()) / char_width).floor();
+                    if row < 0.0 || column < 0.0 {
+                        return None;
+                    }
+                    let (byte, ascii) = column_byte(column as usize)?;
+                    let offset = row as usize * BYTES_PER_ROW + byte;
+                    (offset < data.len()).then_some((offset, ascii))
+                };
+                if let Some(pos) = response.interact_pointer_pos() {
+                    if let Some((offset, ascii)) = byte_at(pos) {
+                        if response.drag_started() || response.clicked() {
+                            ui.memory().request_focus(id);
+                            if !ui.input().modifiers.shift {
+                                self.anchor = offset;
+                            }
+                            self.ascii = ascii;
+                            self.nibble = None;
+                        }
+                        self.cursor = offset;
+                    }
+                }
+                let job = self.row_job(ui, data, row, &font_id, focused);
+                let galley = ui.fonts().layout_job(job);
+                ui.painter().galley(rect.min, galley);
+            }
+        });
+        ui.data()
+            .insert_temp(id.with("scroll"), output.state.offset.y);
+        changed
+    }
+
+    fn keyboard(&mut self, ui: &egui::Ui, data: &mut [u8]) -> bool {
+        let last = data.len() - 1;
+        let mut changed = false;
+        let events = ui.input().events.clone();
+        for event in events {
+            let moved = match event {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                } => {
+                    let page = BYTES_PER_ROW * 16;
+                    let cursor = self.cursor;
+                    let target = match key {
+                        egui::Key::ArrowLeft => cursor.saturating_sub(1),
+                        egui::Key::ArrowRight => (cursor + 1).min(last),
+                        egui::Key::ArrowUp => cursor.saturating_sub(BYTES_PER_ROW),
+                        egui::Key::ArrowDown => (cursor + BYTES_PER_ROW).min(last),
+                        egui::Key::PageUp => cursor.saturating_sub(page),
+                        egui::Key::PageDown => (cursor + page).min(last),
+                        egui::Key::Home if modifiers.command => 0,
+                        egui::Key::End if modifiers.command => last,
+                        egui::Key::Home => cursor - cursor % BYTES_PER_ROW,
+                        egui::Key::End => {
+                            (cursor - cursor % BYTES_PER_ROW + BYTES_PER_ROW - 1).min(last)
+                        }
+                        egui::Key::Tab => {
+                            self.ascii = !self.ascii;
+                            self.nibble = None;
+                            continue;
+                        }
+                        egui::Key::Z if modifiers.command && modifiers.shift => {
+                            self.redo(data);
+                            changed = true;
+                            continue;
+                        }
+                        egui::Key::Z if modifiers.command => {
+                            self.undo(data);
+                            changed = true;
+                            continue;
+                        }
+                        egui::Key::Y if modifiers.command => {
+                            self.redo(data);
+                            changed = true;
+                            continue;
+                        }
+                        _ => continue,
+                    };
+                    self.cursor = target;
+                    if !modifiers.shift {
+                        self.anchor = target;
+                    }
+                    self.nibble = None;
+                    true
+                }
+                egui::Event::Text(text) if !ui.input().modifiers.command => {
+                    for character in text.chars() {
+                        changed |= self.type_character(data, character);
+                    }
+                    true
+                }
+                egui::Event::Copy => {
+                    let hex: Vec<String> = data[self.selection()]
+                        .iter()
+                        .map(|byte| format!("{:02X}", byte))
+                        .collect();
+                    ui.output().copied_text = hex.join(" ");
+                    false
+                }
+                _ => false,
+            };
+            if moved {
+                self.scroll_to_cursor = true;
+            }
+        }
+        changed
+    }
+
+    /// Overwrites the cursor byte with a typed character. Returns whether a byte was written.
+    fn type_character(&mut self, data: &mut [u8], character: char) -> bool {
+        let offset = self.cursor;
+        let value = if self.ascii {
+            if !character.is_ascii() || character.is_ascii_control() {
+                return false;
+            }
+            character as u8
+        } else {
+            let digit = match character.to_digit(16) {
+                Some(digit) => digit as u8,
+                None => return false,
+            };
+            match self.nibble.take() {
+                Some(high) => high << 4 | digit,
+                None => {
+                    self.nibble = Some(digit);
+                    return false;
+                }
+            }
+        };
+        self.write(data, offset, value);
+        self.cursor = (offset + 1).min(data.len() - 1);
+        self.anchor = self.cursor;
+        true
+    }
+
+    fn row_job(
+        &self,
+        ui: &egui::Ui,
+        data: &[u8],
+        row: usize,
+        font_id: &egui::FontId,
+        focused: bool,
+    ) -> egui::text::LayoutJob {
+        let visuals = ui.visuals();
+        let format = |color: egui::Color32, background: egui::Color32| egui::TextFormat {
+            font_id: font_id.clone(),
+            color,
+            background,
+            ..Default::default()
+        };
+        let plain = format(visuals.text_color(), egui::Color32::TRANSPARENT);
+        let weak = format(visuals.weak_text_color(), egui::Color32::TRANSPARENT);
+        let selection = self.selection();
+        let byte_format = |offset: usize, column_ascii: bool| {
+            let color = match self.original.contains_key(&offset) {
+                true => visuals.warn_fg_color,
+                false => visuals.text_color(),
+            };
+            let background = if offset == self.cursor && focused && column_ascii == self.ascii {
+                visuals.selection.stroke.color.linear_multiply(0.5)
+            } else if selection.contains(&offset) {
+                visuals.selection.bg_fill
+            } else {
+                egui::Color32::TRANSPARENT
+            };
+            format(color, background)
+        };
+
+        let mut job = egui::text::LayoutJob::default();
+        let start = row * BYTES_PER_ROW;
+        let bytes = &data[start..(start + BYTES_PER_ROW).min(data.len())];
+        job.append(&format!("{:08X}  ", start), 0.0, weak.clone());
+        for column in 0..BYTES_PER_ROW {
+            let offset = start + column;
+            match bytes.get(column) {
+                Some(byte) => {
+                    let text = match self.nibble {
+                        Some(high) if offset == self.cursor && !self.ascii => {
+                            format!("{:X}_", high)
+                        }
+                        _ => format!("{:02X}", byte),
+                    };
+                    job.append(&text, 0.0, byte_format(offset, false));
+                }
+                None => job.append("  ", 0.0, plain.clone()),
+            }
+            let gap = if column == BYTES_PER_ROW / 2 - 1 {
+                "  "
+            } else {
+                " "
+            };
+            job.append(gap, 0.0, plain.clone());
+        }
+        job.append(" ", 0.0, plain.clone());
+        for (column, byte) in bytes.iter().enumerate() {
+            let character = match byte {
+                0x20..=0x7e => *byte as char,
+                _ => '.',
+            };
+            let mut format = byte_format(start + column, true);
+            if !byte.is_ascii_graphic() && *byte != b' ' {
+                format.color = visuals.weak_text_color();
+            }
+            job.append(&character.to_string(), 0.0, format);
+        }
+        job
+    }
+}
+
+/// The byte of a row under a character column, and whether it's in the ASCII column.
+fn column_byte(column: usize) -> Option<(usize, bool)> {
+    if (HEX_COLUMN..ASCII_COLUMN - 2).contains(&column) {
+        let mut column = column - HEX_COLUMN;
+        // The gap in the middle of the row.
+        if column >= BYTES_PER_ROW / 2 * 3 {
+            column = column.saturating_sub(1);
+        }
+        Some(((column / 3).min(BYTES_PER_ROW - 1), false))
+    } else if (ASCII_COLUMN..ASCII_COLUMN + BYTES_PER_ROW).contains(&column) {
+        Some((column - ASCII_COLUMN, true))
+    } else {
+        None
+    }
+}
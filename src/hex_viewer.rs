@@ -0,0 +1,248 @@
+use std::io::{Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use crate::hex_view::HexView;
+use crate::workspaces::SaveState;
+
+/// Shows any file in a [`HexView`], to search and edit its bytes. The file is mapped copy on
+/// write, so that files of any size open at once and edits stay in memory until they're saved.
+///
+/// Files are opened by entering their path or by dropping them onto the window.
+#[derive(Default)]
+pub struct HexViewer {
+    path: String,
+    file: Option<MappedFile>,
+    view: HexView,
+    search: String,
+    /// Whether the search is for hex bytes, rather than text.
+    search_hex: bool,
+    /// The result of the last action.
+    status: Option<String>,
+    /// The file of a restored workspace, opened once the window is.
+    reopen: Option<PathBuf>,
+}
+
+struct MappedFile {
+    path: PathBuf,
+    data: memmap2::MmapMut,
+}
+
+impl HexViewer {
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if *open {
+            let dropped = self.reopen.take().or_else(|| {
+                ctx.input()
+                    .raw
+                    .dropped_files
+                    .iter()
+                    .find_map(|file| file.path.clone())
+            });
+            if let Some(path) = dropped {
+                self.path = path.display().to_string();
+                self.load(path);
+            }
+        }
+
+        egui::Window::new("🔢 Hex viewer")
+            .open(open)
+            .default_size([640.0, 480.0])
+            .show(ctx, |ui| self.ui(ui));
+    }
+
+    fn load(&mut self, path: PathBuf) {
+        let result = std::fs::File::open(&path).and_then(|file| match file.metadata()?.len() {
+            0 => Err(std::io::Error::other("the file is empty")),
+            // Changes of the file by others while it's mapped show up in the view.
+            _ => unsafe { memmap2::MmapOptions::new().map_copy(&file) },
+        });
+        match result {
+            Ok(data) => {
+                self.file = Some(MappedFile { path, data });
+                self.view.reset(false);
+                self.status = None;
+            }
+            Err(err) => self.status = Some(format!("Can't open {}: {}", path.display(), err)),
+        }
+    }
+
+    /// Writes the changed bytes back into the file.
+    fn save(&mut self) {
+        let file = match &self.file {
+            Some(file) => file,
+            None => return,
+        };
+        let result = (|| {
+            let mut out = std::fs::OpenOptions::new().write(true).open(&file.path)?;
+            let mut modified = self.view.modified().peekable();
+            while let Some(start) = modified.next() {
+                // Consecutive changed bytes are written at once.
+                let mut end = start + 1;
+                while modified.next_if_eq(&end).is_some() {
+                    end += 1;
+                }
+                out.seek(SeekFrom::Start(start as u64))?;
+                out.write_all(&file.data[start..end])?;
+            }
+            out.flush()
+        })();
+        self.status = Some(match result {
+            Ok(()) => {
+                self.view.reset(true);
+                format!("Saved {}", file.path.display())
+            }
+            Err(err) => format!("Can't save {}: {}", file.path.display(), err),
+        });
+    }
+
+    /// Selects the next match of the search after the cursor, wrapping around at the end.
+    fn find_next(&mut self) {
+        let file = match &self.file {
+            Some(file) => file,
+            None => return,
+        };
+        let needle = match self.search_hex {
+            true => match parse_hex(&self.search) {
+                Some(needle) => needle,
+                None => {
+                    self.status = Some("Search for hex bytes, like `DE AD BE EF`".to_owned());
+                    return;
+                }
+            },
+            false => self.search.as_bytes().to_vec(),
+        };
+        if needle.is_empty() {
+            return;
+        }
+        let data = &file.data[..];
+        let start = (*self.view.selection().start() + 1).min(data.len());
+        let find = |haystack: &[u8]| {
+            haystack
+                .windows(needle.len())
+                .position(|window| window == needle)
+        };
+        let found = find(&data[start..])
+            .map(|position| start + position)
+            .or_else(|| find(&data[..(start + needle.len() - 1).min(data.len())]));
+        match found {
+            Some(offset) => {
+                self.view.select(offset..offset + needle.len());
+                self.status = None;
+            }
+            None => self.status = Some("Not found".to_owned()),
+        }
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let response = ui
+                .add(egui::TextEdit::singleline(&mut self.path).hint_text("Path, or drop a file"));
+            let entered = response.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+            if ui.button("Open").clicked() || entered {
+                self.load(PathBuf::from(&self.path));
+            }
+        });
+        if self.file.is_none() {
+            if let Some(status) = &self.status {
+                ui.label(status);
+            }
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.search)
+                    .hint_text("Search")
+                    .desired_width(160.0),
+            );
+            ui.selectable_value(&mut self.search_hex, false, "Text");
+            ui.selectable_value(&mut self.search_hex, true, "Hex");
+            let entered = response.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+            if ui.button("Find next").clicked() || entered {
+                self.find_next();
+            }
+            ui.separator();
+            let file = self.file.as_mut().unwrap();
+            if ui
+                .add_enabled(self.view.can_undo(), egui::Button::new("⟲ Undo"))
+                .clicked()
+            {
+                self.view.undo(&mut file.data);
+            }
+            if ui
+                .add_enabled(self.view.can_redo(), egui::Button::new("⟳ Redo"))
+                .clicked()
+            {
+                self.view.redo(&mut file.data);
+            }
+            let modified = self.view.modified().count();
+            if ui
+                .add_enabled(modified > 0, egui::Button::new("💾 Save"))
+                .on_hover_text(format!("{} changed bytes", modified))
+                .clicked()
+            {
+                self.save();
+            }
+        });
+
+        let file = self.file.as_mut().unwrap();
+        let selection = self.view.selection();
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "Offset 0x{:X}, {} of {} bytes selected",
+                selection.start(),
+                selection.end() - selection.start() + 1,
+                file.data.len()
+            ));
+            // The bytes from the start of the selection, as little endian numbers.
+            let bytes = &file.data[*selection.start()..];
+            if let Some(bytes) = bytes
+                .get(..4)
+                .and_then(|bytes| <[u8; 4]>::try_from(bytes).ok())
+            {
+                ui.weak(format!(
+                    "u8 {}  u16 {}  u32 {}",
+                    bytes[0],
+                    u16::from_le_bytes([bytes[0], bytes[1]]),
+                    u32::from_le_bytes(bytes)
+                ));
+            }
+            if let Some(status) = &self.status {
+                ui.separator();
+                ui.label(status);
+            }
+        });
+        ui.separator();
+        self.view.show(ui, &mut file.data);
+    }
+}
+
+/// Bytes written as hex digits, with optional whitespace between the bytes.
+fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = text
+        .chars()
+        .filter(|character| !character.is_whitespace())
+        .map(|character| character.to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<_>>()?;
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    Some(
+        digits
+            .chunks(2)
+            .map(|pair| pair[0] << 4 | pair[1])
+            .collect(),
+    )
+}
+
+impl SaveState for HexViewer {
+    /// The open file.
+    type State = Option<PathBuf>;
+
+    fn save_state(&self) -> Option<PathBuf> {
+        self.file.as_ref().map(|file| file.path.clone())
+    }
+
+    fn restore_state(&mut self, file: Option<PathBuf>) {
+        self.reopen = file;
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::VecDeque;
+
+use crate::frame_stats::{FrameRecord, STAGES};
+use crate::system_monitor::format_bytes;
+
+/// How many reports are kept, the oldest are dropped first.
+const MAX_REPORTS: usize = 100;
+/// How many earlier frames the median needs, so that the first frames don't count as hitches.
+const MIN_HISTORY: usize = 10;
+/// Frames faster than this never count as hitches, however fast the median is.
+const MIN_HITCH_MS: f32 = 4.0;
+
+/// A frame that took much longer on the CPU than the ones before it.
+struct Report {
+    record: FrameRecord,
+    /// The median CPU time of the frames before it.
+    median_ms: f32,
+    /// The median of each of the [`STAGES`] over the frames before it.
+    stage_medians: Vec<f32>,
+}
+
+impl Report {
+    /// The stage that exceeded its median the most, and a guess at the reason.
+    fn cause(&self) -> String {
+        let (index, excess) = STAGES
+            .iter()
+            .zip(&self.stage_medians)
+            .map(|((_, stage), median)| stage(&self.record).unwrap_or(0.0) - median)
+            .enumerate()
+            .fold((0, f32::MIN), |slowest, (index, excess)| {
+                if excess > slowest.1 {
+                    (index, excess)
+                } else {
+                    slowest
+                }
+            });
+        let (name, _) = STAGES[index];
+        let hint = match name {
+            "Upload" if self.record.font_upload_bytes > 0 => format!(
+                ", the font atlas took {:.2} ms of it for {}: new glyphs, see Font atlas",
+                self.record.font_upload_ms,
+                format_bytes(self.record.font_upload_bytes)
+            ),
+            "Upload" if self.record.texture_upload_bytes > 0 => format!(
+                ", {} of textures were uploaded: a new font atlas or a new image",
+                format_bytes(self.record.texture_upload_bytes)
+            ),
+            "Main UI" | "HUD" => ", a large change of the UI, like a window that opened".to_owned(),
+            "Tessellation" => format!(", {} triangles", self.record.triangles),
+            "Wait for the GPU" => ", the GPU was behind".to_owned(),
+            _ => String::new(),
+        };
+        format!("{} took {:.2} ms longer than usual{}", name, excess, hint)
+    }
+}
+
+/// Detects frames that take a multiple of the median CPU time of the recent frames, and keeps
+/// their stage breakdown, triangles and uploads for the "Hitch reports" window.
+pub struct HitchReports {
+    /// How many times the median a frame has to take.
+    factor: f32,
+    reports: VecDeque<Report>,
+    /// The last frame that was checked, frames come in once per call but overlap.
+    last_checked: Option<u64>,
+}
+
+impl Default for HitchReports {
+    fn default() -> Self {
+        Self {
+            factor: 3.0,
+            reports: VecDeque::new(),
+            last_checked: None,
+        }
+    }
+}
+
+impl HitchReports {
+    /// Checks the last of the recent `frames` against the ones before it.
+    pub fn check(&mut self, frames: &[FrameRecord]) {
+        let (last, history) = match frames.split_last() {
+            Some((last, history)) if history.len() >= MIN_HISTORY => (last, history),
+            _ => return,
+        };
+        if self.last_checked == Some(last.frame) {
+            return;
+        }
+        self.last_checked = Some(last.frame);
+        let median_ms = median(history.iter().map(|record| record.cpu_ms));
+        if last.cpu_ms < MIN_HITCH_MS || last.cpu_ms < median_ms * self.factor {
+            return;
+        }
+        let stage_medians = STAGES
+            .iter()
+            .map(|(_, stage)| median(history.iter().map(|record| stage(record).unwrap_or(0.0))))
+            .collect();
+        if self.reports.len() == MAX_REPORTS {
+            self.reports.pop_front();
+        }
+        self.reports.push_back(Report {
+            record: last.clone(),
+            median_ms,
+            stage_medians,
+        });
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::Slider::new(&mut self.factor, 1.5..=10.0)
+                    .text("× the median")
+                    .fixed_decimals(1),
+            )
+            .on_hover_text("How much longer than the recent frames a frame has to take");
+            if ui.button("Clear").clicked() {
+                self.reports.clear();
+            }
+        });
+        ui.separator();
+        if self.reports.is_empty() {
+            ui.weak("No hitches so far");
+            return;
+        }
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            // The newest first.
+            for report in self.reports.iter().rev() {
+                let record = &report.record;
+                egui::CollapsingHeader::new(format!(
+                    "Frame {} at {:.1} s: {:.2} ms, median {:.2} ms",
+                    record.frame, record.time, record.cpu_ms, report.median_ms
+                ))
+                .id_source(("hitch", record.frame))
+                .show(ui, |ui| report_ui(ui, report));
+            }
+        });
+    }
+}
+
+fn report_ui(ui: &mut egui::Ui, report: &Report) {
+    let record = &report.record;
+    ui.label(report.cause());
+    egui::Grid::new(("hitch_stages", record.frame))
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Stage");
+            ui.strong("This frame");
+            ui.strong("Median");
+            ui.end_row();
+            for ((name, stage), median) in STAGES.iter().zip(&report.stage_medians) {
+                ui.label(*name);
+                ui.monospace(format!("{:7.2} ms", stage(record).unwrap_or(0.0)));
+                ui.monospace(format!("{:7.2} ms", median));
+                ui.end_row();
+            }
+        });
+    ui.label(format!(
+        "{} triangles in {} draw calls, uploaded {} of textures and {} of buffers",
+        record.triangles,
+        record.draw_calls,
+        format_bytes(record.texture_upload_bytes),
+        format_bytes(record.buffer_upload_bytes)
+    ));
+}
+
+fn median(values: impl Iterator<Item = f32>) -> f32 {
+    let mut values: Vec<f32> = values.collect();
+    values.sort_by(f32::total_cmp);
+    values.get(values.len() / 2).copied().unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(cpu_ms: &[f32]) -> Vec<FrameRecord> {
+        cpu_ms
+            .iter()
+            .enumerate()
+            .map(|(frame, &cpu_ms)| FrameRecord {
+                frame: frame as u64,
+                cpu_ms,
+                upload_ms: cpu_ms - 1.0,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn slow_frames_are_reported_once() {
+        let mut hitches = HitchReports::default();
+        let mut cpu_ms = vec![2.0; 20];
+        hitches.check(&frames(&cpu_ms));
+        assert!(hitches.reports.is_empty());
+
+        cpu_ms.push(9.0);
+        let recent = frames(&cpu_ms);
+        hitches.check(&recent);
+        hitches.check(&recent);
+        assert_eq!(hitches.reports.len(), 1);
+        assert!(hitches.reports[0]
+            .cause()
+            .starts_with("Upload took 7.00 ms longer"));
+    }
+
+    #[test]
+    fn fast_frames_are_never_hitches() {
+        let mut hitches = HitchReports::default();
+        let mut cpu_ms = vec![0.5; 20];
+        cpu_ms.push(3.0);
+        hitches.check(&frames(&cpu_ms));
+        assert!(hitches.reports.is_empty());
+    }
+}
@@ -0,0 +1,221 @@
+use egui_winit_platform::{Platform, PlatformDescriptor};
+use winit::event::{ElementState, TouchPhase, WindowEvent};
+use winit::window::Window;
+
+use crate::Event;
+
+/// A second, independent egui context that is drawn below the main UI, like the HUD of a game
+/// is drawn into the scene before the menus go on top.
+///
+/// Each layer gets its own input. Pointer input goes to the main UI wherever it covers the HUD,
+/// and to the HUD everywhere else. A drag stays with the layer it started on, and keyboard input
+/// goes to the layer that was clicked last.
+pub struct Hud {
+    platform: Platform,
+    pointer_on_hud: bool,
+    keyboard_on_hud: bool,
+    /// The pressed buttons and touches, which capture the pointer for one layer.
+    pointers_down: usize,
+    clicks: u32,
+    text: String,
+}
+
+impl Hud {
+    /// `scale_factor` is the one of the main UI.
+    pub fn new(
+        window: &Window,
+        scale_factor: f64,
+        font_definitions: egui::FontDefinitions,
+    ) -> Self {
+        let size = window.inner_size();
+        Self {
+            platform: Platform::new(PlatformDescriptor {
+                physical_width: size.width,
+                physical_height: size.height,
+                scale_factor,
+                font_definitions,
+                style: Default::default(),
+            }),
+            pointer_on_hud: false,
+            keyboard_on_hud: false,
+            pointers_down: 0,
+            clicks: 0,
+            text: "Type here".to_owned(),
+        }
+    }
+
+    /// The egui context of the HUD.
+    pub fn context(&self) -> egui::Context {
+        self.platform.context()
+    }
+
+    /// Forwards `event` to the main UI's `platform`, to the HUD, or to both.
+    pub fn route_event(
+        &mut self,
+        platform: &mut Platform,
+        event: &winit::event::Event<Event>,
+        enabled: bool,
+    ) {
+        let window_event = match event {
+            winit::event::Event::WindowEvent { event, .. } => event,
+            _ => return platform.handle_event(event),
+        };
+        let to_hud = match window_event {
+            WindowEvent::CursorMoved { position, .. } => {
+                let scale_factor = self.platform.context().pixels_per_point() as f64;
+                let pos = egui::pos2(
+                    (position.x / scale_factor) as f32,
+                    (position.y / scale_factor) as f32,
+                );
+                let on_hud = enabled && !covers(&platform.context(), pos);
+                self.move_pointer(platform, event, on_hud);
+                self.pointer_on_hud
+            }
+            WindowEvent::Touch(touch) => {
+                if touch.phase == TouchPhase::Started {
+                    let scale_factor = self.platform.context().pixels_per_point() as f64;
+                    let pos = egui::pos2(
+                        (touch.location.x / scale_factor) as f32,
+                        (touch.location.y / scale_factor) as f32,
+                    );
+                    let on_hud = enabled && !covers(&platform.context(), pos);
+                    self.move_pointer(platform, event, on_hud);
+                }
+                self.press(matches!(touch.phase, TouchPhase::Started));
+                self.pointer_on_hud
+            }
+            WindowEvent::MouseInput { state, .. } => {
+                self.press(*state == ElementState::Pressed);
+                self.pointer_on_hud
+            }
+            WindowEvent::MouseWheel { .. } | WindowEvent::CursorLeft { .. } => self.pointer_on_hud,
+            WindowEvent::KeyboardInput { .. } | WindowEvent::ReceivedCharacter(_) => {
+                self.keyboard_on_hud
+            }
+            // Sizes, focus, modifiers and the like concern both layers, also while the HUD is
+            // hidden, so that it's up to date once it's shown again.
+            _ => {
+                self.platform.handle_event(event);
+                return platform.handle_event(event);
+            }
+        };
+        match enabled && to_hud {
+            true => self.platform.handle_event(event),
+            false => platform.handle_event(event),
+        }
+    }
+
+    /// Moves the pointer to the layer it's over now, unless a drag holds it.
+    fn move_pointer(
+        &mut self,
+        platform: &mut Platform,
+        event: &winit::event::Event<Event>,
+        on_hud: bool,
+    ) {
+        if self.pointers_down > 0 || on_hud == self.pointer_on_hud {
+            return;
+        }
+        // The layer the pointer leaves shouldn't keep showing hover effects.
+        let left: winit::event::Event<Event> = match event {
+            winit::event::Event::WindowEvent { window_id, .. } => {
+                winit::event::Event::WindowEvent {
+                    window_id: *window_id,
+                    event: WindowEvent::CursorLeft {
+                        device_id: unsafe { winit::event::DeviceId::dummy() },
+                    },
+                }
+            }
+            _ => return,
+        };
+        match self.pointer_on_hud {
+            true => self.platform.handle_event(&left),
+            false => platform.handle_event(&left),
+        }
+        self.pointer_on_hud = on_hud;
+    }
+
+    fn press(&mut self, pressed: bool) {
+        if pressed {
+            self.pointers_down += 1;
+            self.keyboard_on_hud = self.pointer_on_hud;
+        } else {
+            self.pointers_down = self.pointers_down.saturating_sub(1);
+        }
+    }
+
+    /// Runs one frame of the HUD. Sets the cursor if the pointer is over the HUD.
+    pub fn run(&mut self, window: &Window, time: f64, time_of_day: f64) -> egui::FullOutput {
+        self.platform.update_time(time);
+        self.platform.begin_frame();
+        let ctx = self.platform.context();
+        self.ui(&ctx, time_of_day);
+        self.platform
+            .end_frame(self.pointer_on_hud.then_some(window))
+    }
+
+    /// Hands the screen rect of the main UI to the HUD, like the safe area on iOS.
+    pub fn set_screen_rect(&mut self, screen_rect: egui::Rect) {
+        self.platform.raw_input_mut().screen_rect = Some(screen_rect);
+    }
+
+    pub fn set_max_texture_side(&mut self, max_texture_side: usize) {
+        self.platform.raw_input_mut().max_texture_side = Some(max_texture_side);
+    }
+
+    /// `time_of_day` is in seconds since midnight.
+    fn ui(&mut self, ctx: &egui::Context, time_of_day: f64) {
+        egui::CentralPanel::default()
+            .frame(egui::Frame::none())
+            .show(ctx, |ui| {
+                let rect = ui.max_rect();
+                let painter = ui.painter();
+                let center = rect.center();
+                let stroke = egui::Stroke::new(2.0, egui::Color32::from_white_alpha(160));
+                painter.line_segment(
+                    [
+                        center - egui::vec2(12.0, 0.0),
+                        center + egui::vec2(12.0, 0.0),
+                    ],
+                    stroke,
+                );
+                painter.line_segment(
+                    [
+                        center - egui::vec2(0.0, 12.0),
+                        center + egui::vec2(0.0, 12.0),
+                    ],
+                    stroke,
+                );
+
+                egui::Area::new("hud_panel")
+                    .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(12.0, -12.0))
+                    .show(ctx, |ui| {
+                        egui::Frame::popup(ui.style()).show(ui, |ui| {
+                            ui.label("HUD layer, drawn by its own egui context");
+                            let seconds = time_of_day as u64;
+                            ui.label(format!(
+                                "{:02}:{:02}:{:02}, {:.0} fps",
+                                seconds / 3600 % 24,
+                                seconds / 60 % 60,
+                                seconds % 60,
+                                1.0 / ctx.input().stable_dt
+                            ));
+                            if ui
+                                .button(format!("Clicked {} times", self.clicks))
+                                .clicked()
+                            {
+                                self.clicks += 1;
+                            }
+                            ui.text_edit_singleline(&mut self.text);
+                        });
+                    });
+            });
+    }
+}
+
+/// Whether the main UI covers `pos` with a window or a panel.
+fn covers(ctx: &egui::Context, pos: egui::Pos2) -> bool {
+    let over_area = ctx
+        .layer_id_at(pos)
+        .is_some_and(|layer| layer.order != egui::Order::Background);
+    over_area || !ctx.available_rect().contains(pos)
+}
@@ -0,0 +1,49 @@
+use std::time::{Duration, Instant};
+
+use winit::event::WindowEvent;
+
+/// Whether `event` is input of the user, as opposed to what the window system reports on its own.
+pub fn is_input(event: &WindowEvent) -> bool {
+    matches!(
+        event,
+        WindowEvent::CursorMoved { .. }
+            | WindowEvent::MouseInput { .. }
+            | WindowEvent::MouseWheel { .. }
+            | WindowEvent::Touch(_)
+            | WindowEvent::KeyboardInput { .. }
+            | WindowEvent::ReceivedCharacter(_)
+    )
+}
+
+/// How long the user hasn't given any input, from the events the runner routes to the UI.
+pub struct IdleTracker {
+    last_input: Instant,
+}
+
+impl Default for IdleTracker {
+    fn default() -> Self {
+        Self {
+            last_input: Instant::now(),
+        }
+    }
+}
+
+impl IdleTracker {
+    /// Takes note of `event`. Returns whether it was input.
+    pub fn observe(&mut self, event: &WindowEvent) -> bool {
+        let input = is_input(event);
+        if input {
+            self.last_input = Instant::now();
+        }
+        input
+    }
+
+    pub fn idle_time(&self) -> Duration {
+        self.last_input.elapsed()
+    }
+
+    /// When the user will have been idle for `timeout`, unless there is input until then.
+    pub fn idle_at(&self, timeout: Duration) -> Instant {
+        self.last_input + timeout
+    }
+}
@@ -0,0 +1,194 @@
+/// How much a point of scrolling zooms in or out.
+const ZOOM_PER_SCROLL_POINT: f32 = 0.002;
+/// From how many points per image pixel on the pixel grid is drawn.
+const PIXEL_GRID_ZOOM: f32 = 8.0;
+
+/// Where an [`ImageCanvas`] looks at. Kept by the owner of the canvas between frames.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CanvasView {
+    /// Points per image pixel. `None` fits the image into the canvas.
+    zoom: Option<f32>,
+    /// The image position in the center of the canvas, in pixels.
+    center: egui::Pos2,
+}
+
+impl CanvasView {
+    /// The buttons to fit the image into the canvas and to show it at its actual size.
+    pub fn buttons(&mut self, ui: &mut egui::Ui) {
+        if ui.button("Fit").clicked() {
+            self.zoom = None;
+        }
+        if ui.button("1:1").clicked() {
+            self.zoom = Some(1.0 / ui.ctx().pixels_per_point());
+        }
+    }
+
+    /// Fits the next image into the canvas.
+    pub fn reset(&mut self) {
+        self.zoom = None;
+    }
+
+    /// The zoom in percent of physical pixels, if it isn't fitted.
+    pub fn percent(&self, ctx: &egui::Context) -> Option<f32> {
+        self.zoom.map(|zoom| zoom * ctx.pixels_per_point() * 100.0)
+    }
+}
+
+/// Maps between image pixels and the screen.
+#[derive(Clone, Copy)]
+pub struct CanvasTransform {
+    /// The screen rect of the canvas.
+    pub rect: egui::Rect,
+    center: egui::Pos2,
+    zoom: f32,
+}
+
+impl CanvasTransform {
+    pub fn to_screen(self, pos: egui::Pos2) -> egui::Pos2 {
+        self.rect.center() + (pos - self.center) * self.zoom
+    }
+
+    pub fn to_image(self, pos: egui::Pos2) -> egui::Pos2 {
+        self.center + (pos - self.rect.center()) / self.zoom
+    }
+
+    pub fn rect_to_screen(&self, rect: egui::Rect) -> egui::Rect {
+        egui::Rect::from_min_max(self.to_screen(rect.min), self.to_screen(rect.max))
+    }
+}
+
+/// A pannable and zoomable view of an image of `size` pixels, which its owner paints.
+///
+/// Dragging pans, scrolling zooms around the pointer. From a zoom of 8 points per pixel on, a
+/// grid outlines the pixels, and the hovered pixel is read out with [`ImageCanvas::pixel_value`].
+pub struct ImageCanvas<'a> {
+    view: &'a mut CanvasView,
+    size: egui::Vec2,
+    pixel_value: Option<Box<dyn Fn(u32, u32) -> String + 'a>>,
+}
+
+impl<'a> ImageCanvas<'a> {
+    pub fn new(view: &'a mut CanvasView, size: egui::Vec2) -> Self {
+        Self {
+            view,
+            size,
+            pixel_value: None,
+        }
+    }
+
+    /// Describes the value of the pixel at `x`, `y` for the readout.
+    pub fn pixel_value(mut self, pixel_value: impl Fn(u32, u32) -> String + 'a) -> Self {
+        self.pixel_value = Some(Box::new(pixel_value));
+        self
+    }
+
+    /// Fills the available space. `paint` draws the image with the given painter, which is
+    /// clipped to the canvas.
+    pub fn show(
+        self,
+        ui: &mut egui::Ui,
+        paint: impl FnOnce(&egui::Painter, &CanvasTransform),
+    ) -> egui::Response {
+        let (rect, response) = ui.allocate_exact_size(ui.available_size(), egui::Sense::drag());
+        let view = self.view;
+        let fit = (rect.width() / self.size.x).min(rect.height() / self.size.y);
+        let zoom = match view.zoom {
+            Some(zoom) => zoom,
+            None => {
+                view.center = (self.size / 2.0).to_pos2();
+                fit
+            }
+        };
+        if response.dragged() {
+            view.center -= response.drag_delta() / zoom;
+            view.zoom = Some(zoom);
+        }
+        let scroll = ui.input().scroll_delta.y;
+        if let (Some(pointer), true) = (response.hover_pos(), scroll != 0.0) {
+            // Keeps the pixel under the pointer in place.
+            let new_zoom = (zoom * (scroll * ZOOM_PER_SCROLL_POINT).exp()).clamp(fit / 4.0, 64.0);
+            let offset = pointer - rect.center();
+            view.center += offset / zoom - offset / new_zoom;
+            view.zoom = Some(new_zoom);
+        }
+
+        let transform = CanvasTransform {
+            rect,
+            center: view.center,
+            zoom: view.zoom.unwrap_or(fit),
+        };
+        let painter = ui.painter_at(rect);
+        paint(&painter, &transform);
+        if transform.zoom >= PIXEL_GRID_ZOOM {
+            pixel_grid(ui, &painter, &transform, self.size);
+        }
+
+        if let Some(pointer) = response.hover_pos() {
+            let pixel = transform.to_image(pointer).floor();
+            let inside = egui::Rect::from_min_size(egui::Pos2::ZERO, self.size).contains(pixel);
+            if inside {
+                let (x, y) = (pixel.x as u32, pixel.y as u32);
+                let mut text = format!("{}, {}", x, y);
+                if let Some(pixel_value) = &self.pixel_value {
+                    text += &format!(": {}", pixel_value(x, y));
+                }
+                readout(ui, &painter, rect, text);
+            }
+        }
+        response
+    }
+}
+
+/// Outlines the visible pixels.
+fn pixel_grid(
+    ui: &egui::Ui,
+    painter: &egui::Painter,
+    transform: &CanvasTransform,
+    size: egui::Vec2,
+) {
+    let visible = egui::Rect::from_min_max(
+        transform.to_image(transform.rect.min).floor(),
+        transform.to_image(transform.rect.max).ceil(),
+    )
+    .intersect(egui::Rect::from_min_size(egui::Pos2::ZERO, size));
+    let stroke = egui::Stroke::new(1.0, ui.visuals().weak_text_color().linear_multiply(0.5));
+    let mut x = visible.min.x;
+    while x <= visible.max.x {
+        painter.line_segment(
+            [
+                transform.to_screen(egui::pos2(x, visible.min.y)),
+                transform.to_screen(egui::pos2(x, visible.max.y)),
+            ],
+            stroke,
+        );
+        x += 1.0;
+    }
+    let mut y = visible.min.y;
+    while y <= visible.max.y {
+        painter.line_segment(
+            [
+                transform.to_screen(egui::pos2(visible.min.x, y)),
+                transform.to_screen(egui::pos2(visible.max.x, y)),
+            ],
+            stroke,
+        );
+        y += 1.0;
+    }
+}
+
+/// Shows `text` in the bottom left corner of the canvas.
+fn readout(ui: &egui::Ui, painter: &egui::Painter, rect: egui::Rect, text: String) {
+    let galley = painter.layout_no_wrap(
+        text,
+        egui::TextStyle::Monospace.resolve(ui.style()),
+        ui.visuals().text_color(),
+    );
+    let margin = egui::vec2(4.0, 2.0);
+    let pos = rect.left_bottom() + egui::vec2(margin.x, -margin.y - galley.size().y);
+    painter.rect_filled(
+        egui::Rect::from_min_size(pos - margin, galley.size() + 2.0 * margin),
+        2.0,
+        ui.visuals().extreme_bg_color.linear_multiply(0.8),
+    );
+    painter.galley(pos, galley);
+}
@@ -0,0 +1,94 @@
+/// The channels the statistics are computed for, with their plot colors.
+const CHANNELS: [(&str, egui::Color32); 4] = [
+    ("Red", egui::Color32::from_rgb(230, 70, 70)),
+    ("Green", egui::Color32::from_rgb(70, 200, 70)),
+    ("Blue", egui::Color32::from_rgb(80, 120, 240)),
+    ("Luma", egui::Color32::GRAY),
+];
+
+/// Histograms and value statistics of an 8-bit RGBA image, per channel and for the luma.
+pub struct ImageStats {
+    histograms: [[u32; 256]; 4],
+    min: [u8; 4],
+    max: [u8; 4],
+    mean: [f64; 4],
+}
+
+impl ImageStats {
+    /// Goes over all pixels once. Meant to run on the thread that loaded the image.
+    pub fn compute(pixels: &[u8]) -> Self {
+        let mut histograms = [[0; 256]; 4];
+        let mut sums = [0u64; 4];
+        for pixel in pixels.chunks_exact(4) {
+            // Rec. 709 luma on the encoded values.
+            let luma =
+                (0.2126 * pixel[0] as f32 + 0.7152 * pixel[1] as f32 + 0.0722 * pixel[2] as f32)
+                    .round() as u8;
+            for (channel, value) in [pixel[0], pixel[1], pixel[2], luma].into_iter().enumerate() {
+                histograms[channel][value as usize] += 1;
+                sums[channel] += value as u64;
+            }
+        }
+        let count = (pixels.len() / 4).max(1) as f64;
+        let bounds = |histogram: &[u32; 256], mut range: std::ops::Range<usize>, last: bool| {
+            let found = match last {
+                false => range.find(|value| histogram[*value] > 0),
+                true => range.rev().find(|value| histogram[*value] > 0),
+            };
+            found.unwrap_or_default() as u8
+        };
+        Self {
+            min: histograms.map(|histogram| bounds(&histogram, 0..256, false)),
+            max: histograms.map(|histogram| bounds(&histogram, 0..256, true)),
+            mean: sums.map(|sum| sum as f64 / count),
+            histograms,
+        }
+    }
+
+    /// A `log_scale` draws the histograms with a logarithmic count, which brings out rare values.
+    pub fn ui(&self, ui: &mut egui::Ui, log_scale: &mut bool) {
+        ui.checkbox(log_scale, "Logarithmic count");
+        let log_scale = *log_scale;
+        egui::plot::Plot::new("image_histogram")
+            .height(160.0)
+            .include_x(0.0)
+            .include_x(255.0)
+            .include_y(0.0)
+            .show_y(false)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .legend(egui::plot::Legend::default())
+            .show(ui, |plot_ui| {
+                for ((name, color), histogram) in CHANNELS.iter().zip(&self.histograms) {
+                    let points: egui::plot::PlotPoints = histogram
+                        .iter()
+                        .enumerate()
+                        .map(|(value, count)| {
+                            let count = *count as f64;
+                            [value as f64, if log_scale { count.ln_1p() } else { count }]
+                        })
+                        .collect();
+                    plot_ui.line(egui::plot::Line::new(points).color(*color).name(name));
+                }
+            });
+
+        egui::Grid::new("image_stats")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("");
+                ui.strong("Min");
+                ui.strong("Max");
+                ui.strong("Mean");
+                ui.end_row();
+                for (channel, (name, _)) in CHANNELS.iter().enumerate() {
+                    ui.label(*name);
+                    ui.label(self.min[channel].to_string());
+                    ui.label(self.max[channel].to_string());
+                    ui.label(format!("{:.1}", self.mean[channel]));
+                    ui.end_row();
+                }
+            });
+    }
+}
@@ -0,0 +1,578 @@
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+use crate::image_canvas::{CanvasView, ImageCanvas};
+use crate::image_stats::ImageStats;
+use crate::user_textures::{Sampling, UserTexture, UserTextures};
+use crate::workspaces::SaveState;
+
+/// The extensions of the files the viewer opens, in lowercase.
+pub const EXTENSIONS: &[&str] = &[
+    "png",
+    "jpg",
+    "jpeg",
+    "gif",
+    "bmp",
+    "tga",
+    "tif",
+    "tiff",
+    "webp",
+    #[cfg(feature = "hdr")]
+    "exr",
+    #[cfg(feature = "hdr")]
+    "hdr",
+    #[cfg(feature = "ktx2")]
+    "ktx2",
+];
+
+/// Shows images of any size, with panning and zooming. Images larger than the biggest texture
+/// of the GPU are split into tiles, each uploaded as its own user texture. The filtering and
+/// wrapping of the textures can be changed, so that pixel art stays sharp.
+///
+/// The tiles of images decoded on the CPU can be evicted, and are uploaded again from the
+/// decoded pixels when they come into view.
+///
+/// Images are opened by entering their path or by dropping them onto the window.
+#[derive(Default)]
+pub struct ImageViewer {
+    path: String,
+    loading: Option<mpsc::Receiver<Result<TiledImage<wgpu::Texture>, String>>>,
+    image: Option<TiledImage<UserTexture>>,
+    error: Option<String>,
+    sampling: Sampling,
+    view: CanvasView,
+    stats_open: bool,
+    stats_log_scale: bool,
+    /// The file of a restored workspace, opened once the window is.
+    reopen: Option<PathBuf>,
+}
+
+/// An image, first with the uploaded textures and then with the registered ones.
+struct TiledImage<T> {
+    size: egui::Vec2,
+    tiles: Vec<Tile<T>>,
+    /// How the image is stored on the GPU.
+    memory: String,
+    /// Only computed for images decoded on the CPU.
+    stats: Option<ImageStats>,
+    /// The decoded pixels, for the readout of the canvas. Only kept for 8-bit images.
+    pixels: Option<image::RgbaImage>,
+    /// The source of the only tile of a tonemapped HDR image.
+    #[cfg(feature = "hdr")]
+    hdr: Option<crate::hdr_image::HdrImage>,
+}
+
+struct Tile<T> {
+    texture: T,
+    /// Where the tile is drawn, in image pixels.
+    rect: egui::Rect,
+    uv: egui::Rect,
+    /// The memory the texture takes on the GPU.
+    bytes: u64,
+    /// The columns and rows of the decoded pixels it was uploaded from, to upload it again.
+    source: Option<[std::ops::Range<usize>; 2]>,
+}
+
+/// A tile along one axis of the image.
+struct Span {
+    /// The source pixels of the tile.
+    pixels: std::ops::Range<usize>,
+    /// Where the tile is drawn, in image pixels.
+    draw: (f32, f32),
+    uv: (f32, f32),
+}
+
+impl ImageViewer {
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool, textures: &mut UserTextures) {
+        if *open {
+            let dropped = self.reopen.take().or_else(|| {
+                ctx.input().raw.dropped_files.iter().find_map(|file| {
+                    file.path.clone().filter(|path| {
+                        path.extension().is_some_and(|extension| {
+                            EXTENSIONS
+                                .iter()
+                                .any(|known| extension.eq_ignore_ascii_case(known))
+                        })
+                    })
+                })
+            });
+            if let Some(path) = dropped {
+                self.path = path.display().to_string();
+                self.load(ctx, path, textures);
+            }
+        }
+        self.poll_loading(ctx, textures);
+
+        egui::Window::new("🖼 Image viewer")
+            .open(open)
+            .default_size([640.0, 480.0])
+            .show(ctx, |ui| self.ui(ui, textures));
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, textures: &mut UserTextures) {
+        ui.horizontal(|ui| {
+            let response = ui
+                .add(egui::TextEdit::singleline(&mut self.path).hint_text("Path, or drop a file"));
+            let entered = response.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+            if ui.button("Open").clicked() || entered {
+                self.load(ui.ctx(), PathBuf::from(&self.path), textures);
+            }
+            ui.separator();
+            self.view.buttons(ui);
+            ui.separator();
+            ui.toggle_value(&mut self.stats_open, "📊 Histogram");
+        });
+        self.sampling_ui(ui, textures);
+        #[cfg(feature = "hdr")]
+        self.hdr_ui(ui, textures);
+        if self.loading.is_some() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Loading…");
+            });
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        let image = match &self.image {
+            Some(image) => image,
+            None => return,
+        };
+        ui.label(format!(
+            "{} × {} pixels in {} tiles, {:.0} %",
+            image.size.x,
+            image.size.y,
+            image.tiles.len(),
+            self.view.percent(ui.ctx()).unwrap_or(100.0)
+        ));
+        ui.weak(&image.memory);
+
+        if self.stats_open {
+            egui::SidePanel::right("image_stats_panel").show_inside(ui, |ui| match &image.stats {
+                Some(stats) => stats.ui(ui, &mut self.stats_log_scale),
+                None => {
+                    ui.label("Only computed for images that are decoded on the CPU");
+                }
+            });
+        }
+        let mut canvas = ImageCanvas::new(&mut self.view, image.size);
+        if let Some(pixels) = &image.pixels {
+            canvas = canvas.pixel_value(|x, y| {
+                let [r, g, b, a] = pixels.get_pixel(x, y).0;
+                format!("{:3} {:3} {:3} {:3}", r, g, b, a)
+            });
+        }
+        let wraps = self.sampling.address_mode != wgpu::AddressMode::ClampToEdge;
+        // The visible tiles that were evicted.
+        let mut missing = Vec::new();
+        canvas.show(ui, |painter, transform| {
+            // A single texture shows how it wraps around its edges.
+            if let ([tile], true) = (image.tiles.as_slice(), wraps) {
+                if !textures.is_resident(&tile.texture) {
+                    missing.push(0);
+                    return;
+                }
+                let screen_rect = transform.rect_to_screen(egui::Rect::from_min_max(
+                    (-image.size).to_pos2(),
+                    (image.size * 2.0).to_pos2(),
+                ));
+                let uv = egui::Rect::from_min_max(egui::pos2(-1.0, -1.0), egui::pos2(2.0, 2.0));
+                painter.add(egui::Shape::image(
+                    tile.texture.id,
+                    screen_rect,
+                    uv,
+                    egui::Color32::WHITE,
+                ));
+                return;
+            }
+            for (index, tile) in image.tiles.iter().enumerate() {
+                let screen_rect = transform.rect_to_screen(tile.rect);
+                if !screen_rect.intersects(transform.rect) {
+                    continue;
+                }
+                if !textures.is_resident(&tile.texture) {
+                    missing.push(index);
+                } else {
+                    painter.add(egui::Shape::image(
+                        tile.texture.id,
+                        screen_rect,
+                        tile.uv,
+                        egui::Color32::WHITE,
+                    ));
+                }
+            }
+        });
+        if !missing.is_empty() {
+            self.upload_again(&missing, textures);
+            ui.ctx().request_repaint();
+        }
+    }
+
+    /// Uploads the evicted tiles at `indices` again, from the decoded pixels.
+    fn upload_again(&mut self, indices: &[usize], textures: &mut UserTextures) {
+        let image = match &mut self.image {
+            Some(image) => image,
+            None => return,
+        };
+        let pixels = match &image.pixels {
+            Some(pixels) => pixels,
+            None => return,
+        };
+        for &index in indices {
+            let tile = &mut image.tiles[index];
+            if let Some(source) = &tile.source {
+                let texture = crate::user_textures::upload(
+                    textures.device,
+                    textures.queue,
+                    "image_viewer_tile",
+                    &tile_image(pixels, source),
+                );
+                tile.texture = textures.register_evictable(&texture, tile.bytes, self.sampling);
+            }
+        }
+    }
+
+    fn sampling_ui(&mut self, ui: &mut egui::Ui, textures: &mut UserTextures) {
+        let before = self.sampling;
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            ui.radio_value(
+                &mut self.sampling.filter,
+                wgpu::FilterMode::Linear,
+                "Linear",
+            );
+            ui.radio_value(
+                &mut self.sampling.filter,
+                wgpu::FilterMode::Nearest,
+                "Nearest",
+            );
+            ui.separator();
+            ui.label("Edges:");
+            for (mode, name) in [
+                (wgpu::AddressMode::ClampToEdge, "Clamp"),
+                (wgpu::AddressMode::Repeat, "Repeat"),
+                (wgpu::AddressMode::MirrorRepeat, "Mirror"),
+            ] {
+                ui.radio_value(&mut self.sampling.address_mode, mode, name);
+            }
+        });
+        if let (Some(image), true) = (&self.image, self.sampling != before) {
+            for tile in &image.tiles {
+                textures.set_sampling(&tile.texture, self.sampling);
+            }
+        }
+    }
+
+    #[cfg(feature = "hdr")]
+    fn hdr_ui(&mut self, ui: &mut egui::Ui, textures: &UserTextures) {
+        let hdr = match self.image.as_mut().and_then(|image| image.hdr.as_mut()) {
+            Some(hdr) => hdr,
+            None => return,
+        };
+        let changed = ui
+            .horizontal(|ui| {
+                let exposure = ui.add(
+                    egui::Slider::new(&mut hdr.exposure, -8.0..=8.0)
+                        .text("Exposure")
+                        .suffix(" EV"),
+                );
+                let gamma = ui.add(egui::Slider::new(&mut hdr.gamma, 1.0..=3.0).text("Gamma"));
+                if ui.button("Reset").clicked() {
+                    (hdr.exposure, hdr.gamma) = (0.0, 2.2);
+                    return true;
+                }
+                exposure.changed() || gamma.changed()
+            })
+            .inner;
+        if changed {
+            hdr.tonemap(textures.device, textures.queue);
+        }
+    }
+
+    /// Decodes and uploads the image at `path` on another thread.
+    fn load(&mut self, ctx: &egui::Context, path: PathBuf, textures: &UserTextures) {
+        let (sender, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+        let (device, queue) = (textures.device.clone(), textures.queue.clone());
+        std::thread::spawn(move || {
+            let result = load_tiled(&device, &queue, &path)
+                .map_err(|err| format!("Can't open {}: {}", path.display(), err));
+            // The viewer might have started to load another image meanwhile.
+            let _ = sender.send(result);
+            ctx.request_repaint();
+        });
+        self.loading = Some(receiver);
+    }
+
+    fn poll_loading(&mut self, ctx: &egui::Context, textures: &mut UserTextures) {
+        let result = match self.loading.as_ref().map(mpsc::Receiver::try_recv) {
+            Some(Ok(result)) => result,
+            Some(Err(mpsc::TryRecvError::Empty)) | None => return,
+            Some(Err(mpsc::TryRecvError::Disconnected)) => {
+                Err("The image loader crashed".to_owned())
+            }
+        };
+        self.loading = None;
+        match result {
+            Ok(image) => {
+                if let Some(old) = self.image.take() {
+                    textures.free(old.tiles.into_iter().map(|tile| tile.texture));
+                }
+                let tiles = image
+                    .tiles
+                    .into_iter()
+                    .map(|tile| Tile {
+                        texture: match tile.source {
+                            Some(_) => textures.register_evictable(
+                                &tile.texture,
+                                tile.bytes,
+                                self.sampling,
+                            ),
+                            None => textures.register(&tile.texture, tile.bytes, self.sampling),
+                        },
+                        rect: tile.rect,
+                        uv: tile.uv,
+                        bytes: tile.bytes,
+                        source: tile.source,
+                    })
+                    .collect();
+                self.image = Some(TiledImage {
+                    size: image.size,
+                    tiles,
+                    memory: image.memory,
+                    stats: image.stats,
+                    pixels: image.pixels,
+                    #[cfg(feature = "hdr")]
+                    hdr: image.hdr,
+                });
+                self.error = None;
+                self.view.reset();
+            }
+            Err(err) => self.error = Some(err),
+        }
+        ctx.request_repaint();
+    }
+}
+
+/// Loads the image at `path` into textures of at most `max_texture_dimension_2d` pixels per
+/// side.
+fn load_tiled(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    path: &std::path::Path,
+) -> Result<TiledImage<wgpu::Texture>, String> {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+        #[cfg(feature = "ktx2")]
+        Some("ktx2") => return load_ktx2(device, queue, path),
+        #[cfg(feature = "hdr")]
+        Some("exr" | "hdr") => return load_hdr(device, queue, path),
+        _ => {}
+    }
+
+    let mut reader = image::ImageReader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|err| err.to_string())?;
+    // Maps and scans easily exceed the default allocation limit.
+    reader.no_limits();
+    let image = reader.decode().map_err(|err| err.to_string())?.into_rgba8();
+    let memory = format!("{} as RGBA", mebibytes(image.as_raw().len()));
+    let mut tiled = tile(device, queue, path, image);
+    tiled.memory = memory;
+    Ok(tiled)
+}
+
+#[cfg(feature = "ktx2")]
+fn load_ktx2(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    path: &std::path::Path,
+) -> Result<TiledImage<wgpu::Texture>, String> {
+    use crate::ktx2_loader::Ktx2Image;
+
+    match crate::ktx2_loader::load(device, queue, path)? {
+        Ktx2Image::Compressed {
+            texture,
+            size,
+            format,
+            bytes,
+        } => {
+            let rgba_bytes = (size[0] * size[1]) as usize * 4;
+            let memory = format!(
+                "{:?}, sampled as it is: {} instead of {} as RGBA",
+                format,
+                mebibytes(bytes),
+                mebibytes(rgba_bytes)
+            );
+            Ok(single_tile(texture, size, bytes as u64, memory))
+        }
+        Ktx2Image::Decompressed {
+            image,
+            format,
+            reason,
+        } => {
+            let memory = format!(
+                "{:?}, decompressed on the CPU because {}: {} as RGBA",
+                format,
+                reason,
+                mebibytes(image.as_raw().len())
+            );
+            let mut tiled = tile(device, queue, path, image);
+            tiled.memory = memory;
+            Ok(tiled)
+        }
+    }
+}
+
+#[cfg(feature = "hdr")]
+fn load_hdr(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    path: &std::path::Path,
+) -> Result<TiledImage<wgpu::Texture>, String> {
+    let (hdr, target) = crate::hdr_image::HdrImage::load(device, queue, path)?;
+    let memory = format!(
+        "Rgba16Float, tonemapped into Rgba8UnormSrgb: {}",
+        mebibytes(hdr.bytes())
+    );
+    let bytes = u64::from(hdr.size()[0]) * u64::from(hdr.size()[1]) * 4;
+    let mut tiled = single_tile(target, hdr.size(), bytes, memory);
+    tiled.hdr = Some(hdr);
+    Ok(tiled)
+}
+
+fn mebibytes(bytes: usize) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Splits `image` into textures of at most `max_texture_dimension_2d` pixels per side.
+fn tile(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    path: &std::path::Path,
+    image: image::RgbaImage,
+) -> TiledImage<wgpu::Texture> {
+    let max_side = device.limits().max_texture_dimension_2d as usize;
+    let (width, height) = (image.width() as usize, image.height() as usize);
+
+    let mut tiles = Vec::new();
+    for (row, y) in tile_spans(height, max_side).into_iter().enumerate() {
+        for (column, x) in tile_spans(width, max_side).iter().enumerate() {
+            let source = [x.pixels.clone(), y.pixels.clone()];
+            tiles.push(Tile {
+                texture: crate::user_textures::upload(
+                    device,
+                    queue,
+                    &format!("{}_{}_{}", path.display(), column, row),
+                    &tile_image(&image, &source),
+                ),
+                rect: egui::Rect::from_x_y_ranges(x.draw.0..=x.draw.1, y.draw.0..=y.draw.1),
+                uv: egui::Rect::from_x_y_ranges(x.uv.0..=x.uv.1, y.uv.0..=y.uv.1),
+                bytes: (x.pixels.len() * y.pixels.len() * 4) as u64,
+                source: Some(source),
+            });
+        }
+    }
+    TiledImage {
+        size: egui::vec2(width as f32, height as f32),
+        tiles,
+        memory: String::new(),
+        stats: Some(ImageStats::compute(image.as_raw())),
+        pixels: Some(image),
+        #[cfg(feature = "hdr")]
+        hdr: None,
+    }
+}
+
+/// The pixels of `image` in the columns and rows of `source`.
+fn tile_image(
+    image: &image::RgbaImage,
+    [columns, rows]: &[std::ops::Range<usize>; 2],
+) -> egui::ColorImage {
+    let (width, pixels) = (image.width() as usize, image.as_raw());
+    let mut tile_pixels = Vec::with_capacity(columns.len() * rows.len() * 4);
+    for line in rows.clone() {
+        let start = (line * width + columns.start) * 4;
+        tile_pixels.extend_from_slice(&pixels[start..start + columns.len() * 4]);
+    }
+    egui::ColorImage::from_rgba_unmultiplied([columns.len(), rows.len()], &tile_pixels)
+}
+
+/// An image in a single texture, which is never larger than the texture limit.
+#[cfg(any(feature = "ktx2", feature = "hdr"))]
+fn single_tile(
+    texture: wgpu::Texture,
+    size: [u32; 2],
+    bytes: u64,
+    memory: String,
+) -> TiledImage<wgpu::Texture> {
+    let size = egui::vec2(size[0] as f32, size[1] as f32);
+    TiledImage {
+        size,
+        tiles: vec![Tile {
+            texture,
+            rect: egui::Rect::from_min_size(egui::Pos2::ZERO, size),
+            uv: egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0)),
+            bytes,
+            source: None,
+        }],
+        memory,
+        stats: None,
+        pixels: None,
+        #[cfg(feature = "hdr")]
+        hdr: None,
+    }
+}
+
+/// Splits `len` pixels into tiles of at most `max_side` pixels.
+///
+/// Neighbouring tiles share a pixel, and the seam lies halfway between the centers of the
+/// shared pixel in both tiles. Like that, linear filtering blends across the seam just like
+/// within a single texture, and the tiles look seamless at any zoom.
+fn tile_spans(len: usize, max_side: usize) -> Vec<Span> {
+    if len <= max_side {
+        return vec![Span {
+            pixels: 0..len,
+            draw: (0.0, len as f32),
+            uv: (0.0, 1.0),
+        }];
+    }
+    let step = max_side.max(2) - 1;
+    let mut spans = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = (start + step + 1).min(len);
+        let texels = (end - start) as f32;
+        let (first, last) = (start == 0, end == len);
+        spans.push(Span {
+            pixels: start..end,
+            draw: (
+                if first { 0.0 } else { start as f32 + 0.5 },
+                if last { len as f32 } else { end as f32 - 0.5 },
+            ),
+            uv: (
+                if first { 0.0 } else { 0.5 / texels },
+                if last { 1.0 } else { (texels - 0.5) / texels },
+            ),
+        });
+        if last {
+            return spans;
+        }
+        start += step;
+    }
+}
+
+impl SaveState for ImageViewer {
+    /// The open file.
+    type State = Option<PathBuf>;
+
+    fn save_state(&self) -> Option<PathBuf> {
+        (self.image.is_some() || self.loading.is_some()).then(|| PathBuf::from(&self.path))
+    }
+
+    fn restore_state(&mut self, file: Option<PathBuf>) {
+        self.reopen = file;
+    }
+}
@@ -0,0 +1,122 @@
+use winit::event::{Event, MouseScrollDelta, WindowEvent};
+
+/// Coalesces the pointer motion and the scrolling between two frames, so that a mouse with a
+/// high polling rate doesn't pass hundreds of events per frame through egui. Only the last
+/// position and the sum of the scroll deltas are forwarded, before the next event of another
+/// kind or before the frame is drawn.
+#[derive(Default)]
+pub struct InputBatch {
+    moved: Option<Event<'static, crate::Event>>,
+    wheel: Option<Event<'static, crate::Event>>,
+    /// The window events of the current frame, as they came from winit.
+    received: u32,
+    /// The window events of the current frame that were forwarded.
+    forwarded: u32,
+}
+
+impl InputBatch {
+    /// Keeps `event` for later if it can be coalesced and `enabled`, and returns whether it did.
+    /// Counts the window events either way.
+    #[allow(deprecated)] // The `modifiers` of the pointer events.
+    pub fn coalesce(&mut self, event: &Event<crate::Event>, enabled: bool) -> bool {
+        let (window_id, window_event) = match event {
+            Event::WindowEvent { window_id, event } => (*window_id, event),
+            _ => return false,
+        };
+        self.received += 1;
+        if !enabled {
+            return false;
+        }
+        match *window_event {
+            WindowEvent::CursorMoved {
+                device_id,
+                position,
+                modifiers,
+            } => {
+                // Only the last position matters to egui.
+                self.moved = Some(Event::WindowEvent {
+                    window_id,
+                    event: WindowEvent::CursorMoved {
+                        device_id,
+                        position,
+                        modifiers,
+                    },
+                });
+                true
+            }
+            WindowEvent::MouseWheel {
+                device_id,
+                delta,
+                phase,
+                modifiers,
+            } => {
+                let added = match &mut self.wheel {
+                    Some(Event::WindowEvent {
+                        event:
+                            WindowEvent::MouseWheel {
+                                delta: pending,
+                                phase: pending_phase,
+                                ..
+                            },
+                        ..
+                    }) if *pending_phase == phase => add_delta(pending, delta),
+                    _ => false,
+                };
+                if !added {
+                    if self.wheel.is_some() {
+                        return false;
+                    }
+                    self.wheel = Some(Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::MouseWheel {
+                            device_id,
+                            delta,
+                            phase,
+                            modifiers,
+                        },
+                    });
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The coalesced events, in the order they have to be forwarded in.
+    pub fn take(&mut self) -> impl Iterator<Item = Event<'static, crate::Event>> {
+        self.moved.take().into_iter().chain(self.wheel.take())
+    }
+
+    /// Counts a window event that was forwarded.
+    pub fn forwarded(&mut self, event: &Event<crate::Event>) {
+        if let Event::WindowEvent { .. } = event {
+            self.forwarded += 1;
+        }
+    }
+
+    /// The window events received from winit and forwarded since the last call, for the
+    /// counter in the event log.
+    pub fn take_counts(&mut self) -> (u32, u32) {
+        (
+            std::mem::take(&mut self.received),
+            std::mem::take(&mut self.forwarded),
+        )
+    }
+}
+
+/// Adds `delta` to `pending` if they're of the same kind.
+fn add_delta(pending: &mut MouseScrollDelta, delta: MouseScrollDelta) -> bool {
+    match (pending, delta) {
+        (MouseScrollDelta::LineDelta(x, y), MouseScrollDelta::LineDelta(dx, dy)) => {
+            *x += dx;
+            *y += dy;
+            true
+        }
+        (MouseScrollDelta::PixelDelta(position), MouseScrollDelta::PixelDelta(added)) => {
+            position.x += added.x;
+            position.y += added.y;
+            true
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{
+    DeviceId, ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+    TouchPhase, WindowEvent,
+};
+use winit::window::WindowId;
+
+use crate::clock::ReplayClock;
+use crate::Event;
+
+/// One line of a recording. Only the events the platform integration reacts to are recorded.
+///
+/// The events in front of a `Frame` are the input of that frame.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum Entry {
+    /// The window at the start of the recording.
+    Start {
+        width: u32,
+        height: u32,
+        scale_factor: f64,
+    },
+    /// A frame was drawn at `time`, in seconds since the start.
+    Frame {
+        time: f64,
+    },
+    Resized {
+        width: u32,
+        height: u32,
+    },
+    ScaleFactorChanged {
+        scale_factor: f64,
+        width: u32,
+        height: u32,
+    },
+    CursorMoved {
+        x: f64,
+        y: f64,
+    },
+    CursorLeft,
+    MouseInput {
+        state: ElementState,
+        button: MouseButton,
+    },
+    MouseWheel {
+        delta: MouseScrollDelta,
+        phase: TouchPhase,
+    },
+    KeyboardInput {
+        input: KeyboardInput,
+    },
+    ReceivedCharacter {
+        character: char,
+    },
+    ModifiersChanged {
+        modifiers: ModifiersState,
+    },
+    Touch {
+        id: u64,
+        phase: TouchPhase,
+        x: f64,
+        y: f64,
+    },
+}
+
+impl Entry {
+    fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        Some(match event {
+            WindowEvent::Resized(size) => Entry::Resized {
+                width: size.width,
+                height: size.height,
+            },
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                new_inner_size,
+            } => Entry::ScaleFactorChanged {
+                scale_factor: *scale_factor,
+                width: new_inner_size.width,
+                height: new_inner_size.height,
+            },
+            WindowEvent::CursorMoved { position, .. } => Entry::CursorMoved {
+                x: position.x,
+                y: position.y,
+            },
+            WindowEvent::CursorLeft { .. } => Entry::CursorLeft,
+            WindowEvent::MouseInput { state, button, .. } => Entry::MouseInput {
+                state: *state,
+                button: *button,
+            },
+            WindowEvent::MouseWheel { delta, phase, .. } => Entry::MouseWheel {
+                delta: *delta,
+                phase: *phase,
+            },
+            WindowEvent::KeyboardInput { input, .. } => Entry::KeyboardInput { input: *input },
+            WindowEvent::ReceivedCharacter(character) => Entry::ReceivedCharacter {
+                character: *character,
+            },
+            WindowEvent::ModifiersChanged(modifiers) => Entry::ModifiersChanged {
+                modifiers: *modifiers,
+            },
+            WindowEvent::Touch(touch) => Entry::Touch {
+                id: touch.id,
+                phase: touch.phase,
+                x: touch.location.x,
+                y: touch.location.y,
+            },
+            _ => return None,
+        })
+    }
+
+    /// Recreates the winit event and hands it to `f`. Non-event entries are ignored.
+    #[allow(deprecated)]
+    fn replay(&self, window_id: WindowId, f: &mut impl FnMut(&winit::event::Event<Event>)) {
+        // The platform integration doesn't look at the device.
+        let device_id = unsafe { DeviceId::dummy() };
+        let mut scaled_size;
+        let event = match *self {
+            Entry::Start { .. } | Entry::Frame { .. } => return,
+            Entry::Resized { width, height } => {
+                WindowEvent::Resized(PhysicalSize::new(width, height))
+            }
+            Entry::ScaleFactorChanged {
+                scale_factor,
+                width,
+                height,
+            } => {
+                scaled_size = PhysicalSize::new(width, height);
+                WindowEvent::ScaleFactorChanged {
+                    scale_factor,
+                    new_inner_size: &mut scaled_size,
+                }
+            }
+            Entry::CursorMoved { x, y } => WindowEvent::CursorMoved {
+                device_id,
+                position: PhysicalPosition::new(x, y),
+                modifiers: ModifiersState::empty(),
+            },
+            Entry::CursorLeft => WindowEvent::CursorLeft { device_id },
+            Entry::MouseInput { state, button } => WindowEvent::MouseInput {
+                device_id,
+                state,
+                button,
+                modifiers: ModifiersState::empty(),
+            },
+            Entry::MouseWheel { delta, phase } => WindowEvent::MouseWheel {
+                device_id,
+                delta,
+                phase,
+                modifiers: ModifiersState::empty(),
+            },
+            Entry::KeyboardInput { input } => WindowEvent::KeyboardInput {
+                device_id,
+                input,
+                is_synthetic: false,
+            },
+            Entry::ReceivedCharacter { character } => WindowEvent::ReceivedCharacter(character),
+            Entry::ModifiersChanged { modifiers } => WindowEvent::ModifiersChanged(modifiers),
+            Entry::Touch { id, phase, x, y } => WindowEvent::Touch(winit::event::Touch {
+                device_id,
+                phase,
+                location: PhysicalPosition::new(x, y),
+                force: None,
+                id,
+            }),
+        };
+        f(&winit::event::Event::WindowEvent { window_id, event });
+    }
+}
+
+/// Writes the input events and frame times to a file, one JSON entry per line.
+pub struct Recorder {
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, size: PhysicalSize<u32>, scale_factor: f64) -> io::Result<Self> {
+        let mut recorder = Self {
+            writer: BufWriter::new(File::create(path)?),
+        };
+        recorder.write(&Entry::Start {
+            width: size.width,
+            height: size.height,
+            scale_factor,
+        })?;
+        Ok(recorder)
+    }
+
+    /// Records `event` if the platform integration reacts to it.
+    pub fn record_event(&mut self, event: &WindowEvent) -> io::Result<()> {
+        match Entry::from_window_event(event) {
+            Some(entry) => self.write(&entry),
+            None => Ok(()),
+        }
+    }
+
+    /// Records that a frame was drawn with the input recorded since the last frame.
+    pub fn record_frame(&mut self, time: f64) -> io::Result<()> {
+        self.write(&Entry::Frame { time })
+    }
+
+    /// Writes the buffered entries to the file.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn write(&mut self, entry: &Entry) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, entry)?;
+        self.writer.write_all(b"\n")
+    }
+}
+
+/// Plays a recording back, one recorded frame per drawn frame.
+pub struct Replay {
+    entries: VecDeque<Entry>,
+    finished: bool,
+}
+
+impl Replay {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut entries = VecDeque::new();
+        for (index, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = serde_json::from_str(&line).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: {}", index + 1, err),
+                )
+            })?;
+            entries.push_back(entry);
+        }
+        Ok(Self {
+            entries,
+            finished: false,
+        })
+    }
+
+    /// The window size at the start of the recording.
+    pub fn start_size(&self) -> Option<PhysicalSize<u32>> {
+        match self.entries.front() {
+            Some(Entry::Start { width, height, .. }) => Some(PhysicalSize::new(*width, *height)),
+            _ => None,
+        }
+    }
+
+    /// The clock that plays the recorded frame times back, frame by frame along with the input.
+    pub fn clock(&self) -> ReplayClock {
+        let times = self
+            .entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::Frame { time } => Some(*time),
+                _ => None,
+            })
+            .collect();
+        ReplayClock::new(times)
+    }
+
+    /// Hands the input of the next recorded frame to `f`. Returns `false` once the recording is
+    /// over.
+    pub fn next_frame(
+        &mut self,
+        window_id: WindowId,
+        mut f: impl FnMut(&winit::event::Event<Event>),
+    ) -> bool {
+        while let Some(entry) = self.entries.pop_front() {
+            if let Entry::Frame { .. } = entry {
+                return true;
+            }
+            entry.replay(window_id, &mut f);
+        }
+        self.finished = true;
+        false
+    }
+
+    /// Whether all recorded frames were played back.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
@@ -0,0 +1,459 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+
+use crate::gif_encoder::GifEncoder;
+use crate::readback::Region;
+
+/// How many frames per second a recording captures at most.
+const FRAME_RATE: f64 = 15.0;
+/// How many captured frames can wait for the encoder. Frames beyond that are dropped, and the
+/// one before is shown for longer instead.
+const MAX_QUEUED_FRAMES: usize = 30;
+/// How long a badge of pressed keys stays, in seconds.
+const BADGE_DURATION: f64 = 1.5;
+/// How many badges are shown at once, the oldest go first.
+const MAX_BADGES: usize = 4;
+/// How long the ring of a click expands, in seconds.
+const CLICK_DURATION: f64 = 0.5;
+/// The largest radius of the ring of a click, in points.
+const CLICK_RADIUS: f32 = 24.0;
+
+/// A captured frame, or the end of the recording, for the encoder thread.
+enum Message {
+    Frame {
+        pixels: Vec<u8>,
+        size: [u32; 2],
+        time: f64,
+    },
+    End(f64),
+}
+
+struct Recording {
+    start: f64,
+    last_frame: Option<f64>,
+    /// The size of the first frame, the one of the animation. Frames of a larger window are
+    /// cut off.
+    size: Option<[u32; 2]>,
+    sender: mpsc::SyncSender<Message>,
+    writer: JoinHandle<Result<(PathBuf, u32), String>>,
+    dropped: u32,
+}
+
+/// A key combination or typed text, shown as a badge at the bottom of the window.
+struct Badge {
+    text: String,
+    time: f64,
+    /// Typed text grows while the user keeps typing, shortcuts repeat with a count.
+    typing: bool,
+    repeats: u32,
+}
+
+/// A press of a mouse button, shown as a ring that expands around it.
+struct Click {
+    pos: egui::Pos2,
+    button: egui::PointerButton,
+    time: f64,
+}
+
+/// Draws what the user does on top of the UI: badges of the pressed shortcuts and typed text,
+/// rings around clicks and a mark at the pointer, which the window system doesn't put into the
+/// frame.
+#[derive(Default)]
+pub struct InputOverlay {
+    badges: VecDeque<Badge>,
+    clicks: Vec<Click>,
+}
+
+impl InputOverlay {
+    /// Takes note of the input of this frame.
+    pub fn update(&mut self, ctx: &egui::Context) {
+        let input = ctx.input();
+        let time = input.time;
+        for event in &input.events {
+            match event {
+                egui::Event::Text(text) => match self.badges.back_mut() {
+                    Some(badge) if badge.typing && time - badge.time < BADGE_DURATION => {
+                        badge.text.push_str(text);
+                        badge.time = time;
+                    }
+                    _ => self.push(text.clone(), true, time),
+                },
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                } => {
+                    let text = match key_label(*key, *modifiers) {
+                        Some(text) => text,
+                        None => continue,
+                    };
+                    match self.badges.back_mut() {
+                        Some(badge) if !badge.typing && badge.text == text => {
+                            badge.repeats += 1;
+                            badge.time = time;
+                        }
+                        _ => self.push(text, false, time),
+                    }
+                }
+                egui::Event::PointerButton {
+                    pos,
+                    button,
+                    pressed: true,
+                    ..
+                } => self.clicks.push(Click {
+                    pos: *pos,
+                    button: *button,
+                    time,
+                }),
+                _ => {}
+            }
+        }
+        self.badges
+            .retain(|badge| time - badge.time < BADGE_DURATION);
+        self.clicks
+            .retain(|click| time - click.time < CLICK_DURATION);
+    }
+
+    fn push(&mut self, text: String, typing: bool, time: f64) {
+        if self.badges.len() == MAX_BADGES {
+            self.badges.pop_front();
+        }
+        self.badges.push_back(Badge {
+            text,
+            time,
+            typing,
+            repeats: 1,
+        });
+    }
+
+    /// Paints the overlay above everything else. Keeps repainting while something fades.
+    pub fn paint(&self, ctx: &egui::Context) {
+        let layer = egui::LayerId::new(egui::Order::Tooltip, egui::Id::new("input_overlay"));
+        let painter = ctx.layer_painter(layer);
+        let (time, pointer, pressed) = {
+            let input = ctx.input();
+            (
+                input.time,
+                input.pointer.hover_pos(),
+                input.pointer.any_down(),
+            )
+        };
+        let accent = egui::Color32::from_rgb(255, 180, 40);
+        for click in &self.clicks {
+            let progress = ((time - click.time) / CLICK_DURATION) as f32;
+            let color = match click.button {
+                egui::PointerButton::Primary => accent,
+                _ => egui::Color32::from_rgb(80, 170, 255),
+            };
+            painter.circle_stroke(
+                click.pos,
+                CLICK_RADIUS * progress,
+                egui::Stroke::new(3.0, color.linear_multiply(1.0 - progress)),
+            );
+        }
+        if let Some(pointer) = pointer {
+            let fill = match pressed {
+                true => accent.linear_multiply(0.6),
+                false => egui::Color32::from_white_alpha(60),
+            };
+            painter.circle(
+                pointer,
+                8.0,
+                fill,
+                egui::Stroke::new(1.0, egui::Color32::BLACK),
+            );
+        }
+
+        let font = egui::FontId::proportional(20.0);
+        let margin = egui::vec2(12.0, 6.0);
+        let mut bottom = ctx.input().screen_rect().center_bottom() - egui::vec2(0.0, 48.0);
+        // The newest at the bottom.
+        for badge in self.badges.iter().rev() {
+            let fade = (1.0 - (time - badge.time) / BADGE_DURATION).clamp(0.0, 1.0) as f32;
+            let text = match badge.repeats {
+                1 => badge.text.clone(),
+                repeats => format!("{} ×{}", badge.text, repeats),
+            };
+            let galley = painter.layout_no_wrap(
+                text,
+                font.clone(),
+                egui::Color32::WHITE.linear_multiply(fade),
+            );
+            let rect = egui::Rect::from_center_size(
+                bottom - egui::vec2(0.0, galley.size().y / 2.0 + margin.y),
+                galley.size() + 2.0 * margin,
+            );
+            painter.rect_filled(
+                rect,
+                8.0,
+                egui::Color32::from_black_alpha((200.0 * fade) as u8),
+            );
+            painter.galley(rect.min + margin, galley);
+            bottom.y = rect.top() - 6.0;
+        }
+        if !self.badges.is_empty() || !self.clicks.is_empty() {
+            ctx.request_repaint();
+        }
+    }
+}
+
+/// How the badge shows `key` pressed with `modifiers`. `None` for keys that type text, which
+/// the text events show instead.
+fn key_label(key: egui::Key, modifiers: egui::Modifiers) -> Option<String> {
+    use egui::Key;
+
+    let shortcut = modifiers.ctrl || modifiers.alt || modifiers.mac_cmd;
+    let name = match key {
+        Key::ArrowDown => "↓".to_owned(),
+        Key::ArrowLeft => "←".to_owned(),
+        Key::ArrowRight => "→".to_owned(),
+        Key::ArrowUp => "↑".to_owned(),
+        Key::Escape => "Esc".to_owned(),
+        Key::Tab => "Tab".to_owned(),
+        Key::Backspace => "⌫".to_owned(),
+        Key::Enter => "⏎".to_owned(),
+        Key::Space if shortcut => "Space".to_owned(),
+        Key::Insert => "Insert".to_owned(),
+        Key::Delete => "Del".to_owned(),
+        Key::Home => "Home".to_owned(),
+        Key::End => "End".to_owned(),
+        Key::PageUp => "PgUp".to_owned(),
+        Key::PageDown => "PgDn".to_owned(),
+        // The letters, digits and function keys are named like that already.
+        key if shortcut || format!("{:?}", key).starts_with('F') => {
+            let name = format!("{:?}", key);
+            name.strip_prefix("Num").unwrap_or(&name).to_owned()
+        }
+        _ => return None,
+    };
+    let mut label = String::new();
+    for (held, modifier) in [
+        (modifiers.ctrl, "Ctrl+"),
+        (modifiers.alt, "Alt+"),
+        (modifiers.mac_cmd, "⌘"),
+        (modifiers.shift, "Shift+"),
+    ] {
+        if held {
+            label.push_str(modifier);
+        }
+    }
+    label.push_str(&name);
+    Some(label)
+}
+
+/// Records the window into an animated GIF for tutorials, with the [`InputOverlay`] on top.
+/// Each frame is shown as long as it was on screen, the timeline of the GIF follows the one of
+/// the recording.
+#[derive(Default)]
+pub struct InteractionRecorder {
+    recording: Option<Recording>,
+    /// The recording that is encoded to the end in the background.
+    finishing: Option<JoinHandle<Result<(PathBuf, u32), String>>>,
+    overlay: InputOverlay,
+    /// The time of the current frame.
+    time: f64,
+}
+
+impl InteractionRecorder {
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn start(&mut self, ctx: &egui::Context) {
+        let directory = dirs::picture_dir().unwrap_or_else(|| PathBuf::from("."));
+        let name = chrono::Local::now()
+            .format("egui_example_%Y%m%d_%H%M%S.gif")
+            .to_string();
+        let path = directory.join(name);
+        let (sender, frames) = mpsc::sync_channel(MAX_QUEUED_FRAMES);
+        let repaint = ctx.clone();
+        let writer = std::thread::spawn(move || {
+            let result = encode(path, frames);
+            repaint.request_repaint();
+            result
+        });
+        self.recording = Some(Recording {
+            start: ctx.input().time,
+            last_frame: None,
+            size: None,
+            sender,
+            writer,
+            dropped: 0,
+        });
+        self.overlay = InputOverlay::default();
+    }
+
+    /// Ends the recording, the rest of the frames is encoded in the background.
+    pub fn stop(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            // Waits for a free place in the queue, the last frame needs the end for its delay.
+            let _ = recording.sender.send(Message::End(self.time));
+            if recording.dropped > 0 {
+                eprintln!(
+                    "The GIF encoder didn't keep up, {} frames were dropped",
+                    recording.dropped
+                );
+            }
+            self.finishing = Some(recording.writer);
+        }
+    }
+
+    /// Draws the overlay while recording and schedules the next capture. Returns a message
+    /// once a recording is saved.
+    pub fn update(&mut self, ctx: &egui::Context) -> Option<String> {
+        self.time = ctx.input().time;
+        if let Some(recording) = &self.recording {
+            self.overlay.update(ctx);
+            self.overlay.paint(ctx);
+            let next = recording
+                .last_frame
+                .map_or(0.0, |last| last + 1.0 / FRAME_RATE);
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(
+                (next - self.time).max(0.0),
+            ));
+        }
+        if !self.finishing.as_ref()?.is_finished() {
+            return None;
+        }
+        let result = self
+            .finishing
+            .take()?
+            .join()
+            .unwrap_or_else(|_| Err("the encoder crashed".to_owned()));
+        Some(match result {
+            Ok((path, frames)) => format!("Saved {} frames to {}", frames, path.display()),
+            Err(err) => format!("Recording failed: {}", err),
+        })
+    }
+
+    /// The region of the current frame to capture: all of it, if it's time for the next frame.
+    pub fn take_capture_region(&mut self, size: [u32; 2]) -> Option<Region> {
+        let recording = self.recording.as_mut()?;
+        if let Some(last) = recording.last_frame {
+            if self.time - last < 1.0 / FRAME_RATE {
+                return None;
+            }
+        }
+        recording.last_frame = Some(self.time);
+        let size = *recording.size.get_or_insert(size);
+        Some(Region {
+            origin: [0, 0],
+            size,
+        })
+    }
+
+    /// Hands the pixels read back for `take_capture_region` to the encoder.
+    pub fn add_frame(&mut self, pixels: Vec<u8>, size: [u32; 2]) {
+        let recording = match &mut self.recording {
+            Some(recording) => recording,
+            None => return,
+        };
+        let frame = Message::Frame {
+            pixels,
+            size,
+            time: self.time,
+        };
+        if recording.sender.try_send(frame).is_err() {
+            recording.dropped += 1;
+        }
+    }
+
+    /// A button that shows how long the recording runs and stops it, for the menu bar.
+    pub fn status_ui(&mut self, ui: &mut egui::Ui) {
+        let start = match &self.recording {
+            Some(recording) => recording.start,
+            None => return,
+        };
+        let text = egui::RichText::new(format!("⏺ REC {:.0} s", self.time - start))
+            .color(egui::Color32::from_rgb(230, 40, 40));
+        if ui
+            .button(text)
+            .on_hover_text("Stop recording and save the GIF")
+            .clicked()
+        {
+            self.stop();
+        }
+    }
+}
+
+/// The body of the encoder thread. Writes each frame once the next one arrived, which decides
+/// how long it's shown. Frames that look like the one before only make it last longer.
+fn encode(path: PathBuf, frames: mpsc::Receiver<Message>) -> Result<(PathBuf, u32), String> {
+    let mut encoder = None;
+    let mut previous: Option<(Vec<u8>, [u32; 2], f64)> = None;
+    let mut written = 0;
+    for message in frames {
+        let (next, time) = match message {
+            Message::Frame { pixels, size, time } => (Some((pixels, size)), time),
+            Message::End(time) => (None, time),
+        };
+        if let (Some((pixels, size)), Some((previous_pixels, previous_size, _))) =
+            (&next, &previous)
+        {
+            if pixels == previous_pixels && size == previous_size {
+                continue;
+            }
+        }
+        if let Some((pixels, size, start)) = previous.take() {
+            let gif_size = [size[0] as u16, size[1] as u16];
+            let encoder = match &mut encoder {
+                Some(encoder) => encoder,
+                None => {
+                    let file = std::fs::File::create(&path)
+                        .map_err(|err| format!("Can't create {}: {}", path.display(), err))?;
+                    encoder.insert(
+                        GifEncoder::new(std::io::BufWriter::new(file), gif_size)
+                            .map_err(|err| err.to_string())?,
+                    )
+                }
+            };
+            // Browsers show shorter delays than 2 hundredths slower instead.
+            let delay = ((time - start) * 100.0).round().clamp(2.0, u16::MAX as f64) as u16;
+            encoder
+                .frame(&pixels, gif_size, delay)
+                .map_err(|err| err.to_string())?;
+            written += 1;
+        }
+        match next {
+            Some((pixels, size)) => previous = Some((pixels, size, time)),
+            None => break,
+        }
+    }
+    match encoder {
+        Some(encoder) => {
+            encoder.finish().map_err(|err| err.to_string())?;
+            Ok((path, written))
+        }
+        None => Err("no frames were captured".to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_labeled() {
+        let ctrl = egui::Modifiers {
+            ctrl: true,
+            ..Default::default()
+        };
+        let ctrl_shift = egui::Modifiers {
+            shift: true,
+            ..ctrl
+        };
+        let none = egui::Modifiers::default();
+        assert_eq!(
+            key_label(egui::Key::Z, ctrl_shift).as_deref(),
+            Some("Ctrl+Shift+Z")
+        );
+        assert_eq!(key_label(egui::Key::Num1, ctrl).as_deref(), Some("Ctrl+1"));
+        assert_eq!(key_label(egui::Key::F5, none).as_deref(), Some("F5"));
+        assert_eq!(key_label(egui::Key::Enter, none).as_deref(), Some("⏎"));
+        // Typed, the text events show it.
+        assert_eq!(key_label(egui::Key::A, none), None);
+        assert_eq!(key_label(egui::Key::Space, none), None);
+    }
+}
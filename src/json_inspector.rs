@@ -0,0 +1,176 @@
+use std::path::PathBuf;
+
+use crate::json_tree::JsonTree;
+use crate::workspaces::SaveState;
+
+/// Shows a JSON document as a [`JsonTree`], to search it and edit its values. Documents are
+/// opened by entering their path, by dropping them onto the window or from the clipboard.
+#[derive(Default)]
+pub struct JsonInspector {
+    path: String,
+    document: Option<serde_json::Value>,
+    /// Where the document was opened from.
+    source: Option<PathBuf>,
+    edited: bool,
+    search: String,
+    /// The result of the last action.
+    status: Option<String>,
+    // On some platforms the clipboard content is only available while the clipboard is alive.
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    clipboard: Option<arboard::Clipboard>,
+    /// The file of a restored workspace, opened once the window is.
+    reopen: Option<PathBuf>,
+}
+
+impl JsonInspector {
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if *open {
+            let dropped = self.reopen.take().or_else(|| {
+                ctx.input().raw.dropped_files.iter().find_map(|file| {
+                    file.path.clone().filter(|path| {
+                        path.extension()
+                            .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+                    })
+                })
+            });
+            if let Some(path) = dropped {
+                self.path = path.display().to_string();
+                self.load(path);
+            }
+        }
+
+        egui::Window::new("🌲 JSON inspector")
+            .open(open)
+            .default_size([480.0, 560.0])
+            .show(ctx, |ui| self.ui(ui));
+    }
+
+    fn load(&mut self, path: PathBuf) {
+        let result = std::fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| serde_json::from_str(&text).map_err(|err| err.to_string()));
+        match result {
+            Ok(document) => self.open_document(document, Some(path)),
+            Err(err) => {
+                self.status = Some(format!("Can't open {}: {}", path.display(), err));
+            }
+        }
+    }
+
+    fn paste(&mut self) {
+        let result = self.clipboard_text().and_then(|text| {
+            serde_json::from_str(&text).map_err(|err| format!("The clipboard has no JSON: {}", err))
+        });
+        match result {
+            Ok(document) => self.open_document(document, None),
+            Err(err) => self.status = Some(err),
+        }
+    }
+
+    fn open_document(&mut self, document: serde_json::Value, source: Option<PathBuf>) {
+        self.document = Some(document);
+        self.source = source;
+        self.edited = false;
+        self.status = None;
+    }
+
+    fn save(&mut self) {
+        let (document, path) = match (&self.document, &self.source) {
+            (Some(document), Some(path)) => (document, path),
+            _ => return,
+        };
+        let result = serde_json::to_string_pretty(document)
+            .map_err(|err| err.to_string())
+            .and_then(|text| std::fs::write(path, text + "\n").map_err(|err| err.to_string()));
+        self.status = Some(match result {
+            Ok(()) => {
+                self.edited = false;
+                format!("Saved {}", path.display())
+            }
+            Err(err) => format!("Can't save {}: {}", path.display(), err),
+        });
+    }
+
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    fn clipboard_text(&mut self) -> Result<String, String> {
+        if self.clipboard.is_none() {
+            self.clipboard = Some(arboard::Clipboard::new().map_err(|err| err.to_string())?);
+        }
+        let clipboard = self.clipboard.as_mut().unwrap();
+        clipboard.get_text().map_err(|err| err.to_string())
+    }
+
+    /// arboard has no backend for the mobile platforms.
+    #[cfg(any(target_os = "android", target_os = "ios"))]
+    fn clipboard_text(&mut self) -> Result<String, String> {
+        Err("reading the clipboard is not supported on this platform".to_owned())
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let response = ui
+                .add(egui::TextEdit::singleline(&mut self.path).hint_text("Path, or drop a file"));
+            let entered = response.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+            if ui.button("Open").clicked() || entered {
+                self.load(PathBuf::from(&self.path));
+            }
+            if ui.button("📋 Paste").clicked() {
+                self.paste();
+            }
+        });
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+        let document = match &mut self.document {
+            Some(document) => document,
+            None => return,
+        };
+
+        let mut save = false;
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.search).hint_text("Search keys and values"),
+            );
+            if ui.button("Copy all").clicked() {
+                ui.output().copied_text =
+                    serde_json::to_string_pretty(document).unwrap_or_default();
+            }
+            let label = match self.edited {
+                true => "💾 Save*",
+                false => "💾 Save",
+            };
+            save = ui
+                .add_enabled(self.source.is_some(), egui::Button::new(label))
+                .on_disabled_hover_text("Pasted documents can only be copied")
+                .clicked();
+        });
+        ui.separator();
+
+        egui::ScrollArea::both()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                if JsonTree::new("json_inspector")
+                    .search(&self.search)
+                    .show(ui, document)
+                {
+                    self.edited = true;
+                }
+            });
+        if save {
+            self.save();
+        }
+    }
+}
+
+impl SaveState for JsonInspector {
+    /// The open file.
+    type State = Option<PathBuf>;
+
+    fn save_state(&self) -> Option<PathBuf> {
+        self.source.clone()
+    }
+
+    fn restore_state(&mut self, file: Option<PathBuf>) {
+        self.reopen = file;
+    }
+}
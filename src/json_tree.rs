@@ -0,0 +1,197 @@
+use serde_json::Value;
+
+/// A collapsible tree of a JSON value, in which the values can be edited. Each node is
+/// identified by its JSON pointer, so the collapse state survives edits and reloads of
+/// documents of the same shape. The context menu of a key copies its path or its value.
+pub struct JsonTree<'a> {
+    id: egui::Id,
+    search: &'a str,
+}
+
+impl<'a> JsonTree<'a> {
+    pub fn new(id_source: impl std::hash::Hash) -> Self {
+        Self {
+            id: egui::Id::new(id_source),
+            search: "",
+        }
+    }
+
+    /// Only shows the nodes whose key or value contains `search`, ignoring case, and their
+    /// parents. While searching, all shown nodes are expanded.
+    pub fn search(mut self, search: &'a str) -> Self {
+        self.search = search;
+        self
+    }
+
+    /// Returns whether a value was edited.
+    pub fn show(self, ui: &mut egui::Ui, value: &mut Value) -> bool {
+        let search = self.search.to_lowercase();
+        let mut changed = false;
+        let node = Node {
+            id: self.id,
+            search: &search,
+            pointer: String::new(),
+            key: "root".to_owned(),
+            depth: 0,
+        };
+        node.show(ui, value, &mut changed);
+        changed
+    }
+}
+
+struct Node<'a> {
+    id: egui::Id,
+    /// In lowercase.
+    search: &'a str,
+    /// The JSON pointer of the node, as in RFC 6901.
+    pointer: String,
+    key: String,
+    depth: usize,
+}
+
+impl<'a> Node<'a> {
+    fn child(&self, key: String) -> Self {
+        let escaped = key.replace('~', "~0").replace('/', "~1");
+        Self {
+            id: self.id,
+            search: self.search,
+            pointer: format!("{}/{}", self.pointer, escaped),
+            key,
+            depth: self.depth + 1,
+        }
+    }
+
+    fn show(self, ui: &mut egui::Ui, value: &mut Value, changed: &mut bool) {
+        if !subtree_matches(&self.key, value, self.search) {
+            return;
+        }
+        let summary = match value {
+            Value::Array(array) => format!("[{}]", array.len()),
+            Value::Object(object) => format!("{{{}}}", object.len()),
+            _ => {
+                ui.horizontal(|ui| {
+                    self.key_label(ui, value);
+                    *changed |= value_editor(ui, value);
+                });
+                return;
+            }
+        };
+
+        let header = |ui: &mut egui::Ui| {
+            self.key_label(ui, value);
+            ui.weak(summary);
+        };
+        if self.search.is_empty() {
+            let id = self.id.with(&self.pointer);
+            egui::collapsing_header::CollapsingState::load_with_default_open(
+                ui.ctx(),
+                id,
+                self.depth == 0,
+            )
+            .show_header(ui, header)
+            .body(|ui| self.children(ui, value, changed));
+        } else {
+            // Doesn't touch the stored collapse state, which is back once the search is cleared.
+            ui.horizontal(header);
+            ui.indent(self.id.with(&self.pointer), |ui| {
+                self.children(ui, value, changed)
+            });
+        }
+    }
+
+    fn children(&self, ui: &mut egui::Ui, value: &mut Value, changed: &mut bool) {
+        match value {
+            Value::Array(array) => {
+                for (index, child) in array.iter_mut().enumerate() {
+                    self.child(index.to_string()).show(ui, child, changed);
+                }
+            }
+            Value::Object(object) => {
+                for (key, child) in object.iter_mut() {
+                    self.child(key.clone()).show(ui, child, changed);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn key_label(&self, ui: &mut egui::Ui, value: &Value) {
+        let mut text = egui::RichText::new(&self.key).monospace();
+        if !self.search.is_empty() && self.key.to_lowercase().contains(self.search) {
+            text = text.background_color(ui.visuals().selection.bg_fill);
+        }
+        ui.add(egui::Label::new(text).sense(egui::Sense::click()))
+            .on_hover_text(&self.pointer)
+            .context_menu(|ui| {
+                if ui.button("Copy path").clicked() {
+                    ui.output().copied_text = self.pointer.clone();
+                    ui.close_menu();
+                }
+                if ui.button("Copy value").clicked() {
+                    ui.output().copied_text = match value {
+                        Value::String(string) => string.clone(),
+                        _ => serde_json::to_string_pretty(value).unwrap_or_default(),
+                    };
+                    ui.close_menu();
+                }
+            });
+    }
+}
+
+/// Edits a value that isn't an array or object. Returns whether it was changed.
+fn value_editor(ui: &mut egui::Ui, value: &mut Value) -> bool {
+    match value {
+        Value::Null => {
+            ui.weak("null");
+            false
+        }
+        Value::Bool(bool) => ui.checkbox(bool, "").changed(),
+        Value::Number(number) => {
+            if let Some(mut integer) = number.as_i64() {
+                let changed = ui.add(egui::DragValue::new(&mut integer)).changed();
+                if changed {
+                    *value = integer.into();
+                }
+                changed
+            } else if let Some(mut integer) = number.as_u64() {
+                let changed = ui.add(egui::DragValue::new(&mut integer)).changed();
+                if changed {
+                    *value = integer.into();
+                }
+                changed
+            } else {
+                let mut float = number.as_f64().unwrap_or_default();
+                let response = ui.add(egui::DragValue::new(&mut float).speed(0.01));
+                if response.changed() {
+                    // JSON has no infinite numbers.
+                    *value = serde_json::Number::from_f64(float).map_or(Value::Null, Value::Number);
+                }
+                response.changed()
+            }
+        }
+        Value::String(string) => ui
+            .add(egui::TextEdit::singleline(string).desired_width(f32::INFINITY))
+            .changed(),
+        Value::Array(_) | Value::Object(_) => false,
+    }
+}
+
+/// Whether the node or one of its descendants matches `search`.
+fn subtree_matches(key: &str, value: &Value, search: &str) -> bool {
+    if search.is_empty() || key.to_lowercase().contains(search) {
+        return true;
+    }
+    match value {
+        Value::Null => "null".contains(search),
+        Value::Bool(bool) => bool.to_string().contains(search),
+        Value::Number(number) => number.to_string().contains(search),
+        Value::String(string) => string.to_lowercase().contains(search),
+        Value::Array(array) => array
+            .iter()
+            .enumerate()
+            .any(|(index, child)| subtree_matches(&index.to_string(), child, search)),
+        Value::Object(object) => object
+            .iter()
+            .any(|(key, child)| subtree_matches(key, child, search)),
+    }
+}
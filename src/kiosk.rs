@@ -0,0 +1,209 @@
+use std::time::{Duration, Instant};
+
+use winit::event::WindowEvent;
+use winit::window::{Fullscreen, Window};
+
+/// How long the pointer can rest before it's hidden.
+const HIDE_CURSOR_AFTER: Duration = Duration::from_secs(3);
+
+/// The kiosk mode of `--kiosk`: the window covers a monitor without a border and can't be closed
+/// or minimized, the system shortcuts are held back where the platform lets an application do
+/// that, the cursor hides while it rests, and the UI goes back to its start after a while
+/// without input.
+pub struct Kiosk {
+    last_input: Instant,
+    cursor_hidden: bool,
+    /// `None` never resets the UI.
+    reset_after: Option<Duration>,
+    /// Whether the UI was reset since the last input, which happens once per idle time.
+    reset: bool,
+    #[cfg(target_os = "windows")]
+    hook: windows_sys::Win32::UI::WindowsAndMessaging::HHOOK,
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    xlib: Option<x11_dl::xlib::Xlib>,
+}
+
+impl Kiosk {
+    /// Puts `window` on the monitor with the index `monitor` in the list of the window system,
+    /// or on the primary one.
+    pub fn new(window: &Window, monitor: Option<usize>, reset_after: Option<Duration>) -> Self {
+        let monitor = match monitor {
+            Some(index) => window.available_monitors().nth(index).or_else(|| {
+                eprintln!("There is no monitor {}, using the primary one", index);
+                window.primary_monitor()
+            }),
+            None => window.primary_monitor(),
+        };
+        window.set_decorations(false);
+        window.set_resizable(false);
+        window.set_fullscreen(Some(Fullscreen::Borderless(monitor)));
+        let kiosk = Self {
+            last_input: Instant::now(),
+            cursor_hidden: false,
+            reset_after,
+            reset: false,
+            #[cfg(target_os = "windows")]
+            hook: hold_back_shortcuts(),
+            #[cfg(any(
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ))]
+            xlib: x11_dl::xlib::Xlib::open()
+                .map_err(|err| eprintln!("Can't load Xlib to grab the keyboard: {}", err))
+                .ok(),
+        };
+        kiosk.grab_keyboard(window);
+        kiosk
+    }
+
+    /// Reacts to the events of the window: input shows the cursor and restarts the idle time.
+    pub fn handle_event(&mut self, window: &Window, event: &WindowEvent) {
+        match event {
+            event if crate::idle::is_input(event) => {
+                self.last_input = Instant::now();
+                self.reset = false;
+                if self.cursor_hidden {
+                    window.set_cursor_visible(true);
+                    self.cursor_hidden = false;
+                }
+            }
+            // A grab only works while the window is shown.
+            WindowEvent::Focused(true) => self.grab_keyboard(window),
+            // Windows reports minimizing as a resize to zero.
+            WindowEvent::Resized(size) if size.width == 0 || size.height == 0 => {
+                window.set_minimized(false);
+            }
+            _ => {}
+        }
+    }
+
+    /// Hides the cursor once it rested long enough. Returns whether the UI should go back to its
+    /// start now.
+    pub fn update(&mut self, window: &Window) -> bool {
+        let idle = self.last_input.elapsed();
+        if !self.cursor_hidden && idle >= HIDE_CURSOR_AFTER {
+            window.set_cursor_visible(false);
+            self.cursor_hidden = true;
+        }
+        match self.reset_after {
+            Some(reset_after) if !self.reset && idle >= reset_after => {
+                self.reset = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// When `update` has something to do next, for the event loop to wake up.
+    pub fn deadline(&self) -> Option<Instant> {
+        let hide = (!self.cursor_hidden).then_some(self.last_input + HIDE_CURSOR_AFTER);
+        let reset = self
+            .reset_after
+            .filter(|_| !self.reset)
+            .map(|reset_after| self.last_input + reset_after);
+        hide.into_iter().chain(reset).min()
+    }
+
+    /// Takes the keyboard on X11, so that the window manager doesn't see its shortcuts.
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    fn grab_keyboard(&self, window: &Window) {
+        use winit::platform::unix::WindowExtUnix;
+
+        let (xlib, display, xwindow) =
+            match (&self.xlib, window.xlib_display(), window.xlib_window()) {
+                (Some(xlib), Some(display), Some(xwindow)) => (xlib, display, xwindow),
+                // Wayland has no way to hold the shortcuts of the compositor back.
+                _ => return,
+            };
+        // Fails while the window isn't viewable yet, the next focus tries again.
+        unsafe {
+            let display = display as *mut x11_dl::xlib::Display;
+            (xlib.XGrabKeyboard)(
+                display,
+                xwindow,
+                x11_dl::xlib::True,
+                x11_dl::xlib::GrabModeAsync,
+                x11_dl::xlib::GrabModeAsync,
+                x11_dl::xlib::CurrentTime,
+            );
+            (xlib.XFlush)(display);
+        }
+    }
+
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )))]
+    fn grab_keyboard(&self, _: &Window) {}
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for Kiosk {
+    fn drop(&mut self) {
+        if !self.hook.is_null() {
+            unsafe {
+                windows_sys::Win32::UI::WindowsAndMessaging::UnhookWindowsHookEx(self.hook);
+            }
+        }
+    }
+}
+
+/// Swallows the Windows key, Alt+Tab, Alt+Esc, Ctrl+Esc and Alt+F4 system-wide. Ctrl+Alt+Del
+/// can't be held back. The hook runs on the thread that installed it, here the one of the event
+/// loop, which pumps the messages it needs.
+#[cfg(target_os = "windows")]
+fn hold_back_shortcuts() -> windows_sys::Win32::UI::WindowsAndMessaging::HHOOK {
+    use windows_sys::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+        GetAsyncKeyState, VK_CONTROL, VK_ESCAPE, VK_F4, VK_LWIN, VK_RWIN, VK_TAB,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, SetWindowsHookExW, HC_ACTION, KBDLLHOOKSTRUCT, LLKHF_ALTDOWN,
+        WH_KEYBOARD_LL,
+    };
+
+    unsafe extern "system" fn hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code == HC_ACTION as i32 {
+            let key = &*(lparam as *const KBDLLHOOKSTRUCT);
+            let alt = key.flags & LLKHF_ALTDOWN != 0;
+            let control = GetAsyncKeyState(VK_CONTROL as i32) < 0;
+            let held_back = match key.vkCode as u16 {
+                VK_LWIN | VK_RWIN => true,
+                VK_TAB | VK_F4 => alt,
+                VK_ESCAPE => alt || control,
+                _ => false,
+            };
+            if held_back {
+                return 1;
+            }
+        }
+        CallNextHookEx(std::ptr::null_mut(), code, wparam, lparam)
+    }
+
+    let hook = unsafe { SetWindowsHookExW(WH_KEYBOARD_LL, Some(hook), std::ptr::null_mut(), 0) };
+    if hook.is_null() {
+        eprintln!(
+            "Can't hold back the system shortcuts: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+    hook
+}
@@ -0,0 +1,200 @@
+use ktx2::Format;
+use wgpu::{AstcBlock, AstcChannel, TextureFormat};
+
+/// The texture compression features that are requested for the device when the adapter has
+/// them, so that compressed textures can be sampled without decompressing them first.
+pub const COMPRESSION_FEATURES: wgpu::Features =
+    wgpu::Features::TEXTURE_COMPRESSION_BC.union(wgpu::Features::TEXTURE_COMPRESSION_ASTC_LDR);
+
+/// The base level of a KTX2 file.
+pub enum Ktx2Image {
+    /// Uploaded as it is, because the device can sample the format.
+    Compressed {
+        texture: wgpu::Texture,
+        size: [u32; 2],
+        format: TextureFormat,
+        bytes: usize,
+    },
+    /// Decompressed on the CPU, for the given reason.
+    Decompressed {
+        image: image::RgbaImage,
+        format: TextureFormat,
+        reason: String,
+    },
+}
+
+/// Loads the base level of the first layer of a BC or ASTC compressed KTX2 file.
+///
+/// The textures are always sampled as sRGB, since egui expects its textures to be. Their alpha
+/// isn't premultiplied like egui expects either, which only shows with translucent textures.
+pub fn load(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    path: &std::path::Path,
+) -> Result<Ktx2Image, String> {
+    let data = std::fs::read(path).map_err(|err| err.to_string())?;
+    let reader = ktx2::Reader::new(&data).map_err(|err| format!("{:?}", err))?;
+    let header = reader.header();
+    if let Some(scheme) = header.supercompression_scheme {
+        return Err(format!(
+            "Supercompression with {:?} isn't supported",
+            scheme
+        ));
+    }
+    let ktx2_format = header
+        .format
+        .ok_or("Files without a Vulkan format, like Basis Universal ones, aren't supported")?;
+    let (format, decode) = texture_format(ktx2_format)
+        .ok_or_else(|| format!("{:?} isn't a BC or ASTC format", ktx2_format))?;
+
+    let [width, height] = [header.pixel_width, header.pixel_height.max(1)];
+    let info = format.describe();
+    let (block_width, block_height) = (
+        info.block_dimensions.0 as u32,
+        info.block_dimensions.1 as u32,
+    );
+    let blocks = [width.div_ceil(block_width), height.div_ceil(block_height)];
+    let bytes = (blocks[0] * blocks[1]) as usize * info.block_size as usize;
+    let level = reader.levels().next().ok_or("The file has no levels")?;
+    // The levels hold all layers and faces, the first one comes first.
+    let level = level.get(..bytes).ok_or("The base level is too short")?;
+
+    let max_side = device.limits().max_texture_dimension_2d;
+    let reason = if !device.features().contains(info.required_features) {
+        Some(format!("the device lacks {:?}", info.required_features))
+    } else if width % block_width != 0 || height % block_height != 0 {
+        // wgpu 0.13 wants compressed textures to be made of whole blocks.
+        Some(format!(
+            "{} × {} pixels aren't whole {} × {} blocks",
+            width, height, block_width, block_height
+        ))
+    } else if width.max(height) > max_side {
+        Some(format!("the texture is larger than {} pixels", max_side))
+    } else {
+        None
+    };
+    if let Some(reason) = reason {
+        let image = decode(level, width as usize, height as usize)?;
+        return Ok(Ktx2Image::Decompressed {
+            image,
+            format,
+            reason,
+        });
+    }
+
+    let size = wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(&path.display().to_string()),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+    });
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        level,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: std::num::NonZeroU32::new(blocks[0] * info.block_size as u32),
+            rows_per_image: None,
+        },
+        size,
+    );
+    Ok(Ktx2Image::Compressed {
+        texture,
+        size: [width, height],
+        format,
+        bytes,
+    })
+}
+
+type Decoder = fn(&[u8], usize, usize) -> Result<image::RgbaImage, String>;
+
+/// The sRGB texture format for a KTX2 format, with its CPU decoder.
+fn texture_format(format: Format) -> Option<(TextureFormat, Decoder)> {
+    macro_rules! bc {
+        ($format:ident, $decode:ident) => {
+            (
+                TextureFormat::$format,
+                (|data, width, height| {
+                    decode(data, width, height, |data, width, height, pixels| {
+                        texture2ddecoder::$decode(data, width, height, pixels)
+                    })
+                }) as Decoder,
+            )
+        };
+    }
+    macro_rules! astc {
+        ($block:ident, $width:literal, $height:literal) => {
+            (
+                TextureFormat::Astc {
+                    block: AstcBlock::$block,
+                    channel: AstcChannel::UnormSrgb,
+                },
+                (|data, width, height| {
+                    decode(data, width, height, |data, width, height, pixels| {
+                        texture2ddecoder::decode_astc(data, width, height, $width, $height, pixels)
+                    })
+                }) as Decoder,
+            )
+        };
+    }
+    Some(match format {
+        Format::BC1_RGB_UNORM_BLOCK | Format::BC1_RGB_SRGB_BLOCK => {
+            bc!(Bc1RgbaUnormSrgb, decode_bc1)
+        }
+        Format::BC1_RGBA_UNORM_BLOCK | Format::BC1_RGBA_SRGB_BLOCK => {
+            bc!(Bc1RgbaUnormSrgb, decode_bc1a)
+        }
+        Format::BC2_UNORM_BLOCK | Format::BC2_SRGB_BLOCK => bc!(Bc2RgbaUnormSrgb, decode_bc2),
+        Format::BC3_UNORM_BLOCK | Format::BC3_SRGB_BLOCK => bc!(Bc3RgbaUnormSrgb, decode_bc3),
+        Format::BC7_UNORM_BLOCK | Format::BC7_SRGB_BLOCK => bc!(Bc7RgbaUnormSrgb, decode_bc7),
+        Format::ASTC_4x4_UNORM_BLOCK | Format::ASTC_4x4_SRGB_BLOCK => astc!(B4x4, 4, 4),
+        Format::ASTC_5x4_UNORM_BLOCK | Format::ASTC_5x4_SRGB_BLOCK => astc!(B5x4, 5, 4),
+        Format::ASTC_5x5_UNORM_BLOCK | Format::ASTC_5x5_SRGB_BLOCK => astc!(B5x5, 5, 5),
+        Format::ASTC_6x5_UNORM_BLOCK | Format::ASTC_6x5_SRGB_BLOCK => astc!(B6x5, 6, 5),
+        Format::ASTC_6x6_UNORM_BLOCK | Format::ASTC_6x6_SRGB_BLOCK => astc!(B6x6, 6, 6),
+        Format::ASTC_8x5_UNORM_BLOCK | Format::ASTC_8x5_SRGB_BLOCK => astc!(B8x5, 8, 5),
+        Format::ASTC_8x6_UNORM_BLOCK | Format::ASTC_8x6_SRGB_BLOCK => astc!(B8x6, 8, 6),
+        Format::ASTC_8x8_UNORM_BLOCK | Format::ASTC_8x8_SRGB_BLOCK => astc!(B8x8, 8, 8),
+        Format::ASTC_10x5_UNORM_BLOCK | Format::ASTC_10x5_SRGB_BLOCK => astc!(B10x5, 10, 5),
+        Format::ASTC_10x6_UNORM_BLOCK | Format::ASTC_10x6_SRGB_BLOCK => astc!(B10x6, 10, 6),
+        Format::ASTC_10x8_UNORM_BLOCK | Format::ASTC_10x8_SRGB_BLOCK => astc!(B10x8, 10, 8),
+        Format::ASTC_10x10_UNORM_BLOCK | Format::ASTC_10x10_SRGB_BLOCK => astc!(B10x10, 10, 10),
+        Format::ASTC_12x10_UNORM_BLOCK | Format::ASTC_12x10_SRGB_BLOCK => astc!(B12x10, 12, 10),
+        Format::ASTC_12x12_UNORM_BLOCK | Format::ASTC_12x12_SRGB_BLOCK => astc!(B12x12, 12, 12),
+        _ => return None,
+    })
+}
+
+/// Runs a decoder of texture2ddecoder, which writes BGRA pixels as `u32`, and converts its
+/// output to RGBA.
+fn decode(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    decoder: impl Fn(&[u8], usize, usize, &mut [u32]) -> Result<(), &'static str>,
+) -> Result<image::RgbaImage, String> {
+    let mut pixels = vec![0; width * height];
+    decoder(data, width, height, &mut pixels)?;
+    let rgba = pixels
+        .iter()
+        .flat_map(|pixel| {
+            let [b, g, r, a] = pixel.to_le_bytes();
+            [r, g, b, a]
+        })
+        .collect();
+    image::RgbaImage::from_raw(width as u32, height as u32, rgba)
+        .ok_or_else(|| "The decoded image has the wrong size".to_owned())
+}
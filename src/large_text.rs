@@ -0,0 +1,415 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Instant;
+
+use egui::epaint::text::Galley;
+
+/// How many frames the layout times and uploads are averaged over.
+const RECENT_FRAMES: usize = 60;
+
+/// The words of the generated document. The scripts other than Latin make egui rasterize more
+/// glyphs, so that the atlas has to grow.
+const WORDS: &[&str] = &[
+    "the",
+    "quick",
+    "brown",
+    "fox",
+    "jumps",
+    "over",
+    "lazy",
+    "dog",
+    "render",
+    "glyph",
+    "atlas",
+    "texture",
+    "upload",
+    "galley",
+    "layout",
+    "frame",
+    "λόγος",
+    "κόσμος",
+    "φως",
+    "слово",
+    "текст",
+    "шрифт",
+    "Größe",
+    "façade",
+    "naïve",
+    "smörgåsbord",
+    "0x1F",
+    "3.14159",
+    "#42",
+    "[ok]",
+];
+
+/// How the visible lines are laid out every frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Strategy {
+    /// Every line is laid out again, which egui's galley cache answers while the line stays in
+    /// view.
+    EguiCache,
+    /// The galleys of the lines in view are kept from frame to frame, egui isn't asked again.
+    Retained,
+    /// All visible lines form one galley, which egui has to lay out again whenever the view
+    /// scrolls by a line.
+    Block,
+}
+
+impl Strategy {
+    const ALL: [Strategy; 3] = [Strategy::EguiCache, Strategy::Retained, Strategy::Block];
+
+    fn name(self) -> &'static str {
+        match self {
+            Strategy::EguiCache => "egui's cache",
+            Strategy::Retained => "Retained galleys",
+            Strategy::Block => "One block",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Strategy::EguiCache => "Lays out every visible line each frame, egui's cache hits",
+            Strategy::Retained => {
+                "Keeps the galleys of the visible lines, dropped when the atlas is recreated"
+            }
+            Strategy::Block => "Lays out the visible lines as one text, a miss on every scroll",
+        }
+    }
+}
+
+/// A generated document and where its lines start.
+struct Document {
+    text: String,
+    /// The byte ranges of the lines, without the line breaks.
+    lines: Vec<std::ops::Range<usize>>,
+}
+
+impl Document {
+    /// About `bytes` of text in lines of up to 100 characters, the same for every `seed`.
+    fn generate(bytes: usize, seed: u64) -> Self {
+        let mut state = seed.max(1);
+        let mut next = move || {
+            // xorshift64, good enough to mix the words.
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let mut text = String::with_capacity(bytes + 128);
+        let mut lines = Vec::new();
+        while text.len() < bytes {
+            let start = text.len();
+            text.push_str(&format!("{:>7}  ", lines.len() + 1));
+            let words = 4 + next() % 12;
+            for index in 0..words {
+                if index > 0 {
+                    text.push(' ');
+                }
+                text.push_str(WORDS[(next() % WORDS.len() as u64) as usize]);
+            }
+            lines.push(start..text.len());
+            text.push('\n');
+        }
+        Self { text, lines }
+    }
+
+    fn line(&self, index: usize) -> &str {
+        &self.text[self.lines[index].clone()]
+    }
+}
+
+/// What the last frames laid out, drew and uploaded.
+#[derive(Clone, Copy, Default)]
+struct FrameStats {
+    lines: usize,
+    glyphs: usize,
+    /// The galleys laid out by the strategy, as opposed to taken from its own cache.
+    layouts: usize,
+    layout_ms: f32,
+    /// The bytes of the font texture uploaded in the frame.
+    upload_bytes: usize,
+}
+
+/// Renders a multi-megabyte document in a scroll area that only lays out the visible lines, and
+/// shows how the glyph atlas of egui and its uploads behave meanwhile.
+///
+/// egui rasterizes glyphs into one atlas texture on first use, per font size and scale factor.
+/// The atlas grows in height when it's full, which uploads it as a whole, and is recreated from
+/// scratch once it's 80 % full. Galleys keep the texel positions of their glyphs in the atlas,
+/// which survive growing, but kept galleys are stale after it was recreated.
+pub struct LargeText {
+    document: Option<Document>,
+    megabytes: usize,
+    seed: u64,
+    font_size: f32,
+    strategy: Strategy,
+    /// The galleys of `Retained`, by line, of the lines in view in the last frame.
+    retained: HashMap<usize, Arc<Galley>>,
+    /// The font and the scale the retained galleys were laid out with.
+    retained_for: Option<(egui::FontId, f32)>,
+    atlas_size: [usize; 2],
+    atlas_fill: f32,
+    atlas_growths: usize,
+    atlas_recreations: usize,
+    full_uploads: usize,
+    uploaded_bytes: usize,
+    frames: VecDeque<FrameStats>,
+    /// The stats of the frame being drawn, until its output comes in.
+    current: FrameStats,
+}
+
+impl Default for LargeText {
+    fn default() -> Self {
+        Self {
+            document: None,
+            megabytes: 4,
+            seed: 1,
+            font_size: 14.0,
+            strategy: Strategy::EguiCache,
+            retained: HashMap::new(),
+            retained_for: None,
+            atlas_size: [0, 0],
+            atlas_fill: 0.0,
+            atlas_growths: 0,
+            atlas_recreations: 0,
+            full_uploads: 0,
+            uploaded_bytes: 0,
+            frames: VecDeque::new(),
+            current: FrameStats::default(),
+        }
+    }
+}
+
+impl LargeText {
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        egui::Window::new("📚 Large text")
+            .open(open)
+            .default_size(egui::vec2(640.0, 480.0))
+            .show(ctx, |ui| self.ui(ui));
+    }
+
+    /// Takes note of the font texture in the output of the frame, while the window is open.
+    pub fn observe(&mut self, ctx: &egui::Context, output: &egui::FullOutput) {
+        let fonts = ctx.fonts();
+        let size = fonts.font_image_size();
+        let fill = fonts.font_atlas_fill_ratio();
+        drop(fonts);
+        if size[1] > self.atlas_size[1] && self.atlas_size[1] > 0 {
+            self.atlas_growths += 1;
+        }
+        self.atlas_size = size;
+        self.atlas_fill = fill;
+
+        let mut stats = std::mem::take(&mut self.current);
+        for (id, delta) in &output.textures_delta.set {
+            if *id != egui::TextureId::default() {
+                continue;
+            }
+            let [width, height] = delta.image.size();
+            // The backend uploads the coverage of the glyphs as RGBA.
+            stats.upload_bytes += width * height * 4;
+            if delta.pos.is_none() {
+                self.full_uploads += 1;
+            }
+        }
+        self.uploaded_bytes += stats.upload_bytes;
+        self.frames.push_back(stats);
+        while self.frames.len() > RECENT_FRAMES {
+            self.frames.pop_front();
+        }
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        // egui recreates the atlas at the start of a frame, emptier than it was at the end of
+        // the last one.
+        let fill = ui.fonts().font_atlas_fill_ratio();
+        if fill < self.atlas_fill {
+            self.atlas_recreations += 1;
+            self.atlas_size = ui.fonts().font_image_size();
+            self.retained.clear();
+        }
+        self.atlas_fill = fill;
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("large_text_size")
+                .selected_text(format!("{} MB", self.megabytes))
+                .show_ui(ui, |ui| {
+                    for megabytes in [1, 4, 16, 64] {
+                        ui.selectable_value(
+                            &mut self.megabytes,
+                            megabytes,
+                            format!("{} MB", megabytes),
+                        );
+                    }
+                });
+            if ui.button("⟳ Generate").clicked() || self.document.is_none() {
+                self.seed += 1;
+                self.document = Some(Document::generate(self.megabytes << 20, self.seed));
+                self.retained.clear();
+            }
+            ui.add(egui::Slider::new(&mut self.font_size, 6.0..=64.0).text("Font size"))
+                .on_hover_text("New sizes rasterize their glyphs into the atlas");
+        });
+        ui.horizontal(|ui| {
+            for strategy in Strategy::ALL {
+                ui.selectable_value(&mut self.strategy, strategy, strategy.name())
+                    .on_hover_text(strategy.description());
+            }
+        });
+        self.stats_ui(ui);
+        ui.separator();
+        self.document_ui(ui);
+        if self.strategy != Strategy::Retained {
+            self.retained.clear();
+        }
+    }
+
+    fn stats_ui(&self, ui: &mut egui::Ui) {
+        let frames = self.frames.len().max(1) as f32;
+        let average =
+            |stat: fn(&FrameStats) -> f32| self.frames.iter().map(stat).sum::<f32>() / frames;
+        let last = self.frames.back().copied().unwrap_or_default();
+        egui::Grid::new("large_text_stats")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Drawn");
+                ui.monospace(format!("{} lines, {} glyphs", last.lines, last.glyphs));
+                ui.end_row();
+                ui.label("Layout");
+                ui.monospace(format!(
+                    "{:.2} ms, {:.1} galleys laid out per frame",
+                    average(|frame| frame.layout_ms),
+                    average(|frame| frame.layouts as f32)
+                ));
+                ui.end_row();
+                ui.label("Galleys in egui's cache");
+                let cached = ui.fonts().num_galleys_in_cache();
+                ui.monospace(cached.to_string());
+                ui.end_row();
+                ui.label("Atlas");
+                ui.monospace(format!(
+                    "{} × {}, {:.0} % full",
+                    self.atlas_size[0],
+                    self.atlas_size[1],
+                    self.atlas_fill * 100.0
+                ));
+                ui.end_row();
+                ui.label("Atlas events");
+                ui.monospace(format!(
+                    "grew {} times, recreated {} times",
+                    self.atlas_growths, self.atlas_recreations
+                ));
+                ui.end_row();
+                ui.label("Font uploads");
+                ui.monospace(format!(
+                    "{:.1} KiB per frame, {} whole, {:.1} MiB in total",
+                    average(|frame| frame.upload_bytes as f32) / 1024.0,
+                    self.full_uploads,
+                    self.uploaded_bytes as f64 / (1024.0 * 1024.0)
+                ));
+                ui.end_row();
+            });
+    }
+
+    fn document_ui(&mut self, ui: &mut egui::Ui) {
+        let document = match &self.document {
+            Some(document) => document,
+            None => return,
+        };
+        let font_id = egui::FontId::monospace(self.font_size);
+        let color = ui.visuals().text_color();
+        let row_height = ui.fonts().row_height(&font_id);
+        let key = (font_id.clone(), ui.ctx().pixels_per_point());
+        if self.retained_for.as_ref() != Some(&key) {
+            self.retained.clear();
+            self.retained_for = Some(key);
+        }
+        let strategy = self.strategy;
+        let retained = &mut self.retained;
+        let mut stats = FrameStats::default();
+        egui::ScrollArea::both().auto_shrink([false; 2]).show_rows(
+            ui,
+            row_height,
+            document.lines.len(),
+            |ui, rows| {
+                let start = Instant::now();
+                stats.lines = rows.len();
+                let fonts = ui.fonts();
+                let galleys: Vec<Arc<Galley>> = match strategy {
+                    Strategy::EguiCache => {
+                        stats.layouts = rows.len();
+                        rows.map(|row| {
+                            fonts.layout_no_wrap(
+                                document.line(row).to_owned(),
+                                font_id.clone(),
+                                color,
+                            )
+                        })
+                        .collect()
+                    }
+                    Strategy::Retained => {
+                        let mut last = std::mem::take(retained);
+                        rows.map(|row| {
+                            let galley = last.remove(&row).unwrap_or_else(|| {
+                                stats.layouts += 1;
+                                fonts.layout_no_wrap(
+                                    document.line(row).to_owned(),
+                                    font_id.clone(),
+                                    color,
+                                )
+                            });
+                            retained.insert(row, galley.clone());
+                            galley
+                        })
+                        .collect()
+                    }
+                    Strategy::Block => {
+                        stats.layouts = 1;
+                        let text = match rows.is_empty() {
+                            true => String::new(),
+                            false => {
+                                let start = document.lines[rows.start].start;
+                                document.text[start..document.lines[rows.end - 1].end].to_owned()
+                            }
+                        };
+                        vec![fonts.layout_no_wrap(text, font_id.clone(), color)]
+                    }
+                };
+                drop(fonts);
+                stats.layout_ms = start.elapsed().as_secs_f32() * 1000.0;
+                for galley in galleys {
+                    stats.glyphs += galley
+                        .rows
+                        .iter()
+                        .map(|row| row.glyphs.len())
+                        .sum::<usize>();
+                    let (rect, _) = ui.allocate_exact_size(galley.size(), egui::Sense::hover());
+                    ui.painter().galley(rect.min, galley);
+                }
+            },
+        );
+        self.current = stats;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_lines_cover_the_document() {
+        let document = Document::generate(64 * 1024, 7);
+        assert!(document.text.len() >= 64 * 1024);
+        let mut end = 0;
+        for (index, line) in document.lines.iter().enumerate() {
+            assert_eq!(line.start, end);
+            assert!(document
+                .line(index)
+                .trim_start()
+                .starts_with(&(index + 1).to_string()));
+            end = line.end + 1;
+        }
+        assert_eq!(end, document.text.len());
+        assert_eq!(document.text, Document::generate(64 * 1024, 7).text);
+    }
+}
@@ -0,0 +1,203 @@
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// One click, from the press to the frame that showed the flash.
+#[derive(Clone, Copy)]
+struct Sample {
+    present_mode: wgpu::PresentMode,
+    /// Until `present` returned, in milliseconds.
+    present_ms: Option<f32>,
+    /// Until wgpu reported the frame as done on the GPU.
+    done_ms: Option<f32>,
+}
+
+/// The flash that's drawn in the current frame.
+struct Flash {
+    input: Instant,
+    /// The index of its sample, once the frame was submitted.
+    sample: Option<usize>,
+}
+
+/// Flashes a quad on click and measures how long it takes from the press to the presented
+/// frame, for each present mode.
+///
+/// wgpu 0.13 doesn't report when a frame reaches the display. The measurement ends when
+/// `present` returns and when the GPU finished the frame; the display scans it out at the next
+/// vertical blank after that, which adds up to one refresh interval with Fifo.
+pub struct LatencyTester {
+    present_modes: Vec<wgpu::PresentMode>,
+    present_mode: wgpu::PresentMode,
+    /// When the last press arrived from winit, before egui saw it.
+    last_press: Option<Instant>,
+    flash: Option<Flash>,
+    /// Shared with the completion callbacks of the GPU.
+    samples: Arc<Mutex<Vec<Sample>>>,
+}
+
+impl LatencyTester {
+    /// `present_modes` are the ones the surface supports.
+    pub fn new(present_modes: Vec<wgpu::PresentMode>) -> Self {
+        Self {
+            present_modes,
+            present_mode: wgpu::PresentMode::Fifo,
+            last_press: None,
+            flash: None,
+            samples: Default::default(),
+        }
+    }
+
+    /// The present mode to configure the surface with.
+    pub fn present_mode(&self) -> wgpu::PresentMode {
+        self.present_mode
+    }
+
+    /// Switches to `mode`, or to Fifo, which every surface supports, if this one doesn't.
+    pub fn set_present_mode(&mut self, mode: wgpu::PresentMode) {
+        self.present_mode = match self.present_modes.contains(&mode) {
+            true => mode,
+            false => wgpu::PresentMode::Fifo,
+        };
+    }
+
+    /// Called when winit delivers a mouse button press, which is the start of a measurement.
+    pub fn pointer_pressed(&mut self) {
+        self.last_press = Some(Instant::now());
+    }
+
+    /// The frame with the flash was submitted to `queue`.
+    pub fn submitted(&mut self, queue: &wgpu::Queue) {
+        let flash = match &mut self.flash {
+            Some(flash) if flash.sample.is_none() => flash,
+            _ => return,
+        };
+        let index = {
+            let mut samples = self.samples.lock().unwrap();
+            samples.push(Sample {
+                present_mode: self.present_mode,
+                present_ms: None,
+                done_ms: None,
+            });
+            samples.len() - 1
+        };
+        flash.sample = Some(index);
+        let input = flash.input;
+        let samples = self.samples.clone();
+        queue.on_submitted_work_done(move || {
+            // The samples might have been cleared meanwhile.
+            if let Some(sample) = samples.lock().unwrap().get_mut(index) {
+                sample.done_ms = Some(input.elapsed().as_secs_f32() * 1000.0);
+            }
+        });
+    }
+
+    /// The frame with the flash was presented.
+    pub fn presented(&mut self) {
+        let (input, index) = match &self.flash {
+            Some(Flash {
+                input,
+                sample: Some(index),
+            }) => (*input, *index),
+            _ => return,
+        };
+        self.flash = None;
+        if let Some(sample) = self.samples.lock().unwrap().get_mut(index) {
+            sample.present_ms = Some(input.elapsed().as_secs_f32() * 1000.0);
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        egui::Window::new("⏲ Latency tester")
+            .open(open)
+            .default_size([420.0, 420.0])
+            .show(ctx, |ui| self.ui(ui));
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Present mode:");
+            for mode in self.present_modes.clone() {
+                ui.selectable_value(&mut self.present_mode, mode, format!("{:?}", mode));
+            }
+        });
+
+        let (rect, response) =
+            ui.allocate_exact_size(egui::vec2(ui.available_width(), 80.0), egui::Sense::click());
+        let pressed = response.hovered() && ui.input().pointer.any_pressed();
+        if let (true, Some(input)) = (pressed, self.last_press.take()) {
+            // A flash that never got presented, e.g. because the surface was outdated, is lost.
+            self.flash = Some(Flash {
+                input,
+                sample: None,
+            });
+            // The next frame takes the flash away again.
+            ui.ctx().request_repaint();
+        }
+        let flashing = self.flash.is_some();
+        let (fill, text) = if flashing {
+            (egui::Color32::WHITE, egui::Color32::BLACK)
+        } else {
+            (ui.visuals().extreme_bg_color, ui.visuals().text_color())
+        };
+        ui.painter().rect_filled(rect, 4.0, fill);
+        ui.painter().text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "Click here",
+            egui::FontId::proportional(18.0),
+            text,
+        );
+
+        let samples = self.samples.lock().unwrap().clone();
+        ui.horizontal(|ui| {
+            ui.label(format!("{} samples", samples.len()));
+            if ui.button("Clear").clicked() {
+                self.samples.lock().unwrap().clear();
+            }
+        });
+        egui::Grid::new("latency_stats")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Present mode");
+                ui.strong("Press → present");
+                ui.strong("Press → GPU done");
+                ui.end_row();
+                for mode in &self.present_modes {
+                    let of_mode = samples.iter().filter(|sample| sample.present_mode == *mode);
+                    let present: Vec<f32> = of_mode.clone().filter_map(|s| s.present_ms).collect();
+                    let done: Vec<f32> = of_mode.filter_map(|s| s.done_ms).collect();
+                    ui.label(format!("{:?}", mode));
+                    ui.monospace(summary(&present));
+                    ui.monospace(summary(&done));
+                    ui.end_row();
+                }
+            });
+
+        egui::plot::Plot::new("latency_plot")
+            .legend(egui::plot::Legend::default())
+            .include_y(0.0)
+            .allow_scroll(false)
+            .show(ui, |plot_ui| {
+                for mode in &self.present_modes {
+                    let points: egui::plot::PlotPoints = samples
+                        .iter()
+                        .filter(|sample| sample.present_mode == *mode)
+                        .filter_map(|sample| sample.present_ms)
+                        .enumerate()
+                        .map(|(index, ms)| [index as f64, ms as f64])
+                        .collect();
+                    plot_ui.line(egui::plot::Line::new(points).name(format!("{:?}", mode)));
+                }
+            });
+    }
+}
+
+/// The mean and the worst of `values`, in milliseconds.
+fn summary(values: &[f32]) -> String {
+    if values.is_empty() {
+        return "-".to_owned();
+    }
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    let worst = values.iter().copied().fold(0.0, f32::max);
+    format!("{:6.2} ms, worst {:6.2}", mean, worst)
+}
@@ -0,0 +1,262 @@
+//! A simple egui + wgpu + winit based example.
+//!
+//! The code lives in a library, so that it can be built as the `cdylib` that Android loads. On
+//! desktop platforms `main.rs` just calls [`run`].
+
+use chrono::Timelike;
+use epi::backend::RepaintSignal;
+
+mod app;
+mod assets;
+mod attract_screen;
+#[cfg(feature = "audio")]
+mod audio;
+mod clock;
+mod color_blindness;
+mod color_picker;
+mod config;
+mod crash_report;
+mod csv_file;
+mod csv_viewer;
+mod damage;
+mod data_table;
+mod debug_tools;
+mod display_mode;
+mod ecs_scene;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+mod embedded;
+mod emoji;
+mod file_browser;
+mod focus_debug;
+mod font_atlas;
+#[cfg(feature = "renderdoc")]
+mod frame_capture;
+mod frame_debugger;
+mod frame_latency;
+mod frame_scheduler;
+mod frame_stats;
+mod fuzz;
+mod gif_encoder;
+mod gizmo;
+mod global_hotkey;
+mod gpu_report;
+mod gpu_watchdog;
+#[cfg(feature = "hdr")]
+mod hdr_image;
+mod hex_view;
+mod hex_viewer;
+mod hitch_reports;
+mod hud;
+mod idle;
+mod image_canvas;
+mod image_stats;
+mod image_viewer;
+mod input_batch;
+mod input_recording;
+mod interaction_recorder;
+mod json_inspector;
+mod json_tree;
+mod kiosk;
+#[cfg(feature = "ktx2")]
+mod ktx2_loader;
+mod large_text;
+mod latency_tester;
+mod log_tail;
+#[cfg(target_os = "macos")]
+mod macos_title_bar;
+#[cfg(feature = "midi")]
+mod midi;
+#[cfg(feature = "mqtt")]
+mod mqtt_dashboard;
+mod on_screen_keyboard;
+mod onboarding;
+#[cfg(target_os = "macos")]
+mod open_with;
+mod options;
+mod paths;
+#[cfg(feature = "pdf")]
+mod pdf_export;
+#[cfg(feature = "physics")]
+mod physics_sandbox;
+mod presentation;
+mod profiles;
+mod readback;
+mod render_graph;
+mod runner;
+mod scene_graph;
+mod scene_renderer;
+mod screenshot;
+mod shader_reflection;
+mod shortcuts;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+mod single_instance;
+#[cfg(feature = "tts")]
+mod speech;
+mod sprite_batch;
+#[cfg(feature = "sqlite")]
+mod sqlite_browser;
+mod startup_report;
+mod stress_test;
+mod style_editor;
+mod svg_export;
+mod system_monitor;
+#[cfg(feature = "terminal")]
+mod terminal;
+mod tessellator;
+mod thumbnails;
+mod tile_editor;
+#[cfg(target_os = "windows")]
+mod title_bar;
+mod ui_driver;
+mod ui_replay;
+mod user_textures;
+mod workspaces;
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+pub use embedded::{ControlFlowDecision, Embedded};
+pub use runner::{ExternalGpu, RenderTarget};
+
+const INITIAL_WIDTH: u32 = 1920;
+const INITIAL_HEIGHT: u32 = 1080;
+
+/// A custom event type for the winit app.
+enum Event {
+    RequestRedraw,
+    /// Files to open, from another instance, the Finder or the recent files. Brings the window
+    /// to the front.
+    Activate(Vec<std::path::PathBuf>),
+    /// The global hotkey was pressed: hides the window if it's in front, brings it there
+    /// otherwise.
+    ToggleVisibility,
+    /// A MIDI controller moved.
+    #[cfg(feature = "midi")]
+    Midi(midi::ControlChange),
+    /// The GPU watchdog gave up on the GPU and is about to exit.
+    GpuHung,
+}
+
+/// This is the repaint signal type that egui needs for requesting a repaint from another thread.
+/// It sends the custom RequestRedraw event to the winit event loop.
+struct ExampleRepaintSignal(std::sync::Mutex<winit::event_loop::EventLoopProxy<Event>>);
+
+impl epi::backend::RepaintSignal for ExampleRepaintSignal {
+    fn request_repaint(&self) {
+        self.0.lock().unwrap().send_event(Event::RequestRedraw).ok();
+    }
+}
+
+/// Creates the window and runs the example until the window is closed.
+///
+/// If the example is already running, its window comes to the front instead and opens the files
+/// passed on the command line. `--new-instance` and the other options start another one
+/// anyway.
+pub fn run() {
+    let options = options::Options::from_args();
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    if options.hands_off() && single_instance::hand_off(&options.files) {
+        return;
+    }
+    let single_instance = options.receives_hand_offs();
+    run_with(options, None, single_instance);
+}
+
+/// Like [`run`], but renders with the GPU objects of a host application instead of creating its
+/// own. `gpu.format` has to be one of the sRGB or linear 8-bit BGRA or RGBA formats.
+pub fn run_with_device(gpu: ExternalGpu) {
+    run_with(options::Options::from_args(), Some(gpu), false);
+}
+
+/// `single_instance` makes later instances hand off to this one, see [`run`] and
+/// `Options::receives_hand_offs`.
+#[cfg_attr(any(target_os = "android", target_os = "ios"), allow(unused_variables))]
+fn run_with(options: options::Options, gpu: Option<ExternalGpu>, single_instance: bool) {
+    paths::init(options.config_dir.clone());
+    crash_report::install();
+    let event_loop = create_event_loop(&options);
+    let mut runner = create_runner(&event_loop, options, gpu);
+    #[cfg(target_os = "macos")]
+    open_with::install(event_loop.create_proxy());
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    if single_instance {
+        single_instance::listen(event_loop.create_proxy());
+    }
+    event_loop
+        .run(move |event, target, control_flow| runner.handle_event(event, target, control_flow));
+}
+
+/// Creates the event loop on the window system of `options`, if there is a choice.
+#[cfg_attr(
+    not(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )),
+    allow(unused_variables)
+)]
+fn create_event_loop(options: &options::Options) -> winit::event_loop::EventLoop<Event> {
+    let mut builder = winit::event_loop::EventLoopBuilder::<Event>::with_user_event();
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    {
+        use winit::platform::unix::EventLoopBuilderExtUnix;
+        match options.window_system {
+            Some(options::WindowSystem::X11) => builder.with_x11(),
+            Some(options::WindowSystem::Wayland) => builder.with_wayland(),
+            None => &mut builder,
+        };
+    }
+    builder.build()
+}
+
+/// Creates the window and the runner that draws into it.
+fn create_runner(
+    event_loop: &winit::event_loop::EventLoop<Event>,
+    options: options::Options,
+    gpu: Option<ExternalGpu>,
+) -> runner::Runner {
+    let window = winit::window::WindowBuilder::new()
+        .with_decorations(true)
+        .with_resizable(true)
+        .with_transparent(false)
+        .with_title("egui-wgpu_winit example")
+        .with_inner_size(winit::dpi::PhysicalSize {
+            width: INITIAL_WIDTH,
+            height: INITIAL_HEIGHT,
+        })
+        .build(event_loop)
+        .unwrap();
+
+    let proxy = event_loop.create_proxy();
+    let runner = match gpu {
+        Some(gpu) => runner::Runner::with_device(window, proxy, options, gpu),
+        None => runner::Runner::new(window, proxy, options),
+    };
+
+    // Background threads (like the audio analysis) request repaints through egui. Forward those
+    // requests to the event loop, which might be sleeping at that moment.
+    let repaint_signal = ExampleRepaintSignal(std::sync::Mutex::new(event_loop.create_proxy()));
+    runner
+        .context()
+        .set_request_repaint_callback(move || repaint_signal.request_repaint());
+    runner
+}
+
+/// The entry point on Android, called by `ndk-glue` once the activity got created.
+#[cfg(target_os = "android")]
+#[ndk_glue::main(backtrace = "on")]
+fn android_main() {
+    run();
+}
+
+/// Time of day as seconds since midnight. Used for clock in demo app.
+pub fn seconds_since_midnight() -> f64 {
+    let time = chrono::Local::now().time();
+    time.num_seconds_from_midnight() as f64 + 1e-9 * (time.nanosecond() as f64)
+}
@@ -0,0 +1,558 @@
+use std::collections::VecDeque;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use notify::Watcher;
+use regex::Regex;
+
+use crate::workspaces::SaveState;
+
+/// How much of the end of a file is read when it's opened.
+const TAIL_BYTES: u64 = 8 * 1024 * 1024;
+/// How much is read at once.
+const CHUNK_BYTES: usize = 1024 * 1024;
+/// How many lines are kept. Older ones are dropped.
+const MAX_LINES: usize = 500_000;
+/// How often the file is checked for new lines when no change was reported.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Follows a log file like `tail -f`. A thread reads what gets appended to the file whenever
+/// `notify` reports a change, so the file can be of any size: only its end is read when it's
+/// opened. Lines can be filtered by level and by a regular expression, and the matches of the
+/// highlighting rules are marked.
+///
+/// Files are opened by entering their path or by dropping them onto the window.
+pub struct LogTail {
+    path: String,
+    follower: Option<Follower>,
+    error: Option<String>,
+    /// The last lines of the file.
+    lines: VecDeque<Line>,
+    /// The number of the first line in `lines`, counted from where reading started.
+    first: usize,
+    /// Where reading started, in bytes. Non-zero if the beginning of the file was skipped.
+    skipped: u64,
+    /// The numbers of the lines that pass the filters.
+    matches: VecDeque<usize>,
+    min_level: Level,
+    filter: String,
+    filter_regex: Option<Regex>,
+    filter_error: Option<String>,
+    rules: Vec<Rule>,
+    /// Lines aren't added while paused, they wait in `pending`.
+    paused: bool,
+    pending: Vec<String>,
+    /// Whether the view sticks to the newest line.
+    follow: bool,
+    /// The file of a restored workspace, opened once the window is.
+    reopen: Option<PathBuf>,
+}
+
+struct Follower {
+    messages: mpsc::Receiver<Message>,
+    running: Arc<AtomicBool>,
+}
+
+impl Drop for Follower {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+enum Message {
+    /// Reading starts at this byte offset.
+    Start(u64),
+    Lines(Vec<String>),
+    /// The file got shorter, so it was truncated or replaced. Reading starts over.
+    Truncated,
+    Error(String),
+}
+
+struct Line {
+    text: String,
+    level: Level,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Level {
+    Trace,
+    Debug,
+    /// Lines without a level are shown with the info ones.
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    const ALL: [Level; 5] = [
+        Level::Trace,
+        Level::Debug,
+        Level::Info,
+        Level::Warn,
+        Level::Error,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Level::Trace => "TRACE",
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+        }
+    }
+
+    /// The first level name in `line`, upper case as most loggers write it.
+    fn of(line: &str) -> Level {
+        let mut found = None;
+        for level in Level::ALL {
+            let name = match level {
+                // "WARNING" starts with it, too.
+                Level::Warn => "WARN",
+                _ => level.name(),
+            };
+            if let Some(position) = line.find(name) {
+                if found.is_none_or(|(first, _)| position < first) {
+                    found = Some((position, level));
+                }
+            }
+        }
+        found.map_or(Level::Info, |(_, level)| level)
+    }
+
+    fn color(self, visuals: &egui::Visuals) -> egui::Color32 {
+        match self {
+            Level::Trace | Level::Debug => visuals.weak_text_color(),
+            Level::Info => visuals.text_color(),
+            Level::Warn => visuals.warn_fg_color,
+            Level::Error => visuals.error_fg_color,
+        }
+    }
+}
+
+/// Marks the matches of `pattern` with `color`.
+struct Rule {
+    pattern: String,
+    regex: Option<Regex>,
+    color: egui::Color32,
+}
+
+impl Rule {
+    fn new(pattern: &str, color: egui::Color32) -> Self {
+        Self {
+            pattern: pattern.to_owned(),
+            regex: Regex::new(pattern).ok(),
+            color,
+        }
+    }
+}
+
+impl Default for LogTail {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            follower: None,
+            error: None,
+            lines: VecDeque::new(),
+            first: 0,
+            skipped: 0,
+            matches: VecDeque::new(),
+            min_level: Level::Trace,
+            filter: String::new(),
+            filter_regex: None,
+            filter_error: None,
+            rules: vec![
+                Rule::new(r"\d+\.\d+\.\d+\.\d+", egui::Color32::from_rgb(40, 80, 120)),
+                Rule::new(
+                    r"\b\d+(\.\d+)?\s?ms\b",
+                    egui::Color32::from_rgb(90, 60, 110),
+                ),
+            ],
+            paused: false,
+            pending: Vec::new(),
+            follow: true,
+            reopen: None,
+        }
+    }
+}
+
+impl LogTail {
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if *open {
+            let dropped = self.reopen.take().or_else(|| {
+                ctx.input().raw.dropped_files.iter().find_map(|file| {
+                    file.path.clone().filter(|path| {
+                        path.extension().is_some_and(|extension| {
+                            extension.eq_ignore_ascii_case("log")
+                                || extension.eq_ignore_ascii_case("txt")
+                        })
+                    })
+                })
+            });
+            if let Some(path) = dropped {
+                self.path = path.display().to_string();
+                self.follow_file(ctx, path);
+            }
+        }
+        self.poll();
+
+        egui::Window::new("📜 Log tail")
+            .open(open)
+            .default_size([720.0, 480.0])
+            .show(ctx, |ui| self.ui(ui));
+    }
+
+    fn follow_file(&mut self, ctx: &egui::Context, path: PathBuf) {
+        let (sender, messages) = mpsc::channel();
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            if let Err(err) = follow(&path, &sender, &thread_running, &ctx) {
+                let _ = sender.send(Message::Error(err));
+                ctx.request_repaint();
+            }
+        });
+        self.follower = Some(Follower { messages, running });
+        self.error = None;
+        self.clear();
+    }
+
+    fn clear(&mut self) {
+        self.first += self.lines.len();
+        self.lines.clear();
+        self.matches.clear();
+        self.pending.clear();
+    }
+
+    fn poll(&mut self) {
+        let follower = match &self.follower {
+            Some(follower) => follower,
+            None => return,
+        };
+        let mut lines = Vec::new();
+        let mut truncated = false;
+        while let Ok(message) = follower.messages.try_recv() {
+            match message {
+                Message::Start(offset) => self.skipped = offset,
+                Message::Lines(batch) => lines.extend(batch),
+                Message::Truncated => {
+                    lines.clear();
+                    truncated = true;
+                }
+                Message::Error(err) => self.error = Some(err),
+            }
+        }
+        if truncated {
+            self.skipped = 0;
+            self.clear();
+        }
+        if self.paused {
+            self.pending.extend(lines);
+        } else {
+            self.append(lines);
+        }
+    }
+
+    fn append(&mut self, lines: Vec<String>) {
+        for text in lines {
+            let number = self.first + self.lines.len();
+            let line = Line {
+                level: Level::of(&text),
+                text,
+            };
+            if self.passes(&line) {
+                self.matches.push_back(number);
+            }
+            self.lines.push_back(line);
+        }
+        while self.lines.len() > MAX_LINES {
+            self.lines.pop_front();
+            self.first += 1;
+        }
+        while self
+            .matches
+            .front()
+            .is_some_and(|number| *number < self.first)
+        {
+            self.matches.pop_front();
+        }
+    }
+
+    fn passes(&self, line: &Line) -> bool {
+        line.level >= self.min_level
+            && self
+                .filter_regex
+                .as_ref()
+                .is_none_or(|regex| regex.is_match(&line.text))
+    }
+
+    fn refilter(&mut self) {
+        self.filter_regex = None;
+        self.filter_error = None;
+        if !self.filter.is_empty() {
+            match Regex::new(&self.filter) {
+                Ok(regex) => self.filter_regex = Some(regex),
+                Err(err) => self.filter_error = Some(err.to_string()),
+            }
+        }
+        self.matches = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| self.passes(line))
+            .map(|(index, _)| self.first + index)
+            .collect();
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let response = ui
+                .add(egui::TextEdit::singleline(&mut self.path).hint_text("Path, or drop a file"));
+            let entered = response.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+            if ui.button("Follow").clicked() || entered {
+                self.follow_file(ui.ctx(), PathBuf::from(&self.path));
+            }
+            ui.separator();
+            let pause = match self.pending.len() {
+                0 => "⏸ Pause".to_owned(),
+                pending => format!("⏸ Pause ({} new)", pending),
+            };
+            if ui.toggle_value(&mut self.paused, pause).changed() && !self.paused {
+                let pending = std::mem::take(&mut self.pending);
+                self.append(pending);
+            }
+            ui.toggle_value(&mut self.follow, "⬇ Scroll lock")
+                .on_hover_text("Stick to the newest line");
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        let mut refilter = false;
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("log_tail_level")
+                .selected_text(format!("{} and above", self.min_level.name()))
+                .show_ui(ui, |ui| {
+                    for level in Level::ALL {
+                        refilter |= ui
+                            .selectable_value(&mut self.min_level, level, level.name())
+                            .changed();
+                    }
+                });
+            refilter |= ui
+                .add(egui::TextEdit::singleline(&mut self.filter).hint_text("Filter regex"))
+                .changed();
+            ui.label(format!(
+                "{} of {} lines",
+                self.matches.len(),
+                self.lines.len()
+            ));
+            if self.skipped > 0 {
+                ui.weak(format!(
+                    "(skipped the first {} MiB)",
+                    self.skipped / (1024 * 1024)
+                ))
+                .on_hover_text("Only the end of large files is read");
+            }
+        });
+        if refilter {
+            self.refilter();
+        }
+        if let Some(error) = &self.filter_error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        egui::CollapsingHeader::new("Highlighting")
+            .id_source("log_tail_rules")
+            .show(ui, |ui| self.rules_ui(ui));
+        ui.separator();
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::both()
+            .auto_shrink([false; 2])
+            .stick_to_bottom(self.follow)
+            .show_rows(ui, row_height, self.matches.len(), |ui, range| {
+                for index in range {
+                    let line = &self.lines[self.matches[index] - self.first];
+                    ui.add(egui::Label::new(self.highlight(ui, line)).wrap(false));
+                }
+            });
+    }
+
+    fn rules_ui(&mut self, ui: &mut egui::Ui) {
+        let mut removed = None;
+        for (index, rule) in self.rules.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.color_edit_button_srgba(&mut rule.color);
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut rule.pattern)
+                        .hint_text("Regex")
+                        .text_color_opt(rule.regex.is_none().then(|| ui.visuals().error_fg_color)),
+                );
+                if response.changed() {
+                    rule.regex = Regex::new(&rule.pattern).ok();
+                }
+                if ui.small_button("❌").clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+        if let Some(index) = removed {
+            self.rules.remove(index);
+        }
+        if ui.button("➕ Add rule").clicked() {
+            self.rules
+                .push(Rule::new("", egui::Color32::from_rgb(100, 90, 30)));
+        }
+    }
+
+    /// The line in the color of its level, with the matches of the rules as background. Later
+    /// rules are drawn over earlier ones.
+    fn highlight(&self, ui: &egui::Ui, line: &Line) -> egui::text::LayoutJob {
+        let text = &line.text;
+        let mut backgrounds = vec![egui::Color32::TRANSPARENT; text.len()];
+        for rule in &self.rules {
+            let regex = match &rule.regex {
+                Some(regex) if !rule.pattern.is_empty() => regex,
+                _ => continue,
+            };
+            for found in regex.find_iter(text) {
+                backgrounds[found.range()].fill(rule.color);
+            }
+        }
+
+        let format = egui::TextFormat {
+            font_id: egui::TextStyle::Monospace.resolve(ui.style()),
+            color: line.level.color(ui.visuals()),
+            ..Default::default()
+        };
+        let mut job = egui::text::LayoutJob::default();
+        let mut start = 0;
+        // Matches start and end at character boundaries, so the sections do as well.
+        for end in 1..=text.len() {
+            if end == text.len() || backgrounds[end] != backgrounds[start] {
+                let format = egui::TextFormat {
+                    background: backgrounds[start],
+                    ..format.clone()
+                };
+                job.append(&text[start..end], 0.0, format);
+                start = end;
+            }
+        }
+        job
+    }
+}
+
+/// The body of the follower thread. Reads the end of the file at `path` and then what gets
+/// appended to it, until `running` is cleared.
+fn follow(
+    path: &Path,
+    sender: &mpsc::Sender<Message>,
+    running: &AtomicBool,
+    ctx: &egui::Context,
+) -> Result<(), String> {
+    let send = |message| {
+        let sent = sender.send(message).is_ok();
+        ctx.request_repaint();
+        sent
+    };
+    let (watch_sender, changes) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(watch_sender).map_err(|err| err.to_string())?;
+    // Watching the directory also notices when the file is replaced, as log rotation does.
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    watcher
+        .watch(directory, notify::RecursiveMode::NonRecursive)
+        .map_err(|err| format!("Can't watch {}: {}", directory.display(), err))?;
+
+    let len = std::fs::metadata(path)
+        .map_err(|err| format!("Can't open {}: {}", path.display(), err))?
+        .len();
+    let mut offset = len.saturating_sub(TAIL_BYTES);
+    // The first line is cut off, unless reading starts at the beginning.
+    let mut skip_partial = offset > 0;
+    if !send(Message::Start(offset)) {
+        return Ok(());
+    }
+    let mut carry = Vec::new();
+    let mut buffer = vec![0; CHUNK_BYTES];
+
+    while running.load(Ordering::Relaxed) {
+        let mut file = match std::fs::File::open(path) {
+            Ok(file) => file,
+            // Rotated away, the next one shows up soon.
+            Err(_) => {
+                wait(&changes);
+                continue;
+            }
+        };
+        let len = file.metadata().map_err(|err| err.to_string())?.len();
+        if len < offset {
+            offset = 0;
+            carry.clear();
+            skip_partial = false;
+            if !send(Message::Truncated) {
+                return Ok(());
+            }
+        }
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|err| err.to_string())?;
+        while offset < len && running.load(Ordering::Relaxed) {
+            let read = file.read(&mut buffer).map_err(|err| err.to_string())?;
+            if read == 0 {
+                break;
+            }
+            offset += read as u64;
+            carry.extend_from_slice(&buffer[..read]);
+            let complete = match carry.iter().rposition(|byte| *byte == b'\n') {
+                Some(end) => end + 1,
+                None => continue,
+            };
+            let mut lines: Vec<String> = carry[..complete]
+                .split(|byte| *byte == b'\n')
+                .map(|line| {
+                    let line = line.strip_suffix(b"\r").unwrap_or(line);
+                    String::from_utf8_lossy(line).into_owned()
+                })
+                .collect();
+            // The split leaves an empty string after the last line break.
+            lines.pop();
+            if skip_partial && !lines.is_empty() {
+                lines.remove(0);
+                skip_partial = false;
+            }
+            carry.drain(..complete);
+            if !send(Message::Lines(lines)) {
+                return Ok(());
+            }
+        }
+        wait(&changes);
+    }
+    Ok(())
+}
+
+/// Waits for the next change, or at most [`POLL_INTERVAL`]. Changes to other files of the
+/// directory wake the thread as well, which is harmless.
+fn wait(changes: &mpsc::Receiver<notify::Result<notify::Event>>) {
+    let _ = changes.recv_timeout(POLL_INTERVAL);
+    // One read catches up with all changes that piled up meanwhile.
+    while changes.try_recv().is_ok() {}
+}
+
+impl SaveState for LogTail {
+    /// The open file.
+    type State = Option<PathBuf>;
+
+    fn save_state(&self) -> Option<PathBuf> {
+        self.follower.as_ref().map(|_| PathBuf::from(&self.path))
+    }
+
+    fn restore_state(&mut self, file: Option<PathBuf>) {
+        self.reopen = file;
+    }
+}
@@ -0,0 +1,74 @@
+use objc::runtime::{Object, BOOL, NO, YES};
+use objc::{msg_send, sel, sel_impl};
+use winit::platform::macos::WindowExtMacOS;
+use winit::window::Window;
+
+/// `NSWindowStyleMaskFullSizeContentView`, the content view reaches under the title bar.
+const FULL_SIZE_CONTENT_VIEW: usize = 1 << 15;
+/// `NSWindowTitleHidden`, `NSWindowTitleVisible` is 0.
+const TITLE_HIDDEN: isize = 1;
+/// `NSWindowZoomButton`, the rightmost of the window buttons.
+const ZOOM_BUTTON: usize = 2;
+/// The space between the window buttons and the menu bar, in points.
+const BUTTON_SPACING: f32 = 12.0;
+
+/// An `NSRect`, with the origin at the bottom left.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Rect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+/// Makes the title bar of `window` transparent and lets the content view reach under it, so
+/// that the menu bar of the example can sit next to the window buttons. The window is still
+/// moved by the title bar, wherever it isn't covered by a widget.
+pub fn apply(window: &Window, transparent: bool) {
+    let ns_window = window.ns_window() as *mut Object;
+    let flag: BOOL = if transparent { YES } else { NO };
+    let visibility = if transparent { TITLE_HIDDEN } else { 0 };
+    unsafe {
+        let mask: usize = msg_send![ns_window, styleMask];
+        let mask = if transparent {
+            mask | FULL_SIZE_CONTENT_VIEW
+        } else {
+            mask & !FULL_SIZE_CONTENT_VIEW
+        };
+        let _: () = msg_send![ns_window, setStyleMask: mask];
+        let _: () = msg_send![ns_window, setTitlebarAppearsTransparent: flag];
+        let _: () = msg_send![ns_window, setTitleVisibility: visibility];
+    }
+}
+
+/// The room the title bar of a transparent window takes from the content view, in points: `top`
+/// is the height of the title bar, the top of the content layout rect that AppKit reports as
+/// safe, and `left` the width of the window buttons.
+pub fn insets(window: &Window) -> egui::style::Margin {
+    let ns_window = window.ns_window() as *mut Object;
+    unsafe {
+        let view: *mut Object = msg_send![ns_window, contentView];
+        let bounds: Rect = msg_send![view, bounds];
+        let layout: Rect = msg_send![ns_window, contentLayoutRect];
+        let top = bounds.height - layout.y - layout.height;
+        let zoom: *mut Object = msg_send![ns_window, standardWindowButton: ZOOM_BUTTON];
+        // There are no buttons in full screen.
+        let buttons = if zoom.is_null() {
+            0.0
+        } else {
+            let frame: Rect = msg_send![zoom, frame];
+            let hidden: BOOL = msg_send![zoom, isHidden];
+            if hidden == YES {
+                0.0
+            } else {
+                (frame.x + frame.width) as f32 + BUTTON_SPACING
+            }
+        };
+        egui::style::Margin {
+            left: buttons,
+            top: top.max(0.0) as f32,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+
+use midir::{MidiInput, MidiInputConnection};
+use winit::event_loop::EventLoopProxy;
+
+use crate::config::MidiBinding;
+use crate::Event;
+
+const CLIENT_NAME: &str = "egui_example";
+
+/// A MIDI control change message, sent from the MIDI thread to the event loop.
+#[derive(Clone, Copy, Debug)]
+pub struct ControlChange {
+    pub binding: MidiBinding,
+    /// The controller position in `0..=127`.
+    pub value: u8,
+}
+
+/// MIDI input that lets CC knobs drive sliders.
+///
+/// midir calls us on its own thread, so the messages are sent as user events through the winit
+/// event loop. This wakes up the loop and keeps all state changes on the main thread. Sliders
+/// are bound with a "learn" mode: Pick "MIDI learn" from the context menu of a slider and turn a
+/// knob.
+pub struct Midi {
+    proxy: EventLoopProxy<Event>,
+    port_names: Vec<String>,
+    selected_port: usize,
+    connection: Option<(String, MidiInputConnection<()>)>,
+    /// The key of the slider waiting for the next CC message.
+    learning: Option<String>,
+    /// The latest value of every controller that moved since it was last applied.
+    pending: HashMap<MidiBinding, u8>,
+    error: Option<String>,
+}
+
+impl Midi {
+    pub fn new(proxy: EventLoopProxy<Event>) -> Self {
+        let mut midi = Self {
+            proxy,
+            port_names: Vec::new(),
+            selected_port: 0,
+            connection: None,
+            learning: None,
+            pending: HashMap::new(),
+            error: None,
+        };
+        midi.refresh_ports();
+        midi
+    }
+
+    fn refresh_ports(&mut self) {
+        match MidiInput::new(CLIENT_NAME) {
+            Ok(input) => {
+                self.port_names = input
+                    .ports()
+                    .iter()
+                    .filter_map(|port| input.port_name(port).ok())
+                    .collect();
+                self.selected_port = self
+                    .selected_port
+                    .min(self.port_names.len().saturating_sub(1));
+            }
+            Err(err) => self.error = Some(format!("Can't initialize MIDI input: {}", err)),
+        }
+    }
+
+    fn connect(&mut self) -> Result<(), String> {
+        self.connection = None;
+
+        let input = MidiInput::new(CLIENT_NAME).map_err(|err| err.to_string())?;
+        let name = self
+            .port_names
+            .get(self.selected_port)
+            .cloned()
+            .ok_or_else(|| "No MIDI port available".to_owned())?;
+        let port = input
+            .ports()
+            .into_iter()
+            .find(|port| input.port_name(port).map(|n| n == name).unwrap_or(false))
+            .ok_or_else(|| format!("MIDI port {:?} disappeared", name))?;
+
+        let proxy = self.proxy.clone();
+        let connection = input
+            .connect(
+                &port,
+                "egui_example-input",
+                move |_timestamp, message, _| {
+                    if let Some(control_change) = parse_control_change(message) {
+                        // Fails only when the event loop is already gone.
+                        proxy.send_event(Event::Midi(control_change)).ok();
+                    }
+                },
+                (),
+            )
+            .map_err(|err| err.to_string())?;
+        self.connection = Some((name, connection));
+        Ok(())
+    }
+
+    /// Handles a message forwarded by the event loop. While learning, the controller gets bound
+    /// to the slider that requested it.
+    pub fn handle(
+        &mut self,
+        control_change: ControlChange,
+        bindings: &mut BTreeMap<String, MidiBinding>,
+    ) {
+        if let Some(key) = self.learning.take() {
+            // One controller drives exactly one slider.
+            bindings.retain(|_, binding| *binding != control_change.binding);
+            bindings.insert(key, control_change.binding);
+        }
+        self.pending
+            .insert(control_change.binding, control_change.value);
+    }
+
+    /// Adds MIDI learn to the context menu of the slider of `response`, and moves `value` if its
+    /// controller changed since the last frame.
+    pub fn bind(
+        &mut self,
+        response: egui::Response,
+        key: &str,
+        value: &mut f32,
+        range: RangeInclusive<f32>,
+        bindings: &mut BTreeMap<String, MidiBinding>,
+    ) -> egui::Response {
+        let bound = bindings.get(key).copied();
+        if let Some(position) = bound.and_then(|binding| self.pending.remove(&binding)) {
+            let t = position as f32 / 127.0;
+            *value = range.start() + t * (range.end() - range.start());
+        }
+
+        let learning = self.learning.as_deref() == Some(key);
+        let response = if learning {
+            response.on_hover_text("Turn a knob on your MIDI controller")
+        } else if let Some(binding) = bound {
+            response.on_hover_text(format!(
+                "Bound to CC {} on channel {}",
+                binding.controller,
+                binding.channel + 1
+            ))
+        } else {
+            response
+        };
+
+        response.context_menu(|ui| {
+            if ui.button("🎹 MIDI learn").clicked() {
+                self.learning = Some(key.to_owned());
+                ui.close_menu();
+            }
+            if bound.is_some() && ui.button("Remove MIDI binding").clicked() {
+                bindings.remove(key);
+                ui.close_menu();
+            }
+        })
+    }
+
+    /// Shows the port selection and the current bindings.
+    pub fn ui(&mut self, ui: &mut egui::Ui, bindings: &mut BTreeMap<String, MidiBinding>) {
+        ui.horizontal(|ui| {
+            let selected_text = self
+                .port_names
+                .get(self.selected_port)
+                .map(String::as_str)
+                .unwrap_or("<none>");
+            egui::ComboBox::from_label("MIDI port")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for (index, name) in self.port_names.iter().enumerate() {
+                        ui.selectable_value(&mut self.selected_port, index, name);
+                    }
+                });
+            if ui.button("🔄").on_hover_text("Refresh ports").clicked() {
+                self.refresh_ports();
+            }
+        });
+
+        let mut toggle_connection = false;
+        ui.horizontal(|ui| {
+            if let Some((name, _)) = &self.connection {
+                ui.label(format!("Connected to {}", name));
+                toggle_connection = ui.button("Disconnect").clicked();
+            } else {
+                toggle_connection = ui.button("Connect").clicked();
+            }
+        });
+        if toggle_connection {
+            if self.connection.is_some() {
+                self.connection = None;
+            } else {
+                self.error = self.connect().err();
+            }
+        }
+
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        if self.learning.is_some() {
+            ui.horizontal(|ui| {
+                ui.label("Waiting for a controller…");
+                if ui.button("Cancel").clicked() {
+                    self.learning = None;
+                }
+            });
+        }
+
+        if bindings.is_empty() {
+            ui.label("Right-click a slider to bind it to a controller.");
+        }
+        bindings.retain(|key, binding| {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "{}: CC {} / channel {}",
+                    key,
+                    binding.controller,
+                    binding.channel + 1
+                ));
+                !ui.small_button("🗑").clicked()
+            })
+            .inner
+        });
+    }
+}
+
+/// Extracts a control change from a raw MIDI message.
+fn parse_control_change(message: &[u8]) -> Option<ControlChange> {
+    match *message {
+        [status, controller, value] if status & 0xF0 == 0xB0 => Some(ControlChange {
+            binding: MidiBinding {
+                channel: status & 0x0F,
+                controller,
+            },
+            value,
+        }),
+        _ => None,
+    }
+}
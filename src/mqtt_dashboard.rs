@@ -0,0 +1,360 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use rumqttc::{Client, Event, MqttOptions, Packet, QoS};
+
+/// How many messages the log keeps.
+const LOG_LENGTH: usize = 200;
+/// How many values of a topic are kept for its plot.
+const HISTORY_LENGTH: usize = 600;
+/// How long the connection thread waits before reconnecting after an error.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// An MQTT client that subscribes to topics of a broker. Topics with numeric payloads can be
+/// shown as gauges and plotted over time, and messages can be published for testing.
+///
+/// The connection runs on its own thread, which sends the received messages through a channel
+/// and requests a repaint for each of them.
+pub struct MqttDashboard {
+    host: String,
+    port: u16,
+    client_id: String,
+    connection: Option<Connection>,
+    /// Whether the broker accepted the connection.
+    connected: bool,
+    error: Option<String>,
+    new_filter: String,
+    /// The topic filters that are subscribed to, again after every reconnect.
+    filters: Vec<String>,
+    topics: BTreeMap<String, Topic>,
+    log: VecDeque<(f64, String, String)>,
+    publish_topic: String,
+    publish_payload: String,
+    publish_qos: QoS,
+    publish_retain: bool,
+}
+
+struct Connection {
+    client: Client,
+    messages: mpsc::Receiver<Message>,
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // Dropping the client ends the connection thread.
+        let _ = self.client.try_disconnect();
+    }
+}
+
+enum Message {
+    Connected,
+    Publish { topic: String, payload: String },
+    Error(String),
+}
+
+/// The numeric values received on a topic.
+#[derive(Default)]
+struct Topic {
+    /// The times the values were received at and the values.
+    history: VecDeque<[f64; 2]>,
+    gauge: bool,
+    plot: bool,
+    /// The range of the gauge.
+    range: (f64, f64),
+}
+
+impl Default for MqttDashboard {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_owned(),
+            port: 1883,
+            client_id: format!("egui_example-{}", std::process::id()),
+            connection: None,
+            connected: false,
+            error: None,
+            new_filter: "#".to_owned(),
+            filters: Vec::new(),
+            topics: BTreeMap::new(),
+            log: VecDeque::new(),
+            publish_topic: "egui_example/test".to_owned(),
+            publish_payload: "42".to_owned(),
+            publish_qos: QoS::AtMostOnce,
+            publish_retain: false,
+        }
+    }
+}
+
+impl MqttDashboard {
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        self.poll(ctx);
+
+        egui::Window::new("📡 MQTT dashboard")
+            .open(open)
+            .default_size([640.0, 560.0])
+            .show(ctx, |ui| self.ui(ui));
+    }
+
+    fn connect(&mut self, ctx: &egui::Context) {
+        let mut options = MqttOptions::new(&self.client_id, &self.host, self.port);
+        options.set_keep_alive(Duration::from_secs(10));
+        let (client, mut connection) = Client::new(options, 64);
+        let (sender, messages) = mpsc::channel();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            // Ends when the client is dropped.
+            for event in connection.iter() {
+                let message = match event {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => Message::Connected,
+                    Ok(Event::Incoming(Packet::Publish(publish))) => Message::Publish {
+                        topic: publish.topic,
+                        payload: String::from_utf8_lossy(&publish.payload).into_owned(),
+                    },
+                    Ok(_) => continue,
+                    Err(err) => Message::Error(err.to_string()),
+                };
+                let error = matches!(message, Message::Error(_));
+                if sender.send(message).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+                if error {
+                    // Iterating further reconnects.
+                    std::thread::sleep(RECONNECT_DELAY);
+                }
+            }
+        });
+        self.connection = Some(Connection { client, messages });
+        self.connected = false;
+        self.error = None;
+    }
+
+    fn poll(&mut self, ctx: &egui::Context) {
+        let connection = match &self.connection {
+            Some(connection) => connection,
+            None => return,
+        };
+        let time = ctx.input().time;
+        while let Ok(message) = connection.messages.try_recv() {
+            match message {
+                Message::Connected => {
+                    self.connected = true;
+                    self.error = None;
+                    // The broker forgets the subscriptions of clean sessions.
+                    for filter in &self.filters {
+                        let _ = connection.client.try_subscribe(filter, QoS::AtMostOnce);
+                    }
+                }
+                Message::Publish { topic, payload } => {
+                    if let Ok(value) = payload.trim().parse::<f64>() {
+                        let topic = self.topics.entry(topic.clone()).or_insert_with(|| Topic {
+                            range: (0.0, value.abs().max(1.0) * 2.0),
+                            ..Default::default()
+                        });
+                        if topic.history.len() == HISTORY_LENGTH {
+                            topic.history.pop_front();
+                        }
+                        topic.history.push_back([time, value]);
+                    }
+                    if self.log.len() == LOG_LENGTH {
+                        self.log.pop_front();
+                    }
+                    self.log.push_back((time, topic, payload));
+                }
+                Message::Error(err) => {
+                    self.connected = false;
+                    self.error = Some(err);
+                }
+            }
+        }
+    }
+
+    fn subscribe(&mut self) {
+        let filter = self.new_filter.trim().to_owned();
+        if filter.is_empty() || self.filters.contains(&filter) {
+            return;
+        }
+        if let Some(connection) = &self.connection {
+            if let Err(err) = connection.client.try_subscribe(&filter, QoS::AtMostOnce) {
+                self.error = Some(err.to_string());
+                return;
+            }
+        }
+        self.filters.push(filter);
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let connected = self.connection.is_some();
+            ui.add_enabled_ui(!connected, |ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.host).desired_width(160.0));
+                ui.label(":");
+                ui.add(egui::DragValue::new(&mut self.port));
+                ui.add(egui::TextEdit::singleline(&mut self.client_id).desired_width(160.0))
+                    .on_hover_text("Client id");
+            });
+            if connected {
+                if ui.button("Disconnect").clicked() {
+                    self.connection = None;
+                    self.connected = false;
+                }
+            } else if ui.button("Connect").clicked() {
+                self.connect(ui.ctx());
+            }
+        });
+        match (&self.connection, &self.error) {
+            (_, Some(error)) => {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            (Some(_), None) if self.connected => {
+                ui.label("Connected");
+            }
+            (Some(_), None) => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Connecting…");
+                });
+            }
+            (None, None) => {}
+        }
+
+        ui.separator();
+        self.subscriptions_ui(ui);
+        ui.separator();
+        self.publish_ui(ui);
+        ui.separator();
+        self.topics_ui(ui);
+    }
+
+    fn subscriptions_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.new_filter)
+                    .hint_text("Topic filter, like a/+/#"),
+            );
+            let entered = response.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+            if ui.button("Subscribe").clicked() || entered {
+                self.subscribe();
+            }
+        });
+        ui.horizontal_wrapped(|ui| {
+            let mut removed = None;
+            for (index, filter) in self.filters.iter().enumerate() {
+                if ui
+                    .button(format!("{} ❌", filter))
+                    .on_hover_text("Unsubscribe")
+                    .clicked()
+                {
+                    removed = Some(index);
+                }
+            }
+            if let Some(index) = removed {
+                let filter = self.filters.remove(index);
+                if let Some(connection) = &self.connection {
+                    let _ = connection.client.try_unsubscribe(filter);
+                }
+            }
+        });
+    }
+
+    fn publish_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.publish_topic).desired_width(160.0))
+                .on_hover_text("Topic");
+            ui.add(egui::TextEdit::singleline(&mut self.publish_payload).desired_width(120.0))
+                .on_hover_text("Payload");
+            egui::ComboBox::from_id_source("mqtt_publish_qos")
+                .width(60.0)
+                .selected_text(format!("QoS {}", self.publish_qos as u8))
+                .show_ui(ui, |ui| {
+                    for qos in [QoS::AtMostOnce, QoS::AtLeastOnce, QoS::ExactlyOnce] {
+                        ui.selectable_value(
+                            &mut self.publish_qos,
+                            qos,
+                            format!("QoS {}", qos as u8),
+                        );
+                    }
+                });
+            ui.checkbox(&mut self.publish_retain, "Retain");
+            let publish = ui.add_enabled(self.connected, egui::Button::new("Publish"));
+            if publish.clicked() {
+                if let Some(connection) = &self.connection {
+                    let result = connection.client.try_publish(
+                        &self.publish_topic,
+                        self.publish_qos,
+                        self.publish_retain,
+                        self.publish_payload.as_bytes(),
+                    );
+                    if let Err(err) = result {
+                        self.error = Some(err.to_string());
+                    }
+                }
+            }
+        });
+    }
+
+    fn topics_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            for (name, topic) in &self.topics {
+                let (min, max) = topic.range;
+                if let (true, Some([_, value])) = (topic.gauge, topic.history.back()) {
+                    ui.vertical(|ui| {
+                        let fraction = (value - min) / (max - min);
+                        crate::system_monitor::gauge(ui, fraction as f32, &format!("{:.2}", value));
+                        ui.small(name);
+                    });
+                }
+            }
+        });
+        let plotted: Vec<_> = self.topics.iter().filter(|(_, topic)| topic.plot).collect();
+        if !plotted.is_empty() {
+            egui::plot::Plot::new("mqtt_plot")
+                .height(160.0)
+                .legend(egui::plot::Legend::default())
+                .show(ui, |plot_ui| {
+                    for (name, topic) in plotted {
+                        let points: egui::plot::PlotPoints =
+                            topic.history.iter().copied().collect();
+                        plot_ui.line(egui::plot::Line::new(points).name(name));
+                    }
+                });
+        }
+
+        ui.columns(2, |columns| {
+            columns[0].strong("Numeric topics");
+            egui::ScrollArea::vertical()
+                .id_source("mqtt_topics")
+                .max_height(200.0)
+                .show(&mut columns[0], |ui| {
+                    egui::Grid::new("mqtt_topics_grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (name, topic) in &mut self.topics {
+                                ui.label(name);
+                                ui.checkbox(&mut topic.gauge, "Gauge");
+                                ui.checkbox(&mut topic.plot, "Plot");
+                                ui.add(egui::DragValue::new(&mut topic.range.0).speed(0.1))
+                                    .on_hover_text("Gauge minimum");
+                                ui.add(egui::DragValue::new(&mut topic.range.1).speed(0.1))
+                                    .on_hover_text("Gauge maximum");
+                                ui.end_row();
+                            }
+                        });
+                });
+            columns[1].strong("Messages");
+            egui::ScrollArea::vertical()
+                .id_source("mqtt_log")
+                .max_height(200.0)
+                .stick_to_bottom(true)
+                .show(&mut columns[1], |ui| {
+                    for (time, topic, payload) in &self.log {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.weak(format!("{:.1}", time));
+                            ui.monospace(topic);
+                            ui.label(payload);
+                        });
+                    }
+                });
+        });
+    }
+}
@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+
+/// When the on-screen keyboard shows up for a focused text field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mode {
+    /// Once the screen was touched, until a hardware keyboard was used.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+const LETTERS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+const SYMBOLS: [&str; 4] = ["1234567890", "@#$%&*-+()", "!\"':;/?=_", "[]{}<>\\"];
+
+/// A keyboard drawn by egui at the bottom of the screen, for touch screens without a hardware
+/// keyboard. The pressed keys go to egui as the events of the next frame.
+#[derive(Default)]
+pub struct OnScreenKeyboard {
+    /// The text field the keys go to, while the keyboard is shown.
+    target: Option<egui::Id>,
+    shift: bool,
+    symbols: bool,
+    /// Whether egui got key events of a real keyboard, or touches, since the start.
+    hardware_keyboard: bool,
+    touched: bool,
+    /// The events of the keys pressed in the last frame.
+    pending: Vec<egui::Event>,
+}
+
+impl OnScreenKeyboard {
+    /// Looks for hardware keys and touches in the input of the frame, then adds the keys pressed
+    /// in the last one.
+    pub fn next_frame(&mut self, raw_input: &mut egui::RawInput) {
+        for event in &raw_input.events {
+            match event {
+                egui::Event::Key { .. } => self.hardware_keyboard = true,
+                egui::Event::Touch { .. } => self.touched = true,
+                _ => {}
+            }
+        }
+        raw_input.events.append(&mut self.pending);
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, mode: Mode) {
+        let wanted = match mode {
+            Mode::Auto => self.touched && !self.hardware_keyboard,
+            Mode::Always => true,
+            Mode::Never => false,
+        };
+        if !wanted {
+            self.target = None;
+            return;
+        }
+        if ctx.wants_keyboard_input() {
+            self.target = ctx.memory().focus();
+        }
+        let target = match self.target {
+            Some(target) => target,
+            None => return,
+        };
+
+        let mut pressed = Vec::new();
+        let mut used = false;
+        let mut hide = false;
+        egui::Area::new("on_screen_keyboard")
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -8.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    let width = (ctx.input().screen_rect().width() - 32.0).min(640.0);
+                    let key_size = egui::vec2(width / 10.0 - ui.spacing().item_spacing.x, 36.0);
+                    let mut key = |ui: &mut egui::Ui, label: egui::WidgetText, keys: f32| {
+                        let size = egui::vec2(
+                            key_size.x * keys + ui.spacing().item_spacing.x * (keys - 1.0),
+                            key_size.y,
+                        );
+                        let response = ui.add_sized(size, egui::Button::new(label));
+                        used |= response.is_pointer_button_down_on() || response.clicked();
+                        response.clicked()
+                    };
+                    let rows = if self.symbols { SYMBOLS } else { LETTERS };
+                    for (index, row) in rows.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let last = index + 1 == rows.len();
+                            let shift = match self.shift {
+                                true => egui::RichText::new("⇧").strong().underline(),
+                                false => egui::RichText::new("⇧"),
+                            };
+                            if last && key(ui, shift.into(), 1.5) {
+                                self.shift = !self.shift;
+                            }
+                            if !last {
+                                let indent = (10 - row.chars().count()) as f32 / 2.0;
+                                ui.add_space(indent * (key_size.x + ui.spacing().item_spacing.x));
+                            }
+                            for c in row.chars() {
+                                let c = match self.shift {
+                                    true => c.to_uppercase().next().unwrap_or(c),
+                                    false => c,
+                                };
+                                if key(ui, c.to_string().into(), 1.0) {
+                                    pressed.push(egui::Event::Text(c.to_string()));
+                                    self.shift = false;
+                                }
+                            }
+                            if last && key(ui, "⌫".into(), 1.5) {
+                                pressed.extend(key_events(egui::Key::Backspace));
+                            }
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        let layout = if self.symbols { "abc" } else { "?123" };
+                        if key(ui, layout.into(), 1.5) {
+                            self.symbols = !self.symbols;
+                        }
+                        if key(ui, "⏴".into(), 1.0) {
+                            pressed.extend(key_events(egui::Key::ArrowLeft));
+                        }
+                        if key(ui, " ".into(), 4.0) {
+                            pressed.push(egui::Event::Text(" ".to_owned()));
+                        }
+                        if key(ui, "⏵".into(), 1.0) {
+                            pressed.extend(key_events(egui::Key::ArrowRight));
+                        }
+                        if key(ui, "⏎".into(), 1.5) {
+                            pressed.extend(key_events(egui::Key::Enter));
+                        }
+                        hide = key(ui, "⏷".into(), 1.0);
+                    });
+                });
+            });
+
+        if hide {
+            ctx.memory().surrender_focus(target);
+            self.target = None;
+        } else if used {
+            // Pressing a key takes the focus from the text field, it gets it back for the keys.
+            ctx.memory().request_focus(target);
+            self.pending.extend(pressed);
+            ctx.request_repaint();
+        } else if !ctx.wants_keyboard_input() {
+            self.target = None;
+        }
+    }
+}
+
+/// The press and release of `key`.
+fn key_events(key: egui::Key) -> [egui::Event; 2] {
+    [true, false].map(|pressed| egui::Event::Key {
+        key,
+        pressed,
+        modifiers: Default::default(),
+    })
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+/// A step of the tour, with the widget it points at. Steps without one are shown in the middle.
+struct Step {
+    anchor: Option<&'static str>,
+    title: &'static str,
+    text: &'static str,
+}
+
+const STEPS: &[Step] = &[
+    Step {
+        anchor: None,
+        title: "Welcome",
+        text: "This example runs egui on wgpu and winit: the demos that ship with egui, plus tool \
+               windows of its own. The tour shows where to find them.",
+    },
+    Step {
+        anchor: Some("file_menu"),
+        title: "File",
+        text: "Reopens the files opened last. Files dropped onto the window or passed on the \
+               command line open in the tool that shows them.",
+    },
+    Step {
+        anchor: Some("tools_menu"),
+        title: "Tools",
+        text: "The tool windows, from the settings over the viewers to the stress test, and the \
+               screenshots.",
+    },
+    Step {
+        anchor: Some("workspaces_menu"),
+        title: "Workspaces",
+        text: "Saves the open windows, the open files and the theme under a name, to come back \
+               to them later.",
+    },
+    Step {
+        anchor: Some("debug_menu"),
+        title: "Debug",
+        text: "Looks into egui and the frames: the event log, the tessellation, the frame \
+               statistics and the hitch reports.",
+    },
+    Step {
+        anchor: Some("help_menu"),
+        title: "Help",
+        text: "Starts this tour again.",
+    },
+];
+
+/// The darkening around the highlighted widget.
+const DIM: egui::Color32 = egui::Color32::from_black_alpha(160);
+
+/// The rects of the widgets the tour can point at, tracked across frames: the ones of the
+/// current frame, and of the last one for widgets that come later in the frame than the tour.
+#[derive(Default)]
+struct Spotlight {
+    current: HashMap<&'static str, egui::Rect>,
+    previous: HashMap<&'static str, egui::Rect>,
+}
+
+impl Spotlight {
+    fn rect(&self, anchor: &str) -> Option<egui::Rect> {
+        self.current
+            .get(anchor)
+            .or_else(|| self.previous.get(anchor))
+            .copied()
+    }
+}
+
+/// A tour of the UI: dims everything but the widget of the current step, and explains it in a
+/// callout next to it.
+#[derive(Default)]
+pub struct Onboarding {
+    spotlight: Spotlight,
+    /// The current step, while the tour runs.
+    step: Option<usize>,
+}
+
+impl Onboarding {
+    pub fn start(&mut self) {
+        self.step = Some(0);
+    }
+
+    /// Forgets the widgets that weren't drawn in the last frame. Called at the start of a frame.
+    pub fn begin_frame(&mut self) {
+        self.spotlight.previous = std::mem::take(&mut self.spotlight.current);
+    }
+
+    /// Tells where the widget `anchor` is in this frame, for the steps that point at it.
+    pub fn track(&mut self, anchor: &'static str, rect: egui::Rect) {
+        self.spotlight.current.insert(anchor, rect);
+    }
+
+    /// Draws the overlay on top of everything else, while the tour runs.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let index = match self.step {
+            Some(index) => index,
+            None => return,
+        };
+        let step = &STEPS[index];
+        let screen = ctx.input().screen_rect();
+        let highlight = step
+            .anchor
+            .and_then(|anchor| self.spotlight.rect(anchor))
+            .map(|rect| rect.expand(4.0));
+
+        // The overlay takes the clicks, so that the UI below stays as the tour describes it.
+        egui::Area::new("onboarding_overlay")
+            .order(egui::Order::Foreground)
+            .fixed_pos(screen.min)
+            .show(ctx, |ui| {
+                ui.allocate_rect(screen, egui::Sense::click_and_drag());
+                let painter = ui.painter();
+                match highlight {
+                    Some(hole) => {
+                        for rect in surrounding(screen, hole) {
+                            painter.rect_filled(rect, 0.0, DIM);
+                        }
+                        let stroke = egui::Stroke::new(2.0, ui.visuals().selection.stroke.color);
+                        painter.rect_stroke(hole, 4.0, stroke);
+                    }
+                    None => {
+                        painter.rect_filled(screen, 0.0, DIM);
+                    }
+                }
+            });
+
+        let mut callout = egui::Area::new("onboarding_callout").order(egui::Order::Tooltip);
+        callout = match highlight {
+            Some(hole) => callout.fixed_pos(hole.left_bottom() + egui::vec2(0.0, 8.0)),
+            None => callout.anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO),
+        };
+        let mut next = self.step;
+        callout.show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_max_width(280.0);
+                ui.heading(step.title);
+                ui.label(step.text);
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    ui.weak(format!("{} of {}", index + 1, STEPS.len()));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let last = index + 1 == STEPS.len();
+                        if ui.button(if last { "Done" } else { "Next" }).clicked() {
+                            next = (!last).then_some(index + 1);
+                        }
+                        if index > 0 && ui.button("Back").clicked() {
+                            next = Some(index - 1);
+                        }
+                        if !last && ui.button("Skip").clicked() {
+                            next = None;
+                        }
+                    });
+                });
+            });
+        });
+        if ctx.input().key_pressed(egui::Key::Escape) {
+            next = None;
+        }
+        self.step = next;
+    }
+}
+
+/// The four rects that cover `screen` except for `hole`.
+fn surrounding(screen: egui::Rect, hole: egui::Rect) -> [egui::Rect; 4] {
+    let hole = hole.intersect(screen);
+    [
+        egui::Rect::from_min_max(screen.min, egui::pos2(screen.max.x, hole.min.y)),
+        egui::Rect::from_min_max(egui::pos2(screen.min.x, hole.max.y), screen.max),
+        egui::Rect::from_min_max(
+            egui::pos2(screen.min.x, hole.min.y),
+            egui::pos2(hole.min.x, hole.max.y),
+        ),
+        egui::Rect::from_min_max(
+            egui::pos2(hole.max.x, hole.min.y),
+            egui::pos2(screen.max.x, hole.max.y),
+        ),
+    ]
+}
@@ -0,0 +1,67 @@
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use objc::runtime::{class_addMethod, Class, Object, Sel};
+use objc::{class, msg_send, sel, sel_impl};
+use winit::event_loop::EventLoopProxy;
+
+use crate::Event;
+
+/// `application:openFiles:` can't capture anything, so it reaches the event loop through this.
+static PROXY: OnceLock<Mutex<EventLoopProxy<Event>>> = OnceLock::new();
+
+/// Lets "Open with" in the Finder and files dropped onto the Dock icon open in the example. macOS
+/// doesn't pass them as arguments, but sends them to the application delegate, which winit 0.27
+/// doesn't forward. The delegate gets the missing method here, and the files arrive as
+/// [`Event::Activate`].
+///
+/// Has to be called after the event loop is created and before it runs, so that the files the
+/// example is launched with aren't missed.
+pub fn install(proxy: EventLoopProxy<Event>) {
+    if PROXY.set(Mutex::new(proxy)).is_err() {
+        return;
+    }
+    unsafe {
+        let app: *mut Object = msg_send![class!(NSApplication), sharedApplication];
+        let delegate: *mut Object = msg_send![app, delegate];
+        if delegate.is_null() {
+            eprintln!("Can't handle files opened by the Finder: there is no application delegate");
+            return;
+        }
+        let class: *mut Class = msg_send![delegate, class];
+        let open_files: extern "C" fn(&Object, Sel, *mut Object, *mut Object) = open_files;
+        class_addMethod(
+            class,
+            sel!(application:openFiles:),
+            std::mem::transmute(open_files),
+            c"v@:@@".as_ptr(),
+        );
+    }
+}
+
+extern "C" fn open_files(_: &Object, _: Sel, app: *mut Object, files: *mut Object) {
+    let mut paths = Vec::new();
+    unsafe {
+        let count: usize = msg_send![files, count];
+        for index in 0..count {
+            let file: *mut Object = msg_send![files, objectAtIndex: index];
+            let utf8: *const c_char = msg_send![file, UTF8String];
+            if !utf8.is_null() {
+                paths.push(PathBuf::from(
+                    CStr::from_ptr(utf8).to_string_lossy().into_owned(),
+                ));
+            }
+        }
+        // NSApplicationDelegateReplySuccess, the files are opened asynchronously.
+        let _: () = msg_send![app, replyToOpenOrPrint: 0usize];
+    }
+    if let Some(proxy) = PROXY.get() {
+        proxy
+            .lock()
+            .unwrap()
+            .send_event(Event::Activate(paths))
+            .ok();
+    }
+}
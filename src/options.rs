@@ -0,0 +1,371 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// The graphics APIs wgpu is allowed to use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// Vulkan, Metal, DX12 or WebGPU, whatever the platform offers.
+    Primary,
+    /// OpenGL / GLES with downlevel limits, for the Raspberry Pi and old integrated GPUs.
+    Gl,
+}
+
+impl Backend {
+    pub fn backends(self) -> wgpu::Backends {
+        match self {
+            Backend::Primary => wgpu::Backends::PRIMARY,
+            Backend::Gl => wgpu::Backends::GL,
+        }
+    }
+
+    /// The limits to request from `adapter`.
+    pub fn limits(self, adapter: &wgpu::Adapter) -> wgpu::Limits {
+        match self {
+            Backend::Primary => wgpu::Limits::default(),
+            // GLES 3.0 can't reach the default limits, but the texture size should still match
+            // what the adapter supports, so that large windows work.
+            Backend::Gl => {
+                wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits())
+            }
+        }
+    }
+}
+
+/// The window systems winit can use on Linux and the BSDs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowSystem {
+    X11,
+    Wayland,
+}
+
+/// The theme `--theme` starts with, instead of the one of the stored style.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub fn visuals(self) -> egui::Visuals {
+        match self {
+            Theme::Dark => egui::Visuals::dark(),
+            Theme::Light => egui::Visuals::light(),
+        }
+    }
+}
+
+/// The command line options of the example.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    pub backend: Backend,
+    /// Use the software renderer even if there is a GPU, to test the fallback.
+    pub force_fallback_adapter: bool,
+    /// Records the input to this file.
+    pub record: Option<PathBuf>,
+    /// Plays the input recorded in this file back instead of reacting to the real input.
+    pub replay: Option<PathBuf>,
+    /// Runs in the kiosk mode, see `Kiosk`.
+    pub kiosk: bool,
+    /// The index of the monitor the kiosk covers, the primary one without it.
+    pub kiosk_monitor: Option<usize>,
+    /// The seconds without input after which the kiosk resets the UI, 0 never does.
+    pub kiosk_reset: f64,
+    /// Switches to the settings profile of this name at the start.
+    pub profile: Option<String>,
+    /// Records the input egui gets to this `.egui` file, see `ui_replay`.
+    pub record_ui: Option<PathBuf>,
+    /// Runs the UI again from this `.egui` file instead of reacting to the real input.
+    pub play: Option<PathBuf>,
+    /// Injects random input for this many seconds, then exits.
+    pub fuzz: Option<f64>,
+    /// The seed of the random input, to reproduce a failed fuzzing run.
+    pub fuzz_seed: Option<u64>,
+    /// Advances the time by this many seconds every frame instead of following the wall clock.
+    pub fixed_step: Option<f64>,
+    /// Runs the steps of this UI script, see `UiDriver::parse`, then exits.
+    pub ui_script: Option<PathBuf>,
+    /// Exits with an error once the GPU takes this many seconds for a frame, 0 never does.
+    pub gpu_timeout: f64,
+    /// Records a wgpu API trace into this directory.
+    pub trace: Option<PathBuf>,
+    /// Ends the trace and exits after this many frames.
+    pub trace_frames: Option<u32>,
+    /// Files to open in the tool windows that show them.
+    pub files: Vec<PathBuf>,
+    /// Starts another instance, even if the example is already running.
+    pub new_instance: bool,
+    /// Stores the config, workspaces and crash reports here instead of the config directory of
+    /// the OS.
+    pub config_dir: Option<PathBuf>,
+    /// Forces winit to this window system instead of preferring Wayland, to narrow down
+    /// compositor issues.
+    pub window_system: Option<WindowSystem>,
+    /// Multiplies the scale factor of the monitor, to try other DPIs. Not saved in the config,
+    /// like the theme and the font.
+    pub scale: Option<f32>,
+    pub theme: Option<Theme>,
+    /// A TTF or OTF font that replaces egui's proportional font.
+    pub font: Option<PathBuf>,
+    /// The directory of the images, fonts and shaders that are reloaded when they change.
+    pub assets: PathBuf,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            backend: Backend::Primary,
+            force_fallback_adapter: false,
+            record: None,
+            replay: None,
+            kiosk: false,
+            kiosk_monitor: None,
+            kiosk_reset: 120.0,
+            profile: None,
+            record_ui: None,
+            play: None,
+            fuzz: None,
+            fuzz_seed: None,
+            fixed_step: None,
+            ui_script: None,
+            gpu_timeout: 10.0,
+            trace: None,
+            trace_frames: None,
+            files: Vec::new(),
+            new_instance: false,
+            config_dir: None,
+            window_system: None,
+            scale: None,
+            theme: None,
+            font: None,
+            assets: PathBuf::from("assets"),
+        }
+    }
+}
+
+impl Options {
+    /// Whether a running instance should take over: only if the command line holds nothing but
+    /// files, the options for modes, tests and the config need an instance of their own.
+    pub fn hands_off(&self) -> bool {
+        let files_only = Self {
+            files: Vec::new(),
+            ..self.clone()
+        };
+        files_only == Self::default()
+    }
+
+    /// Whether this instance opens the files of the later ones, see `hands_off`. Runs that need
+    /// to be reproducible or keep a config of their own don't, and neither does a second
+    /// instance that would take the socket of the first.
+    pub fn receives_hand_offs(&self) -> bool {
+        self.hands_off()
+    }
+
+    /// Parses the options from the command line. Invalid options are reported and ignored.
+    pub fn from_args() -> Self {
+        let mut options = Self::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--backend" => match args.next().as_deref() {
+                    Some("primary") => options.backend = Backend::Primary,
+                    Some("gl") => options.backend = Backend::Gl,
+                    Some(other) => eprintln!(
+                        "Unknown backend '{}', expected 'primary' or 'gl'. Using the default.",
+                        other
+                    ),
+                    None => eprintln!("Missing value for --backend"),
+                },
+                "--software" => options.force_fallback_adapter = true,
+                "--record" | "--replay" => match args.next() {
+                    Some(path) if arg == "--record" => options.record = Some(path.into()),
+                    Some(path) => options.replay = Some(path.into()),
+                    None => eprintln!("Missing file for {}", arg),
+                },
+                "--kiosk" => options.kiosk = true,
+                "--kiosk-monitor" => match args.next().map(|value| value.parse()) {
+                    Some(Ok(index)) => options.kiosk_monitor = Some(index),
+                    _ => eprintln!("Expected the index of a monitor after --kiosk-monitor"),
+                },
+                "--kiosk-reset" => match args.next().map(|value| value.parse::<f64>()) {
+                    Some(Ok(seconds)) if seconds.is_finite() && seconds >= 0.0 => {
+                        options.kiosk_reset = seconds
+                    }
+                    _ => eprintln!("Expected the idle time in seconds after --kiosk-reset"),
+                },
+                "--profile" => match args.next() {
+                    Some(name) => options.profile = Some(name),
+                    None => eprintln!("Missing name for --profile"),
+                },
+                "--record-ui" | "--play" => match args.next() {
+                    Some(path) if arg == "--record-ui" => options.record_ui = Some(path.into()),
+                    Some(path) => options.play = Some(path.into()),
+                    None => eprintln!("Missing file for {}", arg),
+                },
+                "--fuzz" => match args.next().map(|value| value.parse()) {
+                    Some(Ok(seconds)) => options.fuzz = Some(seconds),
+                    _ => eprintln!("Expected the duration in seconds after --fuzz"),
+                },
+                "--fuzz-seed" => match args.next().map(|value| value.parse()) {
+                    Some(Ok(seed)) => options.fuzz_seed = Some(seed),
+                    _ => eprintln!("Expected a number after --fuzz-seed"),
+                },
+                "--fixed-step" => match args.next().map(|value| value.parse()) {
+                    Some(Ok(seconds)) if seconds > 0.0 => options.fixed_step = Some(seconds),
+                    _ => eprintln!("Expected the step in seconds after --fixed-step"),
+                },
+                "--ui-script" => match args.next() {
+                    Some(path) => options.ui_script = Some(path.into()),
+                    None => eprintln!("Missing file for --ui-script"),
+                },
+                "--gpu-timeout" => match args.next().map(|value| value.parse::<f64>()) {
+                    Some(Ok(seconds)) if seconds.is_finite() && seconds >= 0.0 => {
+                        options.gpu_timeout = seconds
+                    }
+                    _ => eprintln!("Expected the timeout in seconds after --gpu-timeout"),
+                },
+                "--trace" => match args.next() {
+                    Some(dir) => options.trace = Some(dir.into()),
+                    None => eprintln!("Missing directory for --trace"),
+                },
+                "--trace-frames" => match args.next().map(|value| value.parse()) {
+                    Some(Ok(frames)) => options.trace_frames = Some(frames),
+                    _ => eprintln!("Expected the number of frames after --trace-frames"),
+                },
+                "--new-instance" => options.new_instance = true,
+                "--config-dir" => match args.next() {
+                    Some(path) => options.config_dir = Some(path.into()),
+                    None => eprintln!("Missing directory for --config-dir"),
+                },
+                "--scale" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(scale)) if scale > 0.0 => options.scale = Some(scale),
+                    _ => eprintln!("Expected a positive factor after --scale"),
+                },
+                "--theme" => match args.next().as_deref() {
+                    Some("dark") => options.theme = Some(Theme::Dark),
+                    Some("light") => options.theme = Some(Theme::Light),
+                    Some(other) => eprintln!(
+                        "Unknown theme '{}', expected 'dark' or 'light'. Using the config.",
+                        other
+                    ),
+                    None => eprintln!("Missing value for --theme"),
+                },
+                "--font" => match args.next() {
+                    Some(path) => options.font = Some(path.into()),
+                    None => eprintln!("Missing file for --font"),
+                },
+                "--assets" => match args.next() {
+                    Some(dir) => options.assets = dir.into(),
+                    None => eprintln!("Missing directory for --assets"),
+                },
+                "--x11" => options.window_system = Some(WindowSystem::X11),
+                "--wayland" => options.window_system = Some(WindowSystem::Wayland),
+                _ if !arg.starts_with('-') => options.files.push(arg.into()),
+                _ => eprintln!("Ignoring unknown argument '{}'", arg),
+            }
+        }
+        options
+    }
+
+    /// The scale factor of the UI on a monitor with `monitor_scale_factor`.
+    pub fn scale_factor(&self, monitor_scale_factor: f64) -> f64 {
+        monitor_scale_factor * f64::from(self.scale.unwrap_or(1.0))
+    }
+
+    /// egui's fonts with the emoji fonts of the system, and the `--font` in front of the
+    /// proportional ones. A font that can't be read is reported and left out.
+    pub fn font_definitions(&self) -> egui::FontDefinitions {
+        let mut fonts = crate::emoji::font_definitions();
+        if let Some(path) = &self.font {
+            match crate::assets::read_font(path) {
+                Ok(data) => {
+                    let name = path.display().to_string();
+                    fonts
+                        .font_data
+                        .insert(name.clone(), egui::FontData::from_owned(data));
+                    fonts
+                        .families
+                        .entry(egui::FontFamily::Proportional)
+                        .or_default()
+                        .insert(0, name);
+                }
+                Err(err) => eprintln!("Can't read the font {}: {}", path.display(), err),
+            }
+        }
+        fonts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_files_are_handed_off() {
+        assert!(Options::default().hands_off());
+        let files = Options {
+            files: vec![PathBuf::from("data.csv")],
+            ..Default::default()
+        };
+        assert!(files.hands_off());
+        let modes = [
+            Options {
+                new_instance: true,
+                ..files.clone()
+            },
+            Options {
+                replay: Some(PathBuf::from("session.jsonl")),
+                ..Default::default()
+            },
+            Options {
+                fuzz: Some(10.0),
+                ..Default::default()
+            },
+            Options {
+                kiosk: true,
+                ..files.clone()
+            },
+            Options {
+                config_dir: Some(PathBuf::from("portable")),
+                ..Default::default()
+            },
+        ];
+        for options in modes {
+            assert!(!options.hands_off(), "{:?}", options);
+        }
+    }
+
+    #[test]
+    fn only_plain_instances_receive_files() {
+        assert!(Options::default().receives_hand_offs());
+        let modes = [
+            Options {
+                new_instance: true,
+                ..Default::default()
+            },
+            Options {
+                fuzz: Some(10.0),
+                ..Default::default()
+            },
+            Options {
+                replay: Some(PathBuf::from("session.jsonl")),
+                ..Default::default()
+            },
+            Options {
+                play: Some(PathBuf::from("ui.json")),
+                ..Default::default()
+            },
+            Options {
+                ui_script: Some(PathBuf::from("script.txt")),
+                ..Default::default()
+            },
+            Options {
+                config_dir: Some(PathBuf::from("portable")),
+                ..Default::default()
+            },
+        ];
+        for options in modes {
+            assert!(!options.receives_hand_offs(), "{:?}", options);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// The file next to the executable that keeps everything in the `data` directory beside it.
+const PORTABLE_MARKER: &str = "portable";
+
+/// Where everything is stored.
+struct Dirs {
+    data: Option<PathBuf>,
+    cache: Option<PathBuf>,
+}
+
+static DIRS: OnceLock<Dirs> = OnceLock::new();
+
+/// Picks the directory everything is stored in: `config_dir` from `--config-dir` if given, the
+/// `data` directory next to the executable in portable mode, or the config directory of the OS.
+/// Later calls keep the first choice.
+pub fn init(config_dir: Option<PathBuf>) {
+    DIRS.get_or_init(|| dirs(config_dir));
+}
+
+fn dirs(config_dir: Option<PathBuf>) -> Dirs {
+    match config_dir.or_else(portable_dir) {
+        // The caches stay with the rest, so that nothing is left behind in the OS directories.
+        Some(data) => Dirs {
+            cache: Some(data.join("cache")),
+            data: Some(data),
+        },
+        None => Dirs {
+            data: dirs::config_dir().map(|dir| dir.join("egui_example")),
+            cache: dirs::cache_dir().map(|dir| dir.join("egui_example")),
+        },
+    }
+}
+
+/// The `data` directory next to the executable, if there is a portable marker beside it.
+fn portable_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let exe_dir = exe.parent()?;
+    exe_dir
+        .join(PORTABLE_MARKER)
+        .exists()
+        .then(|| exe_dir.join("data"))
+}
+
+fn get() -> &'static Dirs {
+    DIRS.get_or_init(|| dirs(None))
+}
+
+/// The directory of the config file, the themes, the workspaces and the crash reports.
+pub fn data_dir() -> Option<PathBuf> {
+    get().data.clone()
+}
+
+pub fn config_file() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("config.json"))
+}
+
+pub fn themes_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("themes"))
+}
+
+pub fn workspaces_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("workspaces"))
+}
+
+/// The crash report the panic hook writes, until the next session has shown it.
+pub fn pending_crash_report() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("crash.txt"))
+}
+
+/// The thumbnails of the file browser, which can be deleted any time.
+pub fn thumbnails_dir() -> Option<PathBuf> {
+    get().cache.as_ref().map(|dir| dir.join("thumbnails"))
+}
@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+use std::io::{BufWriter, Cursor};
+use std::path::PathBuf;
+
+use ab_glyph::Font as _;
+use egui::epaint::{ClippedShape, ImageData, Mesh, RectShape, Rounding, Shape, TextShape};
+use egui::{pos2, vec2, Color32, ColorImage, Pos2, Rect, Stroke, TextureId};
+use printpdf::path::{PaintMode, WindingOrder};
+use printpdf::{
+    ColorBits, ColorSpace, Image, ImageTransform, ImageXObject, IndirectFontRef, Line, Mm,
+    PdfDocument, PdfDocumentReference, PdfLayerReference, Point, Polygon, Pt, Px, Rgb,
+};
+
+/// A4 in points, which are also the points of egui: the panel is laid out 1:1 on the page.
+const PAGE_WIDTH: f32 = 595.28;
+const PAGE_HEIGHT: f32 = 841.89;
+const MARGIN: f32 = 36.0;
+/// egui rounds the layout to pixels, and the images embedded at this resolution.
+const DPI: f32 = 300.0;
+/// Taller than any panel, so that nothing scrolls or wraps at the bottom.
+const LAYOUT_HEIGHT: f32 = 20_000.0;
+/// The segments of a rounded corner, and of a circle.
+const CORNER_STEPS: usize = 4;
+const CIRCLE_STEPS: usize = 32;
+
+/// Lays out `add_contents` below `title` on A4 pages and saves them as PDF to the documents
+/// directory, as `egui_example_<name>_<time>.pdf`.
+///
+/// The panel runs in a context of its own, with the light theme and the fonts of the example.
+/// Text is embedded with the fonts egui used for each glyph, rectangles, circles and paths stay
+/// vectors, and images the panel loads are embedded at their resolution. Custom painting has no
+/// form outside of the GPU and is left out, rotated text is printed unrotated.
+pub fn export(
+    title: &str,
+    name: &str,
+    mut add_contents: impl FnMut(&mut egui::Ui),
+) -> Result<PathBuf, String> {
+    let fonts = crate::emoji::font_definitions();
+    let ctx = egui::Context::default();
+    ctx.set_fonts(fonts.clone());
+    ctx.set_visuals(egui::Visuals::light());
+    let width = PAGE_WIDTH - 2.0 * MARGIN;
+    let mut textures = HashMap::new();
+    let mut shapes = Vec::new();
+    let mut bottom = 0.0;
+    // Grids measure their columns in the first frame and lay them out in the second one.
+    for _ in 0..2 {
+        let input = egui::RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, vec2(width, LAYOUT_HEIGHT))),
+            pixels_per_point: Some(DPI / 72.0),
+            max_texture_side: Some(16384),
+            ..Default::default()
+        };
+        let output = ctx.run(input, |ctx| {
+            egui::CentralPanel::default()
+                .frame(egui::Frame::none())
+                .show(ctx, |ui| {
+                    ui.heading(title);
+                    ui.separator();
+                    add_contents(ui);
+                    bottom = ui.min_rect().bottom();
+                });
+        });
+        for (id, delta) in output.textures_delta.set {
+            // The font atlas isn't needed, text is embedded as text. Partial updates only
+            // happen to it as well.
+            if let (ImageData::Color(image), true) = (delta.image, delta.pos.is_none()) {
+                textures.insert(id, image);
+            }
+        }
+        shapes = output.shapes;
+    }
+
+    let page_size = (Mm::from(Pt(PAGE_WIDTH)), Mm::from(Pt(PAGE_HEIGHT)));
+    let (doc, first_page, first_layer) =
+        PdfDocument::new(title, page_size.0, page_size.1, "Layer 1");
+    let mut writer = PdfWriter::new(&doc, &fonts, &textures, first_page, first_layer);
+    let band = PAGE_HEIGHT - 2.0 * MARGIN;
+    let pages = (bottom / band).ceil().max(1.0) as usize;
+    for page in 0..pages {
+        let (page_index, layer_index) = match page {
+            0 => (first_page, first_layer),
+            _ => doc.add_page(page_size.0, page_size.1, "Layer 1"),
+        };
+        writer.layer = doc.get_page(page_index).get_layer(layer_index);
+        let top = page as f32 * band;
+        writer.page(
+            Rect::from_min_max(pos2(0.0, top), pos2(width, top + band)),
+            &shapes,
+        );
+    }
+
+    let directory = dirs::document_dir().unwrap_or_else(|| PathBuf::from("."));
+    let path = directory.join(format!(
+        "egui_example_{}_{}.pdf",
+        name,
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    ));
+    let file = std::fs::File::create(&path).map_err(|err| err.to_string())?;
+    doc.save(&mut BufWriter::new(file))
+        .map_err(|err| err.to_string())?;
+    Ok(path)
+}
+
+struct PdfWriter<'a> {
+    doc: &'a PdfDocumentReference,
+    fonts: &'a egui::FontDefinitions,
+    /// The fonts that can be embedded, to find the one egui used for a glyph.
+    glyph_fonts: HashMap<&'a str, ab_glyph::FontRef<'a>>,
+    /// The fonts embedded so far.
+    pdf_fonts: HashMap<&'a str, IndirectFontRef>,
+    textures: &'a HashMap<TextureId, ColorImage>,
+    layer: PdfLayerReference,
+    /// Where the current page starts in the layout.
+    top: f32,
+}
+
+impl<'a> PdfWriter<'a> {
+    fn new(
+        doc: &'a PdfDocumentReference,
+        fonts: &'a egui::FontDefinitions,
+        textures: &'a HashMap<TextureId, ColorImage>,
+        page: printpdf::PdfPageIndex,
+        layer: printpdf::PdfLayerIndex,
+    ) -> Self {
+        // printpdf only embeds the first font of a collection.
+        let glyph_fonts = fonts
+            .font_data
+            .iter()
+            .filter(|(_, data)| data.index == 0)
+            .filter_map(|(name, data)| {
+                let font = ab_glyph::FontRef::try_from_slice(&data.font).ok()?;
+                Some((name.as_str(), font))
+            })
+            .collect();
+        Self {
+            doc,
+            fonts,
+            glyph_fonts,
+            pdf_fonts: HashMap::new(),
+            textures,
+            layer: doc.get_page(page).get_layer(layer),
+            top: 0.0,
+        }
+    }
+
+    /// Writes the shapes in `band` of the layout to the current page.
+    fn page(&mut self, band: Rect, shapes: &[ClippedShape]) {
+        self.top = band.top();
+        self.layer.save_graphics_state();
+        self.clip(band);
+        for ClippedShape(clip_rect, shape) in shapes {
+            let clip = clip_rect.intersect(band);
+            let bounds = shape.visual_bounding_rect();
+            if !bounds.intersects(clip) {
+                continue;
+            }
+            let clipped = !clip_rect.contains_rect(bounds);
+            if clipped {
+                self.layer.save_graphics_state();
+                self.clip(clip);
+            }
+            self.shape(shape);
+            if clipped {
+                self.layer.restore_graphics_state();
+            }
+        }
+        self.layer.restore_graphics_state();
+    }
+
+    /// Converts a position of the layout to the page, which starts at the bottom.
+    fn point(&self, pos: Pos2) -> Point {
+        Point {
+            x: Pt(MARGIN + pos.x),
+            y: Pt(PAGE_HEIGHT - MARGIN - (pos.y - self.top)),
+        }
+    }
+
+    fn clip(&self, rect: Rect) {
+        self.layer.add_rect(printpdf::Rect {
+            ll: self.point(rect.left_bottom()),
+            ur: self.point(rect.right_top()),
+            mode: PaintMode::Clip,
+            winding: WindingOrder::NonZero,
+        });
+    }
+
+    fn shape(&mut self, shape: &Shape) {
+        match shape {
+            Shape::Noop => {}
+            Shape::Vec(shapes) => {
+                for shape in shapes {
+                    self.shape(shape);
+                }
+            }
+            Shape::Circle(circle) => {
+                let points: Vec<Pos2> = (0..CIRCLE_STEPS)
+                    .map(|step| {
+                        let angle = TAU * step as f32 / CIRCLE_STEPS as f32;
+                        circle.center + circle.radius * vec2(angle.cos(), angle.sin())
+                    })
+                    .collect();
+                self.path(&points, true, circle.fill, circle.stroke);
+            }
+            Shape::LineSegment { points, stroke } => {
+                self.path(points, false, Color32::TRANSPARENT, *stroke)
+            }
+            Shape::Path(path) => self.path(&path.points, path.closed, path.fill, path.stroke),
+            Shape::Rect(rect) => self.rect(rect),
+            Shape::Text(text) => self.text(text),
+            Shape::Mesh(mesh) => self.mesh(mesh),
+            Shape::QuadraticBezier(bezier) => self.path(
+                &bezier.flatten(Some(0.1)),
+                bezier.closed,
+                bezier.fill,
+                bezier.stroke,
+            ),
+            Shape::CubicBezier(bezier) => self.path(
+                &bezier.flatten(Some(0.1)),
+                bezier.closed,
+                bezier.fill,
+                bezier.stroke,
+            ),
+            Shape::Callback(_) => {}
+        }
+    }
+
+    fn path(&self, points: &[Pos2], closed: bool, fill: Color32, stroke: Stroke) {
+        if points.len() < 2 {
+            return;
+        }
+        let filled = closed && fill.a() > 0;
+        let stroked = stroke.width > 0.0 && stroke.color.a() > 0;
+        if filled {
+            self.layer.set_fill_color(color(fill));
+        }
+        if stroked {
+            self.layer.set_outline_color(color(stroke.color));
+            self.layer.set_outline_thickness(stroke.width);
+        }
+        let points: Vec<(Point, bool)> =
+            points.iter().map(|pos| (self.point(*pos), false)).collect();
+        match (filled, stroked) {
+            (true, _) => self.layer.add_polygon(Polygon {
+                rings: vec![points],
+                mode: match stroked {
+                    true => PaintMode::FillStroke,
+                    false => PaintMode::Fill,
+                },
+                winding_order: WindingOrder::NonZero,
+            }),
+            (false, true) => self.layer.add_line(Line {
+                points,
+                is_closed: closed,
+            }),
+            (false, false) => {}
+        }
+    }
+
+    fn rect(&self, rect: &RectShape) {
+        let r = rect.rect;
+        let Rounding { nw, ne, sw, se } = rect.rounding;
+        // The radii are limited like egui does, to half of the shorter side.
+        let limit = 0.5 * r.width().min(r.height());
+        let corners = [
+            (nw.min(limit), pos2(r.left(), r.top()), vec2(1.0, 1.0), PI),
+            (
+                ne.min(limit),
+                pos2(r.right(), r.top()),
+                vec2(-1.0, 1.0),
+                1.5 * PI,
+            ),
+            (
+                se.min(limit),
+                pos2(r.right(), r.bottom()),
+                vec2(-1.0, -1.0),
+                0.0,
+            ),
+            (
+                sw.min(limit),
+                pos2(r.left(), r.bottom()),
+                vec2(1.0, -1.0),
+                FRAC_PI_2,
+            ),
+        ];
+        let mut points = Vec::new();
+        for (radius, corner, inwards, start) in corners {
+            if radius <= 0.0 {
+                points.push(corner);
+                continue;
+            }
+            let center = corner + radius * inwards;
+            for step in 0..=CORNER_STEPS {
+                let angle = start + FRAC_PI_2 * step as f32 / CORNER_STEPS as f32;
+                points.push(center + radius * vec2(angle.cos(), angle.sin()));
+            }
+        }
+        self.path(&points, true, rect.fill, rect.stroke);
+    }
+
+    fn text(&mut self, text: &TextShape) {
+        let galley = &text.galley;
+        for row in &galley.rows {
+            for glyph in &row.glyphs {
+                let format = &galley.job.sections[glyph.section_index as usize].format;
+                let left_top = text.pos + glyph.pos.to_vec2();
+                if format.background != Color32::TRANSPARENT {
+                    let background = Rect::from_min_size(left_top, glyph.size);
+                    let fill = RectShape::filled(background, Rounding::none(), format.background);
+                    self.rect(&fill);
+                }
+                for (line, y) in [
+                    (format.underline, left_top.y + glyph.size.y),
+                    (format.strikethrough, left_top.y + 0.5 * glyph.size.y),
+                ] {
+                    let points = [pos2(left_top.x, y), pos2(text.pos.x + glyph.max_x(), y)];
+                    self.path(&points, false, Color32::TRANSPARENT, line);
+                }
+                if glyph.chr.is_whitespace() {
+                    continue;
+                }
+                let (name, font) = match self.glyph_font(&format.font_id.family, glyph.chr) {
+                    Some(found) => found,
+                    None => continue,
+                };
+                // Placed like egui does: scaled by the tweak of the font, with the baseline one
+                // size below the top of the row and moved by the offsets of the tweak.
+                let tweak = self.fonts.font_data[name].tweak;
+                let size = format.font_id.size * tweak.scale;
+                let baseline = left_top.y + size + size * tweak.y_offset_factor + tweak.y_offset;
+                let pdf_size =
+                    size * font.units_per_em().unwrap_or(1000.0) / font.height_unscaled();
+                let pdf_font = match self.pdf_font(name) {
+                    Some(pdf_font) => pdf_font,
+                    None => continue,
+                };
+                let pos = self.point(pos2(left_top.x, baseline));
+                self.layer
+                    .set_fill_color(color(text.override_text_color.unwrap_or(format.color)));
+                self.layer.use_text(
+                    glyph.chr.to_string(),
+                    pdf_size,
+                    Mm::from(pos.x),
+                    Mm::from(pos.y),
+                    &pdf_font,
+                );
+            }
+            if text.underline.width > 0.0 {
+                let y = text.pos.y + row.rect.bottom();
+                let points = [
+                    pos2(text.pos.x + row.rect.left(), y),
+                    pos2(text.pos.x + row.rect.right(), y),
+                ];
+                self.path(&points, false, Color32::TRANSPARENT, text.underline);
+            }
+        }
+    }
+
+    /// The first font of `family` that has a glyph for `chr`, like egui falls back.
+    fn glyph_font(
+        &self,
+        family: &egui::FontFamily,
+        chr: char,
+    ) -> Option<(&'a str, ab_glyph::FontRef<'a>)> {
+        self.fonts.families.get(family)?.iter().find_map(|name| {
+            let (name, font) = self.glyph_fonts.get_key_value(name.as_str())?;
+            (font.glyph_id(chr).0 != 0).then(|| (*name, font.clone()))
+        })
+    }
+
+    /// Embeds the font on first use.
+    fn pdf_font(&mut self, name: &'a str) -> Option<IndirectFontRef> {
+        if let Some(font) = self.pdf_fonts.get(name) {
+            return Some(font.clone());
+        }
+        let data = Cursor::new(&self.fonts.font_data[name].font[..]);
+        match self.doc.add_external_font(data) {
+            Ok(font) => {
+                self.pdf_fonts.insert(name, font.clone());
+                Some(font)
+            }
+            Err(err) => {
+                eprintln!("Can't embed the font {} into the PDF: {}", name, err);
+                // The glyphs fall back to the next font of the family.
+                self.glyph_fonts.remove(name);
+                None
+            }
+        }
+    }
+
+    fn mesh(&self, mesh: &Mesh) {
+        if mesh.texture_id == TextureId::default() {
+            // Solid colored meshes sample the white texel of the font texture.
+            for triangle in mesh.indices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|corner| &mesh.vertices[triangle[corner] as usize]);
+                let fill = crate::svg_export::average([a.color, b.color, c.color]);
+                self.path(&[a.pos, b.pos, c.pos], true, fill, Stroke::none());
+            }
+            return;
+        }
+        // Images are rectangles, which are cut out of the texture by their UV coordinates.
+        let image = match self.textures.get(&mesh.texture_id) {
+            Some(image) => image,
+            None => return,
+        };
+        let bounds = Rect::from_points(&mesh.vertices.iter().map(|v| v.pos).collect::<Vec<_>>());
+        let uv = Rect::from_points(&mesh.vertices.iter().map(|v| v.uv).collect::<Vec<_>>());
+        let [width, height] = image.size;
+        let x0 = ((uv.min.x * width as f32).floor().max(0.0) as usize).min(width);
+        let y0 = ((uv.min.y * height as f32).floor().max(0.0) as usize).min(height);
+        let x1 = ((uv.max.x * width as f32).ceil().max(0.0) as usize).min(width);
+        let y1 = ((uv.max.y * height as f32).ceil().max(0.0) as usize).min(height);
+        if x1 <= x0 || y1 <= y0 || !bounds.is_positive() {
+            return;
+        }
+        let mut rgb = Vec::with_capacity((x1 - x0) * (y1 - y0) * 3);
+        for y in y0..y1 {
+            for pixel in &image.pixels[y * width + x0..y * width + x1] {
+                rgb.extend_from_slice(&over_white(*pixel));
+            }
+        }
+        let left_bottom = self.point(bounds.left_bottom());
+        Image::from(ImageXObject {
+            width: Px(x1 - x0),
+            height: Px(y1 - y0),
+            color_space: ColorSpace::Rgb,
+            bits_per_component: ColorBits::Bit8,
+            interpolate: true,
+            image_data: rgb,
+            image_filter: None,
+            smask: None,
+            clipping_bbox: None,
+        })
+        .add_to_layer(
+            self.layer.clone(),
+            ImageTransform {
+                translate_x: Some(Mm::from(left_bottom.x)),
+                translate_y: Some(Mm::from(left_bottom.y)),
+                rotate: None,
+                scale_x: Some(bounds.width() / (x1 - x0) as f32),
+                scale_y: Some(bounds.height() / (y1 - y0) as f32),
+                // One pixel is one point before scaling.
+                dpi: Some(72.0),
+            },
+        );
+    }
+}
+
+/// The premultiplied `color` on the white paper, PDF 1.3 has no transparency.
+fn over_white(color: Color32) -> [u8; 3] {
+    let white = 255 - color.a();
+    [color.r(), color.g(), color.b()].map(|channel| channel.saturating_add(white))
+}
+
+fn color(color: Color32) -> printpdf::Color {
+    let [r, g, b] = over_white(color).map(|channel| channel as f32 / 255.0);
+    printpdf::Color::Rgb(Rgb::new(r, g, b, None))
+}
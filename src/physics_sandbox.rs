@@ -0,0 +1,377 @@
+use std::time::{Duration, Instant};
+
+use rapier2d::prelude::*;
+
+use crate::sprite_batch::{Camera, Sprite, SpriteBatch};
+use crate::user_textures::UserTextures;
+
+/// The pixels per side of a shape in the atlas.
+const SHAPE_PIXELS: usize = 32;
+/// How much a point of scrolling zooms in or out.
+const ZOOM_PER_SCROLL_POINT: f32 = 0.002;
+/// The most steps simulated per frame, so that a slow frame doesn't make the next one slower.
+const MAX_STEPS_PER_FRAME: u32 = 4;
+/// Bodies that fall further than this below the arena are removed, in meters.
+const KILL_DEPTH: f32 = 50.0;
+
+/// The shape the next click spawns.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Box,
+    Ball,
+}
+
+/// A 2D physics sandbox: clicks spawn boxes and balls into an arena, rapier simulates them with
+/// a fixed time step, and a [`SpriteBatch`] draws them. The y axis points downwards, like on the
+/// screen.
+pub struct PhysicsSandbox {
+    pipeline: PhysicsPipeline,
+    parameters: IntegrationParameters,
+    islands: IslandManager,
+    broad_phase: BroadPhase,
+    narrow_phase: NarrowPhase,
+    bodies: RigidBodySet,
+    colliders: ColliderSet,
+    impulse_joints: ImpulseJointSet,
+    multibody_joints: MultibodyJointSet,
+    ccd_solver: CCDSolver,
+    /// Downwards, in m/s².
+    gravity: f32,
+    restitution: f32,
+    shape: Shape,
+    paused: bool,
+    /// How many bodies were spawned, which seeds their sizes and colors.
+    spawned: u32,
+    /// The simulation time that is due but wasn't simulated yet, in seconds.
+    lag: f64,
+    last_time: Option<f64>,
+    /// How long the physics of the last frame took.
+    step_time: Duration,
+    /// The position in the center of the viewport, in meters.
+    center: egui::Pos2,
+    /// Points per meter.
+    zoom: f32,
+    /// Created when the sandbox is first shown.
+    batch: Option<SpriteBatch>,
+}
+
+impl Default for PhysicsSandbox {
+    fn default() -> Self {
+        let mut colliders = ColliderSet::new();
+        // The floor and the walls of the arena.
+        colliders.insert(
+            ColliderBuilder::cuboid(16.0, 0.5)
+                .translation(vector![0.0, 8.0])
+                .build(),
+        );
+        for x in [-16.5, 16.5] {
+            colliders.insert(
+                ColliderBuilder::cuboid(0.5, 6.0)
+                    .translation(vector![x, 2.5])
+                    .build(),
+            );
+        }
+        Self {
+            pipeline: PhysicsPipeline::new(),
+            parameters: IntegrationParameters::default(),
+            islands: IslandManager::new(),
+            broad_phase: BroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            bodies: RigidBodySet::new(),
+            colliders,
+            impulse_joints: ImpulseJointSet::new(),
+            multibody_joints: MultibodyJointSet::new(),
+            ccd_solver: CCDSolver::new(),
+            gravity: 9.81,
+            restitution: 0.3,
+            shape: Shape::Box,
+            paused: false,
+            spawned: 0,
+            lag: 0.0,
+            last_time: None,
+            step_time: Duration::ZERO,
+            center: egui::pos2(0.0, 2.0),
+            zoom: 20.0,
+            batch: None,
+        }
+    }
+}
+
+impl PhysicsSandbox {
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool, textures: &mut UserTextures) {
+        if !*open {
+            self.last_time = None;
+            return;
+        }
+        self.simulate(ctx);
+        egui::Window::new("🎱 Physics sandbox")
+            .open(open)
+            .default_size([640.0, 480.0])
+            .show(ctx, |ui| self.ui(ui, textures));
+    }
+
+    /// Catches the simulation up with the time that passed since the last frame.
+    fn simulate(&mut self, ctx: &egui::Context) {
+        let time = ctx.input().time;
+        if let (Some(last_time), false) = (self.last_time, self.paused) {
+            self.lag += time - last_time;
+            let steps = (self.lag / self.parameters.dt as f64) as u32;
+            self.lag -= steps as f64 * self.parameters.dt as f64;
+            if steps > MAX_STEPS_PER_FRAME {
+                self.lag = 0.0;
+            }
+            let start = Instant::now();
+            for _ in 0..steps.min(MAX_STEPS_PER_FRAME) {
+                self.step();
+            }
+            self.step_time = start.elapsed();
+            ctx.request_repaint();
+        }
+        self.last_time = Some(time);
+    }
+
+    fn step(&mut self) {
+        self.pipeline.step(
+            &vector![0.0, self.gravity],
+            &self.parameters,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            &mut self.ccd_solver,
+            None,
+            &(),
+            &(),
+        );
+        let fallen: Vec<RigidBodyHandle> = self
+            .bodies
+            .iter()
+            .filter(|(_, body)| body.translation().y > KILL_DEPTH)
+            .map(|(handle, _)| handle)
+            .collect();
+        for handle in fallen {
+            self.remove(handle);
+        }
+    }
+
+    fn remove(&mut self, handle: RigidBodyHandle) {
+        self.bodies.remove(
+            handle,
+            &mut self.islands,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            true,
+        );
+    }
+
+    /// Spawns the selected shape at `position`, in meters.
+    fn spawn(&mut self, position: egui::Pos2) {
+        self.spawned += 1;
+        // Cheap pseudo random numbers from the spawn count.
+        let random = |salt: u32| {
+            let hash = (self.spawned.wrapping_mul(2654435761) ^ salt).wrapping_mul(2246822519);
+            (hash >> 8) as f32 / (1 << 24) as f32
+        };
+        let color = egui::Color32::from(egui::color::Hsva::new(random(1), 0.6, 0.9, 1.0));
+        let body = RigidBodyBuilder::dynamic()
+            .translation(vector![position.x, position.y])
+            .rotation(random(2) * std::f32::consts::PI)
+            .user_data(u32::from_le_bytes(color.to_array()) as u128)
+            .build();
+        let size = 0.4 + random(3) * 0.6;
+        let collider = match self.shape {
+            Shape::Box => ColliderBuilder::cuboid(size / 2.0, size / 2.0),
+            Shape::Ball => ColliderBuilder::ball(size / 2.0),
+        }
+        .restitution(self.restitution)
+        .build();
+        let handle = self.bodies.insert(body);
+        self.colliders
+            .insert_with_parent(collider, handle, &mut self.bodies);
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, textures: &mut UserTextures) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.shape, Shape::Box, "⬛ Box");
+            ui.selectable_value(&mut self.shape, Shape::Ball, "⚫ Ball");
+            ui.separator();
+            ui.toggle_value(&mut self.paused, "⏸ Pause");
+            if ui
+                .add_enabled(self.paused, egui::Button::new("⏭ Step"))
+                .clicked()
+            {
+                self.step();
+            }
+            if ui.button("Spawn 100").clicked() {
+                for index in 0..100 {
+                    let column = (index % 20) as f32 - 9.5;
+                    self.spawn(egui::pos2(column * 1.2, -4.0 - (index / 20) as f32 * 1.2));
+                }
+            }
+            if ui.button("Clear").clicked() {
+                let handles: Vec<RigidBodyHandle> =
+                    self.bodies.iter().map(|(handle, _)| handle).collect();
+                for handle in handles {
+                    self.remove(handle);
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::Slider::new(&mut self.gravity, -20.0..=20.0)
+                    .text("Gravity")
+                    .suffix(" m/s²"),
+            );
+            let restitution =
+                ui.add(egui::Slider::new(&mut self.restitution, 0.0..=1.0).text("Restitution"));
+            if restitution.changed() {
+                for (_, collider) in self.colliders.iter_mut() {
+                    collider.set_restitution(self.restitution);
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(format!("{} bodies", self.bodies.len()));
+            ui.separator();
+            ui.label(format!(
+                "Physics {:.2} ms",
+                self.step_time.as_secs_f64() * 1000.0
+            ));
+            ui.separator();
+            ui.label(format!(
+                "Frame {:.1} ms",
+                ui.input().unstable_dt as f64 * 1000.0
+            ));
+            ui.separator();
+            ui.weak("Click spawns, right drag pans, scrolling zooms");
+        });
+
+        let (rect, response) =
+            ui.allocate_exact_size(ui.available_size(), egui::Sense::click_and_drag());
+        if response.dragged_by(egui::PointerButton::Secondary)
+            || response.dragged_by(egui::PointerButton::Middle)
+        {
+            self.center -= response.drag_delta() / self.zoom;
+        }
+        let to_world =
+            |zoom: f32, center: egui::Pos2, pos: egui::Pos2| center + (pos - rect.center()) / zoom;
+        let scroll = ui.input().scroll_delta.y;
+        if let (Some(pointer), true) = (response.hover_pos(), scroll != 0.0) {
+            // Keeps the point under the pointer in place.
+            let zoom = (self.zoom * (scroll * ZOOM_PER_SCROLL_POINT).exp()).clamp(2.0, 200.0);
+            self.center +=
+                to_world(self.zoom, self.center, pointer) - to_world(zoom, self.center, pointer);
+            self.zoom = zoom;
+        }
+        if let (true, Some(pointer)) = (response.clicked(), response.interact_pointer_pos()) {
+            self.spawn(to_world(self.zoom, self.center, pointer));
+        }
+
+        let sprites: Vec<Sprite> = self
+            .colliders
+            .iter()
+            .filter_map(|(_, collider)| {
+                let (size, uv) = match collider.shape().as_typed_shape() {
+                    TypedShape::Cuboid(cuboid) => (cuboid.half_extents * 2.0, shape_uv(Shape::Box)),
+                    TypedShape::Ball(ball) => (
+                        vector![ball.radius, ball.radius] * 2.0,
+                        shape_uv(Shape::Ball),
+                    ),
+                    _ => return None,
+                };
+                let tint = match collider.parent() {
+                    Some(body) => {
+                        let [r, g, b, a] = (self.bodies[body].user_data as u32).to_le_bytes();
+                        egui::Color32::from_rgba_premultiplied(r, g, b, a)
+                    }
+                    None => egui::Color32::GRAY,
+                };
+                let position = collider.position();
+                Some(Sprite {
+                    rect: egui::Rect::from_center_size(
+                        egui::pos2(position.translation.x, position.translation.y),
+                        egui::vec2(size.x, size.y),
+                    ),
+                    uv,
+                    tint: tint.into(),
+                    rotation: position.rotation.angle(),
+                })
+            })
+            .collect();
+
+        let batch = self.batch.get_or_insert_with(|| {
+            let atlas = crate::user_textures::upload(
+                textures.device,
+                textures.queue,
+                "physics_atlas",
+                &atlas_image(),
+            );
+            SpriteBatch::new(textures.device, &atlas)
+        });
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        let size = (rect.size() * pixels_per_point).round();
+        let texture = batch.render(
+            textures,
+            [size.x as u32, size.y as u32],
+            Camera {
+                center: self.center,
+                zoom: self.zoom * pixels_per_point,
+            },
+            wgpu::Color {
+                r: 0.04,
+                g: 0.04,
+                b: 0.05,
+                a: 1.0,
+            },
+            &sprites,
+        );
+        ui.painter().add(egui::Shape::image(
+            texture,
+            rect,
+            egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0)),
+            egui::Color32::WHITE,
+        ));
+    }
+}
+
+/// Where `shape` is in the atlas.
+fn shape_uv(shape: Shape) -> egui::Rect {
+    let x = match shape {
+        Shape::Box => 0.0,
+        Shape::Ball => 0.5,
+    };
+    egui::Rect::from_min_max(egui::pos2(x, 0.0), egui::pos2(x + 0.5, 1.0))
+}
+
+/// A white box and a white ball side by side, with darker marks that show how they rotate.
+fn atlas_image() -> egui::ColorImage {
+    let mut image =
+        egui::ColorImage::new([SHAPE_PIXELS * 2, SHAPE_PIXELS], egui::Color32::TRANSPARENT);
+    let radius = SHAPE_PIXELS as f32 / 2.0;
+    for y in 0..SHAPE_PIXELS {
+        for x in 0..SHAPE_PIXELS {
+            let offset = egui::vec2(x as f32 + 0.5 - radius, y as f32 + 0.5 - radius);
+            let border = x < 2 || y < 2 || x >= SHAPE_PIXELS - 2 || y >= SHAPE_PIXELS - 2;
+            image.pixels[y * image.size[0] + x] = egui::Color32::from_gray(match border {
+                true => 160,
+                false => 255,
+            });
+            // Antialiased, and premultiplied like every texture egui shows.
+            let coverage = (radius - offset.length()).clamp(0.0, 1.0);
+            let spoke = offset.x > 0.0 && offset.y.abs() < 1.5;
+            let gray = if spoke { 160.0 } else { 255.0 } * coverage;
+            image.pixels[y * image.size[0] + SHAPE_PIXELS + x] =
+                egui::Color32::from_rgba_premultiplied(
+                    gray as u8,
+                    gray as u8,
+                    gray as u8,
+                    (coverage * 255.0) as u8,
+                );
+        }
+    }
+    image
+}
@@ -0,0 +1,507 @@
+use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
+use egui_winit_platform::{Platform, PlatformDescriptor};
+use winit::event::WindowEvent;
+use winit::event_loop::EventLoopWindowTarget;
+use winit::monitor::MonitorHandle;
+use winit::window::{Fullscreen, Window, WindowBuilder, WindowId};
+
+use crate::Event;
+
+/// The slides until the user writes their own.
+const DEFAULT_SLIDES: &str = "\
+# egui on wgpu and winit
+This screen is a second window, drawn by the same device as the main one.
+---
+# Two modes
+- Mirror shows the main window, scaled to fit
+- Slides shows its own egui context, like this one
+---
+# Controls
+- Previous and Next in the presentation window of the main one
+- Page Up and Page Down, or the arrow keys on this screen
+- A click on this screen goes to the next slide";
+
+/// What the presentation screen shows.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// The main UI, scaled to fit the screen.
+    Mirror,
+    Slides,
+}
+
+/// What the controls want the runner to do with the presentation window.
+pub enum Request {
+    Open(MonitorHandle),
+    Close,
+}
+
+/// One line of a slide.
+#[derive(Debug, PartialEq)]
+enum Line<'a> {
+    Title(&'a str),
+    Bullet(&'a str),
+    Text(&'a str),
+    Space,
+}
+
+/// Splits `source` into slides at lines of `---`. In a slide, `# ` starts the title and `- ` a
+/// bullet point.
+fn slides(source: &str) -> Vec<Vec<Line<'_>>> {
+    let mut slides = vec![Vec::new()];
+    for line in source.lines() {
+        let line = line.trim_end();
+        let parsed = if line == "---" {
+            slides.push(Vec::new());
+            continue;
+        } else if let Some(title) = line.strip_prefix("# ") {
+            Line::Title(title)
+        } else if let Some(bullet) = line.strip_prefix("- ") {
+            Line::Bullet(bullet)
+        } else if line.trim().is_empty() {
+            Line::Space
+        } else {
+            Line::Text(line)
+        };
+        slides.last_mut().unwrap().push(parsed);
+    }
+    slides.retain(|slide| slide.iter().any(|line| *line != Line::Space));
+    slides
+}
+
+/// The controls of the presentation in the main window, and the slides it shows.
+pub struct Presentation {
+    mode: Mode,
+    /// The monitor to present on. Defaults to one the main window isn't on.
+    monitor: Option<MonitorHandle>,
+    slide: usize,
+    /// Whether the presentation window is open, as far as the controls know.
+    open: bool,
+    request: Option<Request>,
+    error: Option<String>,
+}
+
+impl Default for Presentation {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Slides,
+            monitor: None,
+            slide: 0,
+            open: false,
+            request: None,
+            error: None,
+        }
+    }
+}
+
+impl Presentation {
+    /// What the runner should do with the presentation window.
+    pub fn take_request(&mut self) -> Option<Request> {
+        self.request.take()
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Reports how opening the presentation window went.
+    pub fn opened(&mut self, result: Result<(), String>) {
+        self.open = result.is_ok();
+        self.error = result.err();
+    }
+
+    /// The presentation window was closed from outside, e.g. by the window manager.
+    pub fn closed(&mut self) {
+        self.open = false;
+    }
+
+    /// Moves `offset` slides on, while the slides are presented.
+    pub fn step(&mut self, offset: isize, source: &Option<String>) {
+        if !self.open || self.mode != Mode::Slides {
+            return;
+        }
+        let last = slides(source.as_deref().unwrap_or(DEFAULT_SLIDES))
+            .len()
+            .saturating_sub(1);
+        self.slide = self.slide.saturating_add_signed(offset).min(last);
+    }
+
+    /// Shows the presentation controls. `slides` is the source of the slides, `None` while
+    /// they are the built-in ones.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        open: &mut bool,
+        window: &Window,
+        slides: &mut Option<String>,
+    ) {
+        egui::Window::new("📽 Presentation")
+            .open(open)
+            .default_width(360.0)
+            .show(ctx, |ui| self.ui(ui, window, slides));
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, window: &Window, source: &mut Option<String>) {
+        let monitors: Vec<MonitorHandle> = window.available_monitors().collect();
+        if self.monitor.is_none() {
+            let current = window.current_monitor();
+            self.monitor = monitors
+                .iter()
+                .find(|monitor| Some(*monitor) != current.as_ref())
+                .or_else(|| monitors.first())
+                .cloned();
+        }
+        let monitor = match self.monitor.clone() {
+            Some(monitor) => monitor,
+            None => {
+                ui.label("No monitor found.");
+                return;
+            }
+        };
+
+        egui::Grid::new("presentation_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Monitor");
+                ui.add_enabled_ui(!self.open, |ui| {
+                    egui::ComboBox::from_id_source("presentation_monitor")
+                        .width(240.0)
+                        .selected_text(crate::display_mode::monitor_label(&monitor))
+                        .show_ui(ui, |ui| {
+                            for candidate in &monitors {
+                                let label = crate::display_mode::monitor_label(candidate);
+                                if ui.selectable_label(*candidate == monitor, label).clicked() {
+                                    self.monitor = Some(candidate.clone());
+                                }
+                            }
+                        });
+                });
+                ui.end_row();
+
+                ui.label("Shows");
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.mode, Mode::Slides, "Slides");
+                    ui.radio_value(&mut self.mode, Mode::Mirror, "This window");
+                });
+                ui.end_row();
+            });
+        if monitors.len() < 2 {
+            ui.weak("Only one monitor is connected, the presentation covers this window.");
+        }
+
+        ui.horizontal(|ui| match self.open {
+            false => {
+                if ui.button("▶ Present").clicked() {
+                    self.request = Some(Request::Open(monitor));
+                }
+            }
+            true => {
+                if ui.button("⏹ Stop").clicked() {
+                    self.request = Some(Request::Close);
+                    self.open = false;
+                }
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        ui.separator();
+        let mut text = source.as_deref().unwrap_or(DEFAULT_SLIDES).to_owned();
+        let count = slides(&text).len();
+        self.slide = self.slide.min(count.saturating_sub(1));
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(self.slide > 0, egui::Button::new("⏴ Previous"))
+                .clicked()
+            {
+                self.slide -= 1;
+            }
+            ui.label(format!("Slide {} of {}", self.slide + 1, count));
+            if ui
+                .add_enabled(self.slide + 1 < count, egui::Button::new("Next ⏵"))
+                .clicked()
+            {
+                self.slide += 1;
+            }
+        });
+        egui::CollapsingHeader::new("Edit the slides").show(ui, |ui| {
+            ui.label("Slides are separated by ---, # starts the title and - a bullet point.");
+            let response = ui.add(
+                egui::TextEdit::multiline(&mut text)
+                    .code_editor()
+                    .desired_rows(10)
+                    .desired_width(f32::INFINITY),
+            );
+            if response.changed() {
+                *source = Some(text);
+            }
+            if ui
+                .add_enabled(source.is_some(), egui::Button::new("⟲ Built-in slides"))
+                .clicked()
+            {
+                *source = None;
+            }
+        });
+    }
+
+    /// Draws the current slide on the presentation screen, and moves on with its keys and
+    /// clicks.
+    pub fn slide_ui(&mut self, ctx: &egui::Context, source: &Option<String>) {
+        let slides = slides(source.as_deref().unwrap_or(DEFAULT_SLIDES));
+        {
+            let input = ctx.input();
+            let forward = [egui::Key::PageDown, egui::Key::ArrowRight, egui::Key::Space];
+            let back = [egui::Key::PageUp, egui::Key::ArrowLeft];
+            if forward.iter().any(|key| input.key_pressed(*key)) || input.pointer.any_click() {
+                self.slide += 1;
+            }
+            if back.iter().any(|key| input.key_pressed(*key)) {
+                self.slide = self.slide.saturating_sub(1);
+            }
+        }
+        self.slide = self.slide.min(slides.len().saturating_sub(1));
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // Sized for the screen, so that the slides read the same on every projector.
+            let size = ui.available_height() / 14.0;
+            let margin = ui.available_width() / 12.0;
+            ui.add_space(margin / 2.0);
+            ui.horizontal(|ui| {
+                ui.add_space(margin);
+                ui.vertical(|ui| {
+                    ui.set_max_width(ui.available_width() - margin);
+                    for line in slides.get(self.slide).into_iter().flatten() {
+                        match line {
+                            Line::Title(title) => {
+                                ui.label(egui::RichText::new(*title).size(size * 1.6).strong());
+                                ui.add_space(size / 2.0);
+                            }
+                            Line::Bullet(text) => {
+                                ui.label(egui::RichText::new(format!("• {}", text)).size(size));
+                            }
+                            Line::Text(text) => {
+                                ui.label(egui::RichText::new(*text).size(size));
+                            }
+                            Line::Space => ui.add_space(size / 2.0),
+                        }
+                    }
+                });
+            });
+            let count = format!("{} / {}", self.slide + 1, slides.len());
+            ui.painter().text(
+                ui.max_rect().right_bottom(),
+                egui::Align2::RIGHT_BOTTOM,
+                count,
+                egui::FontId::proportional(size / 2.0),
+                ui.visuals().weak_text_color(),
+            );
+        });
+    }
+}
+
+/// The borderless fullscreen window on another monitor, with the surface and the egui context
+/// of the slides. Rendered with the device of the main window.
+pub struct Screen {
+    window: Window,
+    surface: wgpu::Surface,
+    surface_config: wgpu::SurfaceConfiguration,
+    platform: Platform,
+    rpass: RenderPass,
+}
+
+impl Screen {
+    pub fn open(
+        target: &EventLoopWindowTarget<Event>,
+        monitor: MonitorHandle,
+        instance: &wgpu::Instance,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+    ) -> Result<Self, String> {
+        let window = WindowBuilder::new()
+            .with_title("Presentation")
+            .with_decorations(false)
+            .with_fullscreen(Some(Fullscreen::Borderless(Some(monitor))))
+            .build(target)
+            .map_err(|err| err.to_string())?;
+        let size = window.inner_size();
+        let surface = unsafe { instance.create_surface(&window) };
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+        surface.configure(device, &surface_config);
+        let mut platform = Platform::new(PlatformDescriptor {
+            physical_width: size.width,
+            physical_height: size.height,
+            scale_factor: window.scale_factor(),
+            font_definitions: crate::emoji::font_definitions(),
+            style: Default::default(),
+        });
+        platform.raw_input_mut().max_texture_side =
+            Some(device.limits().max_texture_dimension_2d as usize);
+        Ok(Self {
+            window,
+            surface,
+            surface_config,
+            platform,
+            rpass: RenderPass::new(device, format, 1),
+        })
+    }
+
+    pub fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    /// Handles an event of the presentation window. Returns `false` once it should close.
+    pub fn handle_event(
+        &mut self,
+        event: &winit::event::Event<Event>,
+        device: &wgpu::Device,
+    ) -> bool {
+        self.platform.handle_event(event);
+        if let winit::event::Event::WindowEvent { event, .. } = event {
+            match event {
+                WindowEvent::CloseRequested | WindowEvent::Destroyed => return false,
+                WindowEvent::Resized(size) if size.width > 0 && size.height > 0 => {
+                    self.surface_config.width = size.width;
+                    self.surface_config.height = size.height;
+                    self.surface.configure(device, &self.surface_config);
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        winit::event::KeyboardInput {
+                            virtual_keycode: Some(winit::event::VirtualKeyCode::Escape),
+                            state: winit::event::ElementState::Pressed,
+                            ..
+                        },
+                    ..
+                } => return false,
+                _ => {}
+            }
+        }
+        true
+    }
+
+    /// Runs a frame of the slides and presents it. Returns when egui wants the next one.
+    pub fn render_slides(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        time: f64,
+        style: std::sync::Arc<egui::Style>,
+        clear_color: wgpu::Color,
+        ui: impl FnOnce(&egui::Context),
+    ) -> std::time::Duration {
+        self.platform.update_time(time);
+        self.platform.context().set_style(style);
+        self.platform.begin_frame();
+        ui(&self.platform.context());
+        let output = self.platform.end_frame(Some(&self.window));
+        let jobs = self.platform.context().tessellate(output.shapes);
+        let frame = match self.surface.get_current_texture() {
+            Ok(frame) => frame,
+            Err(err) => {
+                eprintln!("Dropped presentation frame with error: {}", err);
+                return output.repaint_after;
+            }
+        };
+        let view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let screen_descriptor = ScreenDescriptor {
+            physical_width: self.surface_config.width,
+            physical_height: self.surface_config.height,
+            scale_factor: self.window.scale_factor() as f32,
+        };
+        self.rpass
+            .add_textures(device, queue, &output.textures_delta)
+            .expect("add texture ok");
+        self.rpass
+            .update_buffers(device, queue, &jobs, &screen_descriptor);
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("presentation"),
+        });
+        self.rpass
+            .execute(
+                &mut encoder,
+                &view,
+                &jobs,
+                &screen_descriptor,
+                Some(clear_color),
+            )
+            .expect("Can't record the presentation pass");
+        queue.submit(Some(encoder.finish()));
+        frame.present();
+        self.rpass
+            .remove_textures(output.textures_delta)
+            .expect("remove texture ok");
+        output.repaint_after
+    }
+
+    /// Draws the layers of the main UI again, scaled so that `size` in points fits the screen.
+    /// The render passes still have the textures of the frame, but their buffers get the
+    /// screen size of the presentation.
+    pub fn mirror(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layers: &mut [(&mut RenderPass, &[egui::ClippedPrimitive])],
+        size: egui::Vec2,
+        clear_color: wgpu::Color,
+    ) {
+        let frame = match self.surface.get_current_texture() {
+            Ok(frame) => frame,
+            Err(err) => {
+                eprintln!("Dropped presentation frame with error: {}", err);
+                return;
+            }
+        };
+        let view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let [width, height] = [self.surface_config.width, self.surface_config.height];
+        let screen_descriptor = ScreenDescriptor {
+            physical_width: width,
+            physical_height: height,
+            scale_factor: (width as f32 / size.x).min(height as f32 / size.y),
+        };
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("presentation mirror"),
+        });
+        for (index, (rpass, jobs)) in layers.iter_mut().enumerate() {
+            rpass.update_buffers(device, queue, jobs, &screen_descriptor);
+            rpass
+                .execute(
+                    &mut encoder,
+                    &view,
+                    jobs,
+                    &screen_descriptor,
+                    (index == 0).then_some(clear_color),
+                )
+                .expect("Can't record the presentation pass");
+        }
+        queue.submit(Some(encoder.finish()));
+        frame.present();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_slides() {
+        let slides = slides("# One\ntext\n\n---\n\n---\n# Two\n- point");
+        assert_eq!(
+            slides,
+            [
+                vec![Line::Title("One"), Line::Text("text"), Line::Space],
+                vec![Line::Title("Two"), Line::Bullet("point")],
+            ]
+        );
+        assert_eq!(super::slides(DEFAULT_SLIDES).len(), 3);
+    }
+}
@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::Settings;
+use crate::options::Theme;
+
+/// wgpu only serializes its present modes with the features of its API traces.
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "wgpu::PresentMode")]
+enum PresentModeDef {
+    AutoVsync,
+    AutoNoVsync,
+    Fifo,
+    FifoRelaxed,
+    Immediate,
+    Mailbox,
+}
+
+/// A named bundle of the renderer settings and the theme, switched as a whole.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    /// Falls back to Fifo on surfaces that don't support it.
+    #[serde(with = "PresentModeDef")]
+    pub present_mode: wgpu::PresentMode,
+    pub frame_rate_limit: Option<u32>,
+    pub msaa_samples: u32,
+    /// `None` keeps the colors of the style.
+    pub theme: Option<Theme>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            present_mode: wgpu::PresentMode::Fifo,
+            frame_rate_limit: None,
+            msaa_samples: 1,
+            theme: None,
+        }
+    }
+}
+
+impl Profile {
+    /// The current renderer settings under `name`.
+    fn capture(
+        name: &str,
+        settings: &Settings,
+        present_mode: wgpu::PresentMode,
+        theme: Option<Theme>,
+    ) -> Self {
+        Self {
+            name: name.to_owned(),
+            present_mode,
+            frame_rate_limit: settings.frame_rate_limit,
+            msaa_samples: settings.msaa_samples,
+            theme,
+        }
+    }
+
+    fn summary(&self) -> String {
+        let frame_rate = match self.frame_rate_limit {
+            Some(limit) => format!("{} fps", limit),
+            None => "unlimited".to_owned(),
+        };
+        let msaa = match self.msaa_samples {
+            1 => "no MSAA".to_owned(),
+            samples => format!("{}× MSAA", samples),
+        };
+        let theme = match self.theme {
+            Some(theme) => format!("{:?} theme", theme),
+            None => "style colors".to_owned(),
+        };
+        format!(
+            "{:?}, {}, {}, {}",
+            self.present_mode, frame_rate, msaa, theme
+        )
+    }
+}
+
+/// The profiles of the config, and the one switched to last.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profiles {
+    pub list: Vec<Profile>,
+    pub active: Option<String>,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Self {
+            list: vec![
+                Profile {
+                    name: "Battery saver".to_owned(),
+                    present_mode: wgpu::PresentMode::Fifo,
+                    frame_rate_limit: Some(30),
+                    msaa_samples: 1,
+                    // Dark pixels take less power on OLED screens.
+                    theme: Some(Theme::Dark),
+                },
+                Profile {
+                    name: "Max quality".to_owned(),
+                    present_mode: wgpu::PresentMode::Mailbox,
+                    frame_rate_limit: None,
+                    msaa_samples: 4,
+                    theme: None,
+                },
+            ],
+            active: None,
+        }
+    }
+}
+
+impl Profiles {
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.list.iter().find(|profile| profile.name == name)
+    }
+
+    pub fn active(&self) -> Option<&Profile> {
+        self.active.as_deref().and_then(|name| self.get(name))
+    }
+}
+
+/// The profiles in the settings: switches between them, saves the current settings as one and
+/// deletes them.
+#[derive(Default)]
+pub struct ProfilesEditor {
+    name: String,
+    /// The theme of the next saved profile.
+    theme: Option<Theme>,
+}
+
+impl ProfilesEditor {
+    /// Returns the profile the user switched to.
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        profiles: &mut Profiles,
+        settings: &Settings,
+        present_mode: wgpu::PresentMode,
+    ) -> Option<String> {
+        let mut switched = None;
+        let mut deleted = None;
+        for profile in &profiles.list {
+            ui.horizontal(|ui| {
+                let active = profiles.active.as_ref() == Some(&profile.name);
+                if ui.radio(active, &profile.name).clicked() {
+                    switched = Some(profile.name.clone());
+                }
+                ui.weak(profile.summary());
+                if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                    deleted = Some(profile.name.clone());
+                }
+            });
+        }
+        if let Some(name) = deleted {
+            profiles.list.retain(|profile| profile.name != name);
+            if profiles.active.as_ref() == Some(&name) {
+                profiles.active = None;
+            }
+        }
+
+        // The settings can change after switching, which the profile doesn't follow on its own.
+        if let Some(active) = profiles.active().cloned() {
+            let current = Profile::capture(&active.name, settings, present_mode, active.theme);
+            if current != active {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!("⚠ The settings differ from {}", active.name),
+                    );
+                    if ui.button("Update the profile").clicked() {
+                        if let Some(profile) = profiles
+                            .list
+                            .iter_mut()
+                            .find(|profile| profile.name == active.name)
+                        {
+                            *profile = current;
+                        }
+                    }
+                });
+            }
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.name)
+                    .hint_text("Name")
+                    .desired_width(120.0),
+            );
+            egui::ComboBox::from_id_source("profile_theme")
+                .selected_text(match self.theme {
+                    Some(theme) => format!("{:?}", theme),
+                    None => "Style colors".to_owned(),
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.theme, None, "Style colors");
+                    ui.selectable_value(&mut self.theme, Some(Theme::Dark), "Dark");
+                    ui.selectable_value(&mut self.theme, Some(Theme::Light), "Light");
+                });
+            let name = self.name.trim();
+            if ui
+                .add_enabled(!name.is_empty(), egui::Button::new("💾 Save current"))
+                .on_hover_text("Saves the present mode, frame rate limit and MSAA as a profile")
+                .clicked()
+            {
+                let profile = Profile::capture(name, settings, present_mode, self.theme);
+                match profiles.list.iter_mut().find(|other| other.name == name) {
+                    Some(other) => *other = profile,
+                    None => profiles.list.push(profile),
+                }
+                switched = Some(name.to_owned());
+                self.name.clear();
+            }
+        });
+        switched
+    }
+
+    /// The profiles as radio buttons, for the menu.
+    pub fn menu(ui: &mut egui::Ui, profiles: &Profiles) -> Option<String> {
+        let mut switched = None;
+        if profiles.list.is_empty() {
+            ui.weak("No profiles, see the settings");
+        }
+        for profile in &profiles.list {
+            let active = profiles.active.as_ref() == Some(&profile.name);
+            if ui
+                .radio(active, &profile.name)
+                .on_hover_text(profile.summary())
+                .clicked()
+            {
+                switched = Some(profile.name.clone());
+                ui.close_menu();
+            }
+        }
+        switched
+    }
+}
@@ -0,0 +1,232 @@
+use std::num::NonZeroU32;
+
+use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
+
+use crate::render_graph::{self, AttachmentDesc, RenderGraph};
+
+/// The attachment the frame is rendered into a second time. It stays around for the next
+/// capture, the color picker samples in every frame.
+const READBACK: &str = "readback";
+
+/// Everything needed to render the current frame a second time, into a texture that can be
+/// read back. The surface texture itself can't be copied from on every platform.
+pub struct Frame<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub graph: &'a mut RenderGraph,
+    /// The render passes with their paint jobs, from bottom to top.
+    pub layers: &'a [(&'a RenderPass, &'a [egui::ClippedPrimitive])],
+    pub screen_descriptor: &'a ScreenDescriptor,
+    pub format: wgpu::TextureFormat,
+    pub clear_color: wgpu::Color,
+}
+
+/// A rectangle of a frame in physical pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region {
+    pub origin: [u32; 2],
+    pub size: [u32; 2],
+}
+
+impl Frame<'_> {
+    /// Renders the frame offscreen and reads `region` back as RGBA. Needs to be called before
+    /// the textures of the frame are freed.
+    ///
+    /// Returns the region clamped to the frame size together with the pixels.
+    pub fn read_region(&mut self, region: Region) -> Result<(Region, Vec<u8>), String> {
+        let width = self.screen_descriptor.physical_width;
+        let height = self.screen_descriptor.physical_height;
+        let origin = [
+            region.origin[0].min(width - 1),
+            region.origin[1].min(height - 1),
+        ];
+        // The window might have gotten smaller since the region was selected.
+        let size = [
+            region.size[0].min(width - origin[0]),
+            region.size[1].min(height - origin[1]),
+        ];
+
+        self.graph.declare(
+            READBACK,
+            AttachmentDesc {
+                format: self.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            },
+        );
+        let encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("readback_render_encoder"),
+            });
+        let passes = render_graph::egui_passes(
+            self.layers,
+            READBACK,
+            self.clear_color,
+            self.screen_descriptor,
+        );
+        let encoders =
+            self.graph
+                .execute(self.device, encoder, [width, height], &[], passes, false)?;
+        self.queue
+            .submit(encoders.into_iter().map(wgpu::CommandEncoder::finish));
+
+        let texture = self
+            .graph
+            .texture(READBACK)
+            .expect("The readback attachment was just created");
+        let pixels = read_rgba(self.device, self.queue, texture, self.format, origin, size);
+        Ok((Region { origin, size }, pixels))
+    }
+}
+
+/// Rows of a buffer copy have to be a multiple of `COPY_BYTES_PER_ROW_ALIGNMENT` bytes long.
+pub fn padded_bytes_per_row(unpadded: u32) -> u32 {
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    unpadded.div_ceil(align) * align
+}
+
+/// Copies a region of a 4 bytes per pixel texture to the CPU and returns the pixels as tightly
+/// packed RGBA. BGRA formats get swizzled. Blocks until the GPU finished the copy.
+///
+/// `texture` needs to have been created with `TextureUsages::COPY_SRC`.
+pub fn read_rgba(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    format: wgpu::TextureFormat,
+    origin: [u32; 2],
+    size: [u32; 2],
+) -> Vec<u8> {
+    let [width, height] = size;
+    // A copy without pixels is valid, but there would be no rows to split the buffer into.
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+    let unpadded_bytes_per_row = width * 4;
+    let padded_bytes_per_row = padded_bytes_per_row(unpadded_bytes_per_row);
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("readback_buffer"),
+        size: padded_bytes_per_row as u64 * height as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("readback_encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d {
+                x: origin[0],
+                y: origin[1],
+                z: 0,
+            },
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(padded_bytes_per_row),
+                rows_per_image: NonZeroU32::new(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |result| {
+        if let Err(err) = result {
+            eprintln!("Can't map readback buffer: {:?}", err);
+        }
+    });
+    device.poll(wgpu::Maintain::Wait);
+
+    let bgra = matches!(
+        format,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+    );
+    let pixels = unpad_rows(&slice.get_mapped_range(), width, height, bgra);
+    buffer.unmap();
+    pixels
+}
+
+/// Strips the padding of the rows of a 4 bytes per pixel image that was copied into a buffer
+/// with [`padded_bytes_per_row`], and swizzles BGRA to RGBA if `bgra` is set.
+fn unpad_rows(data: &[u8], width: u32, height: u32, bgra: bool) -> Vec<u8> {
+    let unpadded_bytes_per_row = width as usize * 4;
+    let padded_bytes_per_row = padded_bytes_per_row(width * 4) as usize;
+    let mut pixels = Vec::with_capacity(unpadded_bytes_per_row * height as usize);
+    for row in data.chunks(padded_bytes_per_row).take(height as usize) {
+        let row = &row[..unpadded_bytes_per_row];
+        if bgra {
+            for pixel in row.chunks_exact(4) {
+                pixels.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+            }
+        } else {
+            pixels.extend_from_slice(row);
+        }
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lays out an image like a texture to buffer copy does, with the padding filled with 0xFF.
+    fn padded_image(width: u32, height: u32) -> (Vec<u8>, Vec<u8>) {
+        let pixels: Vec<u8> = (0..width * height * 4).map(|i| (i % 251) as u8).collect();
+        let padded_bytes_per_row = padded_bytes_per_row(width * 4) as usize;
+        let mut data = vec![0xFF; padded_bytes_per_row * height as usize];
+        for (row, padded_row) in pixels
+            .chunks(width as usize * 4)
+            .zip(data.chunks_mut(padded_bytes_per_row))
+        {
+            padded_row[..row.len()].copy_from_slice(row);
+        }
+        (pixels, data)
+    }
+
+    #[test]
+    fn pads_rows_to_the_copy_alignment() {
+        assert_eq!(padded_bytes_per_row(4), 256);
+        assert_eq!(padded_bytes_per_row(255 * 4), 1024);
+        assert_eq!(padded_bytes_per_row(256 * 4), 1024);
+        assert_eq!(padded_bytes_per_row(4097 * 4), 16640);
+    }
+
+    #[test]
+    fn unpads_odd_widths() {
+        for (width, height) in [(1, 1), (255, 3), (4097, 2)] {
+            let (pixels, data) = padded_image(width, height);
+            assert_eq!(
+                unpad_rows(&data, width, height, false),
+                pixels,
+                "{}×{}",
+                width,
+                height
+            );
+        }
+    }
+
+    #[test]
+    fn swizzles_bgra() {
+        let (_, data) = padded_image(255, 3);
+        let pixels = unpad_rows(&data, 255, 3, true);
+        assert_eq!(pixels.len(), 255 * 3 * 4);
+        let last_row = &data[2 * 1024..];
+        assert_eq!(
+            pixels[2 * 1020..2 * 1020 + 4],
+            [last_row[2], last_row[1], last_row[0], last_row[3]]
+        );
+    }
+}
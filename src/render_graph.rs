@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+
+use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
+
+/// The texture the frame ends up in, usually the surface. It's imported into every execution.
+pub const SURFACE: &str = "surface";
+
+/// An attachment the graph creates, as large as the frame it's executed for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AttachmentDesc {
+    pub format: wgpu::TextureFormat,
+    pub usage: wgpu::TextureUsages,
+}
+
+struct Attachment {
+    desc: AttachmentDesc,
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    size: [u32; 2],
+}
+
+/// Records the commands of a pass. Gets its own encoder when the passes are recorded on threads.
+type Record<'a> = Box<dyn FnOnce(&mut wgpu::CommandEncoder, &Resources) + Send + 'a>;
+
+/// A pass of a frame, with the named resources it reads and writes.
+pub struct Pass<'a> {
+    name: &'static str,
+    reads: Vec<&'static str>,
+    writes: Vec<&'static str>,
+    record: Record<'a>,
+}
+
+impl<'a> Pass<'a> {
+    pub fn new(
+        name: &'static str,
+        record: impl FnOnce(&mut wgpu::CommandEncoder, &Resources) + Send + 'a,
+    ) -> Self {
+        Self {
+            name,
+            reads: Vec::new(),
+            writes: Vec::new(),
+            record: Box::new(record),
+        }
+    }
+
+    pub fn reads(mut self, resource: &'static str) -> Self {
+        self.reads.push(resource);
+        self
+    }
+
+    pub fn writes(mut self, resource: &'static str) -> Self {
+        self.writes.push(resource);
+        self
+    }
+}
+
+/// The views of the resources, for the passes.
+pub struct Resources<'a> {
+    views: HashMap<&'static str, &'a wgpu::TextureView>,
+}
+
+impl Resources<'_> {
+    /// The view of `resource`. Only the resources a pass declared are guaranteed to exist.
+    pub fn view(&self, resource: &str) -> &wgpu::TextureView {
+        self.views
+            .get(resource)
+            .unwrap_or_else(|| panic!("The render graph has no resource {:?}", resource))
+    }
+}
+
+/// A minimal render graph: passes declare the named textures they read and write, and the graph
+/// orders them, creates the attachments and recreates them when the frame size changes.
+///
+/// Passes that write the same resource run in the order they were added, and a pass that reads
+/// a resource runs after all the others that write it. The graph lives as long as the device,
+/// so the attachments survive between frames.
+#[derive(Default)]
+pub struct RenderGraph {
+    attachments: HashMap<&'static str, Attachment>,
+    declared: HashMap<&'static str, AttachmentDesc>,
+}
+
+impl RenderGraph {
+    /// Declares an attachment the passes can use. Declaring it again with another description
+    /// recreates it.
+    pub fn declare(&mut self, name: &'static str, desc: AttachmentDesc) {
+        self.declared.insert(name, desc);
+    }
+
+    /// Drops an attachment that no pass uses anymore, with its texture.
+    pub fn remove(&mut self, name: &str) {
+        self.declared.remove(name);
+        self.attachments.remove(name);
+    }
+
+    /// The texture behind an attachment, after the first execution that used it. For copies
+    /// once the passes were submitted.
+    pub fn texture(&self, name: &str) -> Option<&wgpu::Texture> {
+        self.attachments
+            .get(name)
+            .map(|attachment| &attachment.texture)
+    }
+
+    /// Records `passes` in their order, for a frame of `size` physical pixels. `imports` are the
+    /// textures the graph doesn't own, like [`SURFACE`].
+    ///
+    /// With `threads`, every pass after the first one is recorded into an encoder of its own on
+    /// a thread, the first one is recorded into `encoder`. Submitting the returned encoders in
+    /// their order runs the passes in the order of the graph either way.
+    pub fn execute(
+        &mut self,
+        device: &wgpu::Device,
+        mut encoder: wgpu::CommandEncoder,
+        size: [u32; 2],
+        imports: &[(&'static str, &wgpu::TextureView)],
+        passes: Vec<Pass>,
+        threads: bool,
+    ) -> Result<Vec<wgpu::CommandEncoder>, String> {
+        for pass in &passes {
+            for resource in pass.reads.iter().chain(&pass.writes) {
+                let imported = imports.iter().any(|(name, _)| name == resource);
+                if !imported && !self.declared.contains_key(resource) {
+                    return Err(format!(
+                        "The pass {:?} uses {:?}, which was neither declared nor imported",
+                        pass.name, resource
+                    ));
+                }
+            }
+        }
+        let order = order(&passes)?;
+        self.create_attachments(device, size);
+
+        let mut views: HashMap<&'static str, &wgpu::TextureView> = self
+            .attachments
+            .iter()
+            .map(|(name, attachment)| (*name, &attachment.view))
+            .collect();
+        views.extend(imports.iter().copied());
+        let resources = Resources { views };
+
+        let mut passes: Vec<Option<Pass>> = passes.into_iter().map(Some).collect();
+        let mut ordered = order.into_iter().filter_map(|index| passes[index].take());
+        let first = match ordered.next() {
+            Some(first) => first,
+            None => return Ok(vec![encoder]),
+        };
+        if !threads {
+            (first.record)(&mut encoder, &resources);
+            for pass in ordered {
+                (pass.record)(&mut encoder, &resources);
+            }
+            return Ok(vec![encoder]);
+        }
+        let resources = &resources;
+        Ok(std::thread::scope(|scope| {
+            let threads: Vec<_> = ordered
+                .map(|pass| {
+                    scope.spawn(move || {
+                        let mut encoder =
+                            device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                                label: Some(pass.name),
+                            });
+                        (pass.record)(&mut encoder, resources);
+                        encoder
+                    })
+                })
+                .collect();
+            (first.record)(&mut encoder, resources);
+            let mut encoders = vec![encoder];
+            encoders.extend(
+                threads
+                    .into_iter()
+                    .map(|thread| thread.join().expect("An encoding thread panicked")),
+            );
+            encoders
+        }))
+    }
+
+    fn create_attachments(&mut self, device: &wgpu::Device, size: [u32; 2]) {
+        let size = [size[0].max(1), size[1].max(1)];
+        self.attachments
+            .retain(|name, attachment| self.declared.get(name) == Some(&attachment.desc));
+        for (name, desc) in &self.declared {
+            if self
+                .attachments
+                .get(name)
+                .is_some_and(|attachment| attachment.size == size)
+            {
+                continue;
+            }
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(name),
+                size: wgpu::Extent3d {
+                    width: size[0],
+                    height: size[1],
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: desc.format,
+                usage: desc.usage,
+            });
+            let view = texture.create_view(&Default::default());
+            self.attachments.insert(
+                name,
+                Attachment {
+                    desc: *desc,
+                    texture,
+                    view,
+                    size,
+                },
+            );
+        }
+    }
+}
+
+/// The indices of `passes` in the order they have to run in. Fails for cycles, like two passes
+/// that each read what the other one writes.
+fn order(passes: &[Pass]) -> Result<Vec<usize>, String> {
+    let mut dependencies: Vec<Vec<usize>> = vec![Vec::new(); passes.len()];
+    for (index, pass) in passes.iter().enumerate() {
+        for (other, writer) in passes.iter().enumerate() {
+            if other == index {
+                continue;
+            }
+            for resource in &writer.writes {
+                let reads = pass.reads.contains(resource);
+                let writes = pass.writes.contains(resource);
+                // A pass that modifies a resource only waits for the writers added before it.
+                if (reads && !writes) || (writes && other < index) {
+                    dependencies[index].push(other);
+                }
+            }
+        }
+    }
+
+    let mut done = vec![false; passes.len()];
+    let mut order = Vec::with_capacity(passes.len());
+    while order.len() < passes.len() {
+        // The first pass that can run keeps the order the passes were added in where possible.
+        let next = (0..passes.len()).find(|&index| {
+            !done[index]
+                && dependencies[index]
+                    .iter()
+                    .all(|&dependency| done[dependency])
+        });
+        match next {
+            Some(index) => {
+                done[index] = true;
+                order.push(index);
+            }
+            None => {
+                let stuck: Vec<&str> = (0..passes.len())
+                    .filter(|&index| !done[index])
+                    .map(|index| passes[index].name)
+                    .collect();
+                return Err(format!("The passes {:?} depend on each other", stuck));
+            }
+        }
+    }
+    Ok(order)
+}
+
+/// The passes that draw the egui `layers` into `target`, from bottom to top, after clearing it
+/// to `clear_color`.
+pub fn egui_passes<'a>(
+    layers: &'a [(&'a RenderPass, &'a [egui::ClippedPrimitive])],
+    target: &'static str,
+    clear_color: wgpu::Color,
+    screen_descriptor: &'a ScreenDescriptor,
+) -> Vec<Pass<'a>> {
+    let clear = Pass::new("clear", move |encoder, resources| {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("clear"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: resources.view(target),
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(clear_color),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+    })
+    .writes(target);
+    let mut passes = vec![clear];
+    for (rpass, jobs) in layers {
+        passes.push(
+            Pass::new("egui", move |encoder, resources| {
+                rpass
+                    .execute(
+                        encoder,
+                        resources.view(target),
+                        jobs,
+                        screen_descriptor,
+                        None,
+                    )
+                    .expect("Can't record the egui render pass");
+            })
+            .writes(target),
+        );
+    }
+    passes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pass(name: &'static str, reads: &[&'static str], writes: &[&'static str]) -> Pass<'static> {
+        let mut pass = Pass::new(name, |_, _| {});
+        pass.reads = reads.to_vec();
+        pass.writes = writes.to_vec();
+        pass
+    }
+
+    fn names(passes: &[Pass]) -> Vec<&'static str> {
+        order(passes)
+            .unwrap()
+            .into_iter()
+            .map(|index| passes[index].name)
+            .collect()
+    }
+
+    #[test]
+    fn readers_run_after_the_writers() {
+        let passes = [
+            pass("clear", &[], &[SURFACE]),
+            pass("post", &["scene"], &[SURFACE]),
+            pass("scene", &[], &["scene"]),
+            pass("egui", &[], &[SURFACE]),
+        ];
+        assert_eq!(names(&passes), ["clear", "scene", "post", "egui"]);
+    }
+
+    #[test]
+    fn modifying_passes_keep_their_order() {
+        let passes = [
+            pass("scene", &[], &["color"]),
+            pass("bloom", &["color"], &["color"]),
+            pass("tonemap", &["color"], &[SURFACE]),
+        ];
+        assert_eq!(names(&passes), ["scene", "bloom", "tonemap"]);
+    }
+
+    #[test]
+    fn cycles_are_errors() {
+        let passes = [pass("a", &["x"], &["y"]), pass("b", &["y"], &["x"])];
+        assert!(order(&passes).is_err());
+    }
+}
@@ -0,0 +1,1487 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
+use egui_winit_platform::{Platform, PlatformDescriptor};
+use winit::event::Event::*;
+use winit::event_loop::{ControlFlow, EventLoopProxy, EventLoopWindowTarget};
+use winit::window::Window;
+
+use crate::app::App;
+use crate::attract_screen::AttractScreen;
+use crate::clock::{Clock, FixedStepClock, RealClock};
+use crate::color_blindness::{ColorBlindnessFilter, COMPOSITED};
+use crate::damage::DamageTracker;
+use crate::frame_scheduler::FrameScheduler;
+use crate::frame_stats::{FrameRecord, FrameStats, Stages};
+use crate::fuzz::Fuzzer;
+use crate::gpu_watchdog::GpuWatchdog;
+use crate::hud::Hud;
+use crate::idle::IdleTracker;
+use crate::input_batch::InputBatch;
+use crate::input_recording::{Recorder, Replay};
+use crate::kiosk::Kiosk;
+use crate::options::Options;
+use crate::presentation::{self, Screen};
+use crate::render_graph::{self, RenderGraph};
+use crate::startup_report::StartupReport;
+use crate::tessellator::{self, Tessellator};
+use crate::ui_driver::UiDriver;
+use crate::ui_replay::{UiPlayer, UiRecorder};
+use crate::user_textures::{Residency, UserTextures};
+use crate::{config, font_atlas, gpu_report, readback, screenshot, svg_export, Event};
+
+/// The surface formats egui_wgpu_backend can render to, in order of preference. GL surfaces in
+/// particular might offer formats that aren't on this list.
+const SURFACE_FORMATS: [wgpu::TextureFormat; 4] = [
+    wgpu::TextureFormat::Bgra8UnormSrgb,
+    wgpu::TextureFormat::Rgba8UnormSrgb,
+    wgpu::TextureFormat::Bgra8Unorm,
+    wgpu::TextureFormat::Rgba8Unorm,
+];
+
+/// How many frames the stress test averages its stage timings over.
+const RECENT_FRAMES: usize = 60;
+
+/// The GPU objects. They are created together with the first surface, since the adapter should
+/// be compatible with it.
+struct Gpu {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    surface_config: wgpu::SurfaceConfiguration,
+    egui_rpass: RenderPass,
+    /// Renders the HUD, which has its own textures and buffers.
+    hud_rpass: RenderPass,
+    frame_stats: FrameStats,
+    /// Orders the passes of a frame and owns their attachments.
+    render_graph: RenderGraph,
+    watchdog: Option<GpuWatchdog>,
+    /// The memory of the user textures, against the budget in the settings.
+    residency: Residency,
+    /// Created when a color-blindness simulation is turned on for the first time.
+    color_blindness: Option<ColorBlindnessFilter>,
+    /// Created when the attract screen is shown for the first time.
+    attract_screen: Option<AttractScreen>,
+}
+
+/// The GPU objects of a host application that already owns the GPU, for `run_with_device` and
+/// `Embedded::with_device`. The surface of the window is created with `instance`, and `format`
+/// has to be supported by that surface.
+pub struct ExternalGpu {
+    pub instance: Arc<wgpu::Instance>,
+    pub adapter: Arc<wgpu::Adapter>,
+    pub device: Arc<wgpu::Device>,
+    pub queue: Arc<wgpu::Queue>,
+    pub format: wgpu::TextureFormat,
+}
+
+/// A texture to render a frame into, instead of the window surface. See
+/// `Embedded::render_to`.
+pub struct RenderTarget<'a> {
+    pub view: &'a wgpu::TextureView,
+    /// The size of the texture in physical pixels.
+    pub width: u32,
+    pub height: u32,
+    /// Has to be the format the UI is rendered with, e.g. the one of the `ExternalGpu`.
+    pub format: wgpu::TextureFormat,
+}
+
+/// Owns the window and everything needed to render the UI into it, and reacts to the events of
+/// the event loop.
+///
+/// The surface only exists between `Resumed` and `Suspended`. Desktop platforms resume once right
+/// at the start, Android whenever the app comes to the foreground. Before that there is no native
+/// window to create a surface for.
+pub struct Runner {
+    options: Options,
+    window: Window,
+    proxy: EventLoopProxy<Event>,
+    instance: Arc<wgpu::Instance>,
+    /// The GPU objects handed in by a host application, until the first resume takes them.
+    external_gpu: Option<ExternalGpu>,
+    surface: Option<wgpu::Surface>,
+    gpu: Option<Gpu>,
+    platform: Platform,
+    hud: Hud,
+    /// The second window of the presentation, while one is shown.
+    presentation: Option<Screen>,
+    app: Option<App>,
+    start_time: Instant,
+    recorder: Option<Recorder>,
+    replay: Option<Replay>,
+    ui_recorder: Option<UiRecorder>,
+    /// Plays a `.egui` file back, see `ui_replay`.
+    player: Option<UiPlayer>,
+    fuzzer: Option<Fuzzer>,
+    /// Clicks through the UI as `--ui-script` tells it to.
+    ui_driver: Option<UiDriver>,
+    /// The time egui animates with, see `Clock`.
+    clock: Box<dyn Clock>,
+    /// The first wgpu validation error, collected while fuzzing.
+    validation_error: std::sync::Arc<std::sync::Mutex<Option<String>>>,
+    scheduler: FrameScheduler,
+    tessellator: Tessellator,
+    damage: DamageTracker,
+    /// Collects the startup times until the app takes them over.
+    startup: StartupReport,
+    /// Hidden by the global hotkey, nothing is drawn meanwhile.
+    hidden: bool,
+    /// The size the UI was laid out for last, to lay it out again when it changes.
+    layout_size: [u32; 2],
+    input_batch: InputBatch,
+    focused: bool,
+    /// Keeps the window in front and resets the UI while `--kiosk` is on.
+    kiosk: Option<Kiosk>,
+    idle: IdleTracker,
+    /// Whether the attract screen is shown instead of the UI, until the next input.
+    attracting: bool,
+}
+
+impl Runner {
+    pub fn new(window: Window, proxy: EventLoopProxy<Event>, options: Options) -> Self {
+        Self::create(window, proxy, options, None)
+    }
+
+    /// Renders with the device and queue of a host application, instead of creating its own
+    /// instance, adapter and device. The backend options are ignored then.
+    pub fn with_device(
+        window: Window,
+        proxy: EventLoopProxy<Event>,
+        options: Options,
+        gpu: ExternalGpu,
+    ) -> Self {
+        Self::create(window, proxy, options, Some(gpu))
+    }
+
+    fn create(
+        window: Window,
+        proxy: EventLoopProxy<Event>,
+        options: Options,
+        external_gpu: Option<ExternalGpu>,
+    ) -> Self {
+        let mut startup = StartupReport::default();
+        let size = window.inner_size();
+
+        // We use the egui_winit_platform crate as the platform. The system emoji fonts are large,
+        // so they are only read once for both contexts.
+        let fonts_start = Instant::now();
+        let font_definitions = options.font_definitions();
+        let scale_factor = options.scale_factor(window.scale_factor());
+        let platform = Platform::new(PlatformDescriptor {
+            physical_width: size.width,
+            physical_height: size.height,
+            scale_factor,
+            font_definitions: font_definitions.clone(),
+            style: Default::default(),
+        });
+        let hud = Hud::new(&window, scale_factor, font_definitions);
+        startup.record("Fonts", fonts_start);
+
+        let recorder = options.record.as_ref().and_then(|path| {
+            Recorder::create(path, size, window.scale_factor())
+                .map_err(|err| eprintln!("Can't record input to {}: {}", path.display(), err))
+                .ok()
+        });
+        let replay = options.replay.as_ref().map(|path| {
+            Replay::open(path).unwrap_or_else(|err| {
+                eprintln!("Can't replay {}: {}", path.display(), err);
+                std::process::exit(1);
+            })
+        });
+        if let Some(size) = replay.as_ref().and_then(Replay::start_size) {
+            window.set_inner_size(size);
+        }
+        let ui_recorder = options.record_ui.as_ref().and_then(|path| {
+            UiRecorder::create(path, size, window.scale_factor())
+                .map_err(|err| eprintln!("Can't record the UI to {}: {}", path.display(), err))
+                .ok()
+        });
+        let player = options.play.as_ref().map(|path| {
+            UiPlayer::open(path).unwrap_or_else(|err| {
+                eprintln!("Can't play {}: {}", path.display(), err);
+                std::process::exit(1);
+            })
+        });
+        if let Some(player) = &player {
+            window.set_inner_size(player.start_size());
+        }
+
+        let fuzzer = options.fuzz.map(|_| {
+            let seed = options.fuzz_seed.unwrap_or_else(|| {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |duration| duration.as_nanos() as u64)
+            });
+            println!("Fuzzing with seed {}", seed);
+            // Print the seed again right next to the panic message.
+            let default_hook = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                eprintln!("Fuzzing failed, reproduce with --fuzz-seed {}", seed);
+                default_hook(info);
+            }));
+            Fuzzer::new(seed)
+        });
+
+        let clock: Box<dyn Clock> = match (&replay, &player, options.fixed_step) {
+            (Some(replay), _, _) => Box::new(replay.clock()),
+            (None, Some(player), _) => Box::new(player.clock()),
+            (None, None, Some(step)) => Box::new(FixedStepClock::new(step)),
+            (None, None, None) => Box::<RealClock>::default(),
+        };
+        let ui_driver = options.ui_script.as_ref().map(|path| {
+            std::fs::read_to_string(path)
+                .map_err(|err| err.to_string())
+                .and_then(|script| UiDriver::parse(&script))
+                .unwrap_or_else(|err| {
+                    eprintln!("Can't run the UI script {}: {}", path.display(), err);
+                    std::process::exit(1);
+                })
+        });
+
+        let kiosk = options.kiosk.then(|| {
+            let reset_after =
+                (options.kiosk_reset > 0.0).then(|| Duration::from_secs_f64(options.kiosk_reset));
+            Kiosk::new(&window, options.kiosk_monitor, reset_after)
+        });
+
+        let instance_start = Instant::now();
+        let instance = match &external_gpu {
+            Some(gpu) => gpu.instance.clone(),
+            None => Arc::new(wgpu::Instance::new(options.backend.backends())),
+        };
+        startup.record("Instance", instance_start);
+
+        Self {
+            startup,
+            recorder,
+            replay,
+            ui_recorder,
+            player,
+            fuzzer,
+            ui_driver,
+            clock,
+            validation_error: Default::default(),
+            scheduler: FrameScheduler::new(
+                options.replay.is_some()
+                    || options.play.is_some()
+                    || options.fuzz.is_some()
+                    || options.ui_script.is_some(),
+            ),
+            tessellator: Tessellator::new(),
+            damage: Default::default(),
+            hidden: false,
+            layout_size: [0, 0],
+            input_batch: Default::default(),
+            focused: true,
+            kiosk,
+            idle: Default::default(),
+            attracting: false,
+            instance,
+            external_gpu,
+            options,
+            window,
+            proxy,
+            surface: None,
+            gpu: None,
+            platform,
+            hud,
+            presentation: None,
+            app: None,
+            start_time: Instant::now(),
+        }
+    }
+
+    /// The egui context of the UI.
+    pub fn context(&self) -> egui::Context {
+        self.platform.context()
+    }
+
+    /// Passes `event` on to the platform integrations of the main UI and the HUD.
+    fn forward_event(&mut self, event: &winit::event::Event<Event>) {
+        // A new monitor brings a new scale factor, which `--scale` still multiplies.
+        if let WindowEvent {
+            window_id,
+            event:
+                winit::event::WindowEvent::ScaleFactorChanged {
+                    scale_factor,
+                    new_inner_size,
+                },
+        } = event
+        {
+            if self.options.scale.is_some() {
+                let mut new_inner_size = **new_inner_size;
+                let scaled = WindowEvent {
+                    window_id: *window_id,
+                    event: winit::event::WindowEvent::ScaleFactorChanged {
+                        scale_factor: self.options.scale_factor(*scale_factor),
+                        new_inner_size: &mut new_inner_size,
+                    },
+                };
+                return self.forward_scaled_event(&scaled);
+            }
+        }
+        self.forward_scaled_event(event);
+    }
+
+    fn forward_scaled_event(&mut self, event: &winit::event::Event<Event>) {
+        if let WindowEvent { event, .. } = event {
+            if self.idle.observe(event) && self.attracting {
+                // The input only brings the UI back, it doesn't click what's under the pointer.
+                self.attracting = false;
+                self.scheduler.request_frame();
+                return;
+            }
+        }
+        let hud_enabled = self.app.as_ref().is_some_and(App::hud_enabled);
+        self.hud.route_event(&mut self.platform, event, hud_enabled);
+        self.input_batch.forwarded(event);
+        if let Some(app) = &mut self.app {
+            app.log_event(event);
+        }
+    }
+
+    /// Forwards the coalesced pointer motion and scrolling.
+    fn forward_batched_input(&mut self) {
+        let events: Vec<_> = self.input_batch.take().collect();
+        for event in &events {
+            self.forward_event(event);
+        }
+    }
+
+    /// Handles one event of the event loop.
+    pub fn handle_event(
+        &mut self,
+        event: winit::event::Event<Event>,
+        target: &EventLoopWindowTarget<Event>,
+        control_flow: &mut ControlFlow,
+    ) {
+        if self.handle_presentation_event(&event) {
+            return;
+        }
+
+        // Pass the winit events to the platform integration. While replaying, the input comes
+        // from the recording instead.
+        let replaying = self.replay.is_some() || self.player.is_some();
+        match event {
+            WindowEvent { .. } if replaying => {}
+            _ => {
+                let batch = self.app.as_ref().is_some_and(App::batch_input);
+                if !self.input_batch.coalesce(&event, batch) {
+                    self.forward_batched_input();
+                    self.forward_event(&event);
+                }
+            }
+        }
+        if let (WindowEvent { event, .. }, Some(recorder)) = (&event, &mut self.recorder) {
+            if let Err(err) = recorder.record_event(event) {
+                eprintln!("Can't record input: {}", err);
+                self.recorder = None;
+            }
+        }
+
+        // Every window event might change what egui shows.
+        if let WindowEvent { event, .. } = &event {
+            self.scheduler.request_frame();
+            if let Some(kiosk) = &mut self.kiosk {
+                kiosk.handle_event(&self.window, event);
+            }
+        }
+
+        match event {
+            NewEvents(cause) => self.scheduler.new_events(cause),
+            Resumed => {
+                self.resume();
+                self.scheduler.request_frame();
+            }
+            Suspended => {
+                // The native window is about to be destroyed on Android, and the app might get
+                // killed in the background without ever seeing a close request.
+                self.surface = None;
+                self.save_config();
+            }
+            RedrawRequested(..) => {
+                self.redraw();
+                self.update_presentation(target);
+                if self.app.as_ref().is_some_and(App::trace_finished) {
+                    // Dropping the device while exiting completes the trace.
+                    println!("Finished the wgpu trace");
+                    *control_flow = ControlFlow::Exit;
+                }
+                if self.replay.as_ref().is_some_and(Replay::is_finished)
+                    || self.player.as_ref().is_some_and(UiPlayer::is_finished)
+                {
+                    println!("Replay finished");
+                    *control_flow = ControlFlow::Exit;
+                }
+                if self.ui_driver.as_ref().is_some_and(UiDriver::is_finished) {
+                    println!("UI script finished");
+                    *control_flow = ControlFlow::Exit;
+                }
+                if let Some(fuzzer) = &self.fuzzer {
+                    if let Some(error) = self.validation_error.lock().unwrap().take() {
+                        eprintln!("wgpu validation error: {}", error);
+                        eprintln!(
+                            "Fuzzing failed, reproduce with --fuzz-seed {}",
+                            fuzzer.seed()
+                        );
+                        std::process::exit(1);
+                    }
+                    let duration = self.options.fuzz.unwrap_or_default();
+                    if self.start_time.elapsed().as_secs_f64() >= duration {
+                        println!("Fuzzing finished without errors");
+                        *control_flow = ControlFlow::Exit;
+                    }
+                }
+            }
+            LoopDestroyed => {
+                if let Some(recorder) = &mut self.recorder {
+                    if let Err(err) = recorder.flush() {
+                        eprintln!("Can't record input: {}", err);
+                    }
+                }
+                if let Some(recorder) = &mut self.ui_recorder {
+                    if let Err(err) = recorder.flush() {
+                        eprintln!("Can't record the UI: {}", err);
+                    }
+                }
+                if let Some(summary) = self.gpu.as_ref().and_then(|gpu| gpu.frame_stats.summary()) {
+                    println!("{}", summary);
+                }
+            }
+            MainEventsCleared => {
+                let reset = self
+                    .kiosk
+                    .as_mut()
+                    .is_some_and(|kiosk| kiosk.update(&self.window));
+                if let (true, Some(app)) = (reset, &mut self.app) {
+                    app.reset_ui();
+                    self.scheduler.request_frame();
+                }
+                let attract_after = self.attract_after();
+                if !self.attracting
+                    && attract_after.is_some_and(|after| self.idle.idle_time() >= after)
+                {
+                    self.attracting = true;
+                    self.scheduler.request_frame();
+                }
+                if self.scheduler.is_due() && !self.hidden {
+                    self.window.request_redraw()
+                }
+            }
+            RedrawEventsCleared => {
+                *control_flow = self.scheduler.control_flow();
+                // The kiosk wakes up to hide the cursor and to reset the UI.
+                if let Some(deadline) = self.kiosk.as_ref().and_then(Kiosk::deadline) {
+                    wake_up_at(control_flow, deadline);
+                }
+                if let (false, Some(after)) = (self.attracting, self.attract_after()) {
+                    wake_up_at(control_flow, self.idle.idle_at(after));
+                }
+            }
+            UserEvent(Event::RequestRedraw) => self.scheduler.request_frame(),
+            UserEvent(Event::Activate(files)) => {
+                self.hidden = false;
+                self.window.set_visible(true);
+                self.window.set_minimized(false);
+                self.window.focus_window();
+                self.open_files(files);
+                self.scheduler.request_frame();
+            }
+            UserEvent(Event::ToggleVisibility) => {
+                if !self.hidden && self.focused {
+                    self.hidden = true;
+                    self.window.set_visible(false);
+                } else {
+                    self.hidden = false;
+                    self.window.set_visible(true);
+                    self.window.set_minimized(false);
+                    self.window.focus_window();
+                    self.scheduler.request_frame();
+                }
+            }
+            UserEvent(Event::GpuHung) => self.save_config(),
+            #[cfg(feature = "midi")]
+            UserEvent(Event::Midi(control_change)) => {
+                if let Some(app) = &mut self.app {
+                    app.handle_midi(control_change);
+                }
+                self.scheduler.request_frame();
+            }
+            WindowEvent { event, .. } => match event {
+                // Resize with 0 width and height is used by winit to signal a minimize event on Windows.
+                // See: https://github.com/rust-windowing/winit/issues/208
+                // This solves an issue where the app would panic when minimizing on Windows.
+                winit::event::WindowEvent::Resized(size) if size.width > 0 && size.height > 0 => {
+                    // The surface always follows the actual size, even while a corrected size is
+                    // still pending.
+                    self.resize(size);
+                    if let Some(app) = &self.app {
+                        app.window_resized(&self.window, size);
+                    }
+                    // The platform might not draw until the user lets go of the border.
+                    let live_resize = self
+                        .app
+                        .as_ref()
+                        .is_some_and(|app| app.config().settings.live_resize);
+                    if live_resize && !self.is_test_run() {
+                        self.redraw();
+                    }
+                }
+                winit::event::WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                    self.resize(*new_inner_size);
+                }
+                // egui_winit_platform doesn't pass dropped files on to egui.
+                winit::event::WindowEvent::DroppedFile(path) => {
+                    if let Some(app) = &mut self.app {
+                        app.add_recent_file(&path);
+                    }
+                    self.platform
+                        .raw_input_mut()
+                        .dropped_files
+                        .push(egui::DroppedFile {
+                            path: Some(path),
+                            ..Default::default()
+                        });
+                }
+                winit::event::WindowEvent::MouseInput {
+                    state: winit::event::ElementState::Pressed,
+                    ..
+                } => {
+                    if let Some(app) = &mut self.app {
+                        app.pointer_pressed();
+                    }
+                }
+                winit::event::WindowEvent::Focused(focused) => {
+                    self.focused = focused;
+                    if let Some(app) = &mut self.app {
+                        app.focus_changed(&self.window, focused);
+                    }
+                }
+                // The kiosk can only be closed by ending the process.
+                winit::event::WindowEvent::CloseRequested if self.kiosk.is_none() => {
+                    self.save_config();
+                    // Give the monitor its desktop mode back right away.
+                    self.window.set_fullscreen(None);
+                    *control_flow = ControlFlow::Exit;
+                }
+                _ => {}
+            },
+            _ => (),
+        }
+    }
+
+    /// Passes the events of the presentation window to it, and closes it when asked to. Returns
+    /// whether `event` belonged to it.
+    fn handle_presentation_event(&mut self, event: &winit::event::Event<Event>) -> bool {
+        let (screen, gpu) = match (&mut self.presentation, &self.gpu) {
+            (Some(screen), Some(gpu)) => (screen, gpu),
+            _ => return false,
+        };
+        match event {
+            WindowEvent { window_id, .. } if *window_id == screen.id() => {
+                if !screen.handle_event(event, &gpu.device) {
+                    self.presentation = None;
+                    if let Some(app) = &mut self.app {
+                        app.presentation_mut().closed();
+                    }
+                }
+                self.scheduler.request_frame();
+                true
+            }
+            // It's drawn together with the main window.
+            RedrawRequested(window_id) => *window_id == screen.id(),
+            _ => false,
+        }
+    }
+
+    /// Opens or closes the presentation window as its controls ask.
+    fn update_presentation(&mut self, target: &EventLoopWindowTarget<Event>) {
+        let (gpu, app) = match (&self.gpu, &mut self.app) {
+            (Some(gpu), Some(app)) => (gpu, app),
+            _ => return,
+        };
+        match app.presentation_mut().take_request() {
+            Some(presentation::Request::Open(monitor)) => {
+                let result = Screen::open(
+                    target,
+                    monitor,
+                    &self.instance,
+                    &gpu.device,
+                    gpu.surface_config.format,
+                );
+                app.presentation_mut()
+                    .opened(result.as_ref().map(|_| ()).map_err(Clone::clone));
+                self.presentation = result.ok();
+                // The controls stay in the main window.
+                self.window.focus_window();
+                self.scheduler.request_frame();
+            }
+            Some(presentation::Request::Close) => self.presentation = None,
+            None => {}
+        }
+    }
+
+    /// Draws the current slide into the presentation window, unless it mirrors the main one.
+    fn redraw_presentation(&mut self) {
+        let (screen, gpu, app) = match (&mut self.presentation, &self.gpu, &mut self.app) {
+            (Some(screen), Some(gpu), Some(app)) => (screen, gpu, app),
+            _ => return,
+        };
+        if app.presentation_mut().mode() != presentation::Mode::Slides {
+            return;
+        }
+        let repaint_after = screen.render_slides(
+            &gpu.device,
+            &gpu.queue,
+            self.clock.now(),
+            self.platform.context().style(),
+            app.clear_color(),
+            |ctx| app.slide_ui(ctx),
+        );
+        self.scheduler.frame_finished(repaint_after);
+    }
+
+    /// Creates the surface, and on the first resume also the GPU objects and the app.
+    fn resume(&mut self) {
+        let surface_start = Instant::now();
+        let surface = unsafe { self.instance.create_surface(&self.window) };
+        if self.gpu.is_none() {
+            self.startup.record("Surface", surface_start);
+        }
+        let size = self.surface_size();
+
+        match &mut self.gpu {
+            Some(gpu) => {
+                // The new native window might have a different size, e.g. after a rotation.
+                gpu.surface_config.width = size.width.max(1);
+                gpu.surface_config.height = size.height.max(1);
+                surface.configure(&gpu.device, &gpu.surface_config);
+            }
+            None => {
+                let (adapter, device, queue, surface_format) = match self.external_gpu.take() {
+                    Some(gpu) => {
+                        let supported_formats = surface.get_supported_formats(&gpu.adapter);
+                        assert!(
+                            supported_formats.contains(&gpu.format),
+                            "The surface doesn't support {:?}, only {:?}",
+                            gpu.format,
+                            supported_formats
+                        );
+                        (gpu.adapter, gpu.device, gpu.queue, gpu.format)
+                    }
+                    None => self.create_device(&surface),
+                };
+                let surface_config = wgpu::SurfaceConfiguration {
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    format: surface_format,
+                    width: size.width.max(1),
+                    height: size.height.max(1),
+                    present_mode: wgpu::PresentMode::Fifo,
+                };
+                surface.configure(&device, &surface_config);
+
+                // egui refuses textures above 2048 pixels unless it knows better.
+                let max_texture_side = device.limits().max_texture_dimension_2d as usize;
+                self.platform.raw_input_mut().max_texture_side = Some(max_texture_side);
+                self.hud.set_max_texture_side(max_texture_side);
+
+                // We use the egui_wgpu_backend crate as the render backend.
+                let pipelines_start = Instant::now();
+                let egui_rpass = RenderPass::new(&device, surface_format, 1);
+                let hud_rpass = RenderPass::new(&device, surface_format, 1);
+                self.startup.record("egui pipelines", pipelines_start);
+
+                // Display the demo application that ships with egui, plus the tools of this example.
+                // A replay has to start from the same state as the recording, and random input
+                // shouldn't mess up the config of the user.
+                let played = self.player.as_ref().map(|player| {
+                    player.config().unwrap_or_else(|err| {
+                        eprintln!("Can't read the config of the recording: {}", err);
+                        std::process::exit(1);
+                    })
+                });
+                let config = match (played, self.is_test_run()) {
+                    (Some(config), _) => config,
+                    // Without the tour, which would take the input.
+                    (None, true) => config::Config {
+                        onboarding_done: true,
+                        ..Default::default()
+                    },
+                    (None, false) => config::Config::load(),
+                };
+                if let Some(recorder) = &mut self.ui_recorder {
+                    if let Err(err) = recorder.start(&config) {
+                        eprintln!("Can't record the UI: {}", err);
+                        self.ui_recorder = None;
+                    }
+                }
+
+                if self.fuzzer.is_some() {
+                    let validation_error = self.validation_error.clone();
+                    device.on_uncaptured_error(move |error| {
+                        validation_error
+                            .lock()
+                            .unwrap()
+                            .get_or_insert_with(|| error.to_string());
+                    });
+                }
+                let app_start = Instant::now();
+                let gpu_report =
+                    gpu_report::GpuReport::new(&adapter, &device, &surface, surface_format);
+                crate::crash_report::set_gpu_info(gpu_report.markdown());
+                let mut app = App::new(config, self.proxy.clone(), gpu_report);
+                app.override_theme(self.options.theme);
+                app.set_kiosk(self.kiosk.is_some());
+                if let Some(name) = &self.options.profile {
+                    if let Err(err) = app.switch_profile(name) {
+                        eprintln!("{}", err);
+                    }
+                }
+                self.startup.record("App", app_start);
+                app.set_startup_report(std::mem::take(&mut self.startup));
+                app.init_window(&self.window);
+                // A test run has to look the same every time.
+                if let (Some(report), false) =
+                    (crate::crash_report::take_previous(), self.is_test_run())
+                {
+                    app.show_crash_dialog(report);
+                }
+                // A test run has to load the same every time.
+                if !self.is_test_run() {
+                    app.watch_assets(&self.context(), &self.options.assets);
+                }
+                if let (Some(dir), true) = (&self.options.trace, cfg!(feature = "trace")) {
+                    app.start_trace(dir.clone(), self.options.trace_frames);
+                }
+                self.app = Some(app);
+                let files = std::mem::take(&mut self.options.files);
+                self.open_files(files);
+                let frame_stats = FrameStats::new(&device, &queue, &adapter.get_info());
+                let watchdog = (self.options.gpu_timeout > 0.0).then(|| {
+                    let timeout = Duration::from_secs_f64(self.options.gpu_timeout);
+                    GpuWatchdog::start(&device, timeout, self.proxy.clone())
+                });
+                self.gpu = Some(Gpu {
+                    device,
+                    queue,
+                    surface_config,
+                    egui_rpass,
+                    hud_rpass,
+                    frame_stats,
+                    render_graph: Default::default(),
+                    watchdog,
+                    residency: Default::default(),
+                    color_blindness: None,
+                    attract_screen: None,
+                });
+            }
+        }
+
+        self.surface = Some(surface);
+        self.window.request_redraw();
+    }
+
+    /// Creates an adapter and device for `surface` and picks the surface format.
+    fn create_device(
+        &mut self,
+        surface: &wgpu::Surface,
+    ) -> (
+        Arc<wgpu::Adapter>,
+        Arc<wgpu::Device>,
+        Arc<wgpu::Queue>,
+        wgpu::TextureFormat,
+    ) {
+        // Machines without proper drivers might still have a software renderer, like
+        // llvmpipe or WARP. It's slow, but better than not running at all.
+        let adapter_start = Instant::now();
+        let adapter = self
+            .request_adapter(surface, self.options.force_fallback_adapter)
+            .or_else(|| {
+                eprintln!("No GPU adapter found, falling back to a software renderer");
+                self.request_adapter(surface, true)
+            })
+            .expect("No adapter found, not even a software renderer");
+        self.startup.record("Adapter", adapter_start);
+
+        // Compressed textures are sampled as they are where the adapter supports them.
+        #[cfg(feature = "ktx2")]
+        let features = adapter.features() & crate::ktx2_loader::COMPRESSION_FEATURES;
+        #[cfg(not(feature = "ktx2"))]
+        let features = wgpu::Features::default();
+        // For the GPU time of the frame statistics.
+        let features = features | (adapter.features() & wgpu::Features::TIMESTAMP_QUERY);
+        // For the parameters of the scene renderer, which falls back to a uniform buffer.
+        let features = features | (adapter.features() & wgpu::Features::PUSH_CONSTANTS);
+        let mut limits = self.options.backend.limits(&adapter);
+        if features.contains(wgpu::Features::PUSH_CONSTANTS) {
+            limits.max_push_constant_size = adapter.limits().max_push_constant_size;
+        }
+
+        let device_start = Instant::now();
+        let trace_dir = self.trace_dir();
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                features,
+                limits,
+                label: None,
+            },
+            trace_dir,
+        ))
+        .unwrap();
+        self.startup.record("Device", device_start);
+
+        let supported_formats = surface.get_supported_formats(&adapter);
+        let surface_format = SURFACE_FORMATS
+            .into_iter()
+            .find(|format| supported_formats.contains(format))
+            .unwrap_or_else(|| {
+                panic!(
+                    "The surface supports none of the formats egui can render to: {:?}",
+                    supported_formats
+                )
+            });
+        (
+            Arc::new(adapter),
+            Arc::new(device),
+            Arc::new(queue),
+            surface_format,
+        )
+    }
+
+    /// The directory to record the wgpu trace into, if one is recorded.
+    fn trace_dir(&self) -> Option<&std::path::Path> {
+        let dir = self.options.trace.as_deref()?;
+        if !cfg!(feature = "trace") {
+            eprintln!("Built without the `trace` feature, --trace is ignored");
+            return None;
+        }
+        if let Err(err) = std::fs::create_dir_all(dir) {
+            eprintln!(
+                "Can't create the trace directory {}: {}",
+                dir.display(),
+                err
+            );
+            return None;
+        }
+        Some(dir)
+    }
+
+    fn request_adapter(
+        &self,
+        surface: &wgpu::Surface,
+        force_fallback_adapter: bool,
+    ) -> Option<wgpu::Adapter> {
+        pollster::block_on(self.instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: Some(surface),
+            force_fallback_adapter,
+        }))
+    }
+
+    /// The size of the surface. On iOS the inner size is only the safe area, but the surface
+    /// covers the whole screen.
+    fn surface_size(&self) -> winit::dpi::PhysicalSize<u32> {
+        if cfg!(target_os = "ios") {
+            self.window.outer_size()
+        } else {
+            self.window.inner_size()
+        }
+    }
+
+    /// The part of the screen that isn't covered by the notch, the status bar or the home
+    /// indicator, in points.
+    #[cfg(target_os = "ios")]
+    fn safe_area(&self) -> Option<egui::Rect> {
+        let inner = self.window.inner_position().ok()?;
+        let outer = self.window.outer_position().ok()?;
+        let size = self.window.inner_size();
+        let scale_factor = self.scale_factor() as f32;
+        let offset = egui::vec2((inner.x - outer.x) as f32, (inner.y - outer.y) as f32);
+        let size = egui::vec2(size.width as f32, size.height as f32);
+        Some(egui::Rect::from_min_size(
+            (offset / scale_factor).to_pos2(),
+            size / scale_factor,
+        ))
+    }
+
+    /// The scale factor of the UI, the one of the monitor unless `--scale` changes it.
+    fn scale_factor(&self) -> f64 {
+        self.options.scale_factor(self.window.scale_factor())
+    }
+
+    /// How long the user has to be idle for the attract screen. A test run never shows it.
+    fn attract_after(&self) -> Option<Duration> {
+        let after = self.app.as_ref()?.config().settings.attract_after?;
+        match self.is_test_run() {
+            true => None,
+            false => Some(Duration::from_secs(u64::from(after))),
+        }
+    }
+
+    /// Whether the input is replayed or generated, instead of coming from the user.
+    fn is_test_run(&self) -> bool {
+        self.replay.is_some()
+            || self.player.is_some()
+            || self.fuzzer.is_some()
+            || self.ui_driver.is_some()
+    }
+
+    /// Opens `files` in the tools that show them, by handing them to egui as dropped files.
+    fn open_files(&mut self, files: Vec<std::path::PathBuf>) {
+        match &mut self.app {
+            Some(app) => app.open_tools_for(&files),
+            // The Finder sends the files the example is launched with before the app exists.
+            None => {
+                self.options.files.extend(files);
+                return;
+            }
+        }
+        self.platform
+            .raw_input_mut()
+            .dropped_files
+            .extend(files.into_iter().map(|path| egui::DroppedFile {
+                path: Some(path),
+                ..Default::default()
+            }));
+    }
+
+    fn save_config(&self) {
+        if self.is_test_run() {
+            return;
+        }
+        if let Some(app) = &self.app {
+            if let Err(err) = app.config().save() {
+                eprintln!("Can't save config: {}", err);
+            }
+        }
+    }
+
+    fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        if size.width == 0 || size.height == 0 {
+            return;
+        }
+        if let (Some(surface), Some(gpu)) = (&self.surface, &mut self.gpu) {
+            gpu.surface_config.width = size.width;
+            gpu.surface_config.height = size.height;
+            surface.configure(&gpu.device, &gpu.surface_config);
+        }
+    }
+
+    fn redraw(&mut self) {
+        self.redraw_window();
+        // Also while the main window can't be drawn, e.g. because it's minimized.
+        self.redraw_presentation();
+    }
+
+    fn redraw_window(&mut self) {
+        self.scheduler.frame_started();
+        self.forward_batched_input();
+        if let Some(app) = &mut self.app {
+            let (received, forwarded) = self.input_batch.take_counts();
+            app.set_input_counts(received, forwarded);
+        }
+
+        // Switching the video mode doesn't report the new size on every platform before the
+        // next frame is drawn.
+        let size = self.surface_size();
+        if let Some(gpu) = &self.gpu {
+            if [gpu.surface_config.width, gpu.surface_config.height] != [size.width, size.height] {
+                self.resize(size);
+            }
+        }
+
+        // Keep the UI out of the notch and the home indicator. egui lays out everything inside the
+        // screen rect, the rest of the surface only shows the clear color.
+        #[cfg(target_os = "ios")]
+        if let Some(safe_area) = self.safe_area() {
+            self.platform.raw_input_mut().screen_rect = Some(safe_area);
+            self.hud.set_screen_rect(safe_area);
+        }
+
+        self.wait_for_gpu();
+
+        // Nothing to draw into while suspended.
+        let (surface, gpu, app) = match (&self.surface, &mut self.gpu, &self.app) {
+            (Some(surface), Some(gpu), Some(app)) => (surface, gpu, app),
+            _ => return,
+        };
+        if gpu.surface_config.present_mode != app.present_mode() {
+            gpu.surface_config.present_mode = app.present_mode();
+            surface.configure(&gpu.device, &gpu.surface_config);
+        }
+        let [width, height] = [gpu.surface_config.width, gpu.surface_config.height];
+
+        let output_frame = match surface.get_current_texture() {
+            Ok(frame) => frame,
+            Err(wgpu::SurfaceError::Outdated) => {
+                // This error occurs when the app is minimized on Windows.
+                // Silently return here to prevent spamming the console with:
+                // "The underlying surface has changed, and therefore the swap chain must be updated"
+                return;
+            }
+            Err(e) => {
+                eprintln!("Dropped frame with error: {}", e);
+                self.scheduler.request_frame();
+                return;
+            }
+        };
+        let output_view = output_frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        // The frame is dropped without being presented once there's nothing more to draw.
+        if !self.render(&output_view, width, height) {
+            return;
+        }
+        output_frame.present();
+        if let Some(app) = &mut self.app {
+            app.frame_presented();
+            app.first_frame_presented();
+        }
+    }
+
+    /// Runs one frame of the UI and renders it into `target` instead of the window surface,
+    /// e.g. to use the UI as a layer in the renderer of a host application. Fails before the
+    /// device exists, or if the format of `target` isn't the one the runner renders with.
+    #[cfg_attr(any(target_os = "android", target_os = "ios"), allow(dead_code))]
+    pub fn render_to(&mut self, target: &RenderTarget) -> Result<(), String> {
+        match self.format() {
+            Some(format) if format == target.format => {}
+            Some(format) => {
+                return Err(format!(
+                    "Can't render into a {:?} texture, the runner renders with {:?}",
+                    target.format, format
+                ))
+            }
+            None => return Err("The device doesn't exist before the first resume".to_owned()),
+        }
+        self.scheduler.frame_started();
+        self.wait_for_gpu();
+
+        // The UI is laid out for the texture, not for the window.
+        let scale_factor = self.scale_factor() as f32;
+        let screen_rect = egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(target.width as f32, target.height as f32) / scale_factor,
+        );
+        self.platform.raw_input_mut().screen_rect = Some(screen_rect);
+        self.hud.set_screen_rect(screen_rect);
+
+        self.render(target.view, target.width, target.height);
+        Ok(())
+    }
+
+    /// Waits until the number of frames in flight is below the limit of the settings.
+    fn wait_for_gpu(&mut self) {
+        if let (Some(gpu), Some(app)) = (&self.gpu, &self.app) {
+            let limit = app.config().settings.max_frames_in_flight;
+            self.scheduler.latency_mut().wait(&gpu.device, limit);
+        }
+    }
+
+    /// The format the runner renders with, once the device exists.
+    #[cfg_attr(any(target_os = "android", target_os = "ios"), allow(dead_code))]
+    pub fn format(&self) -> Option<wgpu::TextureFormat> {
+        self.gpu.as_ref().map(|gpu| gpu.surface_config.format)
+    }
+
+    /// Runs one frame of the UI and renders it into `view`, which is `width` × `height` pixels
+    /// large and has the format of the surface. Returns whether it drew anything, it doesn't once
+    /// the replay or the recording is over.
+    fn render(&mut self, view: &wgpu::TextureView, width: u32, height: u32) -> bool {
+        let scale_factor = self.scale_factor() as f32;
+        let (gpu, app) = match (&mut self.gpu, &mut self.app) {
+            (Some(gpu), Some(app)) => (gpu, app),
+            _ => return false,
+        };
+        if self.attracting {
+            let format = gpu.surface_config.format;
+            let screen = gpu
+                .attract_screen
+                .get_or_insert_with(|| AttractScreen::new(&gpu.device, format));
+            let time = self.clock.tick();
+            let pass = screen.pass(&gpu.queue, [width, height], time, render_graph::SURFACE);
+            let encoder = gpu
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("attract_encoder"),
+                });
+            let encoders = gpu
+                .render_graph
+                .execute(
+                    &gpu.device,
+                    encoder,
+                    [width, height],
+                    &[(render_graph::SURFACE, view)],
+                    vec![pass],
+                    false,
+                )
+                .expect("The attract pass is valid");
+            gpu.queue
+                .submit(encoders.into_iter().map(wgpu::CommandEncoder::finish));
+            self.scheduler.latency_mut().submitted(&gpu.queue);
+            // Animated until the next input, at the frame rate limit of the settings.
+            self.scheduler.request_frame();
+            return true;
+        }
+        let platform = &mut self.platform;
+        let hud = &mut self.hud;
+        let hud_enabled = app.hud_enabled();
+        let mut stages = Stages::start();
+        gpu.frame_stats.collect_gpu_times();
+        let mut record = FrameRecord {
+            frame: gpu.frame_stats.next_frame(),
+            time: self.start_time.elapsed().as_secs_f64(),
+            wait_ms: self.scheduler.latency_mut().last_wait().as_secs_f32() * 1000.0,
+            ..Default::default()
+        };
+
+        // A replayed frame gets the recorded input and time, so that it turns out the same as
+        // during the recording.
+        if let Some(replay) = &mut self.replay {
+            let replayed = replay.next_frame(self.window.id(), |event| {
+                hud.route_event(platform, event, hud_enabled);
+                app.log_event(event);
+            });
+            if !replayed {
+                return false;
+            }
+        }
+        let played = match &mut self.player {
+            Some(player) => match player.next_frame() {
+                Some(frame) => Some(frame),
+                None => return false,
+            },
+            None => None,
+        };
+        let time = self.clock.tick();
+        if let Some(fuzzer) = &mut self.fuzzer {
+            let size = self.window.inner_size();
+            fuzzer.next_frame(self.window.id(), size, |event| {
+                hud.route_event(platform, event, hud_enabled);
+                app.log_event(event);
+            });
+        }
+        platform.update_time(time);
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(err) = recorder.record_frame(time) {
+                eprintln!("Can't record input: {}", err);
+                self.recorder = None;
+            }
+        }
+
+        if let Some(driver) = &mut self.ui_driver {
+            if let Err(err) = driver.next_frame(&platform.context(), platform.raw_input_mut()) {
+                eprintln!("The UI script failed: {}", err);
+                std::process::exit(1);
+            }
+        }
+
+        app.prepare_frame(&platform.context(), platform.raw_input_mut());
+        // Everything the frame gets from the app is part of the recorded input, and a played
+        // frame gets all of it from the recording.
+        if let Some(frame) = played {
+            frame.apply(platform.raw_input_mut());
+        }
+        if let Some(recorder) = &mut self.ui_recorder {
+            if let Err(err) = recorder.record_frame(platform.raw_input_mut()) {
+                eprintln!("Can't record the UI: {}", err);
+                self.ui_recorder = None;
+            }
+        }
+
+        // Begin to draw the UI frame.
+        stages.lap();
+        platform.begin_frame();
+
+        // Draw the application, after making room for its textures.
+        let budget = u64::from(app.config().settings.texture_budget_mib) * 1024 * 1024;
+        gpu.residency.evict(&mut gpu.egui_rpass, budget);
+        let mut textures = UserTextures::new(
+            &gpu.device,
+            &gpu.queue,
+            gpu.surface_config.format,
+            app.config().settings.msaa_samples,
+            &mut gpu.egui_rpass,
+            &mut gpu.residency,
+        );
+        app.ui(
+            &platform.context(),
+            &self.window,
+            &self.scheduler,
+            &mut textures,
+        );
+
+        // End the UI frame. We could now handle the output and draw the UI with the backend.
+        let mut full_output = platform.end_frame(Some(&self.window));
+        // Panels take the size they had in the last frame, so they only fit a new window size one
+        // frame later. A second pass without the input lays them out with the new size.
+        let resized = std::mem::replace(&mut self.layout_size, [width, height]) != [width, height];
+        if resized && app.config().settings.relayout_on_resize {
+            platform.begin_frame();
+            app.ui(
+                &platform.context(),
+                &self.window,
+                &self.scheduler,
+                &mut textures,
+            );
+            full_output.append(platform.end_frame(Some(&self.window)));
+        }
+        if let Some(driver) = &mut self.ui_driver {
+            driver.observe(&full_output.shapes);
+        }
+        app.observe_frame(&platform.context(), &full_output);
+        gpu.residency.displayed(&full_output.shapes);
+        app.set_texture_stats(gpu.residency.stats());
+        // The shapes are gone once they're tessellated.
+        let svg_shapes = app.take_svg_request().then(|| full_output.shapes.clone());
+        app.handle_platform_output(&full_output.platform_output);
+        self.scheduler
+            .set_frame_rate_limit(app.config().settings.frame_rate_limit);
+        self.scheduler.frame_finished(full_output.repaint_after);
+        // The main UI is tessellated on the worker while the HUD runs and its buffers are
+        // uploaded.
+        let tessellation_options = app.tessellation_options();
+        self.tessellator
+            .start(platform.context(), full_output.shapes, tessellation_options);
+        record.ui_ms = stages.lap();
+
+        // The HUD runs after the main UI, so that its cursor wins while the pointer is over it.
+        let time_of_day = self.clock.seconds_since_midnight();
+        let hud_output = hud_enabled.then(|| hud.run(&self.window, time, time_of_day));
+        let hud_svg_shapes = match (&svg_shapes, &hud_output) {
+            (Some(_), Some(hud_output)) => hud_output.shapes.clone(),
+            _ => Vec::new(),
+        };
+        if let Some(hud_output) = &hud_output {
+            self.scheduler.frame_finished(hud_output.repaint_after);
+        }
+        let (hud_jobs, hud_tdelta) = match hud_output {
+            Some(hud_output) => (
+                tessellator::tessellate(&hud.context(), hud_output.shapes, tessellation_options),
+                hud_output.textures_delta,
+            ),
+            None => Default::default(),
+        };
+        record.hud_ms = stages.lap();
+
+        let mut encoder = gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("encoder"),
+            });
+
+        // Upload all resources for the GPU.
+        let screen_descriptor = ScreenDescriptor {
+            physical_width: width,
+            physical_height: height,
+            scale_factor,
+        };
+        gpu.hud_rpass
+            .add_textures(&gpu.device, &gpu.queue, &hud_tdelta)
+            .expect("add texture ok");
+        gpu.hud_rpass
+            .update_buffers(&gpu.device, &gpu.queue, &hud_jobs, &screen_descriptor);
+        let mut tdelta: egui::TexturesDelta = full_output.textures_delta;
+        font_atlas::upload_textures(&mut tdelta, &mut record, |delta| {
+            gpu.egui_rpass
+                .add_textures(&gpu.device, &gpu.queue, delta)
+                .expect("add texture ok");
+        });
+        let paint_jobs = self.tessellator.finish();
+        record.tessellate_ms = stages.lap();
+        gpu.egui_rpass
+            .update_buffers(&gpu.device, &gpu.queue, &paint_jobs, &screen_descriptor);
+        if app.show_damage() {
+            let changed_textures = tdelta
+                .set
+                .iter()
+                .chain(&hud_tdelta.set)
+                .map(|(id, _)| *id)
+                .collect();
+            let damage = self
+                .damage
+                .update(&[&hud_jobs, &paint_jobs], &changed_textures);
+            app.set_damage(damage);
+        } else {
+            self.damage = Default::default();
+        }
+        record.upload_ms = stages.lap();
+
+        // Record all render passes. The HUD goes first, the main UI is drawn on top of it.
+        let layers = [
+            (&gpu.hud_rpass, hud_jobs.as_slice()),
+            (&gpu.egui_rpass, paint_jobs.as_slice()),
+        ];
+        let layers = match hud_enabled {
+            true => &layers[..],
+            false => &layers[1..],
+        };
+        gpu.frame_stats.begin_gpu(&mut encoder);
+        // With a color-blindness simulation, the UI is composited into an attachment that the
+        // filter draws onto the surface.
+        let simulation = app.color_blindness();
+        let format = gpu.surface_config.format;
+        match simulation {
+            Some(_) => gpu
+                .render_graph
+                .declare(COMPOSITED, ColorBlindnessFilter::attachment(format)),
+            None => gpu.render_graph.remove(COMPOSITED),
+        }
+        let mut passes = render_graph::egui_passes(
+            layers,
+            match simulation {
+                Some(_) => COMPOSITED,
+                None => render_graph::SURFACE,
+            },
+            app.clear_color(),
+            &screen_descriptor,
+        );
+        if let Some(simulation) = simulation {
+            let filter = gpu
+                .color_blindness
+                .get_or_insert_with(|| ColorBlindnessFilter::new(&gpu.device, format));
+            passes.push(filter.pass(&gpu.device, &gpu.queue, simulation, render_graph::SURFACE));
+        }
+        let mut encoders = gpu
+            .render_graph
+            .execute(
+                &gpu.device,
+                encoder,
+                [width, height],
+                &[(render_graph::SURFACE, view)],
+                passes,
+                app.multithreaded_encoding(),
+            )
+            .expect("The passes of the frame are valid");
+        if let Some(last) = encoders.last_mut() {
+            gpu.frame_stats.end_gpu(&gpu.device, last);
+        }
+        record.encode_ms = stages.lap();
+        // Submit the commands, in the order of the passes.
+        gpu.queue
+            .submit(encoders.into_iter().map(wgpu::CommandEncoder::finish));
+        self.scheduler.latency_mut().submitted(&gpu.queue);
+        if let Some(watchdog) = &gpu.watchdog {
+            watchdog.submitted(&gpu.queue);
+        }
+        app.frame_submitted(&gpu.queue);
+        record.submit_ms = stages.lap();
+        record.cpu_ms = stages.total();
+        if hud_enabled {
+            record.add_layer("hud", &hud_jobs, &hud_tdelta, &screen_descriptor);
+        }
+        record.add_layer("ui", &paint_jobs, &tdelta, &screen_descriptor);
+        gpu.frame_stats.push(record);
+        app.set_recent_frames(gpu.frame_stats.recent(RECENT_FRAMES));
+        if app.take_frame_stats_export() {
+            let result = gpu.frame_stats.export();
+            app.set_frame_stats_exported(result);
+        }
+
+        // Read back the regions of this frame the tools asked for.
+        let screenshot_request = app.take_screenshot_request();
+        let color_sample_region = app.color_sample_region();
+        let recording_region = app.take_recording_region([width, height]);
+        if screenshot_request.is_some()
+            || color_sample_region.is_some()
+            || svg_shapes.is_some()
+            || recording_region.is_some()
+        {
+            let mut frame = readback::Frame {
+                device: &gpu.device,
+                graph: &mut gpu.render_graph,
+                queue: &gpu.queue,
+                layers,
+                screen_descriptor: &screen_descriptor,
+                format: gpu.surface_config.format,
+                clear_color: app.clear_color(),
+            };
+            if let Some(request) = screenshot_request {
+                match frame.read_region(request.region) {
+                    Ok((region, pixels)) => app.finish_screenshot(
+                        &platform.context(),
+                        screenshot::Request { region, ..request },
+                        pixels,
+                    ),
+                    Err(err) => eprintln!("Can't capture screenshot: {}", err),
+                }
+            }
+            if let Some(region) = recording_region {
+                match frame.read_region(region) {
+                    Ok((region, pixels)) => app.add_recorded_frame(pixels, region.size),
+                    Err(err) => eprintln!("Can't capture the recorded frame: {}", err),
+                }
+            }
+            if let Some(region) = color_sample_region {
+                match frame.read_region(region) {
+                    Ok((_, pixel)) => app.set_color_sample(&pixel),
+                    Err(err) => eprintln!("Can't read color sample: {}", err),
+                }
+            }
+            if let Some(shapes) = &svg_shapes {
+                let region = readback::Region {
+                    origin: [0, 0],
+                    size: [width, height],
+                };
+                let result = frame.read_region(region).and_then(|(region, pixels)| {
+                    let raster = svg_export::Raster {
+                        pixels: &pixels,
+                        size: region.size,
+                        pixels_per_point: screen_descriptor.scale_factor,
+                    };
+                    let clear_color = app.clear_color();
+                    let background = egui::Color32::from(egui::Rgba::from_rgb(
+                        clear_color.r as f32,
+                        clear_color.g as f32,
+                        clear_color.b as f32,
+                    ));
+                    let svg = svg_export::write_svg(
+                        &[&hud_svg_shapes, shapes],
+                        platform.context().input().screen_rect(),
+                        background,
+                        &raster,
+                    );
+                    svg_export::save(&svg)
+                });
+                app.finish_svg(&platform.context(), result);
+            }
+        }
+
+        // The frame is drawn again while the render passes still have its textures.
+        if let Some(screen) = &mut self.presentation {
+            if app.presentation_mut().mode() == presentation::Mode::Mirror {
+                let mut mirrored = vec![
+                    (&mut gpu.hud_rpass, hud_jobs.as_slice()),
+                    (&mut gpu.egui_rpass, paint_jobs.as_slice()),
+                ];
+                if !hud_enabled {
+                    mirrored.remove(0);
+                }
+                screen.mirror(
+                    &gpu.device,
+                    &gpu.queue,
+                    &mut mirrored,
+                    platform.context().input().screen_rect().size(),
+                    app.clear_color(),
+                );
+            }
+        }
+
+        gpu.egui_rpass
+            .remove_textures(tdelta)
+            .expect("remove texture ok");
+        gpu.hud_rpass
+            .remove_textures(hud_tdelta)
+            .expect("remove texture ok");
+        true
+    }
+}
+
+/// Makes the event loop wake up at `deadline` at the latest.
+fn wake_up_at(control_flow: &mut ControlFlow, deadline: Instant) {
+    *control_flow = match *control_flow {
+        ControlFlow::Wait => ControlFlow::WaitUntil(deadline),
+        ControlFlow::WaitUntil(wakeup) => ControlFlow::WaitUntil(wakeup.min(deadline)),
+        other => other,
+    };
+}
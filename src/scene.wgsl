@@ -0,0 +1,83 @@
+// Draws the boxes of the ECS scene with a directional light, one instance per entity.
+
+struct Camera {
+    view_projection: mat4x4<f32>,
+    eye: vec4<f32>,
+    // Towards the light.
+    light: vec4<f32>,
+};
+
+// A push constant instead where the device supports them, see `SceneRenderer::new`.
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+
+// The look of the boxes, which the material inspector of the ECS scene edits.
+struct Material {
+    ambient: f32,
+    diffuse: f32,
+    specular: f32,
+    // The exponent of the rim on the selected entity.
+    rim_power: f32,
+    // Linear RGB.
+    rim_color: vec3<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> material: Material;
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+struct Instance {
+    @location(2) model_0: vec4<f32>,
+    @location(3) model_1: vec4<f32>,
+    @location(4) model_2: vec4<f32>,
+    @location(5) model_3: vec4<f32>,
+    // The inverse transpose of the model matrix, which keeps normals orthogonal to scaled faces.
+    @location(6) normal_0: vec3<f32>,
+    @location(7) normal_1: vec3<f32>,
+    @location(8) normal_2: vec3<f32>,
+    // Linear RGB.
+    @location(9) color: vec3<f32>,
+    // The shininess of the highlights, and whether the entity is selected.
+    @location(10) material: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) color: vec3<f32>,
+    @location(3) material: vec2<f32>,
+};
+
+@vertex
+fn vs_main(vertex: Vertex, instance: Instance) -> VertexOutput {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    let normal = mat3x3<f32>(instance.normal_0, instance.normal_1, instance.normal_2);
+    let world = model * vec4<f32>(vertex.position, 1.0);
+    var out: VertexOutput;
+    out.position = camera.view_projection * world;
+    out.world = world.xyz;
+    out.normal = normal * vertex.normal;
+    out.color = instance.color;
+    out.material = instance.material;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let normal = normalize(in.normal);
+    let light = camera.light.xyz;
+    let view = normalize(camera.eye.xyz - in.world);
+    let diffuse = max(dot(normal, light), 0.0);
+    let half_way = normalize(light + view);
+    let specular = pow(max(dot(normal, half_way), 0.0), in.material.x) * material.specular;
+    var color = in.color * (material.ambient + material.diffuse * diffuse) + vec3<f32>(specular);
+    // A rim on the selected entity.
+    let rim = pow(1.0 - max(dot(normal, view), 0.0), material.rim_power);
+    color = color + material.rim_color * rim * in.material.y;
+    return vec4<f32>(color, 1.0);
+}
@@ -0,0 +1,504 @@
+use std::collections::{HashMap, HashSet};
+
+use glam::Mat4;
+
+use crate::gizmo::Transform;
+
+/// How deep the hierarchy is followed, in case a parent loop slipped in.
+const MAX_DEPTH: usize = 64;
+/// How far each level of the hierarchy is indented, in points.
+const INDENT: f32 = 14.0;
+
+/// The name an entity is listed with.
+pub struct Name(pub String);
+
+/// The entity whose space the [`Transform`] of this one is in. An entity whose parent is gone is a
+/// root.
+#[derive(Clone, Copy)]
+pub struct Parent(pub hecs::Entity);
+
+/// Not drawn, and neither are the children.
+pub struct Hidden;
+
+/// Can't be picked in the viewport or moved, and neither can the children.
+pub struct Locked;
+
+/// The parent of `entity`, if it's still in `world`.
+fn parent(world: &hecs::World, entity: hecs::Entity) -> Option<hecs::Entity> {
+    world
+        .get::<&Parent>(entity)
+        .ok()
+        .map(|parent| parent.0)
+        .filter(|parent| world.contains(*parent))
+}
+
+/// `entity` and its parents, up to the root.
+fn lineage(world: &hecs::World, entity: hecs::Entity) -> impl Iterator<Item = hecs::Entity> + '_ {
+    std::iter::successors(Some(entity), |entity| parent(world, *entity)).take(MAX_DEPTH)
+}
+
+/// From the space of `entity` to the world, through the transforms of its parents.
+pub fn world_matrix(world: &hecs::World, entity: hecs::Entity) -> Mat4 {
+    lineage(world, entity)
+        .filter_map(|entity| world.get::<&Transform>(entity).ok().map(|t| t.matrix()))
+        .fold(Mat4::IDENTITY, |matrix, parent| parent * matrix)
+}
+
+/// The space the [`Transform`] of `entity` is in.
+pub fn parent_matrix(world: &hecs::World, entity: hecs::Entity) -> Mat4 {
+    parent(world, entity).map_or(Mat4::IDENTITY, |parent| world_matrix(world, parent))
+}
+
+/// Whether `entity` or one of its parents is hidden.
+pub fn is_hidden(world: &hecs::World, entity: hecs::Entity) -> bool {
+    lineage(world, entity).any(|entity| world.get::<&Hidden>(entity).is_ok())
+}
+
+/// Whether `entity` or one of its parents is locked.
+pub fn is_locked(world: &hecs::World, entity: hecs::Entity) -> bool {
+    lineage(world, entity).any(|entity| world.get::<&Locked>(entity).is_ok())
+}
+
+/// `entity` and everything below it.
+pub fn subtree(world: &hecs::World, entity: hecs::Entity) -> Vec<hecs::Entity> {
+    let mut query = world.query::<(hecs::Entity, &Parent)>();
+    let children: Vec<(hecs::Entity, hecs::Entity)> = query
+        .iter()
+        .map(|(child, parent)| (parent.0, child))
+        .collect();
+    let mut subtree = vec![entity];
+    let mut next = 0;
+    while let Some(&parent) = subtree.get(next) {
+        next += 1;
+        for &(of, child) in &children {
+            if of == parent && !subtree.contains(&child) {
+                subtree.push(child);
+            }
+        }
+    }
+    subtree
+}
+
+/// A marker component that can be toggled in the hierarchy.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    Hidden,
+    Locked,
+}
+
+/// A change to an entity, in the undo history.
+pub enum Edit {
+    /// A move with the gizmo.
+    Transform {
+        entity: hecs::Entity,
+        before: Transform,
+        after: Transform,
+    },
+    /// A new parent, with the transform that keeps the entity where it was.
+    Parent {
+        entity: hecs::Entity,
+        before: (Option<hecs::Entity>, Transform),
+        after: (Option<hecs::Entity>, Transform),
+    },
+    Name {
+        entity: hecs::Entity,
+        before: String,
+        after: String,
+    },
+    Flag {
+        entity: hecs::Entity,
+        flag: Flag,
+        /// Whether the edit sets the flag, rather than clearing it.
+        on: bool,
+    },
+}
+
+impl Edit {
+    /// Puts `entity` under `parent`, or makes it a root, where it's now in the world. `None` if
+    /// that would make it its own parent, or if it's there already.
+    pub fn reparent(
+        world: &hecs::World,
+        entity: hecs::Entity,
+        parent: Option<hecs::Entity>,
+    ) -> Option<Self> {
+        let before = self::parent(world, entity);
+        if before == parent
+            || parent.is_some_and(|parent| lineage(world, parent).any(|above| above == entity))
+        {
+            return None;
+        }
+        let transform = *world.get::<&Transform>(entity).ok()?;
+        let parent_matrix = parent.map_or(Mat4::IDENTITY, |parent| world_matrix(world, parent));
+        let local = parent_matrix.inverse() * world_matrix(world, entity);
+        Some(Self::Parent {
+            entity,
+            before: (before, transform),
+            after: (parent, Transform::from_matrix(local)),
+        })
+    }
+
+    fn entity(&self) -> hecs::Entity {
+        match self {
+            Self::Transform { entity, .. }
+            | Self::Parent { entity, .. }
+            | Self::Name { entity, .. }
+            | Self::Flag { entity, .. } => *entity,
+        }
+    }
+
+    /// Puts the entity into the state after the edit, or before it for `undo`. `false` if the
+    /// entity is gone.
+    fn apply(&self, world: &mut hecs::World, undo: bool) -> bool {
+        let entity = self.entity();
+        if !world.contains(entity) {
+            return false;
+        }
+        // The entity exists, so inserting and removing components can't fail.
+        match self {
+            Self::Transform { before, after, .. } => {
+                let _ = world.insert_one(entity, if undo { *before } else { *after });
+            }
+            Self::Parent { before, after, .. } => {
+                let (parent, transform) = if undo { before } else { after };
+                let _ = world.insert_one(entity, *transform);
+                match parent {
+                    Some(parent) => {
+                        let _ = world.insert_one(entity, Parent(*parent));
+                    }
+                    None => {
+                        let _ = world.remove_one::<Parent>(entity);
+                    }
+                }
+            }
+            Self::Name { before, after, .. } => {
+                let name = if undo { before } else { after };
+                let _ = world.insert_one(entity, Name(name.clone()));
+            }
+            Self::Flag { flag, on, .. } => {
+                let _ = match (flag, on != &undo) {
+                    (Flag::Hidden, true) => world.insert_one(entity, Hidden).ok(),
+                    (Flag::Locked, true) => world.insert_one(entity, Locked).ok(),
+                    (Flag::Hidden, false) => world.remove_one::<Hidden>(entity).ok().map(|_| ()),
+                    (Flag::Locked, false) => world.remove_one::<Locked>(entity).ok().map(|_| ()),
+                };
+            }
+        }
+        true
+    }
+}
+
+/// The edits that can be undone and redone.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+}
+
+impl History {
+    /// Applies `edit` to `world` and keeps it to be undone.
+    pub fn apply(&mut self, world: &mut hecs::World, edit: Edit) {
+        if edit.apply(world, false) {
+            self.push(edit);
+        }
+    }
+
+    /// Keeps `edit`, which is already in the world, to be undone.
+    pub fn push(&mut self, edit: Edit) {
+        self.undo.push(edit);
+        self.redo.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Undoes the last edit of an entity that still exists, and returns the entity. The edits of
+    /// despawned entities are dropped.
+    pub fn undo(&mut self, world: &mut hecs::World) -> Option<hecs::Entity> {
+        while let Some(edit) = self.undo.pop() {
+            if edit.apply(world, true) {
+                let entity = edit.entity();
+                self.redo.push(edit);
+                return Some(entity);
+            }
+        }
+        None
+    }
+
+    pub fn redo(&mut self, world: &mut hecs::World) -> Option<hecs::Entity> {
+        while let Some(edit) = self.redo.pop() {
+            if edit.apply(world, false) {
+                let entity = edit.entity();
+                self.undo.push(edit);
+                return Some(entity);
+            }
+        }
+        None
+    }
+}
+
+/// A line of the hierarchy.
+struct Row {
+    entity: hecs::Entity,
+    depth: usize,
+    name: String,
+    has_children: bool,
+    hidden: bool,
+    locked: bool,
+    /// Whether a parent is hidden or locked.
+    parent_hidden: bool,
+    parent_locked: bool,
+}
+
+/// The entities as a tree: a row is dragged onto another one to reparent it, or below the tree
+/// to make it a root. Double-clicking renames in place, the buttons in front hide and lock.
+#[derive(Default)]
+pub struct HierarchyPanel {
+    collapsed: HashSet<hecs::Entity>,
+    dragged: Option<hecs::Entity>,
+    renaming: Option<(hecs::Entity, String)>,
+}
+
+impl HierarchyPanel {
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        world: &mut hecs::World,
+        selected: &mut Option<hecs::Entity>,
+        history: &mut History,
+    ) {
+        let rows = self.rows(world);
+        let pointer = ui.input().pointer.hover_pos();
+        let mut edits = Vec::new();
+        // `Some(None)` makes the dragged entity a root.
+        let mut drop_target = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for row in &rows {
+                ui.horizontal(|ui| {
+                    ui.add_space(row.depth as f32 * INDENT);
+                    let toggle = match (row.has_children, self.collapsed.contains(&row.entity)) {
+                        (false, _) => " ",
+                        (true, true) => "⏵",
+                        (true, false) => "⏷",
+                    };
+                    let toggle = ui.add_enabled(
+                        row.has_children,
+                        egui::Button::new(toggle).small().frame(false),
+                    );
+                    if toggle.clicked() && !self.collapsed.remove(&row.entity) {
+                        self.collapsed.insert(row.entity);
+                    }
+                    let flags = [
+                        (Flag::Hidden, row.hidden, row.parent_hidden, "Hide"),
+                        (Flag::Locked, row.locked, row.parent_locked, "Lock"),
+                    ];
+                    for (flag, on, inherited, action) in flags {
+                        let text = match (flag, on || inherited) {
+                            (Flag::Hidden, false) => egui::RichText::new("👁"),
+                            (Flag::Hidden, true) => egui::RichText::new("👁").weak().strikethrough(),
+                            (Flag::Locked, false) => egui::RichText::new("🔓").weak(),
+                            (Flag::Locked, true) => egui::RichText::new("🔒"),
+                        };
+                        let hint = match (on, inherited) {
+                            (true, _) => format!("Un{}", action.to_lowercase()),
+                            (false, true) => format!("{} with a parent", action),
+                            (false, false) => action.to_owned(),
+                        };
+                        let button = egui::Button::new(text).small().frame(false);
+                        if ui.add(button).on_hover_text(hint).clicked() {
+                            edits.push(Edit::Flag {
+                                entity: row.entity,
+                                flag,
+                                on: !on,
+                            });
+                        }
+                    }
+                    if let Some((entity, name)) = &mut self.renaming {
+                        if *entity == row.entity {
+                            let response = ui.text_edit_singleline(name);
+                            if response.lost_focus() {
+                                if !ui.input().key_pressed(egui::Key::Escape)
+                                    && !name.trim().is_empty()
+                                    && name != &row.name
+                                {
+                                    edits.push(Edit::Name {
+                                        entity: row.entity,
+                                        before: row.name.clone(),
+                                        after: name.trim().to_owned(),
+                                    });
+                                }
+                                self.renaming = None;
+                            } else if !response.has_focus() {
+                                response.request_focus();
+                            }
+                            return;
+                        }
+                    }
+                    let label = ui
+                        .selectable_label(*selected == Some(row.entity), &row.name)
+                        .interact(egui::Sense::drag())
+                        .on_hover_text(
+                            "Double-click to rename, drag onto another entity to move it there",
+                        );
+                    if label.clicked() {
+                        *selected = Some(row.entity);
+                    }
+                    if label.double_clicked() {
+                        self.renaming = Some((row.entity, row.name.clone()));
+                    }
+                    if label.drag_started() {
+                        self.dragged = Some(row.entity);
+                    }
+                    let row_rect = ui.min_rect();
+                    if let (Some(dragged), Some(pointer)) = (self.dragged, pointer) {
+                        if dragged != row.entity && row_rect.contains(pointer) {
+                            let valid = Edit::reparent(world, dragged, Some(row.entity)).is_some();
+                            let stroke = match valid {
+                                true => ui.visuals().selection.stroke,
+                                false => egui::Stroke::new(1.0, ui.visuals().error_fg_color),
+                            };
+                            ui.painter().rect_stroke(row_rect.expand(1.0), 2.0, stroke);
+                            if valid {
+                                drop_target = Some(Some(row.entity));
+                            }
+                        }
+                    }
+                });
+            }
+            // The rest of the panel takes roots.
+            let (rest, _) = ui.allocate_exact_size(
+                egui::vec2(ui.available_width(), ui.available_height().max(24.0)),
+                egui::Sense::hover(),
+            );
+            if let (Some(_), Some(pointer)) = (self.dragged, pointer) {
+                if rest.contains(pointer) {
+                    ui.painter().text(
+                        rest.left_top() + egui::vec2(INDENT, 4.0),
+                        egui::Align2::LEFT_TOP,
+                        "Drop here to make it a root",
+                        egui::TextStyle::Small.resolve(ui.style()),
+                        ui.visuals().weak_text_color(),
+                    );
+                    drop_target = Some(None);
+                }
+            }
+        });
+
+        if let Some(dragged) = self.dragged {
+            if let Some(row) = rows.iter().find(|row| row.entity == dragged) {
+                ui.output().cursor_icon = egui::CursorIcon::Grabbing;
+                egui::show_tooltip_at_pointer(ui.ctx(), egui::Id::new("hierarchy_drag"), |ui| {
+                    ui.label(&row.name);
+                });
+            }
+            if !ui.input().pointer.any_down() {
+                self.dragged = None;
+                if let Some(edit) =
+                    drop_target.and_then(|parent| Edit::reparent(world, dragged, parent))
+                {
+                    edits.push(edit);
+                }
+            }
+        }
+        for edit in edits {
+            history.apply(world, edit);
+        }
+    }
+
+    /// The entities in the order of the tree, without the children of collapsed ones.
+    fn rows(&self, world: &hecs::World) -> Vec<Row> {
+        let mut children: HashMap<Option<hecs::Entity>, Vec<(hecs::Entity, String)>> =
+            HashMap::new();
+        for (entity, name) in world.query::<(hecs::Entity, &Name)>().iter() {
+            children
+                .entry(parent(world, entity))
+                .or_default()
+                .push((entity, name.0.clone()));
+        }
+        for siblings in children.values_mut() {
+            siblings.sort_by_key(|(entity, _)| entity.id());
+        }
+        let mut rows = Vec::new();
+        // Depth first, the roots last on the stack come out first.
+        let mut stack: Vec<(hecs::Entity, String, usize)> = children
+            .get(&None)
+            .into_iter()
+            .flatten()
+            .rev()
+            .map(|(entity, name)| (*entity, name.clone(), 0))
+            .collect();
+        while let Some((entity, name, depth)) = stack.pop() {
+            let below = children.get(&Some(entity));
+            let parent_hidden =
+                parent(world, entity).is_some_and(|parent| is_hidden(world, parent));
+            let parent_locked =
+                parent(world, entity).is_some_and(|parent| is_locked(world, parent));
+            rows.push(Row {
+                entity,
+                depth,
+                name,
+                has_children: below.is_some(),
+                hidden: world.get::<&Hidden>(entity).is_ok(),
+                locked: world.get::<&Locked>(entity).is_ok(),
+                parent_hidden,
+                parent_locked,
+            });
+            if depth < MAX_DEPTH && !self.collapsed.contains(&entity) {
+                for (child, name) in below.into_iter().flatten().rev() {
+                    stack.push((*child, name.clone(), depth + 1));
+                }
+            }
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec3;
+
+    fn at(x: f32) -> Transform {
+        Transform {
+            translation: Vec3::new(x, 0.0, 0.0),
+            rotation: Vec3::ZERO,
+            scale: Vec3::ONE,
+        }
+    }
+
+    #[test]
+    fn reparenting_keeps_the_place_and_is_undone() {
+        let mut world = hecs::World::new();
+        let parent = world.spawn((Name("Parent".to_owned()), at(2.0)));
+        let child = world.spawn((Name("Child".to_owned()), at(5.0)));
+        let mut history = History::default();
+        let edit = Edit::reparent(&world, child, Some(parent)).unwrap();
+        history.apply(&mut world, edit);
+        assert_eq!(world.get::<&Transform>(child).unwrap().translation.x, 3.0);
+        assert_eq!(world_matrix(&world, child).w_axis.x, 5.0);
+        // A parent can't go below its own child.
+        assert!(Edit::reparent(&world, parent, Some(child)).is_none());
+        assert_eq!(subtree(&world, parent), [parent, child]);
+
+        history.apply(
+            &mut world,
+            Edit::Flag {
+                entity: parent,
+                flag: Flag::Hidden,
+                on: true,
+            },
+        );
+        assert!(is_hidden(&world, child));
+        assert_eq!(history.undo(&mut world), Some(parent));
+        assert!(!is_hidden(&world, child));
+        assert_eq!(history.undo(&mut world), Some(child));
+        assert_eq!(world.get::<&Transform>(child).unwrap().translation.x, 5.0);
+        assert!(world.get::<&Parent>(child).is_err());
+        assert_eq!(history.redo(&mut world), Some(child));
+        assert_eq!(world.get::<&Parent>(child).unwrap().0, parent);
+    }
+}
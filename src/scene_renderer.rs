@@ -0,0 +1,654 @@
+use std::time::Instant;
+
+use glam::{Mat4, Vec3};
+
+use crate::shader_reflection::{UniformLayout, UniformValues};
+use crate::user_textures::{RenderTarget, UserTextures};
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+/// The bytes of an instance in the instance buffer.
+const INSTANCE_SIZE: u64 = (16 + 9 + 3 + 2) * 4;
+/// The bytes of a vertex of the box.
+const VERTEX_SIZE: u64 = 6 * 4;
+/// The bytes of the scene parameters: the view projection, the eye and the light direction.
+const PARAMETERS_SIZE: u32 = (16 + 4 + 4) * 4;
+/// The shader of the scene, unless the `scene.wgsl` asset replaces it.
+pub const SHADER: &str = include_str!("scene.wgsl");
+/// How much a new upload time measurement weighs in its average.
+const SMOOTHING: f32 = 0.05;
+/// The bytes of the material buffer before a shader declares a larger one, uniform buffers
+/// can't be empty.
+const MATERIAL_MIN_SIZE: u64 = 16;
+/// The values of the material fields of `scene.wgsl`, which a shader that adds them starts with.
+const MATERIAL_DEFAULTS: [(&str, &[f32]); 5] = [
+    ("ambient", &[0.15]),
+    ("diffuse", &[0.85]),
+    ("specular", &[0.5]),
+    ("rim_power", &[2.0]),
+    ("rim_color", &[1.0, 0.5, 0.1]),
+];
+
+/// A box in the scene, from -0.5 to 0.5 on each axis before `model` transforms it.
+pub struct Instance {
+    pub model: Mat4,
+    /// Linear RGB.
+    pub color: [f32; 3],
+    pub shininess: f32,
+    /// Draws an outline-like rim around the box.
+    pub highlighted: bool,
+}
+
+/// Where the scene is looked at from.
+#[derive(Clone, Copy)]
+pub struct Camera {
+    pub eye: Vec3,
+    pub target: Vec3,
+    /// The vertical field of view, in radians.
+    pub fov: f32,
+}
+
+impl Camera {
+    /// From world to clip space, for a target with the aspect ratio of `size`.
+    pub fn view_projection(&self, size: egui::Vec2) -> Mat4 {
+        let projection = Mat4::perspective_rh(self.fov, size.x / size.y, 0.1, 100.0);
+        projection * Mat4::look_at_rh(self.eye, self.target, Vec3::Y)
+    }
+}
+
+/// A minimal 3D renderer with a pipeline of its own, which draws lit boxes into the
+/// [`RenderTarget`] of a [`SceneView`] with one instanced draw call.
+///
+/// The scene parameters are push constants where the device supports them, which records them
+/// into the command buffer instead of uploading them to a uniform buffer before every draw.
+pub struct SceneRenderer {
+    /// Reads the parameters from `camera`.
+    pipeline: wgpu::RenderPipeline,
+    /// Reads the parameters from push constants, if the device has them.
+    push_constant_pipeline: Option<wgpu::RenderPipeline>,
+    /// Whether `push_constant_pipeline` draws, when there is one.
+    use_push_constants: bool,
+    /// The average microseconds it takes to hand the parameters over, with the uniform buffer
+    /// and with push constants, once measured.
+    uniform_us: Option<f32>,
+    push_constant_us: Option<f32>,
+    camera: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    vertices: wgpu::Buffer,
+    indices: wgpu::Buffer,
+    instances: wgpu::Buffer,
+    /// How many instances fit into `instances`.
+    capacity: u64,
+    /// The layouts to create the pipelines again with, when the sample count changes.
+    layout: wgpu::PipelineLayout,
+    push_constant_layout: Option<wgpu::PipelineLayout>,
+    /// The samples per pixel the pipelines draw with, see `Settings::msaa_samples`.
+    sample_count: u32,
+    /// The WGSL the pipelines were created from, the built-in one or a reloaded asset.
+    source: String,
+    material: MaterialUniform,
+}
+
+/// The uniform buffer of the `material` at `@group(1) @binding(0)`, laid out like the shader
+/// declares it.
+struct MaterialUniform {
+    layout: wgpu::BindGroupLayout,
+    buffer: wgpu::Buffer,
+    /// The bytes of `buffer`.
+    capacity: u64,
+    bind_group: wgpu::BindGroup,
+    /// `None` if the shader has no material.
+    values: Option<UniformValues>,
+}
+
+/// What one camera renders the scene into, so that a renderer can draw the scene from several
+/// cameras in a frame, each shown as a texture of its own.
+#[derive(Default)]
+pub struct SceneView {
+    target: Option<RenderTarget>,
+    /// The depth buffer, and the multisampled color attachment that is resolved into the target
+    /// with more than one sample, for the size and sample count they were created with.
+    attachments: Option<(Attachments, [u32; 2], u32)>,
+}
+
+impl SceneView {
+    /// Frees the target, while the view isn't shown.
+    pub fn free(&mut self, textures: &mut UserTextures) {
+        RenderTarget::free(&mut self.target, textures);
+        self.attachments = None;
+    }
+}
+
+struct Attachments {
+    depth: wgpu::TextureView,
+    multisampled: Option<wgpu::TextureView>,
+}
+
+impl SceneRenderer {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("scene_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: wgpu::BufferSize::new(PARAMETERS_SIZE as u64),
+                },
+                count: None,
+            }],
+        });
+        let mut material = MaterialUniform::new(device);
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("scene_uniform_layout"),
+            bind_group_layouts: &[&bind_group_layout, &material.layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = create_pipeline(device, &layout, SHADER.into(), 1);
+        // Push constants are optional, and so small that some adapters that have them can't fit
+        // the parameters in.
+        let push_constant_layout = (device.features().contains(wgpu::Features::PUSH_CONSTANTS)
+            && device.limits().max_push_constant_size >= PARAMETERS_SIZE)
+            .then(|| {
+                // The material stays in a uniform buffer, in the same group.
+                let empty = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("scene_empty_layout"),
+                    entries: &[],
+                });
+                device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("scene_push_constant_layout"),
+                    bind_group_layouts: &[&empty, &material.layout],
+                    push_constant_ranges: &[wgpu::PushConstantRange {
+                        stages: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                        range: 0..PARAMETERS_SIZE,
+                    }],
+                })
+            });
+        let push_constant_pipeline = push_constant_layout
+            .as_ref()
+            .map(|layout| create_pipeline(device, layout, push_constant_shader(SHADER).into(), 1));
+        let camera = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("scene_camera"),
+            size: PARAMETERS_SIZE as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("scene_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera.as_entire_binding(),
+            }],
+        });
+
+        let layout_of_material =
+            crate::shader_reflection::reflect(SHADER, 1, 0).expect("The built-in shader parses");
+        material.set_layout(device, layout_of_material);
+
+        let (vertices, indices) = cube();
+        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("scene_vertices"),
+            size: (vertices.len() * 4) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&vertex_buffer, 0, &to_bytes(&vertices));
+        let index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("scene_indices"),
+            size: (indices.len() * 2) as u64,
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let index_bytes: Vec<u8> = indices
+            .iter()
+            .flat_map(|index| index.to_le_bytes())
+            .collect();
+        queue.write_buffer(&index_buffer, 0, &index_bytes);
+
+        Self {
+            pipeline,
+            use_push_constants: push_constant_pipeline.is_some(),
+            push_constant_pipeline,
+            uniform_us: None,
+            push_constant_us: None,
+            camera,
+            bind_group,
+            vertices: vertex_buffer,
+            indices: index_buffer,
+            instances: instance_buffer(device, 0),
+            capacity: 0,
+            layout,
+            push_constant_layout,
+            sample_count: 1,
+            source: SHADER.to_owned(),
+            material,
+        }
+    }
+
+    /// Creates the pipelines again for `sample_count` samples per pixel, 1 or 4, if they have
+    /// another one.
+    fn set_sample_count(&mut self, device: &wgpu::Device, sample_count: u32) {
+        if sample_count == self.sample_count {
+            return;
+        }
+        self.sample_count = sample_count;
+        self.pipeline = create_pipeline(
+            device,
+            &self.layout,
+            self.source.as_str().into(),
+            sample_count,
+        );
+        self.push_constant_pipeline = self.push_constant_layout.as_ref().map(|layout| {
+            create_pipeline(
+                device,
+                layout,
+                push_constant_shader(&self.source).into(),
+                sample_count,
+            )
+        });
+    }
+
+    /// Creates the pipelines from `source`, a WGSL with the entry points and bindings of
+    /// `scene.wgsl`. Keeps the current ones if that fails.
+    pub fn set_shader(&mut self, device: &wgpu::Device, source: &str) -> Result<(), String> {
+        let material = crate::shader_reflection::reflect(source, 1, 0)?;
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let pipeline = create_pipeline(device, &self.layout, source.into(), self.sample_count);
+        let push_constant_pipeline = self.push_constant_layout.as_ref().map(|layout| {
+            let source = push_constant_shader(source).into();
+            create_pipeline(device, layout, source, self.sample_count)
+        });
+        if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+            return Err(error.to_string());
+        }
+        self.pipeline = pipeline;
+        self.push_constant_pipeline = push_constant_pipeline;
+        self.source = source.to_owned();
+        self.material.set_layout(device, material);
+        Ok(())
+    }
+
+    /// Renders `instances` into the target of `view`, of `size` physical pixels. Returns the
+    /// texture to show, which changes when the size does.
+    pub fn render(
+        &mut self,
+        view: &mut SceneView,
+        textures: &mut UserTextures,
+        size: [u32; 2],
+        camera: Camera,
+        clear_color: wgpu::Color,
+        instances: &[Instance],
+    ) -> egui::TextureId {
+        let device = textures.device;
+        let queue = textures.queue;
+        self.set_sample_count(device, textures.msaa_samples);
+        let target = RenderTarget::resize(&mut view.target, textures, "scene_target", size);
+        let size = target.size();
+        let sample_count = self.sample_count;
+        if view
+            .attachments
+            .as_ref()
+            .map(|(_, attachments_size, samples)| (*attachments_size, *samples))
+            != Some((size, sample_count))
+        {
+            let attachment = |label, format| {
+                device
+                    .create_texture(&wgpu::TextureDescriptor {
+                        label: Some(label),
+                        size: wgpu::Extent3d {
+                            width: size[0],
+                            height: size[1],
+                            depth_or_array_layers: 1,
+                        },
+                        mip_level_count: 1,
+                        sample_count,
+                        dimension: wgpu::TextureDimension::D2,
+                        format,
+                        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    })
+                    .create_view(&Default::default())
+            };
+            let attachments = Attachments {
+                depth: attachment("scene_depth", DEPTH_FORMAT),
+                multisampled: (sample_count > 1)
+                    .then(|| attachment("scene_multisampled", RenderTarget::FORMAT)),
+            };
+            view.attachments = Some((attachments, size, sample_count));
+        }
+        let (attachments, ..) = view
+            .attachments
+            .as_ref()
+            .expect("The attachments were just created");
+
+        if instances.len() as u64 > self.capacity {
+            self.capacity = (instances.len() as u64).next_power_of_two();
+            self.instances = instance_buffer(device, self.capacity);
+        }
+        let instance_data: Vec<f32> = instances
+            .iter()
+            .flat_map(|instance| {
+                let normal = instance.model.inverse().transpose();
+                let mut data = instance.model.to_cols_array().to_vec();
+                for column in [normal.x_axis, normal.y_axis, normal.z_axis] {
+                    data.extend_from_slice(&column.truncate().to_array());
+                }
+                data.extend_from_slice(&instance.color);
+                data.extend_from_slice(&[instance.shininess, instance.highlighted as u8 as f32]);
+                data
+            })
+            .collect();
+        queue.write_buffer(&self.instances, 0, &to_bytes(&instance_data));
+        let view_projection = camera.view_projection(egui::vec2(size[0] as f32, size[1] as f32));
+        let light = Vec3::new(0.4, 1.0, 0.6).normalize();
+        let mut parameters = view_projection.to_cols_array().to_vec();
+        parameters.extend_from_slice(&camera.eye.extend(1.0).to_array());
+        parameters.extend_from_slice(&light.extend(0.0).to_array());
+        let parameters = to_bytes(&parameters);
+        let push_constants = self
+            .push_constant_pipeline
+            .as_ref()
+            .filter(|_| self.use_push_constants);
+        let upload_start = Instant::now();
+        if push_constants.is_none() {
+            queue.write_buffer(&self.camera, 0, &parameters);
+        }
+        let mut upload = upload_start.elapsed();
+        if let Some(bytes) = self
+            .material
+            .values
+            .as_mut()
+            .and_then(UniformValues::take_changed)
+        {
+            queue.write_buffer(&self.material.buffer, 0, bytes);
+        }
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("scene_encoder"),
+        });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("scene_pass"),
+                // The samples are only needed until they're resolved into the target.
+                color_attachments: &[Some(match &attachments.multisampled {
+                    Some(multisampled) => wgpu::RenderPassColorAttachment {
+                        view: multisampled,
+                        resolve_target: Some(target.view()),
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(clear_color),
+                            store: false,
+                        },
+                    },
+                    None => wgpu::RenderPassColorAttachment {
+                        view: target.view(),
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(clear_color),
+                            store: true,
+                        },
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &attachments.depth,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: false,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+            if !instances.is_empty() {
+                match push_constants {
+                    Some(pipeline) => {
+                        pass.set_pipeline(pipeline);
+                        let record_start = Instant::now();
+                        pass.set_push_constants(
+                            wgpu::ShaderStages::VERTEX_FRAGMENT,
+                            0,
+                            &parameters,
+                        );
+                        upload += record_start.elapsed();
+                    }
+                    None => {
+                        pass.set_pipeline(&self.pipeline);
+                        pass.set_bind_group(0, &self.bind_group, &[]);
+                    }
+                }
+                pass.set_bind_group(1, &self.material.bind_group, &[]);
+                pass.set_vertex_buffer(0, self.vertices.slice(..));
+                pass.set_vertex_buffer(1, self.instances.slice(..));
+                pass.set_index_buffer(self.indices.slice(..), wgpu::IndexFormat::Uint16);
+                pass.draw_indexed(0..36, 0, 0..instances.len() as u32);
+            }
+        }
+        let average = if push_constants.is_some() {
+            &mut self.push_constant_us
+        } else {
+            &mut self.uniform_us
+        };
+        let upload_us = upload.as_secs_f32() * 1e6;
+        *average = Some(average.map_or(upload_us, |average| {
+            average + (upload_us - average) * SMOOTHING
+        }));
+        queue.submit(std::iter::once(encoder.finish()));
+        target.id()
+    }
+
+    /// Which way the scene parameters take, and how long handing them over took each way.
+    pub fn parameters_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Scene parameters:");
+            ui.add_enabled_ui(self.push_constant_pipeline.is_some(), |ui| {
+                ui.checkbox(&mut self.use_push_constants, "Push constants")
+                    .on_disabled_hover_text(
+                        "The device doesn't support push constants, the parameters are uploaded \
+                         to a uniform buffer instead.",
+                    );
+            });
+            let measured = |us: Option<f32>| {
+                us.map_or_else(|| "not measured".to_owned(), |us| format!("{:.2} µs", us))
+            };
+            ui.weak(format!(
+                "{} bytes per draw. Uniform buffer: {}, push constants: {}",
+                PARAMETERS_SIZE,
+                measured(self.uniform_us),
+                measured(self.push_constant_us),
+            ));
+            if let (Some(uniform), Some(push_constants)) = (self.uniform_us, self.push_constant_us)
+            {
+                ui.weak(format!("({:.2} µs saved)", uniform - push_constants));
+            }
+        });
+    }
+
+    /// The inspector of the material, generated from the fields the shader declares.
+    pub fn material_ui(&mut self, ui: &mut egui::Ui) {
+        match &mut self.material.values {
+            Some(values) => values.ui(ui),
+            None => {
+                ui.weak("The shader has no material at @group(1) @binding(0)");
+            }
+        }
+    }
+}
+
+impl MaterialUniform {
+    fn new(device: &wgpu::Device) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("scene_material_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    // Whatever the shader declares, a reload can change it.
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let (buffer, bind_group) = material_buffer(device, &layout, MATERIAL_MIN_SIZE);
+        Self {
+            layout,
+            buffer,
+            capacity: MATERIAL_MIN_SIZE,
+            bind_group,
+            values: None,
+        }
+    }
+
+    /// Lays the values out like `layout`. The fields that the last layout had too keep their
+    /// values, the others start at their defaults.
+    fn set_layout(&mut self, device: &wgpu::Device, layout: Option<UniformLayout>) {
+        self.values = match (layout, self.values.take()) {
+            (Some(layout), Some(mut values)) => {
+                values.relayout(layout);
+                Some(values)
+            }
+            (Some(layout), None) => {
+                let mut values = UniformValues::new(layout);
+                for (name, value) in MATERIAL_DEFAULTS {
+                    values.set(name, value);
+                }
+                Some(values)
+            }
+            (None, _) => None,
+        };
+        let size = self
+            .values
+            .as_ref()
+            .map_or(0, |values| values.layout().size as u64);
+        if size > self.capacity {
+            (self.buffer, self.bind_group) = material_buffer(device, &self.layout, size);
+            self.capacity = size;
+        }
+    }
+}
+
+fn material_buffer(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    size: u64,
+) -> (wgpu::Buffer, wgpu::BindGroup) {
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("scene_material"),
+        size,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("scene_material_bind_group"),
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: buffer.as_entire_binding(),
+        }],
+    });
+    (buffer, bind_group)
+}
+
+/// The shader that reads the parameters from push constants instead of the uniform buffer.
+fn push_constant_shader(source: &str) -> String {
+    source.replace("@group(0) @binding(0)\nvar<uniform>", "var<push_constant>")
+}
+
+fn create_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    source: std::borrow::Cow<str>,
+    sample_count: u32,
+) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("scene_shader"),
+        source: wgpu::ShaderSource::Wgsl(source),
+    });
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("scene_pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[
+                wgpu::VertexBufferLayout {
+                    array_stride: VERTEX_SIZE,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3],
+                },
+                wgpu::VertexBufferLayout {
+                    array_stride: INSTANCE_SIZE,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array![
+                        2 => Float32x4,
+                        3 => Float32x4,
+                        4 => Float32x4,
+                        5 => Float32x4,
+                        6 => Float32x3,
+                        7 => Float32x3,
+                        8 => Float32x3,
+                        9 => Float32x3,
+                        10 => Float32x2,
+                    ],
+                },
+            ],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(RenderTarget::FORMAT.into())],
+        }),
+        primitive: wgpu::PrimitiveState {
+            cull_mode: Some(wgpu::Face::Back),
+            ..Default::default()
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: Default::default(),
+            bias: Default::default(),
+        }),
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        },
+        multiview: None,
+    })
+}
+
+fn instance_buffer(device: &wgpu::Device, capacity: u64) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("scene_instances"),
+        // Empty buffers can't be bound.
+        size: capacity.max(1) * INSTANCE_SIZE,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+fn to_bytes(values: &[f32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+/// The positions and normals of a unit box with four vertices per face, and its triangles in
+/// counter-clockwise order.
+fn cube() -> (Vec<f32>, Vec<u16>) {
+    let mut vertices = Vec::with_capacity(24 * 6);
+    let mut indices = Vec::with_capacity(36);
+    for normal in [Vec3::X, -Vec3::X, Vec3::Y, -Vec3::Y, Vec3::Z, -Vec3::Z] {
+        // Two axes along the face, whose cross product points outwards.
+        let u = Vec3::new(normal.y, normal.z, normal.x);
+        let v = normal.cross(u);
+        let first = (vertices.len() / 6) as u16;
+        for (a, b) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            let position = (normal + u * a + v * b) * 0.5;
+            vertices.extend_from_slice(&position.to_array());
+            vertices.extend_from_slice(&normal.to_array());
+        }
+        indices.extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+    (vertices, indices)
+}
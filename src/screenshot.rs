@@ -0,0 +1,318 @@
+use std::path::PathBuf;
+
+use crate::readback::Region;
+
+/// How long the result of a capture is shown, in seconds.
+const STATUS_DURATION: f64 = 3.0;
+
+/// Where a captured region ends up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    File,
+    Clipboard,
+}
+
+/// A region of the frame that should be read back.
+#[derive(Clone, Copy, Debug)]
+pub struct Request {
+    pub target: Target,
+    pub region: Region,
+}
+
+enum State {
+    Idle,
+    /// The overlay is shown and the user drags the selection.
+    Selecting {
+        target: Target,
+        start: Option<egui::Pos2>,
+    },
+    /// The selection is done. The overlay is hidden for one frame, so that it doesn't end up in
+    /// the capture.
+    Pending(Request),
+    /// The current frame is free of the overlay and can be captured.
+    Ready(Request),
+    /// Like `Pending`, for the menu the SVG export was started from.
+    SvgPending,
+    /// The current frame can be exported as SVG.
+    SvgReady,
+}
+
+/// A capture mode where the UI dims, the user drags a rectangle and only that region of the
+/// frame is read back and saved as PNG or copied to the clipboard. Also exports whole frames as
+/// SVG, see [`crate::svg_export`].
+pub struct RegionScreenshot {
+    state: State,
+    status: Option<(String, f64)>,
+    // On some platforms the clipboard content is only available while the clipboard is alive.
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    clipboard: Option<arboard::Clipboard>,
+}
+
+impl Default for RegionScreenshot {
+    fn default() -> Self {
+        Self {
+            state: State::Idle,
+            status: None,
+            #[cfg(not(any(target_os = "android", target_os = "ios")))]
+            clipboard: None,
+        }
+    }
+}
+
+impl RegionScreenshot {
+    /// Enters the capture mode.
+    pub fn start(&mut self, target: Target) {
+        self.state = State::Selecting {
+            target,
+            start: None,
+        };
+    }
+
+    /// Exports the next frame as SVG.
+    pub fn start_svg_export(&mut self) {
+        self.state = State::SvgPending;
+    }
+
+    /// Whether the current frame should be exported as SVG.
+    pub fn take_svg_request(&mut self) -> bool {
+        match self.state {
+            State::SvgReady => {
+                self.state = State::Idle;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Shows where the SVG export ended up.
+    pub fn finish_svg(&mut self, ctx: &egui::Context, result: Result<PathBuf, String>) {
+        let message = match result {
+            Ok(path) => format!("Saved SVG to {}", path.display()),
+            Err(err) => format!("SVG export failed: {}", err),
+        };
+        self.set_status(ctx, message);
+    }
+
+    /// Shows `message` where the result of a capture is shown, for the other exports.
+    pub fn set_status(&mut self, ctx: &egui::Context, message: String) {
+        self.status = Some((message, ctx.input().time));
+        ctx.request_repaint();
+    }
+
+    /// The region to capture from the current frame, if the user finished a selection.
+    pub fn take_request(&mut self) -> Option<Request> {
+        match self.state {
+            State::Ready(request) => {
+                self.state = State::Idle;
+                Some(request)
+            }
+            _ => None,
+        }
+    }
+
+    /// Saves or copies the pixels read back for `request`.
+    pub fn finish(&mut self, ctx: &egui::Context, request: Request, pixels: Vec<u8>) {
+        let result = match request.target {
+            Target::File => save_png(request.region.size, &pixels)
+                .map(|path| format!("Saved screenshot to {}", path.display())),
+            Target::Clipboard => self
+                .copy_to_clipboard(request.region.size, pixels)
+                .map(|_| {
+                    format!(
+                        "Copied {} × {} px to the clipboard",
+                        request.region.size[0], request.region.size[1]
+                    )
+                }),
+        };
+        let message = result.unwrap_or_else(|err| format!("Screenshot failed: {}", err));
+        self.set_status(ctx, message);
+    }
+
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    fn copy_to_clipboard(&mut self, size: [u32; 2], pixels: Vec<u8>) -> Result<(), String> {
+        if self.clipboard.is_none() {
+            self.clipboard = Some(arboard::Clipboard::new().map_err(|err| err.to_string())?);
+        }
+        let clipboard = self.clipboard.as_mut().unwrap();
+        clipboard
+            .set_image(arboard::ImageData {
+                width: size[0] as usize,
+                height: size[1] as usize,
+                bytes: pixels.into(),
+            })
+            .map_err(|err| err.to_string())
+    }
+
+    /// arboard has no backend for the mobile platforms.
+    #[cfg(any(target_os = "android", target_os = "ios"))]
+    fn copy_to_clipboard(&mut self, _size: [u32; 2], _pixels: Vec<u8>) -> Result<(), String> {
+        Err("copying images is not supported on this platform".to_owned())
+    }
+
+    /// Draws the selection overlay and the result of the last capture.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        match self.state {
+            State::Selecting { target, start } => self.overlay(ctx, target, start),
+            State::Pending(request) => {
+                self.state = State::Ready(request);
+                ctx.request_repaint();
+            }
+            State::SvgPending => {
+                self.state = State::SvgReady;
+                ctx.request_repaint();
+            }
+            State::Idle | State::Ready(_) | State::SvgReady => {}
+        }
+
+        if let Some((message, time)) = &self.status {
+            let elapsed = ctx.input().time - time;
+            if elapsed > STATUS_DURATION {
+                self.status = None;
+            } else {
+                egui::Area::new("screenshot_status")
+                    .order(egui::Order::Foreground)
+                    .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -24.0])
+                    .show(ctx, |ui| {
+                        egui::Frame::popup(ui.style()).show(ui, |ui| ui.label(message));
+                    });
+                ctx.request_repaint_after(std::time::Duration::from_secs_f64(
+                    STATUS_DURATION - elapsed,
+                ));
+            }
+        }
+    }
+
+    fn overlay(&mut self, ctx: &egui::Context, target: Target, start: Option<egui::Pos2>) {
+        if ctx.input().key_pressed(egui::Key::Escape) {
+            self.state = State::Idle;
+            return;
+        }
+
+        let screen = ctx.input().screen_rect();
+        let pixels_per_point = ctx.pixels_per_point();
+        egui::Area::new("screenshot_overlay")
+            .order(egui::Order::Foreground)
+            .fixed_pos(screen.min)
+            .show(ctx, |ui| {
+                let response = ui.allocate_rect(screen, egui::Sense::drag());
+                let painter = ui.painter();
+                let dim = egui::Color32::from_black_alpha(140);
+                let pointer = response
+                    .interact_pointer_pos()
+                    .or_else(|| response.hover_pos());
+
+                let selection = match (start, pointer) {
+                    (Some(start), Some(pointer)) => Some(egui::Rect::from_two_pos(start, pointer)),
+                    _ => None,
+                };
+
+                match selection {
+                    Some(selection) => {
+                        // Dim everything around the selection.
+                        for rect in [
+                            egui::Rect::from_x_y_ranges(
+                                screen.x_range(),
+                                screen.top()..=selection.top(),
+                            ),
+                            egui::Rect::from_x_y_ranges(
+                                screen.x_range(),
+                                selection.bottom()..=screen.bottom(),
+                            ),
+                            egui::Rect::from_x_y_ranges(
+                                screen.left()..=selection.left(),
+                                selection.y_range(),
+                            ),
+                            egui::Rect::from_x_y_ranges(
+                                selection.right()..=screen.right(),
+                                selection.y_range(),
+                            ),
+                        ] {
+                            painter.rect_filled(rect, 0.0, dim);
+                        }
+                        painter.rect_stroke(
+                            selection,
+                            0.0,
+                            egui::Stroke::new(1.0, egui::Color32::WHITE),
+                        );
+
+                        let size = physical_size(selection, pixels_per_point);
+                        painter.text(
+                            selection.right_bottom() + egui::vec2(4.0, 4.0),
+                            egui::Align2::LEFT_TOP,
+                            format!("{} × {} px", size[0], size[1]),
+                            egui::FontId::monospace(14.0),
+                            egui::Color32::WHITE,
+                        );
+                    }
+                    None => {
+                        painter.rect_filled(screen, 0.0, dim);
+                        let hint = match target {
+                            Target::File => "Drag to select the region to save. Esc cancels.",
+                            Target::Clipboard => "Drag to select the region to copy. Esc cancels.",
+                        };
+                        painter.text(
+                            screen.center(),
+                            egui::Align2::CENTER_CENTER,
+                            hint,
+                            egui::FontId::proportional(20.0),
+                            egui::Color32::WHITE,
+                        );
+                    }
+                }
+
+                if response.drag_started() {
+                    self.state = State::Selecting {
+                        target,
+                        start: pointer,
+                    };
+                } else if response.drag_released() {
+                    self.state = match selection {
+                        Some(selection) => {
+                            let selection = selection.intersect(screen);
+                            let size = physical_size(selection, pixels_per_point);
+                            if size[0] == 0 || size[1] == 0 {
+                                State::Idle
+                            } else {
+                                State::Pending(Request {
+                                    target,
+                                    region: Region {
+                                        origin: [
+                                            (selection.min.x * pixels_per_point).round() as u32,
+                                            (selection.min.y * pixels_per_point).round() as u32,
+                                        ],
+                                        size,
+                                    },
+                                })
+                            }
+                        }
+                        None => State::Idle,
+                    };
+                    ctx.request_repaint();
+                }
+            });
+    }
+}
+
+fn physical_size(rect: egui::Rect, pixels_per_point: f32) -> [u32; 2] {
+    [
+        (rect.width() * pixels_per_point).round() as u32,
+        (rect.height() * pixels_per_point).round() as u32,
+    ]
+}
+
+fn save_png(size: [u32; 2], pixels: &[u8]) -> Result<PathBuf, String> {
+    let directory = dirs::picture_dir().unwrap_or_else(|| PathBuf::from("."));
+    let name = chrono::Local::now()
+        .format("egui_example_%Y%m%d_%H%M%S.png")
+        .to_string();
+    let path = directory.join(name);
+    image::save_buffer(
+        &path,
+        pixels,
+        size[0],
+        size[1],
+        image::ExtendedColorType::Rgba8,
+    )
+    .map_err(|err| err.to_string())?;
+    Ok(path)
+}
@@ -0,0 +1,354 @@
+/// The type of a field of a uniform struct, as far as the inspector can edit it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldType {
+    F32,
+    I32,
+    U32,
+    /// A vector of 2, 3 or 4 `f32`s.
+    Vector(usize),
+    /// Shown, but not editable, like matrices and arrays.
+    Other(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    /// In bytes, from the start of the struct.
+    pub offset: usize,
+    pub ty: FieldType,
+}
+
+/// The fields of the struct of a uniform buffer, with the offsets of the WGSL layout rules.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UniformLayout {
+    /// The name of the struct.
+    pub name: String,
+    /// The bytes of the whole struct, with its padding.
+    pub size: usize,
+    pub fields: Vec<Field>,
+}
+
+/// Parses `source` with naga and returns the layout of the uniform struct at `group` and
+/// `binding`, if there is one.
+pub fn reflect(source: &str, group: u32, binding: u32) -> Result<Option<UniformLayout>, String> {
+    let module = naga::front::wgsl::parse_str(source).map_err(|err| err.emit_to_string(source))?;
+    let variable = module.global_variables.iter().find(|(_, variable)| {
+        variable.space == naga::AddressSpace::Uniform
+            && variable.binding == Some(naga::ResourceBinding { group, binding })
+    });
+    let ty = match variable {
+        Some((_, variable)) => &module.types[variable.ty],
+        None => return Ok(None),
+    };
+    let (members, span) = match &ty.inner {
+        naga::TypeInner::Struct { members, span } => (members, *span),
+        _ => {
+            return Err(format!(
+                "The uniform at {} {} isn't a struct",
+                group, binding
+            ))
+        }
+    };
+    let fields = members
+        .iter()
+        .enumerate()
+        .map(|(index, member)| Field {
+            name: member
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("field {}", index)),
+            offset: member.offset as usize,
+            ty: field_type(&module.types[member.ty]),
+        })
+        .collect();
+    Ok(Some(UniformLayout {
+        name: ty.name.clone().unwrap_or_default(),
+        size: span as usize,
+        fields,
+    }))
+}
+
+fn field_type(ty: &naga::Type) -> FieldType {
+    use naga::{ScalarKind, TypeInner};
+
+    match &ty.inner {
+        TypeInner::Scalar { kind, width: 4 } => match kind {
+            ScalarKind::Float => FieldType::F32,
+            ScalarKind::Sint => FieldType::I32,
+            ScalarKind::Uint => FieldType::U32,
+            ScalarKind::Bool => FieldType::Other("bool".to_owned()),
+        },
+        TypeInner::Vector {
+            size,
+            kind: ScalarKind::Float,
+            width: 4,
+        } => FieldType::Vector(*size as usize),
+        TypeInner::Vector { size, .. } => FieldType::Other(format!("vec{}", *size as u8)),
+        TypeInner::Matrix { columns, rows, .. } => {
+            FieldType::Other(format!("mat{}x{}", *columns as u8, *rows as u8))
+        }
+        TypeInner::Array { .. } => FieldType::Other("array".to_owned()),
+        _ => FieldType::Other(ty.name.clone().unwrap_or_else(|| "struct".to_owned())),
+    }
+}
+
+/// The contents of a uniform buffer, edited field by field.
+pub struct UniformValues {
+    layout: UniformLayout,
+    bytes: Vec<u8>,
+    /// Whether `bytes` changed since the last upload.
+    changed: bool,
+}
+
+impl UniformValues {
+    /// All fields zero.
+    pub fn new(layout: UniformLayout) -> Self {
+        Self {
+            bytes: vec![0; layout.size],
+            layout,
+            changed: true,
+        }
+    }
+
+    pub fn layout(&self) -> &UniformLayout {
+        &self.layout
+    }
+
+    /// Switches to `layout`, as after the shader changed. The fields with the same name and
+    /// type keep their values, new ones start at zero.
+    pub fn relayout(&mut self, layout: UniformLayout) {
+        let mut bytes = vec![0; layout.size];
+        for field in &layout.fields {
+            let old = self
+                .layout
+                .fields
+                .iter()
+                .find(|old| old.name == field.name && old.ty == field.ty);
+            if let (Some(old), Some(size)) = (old, field.ty.size()) {
+                bytes[field.offset..field.offset + size]
+                    .copy_from_slice(&self.bytes[old.offset..old.offset + size]);
+            }
+        }
+        self.layout = layout;
+        self.bytes = bytes;
+        self.changed = true;
+    }
+
+    /// Sets the `f32` or vector field `name` to `values`, if there is one of that many
+    /// components.
+    pub fn set(&mut self, name: &str, values: &[f32]) {
+        let field = self.layout.fields.iter().find(|field| {
+            field.name == name
+                && matches!(field.ty, FieldType::F32 | FieldType::Vector(_))
+                && field.ty.components() == Some(values.len())
+        });
+        if let Some(field) = field {
+            for (index, value) in values.iter().enumerate() {
+                let offset = field.offset + index * 4;
+                self.bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+            }
+            self.changed = true;
+        }
+    }
+
+    /// The bytes to upload, if they changed since the last time.
+    pub fn take_changed(&mut self) -> Option<&[u8]> {
+        std::mem::take(&mut self.changed).then_some(&self.bytes)
+    }
+
+    /// The inspector of the fields: colors for the vectors named like one, drag values for the
+    /// other numbers.
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        egui::Grid::new(("uniform_values", &self.layout.name))
+            .num_columns(2)
+            .show(ui, |ui| {
+                for field in &self.layout.fields {
+                    ui.label(&field.name);
+                    let bytes = &mut self.bytes[field.offset..];
+                    changed |= match &field.ty {
+                        FieldType::F32 => edit::<f32>(ui, bytes, 0.01),
+                        FieldType::I32 => edit::<i32>(ui, bytes, 0.1),
+                        FieldType::U32 => edit::<u32>(ui, bytes, 0.1),
+                        FieldType::Vector(3 | 4) if field.name.contains("color") => {
+                            edit_color(ui, bytes, field.ty.components() == Some(4))
+                        }
+                        FieldType::Vector(components) => {
+                            ui.horizontal(|ui| {
+                                (0..*components).fold(false, |changed, index| {
+                                    edit::<f32>(ui, &mut bytes[index * 4..], 0.01) | changed
+                                })
+                            })
+                            .inner
+                        }
+                        FieldType::Other(ty) => {
+                            ui.weak(ty);
+                            false
+                        }
+                    };
+                    ui.end_row();
+                }
+            });
+        self.changed |= changed;
+    }
+}
+
+impl FieldType {
+    /// The number of 4 byte components of the editable types.
+    fn components(&self) -> Option<usize> {
+        match self {
+            Self::F32 | Self::I32 | Self::U32 => Some(1),
+            Self::Vector(components) => Some(*components),
+            Self::Other(_) => None,
+        }
+    }
+
+    fn size(&self) -> Option<usize> {
+        self.components().map(|components| components * 4)
+    }
+}
+
+/// A number of 4 bytes that a drag value can edit.
+trait Component: egui::emath::Numeric {
+    fn read(bytes: [u8; 4]) -> Self;
+    fn write(self) -> [u8; 4];
+}
+
+impl Component for f32 {
+    fn read(bytes: [u8; 4]) -> Self {
+        Self::from_le_bytes(bytes)
+    }
+
+    fn write(self) -> [u8; 4] {
+        self.to_le_bytes()
+    }
+}
+
+impl Component for i32 {
+    fn read(bytes: [u8; 4]) -> Self {
+        Self::from_le_bytes(bytes)
+    }
+
+    fn write(self) -> [u8; 4] {
+        self.to_le_bytes()
+    }
+}
+
+impl Component for u32 {
+    fn read(bytes: [u8; 4]) -> Self {
+        Self::from_le_bytes(bytes)
+    }
+
+    fn write(self) -> [u8; 4] {
+        self.to_le_bytes()
+    }
+}
+
+/// Edits the number at the start of `bytes`. Returns whether it changed.
+fn edit<T: Component>(ui: &mut egui::Ui, bytes: &mut [u8], speed: f64) -> bool {
+    let mut value = T::read(bytes[..4].try_into().expect("Four bytes"));
+    let changed = ui
+        .add(egui::DragValue::new(&mut value).speed(speed))
+        .changed();
+    bytes[..4].copy_from_slice(&value.write());
+    changed
+}
+
+/// Edits the linear RGB or RGBA color at the start of `bytes`. Returns whether it changed.
+fn edit_color(ui: &mut egui::Ui, bytes: &mut [u8], alpha: bool) -> bool {
+    let components = if alpha { 4 } else { 3 };
+    let mut color = [1.0; 4];
+    for (index, component) in color.iter_mut().take(components).enumerate() {
+        *component = f32::read(
+            bytes[index * 4..index * 4 + 4]
+                .try_into()
+                .expect("Four bytes"),
+        );
+    }
+    let rgba = egui::Rgba::from_rgba_premultiplied(color[0], color[1], color[2], color[3]);
+    let mut edited = rgba;
+    let alpha = match alpha {
+        true => egui::color_picker::Alpha::OnlyBlend,
+        false => egui::color_picker::Alpha::Opaque,
+    };
+    egui::color_picker::color_edit_button_rgba(ui, &mut edited, alpha);
+    if edited == rgba {
+        return false;
+    }
+    let edited = [edited.r(), edited.g(), edited.b(), edited.a()];
+    for (index, component) in edited.iter().take(components).enumerate() {
+        bytes[index * 4..index * 4 + 4].copy_from_slice(&component.to_le_bytes());
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHADER: &str = "
+        struct Material {
+            ambient: f32,
+            rim_color: vec3<f32>,
+            steps: u32,
+            transform: mat4x4<f32>,
+        };
+
+        @group(1) @binding(0)
+        var<uniform> material: Material;
+
+        @fragment
+        fn fs_main() -> @location(0) vec4<f32> {
+            return vec4<f32>(material.rim_color * material.ambient, 1.0);
+        }
+    ";
+
+    #[test]
+    fn uniform_fields_are_reflected() {
+        let layout = reflect(SHADER, 1, 0).unwrap().unwrap();
+        let fields: Vec<_> = layout
+            .fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.offset, field.ty.clone()))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("ambient", 0, FieldType::F32),
+                ("rim_color", 16, FieldType::Vector(3)),
+                ("steps", 28, FieldType::U32),
+                ("transform", 32, FieldType::Other("mat4x4".to_owned())),
+            ]
+        );
+        assert_eq!(layout.size, 96);
+        assert_eq!(reflect(SHADER, 0, 0).unwrap(), None);
+        assert!(reflect("struct {", 0, 0).is_err());
+        assert!(reflect(crate::scene_renderer::SHADER, 1, 0)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn values_survive_a_new_layout() {
+        let layout = reflect(SHADER, 1, 0).unwrap().unwrap();
+        let mut values = UniformValues::new(layout.clone());
+        values.set("ambient", &[0.5]);
+        values.set("rim_color", &[1.0, 0.5, 0.25]);
+        // Not a vector of two.
+        values.set("rim_color", &[1.0, 0.5]);
+        let mut moved = layout;
+        moved.fields.swap(0, 1);
+        moved.fields[0].offset = 0;
+        moved.fields[1].offset = 12;
+        values.relayout(moved.clone());
+        let float = |values: &UniformValues, offset: usize| {
+            f32::read(values.bytes[offset..offset + 4].try_into().unwrap())
+        };
+        let floats: Vec<f32> = (0..4).map(|index| float(&values, index * 4)).collect();
+        assert_eq!(floats, [1.0, 0.5, 0.25, 0.5]);
+        // Another type starts over.
+        moved.fields[1].ty = FieldType::I32;
+        values.relayout(moved);
+        assert_eq!(float(&values, 12), 0.0);
+    }
+}
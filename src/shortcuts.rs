@@ -0,0 +1,261 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// The actions that can be bound to a key combination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    Settings,
+    SystemMonitor,
+    GpuReport,
+    ColorPicker,
+    StressTest,
+    Hud,
+    ScreenshotToFile,
+    ScreenshotToClipboard,
+    ExportSvg,
+    Tour,
+    NextSlide,
+    PreviousSlide,
+}
+
+impl Action {
+    pub const ALL: &'static [Action] = &[
+        Action::Settings,
+        Action::SystemMonitor,
+        Action::GpuReport,
+        Action::ColorPicker,
+        Action::StressTest,
+        Action::Hud,
+        Action::ScreenshotToFile,
+        Action::ScreenshotToClipboard,
+        Action::ExportSvg,
+        Action::Tour,
+        Action::NextSlide,
+        Action::PreviousSlide,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Settings => "Show the settings",
+            Action::SystemMonitor => "Show the system monitor",
+            Action::GpuReport => "Show the GPU report",
+            Action::ColorPicker => "Show the color picker",
+            Action::StressTest => "Show the stress test",
+            Action::Hud => "Toggle the HUD layer",
+            Action::ScreenshotToFile => "Screenshot region to file",
+            Action::ScreenshotToClipboard => "Screenshot region to clipboard",
+            Action::ExportSvg => "Export frame as SVG",
+            Action::Tour => "Show the tour",
+            Action::NextSlide => "Next slide of the presentation",
+            Action::PreviousSlide => "Previous slide of the presentation",
+        }
+    }
+
+    /// The binding until the user changes it.
+    fn default_shortcut(self) -> Option<Shortcut> {
+        let command_shift = |key| Shortcut {
+            command: true,
+            shift: true,
+            alt: false,
+            key,
+        };
+        let plain = |key| Shortcut {
+            command: false,
+            shift: false,
+            alt: false,
+            key,
+        };
+        match self {
+            Action::Settings => Some(command_shift(egui::Key::P)),
+            Action::Hud => Some(command_shift(egui::Key::H)),
+            Action::ScreenshotToFile => Some(command_shift(egui::Key::S)),
+            Action::Tour => Some(plain(egui::Key::F1)),
+            // What presentation clickers send.
+            Action::NextSlide => Some(plain(egui::Key::PageDown)),
+            Action::PreviousSlide => Some(plain(egui::Key::PageUp)),
+            _ => None,
+        }
+    }
+}
+
+/// A key with the modifiers that have to be held. `command` is Ctrl, or ⌘ on macOS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shortcut {
+    pub command: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub key: egui::Key,
+}
+
+impl Shortcut {
+    fn new(modifiers: egui::Modifiers, key: egui::Key) -> Self {
+        Self {
+            command: modifiers.command,
+            shift: modifiers.shift,
+            alt: modifiers.alt,
+            key,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        if self.command {
+            label += if cfg!(target_os = "macos") {
+                "⌘"
+            } else {
+                "Ctrl+"
+            };
+        }
+        if self.alt {
+            label += if cfg!(target_os = "macos") {
+                "⌥"
+            } else {
+                "Alt+"
+            };
+        }
+        if self.shift {
+            label += if cfg!(target_os = "macos") {
+                "⇧"
+            } else {
+                "Shift+"
+            };
+        }
+        label + &format!("{:?}", self.key)
+    }
+}
+
+/// The bindings the user changed. Actions that aren't in there keep their default, `None`
+/// unbinds one.
+pub type Bindings = BTreeMap<Action, Option<Shortcut>>;
+
+/// The current binding of `action`.
+pub fn shortcut(bindings: &Bindings, action: Action) -> Option<Shortcut> {
+    bindings
+        .get(&action)
+        .copied()
+        .unwrap_or_else(|| action.default_shortcut())
+}
+
+/// The other actions with the same shortcut as `action`.
+fn conflicts(bindings: &Bindings, action: Action) -> Vec<Action> {
+    let shortcut = match shortcut(bindings, action) {
+        Some(shortcut) => shortcut,
+        None => return Vec::new(),
+    };
+    Action::ALL
+        .iter()
+        .copied()
+        .filter(|&other| other != action && self::shortcut(bindings, other) == Some(shortcut))
+        .collect()
+}
+
+/// Rebinds the actions in the settings, by pressing the new key combination.
+#[derive(Default)]
+pub struct ShortcutEditor {
+    /// The action that gets the next key combination.
+    capturing: Option<Action>,
+}
+
+impl ShortcutEditor {
+    /// The actions whose shortcuts were pressed in this frame. Of conflicting actions, only
+    /// the first one runs. Nothing runs while a text field has the keyboard, or while a shortcut
+    /// is being recorded.
+    pub fn pressed(&self, ctx: &egui::Context, bindings: &Bindings) -> Vec<Action> {
+        if self.capturing.is_some() || ctx.wants_keyboard_input() {
+            return Vec::new();
+        }
+        let input = ctx.input();
+        input
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                } => Action::ALL.iter().copied().find(|&action| {
+                    shortcut(bindings, action) == Some(Shortcut::new(*modifiers, *key))
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, bindings: &mut Bindings) {
+        if let Some(action) = self.capturing {
+            let pressed = ui.input().events.iter().find_map(|event| match event {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                } => Some(Shortcut::new(*modifiers, *key)),
+                _ => None,
+            });
+            match pressed {
+                Some(shortcut) if shortcut.key == egui::Key::Escape && !shortcut.command => {
+                    self.capturing = None
+                }
+                Some(shortcut) => {
+                    bindings.insert(action, Some(shortcut));
+                    self.capturing = None;
+                }
+                None => {}
+            }
+        }
+
+        // The platform integration turns Ctrl+C, X and V into clipboard events instead of keys.
+        ui.label("Click a shortcut and press the new key combination, or Escape to keep it.");
+        egui::Grid::new("shortcuts")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for &action in Action::ALL {
+                    ui.label(action.name());
+                    let text = match (self.capturing == Some(action), shortcut(bindings, action)) {
+                        (true, _) => "Press a key…".to_owned(),
+                        (false, Some(shortcut)) => shortcut.label(),
+                        (false, None) => "—".to_owned(),
+                    };
+                    let button = ui.button(text);
+                    if button.clicked() {
+                        self.capturing = Some(action);
+                        // Space and Enter shouldn't click the button again.
+                        ui.memory().surrender_focus(button.id);
+                    }
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(
+                                shortcut(bindings, action).is_some(),
+                                egui::Button::new("✖").small(),
+                            )
+                            .on_hover_text("Unbind")
+                            .clicked()
+                        {
+                            bindings.insert(action, None);
+                        }
+                        if ui
+                            .add_enabled(
+                                bindings.contains_key(&action),
+                                egui::Button::new("⟲").small(),
+                            )
+                            .on_hover_text("Reset to the default")
+                            .clicked()
+                        {
+                            bindings.remove(&action);
+                        }
+                        let conflicts = conflicts(bindings, action);
+                        if !conflicts.is_empty() {
+                            let names: Vec<&str> =
+                                conflicts.iter().map(|action| action.name()).collect();
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                format!("⚠ Also bound to: {}", names.join(", ")),
+                            );
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+    }
+}
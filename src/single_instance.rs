@@ -0,0 +1,86 @@
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use interprocess::local_socket::{prelude::*, GenericNamespaced, ListenerOptions, Name, Stream};
+use winit::event_loop::EventLoopProxy;
+
+use crate::Event;
+
+/// The name of the local socket the running instance listens on: a named pipe on Windows, an
+/// abstract Unix socket on Linux and a socket in `/tmp` on the other Unix systems. It contains
+/// the user name, so that every user has their own instance.
+fn socket_name() -> std::io::Result<Name<'static>> {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    format!("egui_example-{}.sock", user).to_ns_name::<GenericNamespaced>()
+}
+
+/// Hands `files` over to the instance that is already running, which opens them and comes to
+/// the front. Returns false if there is no running instance.
+pub fn hand_off(files: &[PathBuf]) -> bool {
+    let mut stream = match socket_name().and_then(Stream::connect) {
+        Ok(stream) => stream,
+        Err(_) => return false,
+    };
+    // The running instance has another working directory.
+    let message: String = files
+        .iter()
+        .map(|file| {
+            let file = std::fs::canonicalize(file).unwrap_or_else(|_| file.clone());
+            format!("{}\n", file.display())
+        })
+        .collect();
+    match stream.write_all(message.as_bytes()) {
+        Ok(()) => {
+            println!("The example is already running, its window comes to the front");
+            true
+        }
+        Err(err) => {
+            eprintln!(
+                "Can't reach the running instance, starting another one: {}",
+                err
+            );
+            false
+        }
+    }
+}
+
+/// Listens for instances started later, and sends what they hand off to the event loop as
+/// [`Event::Activate`].
+pub fn listen(proxy: EventLoopProxy<Event>) {
+    // A socket file that is left over from a crashed instance is replaced. A running instance
+    // would have accepted the hand-off.
+    let listener = socket_name().and_then(|name| {
+        ListenerOptions::new()
+            .name(name)
+            .try_overwrite(true)
+            .create_sync()
+    });
+    let listener = match listener {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("Can't listen for other instances: {}", err);
+            return;
+        }
+    };
+    let spawned = std::thread::Builder::new()
+        .name("single-instance".to_owned())
+        .spawn(move || {
+            for stream in listener.incoming().filter_map(Result::ok) {
+                // A line per file, until the other instance closes the connection.
+                let files = BufReader::new(stream)
+                    .lines()
+                    .map_while(Result::ok)
+                    .filter(|line| !line.is_empty())
+                    .map(PathBuf::from)
+                    .collect();
+                if proxy.send_event(Event::Activate(files)).is_err() {
+                    break;
+                }
+            }
+        });
+    if let Err(err) = spawned {
+        eprintln!("Can't listen for other instances: {}", err);
+    }
+}
@@ -0,0 +1,136 @@
+use tts::Tts;
+
+use crate::config::SpeechSettings;
+
+/// Speaks the widget that gained focus or changed, using the description egui emits for screen
+/// readers. Meant for platforms without a screen reader installed.
+///
+/// The speech engine is created on first use, so that the platform's TTS service is only started
+/// if the user enables the feature.
+#[derive(Default)]
+pub struct Speech {
+    tts: Option<Result<Tts, String>>,
+    voices: Vec<tts::Voice>,
+    /// The voice the engine started with, to go back to "System default".
+    default_voice: Option<tts::Voice>,
+    /// The rate and voice the engine is currently configured with.
+    applied: Option<(f32, Option<String>)>,
+}
+
+impl Speech {
+    /// Speaks the last accessibility event of the frame.
+    pub fn handle_output(&mut self, output: &egui::PlatformOutput, settings: &SpeechSettings) {
+        if !settings.enabled {
+            return;
+        }
+        let description = output.events_description();
+        if description.is_empty() {
+            return;
+        }
+        self.apply(settings);
+        if let Some(Ok(tts)) = &mut self.tts {
+            if let Err(err) = tts.speak(description, true) {
+                eprintln!("Can't speak: {}", err);
+            }
+        }
+    }
+
+    /// Draws the speech settings.
+    pub fn ui(&mut self, ui: &mut egui::Ui, settings: &mut SpeechSettings) {
+        ui.checkbox(&mut settings.enabled, "Speak focused widgets")
+            .on_hover_text("Move the focus with Tab. Clicks and value changes are spoken too.");
+        if !settings.enabled {
+            return;
+        }
+        self.apply(settings);
+        let tts = match &self.tts {
+            Some(Ok(tts)) => tts,
+            Some(Err(err)) => {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!("Text-to-speech is unavailable: {}", err),
+                );
+                return;
+            }
+            None => return,
+        };
+        let features = tts.supported_features();
+
+        ui.add_enabled(
+            features.rate,
+            egui::Slider::new(&mut settings.rate, 0.5..=2.0)
+                .text("Rate")
+                .suffix("×"),
+        );
+        if features.voice && !self.voices.is_empty() {
+            let selected = settings
+                .voice
+                .as_ref()
+                .and_then(|id| self.voices.iter().find(|voice| voice.id() == *id))
+                .map(voice_label)
+                .unwrap_or_else(|| "System default".to_owned());
+            egui::ComboBox::from_label("Voice")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut settings.voice, None, "System default");
+                    for voice in &self.voices {
+                        ui.selectable_value(
+                            &mut settings.voice,
+                            Some(voice.id()),
+                            voice_label(voice),
+                        );
+                    }
+                });
+        }
+    }
+
+    /// Creates the engine if needed and pushes changed settings to it.
+    fn apply(&mut self, settings: &SpeechSettings) {
+        if self.tts.is_none() {
+            let tts = Tts::default().map_err(|err| err.to_string());
+            if let Ok(tts) = &tts {
+                let features = tts.supported_features();
+                if features.voice {
+                    self.voices = tts.voices().unwrap_or_default();
+                }
+                if features.get_voice {
+                    self.default_voice = tts.voice().ok().flatten();
+                }
+            }
+            self.tts = Some(tts);
+        }
+        let tts = match &mut self.tts {
+            Some(Ok(tts)) => tts,
+            _ => return,
+        };
+
+        let wanted = (settings.rate, settings.voice.clone());
+        if self.applied.as_ref() == Some(&wanted) {
+            return;
+        }
+        let features = tts.supported_features();
+        if features.rate {
+            let rate = (tts.normal_rate() * settings.rate).clamp(tts.min_rate(), tts.max_rate());
+            if let Err(err) = tts.set_rate(rate) {
+                eprintln!("Can't set the speech rate: {}", err);
+            }
+        }
+        if features.voice {
+            let voice = settings
+                .voice
+                .as_ref()
+                .and_then(|id| self.voices.iter().find(|voice| voice.id() == *id))
+                .or(self.default_voice.as_ref());
+            if let Some(voice) = voice {
+                if let Err(err) = tts.set_voice(voice) {
+                    eprintln!("Can't set the voice: {}", err);
+                }
+            }
+        }
+        self.applied = Some(wanted);
+    }
+}
+
+fn voice_label(voice: &tts::Voice) -> String {
+    format!("{} ({})", voice.name(), voice.language())
+}
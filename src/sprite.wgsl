@@ -0,0 +1,56 @@
+// Draws a batch of textured quads from a sprite atlas, one instance per sprite.
+
+struct Camera {
+    // The world position in the center of the target.
+    center: vec2<f32>,
+    // From world units to clip space.
+    scale: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+@group(0) @binding(1)
+var atlas: texture_2d<f32>;
+@group(0) @binding(2)
+var atlas_sampler: sampler;
+
+struct Sprite {
+    @location(0) min: vec2<f32>,
+    @location(1) max: vec2<f32>,
+    @location(2) uv_min: vec2<f32>,
+    @location(3) uv_max: vec2<f32>,
+    // Premultiplied.
+    @location(4) tint: vec4<f32>,
+    // Around the center, in radians.
+    @location(5) rotation: f32,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) tint: vec4<f32>,
+};
+
+// The four corners of a triangle strip.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, sprite: Sprite) -> VertexOutput {
+    let corner = vec2<f32>(f32(index & 1u), f32(index >> 1u));
+    let offset = (corner - 0.5) * (sprite.max - sprite.min);
+    let c = cos(sprite.rotation);
+    let s = sin(sprite.rotation);
+    let rotated = vec2<f32>(c * offset.x - s * offset.y, s * offset.x + c * offset.y);
+    let world = (sprite.min + sprite.max) * 0.5 + rotated;
+    // The world has its y axis downwards, like the screen.
+    let clip = (world - camera.center) * camera.scale * vec2<f32>(1.0, -1.0);
+    var out: VertexOutput;
+    out.position = vec4<f32>(clip, 0.0, 1.0);
+    out.uv = mix(sprite.uv_min, sprite.uv_max, corner);
+    out.tint = sprite.tint;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // The atlas is premultiplied, like every texture egui shows.
+    return textureSample(atlas, atlas_sampler, in.uv) * in.tint;
+}
@@ -0,0 +1,207 @@
+use crate::user_textures::{RenderTarget, UserTextures};
+
+/// The bytes of a sprite in the instance buffer.
+const SPRITE_SIZE: u64 = 13 * 4;
+
+/// A textured quad in world units.
+#[derive(Clone, Copy)]
+pub struct Sprite {
+    pub rect: egui::Rect,
+    /// Where the sprite is in the atlas, from 0.0 to 1.0.
+    pub uv: egui::Rect,
+    pub tint: egui::Rgba,
+    /// Around the center of `rect`, in radians.
+    pub rotation: f32,
+}
+
+/// What part of the world a [`SpriteBatch`] renders.
+#[derive(Clone, Copy)]
+pub struct Camera {
+    /// The world position in the center of the target.
+    pub center: egui::Pos2,
+    /// Physical pixels per world unit.
+    pub zoom: f32,
+}
+
+/// A small 2D renderer with its own pipeline, which draws all sprites of a frame with one
+/// instanced draw call into a [`RenderTarget`].
+pub struct SpriteBatch {
+    pipeline: wgpu::RenderPipeline,
+    camera: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    instances: wgpu::Buffer,
+    /// How many sprites fit into `instances`.
+    capacity: u64,
+    target: Option<RenderTarget>,
+}
+
+impl SpriteBatch {
+    /// A renderer that draws sprites from `atlas`, a premultiplied `Rgba8UnormSrgb` texture.
+    pub fn new(device: &wgpu::Device, atlas: &wgpu::Texture) -> Self {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("sprite.wgsl"));
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("sprite_pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: SPRITE_SIZE,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array![
+                        0 => Float32x2,
+                        1 => Float32x2,
+                        2 => Float32x2,
+                        3 => Float32x2,
+                        4 => Float32x4,
+                        5 => Float32,
+                    ],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: RenderTarget::FORMAT,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: Default::default(),
+            multiview: None,
+        });
+        let camera = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("sprite_camera"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        // Pixel art stays sharp when zoomed in.
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("sprite_atlas_sampler"),
+            ..Default::default()
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("sprite_bind_group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(
+                        &atlas.create_view(&Default::default()),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+        Self {
+            pipeline,
+            camera,
+            bind_group,
+            instances: sprite_buffer(device, 0),
+            capacity: 0,
+            target: None,
+        }
+    }
+
+    /// Renders `sprites` in order into a target of `size` physical pixels. Returns the texture
+    /// to show, which changes when the size does.
+    pub fn render(
+        &mut self,
+        textures: &mut UserTextures,
+        size: [u32; 2],
+        camera: Camera,
+        clear_color: wgpu::Color,
+        sprites: &[Sprite],
+    ) -> egui::TextureId {
+        let device = textures.device;
+        let queue = textures.queue;
+        let target = RenderTarget::resize(&mut self.target, textures, "sprite_target", size);
+        let size = target.size();
+
+        if sprites.len() as u64 > self.capacity {
+            self.capacity = (sprites.len() as u64).next_power_of_two();
+            self.instances = sprite_buffer(device, self.capacity);
+        }
+        let instances: Vec<u8> = sprites
+            .iter()
+            .flat_map(|sprite| {
+                let tint = sprite.tint.to_array();
+                [
+                    sprite.rect.min.x,
+                    sprite.rect.min.y,
+                    sprite.rect.max.x,
+                    sprite.rect.max.y,
+                    sprite.uv.min.x,
+                    sprite.uv.min.y,
+                    sprite.uv.max.x,
+                    sprite.uv.max.y,
+                    tint[0],
+                    tint[1],
+                    tint[2],
+                    tint[3],
+                    sprite.rotation,
+                ]
+            })
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        queue.write_buffer(&self.instances, 0, &instances);
+        let scale = [
+            2.0 * camera.zoom / size[0] as f32,
+            2.0 * camera.zoom / size[1] as f32,
+        ];
+        let uniforms: Vec<u8> = [camera.center.x, camera.center.y, scale[0], scale[1]]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        queue.write_buffer(&self.camera, 0, &uniforms);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("sprite_encoder"),
+        });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("sprite_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target.view(),
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(clear_color),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            if !sprites.is_empty() {
+                pass.set_pipeline(&self.pipeline);
+                pass.set_bind_group(0, &self.bind_group, &[]);
+                pass.set_vertex_buffer(0, self.instances.slice(..));
+                pass.draw(0..4, 0..sprites.len() as u32);
+            }
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+        target.id()
+    }
+}
+
+fn sprite_buffer(device: &wgpu::Device, capacity: u64) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("sprite_instances"),
+        // Empty buffers can't be bound.
+        size: capacity.max(1) * SPRITE_SIZE,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
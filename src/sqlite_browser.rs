@@ -0,0 +1,404 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, InterruptHandle, OpenFlags};
+
+use crate::data_table::{self, DataTable};
+
+/// How many rows of a result are kept. The query stops there.
+const MAX_ROWS: usize = 100_000;
+/// The rows the column widths are measured from.
+const MEASURED_ROWS: usize = 100;
+
+/// Opens a SQLite database, lists its tables and runs queries on it. The connection lives on a
+/// thread that runs the queries one after another, so a slow query can be cancelled and never
+/// blocks the UI. Results are shown in a [`DataTable`] and can be exported as CSV.
+///
+/// Databases are opened by entering their path or by dropping them onto the window.
+#[derive(Default)]
+pub struct SqliteBrowser {
+    path: String,
+    database: Option<Database>,
+    tables: Vec<String>,
+    sql: String,
+    running: bool,
+    result: Option<QueryResult>,
+    widths: Vec<f32>,
+    sort: Option<(usize, bool)>,
+    /// The order of the sorted result rows.
+    order: Option<Vec<usize>>,
+    error: Option<String>,
+    export_path: String,
+    /// The result of the last export.
+    status: Option<String>,
+}
+
+struct Database {
+    queries: mpsc::Sender<String>,
+    messages: mpsc::Receiver<Message>,
+    interrupt: InterruptHandle,
+}
+
+impl Drop for Database {
+    fn drop(&mut self) {
+        // The thread ends with its running query, once the sender is gone.
+        self.interrupt.interrupt();
+    }
+}
+
+enum Message {
+    Tables(Result<Vec<String>, String>),
+    Result(Result<QueryResult, String>),
+}
+
+struct QueryResult {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+    /// Whether the query returned more than [`MAX_ROWS`] rows.
+    truncated: bool,
+    /// The changed rows, for statements that don't return rows.
+    changes: Option<usize>,
+    duration: Duration,
+}
+
+impl SqliteBrowser {
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if *open {
+            let dropped = ctx.input().raw.dropped_files.iter().find_map(|file| {
+                file.path.clone().filter(|path| {
+                    path.extension().is_some_and(|extension| {
+                        ["db", "sqlite", "sqlite3"]
+                            .iter()
+                            .any(|known| extension.eq_ignore_ascii_case(known))
+                    })
+                })
+            });
+            if let Some(path) = dropped {
+                self.path = path.display().to_string();
+                self.open(ctx, &path);
+            }
+        }
+        self.poll(ctx);
+
+        egui::Window::new("🗄 SQLite browser")
+            .open(open)
+            .default_size([800.0, 520.0])
+            .show(ctx, |ui| self.ui(ui));
+    }
+
+    fn open(&mut self, ctx: &egui::Context, path: &Path) {
+        // Doesn't create a database for a mistyped path.
+        let flags = OpenFlags::default().difference(OpenFlags::SQLITE_OPEN_CREATE);
+        let connection = match Connection::open_with_flags(path, flags) {
+            Ok(connection) => connection,
+            Err(err) => {
+                self.error = Some(format!("Can't open {}: {}", path.display(), err));
+                return;
+            }
+        };
+        let (queries, receiver) = mpsc::channel();
+        let (sender, messages) = mpsc::channel();
+        let interrupt = connection.get_interrupt_handle();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || run_queries(connection, receiver, sender, ctx));
+
+        *self = Self {
+            path: self.path.clone(),
+            database: Some(Database {
+                queries,
+                messages,
+                interrupt,
+            }),
+            sql: std::mem::take(&mut self.sql),
+            export_path: path.with_extension("csv").display().to_string(),
+            ..Default::default()
+        };
+    }
+
+    fn poll(&mut self, ctx: &egui::Context) {
+        let database = match &self.database {
+            Some(database) => database,
+            None => return,
+        };
+        while let Ok(message) = database.messages.try_recv() {
+            match message {
+                Message::Tables(Ok(tables)) => self.tables = tables,
+                Message::Tables(Err(err)) => self.error = Some(err),
+                Message::Result(result) => {
+                    self.running = false;
+                    match result {
+                        Ok(result) => {
+                            let rows = result.rows.iter().take(MEASURED_ROWS).cloned();
+                            self.widths = data_table::column_widths(ctx, &result.columns, rows);
+                            self.sort = None;
+                            self.order = None;
+                            self.result = Some(result);
+                            self.error = None;
+                        }
+                        Err(err) => self.error = Some(err),
+                    }
+                }
+            }
+        }
+    }
+
+    fn run(&mut self) {
+        let database = match &self.database {
+            Some(database) => database,
+            None => return,
+        };
+        if self.running || self.sql.trim().is_empty() {
+            return;
+        }
+        if database.queries.send(self.sql.clone()).is_ok() {
+            self.running = true;
+            self.status = None;
+        } else {
+            self.error = Some("The query thread crashed".to_owned());
+        }
+    }
+
+    fn export(&self) -> Result<String, String> {
+        let result = match &self.result {
+            Some(result) => result,
+            None => return Err("There is no result to export".to_owned()),
+        };
+        let mut csv = String::new();
+        crate::csv_file::write_record(&mut csv, &result.columns, ',');
+        for index in 0..result.rows.len() {
+            let row = self.order.as_ref().map_or(index, |order| order[index]);
+            crate::csv_file::write_record(&mut csv, &result.rows[row], ',');
+        }
+        std::fs::write(&self.export_path, csv).map_err(|err| err.to_string())?;
+        Ok(format!(
+            "Exported {} rows to {}",
+            result.rows.len(),
+            self.export_path
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let response = ui
+                .add(egui::TextEdit::singleline(&mut self.path).hint_text("Path, or drop a file"));
+            let entered = response.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+            if ui.button("Open").clicked() || entered {
+                let path = PathBuf::from(&self.path);
+                self.open(ui.ctx(), &path);
+            }
+        });
+        if self.database.is_none() {
+            if let Some(error) = &self.error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            return;
+        }
+
+        egui::SidePanel::left("sqlite_tables")
+            .resizable(true)
+            .default_width(160.0)
+            .show_inside(ui, |ui| {
+                ui.strong("Tables");
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    let mut clicked = None;
+                    for table in &self.tables {
+                        if ui.selectable_label(false, table).clicked() {
+                            clicked = Some(table.clone());
+                        }
+                    }
+                    if let Some(table) = clicked {
+                        self.sql = format!("SELECT * FROM \"{}\"", table.replace('"', "\"\""));
+                        self.run();
+                    }
+                });
+            });
+
+        let run_shortcut = ui
+            .input_mut()
+            .consume_key(egui::Modifiers::COMMAND, egui::Key::Enter);
+        ui.add(
+            egui::TextEdit::multiline(&mut self.sql)
+                .code_editor()
+                .desired_rows(4)
+                .desired_width(f32::INFINITY)
+                .hint_text("SELECT …"),
+        );
+        ui.horizontal(|ui| {
+            let run = ui
+                .add_enabled(!self.running, egui::Button::new("▶ Run"))
+                .on_hover_text("Ctrl+Enter");
+            if run.clicked() || run_shortcut {
+                self.run();
+            }
+            if ui
+                .add_enabled(self.running, egui::Button::new("⏹ Cancel"))
+                .clicked()
+            {
+                if let Some(database) = &self.database {
+                    database.interrupt.interrupt();
+                }
+            }
+            if self.running {
+                ui.spinner();
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        let result = match &self.result {
+            Some(result) => result,
+            None => return,
+        };
+        let milliseconds = result.duration.as_secs_f64() * 1000.0;
+        if let Some(changes) = result.changes {
+            ui.label(format!(
+                "{} rows changed in {:.1} ms",
+                changes, milliseconds
+            ));
+            return;
+        }
+        let mut export = false;
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "{}{} rows in {:.1} ms",
+                result.rows.len(),
+                if result.truncated { "+" } else { "" },
+                milliseconds
+            ));
+            ui.separator();
+            ui.add(egui::TextEdit::singleline(&mut self.export_path).hint_text("Export path"));
+            export = ui.button("Export CSV").clicked();
+            if let Some(status) = &self.status {
+                ui.label(status);
+            }
+        });
+        if export {
+            self.status = Some(self.export().unwrap_or_else(|err| err));
+        }
+        ui.separator();
+
+        let result = self.result.as_ref().unwrap();
+        let clicked = DataTable::new(&result.columns, &self.widths, result.rows.len())
+            .sort(self.sort)
+            .show(ui, |index| {
+                let row = self.order.as_ref().map_or(index, |order| order[index]);
+                (row + 1, result.rows[row].clone())
+            });
+        if let Some(column) = clicked {
+            self.sort = data_table::next_sort(self.sort, column);
+            self.order = self.sort.map(|(column, descending)| {
+                let keys: Vec<String> = result
+                    .rows
+                    .iter()
+                    .map(|row| row.get(column).cloned().unwrap_or_default())
+                    .collect();
+                data_table::sort_order(&keys, descending)
+            });
+        }
+    }
+}
+
+/// Runs the queries on `connection`, sending their results and the tables after each of them.
+fn run_queries(
+    connection: Connection,
+    queries: mpsc::Receiver<String>,
+    messages: mpsc::Sender<Message>,
+    ctx: egui::Context,
+) {
+    let send = |message| {
+        let sent = messages.send(message).is_ok();
+        ctx.request_repaint();
+        sent
+    };
+    if !send(Message::Tables(tables(&connection))) {
+        return;
+    }
+    for sql in queries {
+        let result = query(&connection, &sql).map_err(|err| err.to_string());
+        if !send(Message::Result(result)) || !send(Message::Tables(tables(&connection))) {
+            return;
+        }
+    }
+}
+
+fn tables(connection: &Connection) -> Result<Vec<String>, String> {
+    let sql = "SELECT name FROM sqlite_schema WHERE type IN ('table', 'view') ORDER BY name";
+    let tables = connection
+        .prepare(sql)
+        .and_then(|mut statement| {
+            statement
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<_, _>>()
+        })
+        .map_err(|err| format!("Can't list the tables: {}", err))?;
+    Ok(tables)
+}
+
+fn query(connection: &Connection, sql: &str) -> rusqlite::Result<QueryResult> {
+    let start = Instant::now();
+    let mut statement = match connection.prepare(sql) {
+        Ok(statement) => statement,
+        // Scripts of several statements run as a batch, without results.
+        Err(rusqlite::Error::MultipleStatement) => {
+            let changes = connection.total_changes();
+            connection.execute_batch(sql)?;
+            return Ok(QueryResult {
+                columns: Vec::new(),
+                rows: Vec::new(),
+                truncated: false,
+                changes: Some((connection.total_changes() - changes) as usize),
+                duration: start.elapsed(),
+            });
+        }
+        Err(err) => return Err(err),
+    };
+    let columns: Vec<String> = statement
+        .column_names()
+        .into_iter()
+        .map(str::to_owned)
+        .collect();
+    if columns.is_empty() {
+        let changes = statement.execute([])?;
+        return Ok(QueryResult {
+            columns,
+            rows: Vec::new(),
+            truncated: false,
+            changes: Some(changes),
+            duration: start.elapsed(),
+        });
+    }
+
+    let mut rows = Vec::new();
+    let mut truncated = false;
+    let mut cursor = statement.query([])?;
+    while let Some(row) = cursor.next()? {
+        if rows.len() == MAX_ROWS {
+            truncated = true;
+            break;
+        }
+        let values = (0..columns.len())
+            .map(|column| row.get_ref(column).map(format_value))
+            .collect::<rusqlite::Result<_>>()?;
+        rows.push(values);
+    }
+    Ok(QueryResult {
+        columns,
+        rows,
+        truncated,
+        changes: None,
+        duration: start.elapsed(),
+    })
+}
+
+fn format_value(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => "NULL".to_owned(),
+        ValueRef::Integer(integer) => integer.to_string(),
+        ValueRef::Real(real) => real.to_string(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned(),
+        ValueRef::Blob(blob) => format!("<{} bytes>", blob.len()),
+    }
+}
@@ -0,0 +1,69 @@
+use std::time::{Duration, Instant};
+
+/// How long the phases of the startup took, shown in the "Startup report" window.
+#[derive(Clone)]
+pub struct StartupReport {
+    start: Instant,
+    phases: Vec<(&'static str, Duration)>,
+    /// When the first frame was presented, relative to the start.
+    first_frame: Option<Duration>,
+}
+
+impl Default for StartupReport {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            phases: Vec::new(),
+            first_frame: None,
+        }
+    }
+}
+
+impl StartupReport {
+    /// Records that `phase` ran from `start` until now.
+    pub fn record(&mut self, phase: &'static str, start: Instant) {
+        self.phases.push((phase, start.elapsed()));
+    }
+
+    /// Records the first presented frame. Later frames are ignored.
+    pub fn first_frame_presented(&mut self) {
+        self.first_frame.get_or_insert_with(|| self.start.elapsed());
+    }
+
+    pub fn first_frame(&self) -> Option<Duration> {
+        self.first_frame
+    }
+
+    pub fn show(&self, ctx: &egui::Context, open: &mut bool) {
+        egui::Window::new("⏱ Startup report")
+            .open(open)
+            .resizable(false)
+            .show(ctx, |ui| self.ui(ui));
+    }
+
+    pub fn ui(&self, ui: &mut egui::Ui) {
+        egui::Grid::new("startup_report")
+            .striped(true)
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (phase, duration) in &self.phases {
+                    ui.label(*phase);
+                    ui.label(format!("{:.1} ms", duration.as_secs_f64() * 1000.0));
+                    ui.end_row();
+                }
+            });
+        ui.separator();
+        match self.first_frame {
+            Some(first_frame) => ui.strong(format!(
+                "First frame presented {:.1} ms after the start",
+                first_frame.as_secs_f64() * 1000.0
+            )),
+            None => ui.label("The first frame wasn't presented yet"),
+        };
+        ui.weak(
+            "wgpu 0.13 has no pipeline cache that could be stored across runs. Most drivers keep \
+             their own shader cache on disk though, so starting a second time shows how much of \
+             the pipeline time was shader compilation.",
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use crate::frame_stats::{FrameRecord, PassStats, STAGES, TOTALS};
+
+/// Generates windows full of widgets and animated shapes every frame, to find out how far the
+/// backend scales on this machine. The cost of each stage of the frame is averaged over the
+/// recent frames below the sliders.
+pub struct StressTest {
+    windows: usize,
+    widgets: usize,
+    shapes: usize,
+    /// Whether the layers are encoded on threads of their own, see `RenderGraph::execute`.
+    multithreaded_encoding: bool,
+    /// The recent frames, the oldest first.
+    frames: Vec<FrameRecord>,
+}
+
+impl Default for StressTest {
+    fn default() -> Self {
+        Self {
+            windows: 4,
+            widgets: 20,
+            shapes: 100,
+            multithreaded_encoding: false,
+            frames: Vec::new(),
+        }
+    }
+}
+
+impl StressTest {
+    /// Takes the statistics of the recent frames. Only the ones with GPU times are averaged for
+    /// the GPU, those come in a few frames late.
+    pub fn set_recent_frames(&mut self, frames: &[FrameRecord]) {
+        self.frames.clear();
+        self.frames.extend_from_slice(frames);
+    }
+
+    pub fn multithreaded_encoding(&self) -> bool {
+        self.multithreaded_encoding
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        egui::Window::new("🔥 Stress test")
+            .open(open)
+            .resizable(false)
+            .show(ctx, |ui| self.ui(ui));
+        if !*open {
+            return;
+        }
+
+        let time = ctx.input().time;
+        let screen = ctx.input().screen_rect();
+        self.shapes_ui(ctx, screen, time);
+        // The windows are laid out in a grid over the screen, until they're moved.
+        let columns = (self.windows as f32).sqrt().ceil().max(1.0) as usize;
+        let cell = screen.size() / columns as f32;
+        for index in 0..self.windows {
+            let (column, row) = (index % columns, index / columns);
+            egui::Window::new(format!("Stress {}", index))
+                .id(egui::Id::new(("stress_test", index)))
+                .default_pos(screen.min + egui::vec2(column as f32, row as f32) * cell)
+                .default_size(egui::vec2(220.0, 240.0))
+                .vscroll(true)
+                .show(ctx, |ui| widgets_ui(ui, index, self.widgets, time));
+        }
+        // Everything is animated.
+        ctx.request_repaint();
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.add(egui::Slider::new(&mut self.windows, 0..=200).text("Windows"));
+        ui.add(egui::Slider::new(&mut self.widgets, 0..=500).text("Widgets per window"));
+        ui.add(
+            egui::Slider::new(&mut self.shapes, 0..=20_000)
+                .logarithmic(true)
+                .text("Animated shapes"),
+        );
+        ui.checkbox(
+            &mut self.multithreaded_encoding,
+            "Encode the layers on separate threads",
+        )
+        .on_hover_text(
+            "Records the HUD and the main UI into command buffers of their own in parallel, and \
+             submits them together. Compare the encode time with the HUD layer on.",
+        );
+
+        ui.separator();
+        let records = self.frames.len();
+        let (triangles, draw_calls) = self
+            .frames
+            .last()
+            .map_or((0, 0), |record| (record.triangles, record.draw_calls));
+        ui.label(format!(
+            "{} triangles in {} draw calls, averaged over {} frames:",
+            triangles, draw_calls, records
+        ));
+        if let Some(last) = self.frames.last() {
+            pass_stats_ui(ui, &last.passes);
+        }
+        let averages: Vec<(&str, Option<f32>)> = STAGES
+            .iter()
+            .chain(TOTALS)
+            .map(|(name, stage)| {
+                let values: Vec<f32> = self.frames.iter().filter_map(stage).collect();
+                let average =
+                    (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32);
+                (*name, average)
+            })
+            .collect();
+        // The bars share the scale of the slowest total, so that the stages can be compared.
+        let scale = averages
+            .iter()
+            .filter_map(|(_, average)| *average)
+            .fold(1.0, f32::max);
+        egui::Grid::new("stress_test_stages")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for (name, average) in averages {
+                    ui.label(name);
+                    match average {
+                        Some(average) => {
+                            ui.monospace(format!("{:7.2} ms", average));
+                            ui.add(egui::ProgressBar::new(average / scale).desired_width(120.0));
+                        }
+                        None => {
+                            ui.weak("n/a");
+                            ui.label("");
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+
+    /// Circles and rectangles moving along Lissajous curves, behind the windows.
+    fn shapes_ui(&self, ctx: &egui::Context, screen: egui::Rect, time: f64) {
+        let painter = ctx.layer_painter(egui::LayerId::background());
+        for index in 0..self.shapes {
+            let phase = index as f64 * 0.37;
+            let t = time * 0.5 + phase;
+            let pos = screen.center()
+                + egui::vec2(
+                    (t * 1.3).sin() as f32 * 0.45 * screen.width(),
+                    (t * 1.7 + phase).cos() as f32 * 0.45 * screen.height(),
+                );
+            let hue = (index as f32 * 0.618_034).fract();
+            let color: egui::Color32 = egui::color::Hsva::new(hue, 0.8, 0.9, 0.6).into();
+            let size = 4.0 + (index % 7) as f32 * 2.0;
+            if index % 2 == 0 {
+                painter.circle_filled(pos, size, color);
+            } else {
+                let rect = egui::Rect::from_center_size(pos, egui::vec2(size, size) * 2.0);
+                painter.rect(rect, 2.0, color, (1.0, egui::Color32::WHITE));
+            }
+        }
+    }
+}
+
+/// The commands the render passes of the last frame recorded.
+fn pass_stats_ui(ui: &mut egui::Ui, passes: &[PassStats]) {
+    egui::Grid::new("stress_test_passes")
+        .num_columns(5)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Pass");
+            ui.strong("Draws")
+                .on_hover_text("Draw calls, and the triangles in them");
+            ui.strong("Triangles");
+            ui.strong("Scissor")
+                .on_hover_text("The scissor rects that changed, of all set");
+            ui.strong("Textures")
+                .on_hover_text("The texture binds that switched textures, of all");
+            ui.end_row();
+            for pass in passes {
+                ui.label(pass.name);
+                ui.monospace(pass.draw_calls.to_string());
+                ui.monospace(pass.triangles.to_string());
+                ui.monospace(format!("{} / {}", pass.scissor_changes, pass.scissor_rects));
+                ui.monospace(format!(
+                    "{} / {}",
+                    pass.texture_switches, pass.texture_binds
+                ));
+                ui.end_row();
+            }
+        });
+}
+
+/// The widgets of one generated window. Their values come from the time, so that they change in
+/// every frame, and what the user does to them is discarded.
+fn widgets_ui(ui: &mut egui::Ui, window: usize, widgets: usize, time: f64) {
+    for index in 0..widgets {
+        let mut value = ((time + index as f64 * 0.1 + window as f64).sin() * 0.5 + 0.5) as f32;
+        match index % 6 {
+            0 => {
+                ui.label(format!("Label {} of window {}", index, window));
+            }
+            1 => {
+                let _ = ui.button(format!("Button {}", index));
+            }
+            2 => {
+                let mut checked = value > 0.5;
+                ui.checkbox(&mut checked, format!("Checkbox {}", index));
+            }
+            3 => {
+                ui.add(egui::Slider::new(&mut value, 0.0..=1.0).text("Slider"));
+            }
+            4 => {
+                ui.add(egui::ProgressBar::new(value).show_percentage());
+            }
+            _ => {
+                ui.horizontal(|ui| {
+                    let mut percent = value * 100.0;
+                    ui.add(egui::DragValue::new(&mut percent).speed(1.0));
+                    ui.monospace(format!("{:.3}", value));
+                });
+            }
+        }
+    }
+}
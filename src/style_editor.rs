@@ -0,0 +1,225 @@
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use crate::config::Accessibility;
+
+/// The style tab of the settings window: egui's own style editor, a few presets, and import and
+/// export of themes as JSON files in the `themes` directory next to the config.
+///
+/// The editor works on the stored style. The accessibility modes are applied on top of it, see
+/// [`effective_style`], so that they never end up in an exported theme.
+#[derive(Default)]
+pub struct StyleEditor {
+    theme_name: String,
+    status: Option<String>,
+}
+
+impl StyleEditor {
+    /// Shows the editor for `style`, which is `None` as long as egui's default style is used.
+    pub fn ui(&mut self, ui: &mut egui::Ui, style: &mut Option<egui::Style>) {
+        let before = style.clone().unwrap_or_default();
+        let mut edited = before.clone();
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Presets:");
+            for (name, preset) in PRESETS {
+                if ui.button(*name).clicked() {
+                    edited = preset(&before);
+                }
+            }
+            if ui.button("Reset").clicked() {
+                edited = egui::Style::default();
+            }
+        });
+
+        ui.separator();
+        self.themes_ui(ui, &mut edited);
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+
+        ui.separator();
+        egui::ScrollArea::vertical()
+            .max_height(400.0)
+            .show(ui, |ui| edited.ui(ui));
+
+        if edited != before {
+            *style = Some(edited);
+        }
+    }
+
+    fn themes_ui(&mut self, ui: &mut egui::Ui, style: &mut egui::Style) {
+        let dir = match crate::paths::themes_dir() {
+            Some(dir) => dir,
+            None => {
+                ui.weak("No config directory available to store themes in.");
+                return;
+            }
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Theme name:");
+            ui.text_edit_singleline(&mut self.theme_name);
+            let name = self.theme_name.trim();
+            let valid = !name.is_empty() && !name.contains(['/', '\\', '.']);
+            if ui
+                .add_enabled(valid, egui::Button::new("💾 Export"))
+                .clicked()
+            {
+                let path = dir.join(format!("{}.json", name));
+                self.status = Some(match export(&path, style) {
+                    Ok(()) => format!("Exported theme to {}", path.display()),
+                    Err(err) => format!("Can't export theme: {}", err),
+                });
+            }
+        });
+
+        let themes = list_themes(&dir);
+        if themes.is_empty() {
+            ui.weak(format!("Exported themes show up here ({}).", dir.display()));
+            return;
+        }
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Import:");
+            for path in themes {
+                let name = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                if ui
+                    .button(&name)
+                    .on_hover_text(path.display().to_string())
+                    .clicked()
+                {
+                    match import(&path) {
+                        Ok(imported) => {
+                            *style = imported;
+                            self.theme_name = name;
+                            self.status = None;
+                        }
+                        Err(err) => {
+                            self.status = Some(format!("Can't import {}: {}", path.display(), err))
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// The style the UI is drawn with: the stored style in the theme of `--theme`, if there was one,
+/// with the accessibility modes applied.
+pub fn effective_style(
+    style: &Option<egui::Style>,
+    theme: Option<crate::options::Theme>,
+    accessibility: &Accessibility,
+) -> egui::Style {
+    let mut style = style.clone().unwrap_or_default();
+    if let Some(theme) = theme {
+        style.visuals = theme.visuals();
+    }
+    if accessibility.high_contrast {
+        high_contrast(&mut style.visuals);
+    }
+    if accessibility.reduced_motion {
+        style.animation_time = 0.0;
+    }
+    if let Some(outline) = accessibility.focus_outline {
+        // Pressed widgets are drawn like focused ones, text fields use the selection stroke. Its
+        // color is also the one of selected text, so only the width follows.
+        style.visuals.widgets.active.bg_stroke = outline;
+        style.visuals.selection.stroke.width = outline.width;
+    }
+    style
+}
+
+/// Solid black and white instead of the shades of grey, and thicker strokes around widgets.
+fn high_contrast(visuals: &mut egui::Visuals) {
+    let (fg, bg, link) = if visuals.dark_mode {
+        (
+            egui::Color32::WHITE,
+            egui::Color32::BLACK,
+            egui::Color32::YELLOW,
+        )
+    } else {
+        (
+            egui::Color32::BLACK,
+            egui::Color32::WHITE,
+            egui::Color32::from_rgb(0, 0, 170),
+        )
+    };
+    visuals.override_text_color = Some(fg);
+    visuals.hyperlink_color = link;
+    visuals.faint_bg_color = bg;
+    visuals.extreme_bg_color = bg;
+    visuals.code_bg_color = bg;
+    visuals.selection.stroke = egui::Stroke::new(2.0, fg);
+
+    let widgets = &mut visuals.widgets;
+    widgets.noninteractive.bg_fill = bg;
+    widgets.inactive.bg_fill = bg;
+    for widget in [
+        &mut widgets.noninteractive,
+        &mut widgets.inactive,
+        &mut widgets.hovered,
+        &mut widgets.active,
+        &mut widgets.open,
+    ] {
+        widget.bg_stroke = egui::Stroke::new(widget.bg_stroke.width.max(2.0), fg);
+        widget.fg_stroke = egui::Stroke::new(widget.fg_stroke.width.max(1.5), fg);
+    }
+}
+
+type Preset = fn(&egui::Style) -> egui::Style;
+
+/// The built-in presets. Each one starts from the current style and only changes one aspect.
+const PRESETS: &[(&str, Preset)] = &[
+    ("Dark", |style| egui::Style {
+        visuals: egui::Visuals::dark(),
+        ..style.clone()
+    }),
+    ("Light", |style| egui::Style {
+        visuals: egui::Visuals::light(),
+        ..style.clone()
+    }),
+    ("Compact", |style| {
+        let mut style = style.clone();
+        style.spacing.item_spacing = egui::vec2(4.0, 2.0);
+        style.spacing.button_padding = egui::vec2(2.0, 0.0);
+        style.spacing.window_margin = egui::style::Margin::same(4.0);
+        style.spacing.interact_size.y = 16.0;
+        style
+    }),
+    ("Large text", |style| {
+        let mut style = style.clone();
+        style.text_styles = egui::Style::default().text_styles;
+        for font_id in style.text_styles.values_mut() {
+            font_id.size *= 1.25;
+        }
+        style
+    }),
+];
+
+fn list_themes(dir: &Path) -> Vec<PathBuf> {
+    let mut themes: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    themes.sort();
+    themes
+}
+
+fn export(path: &Path, style: &egui::Style) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_vec_pretty(style)?)
+}
+
+fn import(path: &Path) -> io::Result<egui::Style> {
+    let data = fs::read(path)?;
+    Ok(serde_json::from_slice(&data)?)
+}
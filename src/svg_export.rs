@@ -0,0 +1,491 @@
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use egui::epaint::{ClippedShape, Mesh, PathShape, RectShape, Rounding, Shape, TextShape};
+use egui::{Color32, Pos2, Rect, Stroke, TextureId};
+
+/// The baseline of a row of text, as a fraction of its height. egui doesn't keep the ascent of
+/// the font in the galley, this fits its default fonts.
+const BASELINE: f32 = 0.8;
+
+/// The pixels of the rendered frame, to embed the images and custom painting that have no vector
+/// form.
+pub struct Raster<'a> {
+    /// RGBA, row by row.
+    pub pixels: &'a [u8],
+    pub size: [u32; 2],
+    pub pixels_per_point: f32,
+}
+
+/// Writes the shapes of a frame as an SVG document. `layers` are drawn from bottom to top, over
+/// `background`, into a document as large as `screen` in points.
+///
+/// Rectangles, circles, paths and text stay vectors. Text is written glyph by glyph at the
+/// positions egui laid it out, so a different font doesn't move it. Meshes in the font texture
+/// become colored triangles, everything drawn with another texture is cut out of `raster`.
+pub fn write_svg(
+    layers: &[&[ClippedShape]],
+    screen: Rect,
+    background: Color32,
+    raster: &Raster,
+) -> String {
+    let mut writer = SvgWriter {
+        svg: String::new(),
+        defs: String::new(),
+        clip_rects: Vec::new(),
+        raster,
+    };
+    writer.rect(
+        &RectShape::filled(screen, Rounding::none(), background),
+        screen,
+    );
+    for layer in layers {
+        for ClippedShape(clip_rect, shape) in layer.iter() {
+            let clip = writer.clip_id(*clip_rect);
+            let _ = writeln!(writer.svg, r#"<g clip-path="url(#clip{})">"#, clip);
+            writer.shape(shape, *clip_rect);
+            writer.svg.push_str("</g>\n");
+        }
+    }
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"{x} {y} {w} {h}\">\n<defs>\n{defs}</defs>\n{svg}</svg>\n",
+        x = screen.min.x,
+        y = screen.min.y,
+        w = screen.width(),
+        h = screen.height(),
+        defs = writer.defs,
+        svg = writer.svg,
+    )
+}
+
+/// Saves `svg` next to the screenshots.
+pub fn save(svg: &str) -> Result<PathBuf, String> {
+    let directory = dirs::picture_dir().unwrap_or_else(|| PathBuf::from("."));
+    let name = chrono::Local::now()
+        .format("egui_example_%Y%m%d_%H%M%S.svg")
+        .to_string();
+    let path = directory.join(name);
+    std::fs::write(&path, svg).map_err(|err| err.to_string())?;
+    Ok(path)
+}
+
+struct SvgWriter<'a> {
+    svg: String,
+    defs: String,
+    /// The clip rects that have a `clipPath` in the defs, their index is the id.
+    clip_rects: Vec<Rect>,
+    raster: &'a Raster<'a>,
+}
+
+impl SvgWriter<'_> {
+    fn clip_id(&mut self, rect: Rect) -> usize {
+        if let Some(index) = self.clip_rects.iter().position(|clip| *clip == rect) {
+            return index;
+        }
+        let index = self.clip_rects.len();
+        // egui clips to infinite rects, which SVG has no number for.
+        let rect = rect.intersect(Rect::from_min_max(
+            Pos2::new(-1.0e6, -1.0e6),
+            Pos2::new(1.0e6, 1.0e6),
+        ));
+        let _ = writeln!(
+            self.defs,
+            r#"<clipPath id="clip{}"><rect x="{}" y="{}" width="{}" height="{}"/></clipPath>"#,
+            index,
+            rect.min.x,
+            rect.min.y,
+            rect.width().max(0.0),
+            rect.height().max(0.0)
+        );
+        self.clip_rects.push(rect);
+        index
+    }
+
+    fn shape(&mut self, shape: &Shape, clip_rect: Rect) {
+        match shape {
+            Shape::Noop => {}
+            Shape::Vec(shapes) => {
+                for shape in shapes {
+                    self.shape(shape, clip_rect);
+                }
+            }
+            Shape::Circle(circle) => {
+                let _ = writeln!(
+                    self.svg,
+                    r#"<circle cx="{}" cy="{}" r="{}"{}{}/>"#,
+                    circle.center.x,
+                    circle.center.y,
+                    circle.radius,
+                    fill(circle.fill),
+                    stroke(circle.stroke)
+                );
+            }
+            Shape::LineSegment {
+                points,
+                stroke: line,
+            } => {
+                let _ = writeln!(
+                    self.svg,
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}"{}/>"#,
+                    points[0].x,
+                    points[0].y,
+                    points[1].x,
+                    points[1].y,
+                    stroke(*line)
+                );
+            }
+            Shape::Path(path) => self.path(path),
+            Shape::Rect(rect) => self.rect(rect, clip_rect),
+            Shape::Text(text) => self.text(text),
+            Shape::Mesh(mesh) => self.mesh(mesh, clip_rect),
+            Shape::QuadraticBezier(bezier) => {
+                let [start, control, end] = bezier.points;
+                let _ = writeln!(
+                    self.svg,
+                    r#"<path d="M {} {} Q {} {} {} {}{}"{}{}/>"#,
+                    start.x,
+                    start.y,
+                    control.x,
+                    control.y,
+                    end.x,
+                    end.y,
+                    if bezier.closed { " Z" } else { "" },
+                    fill(bezier.fill),
+                    stroke(bezier.stroke)
+                );
+            }
+            Shape::CubicBezier(bezier) => {
+                let [start, control1, control2, end] = bezier.points;
+                let _ = writeln!(
+                    self.svg,
+                    r#"<path d="M {} {} C {} {} {} {} {} {}{}"{}{}/>"#,
+                    start.x,
+                    start.y,
+                    control1.x,
+                    control1.y,
+                    control2.x,
+                    control2.y,
+                    end.x,
+                    end.y,
+                    if bezier.closed { " Z" } else { "" },
+                    fill(bezier.fill),
+                    stroke(bezier.stroke)
+                );
+            }
+            Shape::Callback(callback) => self.image(callback.rect.intersect(clip_rect)),
+        }
+    }
+
+    fn path(&mut self, path: &PathShape) {
+        if path.points.len() < 2 {
+            return;
+        }
+        let points: Vec<String> = path
+            .points
+            .iter()
+            .map(|point| format!("{},{}", point.x, point.y))
+            .collect();
+        let (element, path_fill) = match path.closed {
+            true => ("polygon", path.fill),
+            false => ("polyline", Color32::TRANSPARENT),
+        };
+        let _ = writeln!(
+            self.svg,
+            r#"<{} points="{}"{}{}/>"#,
+            element,
+            points.join(" "),
+            fill(path_fill),
+            stroke(path.stroke)
+        );
+    }
+
+    fn rect(&mut self, rect: &RectShape, clip_rect: Rect) {
+        // Huge rects, like the background of a scroll area, are cut down to what's visible. The
+        // outline of a stroked rect has to stay where it is.
+        let bounds = match rect.stroke.width > 0.0 {
+            true => rect.rect,
+            false => rect.rect.intersect(clip_rect),
+        };
+        if !bounds.is_positive() {
+            return;
+        }
+        let r = rect.rect;
+        let Rounding { nw, ne, sw, se } = rect.rounding;
+        let outline = if [nw, ne, sw, se] == [0.0; 4] {
+            format!(
+                r#"<rect x="{}" y="{}" width="{}" height="{}""#,
+                bounds.min.x,
+                bounds.min.y,
+                bounds.width(),
+                bounds.height()
+            )
+        } else {
+            // The radii are limited like egui does, to half of the shorter side.
+            let limit = 0.5 * r.width().min(r.height());
+            let [nw, ne, sw, se] = [nw, ne, sw, se].map(|radius| radius.min(limit));
+            format!(
+                "<path d=\"M {} {} H {} A {ne} {ne} 0 0 1 {} {} V {} A {se} {se} 0 0 1 {} {} \
+                 H {} A {sw} {sw} 0 0 1 {} {} V {} A {nw} {nw} 0 0 1 {} {} Z\"",
+                r.left() + nw,
+                r.top(),
+                r.right() - ne,
+                r.right(),
+                r.top() + ne,
+                r.bottom() - se,
+                r.right() - se,
+                r.bottom(),
+                r.left() + sw,
+                r.left(),
+                r.bottom() - sw,
+                r.top() + nw,
+                r.left() + nw,
+                r.top(),
+            )
+        };
+        let _ = writeln!(
+            self.svg,
+            "{}{}{}/>",
+            outline,
+            fill(rect.fill),
+            stroke(rect.stroke)
+        );
+    }
+
+    fn text(&mut self, text: &TextShape) {
+        let galley = &text.galley;
+        let transform = match text.angle {
+            0.0 => String::new(),
+            angle => format!(
+                r#" transform="rotate({} {} {})""#,
+                angle.to_degrees(),
+                text.pos.x,
+                text.pos.y
+            ),
+        };
+        let _ = writeln!(self.svg, "<g{}>", transform);
+        for row in &galley.rows {
+            let mut glyphs = row.glyphs.iter().peekable();
+            // One element for each run of glyphs with the same format.
+            while let Some(first) = glyphs.next() {
+                let mut run = vec![first];
+                while let Some(glyph) =
+                    glyphs.next_if(|glyph| glyph.section_index == first.section_index)
+                {
+                    run.push(glyph);
+                }
+                let format = &galley.job.sections[first.section_index as usize].format;
+                let last = run[run.len() - 1];
+                let left_top = text.pos + first.pos.to_vec2();
+                let width = last.max_x() - first.pos.x;
+                let height = first.size.y;
+                if format.background != Color32::TRANSPARENT {
+                    let _ = writeln!(
+                        self.svg,
+                        r#"<rect x="{}" y="{}" width="{}" height="{}"{}/>"#,
+                        left_top.x,
+                        left_top.y,
+                        width,
+                        height,
+                        fill(format.background)
+                    );
+                }
+                let baseline = left_top.y + BASELINE * height;
+                let xs: Vec<String> = run
+                    .iter()
+                    .map(|glyph| (text.pos.x + glyph.pos.x).to_string())
+                    .collect();
+                let content: String = run.iter().map(|glyph| glyph.chr).collect();
+                let family = match &format.font_id.family {
+                    egui::FontFamily::Proportional => "Ubuntu, sans-serif".to_owned(),
+                    egui::FontFamily::Monospace => "Hack, monospace".to_owned(),
+                    egui::FontFamily::Name(name) => escape(name),
+                };
+                let _ = writeln!(
+                    self.svg,
+                    r#"<text xml:space="preserve" x="{}" y="{}" font-family="{}" font-size="{}"{}{}>{}</text>"#,
+                    xs.join(" "),
+                    baseline,
+                    family,
+                    format.font_id.size,
+                    if format.italics {
+                        r#" font-style="italic""#
+                    } else {
+                        ""
+                    },
+                    fill(text.override_text_color.unwrap_or(format.color)),
+                    escape(&content)
+                );
+                for (line, y) in [
+                    (format.underline, left_top.y + height),
+                    (format.strikethrough, left_top.y + 0.5 * height),
+                ] {
+                    if line.width > 0.0 {
+                        self.line(left_top.x, left_top.x + width, y, line);
+                    }
+                }
+            }
+            if text.underline.width > 0.0 {
+                let y = text.pos.y + row.rect.bottom();
+                let x = text.pos.x;
+                self.line(x + row.rect.left(), x + row.rect.right(), y, text.underline);
+            }
+        }
+        self.svg.push_str("</g>\n");
+    }
+
+    fn line(&mut self, left: f32, right: f32, y: f32, line: Stroke) {
+        let _ = writeln!(
+            self.svg,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}"{}/>"#,
+            left,
+            y,
+            right,
+            y,
+            stroke(line)
+        );
+    }
+
+    fn mesh(&mut self, mesh: &Mesh, clip_rect: Rect) {
+        if mesh.texture_id != TextureId::default() {
+            let bounds =
+                Rect::from_points(&mesh.vertices.iter().map(|v| v.pos).collect::<Vec<_>>());
+            self.image(bounds.intersect(clip_rect));
+            return;
+        }
+        // Solid colored meshes sample the white texel of the font texture.
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|corner| &mesh.vertices[triangle[corner] as usize]);
+            let color = average([a.color, b.color, c.color]);
+            if color == Color32::TRANSPARENT {
+                continue;
+            }
+            let _ = writeln!(
+                self.svg,
+                r#"<polygon points="{},{} {},{} {},{}"{}/>"#,
+                a.pos.x,
+                a.pos.y,
+                b.pos.x,
+                b.pos.y,
+                c.pos.x,
+                c.pos.y,
+                fill(color)
+            );
+        }
+    }
+
+    /// Embeds the pixels of `rect` as a PNG image.
+    fn image(&mut self, rect: Rect) {
+        let Raster {
+            pixels,
+            size,
+            pixels_per_point,
+        } = *self.raster;
+        let x0 = ((rect.min.x * pixels_per_point).floor().max(0.0) as u32).min(size[0]);
+        let y0 = ((rect.min.y * pixels_per_point).floor().max(0.0) as u32).min(size[1]);
+        let x1 = ((rect.max.x * pixels_per_point).ceil().max(0.0) as u32).min(size[0]);
+        let y1 = ((rect.max.y * pixels_per_point).ceil().max(0.0) as u32).min(size[1]);
+        if x1 <= x0 || y1 <= y0 {
+            return;
+        }
+        let mut crop = Vec::with_capacity(((x1 - x0) * (y1 - y0) * 4) as usize);
+        for y in y0..y1 {
+            let row = (y * size[0]) as usize * 4;
+            crop.extend_from_slice(&pixels[row + x0 as usize * 4..row + x1 as usize * 4]);
+        }
+        let mut png = Vec::new();
+        let result = image::write_buffer_with_format(
+            &mut std::io::Cursor::new(&mut png),
+            &crop,
+            x1 - x0,
+            y1 - y0,
+            image::ExtendedColorType::Rgba8,
+            image::ImageFormat::Png,
+        );
+        if let Err(err) = result {
+            eprintln!("Can't embed an image into the SVG: {}", err);
+            return;
+        }
+        let _ = writeln!(
+            self.svg,
+            r#"<image x="{}" y="{}" width="{}" height="{}" href="data:image/png;base64,{}"/>"#,
+            x0 as f32 / pixels_per_point,
+            y0 as f32 / pixels_per_point,
+            (x1 - x0) as f32 / pixels_per_point,
+            (y1 - y0) as f32 / pixels_per_point,
+            base64(&png)
+        );
+    }
+}
+
+/// The `fill` attributes of `color`, which is premultiplied like all colors of egui.
+fn fill(color: Color32) -> String {
+    match color.a() {
+        0 => r#" fill="none""#.to_owned(),
+        255 => format!(r#" fill="{}""#, hex(color)),
+        _ => format!(
+            r#" fill="{}" fill-opacity="{:.3}""#,
+            hex(color),
+            color.a() as f32 / 255.0
+        ),
+    }
+}
+
+fn stroke(stroke: Stroke) -> String {
+    if stroke.width <= 0.0 || stroke.color.a() == 0 {
+        return String::new();
+    }
+    let mut attributes = format!(
+        r#" stroke="{}" stroke-width="{}""#,
+        hex(stroke.color),
+        stroke.width
+    );
+    if stroke.color.a() < 255 {
+        let _ = write!(
+            attributes,
+            r#" stroke-opacity="{:.3}""#,
+            stroke.color.a() as f32 / 255.0
+        );
+    }
+    attributes
+}
+
+fn hex(color: Color32) -> String {
+    let [r, g, b, _] = color.to_srgba_unmultiplied();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// The average of the premultiplied `colors`.
+pub fn average(colors: [Color32; 3]) -> Color32 {
+    let channel =
+        |index: usize| (colors.iter().map(|color| color[index] as u32).sum::<u32>() / 3) as u8;
+    Color32::from_rgba_premultiplied(channel(0), channel(1), channel(2), channel(3))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                out.push(ALPHABET[(bits >> (18 - 6 * index) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
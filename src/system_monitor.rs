@@ -0,0 +1,354 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use sysinfo::{CpuExt, CpuRefreshKind, NetworkExt, NetworksExt, RefreshKind, System, SystemExt};
+
+/// How often the background thread samples the system.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// Number of samples kept for the sparklines.
+const HISTORY_LENGTH: usize = 120;
+
+/// One measurement of the system.
+#[derive(Clone, Default)]
+struct Sample {
+    /// Usage of every core in percent.
+    cpu_usage: Vec<f32>,
+    used_memory: u64,
+    total_memory: u64,
+    /// Bytes per second over all network interfaces.
+    received: u64,
+    transmitted: u64,
+    gpu: Option<GpuSample>,
+}
+
+/// Readings that are only available through NVML.
+#[derive(Clone, Default)]
+#[cfg_attr(not(feature = "nvml"), allow(dead_code))]
+struct GpuSample {
+    temperature: u32,
+    utilization: u32,
+}
+
+/// Samples collected by the background thread, newest last.
+#[derive(Default)]
+struct History {
+    samples: VecDeque<Sample>,
+}
+
+/// A running sampler thread. The thread ends on the next tick after `running` got cleared.
+struct Sampler {
+    running: Arc<AtomicBool>,
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// A dashboard window with CPU, memory, network and GPU gauges and sparklines.
+///
+/// Sampling runs on a background timer thread while the window is open. Every new sample
+/// requests a repaint, so the UI doesn't need to poll.
+pub struct SystemMonitor {
+    gpu_name: String,
+    history: Arc<Mutex<History>>,
+    sampler: Option<Sampler>,
+}
+
+impl SystemMonitor {
+    /// `gpu_name` is the name of the adapter used for rendering.
+    pub fn new(gpu_name: String) -> Self {
+        Self {
+            gpu_name,
+            history: Default::default(),
+            sampler: None,
+        }
+    }
+
+    fn start_sampler(&mut self, ctx: &egui::Context) {
+        self.history.lock().unwrap().samples.clear();
+        let running = Arc::new(AtomicBool::new(true));
+        let history = self.history.clone();
+        let ctx = ctx.clone();
+        let thread_running = running.clone();
+        let spawned = thread::Builder::new()
+            .name("system-monitor".to_owned())
+            .spawn(move || sample(thread_running, history, ctx));
+        match spawned {
+            Ok(_) => self.sampler = Some(Sampler { running }),
+            Err(err) => eprintln!("Can't start the system monitor: {}", err),
+        }
+    }
+
+    /// Shows the dashboard window.
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if *open && self.sampler.is_none() {
+            self.start_sampler(ctx);
+        }
+
+        egui::Window::new("📈 System monitor")
+            .open(open)
+            .default_width(420.0)
+            .show(ctx, |ui| self.ui(ui));
+
+        if !*open {
+            self.sampler = None;
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let history = self.history.lock().unwrap();
+        let latest = match history.samples.back() {
+            Some(sample) => sample,
+            None => {
+                ui.spinner();
+                return;
+            }
+        };
+
+        ui.heading("CPU");
+        let cores = latest.cpu_usage.len();
+        ui.horizontal_wrapped(|ui| {
+            for core in 0..cores {
+                let history: Vec<f32> = history
+                    .samples
+                    .iter()
+                    .map(|s| s.cpu_usage.get(core).copied().unwrap_or(0.0))
+                    .collect();
+                ui.vertical(|ui| {
+                    gauge(ui, latest.cpu_usage[core] / 100.0, &format!("#{}", core));
+                    sparkline(ui, &history, 100.0, egui::vec2(48.0, 20.0));
+                });
+            }
+        });
+
+        ui.separator();
+        ui.heading("Memory");
+        let memory: Vec<f32> = history
+            .samples
+            .iter()
+            .map(|s| s.used_memory as f32)
+            .collect();
+        ui.horizontal(|ui| {
+            let fraction = latest.used_memory as f32 / latest.total_memory.max(1) as f32;
+            gauge(ui, fraction, "RAM");
+            ui.vertical(|ui| {
+                ui.label(format!(
+                    "{} of {}",
+                    format_bytes(latest.used_memory),
+                    format_bytes(latest.total_memory)
+                ));
+                sparkline(
+                    ui,
+                    &memory,
+                    latest.total_memory as f32,
+                    egui::vec2(240.0, 32.0),
+                );
+            });
+        });
+
+        ui.separator();
+        ui.heading("Network");
+        let received: Vec<f32> = history.samples.iter().map(|s| s.received as f32).collect();
+        let transmitted: Vec<f32> = history
+            .samples
+            .iter()
+            .map(|s| s.transmitted as f32)
+            .collect();
+        // Both directions share one scale, so that they can be compared.
+        let max = received
+            .iter()
+            .chain(&transmitted)
+            .copied()
+            .fold(1.0, f32::max);
+        egui::Grid::new("system_monitor_network").show(ui, |ui| {
+            ui.label(format!("⬇ {}/s", format_bytes(latest.received)));
+            sparkline(ui, &received, max, egui::vec2(240.0, 24.0));
+            ui.end_row();
+            ui.label(format!("⬆ {}/s", format_bytes(latest.transmitted)));
+            sparkline(ui, &transmitted, max, egui::vec2(240.0, 24.0));
+            ui.end_row();
+        });
+
+        ui.separator();
+        ui.heading("GPU");
+        ui.label(&self.gpu_name);
+        match &latest.gpu {
+            Some(gpu) => {
+                let temperature: Vec<f32> = history
+                    .samples
+                    .iter()
+                    .filter_map(|s| s.gpu.as_ref().map(|gpu| gpu.temperature as f32))
+                    .collect();
+                ui.horizontal(|ui| {
+                    gauge(ui, gpu.utilization as f32 / 100.0, "Load");
+                    ui.vertical(|ui| {
+                        ui.label(format!("{} °C", gpu.temperature));
+                        sparkline(ui, &temperature, 100.0, egui::vec2(240.0, 32.0));
+                    });
+                });
+            }
+            None if cfg!(feature = "nvml") => {
+                ui.label("No NVML capable GPU found.");
+            }
+            None => {
+                ui.label("Build with `--features nvml` for temperature and load of NVIDIA GPUs.");
+            }
+        }
+    }
+}
+
+/// The body of the sampler thread.
+fn sample(running: Arc<AtomicBool>, history: Arc<Mutex<History>>, ctx: egui::Context) {
+    let mut system = System::new_with_specifics(
+        RefreshKind::new()
+            .with_cpu(CpuRefreshKind::new().with_cpu_usage())
+            .with_memory()
+            .with_networks()
+            .with_networks_list(),
+    );
+    #[cfg(feature = "nvml")]
+    let nvml = nvml_wrapper::Nvml::init().ok();
+
+    while running.load(Ordering::Relaxed) {
+        // CPU usage is calculated from the difference to the previous refresh, so the first
+        // sample is only valid after one interval.
+        thread::sleep(SAMPLE_INTERVAL);
+        system.refresh_cpu();
+        system.refresh_memory();
+        system.refresh_networks();
+
+        let networks = system.networks();
+        let interval = SAMPLE_INTERVAL.as_secs_f64();
+        let received: u64 = networks.iter().map(|(_, data)| data.received()).sum();
+        let transmitted: u64 = networks.iter().map(|(_, data)| data.transmitted()).sum();
+
+        #[cfg(feature = "nvml")]
+        let gpu = nvml.as_ref().and_then(|nvml| {
+            use nvml_wrapper::enum_wrappers::device::TemperatureSensor;
+
+            let device = nvml.device_by_index(0).ok()?;
+            Some(GpuSample {
+                temperature: device.temperature(TemperatureSensor::Gpu).ok()?,
+                utilization: device.utilization_rates().ok()?.gpu,
+            })
+        });
+        #[cfg(not(feature = "nvml"))]
+        let gpu = None;
+
+        let sample = Sample {
+            cpu_usage: system.cpus().iter().map(|cpu| cpu.cpu_usage()).collect(),
+            used_memory: system.used_memory(),
+            total_memory: system.total_memory(),
+            received: (received as f64 / interval) as u64,
+            transmitted: (transmitted as f64 / interval) as u64,
+            gpu,
+        };
+
+        {
+            let mut history = history.lock().unwrap();
+            if history.samples.len() == HISTORY_LENGTH {
+                history.samples.pop_front();
+            }
+            history.samples.push_back(sample);
+        }
+        ctx.request_repaint();
+    }
+}
+
+/// Draws a half-circle gauge for a value in `0.0..=1.0`.
+pub fn gauge(ui: &mut egui::Ui, fraction: f32, label: &str) {
+    let size = egui::vec2(48.0, 36.0);
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+
+    let fraction = fraction.clamp(0.0, 1.0);
+    let center = egui::pos2(rect.center().x, rect.bottom() - 10.0);
+    let radius = 20.0;
+    let arc = |to: f32| -> Vec<egui::Pos2> {
+        let steps = 24;
+        (0..=steps)
+            .map(|i| {
+                let angle = std::f32::consts::PI * (1.0 + to * i as f32 / steps as f32);
+                center + radius * egui::vec2(angle.cos(), angle.sin())
+            })
+            .collect()
+    };
+
+    let color = if fraction > 0.9 {
+        egui::Color32::RED
+    } else if fraction > 0.7 {
+        egui::Color32::YELLOW
+    } else {
+        egui::Color32::GREEN
+    };
+    painter.add(egui::Shape::line(
+        arc(1.0),
+        egui::Stroke::new(4.0, visuals.widgets.noninteractive.bg_fill),
+    ));
+    painter.add(egui::Shape::line(
+        arc(fraction),
+        egui::Stroke::new(4.0, color),
+    ));
+    painter.text(
+        center,
+        egui::Align2::CENTER_BOTTOM,
+        format!("{:.0}%", fraction * 100.0),
+        egui::FontId::proportional(11.0),
+        visuals.text_color(),
+    );
+    painter.text(
+        egui::pos2(center.x, rect.bottom()),
+        egui::Align2::CENTER_BOTTOM,
+        label,
+        egui::FontId::proportional(9.0),
+        visuals.weak_text_color(),
+    );
+}
+
+/// Draws the values as a line, scaled so that `max` touches the top of the rect.
+fn sparkline(ui: &mut egui::Ui, values: &[f32], max: f32, size: egui::Vec2) {
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+    if values.len() < 2 || max <= 0.0 {
+        return;
+    }
+
+    // The newest value is at the right edge, the x-axis covers the whole history length.
+    let step = rect.width() / (HISTORY_LENGTH - 1) as f32;
+    let start = rect.right() - step * (values.len() - 1) as f32;
+    let points = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let y = rect.bottom() - (value / max).clamp(0.0, 1.0) * rect.height();
+            egui::pos2(start + step * i as f32, y)
+        })
+        .collect();
+    painter.add(egui::Shape::line(
+        points,
+        egui::Stroke::new(1.0, ui.visuals().selection.bg_fill),
+    ));
+}
+
+/// `bytes` in the largest binary unit that keeps the number above 1.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
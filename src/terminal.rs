@@ -0,0 +1,394 @@
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+
+use portable_pty::{Child, CommandBuilder, MasterPty, PtySize};
+
+/// The lines kept above the screen for scrolling back.
+const SCROLLBACK_LINES: usize = 1000;
+/// The lines a point of scrolling scrolls back.
+const LINES_PER_SCROLL_POINT: f32 = 0.05;
+
+/// The 16 colors of xterm, which programs name by index.
+const ANSI_COLORS: [egui::Color32; 16] = [
+    egui::Color32::from_rgb(0, 0, 0),
+    egui::Color32::from_rgb(205, 0, 0),
+    egui::Color32::from_rgb(0, 205, 0),
+    egui::Color32::from_rgb(205, 205, 0),
+    egui::Color32::from_rgb(0, 0, 238),
+    egui::Color32::from_rgb(205, 0, 205),
+    egui::Color32::from_rgb(0, 205, 205),
+    egui::Color32::from_rgb(229, 229, 229),
+    egui::Color32::from_rgb(127, 127, 127),
+    egui::Color32::from_rgb(255, 0, 0),
+    egui::Color32::from_rgb(0, 255, 0),
+    egui::Color32::from_rgb(255, 255, 0),
+    egui::Color32::from_rgb(92, 92, 255),
+    egui::Color32::from_rgb(255, 0, 255),
+    egui::Color32::from_rgb(0, 255, 255),
+    egui::Color32::from_rgb(255, 255, 255),
+];
+
+/// A panel at the bottom of the window with a shell in a pseudo terminal, whose output a VT100
+/// parser turns into a grid of cells that is drawn with egui text.
+///
+/// Key presses go to the shell while the grid has the focus. Ctrl+C and Ctrl+X are sent as
+/// control characters, Ctrl+V pastes from the clipboard.
+#[derive(Default)]
+pub struct Terminal {
+    /// Started when the panel is first shown.
+    session: Option<Result<Session, String>>,
+    /// The lines scrolled back from the bottom.
+    scrollback: usize,
+    /// The fractions of lines scrolled but not applied yet.
+    scroll_remainder: f32,
+}
+
+struct Session {
+    parser: Arc<Mutex<vt100::Parser>>,
+    pty: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Box<dyn Child + Send + Sync>,
+    /// Rows and columns of the pseudo terminal.
+    size: (u16, u16),
+    exit_status: Option<portable_pty::ExitStatus>,
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        if self.exit_status.is_none() {
+            let _ = self.child.kill();
+        }
+    }
+}
+
+impl Session {
+    /// Starts the default shell of the user. The output is parsed on a thread of its own.
+    fn spawn(ctx: &egui::Context) -> Result<Self, String> {
+        let size = (24, 80);
+        let pair = portable_pty::native_pty_system()
+            .openpty(pty_size(size))
+            .map_err(|err| format!("Can't open a pseudo terminal: {}", err))?;
+        let mut command = CommandBuilder::new_default_prog();
+        command.env("TERM", "xterm-256color");
+        let child = pair
+            .slave
+            .spawn_command(command)
+            .map_err(|err| format!("Can't start the shell: {}", err))?;
+        let mut reader = pair
+            .master
+            .try_clone_reader()
+            .map_err(|err| err.to_string())?;
+        let writer = pair.master.take_writer().map_err(|err| err.to_string())?;
+
+        let parser = Arc::new(Mutex::new(vt100::Parser::new(
+            size.0,
+            size.1,
+            SCROLLBACK_LINES,
+        )));
+        let thread_parser = parser.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let mut buffer = [0; 8192];
+            // Ends when the shell exits and the pseudo terminal closes.
+            while let Ok(read @ 1..) = reader.read(&mut buffer) {
+                thread_parser
+                    .lock()
+                    .expect("The parser doesn't panic")
+                    .process(&buffer[..read]);
+                ctx.request_repaint();
+            }
+            ctx.request_repaint();
+        });
+        Ok(Self {
+            parser,
+            pty: pair.master,
+            writer,
+            child,
+            size,
+            exit_status: None,
+        })
+    }
+
+    fn resize(&mut self, size: (u16, u16)) {
+        if size == self.size {
+            return;
+        }
+        self.size = size;
+        if let Err(err) = self.pty.resize(pty_size(size)) {
+            eprintln!("Can't resize the pseudo terminal: {}", err);
+        }
+        self.parser
+            .lock()
+            .expect("The parser doesn't panic")
+            .screen_mut()
+            .set_size(size.0, size.1);
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        if let Err(err) = self.writer.write_all(bytes) {
+            eprintln!("Can't write to the terminal: {}", err);
+        }
+    }
+}
+
+impl Terminal {
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) {
+        if !*open {
+            return;
+        }
+        egui::TopBottomPanel::bottom("terminal")
+            .resizable(true)
+            .default_height(240.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.strong("💻 Terminal");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("✖").on_hover_text("Close").clicked() {
+                            *open = false;
+                        }
+                        self.status_ui(ui);
+                    });
+                });
+                self.ui(ui);
+            });
+        if !*open {
+            // Ends the shell, a new one starts when the panel is opened again.
+            self.session = None;
+        }
+    }
+
+    fn status_ui(&mut self, ui: &mut egui::Ui) {
+        let restart = match &mut self.session {
+            Some(Ok(session)) => {
+                if session.exit_status.is_none() {
+                    session.exit_status = session.child.try_wait().ok().flatten();
+                }
+                match &session.exit_status {
+                    Some(status) => {
+                        let restart = ui.button("Restart").clicked();
+                        ui.weak(format!("The shell exited with code {}", status.exit_code()));
+                        restart
+                    }
+                    None => {
+                        ui.weak(format!("{}×{}", session.size.1, session.size.0));
+                        false
+                    }
+                }
+            }
+            Some(Err(err)) => {
+                let restart = ui.button("Retry").clicked();
+                ui.colored_label(ui.visuals().error_fg_color, err.as_str());
+                restart
+            }
+            None => false,
+        };
+        if restart {
+            self.session = None;
+        }
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        let session = match self.session.get_or_insert_with(|| Session::spawn(ui.ctx())) {
+            Ok(session) => session,
+            Err(_) => return,
+        };
+
+        let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+        let cell = egui::vec2(
+            ui.fonts().glyph_width(&font_id, 'M'),
+            ui.fonts().row_height(&font_id),
+        );
+        let (rect, response) = ui.allocate_exact_size(ui.available_size(), egui::Sense::click());
+        let size = (
+            ((rect.height() / cell.y) as u16).max(1),
+            ((rect.width() / cell.x) as u16).max(1),
+        );
+        session.resize(size);
+
+        if response.clicked() {
+            response.request_focus();
+        }
+        let focused = response.has_focus();
+        if focused {
+            // Keeps Tab and the arrow keys from moving the focus away.
+            ui.memory().lock_focus(response.id, true);
+            let input = terminal_input(
+                &ui.input().events,
+                session
+                    .parser
+                    .lock()
+                    .expect("The parser doesn't panic")
+                    .screen(),
+            );
+            if !input.is_empty() {
+                session.write(&input);
+                self.scrollback = 0;
+            }
+        }
+        if response.hovered() {
+            self.scroll_remainder += ui.input().scroll_delta.y * LINES_PER_SCROLL_POINT;
+            let lines = self.scroll_remainder.trunc();
+            self.scroll_remainder -= lines;
+            self.scrollback = (self.scrollback as isize + lines as isize).max(0) as usize;
+        }
+
+        let mut parser = session.parser.lock().expect("The parser doesn't panic");
+        let screen = parser.screen_mut();
+        // Clamped to the lines that are actually there.
+        screen.set_scrollback(self.scrollback);
+        self.scrollback = screen.scrollback();
+        let screen = parser.screen();
+
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, egui::Color32::from_gray(16));
+        for row in 0..size.0 {
+            let mut job = egui::text::LayoutJob::default();
+            for column in 0..size.1 {
+                let cell = match screen.cell(row, column) {
+                    Some(cell) if !cell.is_wide_continuation() => cell,
+                    _ => continue,
+                };
+                let (mut foreground, mut background) = (
+                    color(cell.fgcolor(), egui::Color32::from_gray(220)),
+                    color(cell.bgcolor(), egui::Color32::TRANSPARENT),
+                );
+                if cell.inverse() {
+                    std::mem::swap(&mut foreground, &mut background);
+                    if foreground == egui::Color32::TRANSPARENT {
+                        foreground = egui::Color32::from_gray(16);
+                    }
+                    if background == egui::Color32::TRANSPARENT {
+                        background = egui::Color32::from_gray(220);
+                    }
+                }
+                // There's no bold monospace font, so bold text is brighter instead.
+                if cell.bold() {
+                    foreground = foreground.linear_multiply(1.3);
+                }
+                let text = match cell.has_contents() {
+                    true => cell.contents(),
+                    false => " ",
+                };
+                job.append(
+                    text,
+                    0.0,
+                    egui::TextFormat {
+                        font_id: font_id.clone(),
+                        color: foreground,
+                        background,
+                        italics: cell.italic(),
+                        underline: match cell.underline() {
+                            true => egui::Stroke::new(1.0, foreground),
+                            false => egui::Stroke::none(),
+                        },
+                        ..Default::default()
+                    },
+                );
+            }
+            let galley = ui.fonts().layout_job(job);
+            painter.galley(rect.min + egui::vec2(0.0, row as f32 * cell.y), galley);
+        }
+
+        let (row, column) = screen.cursor_position();
+        if !screen.hide_cursor() && self.scrollback == 0 {
+            let cursor = egui::Rect::from_min_size(
+                rect.min + egui::vec2(column as f32 * cell.x, row as f32 * cell.y),
+                cell,
+            );
+            let color = egui::Color32::from_gray(220);
+            match focused {
+                true => painter.rect_filled(cursor, 0.0, color.linear_multiply(0.5)),
+                false => painter.rect_stroke(cursor, 0.0, egui::Stroke::new(1.0, color)),
+            }
+        }
+    }
+}
+
+fn pty_size((rows, cols): (u16, u16)) -> PtySize {
+    PtySize {
+        rows,
+        cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+/// The color of a cell, with `default` for the color the program didn't set.
+fn color(color: vt100::Color, default: egui::Color32) -> egui::Color32 {
+    match color {
+        vt100::Color::Default => default,
+        vt100::Color::Idx(index @ 0..=15) => ANSI_COLORS[index as usize],
+        // A 6×6×6 color cube.
+        vt100::Color::Idx(index @ 16..=231) => {
+            let index = index - 16;
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            egui::Color32::from_rgb(level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        // A ramp of 24 grays.
+        vt100::Color::Idx(index) => egui::Color32::from_gray(8 + (index - 232) * 10),
+        vt100::Color::Rgb(r, g, b) => egui::Color32::from_rgb(r, g, b),
+    }
+}
+
+/// The bytes the egui input `events` send to a terminal showing `screen`.
+fn terminal_input(events: &[egui::Event], screen: &vt100::Screen) -> Vec<u8> {
+    let mut input = Vec::new();
+    for event in events {
+        match event {
+            egui::Event::Text(text) => {
+                // Pastes arrive as text too. Programs that asked for it get them bracketed.
+                let bracketed = screen.bracketed_paste() && text.chars().count() > 1;
+                if bracketed {
+                    input.extend_from_slice(b"\x1b[200~");
+                }
+                input.extend_from_slice(text.as_bytes());
+                if bracketed {
+                    input.extend_from_slice(b"\x1b[201~");
+                }
+            }
+            // egui turns Ctrl+C and Ctrl+X into clipboard events.
+            egui::Event::Copy => input.push(0x03),
+            egui::Event::Cut => input.push(0x18),
+            egui::Event::Key {
+                key,
+                pressed: true,
+                modifiers,
+            } => input.extend_from_slice(&key_input(*key, *modifiers, screen)),
+            _ => {}
+        }
+    }
+    input
+}
+
+/// The escape sequence of a key that doesn't produce text.
+fn key_input(key: egui::Key, modifiers: egui::Modifiers, screen: &vt100::Screen) -> Vec<u8> {
+    use egui::Key;
+
+    // In application cursor mode, e.g. in less or vim, the arrows send SS3 instead of CSI.
+    let arrow = |code: u8| match screen.application_cursor() {
+        true => vec![0x1b, b'O', code],
+        false => vec![0x1b, b'[', code],
+    };
+    let name = format!("{:?}", key);
+    match key {
+        Key::Enter => vec![b'\r'],
+        Key::Tab => vec![b'\t'],
+        Key::Backspace => vec![0x7f],
+        Key::Escape => vec![0x1b],
+        Key::ArrowUp => arrow(b'A'),
+        Key::ArrowDown => arrow(b'B'),
+        Key::ArrowRight => arrow(b'C'),
+        Key::ArrowLeft => arrow(b'D'),
+        Key::Home => arrow(b'H'),
+        Key::End => arrow(b'F'),
+        Key::Insert => b"\x1b[2~".to_vec(),
+        Key::Delete => b"\x1b[3~".to_vec(),
+        Key::PageUp => b"\x1b[5~".to_vec(),
+        Key::PageDown => b"\x1b[6~".to_vec(),
+        // Ctrl with a letter sends its control character, e.g. Ctrl+D the end of the input.
+        _ if modifiers.ctrl && name.len() == 1 => {
+            vec![name.as_bytes()[0].to_ascii_lowercase() & 0x1f]
+        }
+        _ => Vec::new(),
+    }
+}
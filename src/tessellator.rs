@@ -0,0 +1,147 @@
+use std::sync::mpsc;
+use std::thread;
+
+use egui::epaint::TessellationOptions;
+
+use crate::frame_stats::FrameRecord;
+
+type Job = (
+    egui::Context,
+    Vec<egui::epaint::ClippedShape>,
+    TessellationOptions,
+);
+
+/// Tessellates the shapes of the main UI on a worker thread, while the main thread goes on with
+/// the HUD and the uploads for it.
+///
+/// The worker only gets a cloned `egui::Context`, which the main thread doesn't touch until
+/// [`Tessellator::finish`] hands back the paint jobs.
+pub struct Tessellator {
+    jobs: mpsc::Sender<Job>,
+    results: mpsc::Receiver<Vec<egui::ClippedPrimitive>>,
+    pending: bool,
+}
+
+impl Tessellator {
+    pub fn new() -> Self {
+        let (jobs, job_receiver) = mpsc::channel::<Job>();
+        let (result_sender, results) = mpsc::channel();
+        thread::Builder::new()
+            .name("tessellator".to_owned())
+            .spawn(move || {
+                // Ends once the runner and with it the sender is dropped.
+                for (ctx, shapes, options) in job_receiver {
+                    if result_sender
+                        .send(tessellate(&ctx, shapes, options))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            })
+            .expect("Can't spawn the tessellator thread");
+        Self {
+            jobs,
+            results,
+            pending: false,
+        }
+    }
+
+    /// Starts to tessellate `shapes` with `options` on the worker.
+    pub fn start(
+        &mut self,
+        ctx: egui::Context,
+        shapes: Vec<egui::epaint::ClippedShape>,
+        options: TessellationOptions,
+    ) {
+        assert!(!self.pending, "The last tessellation wasn't finished");
+        self.jobs
+            .send((ctx, shapes, options))
+            .expect("The tessellator thread exited");
+        self.pending = true;
+    }
+
+    /// Waits for the paint jobs of the last [`Tessellator::start`].
+    pub fn finish(&mut self) -> Vec<egui::ClippedPrimitive> {
+        assert!(self.pending, "No tessellation was started");
+        self.pending = false;
+        self.results
+            .recv()
+            .expect("The tessellator thread panicked")
+    }
+}
+
+/// Tessellates `shapes` with `options`, which replace the ones egui keeps in the memory of `ctx`.
+/// egui still collects its paint statistics for the inspection window.
+pub fn tessellate(
+    ctx: &egui::Context,
+    shapes: Vec<egui::epaint::ClippedShape>,
+    options: TessellationOptions,
+) -> Vec<egui::ClippedPrimitive> {
+    *ctx.tessellation_options() = options;
+    ctx.tessellate(shapes)
+}
+
+/// The tessellation options of the main UI and the HUD, with what they cost in the last frame.
+/// Takes over from the tessellation settings in egui's settings window.
+#[derive(Default)]
+pub struct TessellationPanel {
+    options: TessellationOptions,
+    triangles: u32,
+    draw_calls: u32,
+    tessellate_ms: f32,
+}
+
+impl TessellationPanel {
+    pub fn options(&self) -> TessellationOptions {
+        self.options
+    }
+
+    /// Takes the triangles and draw calls of both layers from the statistics of a frame.
+    pub fn set_stats(&mut self, record: &FrameRecord) {
+        self.triangles = record.triangles;
+        self.draw_calls = record.draw_calls;
+        self.tessellate_ms = record.tessellate_ms;
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let options = &mut self.options;
+        ui.checkbox(&mut options.feathering, "Feathering")
+            .on_hover_text("Anti-aliases the edges of shapes with a gradient into transparency");
+        ui.add_enabled(
+            options.feathering,
+            egui::Slider::new(&mut options.feathering_size_in_pixels, 0.0..=10.0)
+                .text("Feathering width (px)"),
+        );
+        ui.checkbox(&mut options.coarse_tessellation_culling, "Coarse culling")
+            .on_hover_text("Skips the shapes outside of their clip rect before tessellating them");
+        ui.checkbox(&mut options.prerasterized_discs, "Prerasterized discs")
+            .on_hover_text("Draws small filled circles from the font atlas");
+        ui.checkbox(&mut options.round_text_to_pixels, "Round text to pixels")
+            .on_hover_text("Keeps text crisp at fractional scale factors, like 125 or 150 %");
+        ui.add(
+            egui::Slider::new(&mut options.bezier_tolerance, 0.001..=10.0)
+                .logarithmic(true)
+                .text("Bezier tolerance (pt)"),
+        )
+        .on_hover_text("How far the flattened curves may be from the Bézier curves");
+        if ui.button("Reset").clicked() {
+            *options = Default::default();
+        }
+
+        ui.separator();
+        egui::Grid::new("tessellation_stats")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Triangles");
+                ui.monospace(self.triangles.to_string());
+                ui.end_row();
+                ui.label("Draw calls");
+                ui.monospace(self.draw_calls.to_string());
+                ui.end_row();
+                ui.label("Waiting for the tessellation");
+                ui.monospace(format!("{:.2} ms", self.tessellate_ms));
+                ui.end_row();
+            });
+    }
+}
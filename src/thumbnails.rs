@@ -0,0 +1,230 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::time::SystemTime;
+
+use crate::user_textures::{Sampling, UserTexture, UserTextures};
+
+/// The longest side of a thumbnail, in pixels.
+pub const SIZE: u32 = 128;
+
+/// The most workers, each makes one thumbnail at a time.
+const MAX_WORKERS: usize = 4;
+
+/// A file in the version that was modified at that time.
+type Key = (PathBuf, SystemTime);
+
+/// What there is of the thumbnail of a file.
+pub enum Thumbnail {
+    Loading,
+    Ready(egui::TextureId, egui::Vec2),
+    /// The file isn't an image that can be decoded.
+    Failed,
+}
+
+enum Entry {
+    Pending,
+    Ready(UserTexture, egui::Vec2),
+    Failed,
+}
+
+/// The jobs the workers haven't started yet.
+#[derive(Default)]
+struct Jobs {
+    keys: VecDeque<Key>,
+    /// Tells the workers to stop.
+    closed: bool,
+}
+
+type Shared = (Mutex<Jobs>, Condvar);
+
+/// A thumbnail uploaded by a worker, see `user_textures::upload`.
+type Done = (Key, Result<(wgpu::Texture, [u32; 2]), String>);
+
+/// The workers and their results.
+struct Pool {
+    jobs: Arc<Shared>,
+    done: mpsc::Receiver<Done>,
+}
+
+impl Pool {
+    fn start(ctx: &egui::Context, textures: &UserTextures) -> Self {
+        let jobs = Arc::new(Shared::default());
+        let (sender, done) = mpsc::channel();
+        let workers = std::thread::available_parallelism()
+            .map_or(1, |count| count.get())
+            .saturating_sub(1)
+            .clamp(1, MAX_WORKERS);
+        for index in 0..workers {
+            let jobs = jobs.clone();
+            let sender = sender.clone();
+            let ctx = ctx.clone();
+            let (device, queue) = (textures.device.clone(), textures.queue.clone());
+            let spawned = std::thread::Builder::new()
+                .name(format!("thumbnails {}", index))
+                .spawn(move || {
+                    while let Some(key) = next_job(&jobs) {
+                        let result = load(&key).map(|image| {
+                            let size = [image.width() as u32, image.height() as u32];
+                            let texture =
+                                crate::user_textures::upload(&device, &queue, "thumbnail", &image);
+                            (texture, size)
+                        });
+                        if sender.send((key, result)).is_err() {
+                            return;
+                        }
+                        ctx.request_repaint();
+                    }
+                });
+            if let Err(err) = spawned {
+                eprintln!("Can't start a thumbnail worker: {}", err);
+            }
+        }
+        Self { jobs, done }
+    }
+
+    fn push(&self, key: Key) {
+        let (jobs, ready) = &*self.jobs;
+        jobs.lock().unwrap().keys.push_back(key);
+        ready.notify_one();
+    }
+}
+
+impl Drop for Pool {
+    fn drop(&mut self) {
+        let (jobs, ready) = &*self.jobs;
+        jobs.lock().unwrap().closed = true;
+        ready.notify_all();
+    }
+}
+
+/// Waits for the next job, the one requested last, since it's the likeliest to be in view.
+/// `None` once the pool is dropped.
+fn next_job(shared: &Shared) -> Option<Key> {
+    let (jobs, ready) = shared;
+    let mut jobs = jobs.lock().unwrap();
+    loop {
+        if jobs.closed {
+            return None;
+        }
+        if let Some(key) = jobs.keys.pop_back() {
+            return Some(key);
+        }
+        jobs = ready.wait(jobs).unwrap();
+    }
+}
+
+/// The thumbnail from the disk cache, or else downscaled from the image and added to the cache.
+fn load((path, modified): &Key) -> Result<egui::ColorImage, String> {
+    let cached = cache_path(path, *modified);
+    let thumbnail = match cached.as_deref().map(image::open) {
+        Some(Ok(thumbnail)) => thumbnail.into_rgba8(),
+        _ => {
+            let mut reader = image::ImageReader::open(path)
+                .and_then(|reader| reader.with_guessed_format())
+                .map_err(|err| err.to_string())?;
+            reader.no_limits();
+            let image = reader.decode().map_err(|err| err.to_string())?;
+            let thumbnail = image.thumbnail(SIZE, SIZE).into_rgba8();
+            if let Some(cached) = &cached {
+                let saved = cached
+                    .parent()
+                    .map_or(Ok(()), std::fs::create_dir_all)
+                    .map_err(|err| err.to_string())
+                    .and_then(|()| thumbnail.save(cached).map_err(|err| err.to_string()));
+                if let Err(err) = saved {
+                    eprintln!("Can't cache the thumbnail {}: {}", cached.display(), err);
+                }
+            }
+            thumbnail
+        }
+    };
+    let size = [thumbnail.width() as usize, thumbnail.height() as usize];
+    Ok(egui::ColorImage::from_rgba_unmultiplied(
+        size,
+        thumbnail.as_raw(),
+    ))
+}
+
+/// Where the thumbnail of `path` is cached, by a hash of the path, its modification time and the
+/// thumbnail size. An edited file gets a new thumbnail.
+fn cache_path(path: &Path, modified: SystemTime) -> Option<PathBuf> {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    (path, modified, SIZE).hash(&mut hasher);
+    crate::paths::thumbnails_dir().map(|dir| dir.join(format!("{:016x}.png", hasher.finish())))
+}
+
+/// Makes the thumbnails of images on a pool of workers, which read them from the disk cache or
+/// downscale the images, and upload them as small user textures. The UI asks for the thumbnails
+/// it shows every frame, and gets them as soon as they're ready.
+///
+/// The thumbnails can be evicted like any other user texture, they come back from the cache.
+#[derive(Default)]
+pub struct Thumbnails {
+    entries: HashMap<Key, Entry>,
+    /// Started with the first thumbnail.
+    pool: Option<Pool>,
+}
+
+impl Thumbnails {
+    /// The thumbnail of the image at `path`, queued for the workers if there is none yet.
+    pub fn get(
+        &mut self,
+        ctx: &egui::Context,
+        textures: &UserTextures,
+        path: &Path,
+        modified: SystemTime,
+    ) -> Thumbnail {
+        let key = (path.to_owned(), modified);
+        match self.entries.get(&key) {
+            Some(Entry::Ready(texture, size)) if textures.is_resident(texture) => {
+                return Thumbnail::Ready(texture.id, *size)
+            }
+            Some(Entry::Pending) => return Thumbnail::Loading,
+            Some(Entry::Failed) => return Thumbnail::Failed,
+            // Evicted or not asked for yet.
+            Some(Entry::Ready(..)) | None => {}
+        }
+        self.pool
+            .get_or_insert_with(|| Pool::start(ctx, textures))
+            .push(key.clone());
+        self.entries.insert(key, Entry::Pending);
+        Thumbnail::Loading
+    }
+
+    /// Registers the thumbnails the workers finished.
+    pub fn poll(&mut self, textures: &mut UserTextures) {
+        let pool = match &self.pool {
+            Some(pool) => pool,
+            None => return,
+        };
+        for (key, result) in pool.done.try_iter() {
+            // Forgotten meanwhile by `clear`.
+            let entry = match self.entries.get_mut(&key) {
+                Some(entry) => entry,
+                None => continue,
+            };
+            *entry = match result {
+                Ok((texture, [width, height])) => {
+                    let bytes = u64::from(width) * u64::from(height) * 4;
+                    let texture = textures.register_evictable(&texture, bytes, Sampling::default());
+                    Entry::Ready(texture, egui::vec2(width as f32, height as f32))
+                }
+                Err(_) => Entry::Failed,
+            };
+        }
+    }
+
+    /// Frees the thumbnails and drops the jobs that didn't start yet, e.g. when another
+    /// directory is shown.
+    pub fn clear(&mut self, textures: &mut UserTextures) {
+        if let Some(pool) = &self.pool {
+            pool.jobs.0.lock().unwrap().keys.clear();
+        }
+        textures.free(self.entries.drain().filter_map(|(_, entry)| match entry {
+            Entry::Ready(texture, _) => Some(texture),
+            _ => None,
+        }));
+    }
+}
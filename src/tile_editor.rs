@@ -0,0 +1,252 @@
+use crate::sprite_batch::{Camera, Sprite, SpriteBatch};
+use crate::user_textures::{Sampling, UserTexture, UserTextures};
+
+/// The pixels per side of a tile in the atlas.
+const TILE_PIXELS: usize = 16;
+/// The tiles per side of the map.
+const MAP_SIZE: [usize; 2] = [32, 24];
+/// How much a point of scrolling zooms in or out.
+const ZOOM_PER_SCROLL_POINT: f32 = 0.002;
+const DEFAULT_ZOOM: f32 = 24.0;
+
+/// The tiles of the palette and the base colors their atlas pixels are generated from.
+const TILES: [(&str, [u8; 3]); 6] = [
+    ("Grass", [86, 150, 60]),
+    ("Water", [52, 101, 164]),
+    ("Sand", [222, 196, 130]),
+    ("Stone", [128, 128, 128]),
+    ("Wood", [140, 94, 52]),
+    ("Lava", [220, 80, 20]),
+];
+
+/// A tile map editor: the map is drawn by a [`SpriteBatch`] into an egui panel, the palette to
+/// paint with is plain egui. The pattern of a 2D game editor on this stack.
+pub struct TileEditor {
+    /// The tile of each cell, row by row. `None` is empty.
+    map: Vec<Option<u8>>,
+    /// The tile that is painted. `None` erases.
+    selected: Option<u8>,
+    /// The world position in the center of the viewport, in tiles.
+    center: egui::Pos2,
+    /// Points per tile.
+    zoom: f32,
+    /// Created when the editor is first shown.
+    gpu: Option<Gpu>,
+}
+
+struct Gpu {
+    atlas: UserTexture,
+    batch: SpriteBatch,
+}
+
+impl Default for TileEditor {
+    fn default() -> Self {
+        // A pond with a beach in a meadow.
+        let pond = egui::pos2(MAP_SIZE[0] as f32 / 2.0, MAP_SIZE[1] as f32 / 2.0);
+        let map = (0..MAP_SIZE[0] * MAP_SIZE[1])
+            .map(|index| {
+                let cell = egui::pos2(
+                    (index % MAP_SIZE[0]) as f32 + 0.5,
+                    (index / MAP_SIZE[0]) as f32 + 0.5,
+                );
+                Some(match cell.distance(pond) {
+                    distance if distance < 5.0 => 1,
+                    distance if distance < 7.0 => 2,
+                    _ => 0,
+                })
+            })
+            .collect();
+        Self {
+            map,
+            selected: Some(3),
+            center: pond,
+            zoom: DEFAULT_ZOOM,
+            gpu: None,
+        }
+    }
+}
+
+impl TileEditor {
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool, textures: &mut UserTextures) {
+        egui::Window::new("🗺 Tile editor")
+            .open(open)
+            .default_size([640.0, 480.0])
+            .show(ctx, |ui| self.ui(ui, textures));
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, textures: &mut UserTextures) {
+        let gpu = self.gpu.get_or_insert_with(|| {
+            let image = atlas_image();
+            let atlas =
+                crate::user_textures::upload(textures.device, textures.queue, "tile_atlas", &image);
+            Gpu {
+                batch: SpriteBatch::new(textures.device, &atlas),
+                atlas: textures.register(
+                    &atlas,
+                    image.pixels.len() as u64 * 4,
+                    Sampling {
+                        filter: wgpu::FilterMode::Nearest,
+                        ..Default::default()
+                    },
+                ),
+            }
+        });
+
+        egui::SidePanel::left("tile_palette")
+            .resizable(false)
+            .show_inside(ui, |ui| {
+                ui.strong("Palette");
+                for (index, (name, _)) in TILES.iter().enumerate() {
+                    let tile = Some(index as u8);
+                    ui.horizontal(|ui| {
+                        let button = egui::ImageButton::new(gpu.atlas.id, [32.0, 32.0])
+                            .uv(tile_uv(index as u8))
+                            .selected(self.selected == tile);
+                        if ui.add(button).clicked() {
+                            self.selected = tile;
+                        }
+                        ui.selectable_value(&mut self.selected, tile, *name);
+                    });
+                }
+                ui.selectable_value(&mut self.selected, None, "🗑 Eraser");
+                ui.separator();
+                if ui.button("Fill the map").clicked() {
+                    self.map.fill(self.selected);
+                }
+            });
+
+        ui.horizontal(|ui| {
+            if ui.button("Reset view").clicked() {
+                self.center = egui::pos2(MAP_SIZE[0] as f32 / 2.0, MAP_SIZE[1] as f32 / 2.0);
+                self.zoom = DEFAULT_ZOOM;
+            }
+            ui.weak("Click paints, right or middle drag pans, scrolling zooms");
+        });
+
+        let (rect, response) =
+            ui.allocate_exact_size(ui.available_size(), egui::Sense::click_and_drag());
+        if response.dragged_by(egui::PointerButton::Secondary)
+            || response.dragged_by(egui::PointerButton::Middle)
+        {
+            self.center -= response.drag_delta() / self.zoom;
+        }
+        let to_world =
+            |zoom: f32, center: egui::Pos2, pos: egui::Pos2| center + (pos - rect.center()) / zoom;
+        let scroll = ui.input().scroll_delta.y;
+        if let (Some(pointer), true) = (response.hover_pos(), scroll != 0.0) {
+            // Keeps the tile under the pointer in place.
+            let zoom = (self.zoom * (scroll * ZOOM_PER_SCROLL_POINT).exp()).clamp(4.0, 128.0);
+            self.center +=
+                to_world(self.zoom, self.center, pointer) - to_world(zoom, self.center, pointer);
+            self.zoom = zoom;
+        }
+
+        let hovered = response
+            .hover_pos()
+            .map(|pointer| to_world(self.zoom, self.center, pointer).floor())
+            .filter(|cell| {
+                (0.0..MAP_SIZE[0] as f32).contains(&cell.x)
+                    && (0.0..MAP_SIZE[1] as f32).contains(&cell.y)
+            });
+        let painting = response.is_pointer_button_down_on() && ui.input().pointer.primary_down();
+        if let (Some(cell), true) = (hovered, painting) {
+            self.map[cell.y as usize * MAP_SIZE[0] + cell.x as usize] = self.selected;
+        }
+
+        let mut sprites: Vec<Sprite> = self
+            .map
+            .iter()
+            .enumerate()
+            .filter_map(|(index, tile)| {
+                let cell = egui::pos2((index % MAP_SIZE[0]) as f32, (index / MAP_SIZE[0]) as f32);
+                tile.map(|tile| Sprite {
+                    rect: egui::Rect::from_min_size(cell, egui::Vec2::splat(1.0)),
+                    uv: tile_uv(tile),
+                    tint: egui::Rgba::WHITE,
+                    rotation: 0.0,
+                })
+            })
+            .collect();
+        // A preview of the brush, which darkens the cell for the eraser.
+        if let Some(cell) = hovered {
+            sprites.push(Sprite {
+                rect: egui::Rect::from_min_size(cell, egui::Vec2::splat(1.0)),
+                uv: tile_uv(self.selected.unwrap_or(0)),
+                tint: match self.selected {
+                    Some(_) => egui::Rgba::from_white_alpha(0.6),
+                    None => egui::Rgba::from_black_alpha(0.5),
+                },
+                rotation: 0.0,
+            });
+        }
+
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        let size = (rect.size() * pixels_per_point).round();
+        let texture = gpu.batch.render(
+            textures,
+            [size.x as u32, size.y as u32],
+            Camera {
+                center: self.center,
+                zoom: self.zoom * pixels_per_point,
+            },
+            wgpu::Color {
+                r: 0.02,
+                g: 0.02,
+                b: 0.02,
+                a: 1.0,
+            },
+            &sprites,
+        );
+        let painter = ui.painter_at(rect);
+        painter.add(egui::Shape::image(
+            texture,
+            rect,
+            egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0)),
+            egui::Color32::WHITE,
+        ));
+        let to_screen = |pos: egui::Pos2| rect.center() + (pos - self.center) * self.zoom;
+        painter.rect_stroke(
+            egui::Rect::from_min_max(
+                to_screen(egui::Pos2::ZERO),
+                to_screen(egui::pos2(MAP_SIZE[0] as f32, MAP_SIZE[1] as f32)),
+            ),
+            0.0,
+            ui.visuals().window_stroke(),
+        );
+    }
+}
+
+/// Where `tile` is in the atlas.
+fn tile_uv(tile: u8) -> egui::Rect {
+    let width = 1.0 / TILES.len() as f32;
+    egui::Rect::from_min_max(
+        egui::pos2(tile as f32 * width, 0.0),
+        egui::pos2((tile as f32 + 1.0) * width, 1.0),
+    )
+}
+
+/// Generates the pixel art of the tiles, side by side.
+fn atlas_image() -> egui::ColorImage {
+    let mut image = egui::ColorImage::new(
+        [TILE_PIXELS * TILES.len(), TILE_PIXELS],
+        egui::Color32::TRANSPARENT,
+    );
+    for (tile, (name, [r, g, b])) in TILES.iter().enumerate() {
+        for y in 0..TILE_PIXELS {
+            for x in 0..TILE_PIXELS {
+                // A cheap hash, so that the tiles don't look flat.
+                let noise = ((x * 7 + y * 13 + tile * 5).wrapping_mul(2654435761) >> 13) % 24;
+                let shade = match *name {
+                    "Water" if (x / 2 + y) % 6 == 0 => 30,
+                    "Stone" if y % 8 == 0 || (x + y / 8 * 8) % 16 == 0 => -40,
+                    "Wood" if x % 8 == 0 => -35,
+                    _ => noise as i32 - 12,
+                };
+                let channel = |value: u8| (value as i32 + shade).clamp(0, 255) as u8;
+                image.pixels[y * image.size[0] + tile * TILE_PIXELS + x] =
+                    egui::Color32::from_rgb(channel(*r), channel(*g), channel(*b));
+            }
+        }
+    }
+    image
+}
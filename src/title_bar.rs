@@ -0,0 +1,39 @@
+use windows_sys::Win32::Graphics::Dwm::{
+    DwmSetWindowAttribute, DWMSBT_MAINWINDOW, DWMSBT_NONE, DWMSBT_TRANSIENTWINDOW,
+    DWMWA_SYSTEMBACKDROP_TYPE, DWMWA_USE_IMMERSIVE_DARK_MODE,
+};
+use winit::platform::windows::WindowExtWindows;
+use winit::window::Window;
+
+use crate::config::{Backdrop, TitleBar};
+
+/// Asks the desktop window manager to draw the title bar of `window` dark and with `backdrop`.
+///
+/// The backdrop only shows in the title bar: wgpu 0.13 presents opaque surfaces, so the client
+/// area covers it. Older versions of Windows lack the attributes, which is reported but harmless.
+pub fn apply(window: &Window, title_bar: &TitleBar) {
+    let hwnd = window.hwnd() as _;
+    let dark = title_bar.dark as i32;
+    let backdrop = match title_bar.backdrop {
+        Backdrop::None => DWMSBT_NONE,
+        Backdrop::Mica => DWMSBT_MAINWINDOW,
+        Backdrop::Acrylic => DWMSBT_TRANSIENTWINDOW,
+    };
+    for (name, attribute, value) in [
+        ("dark title bar", DWMWA_USE_IMMERSIVE_DARK_MODE, dark),
+        ("backdrop", DWMWA_SYSTEMBACKDROP_TYPE, backdrop),
+    ] {
+        // Safe, `value` outlives the call and the window handle is valid while `window` is.
+        let result = unsafe {
+            DwmSetWindowAttribute(
+                hwnd,
+                attribute as u32,
+                &value as *const i32 as *const _,
+                std::mem::size_of::<i32>() as u32,
+            )
+        };
+        if result < 0 {
+            eprintln!("Can't set the {}: HRESULT {:#010x}", name, result);
+        }
+    }
+}
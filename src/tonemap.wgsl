@@ -0,0 +1,32 @@
+// Tonemaps a linear HDR image into the sRGB texture the image viewer shows.
+
+struct Tonemap {
+    // Stops above or below the exposure of the image.
+    exposure: f32,
+    gamma: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> tonemap: Tonemap;
+@group(0) @binding(1)
+var source: texture_2d<f32>;
+
+// A triangle that covers the whole target.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let hdr = textureLoad(source, vec2<i32>(position.xy), 0);
+    let exposed = max(hdr.rgb, vec3<f32>(0.0)) * exp2(tonemap.exposure);
+    // Reinhard, which maps any brightness below 1.0.
+    let mapped = exposed / (1.0 + exposed);
+    // The target encodes to sRGB on its own, which a gamma of 2.2 leaves as it is.
+    let color = pow(mapped, vec3<f32>(2.2 / tonemap.gamma));
+    // egui expects premultiplied alpha.
+    let alpha = clamp(hdr.a, 0.0, 1.0);
+    return vec4<f32>(color * alpha, alpha);
+}
@@ -0,0 +1,320 @@
+use std::collections::VecDeque;
+
+use egui::epaint::{ClippedShape, Shape};
+
+/// How many frames a step waits for its widget to show up before it fails, for windows and
+/// menus that open a frame after the click that opened them.
+const MAX_RETRIES: u32 = 30;
+
+/// The widget a step acts on.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Target {
+    /// The widget with this id gets the keyboard focus and is clicked with the space bar. Only
+    /// widgets with an id of their own have one that can be named, like a `TextEdit::id` or the
+    /// id passed to `ui.interact`.
+    Id(egui::Id),
+    /// The topmost text of the last frame that reads exactly like this, for example the text of
+    /// a button, is clicked with the pointer.
+    Label(String),
+}
+
+/// One step of a UI script.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    Click(Target),
+    /// Clicks the target, then types the text into it.
+    Type(Target, String),
+    /// Presses and releases a key.
+    Key(egui::Key),
+    /// Lets frames pass without input, for animations to finish.
+    Wait(u32),
+}
+
+/// Drives the UI like a user would, by injecting pointer and keyboard events that target widgets
+/// by their id or label, one step after the other. For end-to-end tests, from `--ui-script` or
+/// with a bare `egui::Context`:
+///
+/// ```text
+/// loop {
+///     driver.next_frame(&ctx, &mut raw_input)?;
+///     let output = ctx.run(raw_input.take(), |ctx| ui(ctx));
+///     driver.observe(&output.shapes);
+/// }
+/// ```
+pub struct UiDriver {
+    steps: VecDeque<Step>,
+    /// The events of the next frames, for the step that is under way.
+    frames: VecDeque<Vec<egui::Event>>,
+    /// The texts of the last frame and where they are, from the bottom to the top.
+    labels: Vec<(String, egui::Rect)>,
+    /// The frames the current step has waited for its widget.
+    retries: u32,
+}
+
+impl UiDriver {
+    pub fn new(steps: Vec<Step>) -> Self {
+        Self {
+            steps: steps.into(),
+            frames: VecDeque::new(),
+            labels: Vec::new(),
+            retries: 0,
+        }
+    }
+
+    /// Parses a script with one step per line, `#` starts a comment:
+    ///
+    /// ```text
+    /// click Tools
+    /// click #name           <- the widget with the id `egui::Id::new("name")`
+    /// type #name = some text
+    /// key Enter
+    /// wait 10
+    /// ```
+    pub fn parse(script: &str) -> Result<Self, String> {
+        let mut steps = Vec::new();
+        for (index, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+            let argument = argument.trim();
+            let step = match command {
+                "click" => Some(Step::Click(target(argument))),
+                "type" => argument
+                    .split_once(" = ")
+                    .map(|(name, text)| Step::Type(target(name), text.to_owned())),
+                "key" => key(argument).map(Step::Key),
+                "wait" => argument.parse().ok().map(Step::Wait),
+                _ => None,
+            };
+            match step {
+                Some(step) => steps.push(step),
+                None => return Err(format!("Line {}: can't understand {:?}", index + 1, line)),
+            }
+        }
+        Ok(Self::new(steps))
+    }
+
+    /// Whether all the steps are done.
+    pub fn is_finished(&self) -> bool {
+        self.steps.is_empty() && self.frames.is_empty()
+    }
+
+    /// Adds the events of the next frame to `raw_input`. Fails if the widget of the next step
+    /// didn't show up in time.
+    pub fn next_frame(
+        &mut self,
+        ctx: &egui::Context,
+        raw_input: &mut egui::RawInput,
+    ) -> Result<(), String> {
+        if self.frames.is_empty() {
+            if let Some(step) = self.steps.front() {
+                match self.start(ctx, step) {
+                    Some(frames) => {
+                        self.frames = frames;
+                        self.retries = 0;
+                        self.steps.pop_front();
+                    }
+                    None if self.retries < MAX_RETRIES => self.retries += 1,
+                    None => return Err(format!("Can't find the widget of {:?}", step)),
+                }
+            }
+        }
+        if let Some(events) = self.frames.pop_front() {
+            raw_input.events.extend(events);
+        }
+        Ok(())
+    }
+
+    /// Takes note of the texts the last frame drew, for the labels of the next steps.
+    pub fn observe(&mut self, shapes: &[ClippedShape]) {
+        self.labels.clear();
+        for ClippedShape(clip_rect, shape) in shapes {
+            collect_labels(shape, *clip_rect, &mut self.labels);
+        }
+    }
+
+    /// The events of `step`, frame by frame, or `None` if its widget isn't there.
+    fn start(&self, ctx: &egui::Context, step: &Step) -> Option<VecDeque<Vec<egui::Event>>> {
+        let mut frames = VecDeque::new();
+        match step {
+            Step::Click(target) => self.click(ctx, target, &mut frames)?,
+            Step::Type(target, text) => {
+                self.click(ctx, target, &mut frames)?;
+                frames.push_back(vec![egui::Event::Text(text.clone())]);
+            }
+            Step::Key(key) => frames.push_back(key_press(*key)),
+            Step::Wait(count) => frames.extend((0..*count).map(|_| Vec::new())),
+        }
+        Some(frames)
+    }
+
+    fn click(
+        &self,
+        ctx: &egui::Context,
+        target: &Target,
+        frames: &mut VecDeque<Vec<egui::Event>>,
+    ) -> Option<()> {
+        match target {
+            Target::Id(id) => {
+                // The focus is dropped again at the end of the frame if the widget isn't there.
+                ctx.memory().request_focus(*id);
+                frames.push_back(key_press(egui::Key::Space));
+            }
+            Target::Label(label) => {
+                let (_, rect) = self.labels.iter().rev().find(|(text, _)| text == label)?;
+                let pos = rect.center();
+                // Moved, pressed and released in frames of their own, like with a real pointer.
+                frames.push_back(vec![egui::Event::PointerMoved(pos)]);
+                for pressed in [true, false] {
+                    frames.push_back(vec![egui::Event::PointerButton {
+                        pos,
+                        button: egui::PointerButton::Primary,
+                        pressed,
+                        modifiers: Default::default(),
+                    }]);
+                }
+            }
+        }
+        Some(())
+    }
+}
+
+/// `#name` is an id, everything else a label.
+fn target(argument: &str) -> Target {
+    match argument.strip_prefix('#') {
+        Some(name) => Target::Id(egui::Id::new(name)),
+        None => Target::Label(argument.to_owned()),
+    }
+}
+
+/// The keys a script can press, by the name of their `egui::Key`.
+fn key(name: &str) -> Option<egui::Key> {
+    use egui::Key;
+    Some(match name {
+        "Enter" => Key::Enter,
+        "Escape" => Key::Escape,
+        "Tab" => Key::Tab,
+        "Space" => Key::Space,
+        "Backspace" => Key::Backspace,
+        "Delete" => Key::Delete,
+        "ArrowUp" => Key::ArrowUp,
+        "ArrowDown" => Key::ArrowDown,
+        "ArrowLeft" => Key::ArrowLeft,
+        "ArrowRight" => Key::ArrowRight,
+        "Home" => Key::Home,
+        "End" => Key::End,
+        "PageUp" => Key::PageUp,
+        "PageDown" => Key::PageDown,
+        _ => return None,
+    })
+}
+
+fn key_press(key: egui::Key) -> Vec<egui::Event> {
+    [true, false]
+        .into_iter()
+        .map(|pressed| egui::Event::Key {
+            key,
+            pressed,
+            modifiers: Default::default(),
+        })
+        .collect()
+}
+
+/// Adds the visible texts of `shape` to `labels`.
+fn collect_labels(shape: &Shape, clip_rect: egui::Rect, labels: &mut Vec<(String, egui::Rect)>) {
+    match shape {
+        Shape::Vec(shapes) => {
+            for shape in shapes {
+                collect_labels(shape, clip_rect, labels);
+            }
+        }
+        Shape::Text(text) => {
+            let rect = text
+                .galley
+                .rect
+                .translate(text.pos.to_vec2())
+                .intersect(clip_rect);
+            if rect.is_positive() {
+                labels.push((text.galley.text().to_owned(), rect));
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, FixedStepClock};
+
+    /// Runs `ui` until the driver is done, and fails after too many frames.
+    fn drive(mut driver: UiDriver, mut content: impl FnMut(&mut egui::Ui)) {
+        let ctx = egui::Context::default();
+        let mut raw_input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                Default::default(),
+                egui::vec2(800.0, 600.0),
+            )),
+            ..Default::default()
+        };
+        let mut clock = FixedStepClock::new(1.0 / 60.0);
+        for _ in 0..100 {
+            raw_input.time = Some(clock.tick());
+            driver.next_frame(&ctx, &mut raw_input).unwrap();
+            let output = ctx.run(raw_input.take(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| content(ui));
+            });
+            driver.observe(&output.shapes);
+            if driver.is_finished() {
+                return;
+            }
+        }
+        panic!("The driver didn't finish");
+    }
+
+    #[test]
+    fn clicks_buttons_by_label_and_id() {
+        let mut clicks = Vec::new();
+        let driver = UiDriver::parse("click First\nwait 2\nclick #second").unwrap();
+        drive(driver, |ui| {
+            if ui.button("First").clicked() {
+                clicks.push("first");
+            }
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(60.0, 20.0), egui::Sense::hover());
+            if ui
+                .interact(rect, egui::Id::new("second"), egui::Sense::click())
+                .clicked()
+            {
+                clicks.push("second");
+            }
+        });
+        assert_eq!(clicks, ["first", "second"]);
+    }
+
+    #[test]
+    fn types_into_text_fields() {
+        let mut text = String::new();
+        let driver = UiDriver::parse("type #name = Ferris").unwrap();
+        drive(driver, |ui| {
+            ui.add(egui::TextEdit::singleline(&mut text).id(egui::Id::new("name")));
+        });
+        assert_eq!(text, "Ferris");
+    }
+
+    #[test]
+    fn missing_widgets_fail() {
+        let ctx = egui::Context::default();
+        let mut driver = UiDriver::new(vec![Step::Click(Target::Label("Nowhere".into()))]);
+        let failed = (0..=MAX_RETRIES).any(|_| {
+            let mut raw_input = egui::RawInput::default();
+            let failed = driver.next_frame(&ctx, &mut raw_input).is_err();
+            let output = ctx.run(raw_input, |_| {});
+            driver.observe(&output.shapes);
+            failed
+        });
+        assert!(failed);
+        assert!(UiDriver::parse("jump around").is_err());
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use winit::dpi::PhysicalSize;
+
+use crate::clock::ReplayClock;
+use crate::config::Config;
+
+/// The `format` of the header, to tell `.egui` files from other JSON lines.
+const FORMAT: &str = "egui-replay";
+/// Goes up when a reader of the older version would misread the file.
+const VERSION: u32 = 1;
+
+/// The first line of a `.egui` file: the state the UI starts from.
+#[derive(Serialize, Deserialize)]
+struct Header {
+    format: String,
+    version: u32,
+    /// The egui version the file was recorded with. The events are egui's own, so other versions
+    /// might not read them.
+    egui: String,
+    /// The window at the start, in physical pixels.
+    width: u32,
+    height: u32,
+    scale_factor: f64,
+    /// The config the app started with, without the recent files.
+    config: serde_json::Value,
+}
+
+/// Every other line: the `RawInput` of one frame, without what depends on the GPU. Fields with
+/// egui's default value are left out.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Frame {
+    time: f64,
+    #[serde(skip_serializing_if = "is_default_dt")]
+    predicted_dt: f32,
+    /// Only in the frames where the window changed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    screen_rect: Option<egui::Rect>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pixels_per_point: Option<f32>,
+    #[serde(skip_serializing_if = "is_default")]
+    modifiers: egui::Modifiers,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    events: Vec<egui::Event>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    dropped_files: Vec<PathBuf>,
+    #[serde(skip_serializing_if = "is_true")]
+    has_focus: bool,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self::new(&egui::RawInput::default())
+    }
+}
+
+fn is_default_dt(dt: &f32) -> bool {
+    *dt == egui::RawInput::default().predicted_dt
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+impl Frame {
+    fn new(input: &egui::RawInput) -> Self {
+        Self {
+            time: input.time.unwrap_or_default(),
+            predicted_dt: input.predicted_dt,
+            screen_rect: input.screen_rect,
+            pixels_per_point: input.pixels_per_point,
+            modifiers: input.modifiers,
+            events: input.events.clone(),
+            dropped_files: input
+                .dropped_files
+                .iter()
+                .filter_map(|file| file.path.clone())
+                .collect(),
+            has_focus: input.has_focus,
+        }
+    }
+
+    /// Replaces the input of the frame with the recorded one. The time comes from the clock.
+    pub fn apply(self, input: &mut egui::RawInput) {
+        input.predicted_dt = self.predicted_dt;
+        input.screen_rect = self.screen_rect;
+        input.pixels_per_point = self.pixels_per_point;
+        input.modifiers = self.modifiers;
+        input.events = self.events;
+        input.dropped_files = self
+            .dropped_files
+            .into_iter()
+            .map(|path| egui::DroppedFile {
+                path: Some(path),
+                ..Default::default()
+            })
+            .collect();
+        input.has_focus = self.has_focus;
+    }
+}
+
+fn invalid(line: usize, err: impl std::fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: {}", line, err),
+    )
+}
+
+/// Writes the input egui gets to a `.egui` file, frame by frame.
+pub struct UiRecorder {
+    writer: BufWriter<File>,
+    size: PhysicalSize<u32>,
+    scale_factor: f64,
+    started: bool,
+}
+
+impl UiRecorder {
+    pub fn create(path: &Path, size: PhysicalSize<u32>, scale_factor: f64) -> io::Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+            size,
+            scale_factor,
+            started: false,
+        })
+    }
+
+    /// Writes the header, once the app knows its config.
+    pub fn start(&mut self, config: &Config) -> io::Result<()> {
+        let mut config = serde_json::to_value(config)?;
+        if let Some(config) = config.as_object_mut() {
+            config.remove("recent_files");
+        }
+        let header = Header {
+            format: FORMAT.to_owned(),
+            version: VERSION,
+            egui: "0.19".to_owned(),
+            width: self.size.width,
+            height: self.size.height,
+            scale_factor: self.scale_factor,
+            config,
+        };
+        self.started = true;
+        self.write(&header)
+    }
+
+    /// Records the input of a frame, right before egui gets it. Frames before the header are
+    /// left out.
+    pub fn record_frame(&mut self, input: &egui::RawInput) -> io::Result<()> {
+        match self.started {
+            true => self.write(&Frame::new(input)),
+            false => Ok(()),
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn write(&mut self, line: &impl Serialize) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, line)?;
+        self.writer.write_all(b"\n")
+    }
+}
+
+/// Runs the UI again from a `.egui` file, one recorded frame per drawn frame.
+pub struct UiPlayer {
+    header: Header,
+    frames: VecDeque<Frame>,
+    finished: bool,
+}
+
+impl UiPlayer {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    fn read(reader: impl BufRead) -> io::Result<Self> {
+        let mut lines = reader
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.as_ref().is_ok_and(|line| line.trim().is_empty()));
+        let header: Header = match lines.next() {
+            Some((index, line)) => {
+                serde_json::from_str(&line?).map_err(|err| invalid(index + 1, err))?
+            }
+            None => return Err(invalid(1, "the file is empty")),
+        };
+        if header.format != FORMAT {
+            return Err(invalid(1, format!("not an {} file", FORMAT)));
+        }
+        if header.version > VERSION {
+            return Err(invalid(
+                1,
+                format!("version {} is newer than this player", header.version),
+            ));
+        }
+        let mut frames = VecDeque::new();
+        for (index, line) in lines {
+            frames.push_back(serde_json::from_str(&line?).map_err(|err| invalid(index + 1, err))?);
+        }
+        Ok(Self {
+            header,
+            frames,
+            finished: false,
+        })
+    }
+
+    pub fn start_size(&self) -> PhysicalSize<u32> {
+        PhysicalSize::new(self.header.width, self.header.height)
+    }
+
+    /// The config the recording started with.
+    pub fn config(&self) -> io::Result<Config> {
+        Ok(serde_json::from_value(self.header.config.clone())?)
+    }
+
+    /// The clock that plays the recorded frame times back.
+    pub fn clock(&self) -> ReplayClock {
+        ReplayClock::new(self.frames.iter().map(|frame| frame.time).collect())
+    }
+
+    /// Takes the next recorded frame, `None` once the recording is over.
+    pub fn next_frame(&mut self) -> Option<Frame> {
+        let frame = self.frames.pop_front();
+        self.finished = frame.is_none();
+        frame
+    }
+
+    /// Whether all recorded frames were played back.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut input = egui::RawInput {
+            time: Some(1.5),
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(800.0, 600.0),
+            )),
+            ..Default::default()
+        };
+        input.events.push(egui::Event::Text("a".to_owned()));
+        let header = Header {
+            format: FORMAT.to_owned(),
+            version: VERSION,
+            egui: "0.19".to_owned(),
+            width: 800,
+            height: 600,
+            scale_factor: 1.0,
+            config: serde_json::to_value(Config::default()).unwrap(),
+        };
+        let file = [
+            serde_json::to_string(&header).unwrap(),
+            serde_json::to_string(&Frame::new(&input)).unwrap(),
+            serde_json::to_string(&Frame::new(&egui::RawInput::default())).unwrap(),
+        ]
+        .join("\n");
+        assert!(file.ends_with(r#"{"time":0.0}"#));
+
+        let mut player = UiPlayer::read(file.as_bytes()).unwrap();
+        assert_eq!(player.start_size(), PhysicalSize::new(800, 600));
+        let mut played = egui::RawInput::default();
+        player.next_frame().unwrap().apply(&mut played);
+        assert_eq!(played.screen_rect, input.screen_rect);
+        assert_eq!(played.events, input.events);
+        player.next_frame().unwrap();
+        assert!(player.next_frame().is_none());
+        assert!(player.is_finished());
+    }
+}
@@ -0,0 +1,423 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use egui::epaint::{ClippedShape, Shape};
+use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
+
+/// How a user texture is sampled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sampling {
+    pub filter: wgpu::FilterMode,
+    /// How texture coordinates outside of the texture wrap.
+    pub address_mode: wgpu::AddressMode,
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Self {
+            filter: wgpu::FilterMode::Linear,
+            address_mode: wgpu::AddressMode::ClampToEdge,
+        }
+    }
+}
+
+impl Sampling {
+    fn descriptor(&self) -> wgpu::SamplerDescriptor<'static> {
+        wgpu::SamplerDescriptor {
+            label: Some("user_texture_sampler"),
+            address_mode_u: self.address_mode,
+            address_mode_v: self.address_mode,
+            mag_filter: self.filter,
+            min_filter: self.filter,
+            ..Default::default()
+        }
+    }
+}
+
+/// A texture of the example itself instead of egui, which can have its own sampler.
+pub struct UserTexture {
+    pub id: egui::TextureId,
+}
+
+/// The user textures on the GPU and the frame each was displayed in last. When they take more
+/// memory than the budget, the evictable ones that weren't displayed for the longest time are
+/// dropped, and their owners upload them again once they need them.
+#[derive(Default)]
+pub struct Residency {
+    textures: HashMap<egui::TextureId, Resident>,
+    frame: u64,
+    evicted: u64,
+}
+
+struct Resident {
+    /// Keeps the texture alive, it's only dropped with the view.
+    view: wgpu::TextureView,
+    bytes: u64,
+    /// The frame the texture was displayed in last.
+    displayed: u64,
+    /// Textures that can't be uploaded again count against the budget, but are never evicted.
+    evictable: bool,
+}
+
+/// How much memory the user textures take, for the settings.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResidencyStats {
+    pub textures: usize,
+    pub bytes: u64,
+    pub evictable_bytes: u64,
+    /// The textures evicted since the start.
+    pub evicted: u64,
+}
+
+impl Residency {
+    /// Marks the textures drawn by `shapes` as displayed in this frame.
+    pub fn displayed(&mut self, shapes: &[ClippedShape]) {
+        self.frame += 1;
+        for ClippedShape(_, shape) in shapes {
+            self.mark(shape);
+        }
+    }
+
+    fn mark(&mut self, shape: &Shape) {
+        match shape {
+            Shape::Vec(shapes) => {
+                for shape in shapes {
+                    self.mark(shape);
+                }
+            }
+            Shape::Mesh(mesh) => {
+                if let Some(texture) = self.textures.get_mut(&mesh.texture_id) {
+                    texture.displayed = self.frame;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Evicts the least recently displayed textures until they fit into `budget` bytes. The ones
+    /// displayed in the last frame stay, so that they aren't uploaded again every frame.
+    pub fn evict(&mut self, rpass: &mut RenderPass, budget: u64) {
+        let mut bytes = self.stats().bytes;
+        if bytes <= budget {
+            return;
+        }
+        let mut candidates: Vec<(u64, egui::TextureId, u64)> = self
+            .textures
+            .iter()
+            .filter(|(_, texture)| texture.evictable && texture.displayed < self.frame)
+            .map(|(id, texture)| (texture.displayed, *id, texture.bytes))
+            .collect();
+        candidates.sort_by_key(|(displayed, ..)| *displayed);
+        let mut free = Vec::new();
+        for (_, id, texture_bytes) in candidates {
+            if bytes <= budget {
+                break;
+            }
+            self.textures.remove(&id);
+            bytes -= texture_bytes;
+            free.push(id);
+        }
+        self.evicted += free.len() as u64;
+        rpass
+            .remove_textures(egui::TexturesDelta {
+                free,
+                ..Default::default()
+            })
+            .expect("The evicted textures are registered");
+    }
+
+    pub fn stats(&self) -> ResidencyStats {
+        let mut stats = ResidencyStats {
+            textures: self.textures.len(),
+            evicted: self.evicted,
+            ..Default::default()
+        };
+        for texture in self.textures.values() {
+            stats.bytes += texture.bytes;
+            if texture.evictable {
+                stats.evictable_bytes += texture.bytes;
+            }
+        }
+        stats
+    }
+}
+
+/// Lets the UI register its own textures with the render pass of the main UI.
+///
+/// egui_wgpu_backend samples the textures egui manages linearly and clamps them, whatever the
+/// `TextureFilter` of the image said.
+pub struct UserTextures<'a> {
+    pub device: &'a Arc<wgpu::Device>,
+    pub queue: &'a Arc<wgpu::Queue>,
+    /// The format of the surface, the one the render pass of the main UI draws in.
+    pub format: wgpu::TextureFormat,
+    /// The samples per pixel the renderers of the example draw with, from the settings.
+    pub msaa_samples: u32,
+    rpass: &'a mut RenderPass,
+    residency: &'a mut Residency,
+}
+
+impl<'a> UserTextures<'a> {
+    pub fn new(
+        device: &'a Arc<wgpu::Device>,
+        queue: &'a Arc<wgpu::Queue>,
+        format: wgpu::TextureFormat,
+        msaa_samples: u32,
+        rpass: &'a mut RenderPass,
+        residency: &'a mut Residency,
+    ) -> Self {
+        Self {
+            device,
+            queue,
+            format,
+            msaa_samples,
+            rpass,
+            residency,
+        }
+    }
+
+    /// Makes `texture` usable in the UI. It takes `bytes` of GPU memory, and is never evicted.
+    pub fn register(
+        &mut self,
+        texture: &wgpu::Texture,
+        bytes: u64,
+        sampling: Sampling,
+    ) -> UserTexture {
+        self.insert(texture, bytes, sampling, false)
+    }
+
+    /// Like `register`, for a texture the owner can upload again. It might be evicted when it
+    /// wasn't displayed for a while, check `is_resident` before drawing it.
+    pub fn register_evictable(
+        &mut self,
+        texture: &wgpu::Texture,
+        bytes: u64,
+        sampling: Sampling,
+    ) -> UserTexture {
+        self.insert(texture, bytes, sampling, true)
+    }
+
+    fn insert(
+        &mut self,
+        texture: &wgpu::Texture,
+        bytes: u64,
+        sampling: Sampling,
+        evictable: bool,
+    ) -> UserTexture {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let id = self
+            .rpass
+            .egui_texture_from_wgpu_texture_with_sampler_options(
+                self.device,
+                &view,
+                sampling.descriptor(),
+            );
+        self.residency.textures.insert(
+            id,
+            Resident {
+                view,
+                bytes,
+                // New textures count as displayed in the last frame.
+                displayed: self.residency.frame,
+                evictable,
+            },
+        );
+        UserTexture { id }
+    }
+
+    /// Whether `texture` is still on the GPU, instead of evicted.
+    pub fn is_resident(&self, texture: &UserTexture) -> bool {
+        self.residency.textures.contains_key(&texture.id)
+    }
+
+    /// Changes the sampler of `texture`, unless it was evicted.
+    pub fn set_sampling(&mut self, texture: &UserTexture, sampling: Sampling) {
+        let resident = match self.residency.textures.get(&texture.id) {
+            Some(resident) => resident,
+            None => return,
+        };
+        self.rpass
+            .update_egui_texture_from_wgpu_texture_with_sampler_options(
+                self.device,
+                &resident.view,
+                sampling.descriptor(),
+                texture.id,
+            )
+            .expect("The user texture is registered");
+    }
+
+    /// Draws `jobs` with the render pass of the main UI into `view`, which has the format of the
+    /// surface. Meshes with user textures that are gone by now are left out, the textures egui
+    /// manages have to be there. Submitted right away, before the frame itself fills the buffers
+    /// of the render pass again.
+    pub fn render_ui(
+        &mut self,
+        view: &wgpu::TextureView,
+        jobs: &[egui::ClippedPrimitive],
+        screen_descriptor: &ScreenDescriptor,
+        clear_color: wgpu::Color,
+    ) {
+        let jobs: Vec<egui::ClippedPrimitive> = jobs
+            .iter()
+            .filter(|job| match &job.primitive {
+                egui::epaint::Primitive::Mesh(mesh) => match mesh.texture_id {
+                    egui::TextureId::User(_) => {
+                        self.residency.textures.contains_key(&mesh.texture_id)
+                    }
+                    egui::TextureId::Managed(_) => true,
+                },
+                egui::epaint::Primitive::Callback(_) => false,
+            })
+            .cloned()
+            .collect();
+        self.rpass
+            .update_buffers(self.device, self.queue, &jobs, screen_descriptor);
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("render_ui"),
+            });
+        self.rpass
+            .execute(
+                &mut encoder,
+                view,
+                &jobs,
+                screen_descriptor,
+                Some(clear_color),
+            )
+            .expect("The textures of the jobs are registered");
+        self.queue.submit(Some(encoder.finish()));
+    }
+
+    /// Unregisters `textures`. They must not be drawn in the current frame anymore.
+    pub fn free(&mut self, textures: impl IntoIterator<Item = UserTexture>) {
+        let delta = egui::TexturesDelta {
+            free: textures
+                .into_iter()
+                .map(|texture| texture.id)
+                .filter(|id| self.residency.textures.remove(id).is_some())
+                .collect(),
+            ..Default::default()
+        };
+        self.rpass
+            .remove_textures(delta)
+            .expect("The user textures are registered");
+    }
+}
+
+/// A texture the example renders into itself, which the UI shows in a viewport of `size`
+/// physical pixels.
+///
+/// egui_wgpu_backend 0.19 skips paint callbacks, so renderers of their own draw into a target
+/// before egui draws, instead of while it does.
+pub struct RenderTarget {
+    texture: UserTexture,
+    view: wgpu::TextureView,
+    size: [u32; 2],
+}
+
+impl RenderTarget {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    /// The target in `target`, recreated if its size isn't `size` anymore.
+    pub fn resize<'t>(
+        target: &'t mut Option<Self>,
+        textures: &mut UserTextures,
+        label: &str,
+        size: [u32; 2],
+    ) -> &'t Self {
+        let size = [size[0].max(1), size[1].max(1)];
+        if target.as_ref().is_some_and(|target| target.size == size) {
+            return target.as_ref().expect("The target exists");
+        }
+        Self::free(target, textures);
+        let texture = textures.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: size[0],
+                height: size[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        });
+        target.insert(Self {
+            view: texture.create_view(&Default::default()),
+            texture: textures.register(
+                &texture,
+                u64::from(size[0]) * u64::from(size[1]) * 4,
+                Sampling::default(),
+            ),
+            size,
+        })
+    }
+
+    /// Frees the target in `target`, if there is one.
+    pub fn free(target: &mut Option<Self>, textures: &mut UserTextures) {
+        if let Some(old) = target.take() {
+            textures.free([old.texture]);
+        }
+    }
+
+    pub fn id(&self) -> egui::TextureId {
+        self.texture.id
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    /// The size in physical pixels, at least one per side.
+    pub fn size(&self) -> [u32; 2] {
+        self.size
+    }
+}
+
+/// Uploads `image` into a texture that can be registered as a user texture. Can be called from
+/// any thread.
+pub fn upload(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    label: &str,
+    image: &egui::ColorImage,
+) -> wgpu::Texture {
+    let size = wgpu::Extent3d {
+        width: image.width() as u32,
+        height: image.height() as u32,
+        depth_or_array_layers: 1,
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+    });
+    // Unlike buffer copies, writes from the queue have no row alignment requirement.
+    let pixels: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|color| color.to_array())
+        .collect();
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        &pixels,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: std::num::NonZeroU32::new(size.width * 4),
+            rows_per_image: None,
+        },
+        size,
+    );
+    texture
+}
@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::{fs, io};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Accessibility, Config};
+
+/// A part of the app that is saved into workspaces and restored from them.
+pub trait SaveState {
+    /// Has to fall back to its defaults field by field, so that older workspaces keep working.
+    type State: Serialize + DeserializeOwned + Default;
+
+    fn save_state(&self) -> Self::State;
+
+    fn restore_state(&mut self, state: Self::State);
+}
+
+/// A complete session under a name: the open windows and where they are, the open files, the
+/// camera of the scene and the theme.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Workspace {
+    /// Whether the tool windows are open, by their names.
+    pub windows: BTreeMap<String, bool>,
+    /// egui's memory, with the positions and sizes of the windows and which ones are collapsed.
+    pub memory: egui::Memory,
+    pub csv_viewer: Option<PathBuf>,
+    pub json_inspector: Option<PathBuf>,
+    pub log_tail: Option<PathBuf>,
+    pub image_viewer: Option<PathBuf>,
+    pub hex_viewer: Option<PathBuf>,
+    pub ecs_scene: crate::ecs_scene::Orbit,
+    pub theme: Theme,
+}
+
+/// The part of the config a workspace brings along.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub style: Option<egui::Style>,
+    pub accessibility: Accessibility,
+}
+
+impl SaveState for Config {
+    type State = Theme;
+
+    fn save_state(&self) -> Theme {
+        Theme {
+            style: self.style.clone(),
+            accessibility: self.settings.accessibility.clone(),
+        }
+    }
+
+    fn restore_state(&mut self, theme: Theme) {
+        self.style = theme.style;
+        self.settings.accessibility = theme.accessibility;
+    }
+}
+
+fn path(name: &str) -> io::Result<PathBuf> {
+    let dir = crate::paths::workspaces_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory available"))?;
+    Ok(dir.join(format!("{}.json", name)))
+}
+
+/// Whether `name` works as a file name everywhere.
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && !name
+            .chars()
+            .any(|c| c.is_control() || r#"/\:*?"<>|"#.contains(c))
+}
+
+/// The names of the saved workspaces, sorted.
+pub fn list() -> Vec<String> {
+    let mut names: Vec<String> = crate::paths::workspaces_dir()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            match path.extension()?.to_str()? {
+                "json" => Some(path.file_stem()?.to_string_lossy().into_owned()),
+                _ => None,
+            }
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+pub fn save(name: &str, workspace: &Workspace) -> io::Result<()> {
+    let path = path(name)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_vec_pretty(workspace)?)
+}
+
+pub fn load(name: &str) -> io::Result<Workspace> {
+    let data = fs::read(path(name)?)?;
+    Ok(serde_json::from_slice(&data)?)
+}
+
+pub fn delete(name: &str) -> io::Result<()> {
+    fs::remove_file(path(name)?)
+}
+
+/// What the user picked in the Workspaces menu.
+pub enum Action {
+    Save(String),
+    Restore(Box<Workspace>),
+}
+
+/// The Workspaces menu: saves the session under a name, and lists the saved ones to restore or
+/// delete them.
+#[derive(Default)]
+pub struct WorkspacesMenu {
+    name: String,
+    /// The saved workspaces, read when the menu opens.
+    names: Option<Vec<String>>,
+    status: Option<String>,
+}
+
+impl WorkspacesMenu {
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<Action> {
+        let mut action = None;
+        let names = self.names.get_or_insert_with(list);
+        if names.is_empty() {
+            ui.weak("No saved workspaces");
+        }
+        let mut deleted = None;
+        for name in names.iter() {
+            ui.horizontal(|ui| {
+                if ui.button(name).clicked() {
+                    match load(name) {
+                        Ok(workspace) => {
+                            action = Some(Action::Restore(Box::new(workspace)));
+                            self.status = None;
+                            ui.close_menu();
+                        }
+                        Err(err) => self.status = Some(format!("Can't restore {}: {}", name, err)),
+                    }
+                }
+                if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                    deleted = Some(name.clone());
+                }
+            });
+        }
+        if let Some(name) = deleted {
+            if let Err(err) = delete(&name) {
+                self.status = Some(format!("Can't delete {}: {}", name, err));
+            }
+            self.names = None;
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.name)
+                    .hint_text("Name")
+                    .desired_width(120.0),
+            );
+            let name = self.name.trim();
+            if ui
+                .add_enabled(valid_name(name), egui::Button::new("💾 Save"))
+                .clicked()
+            {
+                action = Some(Action::Save(name.to_owned()));
+            }
+        });
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+        action
+    }
+
+    /// Reports how saving the workspace `name` went, and lists it from now on.
+    pub fn saved(&mut self, name: &str, result: io::Result<()>) {
+        self.status = Some(match result {
+            Ok(()) => format!("Saved {}", name),
+            Err(err) => format!("Can't save {}: {}", name, err),
+        });
+        self.names = None;
+    }
+
+    /// Reads the saved workspaces again the next time the menu opens.
+    pub fn closed(&mut self) {
+        self.names = None;
+    }
+}